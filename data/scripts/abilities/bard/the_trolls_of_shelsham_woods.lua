@@ -68,5 +68,5 @@ function apply_heal(parent, ability, targets)
   local stats = parent:stats()
   local target = targets:first()
   
-  target:heal_damage(5 + stats.caster_level / 4 + stats.perception_bonus / 4)
+  target:heal_damage(5 + stats.caster_level / 4 + stats.perception_bonus / 4, parent)
 end
@@ -39,6 +39,6 @@ function apply_heal(parent, ability, targets)
   local stats = parent:stats()
   local target = targets:first()
   
-  target:heal_damage(10 + stats.caster_level / 3 + stats.wisdom_bonus / 3)
+  target:heal_damage(10 + stats.caster_level / 3 + stats.wisdom_bonus / 3, parent)
   game:play_sfx("sfx/spell2")
 end
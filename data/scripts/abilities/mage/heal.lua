@@ -13,7 +13,7 @@ function on_target_select(parent, ability, targets)
   local stats = parent:stats()
   
   local amount = 30 + stats.caster_level + stats.intellect_bonus / 2
-  target:heal_damage(amount)
+  target:heal_damage(amount, parent)
 
   local anim = target:create_particle_generator("heal", 2.0)
   anim:set_moves_with_parent()
@@ -45,7 +45,7 @@ function on_target_select(parent, ability, targets)
   
   local targets = targets:friendly():to_table()
   for i = 1, #targets do
-	targets[i]:heal_damage(amount)
+	targets[i]:heal_damage(amount, parent)
   end
   
   game:play_sfx("sfx/healing_full")
@@ -64,7 +64,7 @@ function apply_heal(parent, ability, targets)
 
   local targets = targets:to_table()
   for i = 1, #targets do
-	targets[i]:heal_damage(amount)
+	targets[i]:heal_damage(amount, parent)
   end
   
   game:play_sfx("sfx/healing_full")
@@ -46,5 +46,5 @@ function apply_heal(parent, ability, targets)
   local stats = parent:stats()
   local target = targets:first()
   
-  target:heal_damage(10 + stats.caster_level / 3 + stats.intellect_bonus / 3)
+  target:heal_damage(10 + stats.caster_level / 3 + stats.intellect_bonus / 3, parent)
 end
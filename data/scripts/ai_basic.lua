@@ -695,8 +695,8 @@ function compute_weight(parent, target)
         modifiers = modifiers - compute_defensive_strength(target_stats)
     end
 
-    -- hostiles that have hurt us are higher priority
-    modifiers = modifiers + parent:get_num_flag("__damage_taken_from" .. target:id())
+    -- hostiles we hold threat towards, from damage, healing, or taunts, are higher priority
+    modifiers = modifiers + parent:get_threat(target) / parent:stats().max_hp
 
     -- hostiles that are difficult to damage with our regular attack are lower priority
     modifiers = modifiers + parent:get_num_flag("__hard_target_for" .. target:id())
@@ -734,13 +734,9 @@ end
 -- OnDamaged script hook
 function on_damaged(parent, targets, hit)
     local target = targets:first()
-    local max_hp = parent:stats().max_hp
-    local damage = hit:total_damage()
-    local frac = damage / max_hp
-    parent:add_num_flag("__damage_taken_from_" .. target:id(), frac)
 
-    game:debug("Added damage_taken_from " .. tostring(frac) .. " for " .. target:id() .. " on "
-        .. parent:id())
+    game:debug("Threat towards " .. target:id() .. " on " .. parent:id() .. " is now "
+        .. tostring(parent:get_threat(target)))
 
     -- game:log(parent:name() .. " damaged by " .. target:name() .. ": "
     --     .. hit:kind() .. " for " .. hit:total_damage() .. " damage.")
@@ -49,5 +49,5 @@ end
 function apply_heal(parent, item, targets)
   local target = targets:first()
   
-  target:heal_damage(4)
+  target:heal_damage(4, parent)
 end
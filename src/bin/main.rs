@@ -20,15 +20,18 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use log::{error, info};
+use log::{error, info, warn};
 
-use sulis_core::resource::ResourceSet;
+use sulis_core::config::Config;
+use sulis_core::resource::{vfs, ResourceSet};
 use sulis_core::io::{DisplayConfiguration, System, ControlFlowUpdater};
 use sulis_core::ui::{self, Cursor, Widget};
 use sulis_core::util::{self, ActiveResources};
 use sulis_module::{Actor, Module};
-use sulis_state::{GameState, NextGameStep, SaveState};
-use sulis_view::{main_menu::{self, MainMenu}, RootView, trigger_activator};
+use sulis_state::{
+    module_validator, replay, save_file, AchievementState, GameState, NextGameStep, SaveState,
+};
+use sulis_view::{main_menu::{self, MainMenu}, replay_player, RootView, trigger_activator};
 
 struct GameControlFlowUpdater {
     display_configurations: Vec<DisplayConfiguration>,
@@ -108,14 +111,14 @@ impl GameControlFlowUpdater {
         self.mode = UiMode::MainMenu(view);
     }
 
-    fn new_campaign(&mut self, pc_actor: Rc<Actor>, party_actors: Vec<Rc<Actor>>, flags: HashMap<String, String>) {
+    fn new_campaign(&mut self, pc_actor: Rc<Actor>, party_actors: Vec<Rc<Actor>>, flags: HashMap<String, String>, ironman: bool, seed: Option<u128>) {
         info!("Initializing game state.");
-        if let Err(e) = GameState::init(pc_actor, party_actors, flags) {
+        if let Err(e) = GameState::init(pc_actor, party_actors, flags, ironman, seed) {
             error!("{}", e);
             util::error_and_exit("There was a fatal error creating the game state.");
         };
 
-        let view = RootView::new();
+        let view = RootView::new(self.display_configurations.clone());
         self.root = ui::create_ui_tree(view.clone());
         self.mode = UiMode::Game(view);
     }
@@ -127,7 +130,7 @@ impl GameControlFlowUpdater {
             util::error_and_exit("There was a fatal error loading the game state.");
         };
 
-        let view = RootView::new();
+        let view = RootView::new(self.display_configurations.clone());
         self.root = ui::create_ui_tree(view.clone());
         self.mode = UiMode::Game(view);
     }
@@ -136,25 +139,61 @@ impl GameControlFlowUpdater {
         use NextGameStep::*;
         match step {
             Exit => {
+                replay::finish_recording();
+                AchievementState::write_profile();
                 self.exit = true;
-            }, NewCampaign { pc_actor } => {
-                self.new_campaign(pc_actor, Vec::new(), HashMap::new());
+            }, NewCampaign { pc_actor, ironman, seed } => {
+                self.new_campaign(pc_actor, Vec::new(), HashMap::new(), ironman, seed);
             }, LoadCampaign { save_state } => {
                 self.load_campaign(*save_state);
-            }, LoadModuleAndNewCampaign { pc_actor, party_actors, flags, module_dir } => {
+            }, LoadModsAndCampaign { mods, save_file } => {
+                let mut active = ActiveResources::read();
+                active.mods = mods;
+                active.write();
+                load_resources();
+
+                match save_file::load_state(&save_file) {
+                    Ok(save_state) => self.load_campaign(save_state),
+                    Err(e) => {
+                        error!("Error reading game state");
+                        error!("{}", e);
+                        self.main_menu();
+                    }
+                }
+            }, LoadModuleAndNewCampaign { pc_actor, party_actors, flags, module_dir, ironman } => {
                 let mut active = ActiveResources::read();
                 active.campaign = Some(module_dir);
                 active.write();
                 load_resources();
-                self.new_campaign(pc_actor, party_actors, flags);
+                self.new_campaign(pc_actor, party_actors, flags, ironman, None);
+            }, PlayReplay { replay } => {
+                let save = replay.save.clone();
+                replay::start_playback(*replay);
+                match save_file::load_state_at(&save) {
+                    Ok(save_state) => self.load_campaign(save_state),
+                    Err(e) => {
+                        error!("Error reading replay save state");
+                        error!("{}", e);
+                        replay::finish_recording();
+                        self.main_menu();
+                    }
+                }
             }, MainMenu => {
+                replay::finish_recording();
+                AchievementState::write_profile();
                 self.main_menu();
             }, MainMenuReloadResources => {
                 load_resources();
                 self.main_menu();
             }, RecreateIO => {
+                // campaign data overrides for a new language are applied on load
+                if ResourceSet::loaded_language() != Config::language() {
+                    load_resources();
+                }
                 self.recreate_window = true;
                 self.main_menu();
+            }, RecreateWindow => {
+                self.recreate_window = true;
             }
         }
     }
@@ -168,6 +207,7 @@ impl GameControlFlowUpdater {
             UiMode::MainMenu(view) => {
                 self.next_step = view.borrow_mut().next_step();
             }, UiMode::Game(view) => {
+                replay_player::play_commands(&self.root);
                 let ui_cb = GameState::update(millis);
 
                 if let Some(cb) = ui_cb {
@@ -199,7 +239,15 @@ fn load_resources() {
 
     let active = ActiveResources::read();
 
-    let dirs = active.directories();
+    let mut dirs = active.directories();
+    if let Some(ref campaign) = active.campaign {
+        match Module::module_directories(campaign) {
+            Ok(module_dirs) => {
+                dirs.splice(1..2, module_dirs);
+            }
+            Err(e) => warn!("Unable to load base modules for '{}': {}", campaign, e),
+        }
+    }
 
     let start_main = std::time::Instant::now();
     info!("Reading resources from '{:?}'", dirs);
@@ -214,7 +262,7 @@ fn load_resources() {
     info!("Loaded base resources in {}s", util::format_elapsed_secs(start_main.elapsed()));
 
     if dirs.len() > 1 {
-        info!("Loading module '{}'", dirs[1]);
+        info!("Loading module '{}'", active.campaign.as_deref().unwrap_or(&dirs[1]));
         if let Err(e) = Module::load_resources(yaml, dirs) {
             error!("{}", e);
         }
@@ -223,6 +271,53 @@ fn load_resources() {
     info!("Loaded all resources in {}s", util::format_elapsed_secs(start.elapsed()));
 }
 
+/// Loads the specified module, or the active campaign if none is specified,
+/// and prints a report of any problems found to standard output without
+/// starting the game.  Exits with a non-zero status if any were found
+fn validate(module_dir: Option<String>) {
+    let module_dir = match module_dir.or(ActiveResources::read().campaign) {
+        Some(dir) => dir,
+        None => {
+            util::error_and_exit("No module specified to validate.");
+            unreachable!();
+        }
+    };
+
+    info!("Validating module '{}'", module_dir);
+    let data_dir = Config::resources_config().directory;
+    let report = module_validator::validate(&data_dir, &module_dir);
+    report.print();
+
+    info!("Found {} errors in '{}'", report.errors.len(), module_dir);
+    std::process::exit(if report.is_ok() { 0 } else { 1 });
+}
+
+/// Packs the module in `module_dir` into a single zip archive, which may be
+/// placed in the campaigns directory in place of the module directory.
+/// The archive is written to `output`, or `<module_dir>.zip` by default
+fn pack(module_dir: Option<String>, output: Option<String>) {
+    let module_dir = match module_dir {
+        Some(dir) => dir.trim_end_matches(['/', '\\']).to_string(),
+        None => {
+            util::error_and_exit("No module specified to pack.");
+            unreachable!();
+        }
+    };
+    let output = output.unwrap_or_else(|| format!("{module_dir}.zip"));
+
+    info!("Packing module '{}' into '{}'", module_dir, output);
+    match vfs::pack(&module_dir, &output) {
+        Ok(count) => {
+            info!("Packed {} files into '{}'", count, output);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            error!("{}", e);
+            util::error_and_exit(&format!("Unable to pack module '{module_dir}'"));
+        }
+    }
+}
+
 fn main() {
     // CONFIG will be lazily initialized here; if it fails it
     // prints an error and exits.  Don't drop the returned handle
@@ -231,10 +326,42 @@ fn main() {
     info!("=========Initializing=========");
     info!("Setup Logger and read configuration from 'config.yml'");
 
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--validate") {
+        validate(args.get(index + 1).cloned());
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--pack") {
+        pack(args.get(index + 1).cloned(), args.get(index + 2).cloned());
+    }
+
+    sulis_module::modification::scan_user_mods_dir();
     load_resources();
 
+    let replay_arg = args.iter().position(|arg| arg == "--replay").map(|index| {
+        let path = match args.get(index + 1) {
+            Some(path) => path,
+            None => {
+                util::error_and_exit("No replay file specified.");
+                unreachable!();
+            }
+        };
+
+        match replay::read_replay(std::path::Path::new(path)) {
+            Ok(replay) => replay,
+            Err(e) => {
+                error!("{}", e);
+                util::error_and_exit(&format!("Unable to read replay '{path}'"));
+                unreachable!();
+            }
+        }
+    });
+
     let system = create_io();
 
-    let flow_controller = GameControlFlowUpdater::new(&system);
+    let mut flow_controller = GameControlFlowUpdater::new(&system);
+    if let Some(replay) = replay_arg {
+        flow_controller.next_step = Some(NextGameStep::PlayReplay { replay: Box::new(replay) });
+    }
     system.main_loop(Box::new(flow_controller));
 }
@@ -0,0 +1,92 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Benchmarks of area generation, path finding, and line of sight.  Run from
+//! the `sulis_bench` directory with `cargo bench`.
+//! The module and areas used may be overridden with the `SULIS_BENCH_MODULE`,
+//! `SULIS_BENCH_GEN_AREA`, `SULIS_BENCH_LOS_AREA`, and `SULIS_BENCH_ACTOR`
+//! environment variables.
+
+use std::env;
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use sulis_state::bench::{self, LosFixture, PathFixture};
+
+const SEED: u128 = 1_234_567;
+const PATH_SIZES: [i32; 3] = [32, 64, 128];
+
+fn var(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+fn setup() {
+    // resource paths in the config are relative to the repository root
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    env::set_current_dir(root).expect("Unable to change to the repository root");
+
+    let module = var("SULIS_BENCH_MODULE", "campaigns/endless_dungeon");
+    bench::load_module(&module).expect("Unable to load the benchmark module");
+}
+
+fn area_generation(c: &mut Criterion) {
+    let area = var("SULIS_BENCH_GEN_AREA", "dungeon_level08");
+
+    let mut group = c.benchmark_group("area_generation");
+    group.sample_size(10);
+    group.bench_function(&area, |b| {
+        b.iter(|| bench::generate_area(black_box(&area), SEED).unwrap())
+    });
+    group.finish();
+}
+
+fn path_finding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path_finding");
+    for size in PATH_SIZES.iter() {
+        let mut fixture = PathFixture::serpentine(*size);
+        assert!(fixture.find().is_some());
+        group.bench_with_input(BenchmarkId::new("serpentine", size), size, |b, _| {
+            b.iter(|| fixture.find())
+        });
+
+        let mut fixture = PathFixture::unreachable(*size);
+        assert!(fixture.find().is_none());
+        group.bench_with_input(BenchmarkId::new("unreachable", size), size, |b, _| {
+            b.iter(|| fixture.find())
+        });
+    }
+    group.finish();
+}
+
+fn los_recomputation(c: &mut Criterion) {
+    let area = var("SULIS_BENCH_LOS_AREA", "dungeon_level08");
+    let actor = var("SULIS_BENCH_ACTOR", "dwarf01");
+    let mut fixture = LosFixture::new(&area, &actor, SEED).unwrap();
+
+    let mut group = c.benchmark_group("los_recomputation");
+    group.bench_function(&area, |b| b.iter(|| fixture.recompute_all()));
+    group.finish();
+}
+
+fn hot_paths(c: &mut Criterion) {
+    setup();
+    area_generation(c);
+    path_finding(c);
+    los_recomputation(c);
+}
+
+criterion_group!(benches, hot_paths);
+criterion_main!(benches);
@@ -0,0 +1 @@
+//! Benchmarks for Sulis.  See `benches/hot_paths.rs`.
@@ -14,7 +14,7 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Error, ErrorKind, Read};
@@ -26,11 +26,15 @@ use serde::{Deserialize, Deserializer};
 use log::{Level, LevelFilter};
 
 use crate::io::keyboard_event::Key;
-use crate::io::{event::ClickKind, InputActionKind, InputAction, KeyboardEvent};
+use crate::io::{
+    event::ClickKind, GamepadBinding, GamepadButton, InputActionKind, InputAction, KeyBindings,
+    KeyboardEvent,
+};
 
 thread_local! {
     static CONFIG: RefCell<Config> = RefCell::new(Config::init());
     static OLD_CONFIG: RefCell<Option<Config>> = RefCell::new(None);
+    static DETECTED_UI_SCALE: Cell<f32> = Cell::new(1.0);
 }
 
 lazy_static! {
@@ -48,6 +52,9 @@ pub struct Config {
     pub logging: LoggingConfig,
     pub editor: EditorConfig,
 
+    #[serde(default)]
+    pub gameplay: GameplayConfig,
+
     #[serde(default)]
     pub debug: DebugConfig,
 }
@@ -78,6 +85,15 @@ impl Config {
         CONFIG.with(|c| c.borrow().display.monitor)
     }
 
+    pub fn language() -> String {
+        CONFIG.with(|c| c.borrow().display.language.to_string())
+    }
+
+    /// Calls `f` with the current language, without copying it
+    pub fn with_language<T>(f: impl FnOnce(&str) -> T) -> T {
+        CONFIG.with(|c| f(&c.borrow().display.language))
+    }
+
     pub fn default_font() -> String {
         CONFIG.with(|c| c.borrow().display.default_font.to_string())
     }
@@ -91,20 +107,52 @@ impl Config {
     }
 
     pub fn ui_height() -> i32 {
-        CONFIG.with(|c| c.borrow().display.height)
+        Config::ui_size().1
     }
 
     pub fn ui_width() -> i32 {
-        CONFIG.with(|c| c.borrow().display.width)
+        Config::ui_size().0
     }
 
+    /// The size of the UI coordinate space that all widgets are laid out in.
+    /// This is the configured base width and height divided by the UI scale
+    /// factor, so smaller factors give more room and smaller widgets
     pub fn ui_size() -> (i32, i32) {
+        let factor = Config::ui_scale();
         CONFIG.with(|c| {
             let c = c.borrow();
-            (c.display.width, c.display.height)
+            let width = (c.display.width as f32 / factor).round() as i32;
+            let height = (c.display.height as f32 / factor).round() as i32;
+            (width, height)
         })
     }
 
+    /// The UI scale factor currently in use, either the configured value or
+    /// the value detected from the monitor when automatic scaling is enabled
+    pub fn ui_scale() -> f32 {
+        let (auto, factor) = CONFIG.with(|c| {
+            let c = c.borrow();
+            (c.display.auto_ui_scale, c.display.ui_scale)
+        });
+
+        let factor = if auto {
+            DETECTED_UI_SCALE.with(|s| s.get())
+        } else {
+            factor
+        };
+        factor.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+    }
+
+    /// Sets the automatic UI scale factor based on the monitor's height in
+    /// logical (DPI independent) pixels, so the UI keeps a similar physical
+    /// size on large and high density displays
+    pub fn detect_ui_scale(logical_height: f64) {
+        let factor = (UI_SCALE_REFERENCE_HEIGHT / logical_height) as f32;
+        let factor = factor.clamp(MIN_AUTO_UI_SCALE, MAX_UI_SCALE);
+        info!("Detected UI scale factor {} for logical height {}", factor, logical_height);
+        DETECTED_UI_SCALE.with(|s| s.set(factor));
+    }
+
     pub fn frame_rate() -> u32 {
         CONFIG.with(|c| c.borrow().display.frame_rate)
     }
@@ -138,14 +186,7 @@ impl Config {
     }
 
     pub fn get_keybindings() -> HashMap<InputActionKind, Key> {
-        CONFIG.with(|c| {
-            c.borrow()
-                .input
-                .keybindings
-                .iter()
-                .map(|(k, v)| (*v, *k))
-                .collect()
-        })
+        CONFIG.with(|c| c.borrow().input.keybindings.by_action())
     }
 
     pub fn get_click_action(button: RawClick) -> ClickKind {
@@ -155,12 +196,28 @@ impl Config {
     pub fn get_input_action(k: KeyboardEvent) -> Option<InputAction> {
         debug!("Got keyboard input '{:?}'", k);
         CONFIG.with(|c| {
-            let kind = c.borrow().input.keybindings.get(&k.key).copied();
+            let kind = c.borrow().input.keybindings.action(k.key);
 
             kind.map(|kind| InputAction { kind, state: k.state })
         })
     }
 
+    pub fn gamepad_enabled() -> bool {
+        CONFIG.with(|c| c.borrow().input.gamepad.enabled)
+    }
+
+    pub fn gamepad_cursor_speed() -> f32 {
+        CONFIG.with(|c| c.borrow().input.gamepad.cursor_speed)
+    }
+
+    pub fn gamepad_dead_zone() -> f32 {
+        CONFIG.with(|c| c.borrow().input.gamepad.dead_zone.clamp(0.0, 0.95))
+    }
+
+    pub fn gamepad_binding(button: GamepadButton) -> Option<GamepadBinding> {
+        CONFIG.with(|c| c.borrow().input.gamepad.buttons.get(&button).copied())
+    }
+
     pub fn scroll_speed() -> f32 {
         CONFIG.with(|c| c.borrow().input.scroll_speed)
     }
@@ -177,11 +234,130 @@ impl Config {
         CONFIG.with(|c| c.borrow().display.scroll_to_active)
     }
 
+    pub fn fog_of_war() -> bool {
+        CONFIG.with(|c| c.borrow().display.fog_of_war)
+    }
+
+    pub fn lighting() -> bool {
+        CONFIG.with(|c| c.borrow().display.lighting)
+    }
+
+    pub fn feedback_channel_enabled(channel: FeedbackChannel) -> bool {
+        CONFIG.with(|c| {
+            !c.borrow()
+                .display
+                .disabled_feedback_channels
+                .contains(&channel)
+        })
+    }
+
+    pub fn autosave() -> AutosaveFrequency {
+        CONFIG.with(|c| c.borrow().gameplay.autosave)
+    }
+
+    /// The configured directory to store saves in, if it overrides the default
+    pub fn save_directory() -> Option<PathBuf> {
+        CONFIG.with(|c| c.borrow().gameplay.save_directory.as_ref().map(PathBuf::from))
+    }
+
+    pub fn save_backups() -> u32 {
+        CONFIG.with(|c| c.borrow().gameplay.save_backups)
+    }
+
+    pub fn fixed_combat_rolls() -> bool {
+        CONFIG.with(|c| c.borrow().gameplay.fixed_combat_rolls)
+    }
+
     pub fn bench_log_level() -> Level {
         CONFIG.with(|c| c.borrow().logging.bench_log_level)
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GameplayConfig {
+    #[serde(default)]
+    pub autosave: AutosaveFrequency,
+
+    /// A directory to store saves in, instead of the platform data directory
+    #[serde(default)]
+    pub save_directory: Option<String>,
+
+    /// The number of previous versions kept when a save file is overwritten
+    #[serde(default = "default_save_backups")]
+    pub save_backups: u32,
+
+    /// Whether combat rolls continue from the state stored in a save when it
+    /// is loaded, so reloading does not reroll them
+    #[serde(default)]
+    pub fixed_combat_rolls: bool,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        GameplayConfig {
+            autosave: AutosaveFrequency::default(),
+            save_directory: None,
+            save_backups: default_save_backups(),
+            fixed_combat_rolls: false,
+        }
+    }
+}
+
+fn default_save_backups() -> u32 {
+    2
+}
+
+/// How often the game is automatically saved while playing.  Autosaves
+/// overwrite a single save file and are skipped during combat
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(deny_unknown_fields)]
+pub enum AutosaveFrequency {
+    #[default]
+    Never,
+    AreaTransition,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+}
+
+const AUTOSAVE_FREQUENCIES: [AutosaveFrequency; 5] = [
+    AutosaveFrequency::Never,
+    AutosaveFrequency::AreaTransition,
+    AutosaveFrequency::FiveMinutes,
+    AutosaveFrequency::FifteenMinutes,
+    AutosaveFrequency::ThirtyMinutes,
+];
+
+impl AutosaveFrequency {
+    pub fn iter() -> impl Iterator<Item = &'static AutosaveFrequency> {
+        AUTOSAVE_FREQUENCIES.iter()
+    }
+
+    /// The time between timed autosaves, or None if this frequency does not
+    /// save on a timer
+    pub fn interval_millis(self) -> Option<u32> {
+        use AutosaveFrequency::*;
+        match self {
+            Never | AreaTransition => None,
+            FiveMinutes => Some(5 * 60 * 1000),
+            FifteenMinutes => Some(15 * 60 * 1000),
+            ThirtyMinutes => Some(30 * 60 * 1000),
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        use AutosaveFrequency::*;
+        match self {
+            Never => "never",
+            AreaTransition => "area_transition",
+            FiveMinutes => "five_minutes",
+            FifteenMinutes => "fifteen_minutes",
+            ThirtyMinutes => "thirty_minutes",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct DebugConfig {
@@ -277,8 +453,43 @@ pub struct DisplayConfig {
     pub default_font: String,
     pub default_cursor: String,
     pub scroll_to_active: bool,
+
+    #[serde(default = "serde_true")]
+    pub fog_of_war: bool,
+
+    #[serde(default = "serde_true")]
+    pub lighting: bool,
+
+    #[serde(default)]
+    pub disabled_feedback_channels: Vec<FeedbackChannel>,
+
+    #[serde(default = "serde_one")]
+    pub ui_scale: f32,
+
+    #[serde(default = "serde_true")]
+    pub auto_ui_scale: bool,
+
+    #[serde(default = "default_language")]
+    pub language: String,
+}
+
+fn serde_true() -> bool {
+    true
 }
 
+fn serde_one() -> f32 {
+    1.0
+}
+
+fn default_language() -> String {
+    crate::resource::DEFAULT_LANGUAGE.to_string()
+}
+
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 1.0;
+const MIN_AUTO_UI_SCALE: f32 = 0.75;
+const UI_SCALE_REFERENCE_HEIGHT: f64 = 1080.0;
+
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(deny_unknown_fields)]
 pub enum DisplayMode {
@@ -287,6 +498,54 @@ pub enum DisplayMode {
     Fullscreen,
 }
 
+/// The categories of floating text shown above entities in the area.  Each
+/// channel may be styled separately by the theme, and disabled in the config
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub enum FeedbackChannel {
+    Damage,
+    Healing,
+    Miss,
+    Xp,
+    Status,
+}
+
+const FEEDBACK_CHANNELS: [FeedbackChannel; 5] = [
+    FeedbackChannel::Damage,
+    FeedbackChannel::Healing,
+    FeedbackChannel::Miss,
+    FeedbackChannel::Xp,
+    FeedbackChannel::Status,
+];
+
+impl FeedbackChannel {
+    pub fn iter() -> impl Iterator<Item = &'static FeedbackChannel> {
+        FEEDBACK_CHANNELS.iter()
+    }
+
+    pub fn index(self) -> usize {
+        use FeedbackChannel::*;
+        match self {
+            Damage => 0,
+            Healing => 1,
+            Miss => 2,
+            Xp => 3,
+            Status => 4,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        use FeedbackChannel::*;
+        match self {
+            Damage => "damage",
+            Healing => "healing",
+            Miss => "miss",
+            Xp => "xp",
+            Status => "status",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ResourcesConfig {
@@ -300,9 +559,51 @@ pub struct ResourcesConfig {
 pub struct InputConfig {
     pub edge_scrolling: bool,
     pub scroll_speed: f32,
-    pub keybindings: HashMap<Key, InputActionKind>,
+    pub keybindings: KeyBindings,
     pub click_actions: HashMap<RawClick, ClickKind>,
     pub crit_screen_shake: bool,
+
+    #[serde(default)]
+    pub gamepad: GamepadConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GamepadConfig {
+    pub enabled: bool,
+    pub cursor_speed: f32,
+    pub dead_zone: f32,
+    pub buttons: HashMap<GamepadButton, GamepadBinding>,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        use GamepadBinding::*;
+        use GamepadButton::*;
+        let buttons = [
+            (South, Click(ClickKind::Primary)),
+            (East, Click(ClickKind::Secondary)),
+            (North, Action(InputActionKind::RadialMenu)),
+            (West, Action(InputActionKind::EndTurn)),
+            (Start, Action(InputActionKind::Back)),
+            (Select, Action(InputActionKind::ToggleMap)),
+            (LeftBumper, Action(InputActionKind::ZoomOut)),
+            (RightBumper, Action(InputActionKind::ZoomIn)),
+            (LeftTrigger, Action(InputActionKind::ToggleInventory)),
+            (RightTrigger, Action(InputActionKind::ToggleCharacter)),
+            (DPadUp, Action(InputActionKind::SelectPartyMember1)),
+            (DPadRight, Action(InputActionKind::SelectPartyMember2)),
+            (DPadDown, Action(InputActionKind::SelectPartyMember3)),
+            (DPadLeft, Action(InputActionKind::SelectPartyMember4)),
+        ];
+
+        GamepadConfig {
+            enabled: true,
+            cursor_speed: 150.0,
+            dead_zone: 0.2,
+            buttons: buttons.into_iter().collect(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -344,6 +645,37 @@ fn get_user_dir() -> PathBuf {
     path
 }
 
+/// The platform specific directory for game data such as saves
+#[cfg(target_os = "windows")]
+pub fn platform_data_dir() -> PathBuf {
+    get_user_dir()
+}
+
+/// The platform specific directory for game data such as saves
+#[cfg(target_os = "macos")]
+pub fn platform_data_dir() -> PathBuf {
+    let mut path = get_home_dir();
+    path.push("Library");
+    path.push("Application Support");
+    path.push("Sulis");
+    path
+}
+
+/// The platform specific directory for game data such as saves
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn platform_data_dir() -> PathBuf {
+    let mut path = match ::std::env::var("XDG_DATA_HOME") {
+        Ok(path_str) => PathBuf::from(path_str),
+        Err(_) => {
+            let mut path = get_home_dir();
+            path.push(".local/share/");
+            path
+        }
+    };
+    path.push("sulis/");
+    path
+}
+
 fn get_home_dir() -> PathBuf {
     match home::home_dir() {
         Some(path) => path,
@@ -29,6 +29,7 @@ pub struct TimerImage {
     frames: Vec<Rc<dyn Image>>,
     frame_time_millis: u32,
     total_frame_time: u32,
+    hold_last_frame: bool,
     size: Size,
 }
 
@@ -73,11 +74,16 @@ impl TimerImage {
             size: size.unwrap(),
             frame_time_millis: builder.frame_time_millis,
             total_frame_time,
+            hold_last_frame: builder.hold_last_frame,
             id: builder.id,
         }))
     }
 
     fn get_cur_frame(&self, millis: u32) -> &Rc<dyn Image> {
+        if self.hold_last_frame && millis >= self.total_frame_time {
+            return &self.frames[self.frames.len() - 1];
+        }
+
         let offset = millis % self.total_frame_time;
         let index = (offset / self.frame_time_millis) as usize;
 
@@ -131,4 +137,8 @@ pub struct TimerImageBuilder {
     id: String,
     frames: Vec<String>,
     frame_time_millis: u32,
+
+    /// If true, the image stops on its last frame rather than looping
+    #[serde(default)]
+    hold_last_frame: bool,
 }
@@ -20,15 +20,21 @@ pub use self::audio::{Audio, AudioDevice, AudioDeviceInfo, SoundSource, create_a
 pub mod event;
 pub use self::event::Event;
 
+mod gamepad;
+pub use self::gamepad::{Gamepad, GamepadBinding, GamepadButton};
+
 mod glium_adapter;
 
 mod input_action;
 pub use self::input_action::{InputAction, InputActionKind, InputActionState};
 
+mod keybindings;
+pub use self::keybindings::KeyBindings;
+
 pub mod keyboard_event;
 pub use self::keyboard_event::KeyboardEvent;
 
-use std::cell::{RefCell};
+use std::cell::{Cell, RefCell};
 use std::io::Error;
 use std::rc::Rc;
 
@@ -39,6 +45,38 @@ use crate::resource::{Sprite};
 use crate::ui::{Color, Widget};
 use crate::util::{Point, Rect, Scale, Size};
 
+/// A capture of the rendered frame, top row first
+pub type Screenshot = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+thread_local! {
+    static SCREENSHOT_REQUESTED: Cell<bool> = Cell::new(false);
+    static SCREENSHOT: RefCell<Option<Screenshot>> = RefCell::new(None);
+}
+
+/// Requests that the next rendered frame be captured.  Once it has been,
+/// `is_screenshot_pending` returns false and the capture is available from
+/// `take_screenshot`, unless the capture failed.
+pub fn request_screenshot() {
+    SCREENSHOT.with(|s| *s.borrow_mut() = None);
+    SCREENSHOT_REQUESTED.with(|r| r.set(true));
+}
+
+pub fn is_screenshot_pending() -> bool {
+    SCREENSHOT_REQUESTED.with(|r| r.get())
+}
+
+pub fn take_screenshot() -> Option<Screenshot> {
+    SCREENSHOT.with(|s| s.borrow_mut().take())
+}
+
+fn take_screenshot_request() -> bool {
+    SCREENSHOT_REQUESTED.with(|r| r.replace(false))
+}
+
+fn set_screenshot(screenshot: Screenshot) {
+    SCREENSHOT.with(|s| *s.borrow_mut() = Some(screenshot));
+}
+
 #[derive(Debug, Clone)]
 pub struct DisplayConfiguration {
     pub name: String,
@@ -104,6 +142,17 @@ pub enum TextureMinFilter {
     LinearMipmapLinear,
 }
 
+/// How a draw list is combined with what has already been drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Standard blending by the alpha of the drawn color
+    Alpha,
+
+    /// The drawn color is multiplied with the existing color, darkening
+    /// and tinting it.  Used for lighting overlays
+    Multiply,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum DrawListKind {
     Font,
@@ -123,6 +172,7 @@ pub struct DrawList {
     pub scale: [f32; 2],
     pub color_swap_enabled: bool,
     pub swap_hue: f32,
+    pub blend_mode: BlendMode,
 }
 
 impl Default for DrawList {
@@ -140,6 +190,7 @@ impl Default for DrawList {
             scale: [1.0, 1.0],
             color_swap_enabled: false,
             swap_hue: 0.0,
+            blend_mode: BlendMode::Alpha,
         }
     }
 }
@@ -244,6 +295,11 @@ impl DrawList {
         self.color_filter = [color.r, color.g, color.b, color.a];
     }
 
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
     #[inline]
     pub fn set_color_sec(&mut self, color: Color) {
         self.color_sec = [color.r, color.g, color.b, color.a];
@@ -19,8 +19,7 @@ use std::fmt;
 use std::time::Duration;
 use std::collections::VecDeque;
 use std::cell::{RefCell};
-use std::io::{BufReader, Error, ErrorKind};
-use std::fs::File;
+use std::io::{Cursor, Error, ErrorKind};
 
 use rodio::{
     Sink, Device, DeviceTrait, Source, Decoder, OutputStream, OutputStreamHandle,
@@ -130,7 +129,7 @@ impl Audio {
 #[derive(Clone)]
 pub struct SoundSource {
     id: String,
-    sound: Buffered<Decoder<BufReader<File>>>,
+    sound: Buffered<Decoder<Cursor<Vec<u8>>>>,
     loops: bool,
     volume: f32,
     delay: Duration,
@@ -147,10 +146,10 @@ impl Eq for SoundSource {}
 impl SoundSource {
     pub fn new(
         id: String,
-        file: File,
+        data: Vec<u8>,
         entry: &EntryBuilder
     ) -> Result<SoundSource, Error> {
-        let sound = match Decoder::new(BufReader::new(file)) {
+        let sound = match Decoder::new(Cursor::new(data)) {
             Ok(sound) => sound,
             Err(e) => {
                 warn!("Error reading sound from file: {}", e);
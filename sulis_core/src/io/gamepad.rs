@@ -0,0 +1,219 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use crate::config::Config;
+use crate::io::{event::ClickKind, InputAction, InputActionKind, InputActionState};
+use crate::ui::{Cursor, Widget};
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    fn from_gilrs(button: Button) -> Option<GamepadButton> {
+        use GamepadButton::*;
+        Some(match button {
+            Button::South => South,
+            Button::East => East,
+            Button::North => North,
+            Button::West => West,
+            Button::LeftTrigger => LeftBumper,
+            Button::RightTrigger => RightBumper,
+            Button::LeftTrigger2 => LeftTrigger,
+            Button::RightTrigger2 => RightTrigger,
+            Button::Select => Select,
+            Button::Start => Start,
+            Button::LeftThumb => LeftStick,
+            Button::RightThumb => RightStick,
+            Button::DPadUp => DPadUp,
+            Button::DPadDown => DPadDown,
+            Button::DPadLeft => DPadLeft,
+            Button::DPadRight => DPadRight,
+            _ => return None,
+        })
+    }
+}
+
+/// What a gamepad button does when pressed.  Buttons either click at the
+/// virtual cursor, or trigger a game action in the same way as a key
+/// binding.
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum GamepadBinding {
+    Click(ClickKind),
+    Action(InputActionKind),
+}
+
+const SCROLL_ACTIONS: [InputActionKind; 4] = [
+    InputActionKind::ScrollUp,
+    InputActionKind::ScrollDown,
+    InputActionKind::ScrollLeft,
+    InputActionKind::ScrollRight,
+];
+
+/// Polls connected controllers and converts their input into `InputAction`s.
+/// The left stick drives the virtual cursor, the right stick scrolls the
+/// view, and buttons are mapped using the gamepad config.
+pub struct Gamepad {
+    gilrs: Gilrs,
+    left_stick: (f32, f32),
+    right_stick: (f32, f32),
+    scroll_active: [bool; 4],
+}
+
+impl Gamepad {
+    /// Creates the gamepad handler, or returns `None` if gamepad input is
+    /// disabled or unavailable on this system
+    pub fn new() -> Option<Gamepad> {
+        if !Config::gamepad_enabled() {
+            return None;
+        }
+
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Gamepad {
+                gilrs,
+                left_stick: (0.0, 0.0),
+                right_stick: (0.0, 0.0),
+                scroll_active: [false; 4],
+            }),
+            Err(e) => {
+                warn!("Unable to initialize gamepad input");
+                warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    pub fn update(&mut self, root: &Rc<RefCell<Widget>>, millis: u32) {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    handle_button(root, button, InputActionState::Started)
+                }
+                EventType::ButtonReleased(button, _) => {
+                    handle_button(root, button, InputActionState::Stopped)
+                }
+                EventType::AxisChanged(axis, value, _) => self.set_axis(axis, value),
+                EventType::Disconnected => {
+                    self.left_stick = (0.0, 0.0);
+                    self.right_stick = (0.0, 0.0);
+                }
+                _ => (),
+            }
+        }
+
+        self.move_cursor(root, millis);
+        self.update_scroll(root);
+    }
+
+    fn set_axis(&mut self, axis: Axis, value: f32) {
+        // gilrs uses positive y for up, while the UI uses positive y for down
+        match axis {
+            Axis::LeftStickX => self.left_stick.0 = value,
+            Axis::LeftStickY => self.left_stick.1 = -value,
+            Axis::RightStickX => self.right_stick.0 = value,
+            Axis::RightStickY => self.right_stick.1 = -value,
+            _ => (),
+        }
+    }
+
+    fn move_cursor(&self, root: &Rc<RefCell<Widget>>, millis: u32) {
+        let dead_zone = Config::gamepad_dead_zone();
+        let x = apply_dead_zone(self.left_stick.0, dead_zone);
+        let y = apply_dead_zone(self.left_stick.1, dead_zone);
+        if x == 0.0 && y == 0.0 {
+            return;
+        }
+
+        let dist = Config::gamepad_cursor_speed() * millis as f32 / 1000.0;
+        let (cur_x, cur_y) = Cursor::get_position_f32();
+        let max_x = (Config::ui_width() - 1) as f32;
+        let max_y = (Config::ui_height() - 1) as f32;
+
+        let new_x = (cur_x + x * dist).clamp(0.0, max_x);
+        let new_y = (cur_y + y * dist).clamp(0.0, max_y);
+        InputAction::mouse_move(new_x, new_y).handle(root);
+    }
+
+    fn update_scroll(&mut self, root: &Rc<RefCell<Widget>>) {
+        let dead_zone = Config::gamepad_dead_zone();
+        let (x, y) = self.right_stick;
+        let desired = [y < -dead_zone, y > dead_zone, x < -dead_zone, x > dead_zone];
+
+        for (index, kind) in SCROLL_ACTIONS.iter().enumerate() {
+            if desired[index] == self.scroll_active[index] {
+                continue;
+            }
+
+            self.scroll_active[index] = desired[index];
+            let state = if desired[index] {
+                InputActionState::Started
+            } else {
+                InputActionState::Stopped
+            };
+            InputAction { kind: *kind, state }.handle(root);
+        }
+    }
+}
+
+fn handle_button(root: &Rc<RefCell<Widget>>, button: Button, state: InputActionState) {
+    let binding = match GamepadButton::from_gilrs(button).and_then(Config::gamepad_binding) {
+        None => return,
+        Some(binding) => binding,
+    };
+
+    let action = match (binding, state) {
+        (GamepadBinding::Click(kind), InputActionState::Started) => {
+            InputAction::mouse_pressed(kind)
+        }
+        (GamepadBinding::Click(kind), InputActionState::Stopped) => {
+            InputAction::mouse_released(kind)
+        }
+        (GamepadBinding::Action(kind), state) => InputAction { kind, state },
+    };
+    action.handle(root);
+}
+
+// rescales the axis value so motion starts smoothly at the edge of the dead zone
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        return 0.0;
+    }
+
+    value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+}
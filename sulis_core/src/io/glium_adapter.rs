@@ -21,8 +21,9 @@ use std::io::{Error, ErrorKind};
 use crate::config::{Config, DisplayMode};
 use crate::io::keyboard_event::Key;
 use crate::io::*;
+use crate::extern_image::imageops;
 use crate::resource::ResourceSet;
-use crate::ui::{Cursor, Widget};
+use crate::ui::{self, Cursor, Widget};
 use crate::util::{Point, get_elapsed_millis};
 
 use glium::backend::Facade;
@@ -198,6 +199,29 @@ fn draw_to_surface<T: glium::Surface>(
         &display.base_program
     };
 
+    let multiply_params;
+    let params = match draw_list.blend_mode {
+        BlendMode::Alpha => params,
+        BlendMode::Multiply => {
+            use glium::draw_parameters::{Blend, BlendingFunction, LinearBlendingFactor};
+            multiply_params = glium::DrawParameters {
+                blend: Blend {
+                    color: BlendingFunction::Addition {
+                        source: LinearBlendingFactor::DestinationColor,
+                        destination: LinearBlendingFactor::Zero,
+                    },
+                    alpha: BlendingFunction::Addition {
+                        source: LinearBlendingFactor::Zero,
+                        destination: LinearBlendingFactor::One,
+                    },
+                    constant_value: (0.0, 0.0, 0.0, 0.0),
+                },
+                ..params.clone()
+            };
+            &multiply_params
+        }
+    };
+
     match surface.draw(&vertex_buffer, indices, program, &uniforms, params) {
         Ok(()) => (),
         Err(e) => error!("Error drawing to surface: {:?}", e),
@@ -443,6 +467,7 @@ impl GliumDisplay {
         );
 
         info!("Using hi dpi scale factor: {}", scale_factor);
+        Config::detect_ui_scale(monitor.size().height as f64 / scale_factor);
 
         let base_program = match glium::Program::from_source(
             &display,
@@ -466,19 +491,12 @@ impl GliumDisplay {
 
         display.gl_window().window().set_cursor_visible(false);
 
-        let (ui_x, ui_y) = Config::ui_size();
-
         Ok((GliumDisplay {
             display,
             monitor,
             base_program,
             swap_program,
-            matrix: [
-                [2.0 / ui_x as f32, 0.0, 0.0, 0.0],
-                [0.0, 2.0 / ui_y as f32, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [-1.0, -1.0, 0.0, 1.0f32],
-            ],
+            matrix: ui_matrix(),
             textures: HashMap::new(),
             scale_factor,
         }, event_loop))
@@ -533,6 +551,16 @@ impl GliumDisplay {
     }
 }
 
+fn ui_matrix() -> [[f32; 4]; 4] {
+    let (ui_x, ui_y) = Config::ui_size();
+    [
+        [2.0 / ui_x as f32, 0.0, 0.0, 0.0],
+        [0.0, 2.0 / ui_y as f32, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [-1.0, -1.0, 0.0, 1.0f32],
+    ]
+}
+
 pub(crate) fn main_loop(
     system: GliumSystem,
     mut updater: Box<dyn ControlFlowUpdater>,
@@ -543,8 +571,9 @@ pub(crate) fn main_loop(
     let mut root = updater.root();
 
     let mut scale = io.scale_factor;
-    let (ui_x, ui_y) = Config::ui_size();
+    let (mut ui_x, mut ui_y) = Config::ui_size();
     let mut mouse_move: Option<(f32, f32)> = None;
+    let mut gamepad = Gamepad::new();
     let mut display_size: LogicalSize<f64> = io.display.gl_window().window().inner_size().to_logical(scale);
 
     let frame_time = time::Duration::from_secs_f32(1.0 / Config::frame_rate() as f32);
@@ -569,6 +598,11 @@ pub(crate) fn main_loop(
             Event::WindowEvent { event: WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size, ..}, .. } => {
                 scale = scale_factor;
                 display_size = new_inner_size.to_logical(scale);
+
+                Config::detect_ui_scale(io.monitor.size().height as f64 / scale);
+                io.matrix = ui_matrix();
+                (ui_x, ui_y) = Config::ui_size();
+                ui::resize_ui_tree(&root);
             }
             Event::NewEvents(_) => {
                 last_elapsed = get_elapsed_millis(last_start_time.elapsed());
@@ -582,6 +616,10 @@ pub(crate) fn main_loop(
                 }
                 mouse_move = None;
 
+                if let Some(gamepad) = gamepad.as_mut() {
+                    gamepad.update(&root, last_elapsed);
+                }
+
                 root = updater.update(last_elapsed);
                 if updater.is_exit() {
                     *control_flow = ControlFlow::Exit;
@@ -601,12 +639,30 @@ pub(crate) fn main_loop(
                     }
 
                     audio = create_audio_device();
+
+                    io.matrix = ui_matrix();
+                    (ui_x, ui_y) = Config::ui_size();
+                    ui::resize_ui_tree(&root);
                 }
 
                 Audio::update(audio.as_mut(), last_elapsed);
 
                 io.render_output(&root.borrow(), total_elapsed);
 
+                if take_screenshot_request() {
+                    match io.display.read_front_buffer::<RawImage2d<u8>>() {
+                        Err(e) => warn!("Unable to capture screenshot: {:?}", e),
+                        Ok(raw) => match ImageBuffer::<Rgba<u8>, _>::from_raw(
+                            raw.width,
+                            raw.height,
+                            raw.data.into_owned(),
+                        ) {
+                            None => warn!("Invalid screenshot data"),
+                            Some(image) => set_screenshot(imageops::flip_vertical(&image)),
+                        }
+                    }
+                }
+
                 render_time += last_start_time.elapsed();
                 frames += 1;
             },
@@ -664,6 +720,7 @@ fn process_window_event(event: WindowEvent) -> Vec<InputAction> {
     match event {
         CloseRequested => vec![InputAction::exit()],
         ReceivedCharacter(c) => vec![InputAction::char_received(c)],
+        ModifiersChanged(modifiers) => vec![InputAction::shift_modifier(modifiers.shift())],
         KeyboardInput { input, .. } => {
             let mut result = Vec::new();
             let kb_event = match process_keyboard_input(input) {
@@ -38,6 +38,8 @@ pub enum InputActionKind {
     ToggleConsole,
     ConsoleHistoryPrevious,
     ConsoleHistoryNext,
+    ConsoleComplete,
+    ToggleScriptErrors,
     ToggleInventory,
     ToggleCharacter,
     ToggleMap,
@@ -73,12 +75,14 @@ pub enum InputActionKind {
     ActivateAbility8,
     ActivateAbility9,
     ActivateAbility10,
+    RadialMenu,
     Exit,
     MouseMove(f32, f32),
     MouseButton(ClickKind),
     MouseScroll(i32),
     CharReceived(char),
     RawKey(Key),
+    ShiftModifier(bool),
 }
 
 impl std::cmp::Eq for InputActionKind {}
@@ -126,6 +130,13 @@ impl InputAction {
         }
     }
 
+    pub fn shift_modifier(down: bool) -> InputAction {
+        InputAction {
+            kind: InputActionKind::ShiftModifier(down),
+            state: InputActionState::Started,
+        }
+    }
+
     pub fn exit() -> InputAction {
         InputAction {
             kind: InputActionKind::Exit,
@@ -178,6 +189,7 @@ impl InputAction {
             RawKey(key) => {
                 Widget::dispatch_event(root, Event::new(Kind::RawKey(key)));
             }
+            ShiftModifier(down) => Cursor::set_shift_down(down),
             _ => {
                 let kind = match self.state {
                     InputActionState::Started => Kind::KeyPress(self.kind),
@@ -0,0 +1,79 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+
+use crate::io::keyboard_event::Key;
+use crate::io::InputActionKind;
+
+/// The mapping of physical keys to the named game actions they trigger.
+/// Each key triggers at most one action.  This is stored as a plain map in
+/// the user config file.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(transparent)]
+pub struct KeyBindings {
+    bindings: HashMap<Key, InputActionKind>,
+}
+
+impl KeyBindings {
+    /// Returns the action triggered by the specified key, if any
+    pub fn action(&self, key: Key) -> Option<InputActionKind> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Returns a key bound to the specified action, if any
+    pub fn key(&self, action: InputActionKind) -> Option<Key> {
+        self.bindings
+            .iter()
+            .find(|(_, a)| **a == action)
+            .map(|(k, _)| *k)
+    }
+
+    /// Returns the action that would be unbound if `key` were bound
+    /// to `action`, or `None` if there is no conflict
+    pub fn conflict(&self, key: Key, action: InputActionKind) -> Option<InputActionKind> {
+        self.action(key).filter(|cur| *cur != action)
+    }
+
+    /// Binds `key` to `action`, replacing any keys previously bound to `action`.
+    /// If `key` was bound to a different action, that action is moved to the
+    /// key previously used by `action`, or is left unbound if `action` had no
+    /// key.  Returns the conflicting action, if there was one.
+    pub fn bind(&mut self, key: Key, action: InputActionKind) -> Option<InputActionKind> {
+        let conflict = self.conflict(key, action);
+        let prev_key = self.key(action);
+        self.bindings.retain(|_, a| *a != action);
+
+        if let (Some(conflict), Some(prev_key)) = (conflict, prev_key) {
+            self.bindings.insert(prev_key, conflict);
+        }
+
+        self.bindings.insert(key, action);
+        conflict
+    }
+
+    /// Returns all bindings, sorted by action
+    pub fn sorted(&self) -> Vec<(Key, InputActionKind)> {
+        let mut bindings: Vec<_> = self.bindings.iter().map(|(k, a)| (*k, *a)).collect();
+        bindings.sort_by(|(_, a1), (_, a2)| a1.partial_cmp(a2).unwrap());
+        bindings
+    }
+
+    /// Returns the reverse mapping, from each bound action to its key
+    pub fn by_action(&self) -> HashMap<InputActionKind, Key> {
+        self.bindings.iter().map(|(k, a)| (*a, *k)).collect()
+    }
+}
@@ -16,10 +16,12 @@
 
 mod resource_builder_set;
 pub use self::resource_builder_set::{
-    read_builder, read_builders, read_single_resource, read_single_resource_path, read_to_string,
-    write_json_to_file, write_to_file,
+    read_builder, read_builders, read_builders_serial, read_single_resource,
+    read_single_resource_path, read_to_string, write_json_to_file, write_to_file,
 };
 
+mod resource_cache;
+
 pub mod sound_set;
 pub use self::sound_set::{SoundSetBuilder, SoundSet};
 
@@ -30,6 +32,14 @@ pub use self::spritesheet::Spritesheet;
 mod font;
 pub use self::font::Font;
 
+mod glossary;
+pub use self::glossary::{GlossaryBuilder, GlossaryEntry};
+
+mod string_table;
+pub use self::string_table::{StringTable, StringTableBuilder};
+
+pub mod vfs;
+
 pub mod yaml_resource_set;
 pub use self::yaml_resource_set::YamlResourceKind;
 pub use self::yaml_resource_set::YamlResourceSet;
@@ -37,7 +47,6 @@ pub use self::yaml_resource_set::YamlResourceSet;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::fs;
 use std::hash::Hash;
 use std::io::{Error, ErrorKind};
 use std::path::Path;
@@ -52,11 +61,40 @@ use crate::image::{
     AnimatedImage, ComposedImage, EmptyImage, Image, SimpleImage, TimerImage, WindowImage,
 };
 use crate::resource::resource_builder_set::ResourceBuilderSet;
-use crate::ui::{Theme, ThemeSet};
+use crate::ui::{theme::expand_args, Theme, ThemeSet};
 use crate::util::{self, invalid_data_error};
 
 thread_local! {
     static RESOURCE_SET: RefCell<ResourceSet> = RefCell::new(ResourceSet::default());
+    static LOAD_ERRORS: RefCell<Vec<LoadError>> = RefCell::new(Vec::new());
+}
+
+/// A resource that could not be created, or a data file that could not be
+/// parsed.  These are logged as they occur and also collected, so that
+/// all of the errors encountered during a load can be reported together.
+/// For parse failures, the location, the path of keys leading to it, and a
+/// snippet of the surrounding lines are included where available.
+#[derive(Debug, Clone)]
+pub struct LoadError {
+    pub kind: String,
+    pub id: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub key_path: Option<String>,
+    pub snippet: Option<String>,
+    pub message: String,
+}
+
+/// Records the specified error, to be retrieved with `take_load_errors`
+pub fn record_load_error(error: LoadError) {
+    LOAD_ERRORS.with(|errors| errors.borrow_mut().push(error));
+}
+
+/// Returns all errors recorded since resources were last loaded, or since
+/// the last call to this function
+pub fn take_load_errors() -> Vec<LoadError> {
+    LOAD_ERRORS.with(|errors| errors.borrow_mut().drain(..).collect())
 }
 
 #[derive(Default)]
@@ -66,6 +104,34 @@ pub struct ResourceSet {
     pub(crate) spritesheets: HashMap<String, Rc<Spritesheet>>,
     pub(crate) fonts: HashMap<String, Rc<Font>>,
     pub(crate) sound_sets: HashMap<String, Rc<SoundSet>>,
+    pub(crate) string_tables: HashMap<String, Rc<StringTable>>,
+    pub(crate) glossary: HashMap<String, Rc<GlossaryEntry>>,
+    loaded_language: String,
+}
+
+/// The language that is used for any strings not found in the current
+/// language's string table
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Looks up the specified key in the current language's string table, see
+/// `ResourceSet::translate`.  Named args are substituted for references such
+/// as `#name#` in the translated string, as with widget text args
+///
+/// ```ignore
+/// let text = tr!("status.save_complete");
+/// let text = tr!("feedback.xp", amount = 10);
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::resource::ResourceSet::translate($key, &[])
+    };
+    ($key:expr, $($arg:ident = $val:expr),+ $(,)?) => {
+        $crate::resource::ResourceSet::translate(
+            $key,
+            &[$((stringify!($arg), $val.to_string().as_str())),+],
+        )
+    };
 }
 
 impl ResourceSet {
@@ -78,15 +144,22 @@ impl ResourceSet {
             ));
         }
 
+        LOAD_ERRORS.with(|errors| errors.borrow_mut().clear());
+        vfs::clear_cache();
+
         let yaml_start = std::time::Instant::now();
+        let language = Config::language();
         let root = dirs.remove(0);
         let path = Path::new(&root);
         let mut yaml = YamlResourceSet::new(path)?;
+        append_language_dir(&mut yaml, path, &language);
 
         for dir in dirs {
             let path = Path::new(&dir);
             yaml.append(path);
+            append_language_dir(&mut yaml, path, &language);
         }
+        yaml.save_cache();
 
         let dir_val = serde_yaml::Value::String(yaml_resource_set::DIRECTORY_VAL_STR.to_string());
         let file_val = serde_yaml::Value::String(yaml_resource_set::FILE_VAL_STR.to_string());
@@ -110,6 +183,7 @@ impl ResourceSet {
 
         let res_start = std::time::Instant::now();
         ResourceSet::load_builders(builder_set)?;
+        RESOURCE_SET.with(|r| r.borrow_mut().loaded_language = language);
         log::info!("  Built resources in {}s", util::format_elapsed_secs(res_start.elapsed()));
 
         Ok(yaml)
@@ -124,6 +198,8 @@ impl ResourceSet {
             set.images.clear();
             set.spritesheets.clear();
             set.fonts.clear();
+            set.string_tables.clear();
+            set.glossary.clear();
 
             set.themes = builder_set.theme_builder.create_theme_set()?;
 
@@ -150,6 +226,20 @@ impl ResourceSet {
             }
             info!("    Loaded fonts in {}s", util::format_elapsed_secs(font_start.elapsed()));
 
+            for (id, table) in builder_set.string_table_builders {
+                let table = StringTable::new(table);
+                insert_if_ok_boxed("string_table", id, table, &mut set.string_tables);
+            }
+
+            for (_, glossary) in builder_set.glossary_builders {
+                for entry in glossary.into_entries() {
+                    if set.glossary.contains_key(&entry.id) {
+                        warn!("Overwriting glossary entry '{}'", entry.id);
+                    }
+                    set.glossary.insert(entry.id.to_string(), entry);
+                }
+            }
+
             if !set.fonts.contains_key(&Config::default_font()) {
                 return invalid_data_error(&format!(
                     "Default font '{}' is not defined.",
@@ -272,6 +362,69 @@ impl ResourceSet {
         RESOURCE_SET.with(|r| get_resource(id, &r.borrow().fonts))
     }
 
+    /// Returns the string for the specified key in the current language, falling
+    /// back to the default language if it is not translated
+    pub fn string(key: &str) -> Option<String> {
+        Config::with_language(|language| {
+            RESOURCE_SET.with(|r| {
+                let r = r.borrow();
+                [language, DEFAULT_LANGUAGE]
+                    .iter()
+                    .filter_map(|lang| r.string_tables.get(*lang))
+                    .find_map(|table| table.get(key))
+                    .map(str::to_string)
+            })
+        })
+    }
+
+    /// Returns the string for the specified key in the current language, with
+    /// the specified args expanded.  If the key is not found in any string
+    /// table, the key itself is returned.  Normally used via `tr!`
+    pub fn translate(key: &str, args: &[(&str, &str)]) -> String {
+        let text = match ResourceSet::string(key) {
+            None => {
+                warn!("No string found for key '{}'", key);
+                return key.to_string();
+            }
+            Some(text) => text,
+        };
+
+        if args.is_empty() {
+            return text;
+        }
+
+        expand_args(&text, |id| {
+            args.iter().find(|(arg, _)| *arg == id).map(|(_, val)| *val)
+        })
+    }
+
+    /// Returns the glossary entry with the specified id, used for
+    /// keywords in widget text markup
+    pub fn glossary_entry(id: &str) -> Option<Rc<GlossaryEntry>> {
+        RESOURCE_SET.with(|r| r.borrow().glossary.get(id).map(Rc::clone))
+    }
+
+    /// All languages with a string table, as pairs of the language id and
+    /// its display name, sorted by name
+    pub fn languages() -> Vec<(String, String)> {
+        RESOURCE_SET.with(|r| {
+            let r = r.borrow();
+            let mut languages: Vec<_> = r
+                .string_tables
+                .values()
+                .map(|table| (table.id.to_string(), table.name.to_string()))
+                .collect();
+            languages.sort_by(|(_, a), (_, b)| a.cmp(b));
+            languages
+        })
+    }
+
+    /// The language that was active when resources were last loaded.  Campaign
+    /// data overrides for a new language require resources to be reloaded
+    pub fn loaded_language() -> String {
+        RESOURCE_SET.with(|r| r.borrow().loaded_language.clone())
+    }
+
     pub fn image(id: &str) -> Option<Rc<dyn Image>> {
         RESOURCE_SET.with(|r| get_resource(id, &r.borrow().images))
     }
@@ -349,6 +502,18 @@ impl ResourceSet {
     }
 }
 
+/// Appends the per-language overrides in `<dir>/lang/<language>`, if present.
+/// Overrides have the same layout as the directory itself, and are merged into
+/// existing resources, so only the translated fields need to be specified
+fn append_language_dir(yaml: &mut YamlResourceSet, dir: &Path, language: &str) {
+    let mut path = dir.to_path_buf();
+    path.push("lang");
+    path.push(language);
+    if vfs::is_dir(&path) {
+        yaml.append(&path);
+    }
+}
+
 pub fn all_resources<V: ?Sized>(map: &HashMap<String, Rc<V>>) -> Vec<Rc<V>> {
     map.iter().map(|ref res| Rc::clone(res.1)).collect()
 }
@@ -398,21 +563,27 @@ fn insert_if_ok_boxed<K: Eq + Hash + Display, V: ?Sized>(
 fn warn_on_insert<K: Display>(type_str: &str, key: K, error: Error) {
     warn!("Error in {} with id '{}'", type_str, key);
     warn!("{}", error);
+    record_load_error(LoadError {
+        kind: type_str.to_string(),
+        id: key.to_string(),
+        file: None,
+        line: None,
+        column: None,
+        key_path: None,
+        snippet: None,
+        message: error.to_string(),
+    });
 }
 
 pub fn subdirs<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
     let mut result = Vec::new();
 
-    let dir_entries = fs::read_dir(path)?;
-
-    for entry in dir_entries {
-        let entry = entry?;
-
-        if !entry.path().is_dir() {
+    for path in vfs::read_dir(path)? {
+        if !vfs::is_dir(&path) {
             continue;
         }
 
-        result.push(entry.path());
+        result.push(path);
     }
 
     Ok(result)
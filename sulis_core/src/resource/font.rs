@@ -22,6 +22,7 @@ use std::rc::Rc;
 
 use crate::config::Config;
 use crate::io::Vertex;
+use crate::resource::vfs;
 use crate::util::{invalid_data_error, unable_to_create_error, Point, Size};
 
 use crate::extern_image::{self, ImageBuffer, Rgba};
@@ -126,7 +127,12 @@ impl Font {
             let mut filepath = PathBuf::from(dir);
             filepath.push(&builder.src);
 
-            if let Ok(read_image) = extern_image::open(&filepath) {
+            let data = match vfs::read(&filepath) {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+
+            if let Ok(read_image) = extern_image::load_from_memory(&data) {
                 image = Some(read_image);
                 break;
             }
@@ -0,0 +1,58 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2020 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A single term that may be referenced from widget text markup as a keyword,
+/// i.e. `[k=armor|Armor]`, showing a tooltip with the name and text.
+#[derive(Debug)]
+pub struct GlossaryEntry {
+    pub id: String,
+    pub name: String,
+    pub text: String,
+}
+
+/// A set of glossary entries.  Glossaries are defined in the `glossary`
+/// directory; the entries from all glossaries are merged, so any entry
+/// may be referenced regardless of which file defines it.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GlossaryBuilder {
+    pub id: String,
+
+    #[serde(default)]
+    pub entries: HashMap<String, GlossaryEntryBuilder>,
+}
+
+impl GlossaryBuilder {
+    pub fn into_entries(self) -> impl Iterator<Item = Rc<GlossaryEntry>> {
+        self.entries.into_iter().map(|(id, entry)| {
+            Rc::new(GlossaryEntry {
+                id,
+                name: entry.name,
+                text: entry.text,
+            })
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GlossaryEntryBuilder {
+    pub name: String,
+    pub text: String,
+}
@@ -16,8 +16,8 @@
 
 use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::fs::{self, File};
-use std::io::{Error, Read};
+use std::fs::File;
+use std::io::Error;
 use std::path::{Path, PathBuf};
 
 use crate::image::animated_image::AnimatedImageBuilder;
@@ -41,6 +41,8 @@ pub struct ResourceBuilderSet {
     pub spritesheet_builders: HashMap<String, SpritesheetBuilder>,
     pub font_builders: HashMap<String, FontBuilder>,
     pub sound_set_builders: HashMap<String, SoundSetBuilder>,
+    pub string_table_builders: HashMap<String, StringTableBuilder>,
+    pub glossary_builders: HashMap<String, GlossaryBuilder>,
 }
 
 impl ResourceBuilderSet {
@@ -71,35 +73,64 @@ impl ResourceBuilderSet {
             animated_builders: read_builders(resources, AnimatedImage)?,
             spritesheet_builders: read_builders_insert_dirs(resources, Spritesheet)?,
             sound_set_builders: read_builders_insert_dirs(resources, SoundSet)?,
+            string_table_builders: read_builders(resources, StringTable)?,
+            glossary_builders: read_builders(resources, Glossary)?,
         })
     }
 }
 
-pub fn read_builders<T: serde::de::DeserializeOwned>(
+pub fn read_builders<T: serde::de::DeserializeOwned + Send>(
     resources: &mut YamlResourceSet,
     kind: YamlResourceKind,
 ) -> Result<HashMap<String, T>, Error> {
     read_builders_internal(resources, kind, false)
 }
 
-fn read_builders_insert_dirs<T: serde::de::DeserializeOwned>(
+fn read_builders_insert_dirs<T: serde::de::DeserializeOwned + Send>(
     resources: &mut YamlResourceSet,
     kind: YamlResourceKind,
 ) -> Result<HashMap<String, T>, Error> {
     read_builders_internal(resources, kind, true)
 }
 
-fn read_builders_internal<T: serde::de::DeserializeOwned>(
+/// Deserializes all resources of the specified `kind`.  Resources are
+/// deserialized in parallel, then checked in order of id, so the same error is
+/// reported on each load when several resources are invalid
+fn read_builders_internal<T: serde::de::DeserializeOwned + Send>(
     resources: &mut YamlResourceSet,
     kind: YamlResourceKind,
     insert_dirs: bool,
 ) -> Result<HashMap<String, T>, Error> {
+    let (sources, values) = take_entries(resources, kind, insert_dirs);
+    let results = util::parallel_map(values, read_builder_internal);
+    collect_builders(sources, results)
+}
+
+/// Deserializes all resources of the specified `kind` on the current thread.
+/// This is needed for builders that look up already loaded resources, such
+/// as images, while being deserialized
+pub fn read_builders_serial<T: serde::de::DeserializeOwned>(
+    resources: &mut YamlResourceSet,
+    kind: YamlResourceKind,
+) -> Result<HashMap<String, T>, Error> {
+    let (sources, values) = take_entries(resources, kind, false);
+    let results = values.into_iter().map(read_builder_internal).collect();
+    collect_builders(sources, results)
+}
+
+/// Removes all entries of the `kind` from the `resources`, returning the id
+/// and source files of each along with its value, sorted by id
+fn take_entries(
+    resources: &mut YamlResourceSet,
+    kind: YamlResourceKind,
+    insert_dirs: bool,
+) -> (Vec<(String, Vec<String>)>, Vec<serde_yaml::Value>) {
     let dir_key = serde_yaml::Value::String(yaml_resource_set::DIRECTORY_VAL_STR.to_string());
     let file_key = serde_yaml::Value::String(yaml_resource_set::FILE_VAL_STR.to_string());
 
-    let mut builders = HashMap::new();
-    if let Some(entries) = resources.resources.remove(&kind) {
-        for (id, mut entry) in entries {
+    let mut entries = Vec::new();
+    if let Some(kind_entries) = resources.resources.remove(&kind) {
+        for (id, mut entry) in kind_entries {
             let mut files = Vec::new();
             let mut dirs = serde_yaml::Sequence::new();
 
@@ -127,17 +158,30 @@ fn read_builders_internal<T: serde::de::DeserializeOwned>(
                 }
             }
 
-            let builder: T = match read_builder_internal(entry) {
-                Err(e) => {
-                    warn!("Error in YAML file merged from {:?}", files);
-                    return Err(e);
-                }
-                Ok(val) => val,
-            };
-
-            builders.insert(id, builder);
+            entries.push(((id, files), entry));
         }
     }
+    entries.sort_by(|a, b| (a.0).0.cmp(&(b.0).0));
+
+    entries.into_iter().unzip()
+}
+
+fn collect_builders<T>(
+    sources: Vec<(String, Vec<String>)>,
+    results: Vec<Result<T, Error>>,
+) -> Result<HashMap<String, T>, Error> {
+    let mut builders = HashMap::new();
+    for ((id, files), result) in sources.into_iter().zip(results) {
+        let builder = match result {
+            Err(e) => {
+                warn!("Error in YAML file merged from {:?}", files);
+                return Err(e);
+            }
+            Ok(val) => val,
+        };
+
+        builders.insert(id, builder);
+    }
     Ok(builders)
 }
 
@@ -221,7 +265,7 @@ pub fn write_to_file<T: serde::ser::Serialize, P: AsRef<Path>>(
 }
 
 pub fn read_single_resource_path<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, Error> {
-    let data = fs::read_to_string(path)?;
+    let data = vfs::read_to_string(path)?;
 
     let result: Result<T, serde_yaml::Error> = serde_yaml::from_str(&data);
     match result {
@@ -231,23 +275,20 @@ pub fn read_single_resource_path<T: serde::de::DeserializeOwned>(path: &Path) ->
 }
 
 pub fn read_single_resource<T: serde::de::DeserializeOwned>(filename: &str) -> Result<T, Error> {
-    let mut file = File::open(format!("{filename}.json"));
-    if file.is_err() {
-        file = File::open(format!("{filename}.yml"));
+    let mut data = vfs::read_to_string(format!("{filename}.json"));
+    if data.is_err() {
+        data = vfs::read_to_string(format!("{filename}.yml"));
     }
 
-    let mut file = match file {
+    let data = match data {
         Err(_) => {
             return invalid_data_error(&format!(
                 "Unable to locate '{filename}.json' or {filename}.yml'"
             ));
         }
-        Ok(f) => f,
+        Ok(data) => data,
     };
 
-    let mut data = String::new();
-    file.read_to_string(&mut data)?;
-
     let result: Result<T, serde_yaml::Error> = serde_yaml::from_str(&data);
     match result {
         Ok(result) => Ok(result),
@@ -269,28 +310,18 @@ fn read_recursive_to_string(dir: PathBuf, resources: &mut HashMap<String, String
     let dir_str = dir.to_string_lossy().to_string();
     debug!("Reading resources from {}", dir_str);
 
-    let dir_entries = match fs::read_dir(dir) {
-        Ok(entries) => entries,
+    let paths = match vfs::read_dir(dir) {
+        Ok(paths) => paths,
         Err(_) => {
             debug!("Unable to read directory: {}", dir_str);
             return;
         }
     };
 
-    for entry in dir_entries {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(error) => {
-                warn!("Error reading file: {}", error);
-                continue;
-            }
-        };
-
-        let path = entry.path();
-
-        if path.is_dir() {
+    for path in paths {
+        if vfs::is_dir(&path) {
             read_recursive_to_string(path, resources);
-        } else if path.is_file() {
+        } else if vfs::is_file(&path) {
             read_file_to_string(path, resources);
         }
     }
@@ -305,7 +336,7 @@ fn read_file_to_string(path: PathBuf, resources: &mut HashMap<String, String>) {
     }
 
     debug!("Reading file at {} to string", path_str);
-    let data = match fs::read_to_string(path.clone()) {
+    let data = match vfs::read_to_string(&path) {
         Ok(data) => data,
         Err(e) => {
             warn!("Error reading file at '{}': {}", path_str, e);
@@ -0,0 +1,329 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2020 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! A binary cache of parsed resource files.  Parsing the YAML for a large
+//! campaign - particularly the area and tile files - makes up much of the
+//! startup time, so after each load the parsed value of every file is
+//! written to `cache/resources.bin` in the user directory.  On the next load,
+//! the value for a file is taken from the cache if the file contents have
+//! the same hash, and the file is only parsed if it has changed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::PathBuf;
+
+use serde_yaml::{Mapping, Number, Value};
+
+use crate::config;
+use crate::resource::vfs;
+use crate::util::invalid_data_error;
+
+const MAGIC: &[u8] = b"SULISRC\0";
+
+/// Increment whenever the encoding changes, to discard old caches
+const VERSION: u32 = 1;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const INT: u8 = 3;
+const UINT: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const SEQUENCE: u8 = 7;
+const MAPPING: u8 = 8;
+
+struct Entry {
+    hash: u64,
+    data: Vec<u8>,
+    used: bool,
+}
+
+#[derive(Default)]
+pub struct ResourceCache {
+    entries: HashMap<String, Entry>,
+    changed: bool,
+}
+
+impl ResourceCache {
+    /// Reads the cache from the user directory.  If there is no cache, or it
+    /// is invalid, an empty cache is returned and all files will be parsed
+    pub fn load() -> ResourceCache {
+        let path = cache_file();
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(_) => {
+                debug!("No resource cache at {:?}", path);
+                return ResourceCache::default();
+            }
+        };
+
+        match read_entries(&data) {
+            Ok(entries) => ResourceCache {
+                entries,
+                changed: false,
+            },
+            Err(e) => {
+                info!("Discarding invalid resource cache {:?}: {}", path, e);
+                ResourceCache {
+                    entries: HashMap::new(),
+                    changed: true,
+                }
+            }
+        }
+    }
+
+    /// Returns the cached value for the file at `path`, if the hash of its
+    /// contents has not changed since it was cached
+    pub fn get(&self, path: &str, hash: u64) -> Option<Value> {
+        let entry = self.entries.get(path)?;
+        if entry.hash != hash {
+            return None;
+        }
+
+        let mut reader = Reader {
+            data: &entry.data,
+            pos: 0,
+        };
+        reader.value().ok()
+    }
+
+    /// Marks the cached value for `path` as current, so it is kept when the
+    /// cache is saved
+    pub fn mark_used(&mut self, path: &str) {
+        if let Some(entry) = self.entries.get_mut(path) {
+            entry.used = true;
+        }
+    }
+
+    /// Caches the `encoded` value of the file at `path`, see `encode`
+    pub fn insert(&mut self, path: &str, hash: u64, encoded: Vec<u8>) {
+        self.entries.insert(
+            path.to_string(),
+            Entry {
+                hash,
+                data: encoded,
+                used: true,
+            },
+        );
+        self.changed = true;
+    }
+
+    /// Writes the cache to the user directory if anything has changed.
+    /// Entries for files that were not read in this load are kept if the
+    /// file still exists, as they may belong to another module
+    pub fn save(mut self) {
+        let before = self.entries.len();
+        self.entries
+            .retain(|path, entry| entry.used || vfs::is_file(path));
+        if !self.changed && self.entries.len() == before {
+            return;
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        let mut paths: Vec<&String> = self.entries.keys().collect();
+        paths.sort();
+        for path in paths {
+            let entry = &self.entries[path];
+            write_bytes(&mut out, path.as_bytes());
+            out.extend_from_slice(&entry.hash.to_le_bytes());
+            write_bytes(&mut out, &entry.data);
+        }
+
+        let path = cache_file();
+        if let Some(dir) = path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!("Unable to create resource cache directory: {}", e);
+                return;
+            }
+        }
+
+        if let Err(e) = fs::write(&path, out) {
+            warn!("Unable to write resource cache {:?}: {}", path, e);
+        }
+    }
+}
+
+fn cache_file() -> PathBuf {
+    let mut path = config::USER_DIR.clone();
+    path.push("cache");
+    path.push("resources.bin");
+    path
+}
+
+/// Encodes the parsed value of a file for insertion into the cache
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// The 64 bit FNV-1a hash of the file contents.  This must be stable between
+/// runs and versions, so the standard library hasher is not used
+pub fn content_hash(data: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in data.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn read_entries(data: &[u8]) -> Result<HashMap<String, Entry>, Error> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return invalid_data_error("not a resource cache");
+    }
+    if reader.u32()? != VERSION {
+        return invalid_data_error("unsupported version");
+    }
+
+    let count = reader.u32()?;
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let path = reader.string()?;
+        let hash = reader.u64()?;
+        let len = reader.u32()? as usize;
+        let data = reader.take(len)?.to_vec();
+        entries.insert(
+            path,
+            Entry {
+                hash,
+                data,
+                used: false,
+            },
+        );
+    }
+
+    Ok(entries)
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(NULL),
+        Value::Bool(false) => out.push(FALSE),
+        Value::Bool(true) => out.push(TRUE),
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                out.push(UINT);
+                out.extend_from_slice(&n.to_le_bytes());
+            } else if let Some(n) = n.as_i64() {
+                out.push(INT);
+                out.extend_from_slice(&n.to_le_bytes());
+            } else {
+                out.push(FLOAT);
+                out.extend_from_slice(&n.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Value::String(s) => {
+            out.push(STRING);
+            write_bytes(out, s.as_bytes());
+        }
+        Value::Sequence(seq) => {
+            out.push(SEQUENCE);
+            out.extend_from_slice(&(seq.len() as u32).to_le_bytes());
+            for value in seq {
+                write_value(out, value);
+            }
+        }
+        Value::Mapping(map) => {
+            out.push(MAPPING);
+            out.extend_from_slice(&(map.len() as u32).to_le_bytes());
+            for (key, value) in map {
+                write_value(out, key);
+                write_value(out, value);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.pos + len > self.data.len() {
+            return invalid_data_error("unexpected end of data");
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.u32()? as usize;
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(s) => Ok(s.to_string()),
+            Err(_) => invalid_data_error("invalid string"),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        let kind = self.take(1)?[0];
+        Ok(match kind {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            INT => Value::Number(Number::from(self.u64()? as i64)),
+            UINT => Value::Number(Number::from(self.u64()?)),
+            FLOAT => Value::Number(Number::from(f64::from_bits(self.u64()?))),
+            STRING => Value::String(self.string()?),
+            SEQUENCE => {
+                let len = self.u32()?;
+                let mut seq = Vec::new();
+                for _ in 0..len {
+                    seq.push(self.value()?);
+                }
+                Value::Sequence(seq)
+            }
+            MAPPING => {
+                let len = self.u32()?;
+                let mut map = Mapping::new();
+                for _ in 0..len {
+                    let key = self.value()?;
+                    let value = self.value()?;
+                    map.insert(key, value);
+                }
+                Value::Mapping(map)
+            }
+            _ => return invalid_data_error("unknown value kind"),
+        })
+    }
+}
@@ -15,12 +15,12 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::io::{Error, ErrorKind};
-use std::fs::File;
 use std::rc::Rc;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::io::SoundSource;
+use crate::resource::vfs;
 
 pub struct SoundSet {
     id: String,
@@ -71,13 +71,13 @@ fn build_source(
         let mut filepath = PathBuf::from(dir);
         filepath.push(&entry_builder.file);
 
-        let file = match File::open(filepath) {
-            Ok(file) => file,
+        let data = match vfs::read(filepath) {
+            Ok(data) => data,
             Err(_) => continue,
         };
 
         let s_id = format!("{builder_id}/{entry_id}");
-        if let Ok(sound_source) = SoundSource::new(s_id, file, entry_builder) {
+        if let Ok(sound_source) = SoundSource::new(s_id, data, entry_builder) {
             source = Some(sound_source);
             break;
         }
@@ -20,7 +20,7 @@ use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::image::SimpleImage;
-use crate::resource::ResourceSet;
+use crate::resource::{vfs, ResourceSet};
 use crate::util::{unable_to_create_error, Point, Size};
 
 use crate::extern_image::{self, ImageBuffer, Rgba};
@@ -84,7 +84,12 @@ impl Spritesheet {
             let mut filepath = PathBuf::from(dir);
             filepath.push(&builder.src);
 
-            if let Ok(read_image) = extern_image::open(&filepath) {
+            let data = match vfs::read(&filepath) {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+
+            if let Ok(read_image) = extern_image::load_from_memory(&data) {
                 image = Some(read_image);
                 break;
             }
@@ -0,0 +1,53 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2020 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::io::Error;
+use std::rc::Rc;
+
+/// The translated strings for a single language.  Each language is defined by
+/// one or more files in the `strings` directory sharing the same id, which is
+/// the language code.  Modules and mods may add or override individual strings.
+#[derive(Debug)]
+pub struct StringTable {
+    pub id: String,
+    pub name: String,
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn new(builder: StringTableBuilder) -> Result<Rc<StringTable>, Error> {
+        Ok(Rc::new(StringTable {
+            id: builder.id,
+            name: builder.name,
+            strings: builder.strings,
+        }))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StringTableBuilder {
+    pub id: String,
+    pub name: String,
+
+    #[serde(default)]
+    pub strings: HashMap<String, String>,
+}
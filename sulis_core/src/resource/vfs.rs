@@ -0,0 +1,480 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2020 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! A virtual filesystem over the real filesystem and zip archives, so that
+//! a module may be distributed as a single `.zip` file.  Any path component
+//! naming a zip archive on disk is treated as a directory containing the
+//! contents of the archive, i.e. `campaigns/my_campaign.zip/areas/town.yml`.
+//! Only stored and deflated entries are supported, which covers archives
+//! written by `pack` and by most zip tools.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use lazy_static::lazy_static;
+
+use crate::util::invalid_data_error;
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x0605_4b50;
+
+const LOCAL_HEADER_LEN: usize = 30;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIR_LEN: usize = 22;
+const MAX_COMMENT_LEN: usize = 0xffff;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// General purpose flag indicating the entry name is UTF-8
+const FLAG_UTF8: u16 = 0x0800;
+
+/// DOS date for 1980-01-01, the earliest representable.  Packed archives
+/// use this for every entry so that packing the same module twice gives
+/// identical output
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+lazy_static! {
+    /// Each path with a `.zip` extension that has been resolved, along with
+    /// its archive if it is one
+    static ref ARCHIVES: Mutex<HashMap<PathBuf, Option<Arc<Archive>>>> =
+        Mutex::new(HashMap::new());
+}
+
+struct ArchiveEntry {
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    size: u64,
+    header_offset: u64,
+}
+
+struct Archive {
+    path: PathBuf,
+    len: u64,
+    files: HashMap<String, ArchiveEntry>,
+    children: HashMap<String, BTreeSet<String>>,
+}
+
+enum Location {
+    Fs(PathBuf),
+    Archive(Arc<Archive>, String),
+}
+
+/// Reads the entire contents of the file at `path`
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, Error> {
+    match resolve(path.as_ref())? {
+        Location::Fs(path) => fs::read(path),
+        Location::Archive(archive, name) => archive.read(&name),
+    }
+}
+
+/// Reads the entire contents of the file at `path` as UTF-8
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let data = read(path)?;
+    String::from_utf8(data).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Returns the paths of the entries in the directory at `path`, sorted
+pub fn read_dir<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
+    let path = path.as_ref();
+    let mut paths = match resolve(path)? {
+        Location::Fs(path) => {
+            let mut paths = Vec::new();
+            for entry in fs::read_dir(path)? {
+                paths.push(entry?.path());
+            }
+            paths
+        }
+        Location::Archive(archive, name) => match archive.children.get(&name) {
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("No directory '{}' in {:?}", name, archive.path),
+                ))
+            }
+            Some(children) => children.iter().map(|child| path.join(child)).collect(),
+        },
+    };
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Returns true if `path` is a directory, including the root of a zip archive
+/// or a directory within one
+pub fn is_dir<P: AsRef<Path>>(path: P) -> bool {
+    match resolve(path.as_ref()) {
+        Ok(Location::Fs(path)) => path.is_dir(),
+        Ok(Location::Archive(archive, name)) => archive.children.contains_key(&name),
+        Err(_) => false,
+    }
+}
+
+/// Returns true if `path` is a regular file.  Zip archives themselves are
+/// treated as directories, not files
+pub fn is_file<P: AsRef<Path>>(path: P) -> bool {
+    match resolve(path.as_ref()) {
+        Ok(Location::Fs(path)) => path.is_file(),
+        Ok(Location::Archive(archive, name)) => archive.files.contains_key(&name),
+        Err(_) => false,
+    }
+}
+
+/// Forgets all archives that have been read, so that any archives added or
+/// changed on disk since are read again.  This is called each time resources
+/// are loaded
+pub fn clear_cache() {
+    ARCHIVES.lock().unwrap().clear();
+}
+
+fn resolve(path: &Path) -> Result<Location, Error> {
+    let mut prefix = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        prefix.push(component);
+        let archive = match open_archive(&prefix)? {
+            None => continue,
+            Some(archive) => archive,
+        };
+
+        let mut parts = Vec::new();
+        for component in components {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    parts.pop();
+                }
+                component => parts.push(component.as_os_str().to_string_lossy().to_string()),
+            }
+        }
+
+        return Ok(Location::Archive(archive, parts.join("/")));
+    }
+
+    Ok(Location::Fs(path.to_path_buf()))
+}
+
+/// Returns the archive at `path`, or None if `path` is not a zip archive.
+/// Results are cached, so only the first lookup of each path touches the
+/// filesystem
+fn open_archive(path: &Path) -> Result<Option<Arc<Archive>>, Error> {
+    if path.extension().is_none_or(|ext| ext != "zip") {
+        return Ok(None);
+    }
+
+    let mut archives = ARCHIVES.lock().unwrap();
+    if let Some(archive) = archives.get(path) {
+        return Ok(archive.clone());
+    }
+
+    let archive = if path.is_file() {
+        debug!("Reading zip archive at '{:?}'", path);
+        Some(Arc::new(Archive::new(path)?))
+    } else {
+        None
+    };
+    archives.insert(path.to_path_buf(), archive.clone());
+    Ok(archive)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+impl Archive {
+    fn new(path: &Path) -> Result<Archive, Error> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        // the end of central directory record is at the end of the file,
+        // followed only by a variable length comment
+        let tail_len = len.min((END_OF_CENTRAL_DIR_LEN + MAX_COMMENT_LEN) as u64);
+        let mut tail = vec![0; tail_len as usize];
+        file.seek(SeekFrom::Start(len - tail_len))?;
+        file.read_exact(&mut tail)?;
+
+        let end = match (0..tail.len().saturating_sub(END_OF_CENTRAL_DIR_LEN - 1))
+            .rev()
+            .find(|&i| u32_at(&tail, i) == END_OF_CENTRAL_DIR_SIG)
+        {
+            None => return invalid_data_error(&format!("{path:?} is not a zip archive")),
+            Some(index) => &tail[index..],
+        };
+
+        let count = u16_at(end, 10) as usize;
+        let dir_size = u32_at(end, 12) as usize;
+        let dir_offset = u32_at(end, 16) as u64;
+
+        // don't trust the header sizes for allocation
+        if dir_offset + dir_size as u64 > len {
+            return invalid_data_error(&format!("Invalid central directory in {path:?}"));
+        }
+
+        let mut dir = vec![0; dir_size];
+        file.seek(SeekFrom::Start(dir_offset))?;
+        file.read_exact(&mut dir)?;
+
+        let mut files = HashMap::new();
+        let mut children: HashMap<String, BTreeSet<String>> = HashMap::new();
+        children.insert(String::new(), BTreeSet::new());
+
+        let mut pos = 0;
+        for _ in 0..count {
+            if pos + CENTRAL_HEADER_LEN > dir.len() || u32_at(&dir, pos) != CENTRAL_HEADER_SIG {
+                return invalid_data_error(&format!("Invalid central directory in {path:?}"));
+            }
+
+            let name_len = u16_at(&dir, pos + 28) as usize;
+            let extra_len = u16_at(&dir, pos + 30) as usize;
+            let comment_len = u16_at(&dir, pos + 32) as usize;
+            let name_start = pos + CENTRAL_HEADER_LEN;
+            if name_start + name_len > dir.len() {
+                return invalid_data_error(&format!("Invalid central directory in {path:?}"));
+            }
+            let name = String::from_utf8_lossy(&dir[name_start..name_start + name_len]);
+
+            let entry = ArchiveEntry {
+                method: u16_at(&dir, pos + 10),
+                crc: u32_at(&dir, pos + 16),
+                compressed_size: u32_at(&dir, pos + 20) as u64,
+                size: u32_at(&dir, pos + 24) as u64,
+                header_offset: u32_at(&dir, pos + 42) as u64,
+            };
+            pos = name_start + name_len + extra_len + comment_len;
+
+            let is_dir = name.ends_with('/');
+            let name: Vec<&str> = name
+                .split(['/', '\\'])
+                .filter(|part| !part.is_empty() && *part != ".")
+                .collect();
+            if name.is_empty() {
+                continue;
+            }
+
+            // add each parent directory, so that directories need not have
+            // their own entries
+            for i in 0..name.len() {
+                let parent = name[..i].join("/");
+                children
+                    .entry(parent)
+                    .or_default()
+                    .insert(name[i].to_string());
+            }
+
+            let name = name.join("/");
+            if is_dir {
+                children.entry(name).or_default();
+            } else {
+                files.insert(name, entry);
+            }
+        }
+
+        Ok(Archive {
+            path: path.to_path_buf(),
+            len,
+            files,
+            children,
+        })
+    }
+
+    fn read(&self, name: &str) -> Result<Vec<u8>, Error> {
+        let entry = match self.files.get(name) {
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("No file '{}' in {:?}", name, self.path),
+                ))
+            }
+            Some(entry) => entry,
+        };
+
+        let mut file = File::open(&self.path)?;
+        let mut header = [0; LOCAL_HEADER_LEN];
+        file.seek(SeekFrom::Start(entry.header_offset))?;
+        file.read_exact(&mut header)?;
+        if u32_at(&header, 0) != LOCAL_HEADER_SIG {
+            return invalid_data_error(&format!("Invalid entry '{name}' in {:?}", self.path));
+        }
+
+        // the name and extra field lengths in the local header may differ
+        // from those in the central directory
+        let skip = u16_at(&header, 26) as i64 + u16_at(&header, 28) as i64;
+        file.seek(SeekFrom::Current(skip))?;
+
+        let compressed = file.take(entry.compressed_size);
+        let mut data = Vec::with_capacity(entry.size.min(self.len) as usize);
+        match entry.method {
+            METHOD_STORED => {
+                let mut compressed = compressed;
+                compressed.read_to_end(&mut data)?;
+            }
+            METHOD_DEFLATED => {
+                DeflateDecoder::new(compressed).read_to_end(&mut data)?;
+            }
+            method => {
+                return invalid_data_error(&format!(
+                    "Unsupported compression method {method} for '{name}' in {:?}",
+                    self.path
+                ))
+            }
+        }
+
+        let mut crc = Crc::new();
+        crc.update(&data);
+        if data.len() as u64 != entry.size || crc.sum() != entry.crc {
+            return invalid_data_error(&format!("Corrupt entry '{name}' in {:?}", self.path));
+        }
+
+        Ok(data)
+    }
+}
+
+/// Packs the contents of the directory `dir` into a zip archive at `output`,
+/// which may then be used in place of the directory.  Returns the number of
+/// files written
+pub fn pack<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, output: Q) -> Result<usize, Error> {
+    let dir = dir.as_ref();
+    let output = output.as_ref();
+    if !dir.is_dir() {
+        return invalid_data_error(&format!("{dir:?} is not a directory"));
+    }
+
+    let mut files = Vec::new();
+    find_files(dir, "", &mut files)?;
+
+    // don't pack the archive into itself when it is written inside `dir`
+    let output_path = output.canonicalize().ok();
+    files.retain(|(path, _)| output_path.is_none() || path.canonicalize().ok() != output_path);
+
+    if files.len() >= 0xffff {
+        return invalid_data_error(&format!("Too many files in {dir:?} to pack"));
+    }
+
+    let mut out = Vec::new();
+    let mut central_dir = Vec::new();
+    for (path, name) in files.iter() {
+        let data = fs::read(path)?;
+        let mut crc = Crc::new();
+        crc.update(&data);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data)?;
+        let deflated = encoder.finish()?;
+        let (method, compressed) = if deflated.len() < data.len() {
+            (METHOD_DEFLATED, deflated)
+        } else {
+            (METHOD_STORED, data.clone())
+        };
+
+        let header_offset = out.len();
+        if header_offset > u32::MAX as usize || data.len() > u32::MAX as usize {
+            return invalid_data_error(&format!("{dir:?} is too large to pack"));
+        }
+
+        let mut header = Vec::new();
+        put_u16(&mut header, 20); // version needed to extract
+        put_u16(&mut header, FLAG_UTF8);
+        put_u16(&mut header, method);
+        put_u16(&mut header, 0); // modification time
+        put_u16(&mut header, DOS_DATE);
+        put_u32(&mut header, crc.sum());
+        put_u32(&mut header, compressed.len() as u32);
+        put_u32(&mut header, data.len() as u32);
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, 0); // extra field length
+
+        put_u32(&mut out, LOCAL_HEADER_SIG);
+        out.extend_from_slice(&header);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&compressed);
+
+        put_u32(&mut central_dir, CENTRAL_HEADER_SIG);
+        put_u16(&mut central_dir, 20); // version made by
+        central_dir.extend_from_slice(&header);
+        put_u16(&mut central_dir, 0); // comment length
+        put_u16(&mut central_dir, 0); // disk number
+        put_u16(&mut central_dir, 0); // internal attributes
+        put_u32(&mut central_dir, 0); // external attributes
+        put_u32(&mut central_dir, header_offset as u32);
+        central_dir.extend_from_slice(name.as_bytes());
+    }
+
+    let dir_offset = out.len();
+    if dir_offset + central_dir.len() > u32::MAX as usize {
+        return invalid_data_error(&format!("{dir:?} is too large to pack"));
+    }
+    out.extend_from_slice(&central_dir);
+
+    put_u32(&mut out, END_OF_CENTRAL_DIR_SIG);
+    put_u16(&mut out, 0); // disk number
+    put_u16(&mut out, 0); // disk with central directory
+    put_u16(&mut out, files.len() as u16);
+    put_u16(&mut out, files.len() as u16);
+    put_u32(&mut out, central_dir.len() as u32);
+    put_u32(&mut out, dir_offset as u32);
+    put_u16(&mut out, 0); // comment length
+
+    fs::write(output, out)?;
+    Ok(files.len())
+}
+
+fn find_files(dir: &Path, prefix: &str, files: &mut Vec<(PathBuf, String)>) -> Result<(), Error> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        paths.push(entry?.path());
+    }
+    paths.sort();
+
+    for path in paths {
+        let name = match path.file_name() {
+            None => continue,
+            Some(name) => format!("{}{}", prefix, name.to_string_lossy()),
+        };
+
+        if path.is_dir() {
+            find_files(&path, &format!("{name}/"), files)?;
+        } else if path.is_file() {
+            files.push((path, name));
+        }
+    }
+
+    Ok(())
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
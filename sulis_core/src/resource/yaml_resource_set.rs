@@ -15,18 +15,22 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::collections::HashMap;
-use std::fs;
 use std::io::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde_yaml::{self, Value};
 
+use crate::resource::resource_cache::{self, ResourceCache};
+use crate::resource::{record_load_error, vfs, LoadError};
+use crate::util;
+
 /// A set of resources that have been parsed into YAML values.  This is built up
 /// by first reading the bottom level "data" layer, then the module layer, then
 /// any active mods.  Each layer read is recursively merged into the previous,
 /// adding new resources or keys to already existing resources.
 pub struct YamlResourceSet {
     pub resources: HashMap<YamlResourceKind, HashMap<String, Value>>,
+    cache: Option<ResourceCache>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -43,9 +47,12 @@ pub enum YamlResourceKind {
     WindowImage,
     Spritesheet,
     SoundSet,
+    StringTable,
+    Glossary,
 
     Ability,
     AbilityList,
+    Achievement,
     Actor,
     AiTemplate,
     Area,
@@ -53,6 +60,8 @@ pub enum YamlResourceKind {
     Conversation,
     Cutscene,
     Encounter,
+    EncounterTable,
+    Faction,
     Item,
     ItemAdjective,
     LootList,
@@ -62,9 +71,21 @@ pub enum YamlResourceKind {
     Size,
     Tile,
     Generator,
+    RoomTemplate,
 }
 
 impl YamlResourceKind {
+    fn snake_case_name(self) -> String {
+        let mut name = String::new();
+        for c in format!("{self:?}").chars() {
+            if c.is_uppercase() && !name.is_empty() {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        }
+        name
+    }
+
     fn from_path(top_level: &Path, path: &Path) -> Option<YamlResourceKind> {
         let path_str = match path.strip_prefix(top_level) {
             Err(e) => {
@@ -93,9 +114,12 @@ impl YamlResourceKind {
             "images/window" | "images\\window" => WindowImage,
             "spritesheets" => Spritesheet,
             "sounds" => SoundSet,
+            "strings" => StringTable,
+            "glossary" => Glossary,
 
             "abilities" => Ability,
             "ability_lists" => AbilityList,
+            "achievements" => Achievement,
             "actors" => Actor,
             "ai" => AiTemplate,
             "areas" => Area,
@@ -103,6 +127,8 @@ impl YamlResourceKind {
             "conversations" => Conversation,
             "cutscenes" => Cutscene,
             "encounters" => Encounter,
+            "encounter_tables" => EncounterTable,
+            "factions" => Faction,
             "items" => Item,
             "item_adjectives" => ItemAdjective,
             "loot_lists" => LootList,
@@ -112,7 +138,8 @@ impl YamlResourceKind {
             "sizes" => Size,
             "tiles" => Tile,
             "generators" => Generator,
-            "scripts" | "theme" => Skip,
+            "room_templates" => RoomTemplate,
+            "scripts" | "theme" | "lang" => Skip,
             _ => return None,
         })
     }
@@ -127,14 +154,18 @@ impl YamlResourceSet {
             data_dir.to_string_lossy().to_string()
         );
 
+        let mut cache = ResourceCache::load();
         read_recursive(
-            data_dir,
             data_dir,
             Some(YamlResourceKind::TopLevel),
             &mut resources,
+            &mut cache,
         );
 
-        Ok(YamlResourceSet { resources })
+        Ok(YamlResourceSet {
+            resources,
+            cache: Some(cache),
+        })
     }
 
     pub fn append(&mut self, dir: &Path) {
@@ -143,41 +174,122 @@ impl YamlResourceSet {
             dir.to_string_lossy().to_string()
         );
 
+        let mut cache = self.cache.take().unwrap_or_default();
         read_recursive(
-            dir,
             dir,
             Some(YamlResourceKind::TopLevel),
             &mut self.resources,
+            &mut cache,
         );
+        self.cache = Some(cache);
+    }
+
+    /// Writes the parsed files to the resource cache, for faster loading on
+    /// the next startup.  This should be called once all directories are read
+    pub fn save_cache(&mut self) {
+        if let Some(cache) = self.cache.take() {
+            cache.save();
+        }
+    }
+
+    /// Returns the kind, id, and list of files merged into each resource in
+    /// this set.  The kind is in snake case, i.e. "encounter_table"
+    pub fn resource_files(&self) -> Vec<(String, String, Vec<String>)> {
+        let file_key = Value::String(FILE_VAL_STR.to_string());
+
+        let mut result = Vec::new();
+        for (kind, map) in self.resources.iter() {
+            let kind = kind.snake_case_name();
+            for (id, value) in map.iter() {
+                let mut files = Vec::new();
+                if let Some(Value::Sequence(seq)) = value.get(&file_key) {
+                    for file in seq {
+                        if let Value::String(file) = file {
+                            files.push(file.to_string());
+                        }
+                    }
+                }
+                result.push((kind.clone(), id.to_string(), files));
+            }
+        }
+        result
     }
 }
 
+/// A data file found while walking a resource directory
+struct DataFile {
+    dir: String,
+    path: PathBuf,
+    kind: YamlResourceKind,
+}
+
+/// A successfully parsed data file, along with the hash and encoded value to
+/// insert into the cache if it was not already cached
+struct ParsedFile {
+    id: String,
+    value: Value,
+    cache_entry: Option<(u64, Vec<u8>)>,
+}
+
+/// Reads all of the data files in `dir`.  The directory tree is walked
+/// first, then the files are parsed in parallel, and finally each parsed
+/// file is merged into the resources in the order it was found.  Entries in
+/// each directory are sorted, so the merge order and any resulting warnings
+/// do not depend on the file system.  Files that are unchanged since they
+/// were cached are taken from the `cache` rather than parsed.
 fn read_recursive(
     dir: &Path,
-    top_level: &Path,
     kind: Option<YamlResourceKind>,
     resources: &mut HashMap<YamlResourceKind, HashMap<String, Value>>,
+    cache: &mut ResourceCache,
+) {
+    let mut files = Vec::new();
+    find_files(dir, dir, kind, &mut files);
+
+    let paths = files.iter().map(|file| file.path.as_path()).collect();
+    let parsed = {
+        let cache = &*cache;
+        util::parallel_map(paths, |path| parse_file(path, cache))
+    };
+
+    for (file, parsed) in files.iter().zip(parsed) {
+        let path_str = file.path.to_string_lossy().to_string();
+        match parsed {
+            Ok(Some(parsed)) => {
+                match parsed.cache_entry {
+                    None => cache.mark_used(&path_str),
+                    Some((hash, encoded)) => cache.insert(&path_str, hash, encoded),
+                }
+                let (id, value) = (parsed.id, parsed.value);
+                insert_file(&file.dir, path_str, file.kind, id, value, resources)
+            }
+            Ok(None) => (),
+            Err(error) => {
+                warn!("Error parsing '{}':", path_str);
+                warn!("{}", error.message);
+                record_load_error(*error);
+            }
+        }
+    }
+}
+
+fn find_files(
+    dir: &Path,
+    top_level: &Path,
+    kind: Option<YamlResourceKind>,
+    files: &mut Vec<DataFile>,
 ) {
     let dir_str = dir.to_string_lossy().to_string();
-    let dir_entries = match fs::read_dir(dir) {
-        Ok(entries) => entries,
+    let paths = match vfs::read_dir(dir) {
+        Ok(paths) => paths,
         Err(_) => {
             debug!("Unable to read directory: {}", dir_str);
             return;
         }
     };
 
-    for entry in dir_entries {
-        let entry = match entry {
-            Ok(entry) => entry,
-            Err(e) => {
-                warn!("Error reading file: {}", e);
-                continue;
-            }
-        };
-
-        let path = entry.path();
-        if path.is_dir() {
+    for path in paths {
+        if vfs::is_dir(&path) {
             let next_kind = match kind {
                 Some(YamlResourceKind::TopLevel) | None => {
                     let kind = YamlResourceKind::from_path(top_level, &path);
@@ -189,8 +301,8 @@ fn read_recursive(
                 Some(kind) => Some(kind),
             };
 
-            read_recursive(&path, top_level, next_kind, resources);
-        } else if path.is_file() {
+            find_files(&path, top_level, next_kind, files);
+        } else if vfs::is_file(&path) {
             match kind {
                 None => {
                     warn!(
@@ -198,58 +310,90 @@ fn read_recursive(
                         path
                     );
                 }
-                Some(kind) => {
-                    read_file(&dir_str, &path, kind, resources);
-                }
+                Some(kind) => files.push(DataFile {
+                    dir: dir_str.to_string(),
+                    path,
+                    kind,
+                }),
             }
         }
     }
 }
 
-fn read_file(
-    dir_str: &str,
+/// Parses the file at `path`, returning its top level id and contents, or
+/// `None` if it is not a data file.  This is called from worker threads, so
+/// errors are returned to be logged and recorded rather than recorded here
+fn parse_file(
     path: &Path,
-    kind: YamlResourceKind,
-    resources: &mut HashMap<YamlResourceKind, HashMap<String, Value>>,
-) {
+    cache: &ResourceCache,
+) -> Result<Option<ParsedFile>, Box<LoadError>> {
     let path_str = path.to_string_lossy().to_string();
 
     if !path_str.ends_with("json") && !path_str.ends_with("yml") {
-        return;
+        return Ok(None);
     }
 
     debug!("Reading file as YAML at '{}'", path_str);
-    let data = match fs::read_to_string(path) {
+    let data = match vfs::read_to_string(path) {
         Ok(data) => data,
-        Err(e) => {
-            warn!("Error reading file at '{}': {}", path_str, e);
-            return;
-        }
+        Err(e) => return Err(Box::new(parse_error(path_str, "", e.to_string(), None))),
     };
 
-    let mut value: Value = match serde_yaml::from_str(&data) {
-        Ok(value) => value,
-        Err(e) => {
-            warn!("Error parsing '{}' as YAML:", path_str);
-            warn!("{}", e);
-            return;
-        }
+    let hash = resource_cache::content_hash(&data);
+    let (value, cache_entry) = match cache.get(&path_str, hash) {
+        Some(value) => (value, None),
+        None => match serde_yaml::from_str(&data) {
+            Ok(value) => {
+                let encoded = resource_cache::encode(&value);
+                (value, Some((hash, encoded)))
+            }
+            Err(e) => {
+                let mut message = e.to_string();
+                let mut location = e.location().map(|loc| (loc.line(), loc.column()));
+
+                // the JSON parser gives more useful errors for JSON files
+                if path_str.ends_with("json") {
+                    if let Err(e) = serde_json::from_str::<Value>(&data) {
+                        message = e.to_string();
+                        location = Some((e.line(), e.column())).filter(|(line, _)| *line > 0);
+                    }
+                }
+
+                return Err(Box::new(parse_error(path_str, &data, message, location)));
+            }
+        },
     };
 
     let id = match value.get("id") {
         Some(ref id_value) => match id_value {
             Value::String(ref s) => s.to_string(),
             _ => {
-                warn!("Top level ID is not a string in '{}'", path_str);
-                return;
+                let location = find_key(&data, "id").map(|line| (line, 1));
+                let message = "Top level ID is not a string".to_string();
+                return Err(Box::new(parse_error(path_str, &data, message, location)));
             }
         },
         None => {
-            warn!("Unable to extract top level ID from '{}'", path_str);
-            return;
+            let message = "Unable to extract top level ID".to_string();
+            return Err(Box::new(parse_error(path_str, &data, message, None)));
         }
     };
 
+    Ok(Some(ParsedFile {
+        id,
+        value,
+        cache_entry,
+    }))
+}
+
+fn insert_file(
+    dir_str: &str,
+    path_str: String,
+    kind: YamlResourceKind,
+    id: String,
+    mut value: Value,
+    resources: &mut HashMap<YamlResourceKind, HashMap<String, Value>>,
+) {
     let map = resources.entry(kind).or_insert_with(HashMap::new);
     // use of entry API here seems to require us to clone our value since
     // we want to either append it or insert it
@@ -283,6 +427,136 @@ fn read_file(
     map.insert(id, value);
 }
 
+/// The number of lines on either side of a parse error shown in its snippet
+const SNIPPET_CONTEXT: usize = 2;
+
+fn parse_error(
+    path_str: String,
+    data: &str,
+    message: String,
+    location: Option<(usize, usize)>,
+) -> LoadError {
+    let line = location.map(|(line, _)| line);
+    LoadError {
+        kind: "file".to_string(),
+        id: path_str.to_string(),
+        file: Some(path_str),
+        line,
+        column: location.map(|(_, column)| column),
+        key_path: line.and_then(|line| key_path(data, line)),
+        snippet: line.map(|line| snippet(data, line)),
+        message,
+    }
+}
+
+/// Returns the lines surrounding the 1 based `line`, with line numbers and
+/// the line itself marked
+fn snippet(data: &str, line: usize) -> String {
+    let start = line.saturating_sub(SNIPPET_CONTEXT + 1);
+    let lines: Vec<String> = data
+        .lines()
+        .enumerate()
+        .skip(start)
+        .take(2 * SNIPPET_CONTEXT + 1)
+        .map(|(index, text)| {
+            let marker = if index + 1 == line { '>' } else { ' ' };
+            format!("{}{:>5} | {}", marker, index + 1, text)
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Determines the path of keys leading to the 1 based `line` from the
+/// indentation of the preceding lines, such as `entries[2].weight`.  This works
+/// for YAML and for reasonably formatted JSON, but is only a best guess for
+/// a file that failed to parse
+fn key_path(data: &str, line: usize) -> Option<String> {
+    let lines: Vec<&str> = data.lines().take(line).collect();
+
+    let mut segments = Vec::new();
+    let mut indent = usize::MAX;
+    for (index, text) in lines.iter().enumerate().rev() {
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let cur_indent = text.len() - trimmed.len();
+        if cur_indent >= indent {
+            continue;
+        }
+
+        let is_error_line = indent == usize::MAX;
+        indent = cur_indent;
+        match trimmed.strip_prefix("- ") {
+            Some(entry) => {
+                if is_error_line {
+                    segments.extend(key_name(entry));
+                }
+                segments.push(format!("[{}]", list_index(&lines[..index], cur_indent)));
+            }
+            None => segments.extend(key_name(trimmed)),
+        }
+
+        if indent == 0 {
+            break;
+        }
+    }
+
+    let mut path = String::new();
+    for segment in segments.iter().rev() {
+        if !path.is_empty() && !segment.starts_with('[') {
+            path.push('.');
+        }
+        path.push_str(segment);
+    }
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Counts the list entries at `indent` preceding the current one, stopping
+/// at the start of the list
+fn list_index(lines: &[&str], indent: usize) -> usize {
+    let mut count = 0;
+    for text in lines.iter().rev() {
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let cur_indent = text.len() - trimmed.len();
+        if cur_indent < indent || (cur_indent == indent && !trimmed.starts_with('-')) {
+            break;
+        }
+
+        if cur_indent == indent {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn key_name(text: &str) -> Option<String> {
+    let (key, _) = text.split_once(':')?;
+    let key = key.trim().trim_matches('"').trim_matches('\'');
+    if key.is_empty() || key.contains(['{', '[', ' ']) {
+        None
+    } else {
+        Some(key.to_string())
+    }
+}
+
+/// Returns the 1 based line of the first top level `key` in the file
+fn find_key(data: &str, key: &str) -> Option<usize> {
+    data.lines()
+        .position(|text| key_name(text).as_deref() == Some(key) && !text.starts_with(' '))
+        .map(|index| index + 1)
+}
+
 pub const DIRECTORY_VAL_STR: &str = "__directory__";
 pub const FILE_VAL_STR: &str = "__file__";
 
@@ -67,6 +67,7 @@ pub use self::widget_state::WidgetState;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::config::Config;
 use crate::util::{Point, Size};
 
 pub fn create_ui_tree(kind: Rc<RefCell<dyn WidgetKind>>) -> Rc<RefCell<Widget>> {
@@ -76,6 +77,22 @@ pub fn create_ui_tree(kind: Rc<RefCell<dyn WidgetKind>>) -> Rc<RefCell<Widget>>
     root
 }
 
+/// Resizes the root of the UI tree to match the current UI size, rebuilding
+/// and laying out all widgets if the UI scale has changed.
+pub fn resize_ui_tree(root: &Rc<RefCell<Widget>>) {
+    let (ui_x, ui_y) = Config::ui_size();
+    Cursor::update_max();
+
+    let mut root = root.borrow_mut();
+    if root.state.size() == Size::new(ui_x, ui_y) {
+        return;
+    }
+
+    info!("Resizing UI to {}x{}", ui_x, ui_y);
+    root.state.set_size(Size::new(ui_x, ui_y));
+    root.invalidate_children();
+}
+
 const SCALE_Y_BASE: f32 = 3200.0;
 const SCALE_X_BASE: f32 = SCALE_Y_BASE * 16.0 / 9.0;
 
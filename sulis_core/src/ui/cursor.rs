@@ -35,6 +35,7 @@ pub struct Cursor {
     pub yf: f32,
 
     pub button_down: Option<ClickKind>,
+    pub shift_down: bool,
     pub image: Option<Rc<dyn Image>>,
     pub state: AnimationState,
 }
@@ -48,6 +49,7 @@ thread_local! {
         xf: 0.0,
         yf: 0.0,
         button_down: None,
+        shift_down: false,
         image: None,
         state: AnimationState::base(),
     });
@@ -213,6 +215,19 @@ impl Cursor {
         CURSOR.with(|c| c.borrow().button_down)
     }
 
+    /// Returns true if either shift key is held, for click actions that
+    /// behave differently with the modifier
+    pub fn is_shift_down() -> bool {
+        CURSOR.with(|c| c.borrow().shift_down)
+    }
+
+    pub fn set_shift_down(shift_down: bool) {
+        CURSOR.with(|c| {
+            let mut cursor = c.borrow_mut();
+            cursor.shift_down = shift_down;
+        });
+    }
+
     fn set_button_down(button_down: Option<ClickKind>) {
         CURSOR.with(|c| {
             let mut cursor = c.borrow_mut();
@@ -167,14 +167,33 @@ impl Theme {
     /// stored in the WidgetState.  See `WidgetState#add_text_arg` and
     /// `expand_text_args`
     pub fn apply_text(&self, state: &mut WidgetState) {
-        let out = match self.text {
+        let out = match self.localized_text() {
             None => String::new(),
-            Some(ref text) => expand_text_args(text, state),
+            Some(text) => expand_text_args(&text, state),
         };
 
         state.set_text_content(out);
     }
 
+    /// The text for this theme.  If the current language's string table has
+    /// an entry keyed by this theme's id, that is used in place of the text
+    /// defined in the theme file
+    pub fn localized_text(&self) -> Option<String> {
+        match ResourceSet::string(&self.id) {
+            Some(text) => Some(text),
+            None => self.text.clone(),
+        }
+    }
+
+    /// The custom value for the specified key, using the current language's
+    /// string table entry for `<theme id>.<key>` if there is one
+    pub fn localized_custom(&self, key: &str) -> Option<String> {
+        match ResourceSet::string(&format!("{}.{}", self.id, key)) {
+            Some(text) => Some(text),
+            None => self.custom.get(key).cloned(),
+        }
+    }
+
     /// Sets the background image for the `WidgetState`.  See `apply_foreground`
     pub fn apply_background(&self, state: &mut WidgetState) {
         let out = match self.background {
@@ -311,6 +330,12 @@ fn compute_theme_subname(id: &str) -> &str {
 /// For example, if the text arg `name` is set to `John Doe`, then the String
 /// `Hello, #name# ##1` will be expanded to `Hello, John Doe #1`
 pub fn expand_text_args(text: &str, state: &WidgetState) -> String {
+    expand_args(text, |id| state.get_text_arg(id))
+}
+
+/// Expands all references to args in the given string, as in
+/// `expand_text_args`, looking up each arg with the specified function
+pub fn expand_args<'a, F: Fn(&str) -> Option<&'a str>>(text: &str, get_arg: F) -> String {
     let mut out = String::new();
     let mut cur_arg = String::new();
     let mut arg_accum = false;
@@ -322,7 +347,7 @@ pub fn expand_text_args(text: &str, state: &WidgetState) -> String {
                     // ## code just gives a #
                     out.push(c);
                 } else {
-                    let text_arg = state.get_text_arg(&cur_arg).unwrap_or_default();
+                    let text_arg = get_arg(&cur_arg).unwrap_or_default();
                     out.push_str(text_arg);
                 }
                 arg_accum = false;
@@ -338,7 +363,7 @@ pub fn expand_text_args(text: &str, state: &WidgetState) -> String {
     }
 
     if !cur_arg.is_empty() {
-        let text_arg = match state.get_text_arg(&cur_arg) {
+        let text_arg = match get_arg(&cur_arg) {
             None => {
                 warn!("Non existant text arg '{}' in text '{}'", cur_arg, text);
                 ""
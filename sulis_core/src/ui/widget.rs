@@ -124,11 +124,13 @@ impl Widget {
         self.marked_for_layout = true;
     }
 
-    pub(crate) fn set_mouse_drag_child(&mut self, child: &Rc<RefCell<Widget>>) {
+    /// Sends all mouse events to `child` until it is cleared, such as while
+    /// the child is being dragged.  Called on the root widget
+    pub fn set_mouse_drag_child(&mut self, child: &Rc<RefCell<Widget>>) {
         self.mouse_drag_child = Some(Rc::clone(child));
     }
 
-    pub(crate) fn clear_mouse_drag_child(&mut self, child: &Rc<RefCell<Widget>>) {
+    pub fn clear_mouse_drag_child(&mut self, child: &Rc<RefCell<Widget>>) {
         if let Some(cur) = self.mouse_drag_child.as_ref() {
             if Rc::ptr_eq(cur, child) {
                 self.mouse_drag_child = None;
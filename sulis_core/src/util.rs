@@ -20,14 +20,17 @@ pub use self::point::{Offset, Point, Rect, Scale};
 pub mod size;
 pub use self::size::Size;
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::f32;
 use std::fmt;
 use std::fs;
-use std::io::{Error, ErrorKind};
+use std::io::{Error, ErrorKind, Write};
 use std::ops::*;
 use std::panic;
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
 use std::time::Duration;
 
 use log::LevelFilter;
@@ -38,6 +41,12 @@ use rand_pcg::Pcg64Mcg;
 use crate::config::{self, Config};
 use crate::resource::write_to_file;
 
+thread_local! {
+    static RAND: RefCell<Pcg64Mcg> =
+        RefCell::new(Pcg64Mcg::new(rand::thread_rng().gen::<u64>() as u128));
+    static COMBAT_RAND: RefCell<ReproducibleRandom> = RefCell::new(ReproducibleRandom::new(None));
+}
+
 const MAX_ULPS: i32 = 100;
 const MAX_DIFF: f32 = 2.0 * std::f32::EPSILON;
 
@@ -77,7 +86,7 @@ impl ReproducibleRandom {
         // TODO only seed with u64 for now because serde_yaml doesn't serialize u128 correctly
         let seed = match seed {
             Some(s) => s,
-            None => rand::thread_rng().gen::<u64>() as u128,
+            None => RAND.with(|r| r.borrow_mut().gen::<u64>()) as u128,
         };
 
         ReproducibleRandom {
@@ -97,6 +106,20 @@ impl ReproducibleRandom {
     pub fn seed(&self) -> u128 {
         self.seed
     }
+
+    /// The current state of the generator, which may be restored with
+    /// `from_state`.  Stored as a string as u128 values are not supported
+    /// by serde_yaml
+    pub fn state(&self) -> String {
+        serde_json::to_string(&self.gen).unwrap_or_default()
+    }
+
+    pub fn from_state(seed: u128, state: &str) -> Result<ReproducibleRandom, Error> {
+        match serde_json::from_str(state) {
+            Ok(gen) => Ok(ReproducibleRandom { seed, gen }),
+            Err(e) => invalid_data_error(&format!("Invalid random state: {e}")),
+        }
+    }
 }
 
 impl std::fmt::Debug for ReproducibleRandom {
@@ -106,12 +129,70 @@ impl std::fmt::Debug for ReproducibleRandom {
     }
 }
 
+/// Parses a seed for a `ReproducibleRandom` entered by the player, such as
+/// an area generation seed.  Empty or whitespace only text is no seed.  Seeds
+/// are limited to u64 values, matching those generated by `ReproducibleRandom`
+pub fn parse_seed(text: &str) -> Result<Option<u128>, Error> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    match text.parse::<u64>() {
+        Ok(seed) => Ok(Some(seed as u128)),
+        Err(e) => invalid_data_error(&format!("Invalid seed '{text}': {e}")),
+    }
+}
+
+/// Mixes `value` into a well distributed seed using the splitmix64 function.
+/// Unlike the standard library hashers, the output is fixed, so seeds derived
+/// with it are the same across builds
+pub fn mix_seed(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Hashes `text` with the FNV-1a function, which is stable across builds
+pub fn hash_str(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Returns a new, non-deterministic seed suitable for `reseed_rand`
+pub fn new_rand_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+/// Reseeds the generator used by `gen_rand` and `shuffle`, so that the
+/// sequence of values they produce from this point on is reproducible
+pub fn reseed_rand(seed: u64) {
+    RAND.with(|r| *r.borrow_mut() = Pcg64Mcg::new(seed as u128));
+}
+
 pub fn shuffle<T>(values: &mut [T]) {
-    values.shuffle(&mut rand::thread_rng());
+    RAND.with(|r| values.shuffle(&mut *r.borrow_mut()));
 }
 
 pub fn gen_rand<T: SampleUniform + PartialOrd>(min: T, max: T) -> T {
-    rand::thread_rng().gen_range(min..max)
+    RAND.with(|r| r.borrow_mut().gen_range(min..max))
+}
+
+/// Generates a value for a combat roll, such as an attack or damage roll.
+/// Combat rolls use a separate generator, which is stored in each save
+pub fn combat_roll<T: SampleUniform + PartialOrd>(min: T, max: T) -> T {
+    COMBAT_RAND.with(|r| r.borrow_mut().gen(min, max))
+}
+
+/// Returns a copy of the current combat roll generator
+pub fn combat_rand() -> ReproducibleRandom {
+    COMBAT_RAND.with(|r| r.borrow().clone())
+}
+
+pub fn set_combat_rand(rand: ReproducibleRandom) {
+    COMBAT_RAND.with(|r| *r.borrow_mut() = rand);
 }
 
 fn active_resources_file_path() -> PathBuf {
@@ -352,6 +433,91 @@ pub fn format_elapsed_secs(elapsed: Duration) -> String {
     format!("{secs:.6}")
 }
 
+/// Applies `f` to each of the `items` on a pool of worker threads, one per
+/// available core.  The results are returned in the same order as the items,
+/// so callers may process them deterministically
+pub fn parallel_map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    if threads < 2 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        // the lock is released before the item is processed
+                        let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                        match next {
+                            None => break,
+                            Some((index, item)) => results.push((index, f(item))),
+                        }
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Copies `text` to the system clipboard by piping it to the platform's
+/// clipboard utility.  Returns an error if no utility could be run.
+pub fn copy_to_clipboard(text: &str) -> Result<(), Error> {
+    let commands: &[(&str, &[&str])] = if cfg!(target_os = "windows") {
+        &[("clip", &[])]
+    } else if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+
+    for (command, args) in commands {
+        let mut child = match Command::new(command)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Err(_) => continue,
+            Ok(child) => child,
+        };
+
+        // the utility reads until stdin is closed, so drop it before waiting
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    Err(Error::new(ErrorKind::NotFound, "No clipboard utility is available"))
+}
+
 pub fn error_and_exit(error: &str) {
     error!("{}", error);
     error!("Exiting...");
@@ -101,8 +101,8 @@ impl WidgetKind for Button {
         let theme = &widget.theme;
         self.repeat_time = theme.get_custom_or_default("repeat_time", 0);
         self.repeat_init_time = theme.get_custom_or_default("repeat_init_time", 0);
-        if let Some(tooltip) = theme.custom.get("tooltip") {
-            self.tooltip = theme::expand_text_args(tooltip, &widget.state);
+        if let Some(tooltip) = theme.localized_custom("tooltip") {
+            self.tooltip = theme::expand_text_args(&tooltip, &widget.state);
         }
     }
 
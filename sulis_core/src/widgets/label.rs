@@ -129,8 +129,8 @@ impl WidgetKind for Label {
             widget.state.text_renderer = Some(Box::new(LineRenderer::new(font)));
         }
 
-        if let Some(tooltip) = widget.theme.custom.get("tooltip") {
-            self.tooltip = theme::expand_text_args(tooltip, &widget.state);
+        if let Some(tooltip) = widget.theme.localized_custom("tooltip") {
+            self.tooltip = theme::expand_text_args(&tooltip, &widget.state);
         }
     }
 
@@ -82,6 +82,11 @@
 //! Note that drawing an image does not advance the writing cursor.  You will probably
 //! want to scale your image with `s`
 //! * **f** - Writes using another defined font.
+//! * **b** - Writes the text in bold.  This param takes no value, i.e. `[b|Bold]`
+//! * **k** - Marks the text as a glossary keyword, i.e. `[k=armor|Armor]`.  The
+//!   keyword id must be defined in a glossary resource.  In a `TextArea`, hovering
+//!   over or clicking the text shows the glossary entry.  Keywords are highlighted,
+//!   which may be overridden by placing a `c` param after the `k` param.
 //! * **?** - Checks for the existance of a text argument.  If the argument is not
 //! present, this tag is ignored when producing the output.
 //! * **!** - Checks for the existance of a text argument.  If the argument is present,
@@ -120,8 +125,26 @@ pub struct MarkupRenderer {
     draw_lists: Vec<DrawList>,
     bottom_y: f32,
     right_x: f32,
+    keywords: Vec<(Rect, String)>,
 }
 
+/// Escapes all characters in `text` that would otherwise be parsed as markup,
+/// so that arbitrary text such as file contents is displayed as is
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// The horizontal offset of the second pass used to draw bold text, as a
+/// fraction of the text scale
+const BOLD_OFFSET: f32 = 0.06;
+
 /// Struct for rendering text that is marked up with the simple
 /// Markup language described in the `markup_tag` module documentation
 impl MarkupRenderer {
@@ -133,9 +156,16 @@ impl MarkupRenderer {
             draw_lists,
             bottom_y: 0.0,
             right_x: 0.0,
+            keywords: Vec::new(),
         }
     }
 
+    /// The areas covered by glossary keyword text, along with the id of each
+    /// keyword.  Only valid once `render_to_cache` has been called
+    pub fn keywords(&self) -> &[(Rect, String)] {
+        &self.keywords
+    }
+
     pub fn text_bottom(&self) -> i32 {
         self.bottom_y as i32
     }
@@ -341,6 +371,7 @@ impl MarkupRenderer {
             x = start_x;
             y += markup.scale * factor;
         }
+        let (word_x, word_y) = (x, y);
 
         let mut quads = Vec::with_capacity(word_buf.len());
         for c in word_buf.chars() {
@@ -363,6 +394,28 @@ impl MarkupRenderer {
                 }
             }
         }
+        if let Some(ref keyword) = markup.keyword {
+            if y == word_y && x > word_x {
+                // the rect spans one line advance, ending where the next line starts
+                let rect = Rect {
+                    x: word_x,
+                    y: word_y - (markup.scale - 1.0) * factor,
+                    w: x - word_x,
+                    h: markup.scale * factor,
+                };
+                self.keywords.push((rect, keyword.to_string()));
+            }
+        }
+
+        if markup.bold {
+            let offset = markup.scale * BOLD_OFFSET;
+            let mut bold_quads = quads.clone();
+            for vertex in bold_quads.iter_mut() {
+                vertex.position[0] += offset;
+            }
+            quads.append(&mut bold_quads);
+        }
+
         let mut draw_list = DrawList::from_font(&markup.font.id, quads);
         draw_list.set_color(markup.color);
         self.append_to_draw_lists(draw_list);
@@ -34,8 +34,13 @@ enum MarkupKind {
     Font,
     If,
     IfNot,
+    Bold,
+    Keyword,
 }
 
+/// The default color for glossary keywords, so they stand out as clickable
+const KEYWORD_COLOR: &str = "8FD0FF";
+
 pub struct Markup {
     pub color: Color,
     pub scale: f32,
@@ -46,6 +51,8 @@ pub struct Markup {
     pub right: Option<f32>,
     pub font: Rc<Font>,
     pub ignore: bool,
+    pub bold: bool,
+    pub keyword: Option<String>,
 }
 
 impl Markup {
@@ -60,6 +67,8 @@ impl Markup {
             right: None,
             font: Rc::clone(font),
             ignore: false,
+            bold: false,
+            keyword: None,
         }
     }
 
@@ -74,6 +83,8 @@ impl Markup {
             right: None,
             font: Rc::clone(&other.font),
             ignore: other.ignore,
+            bold: other.bold,
+            keyword: other.keyword.clone(),
         }
     }
 
@@ -96,6 +107,8 @@ impl Markup {
                         'f' => Some(Font),
                         '?' => Some(If),
                         '!' => Some(IfNot),
+                        'b' => Some(Bold),
+                        'k' => Some(Keyword),
                         _ => None,
                     }
                 }
@@ -150,6 +163,14 @@ impl Markup {
             },
             If => self.ignore = self.ignore || !widget_state.has_text_arg(buf),
             IfNot => self.ignore = self.ignore || widget_state.has_text_arg(buf),
+            Bold => self.bold = true,
+            Keyword => {
+                if ResourceSet::glossary_entry(buf).is_none() {
+                    warn!("Glossary entry not found '{}'", buf);
+                }
+                self.color = ui::Color::from_string(KEYWORD_COLOR);
+                self.keyword = Some(buf.to_string());
+            }
         }
     }
 }
@@ -126,8 +126,8 @@ impl WidgetKind for ProgressBar {
             self.bar = ResourceSet::image(image_id);
         }
 
-        if let Some(tooltip) = widget.theme.custom.get("tooltip") {
-            self.tooltip = theme::expand_text_args(tooltip, &widget.state);
+        if let Some(tooltip) = widget.theme.localized_custom("tooltip") {
+            self.tooltip = theme::expand_text_args(&tooltip, &widget.state);
         }
     }
 
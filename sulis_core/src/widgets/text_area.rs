@@ -20,15 +20,18 @@ use std::rc::Rc;
 
 use crate::config::Config;
 use crate::io::{event::ClickKind, GraphicsRenderer};
+use crate::resource::ResourceSet;
 use crate::ui::theme::SizeRelative;
-use crate::ui::{Widget, WidgetKind};
-use crate::util::{Offset, Point, Size};
+use crate::ui::{Cursor, Widget, WidgetKind};
+use crate::util::{Offset, Point, Rect, Size};
 use crate::widget_kind;
 use crate::widgets::MarkupRenderer;
 
 pub struct TextArea {
     pub text: Option<String>,
     pub(crate) limit_to_screen_edge: bool,
+    keywords: Vec<(Rect, String)>,
+    cur_keyword: Option<String>,
 }
 
 impl TextArea {
@@ -36,6 +39,8 @@ impl TextArea {
         Rc::new(RefCell::new(TextArea {
             text: None,
             limit_to_screen_edge: true,
+            keywords: Vec::new(),
+            cur_keyword: None,
         }))
     }
 
@@ -43,16 +48,62 @@ impl TextArea {
         Rc::new(RefCell::new(TextArea {
             text: Some(text.to_string()),
             limit_to_screen_edge: true,
+            keywords: Vec::new(),
+            cur_keyword: None,
         }))
     }
 
-    fn render_to_cache(&self, widget: &mut Widget) {
+    fn render_to_cache(&mut self, widget: &mut Widget) {
         if let Some(ref font) = widget.state.font {
             let mut renderer = MarkupRenderer::new(font, widget.state.inner_width());
             renderer.render_to_cache(&widget.state);
+            self.keywords = renderer.keywords().to_vec();
             widget.state.text_renderer = Some(Box::new(renderer));
         }
     }
+
+    fn keyword_under_cursor(&self) -> Option<&str> {
+        let (x, y) = Cursor::get_position_f32();
+        self.keywords
+            .iter()
+            .find(|(r, _)| x >= r.x && x < r.x + r.w && y >= r.y && y < r.y + r.h)
+            .map(|(_, id)| id.as_str())
+    }
+
+    /// Shows the glossary entry for the keyword under the cursor, if there is
+    /// one.  If `force` is false, an already shown entry is left in place
+    fn show_keyword(&mut self, widget: &Rc<RefCell<Widget>>, force: bool) -> bool {
+        let id = match self.keyword_under_cursor() {
+            None => {
+                if self.cur_keyword.take().is_some() {
+                    Widget::remove_mouse_over(&Widget::get_root(widget));
+                }
+                return false;
+            }
+            Some(id) => id.to_string(),
+        };
+
+        if !force && self.cur_keyword.as_ref() == Some(&id) {
+            return true;
+        }
+
+        let entry = match ResourceSet::glossary_entry(&id) {
+            None => return false,
+            Some(entry) => entry,
+        };
+
+        let tooltip = Widget::with_theme(TextArea::empty(), "glossary_tooltip");
+        {
+            let state = &mut tooltip.borrow_mut().state;
+            state.add_text_arg("name", &entry.name);
+            state.add_text_arg("text", &entry.text);
+        }
+
+        let (x, y) = Cursor::get_position_f32();
+        Widget::set_mouse_over_widget(widget, tooltip, x as i32 + 1, y as i32 + 1);
+        self.cur_keyword = Some(id);
+        true
+    }
 }
 
 impl WidgetKind for TextArea {
@@ -65,7 +116,7 @@ impl WidgetKind for TextArea {
 
     fn on_mouse_release(&mut self, widget: &Rc<RefCell<Widget>>, kind: ClickKind) -> bool {
         self.super_on_mouse_release(widget, kind);
-        false
+        self.show_keyword(widget, true)
     }
 
     fn on_mouse_drag(
@@ -80,10 +131,13 @@ impl WidgetKind for TextArea {
 
     fn on_mouse_move(
         &mut self,
-        _widget: &Rc<RefCell<Widget>>,
+        widget: &Rc<RefCell<Widget>>,
         _delta_x: f32,
         _delta_y: f32,
     ) -> bool {
+        if !self.keywords.is_empty() {
+            self.show_keyword(widget, false);
+        }
         true
     }
 
@@ -94,6 +148,9 @@ impl WidgetKind for TextArea {
 
     fn on_mouse_exit(&mut self, widget: &Rc<RefCell<Widget>>) -> bool {
         self.super_on_mouse_exit(widget);
+        if self.cur_keyword.take().is_some() {
+            Widget::remove_mouse_over(&Widget::get_root(widget));
+        }
         false
     }
 
@@ -111,6 +168,7 @@ impl WidgetKind for TextArea {
             renderer.render_to_cache(&widget.state);
             bottom = renderer.text_bottom();
             right = renderer.text_right();
+            self.keywords = renderer.keywords().to_vec();
             widget.state.text_renderer = Some(Box::new(renderer));
         }
 
@@ -102,7 +102,10 @@ impl ActorCreatorWindow {
             attributes: AttributeList::new(Module::rules().base_attribute as u8),
             conversation: None,
             faction: Some(self.selected_faction),
+            faction_id: None,
             images,
+            sprite_images: HashMap::new(),
+            facing_sprite_images: HashMap::new(),
             hue: Some(self.selected_hue),
             hair_color: None,
             skin_color: None,
@@ -112,6 +115,7 @@ impl ActorCreatorWindow {
             reward: None,
             abilities: Vec::new(),
             ai: None,
+            ai_script: None,
         };
 
         match write_to_file(&filename, &actor) {
@@ -32,7 +32,7 @@ pub struct AreaModel {
     pub config: EditorConfig,
 
     tiles: TilesModel,
-    actors: Vec<(Point, Rc<Actor>, Option<String>)>,
+    actors: Vec<(Point, Rc<Actor>, Option<String>, Option<Patrol>)>,
     props: Vec<PropData>,
     encounters: Vec<EncounterData>,
     transitions: Vec<Transition>,
@@ -51,8 +51,13 @@ pub struct AreaModel {
     pub on_rest: OnRest,
 
     ambient_sound: Option<String>,
+    ambient_light: Option<f32>,
     default_music: Option<String>,
     default_combat_music: Option<String>,
+
+    /// The additional levels of the area, which are not edited here but are
+    /// preserved when the area is saved
+    levels: Vec<AreaLevelBuilder>,
 }
 
 impl Default for AreaModel {
@@ -98,8 +103,10 @@ impl Default for AreaModel {
             max_vis_distance: 20,
             max_vis_up_one_distance: 6,
             ambient_sound: None,
+            ambient_light: None,
             default_music: None,
             default_combat_music: None,
+            levels: Vec::new(),
             location_kind: LocationKind::Outdoors,
             on_rest: OnRest::Disabled {
                 message: "<PLACEHOLDER>".to_string(),
@@ -164,6 +171,7 @@ impl AreaModel {
             location: Point::new(x, y),
             size: Size::new(w, h),
             triggers: Vec::new(),
+            flee_region: None,
         });
     }
 
@@ -172,11 +180,11 @@ impl AreaModel {
             return;
         }
 
-        self.actors.push((Point::new(x, y), actor, None));
+        self.actors.push((Point::new(x, y), actor, None, None));
     }
 
     pub fn remove_actors_within(&mut self, x: i32, y: i32, width: i32, height: i32) {
-        self.actors.retain(|&(pos, ref actor, _)| {
+        self.actors.retain(|&(pos, ref actor, _, _)| {
             !is_removal(
                 pos,
                 actor.race.size.width,
@@ -197,7 +205,7 @@ impl AreaModel {
         height: i32,
     ) -> Vec<(Point, Rc<Actor>)> {
         let mut actors = Vec::new();
-        for &(pos, ref actor, _) in self.actors.iter() {
+        for &(pos, ref actor, _, _) in self.actors.iter() {
             if !is_removal(
                 pos,
                 actor.race.size.width,
@@ -227,6 +235,8 @@ impl AreaModel {
             location: Point::new(x, y),
             items: Vec::new(),
             hover_text: None,
+            lock: None,
+            detect_difficulty: None,
         };
         self.props.push(prop_data);
     }
@@ -399,7 +409,7 @@ impl AreaModel {
             renderer.draw(draw_list);
         }
 
-        for &(pos, ref actor, _) in self.actors.iter() {
+        for &(pos, ref actor, _, _) in self.actors.iter() {
             let w = actor.race.size.width as f32 / 2.0;
             let h = actor.race.size.height as f32 / 2.0;
             actor.draw(
@@ -443,6 +453,7 @@ impl AreaModel {
                 ToKind::Area { ref id, .. } => format!("to {id}"),
                 ToKind::WorldMap => "to World Map".to_string(),
                 ToKind::FindLink { ref id, .. } => format!("to {id}"),
+                ToKind::Stairs { ref id } => format!("stairs to {id}"),
             };
 
             let (mut draw_list, _) = font_renderer.get_draw_list(&text, offset, 1.0);
@@ -519,8 +530,10 @@ impl AreaModel {
         self.on_rest = area_builder.on_rest.clone();
         self.location_kind = area_builder.location_kind;
         self.ambient_sound = area_builder.ambient_sound;
+        self.ambient_light = area_builder.ambient_light;
         self.default_music = area_builder.default_music;
         self.default_combat_music = area_builder.default_combat_music;
+        self.levels = area_builder.levels;
 
         let width = area_builder.width as i32;
 
@@ -578,6 +591,8 @@ impl AreaModel {
                 location: prop_builder.location,
                 items: prop_builder.items,
                 hover_text: prop_builder.hover_text,
+                lock: prop_builder.lock,
+                detect_difficulty: prop_builder.detect_difficulty,
             };
 
             self.props.push(prop_data);
@@ -659,6 +674,7 @@ impl AreaModel {
                 location: enc_builder.location,
                 size: enc_builder.size,
                 triggers: Vec::new(),
+                flee_region: enc_builder.flee_region,
             };
             self.encounters.push(enc_data);
         }
@@ -676,8 +692,12 @@ impl AreaModel {
                 Some(actor) => actor,
             };
 
-            self.actors
-                .push((actor_data.location, actor, actor_data.unique_id));
+            self.actors.push((
+                actor_data.location,
+                actor,
+                actor_data.unique_id,
+                actor_data.patrol,
+            ));
         }
     }
 
@@ -770,11 +790,12 @@ impl AreaModel {
 
         trace!("Saving actors.");
         let mut actors: Vec<ActorData> = Vec::new();
-        for &(pos, ref actor, ref unique_id) in self.actors.iter() {
+        for &(pos, ref actor, ref unique_id, ref patrol) in self.actors.iter() {
             actors.push(ActorData {
                 id: actor.id.to_string(),
                 unique_id: unique_id.clone(),
                 location: pos,
+                patrol: patrol.clone(),
             });
         }
 
@@ -787,6 +808,8 @@ impl AreaModel {
                 location: prop_data.location,
                 items: prop_data.items.clone(),
                 hover_text: prop_data.hover_text.clone(),
+                lock: prop_data.lock.clone(),
+                detect_difficulty: prop_data.detect_difficulty,
             };
             props.push(builder);
         }
@@ -798,6 +821,7 @@ impl AreaModel {
                 id: enc_data.encounter.id.to_string(),
                 location: enc_data.location,
                 size: enc_data.size,
+                flee_region: enc_data.flee_region,
             };
             encounters.push(builder);
         }
@@ -857,9 +881,11 @@ impl AreaModel {
             max_vis_up_one_distance: self.max_vis_up_one_distance,
             world_map_location: self.world_map_location.clone(),
             ambient_sound: self.ambient_sound.clone(),
+            ambient_light: self.ambient_light,
             default_music: self.default_music.clone(),
             default_combat_music: self.default_combat_music.clone(),
             on_rest: self.on_rest.clone(),
+            levels: self.levels.clone(),
         };
 
         trace!("Writing to file {}", filename);
@@ -181,14 +181,17 @@ impl WidgetKind for SaveWindow {
         {
             let disabled = Widget::with_theme(Button::empty(), "disabled");
             let fire_script = Widget::with_theme(Button::empty(), "fire_script");
+            let camp = Widget::with_theme(Button::empty(), "camp");
 
             match self.area_editor.borrow().model.on_rest {
                 OnRest::Disabled { .. } => disabled.borrow_mut().state.set_active(true),
                 OnRest::FireScript { .. } => fire_script.borrow_mut().state.set_active(true),
+                OnRest::Camp { .. } => camp.borrow_mut().state.set_active(true),
             }
 
             let area_editor_ref = Rc::clone(&self.area_editor);
             let fire_script_ref = Rc::clone(&fire_script);
+            let camp_ref = Rc::clone(&camp);
             disabled
                 .borrow_mut()
                 .state
@@ -198,10 +201,12 @@ impl WidgetKind for SaveWindow {
                     };
                     widget.borrow_mut().state.set_active(true);
                     fire_script_ref.borrow_mut().state.set_active(false);
+                    camp_ref.borrow_mut().state.set_active(false);
                 })));
 
             let area_editor_ref = Rc::clone(&self.area_editor);
             let disabled_ref = Rc::clone(&disabled);
+            let camp_ref = Rc::clone(&camp);
             fire_script
                 .borrow_mut()
                 .state
@@ -212,10 +217,27 @@ impl WidgetKind for SaveWindow {
                     };
                     widget.borrow_mut().state.set_active(true);
                     disabled_ref.borrow_mut().state.set_active(false);
+                    camp_ref.borrow_mut().state.set_active(false);
+                })));
+
+            let area_editor_ref = Rc::clone(&self.area_editor);
+            let disabled_ref = Rc::clone(&disabled);
+            let fire_script_ref = Rc::clone(&fire_script);
+            camp.borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    area_editor_ref.borrow_mut().model.on_rest = OnRest::Camp {
+                        interrupt_chance: 0,
+                        interrupt_encounters: Vec::new(),
+                    };
+                    widget.borrow_mut().state.set_active(true);
+                    disabled_ref.borrow_mut().state.set_active(false);
+                    fire_script_ref.borrow_mut().state.set_active(false);
                 })));
 
             Widget::add_child_to(&on_rest_box, disabled);
             Widget::add_child_to(&on_rest_box, fire_script);
+            Widget::add_child_to(&on_rest_box, camp);
         }
         Widget::add_child_to(&content, on_rest_box);
 
@@ -97,6 +97,7 @@ impl WidgetKind for TransitionWindow {
                     x_offset,
                     y_offset,
                 } => (id.to_string(), x_offset, y_offset),
+                ToKind::Stairs { ref id } => (id.to_string(), 0, 0),
             };
 
             let max = MAX_AREA_SIZE - 1;
@@ -172,12 +173,14 @@ impl WidgetKind for TransitionWindow {
             let area_button = Widget::with_theme(Button::empty(), "area_button");
             let find_link_button = Widget::with_theme(Button::empty(), "find_link_button");
             let world_map_button = Widget::with_theme(Button::empty(), "world_map_button");
+            let stairs_button = Widget::with_theme(Button::empty(), "stairs_button");
 
             match transition.to {
                 ToKind::CurArea { .. } => cur_area_button.borrow_mut().state.set_active(true),
                 ToKind::Area { .. } => area_button.borrow_mut().state.set_active(true),
                 ToKind::WorldMap => world_map_button.borrow_mut().state.set_active(true),
                 ToKind::FindLink { .. } => find_link_button.borrow_mut().state.set_active(true),
+                ToKind::Stairs { .. } => stairs_button.borrow_mut().state.set_active(true),
             }
 
             let refs = vec![
@@ -185,6 +188,7 @@ impl WidgetKind for TransitionWindow {
                 Rc::clone(&area_button),
                 Rc::clone(&world_map_button),
                 Rc::clone(&find_link_button),
+                Rc::clone(&stairs_button),
             ];
             let refs_clone = refs.clone();
             for widget in refs {
@@ -205,6 +209,7 @@ impl WidgetKind for TransitionWindow {
             let area_ref = Rc::clone(&area_button);
             let cur_area_ref = Rc::clone(&cur_area_button);
             let find_link_ref = Rc::clone(&find_link_button);
+            let stairs_ref = Rc::clone(&stairs_button);
             apply
                 .borrow_mut()
                 .state
@@ -243,6 +248,8 @@ impl WidgetKind for TransitionWindow {
                             x_offset: to.x,
                             y_offset: to.y,
                         };
+                    } else if stairs_ref.borrow().state.is_active() {
+                        transition.to = ToKind::Stairs { id: to_area_str };
                     }
 
                     for child in sizes_ref.borrow().children.iter() {
@@ -269,6 +276,7 @@ impl WidgetKind for TransitionWindow {
                 area_button,
                 world_map_button,
                 find_link_button,
+                stairs_button,
             ]);
             widgets.append(&mut vec![
                 to_area,
@@ -307,6 +315,7 @@ impl WidgetKind for TransitionWindow {
                 ToKind::Area { ref id, .. } => id.to_string(),
                 ToKind::WorldMap => "World Map".to_string(),
                 ToKind::FindLink { .. } => "Generated".to_string(),
+                ToKind::Stairs { ref id } => id.to_string(),
             };
 
             let text = format!("{index}: {to}");
@@ -60,6 +60,7 @@ pub struct Active {
     pub duration: Duration,
     pub group: AbilityGroup,
     pub cooldown: u32,
+    pub cooldown_group: Option<String>,
     pub short_description: String,
     pub ai: AIData,
     pub range: Range,
@@ -140,6 +141,7 @@ impl Ability {
                     ap: active.ap,
                     duration: active.duration,
                     cooldown,
+                    cooldown_group: active.cooldown_group,
                     group,
                     short_description: active.short_description,
                     ai: active.ai,
@@ -233,6 +235,10 @@ pub struct ActiveBuilder {
     duration: Duration,
     group: String,
     cooldown: Option<u32>,
+
+    #[serde(default)]
+    cooldown_group: Option<String>,
+
     short_description: String,
 
     #[serde(default = "range_none")]
@@ -0,0 +1,100 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::io::Error;
+
+use sulis_core::util::unable_to_create_error;
+
+use crate::Module;
+
+/// The condition that must be met for an achievement to be unlocked.  Each
+/// game event matching the kind counts toward the achievement's `count`,
+/// except for `Coins`, which is unlocked once the party holds `amount`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum AchievementKind {
+    /// Kill hostile entities.  If `actor` is specified, only that actor counts
+    Kills { actor: Option<String> },
+
+    /// Complete the specified quest
+    QuestComplete { quest: String },
+
+    /// Have at least `amount` coins held by the party at once
+    Coins { amount: i32 },
+}
+
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub kind: AchievementKind,
+    pub count: u32,
+}
+
+impl Achievement {
+    pub fn new(builder: AchievementBuilder, module: &Module) -> Result<Achievement, Error> {
+        match builder.kind {
+            AchievementKind::Kills {
+                actor: Some(ref actor),
+            } if !module.actors.contains_key(actor) => {
+                warn!("no actor '{}' found", actor);
+                return unable_to_create_error("achievement", &builder.id);
+            }
+            AchievementKind::QuestComplete { ref quest } if !module.quests.contains_key(quest) => {
+                warn!("no quest '{}' found", quest);
+                return unable_to_create_error("achievement", &builder.id);
+            }
+            _ => (),
+        }
+
+        if builder.count == 0 {
+            warn!("Achievement count must be at least 1");
+            return unable_to_create_error("achievement", &builder.id);
+        }
+
+        Ok(Achievement {
+            id: builder.id,
+            name: builder.name,
+            description: builder.description,
+            kind: builder.kind,
+            count: builder.count,
+        })
+    }
+
+    /// The progress value at which this achievement is unlocked
+    pub fn target(&self) -> u32 {
+        match self.kind {
+            AchievementKind::Coins { amount } => amount.max(0) as u32,
+            _ => self.count,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AchievementBuilder {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub kind: AchievementKind,
+
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
@@ -127,11 +127,80 @@ impl fmt::Display for Sex {
     }
 }
 
+/// The animation states an entity sprite can be drawn in.  Races and actors may
+/// specify an image for each state, which replaces the static layered sprite
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub enum SpriteState {
+    Idle,
+    Walk,
+    Attack,
+    Death,
+}
+
+/// The direction an entity is facing.  Updated as the entity moves and attacks
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(deny_unknown_fields)]
+pub enum Facing {
+    North,
+    East,
+    #[default]
+    South,
+    West,
+}
+
+impl Facing {
+    pub fn option_from_str(val: &str) -> Option<Facing> {
+        match val {
+            "North" => Some(Facing::North),
+            "East" => Some(Facing::East),
+            "South" => Some(Facing::South),
+            "West" => Some(Facing::West),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(self) -> String {
+        format!("{self:?}")
+    }
+
+    /// Returns the facing most closely matching the direction `(dx, dy)`, or
+    /// `None` if the direction is zero
+    pub fn from_direction(dx: f32, dy: f32) -> Option<Facing> {
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+
+        if dx.abs() >= dy.abs() {
+            if dx > 0.0 {
+                Some(Facing::East)
+            } else {
+                Some(Facing::West)
+            }
+        } else if dy > 0.0 {
+            Some(Facing::South)
+        } else {
+            Some(Facing::North)
+        }
+    }
+
+    /// Returns the unit vector for this facing, in area coordinates
+    pub fn direction(self) -> (f32, f32) {
+        match self {
+            Facing::North => (0.0, -1.0),
+            Facing::East => (1.0, 0.0),
+            Facing::South => (0.0, 1.0),
+            Facing::West => (-1.0, 0.0),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Actor {
     pub id: String,
     pub name: String,
     faction: Faction,
+    pub faction_id: Option<String>,
     pub conversation: Option<Rc<Conversation>>,
     pub portrait: Option<Rc<dyn Image>>,
     pub race: Rc<Race>,
@@ -150,10 +219,19 @@ pub struct Actor {
     // stored directly from the builder, solely for convenience
     pub builder_images: HashMap<ImageLayer, String>,
 
+    sprite_images: HashMap<SpriteState, Rc<dyn Image>>,
+    pub builder_sprite_images: HashMap<SpriteState, String>,
+
+    facing_sprite_images: HashMap<Facing, HashMap<SpriteState, Rc<dyn Image>>>,
+    pub builder_facing_sprite_images: HashMap<Facing, HashMap<SpriteState, String>>,
+
     pub reward: Option<Reward>,
     pub abilities: Vec<OwnedAbility>,
 
     pub ai: Option<Rc<AITemplate>>,
+
+    /// A script to run this actor's AI, in place of the AI template's script
+    pub ai_script: Option<String>,
 }
 
 impl PartialEq for Actor {
@@ -216,6 +294,7 @@ impl Actor {
             id: other.id.to_string(),
             name: other.name.to_string(),
             faction: other.faction,
+            faction_id: other.faction_id.clone(),
             conversation: other.conversation.clone(),
             portrait: other.portrait.clone(),
             race: Rc::clone(&other.race),
@@ -231,9 +310,14 @@ impl Actor {
             image_layers,
             image,
             builder_images: other.builder_images.clone(),
+            sprite_images: other.sprite_images.clone(),
+            builder_sprite_images: other.builder_sprite_images.clone(),
+            facing_sprite_images: other.facing_sprite_images.clone(),
+            builder_facing_sprite_images: other.builder_facing_sprite_images.clone(),
             reward: other.reward.clone(),
             abilities,
             ai: other.ai.clone(),
+            ai_script: other.ai_script.clone(),
         }
     }
 
@@ -303,6 +387,38 @@ impl Actor {
         let images_list = image_layers.get_list(sex, builder.hair_color, builder.skin_color);
         let image = LayeredImage::new(images_list, builder.hue);
 
+        let mut sprite_images = race.sprite_images.clone();
+        for (state, id) in builder.sprite_images.iter() {
+            match ResourceSet::image(id) {
+                None => {
+                    warn!("Unable to find image '{}' for sprite state {:?}", id, state);
+                    return unable_to_create_error("actor", &builder.id);
+                }
+                Some(image) => {
+                    sprite_images.insert(*state, image);
+                }
+            }
+        }
+
+        let mut facing_sprite_images = race.facing_sprite_images.clone();
+        for (facing, images) in builder.facing_sprite_images.iter() {
+            let entry = facing_sprite_images.entry(*facing).or_default();
+            for (state, id) in images.iter() {
+                match ResourceSet::image(id) {
+                    None => {
+                        warn!(
+                            "Unable to find image '{}' for sprite {:?} {:?}",
+                            id, facing, state
+                        );
+                        return unable_to_create_error("actor", &builder.id);
+                    }
+                    Some(image) => {
+                        entry.insert(*state, image);
+                    }
+                }
+            }
+        }
+
         let reward = match builder.reward {
             None => None,
             Some(reward) => {
@@ -361,11 +477,19 @@ impl Actor {
             },
         };
 
+        if let Some(ref script) = builder.ai_script {
+            if !resources.scripts.contains_key(script) {
+                warn!("No AI script found with id '{}'", script);
+                return unable_to_create_error("actor", &builder.id);
+            }
+        }
+
         Ok(Actor {
             id: builder.id,
             name: builder.name,
             conversation,
             faction: builder.faction.unwrap_or(Faction::Hostile),
+            faction_id: builder.faction_id,
             portrait,
             race,
             sex,
@@ -378,11 +502,16 @@ impl Actor {
             image_layers,
             image,
             builder_images: builder.images,
+            sprite_images,
+            builder_sprite_images: builder.sprite_images,
+            facing_sprite_images,
+            builder_facing_sprite_images: builder.facing_sprite_images,
             hue: builder.hue,
             skin_color: builder.skin_color,
             hair_color: builder.hair_color,
             abilities,
             ai,
+            ai_script: builder.ai_script,
         })
     }
 
@@ -390,6 +519,28 @@ impl Actor {
         self.faction
     }
 
+    /// Returns the ID of the script that runs this actor's AI, either specified
+    /// directly by the actor or by its AI template, if any
+    pub fn ai_script_id(&self) -> Option<&str> {
+        match self.ai_script {
+            Some(ref script) => Some(script),
+            None => self.ai.as_ref().map(|ai| ai.script.as_str()),
+        }
+    }
+
+    /// Returns the image to draw for this actor in the specified sprite `state`
+    /// and `facing`.  Images specific to the facing are preferred, and the idle
+    /// image is used for states without an image.  Returns `None` if the actor
+    /// should be drawn using its static layered sprite.
+    pub fn sprite_image(&self, state: SpriteState, facing: Facing) -> Option<&Rc<dyn Image>> {
+        let directional = self.facing_sprite_images.get(&facing);
+        directional
+            .and_then(|images| images.get(&state))
+            .or_else(|| self.sprite_images.get(&state))
+            .or_else(|| directional.and_then(|images| images.get(&SpriteState::Idle)))
+            .or_else(|| self.sprite_images.get(&SpriteState::Idle))
+    }
+
     pub fn levels(&self, other_class: &Rc<Class>) -> u32 {
         for &(ref class, level) in self.levels.iter() {
             if class == other_class {
@@ -475,9 +626,18 @@ pub struct ActorBuilder {
     pub conversation: Option<String>,
     pub faction: Option<Faction>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faction_id: Option<String>,
+
     #[serde(default)]
     pub images: HashMap<ImageLayer, String>,
 
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sprite_images: HashMap<SpriteState, String>,
+
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub facing_sprite_images: HashMap<Facing, HashMap<SpriteState, String>>,
+
     pub hue: Option<f32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -496,4 +656,7 @@ pub struct ActorBuilder {
     pub reward: Option<RewardBuilder>,
     pub abilities: Vec<String>,
     pub ai: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_script: Option<String>,
 }
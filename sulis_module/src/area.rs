@@ -21,7 +21,7 @@ mod layer_set;
 pub use self::layer_set::LayerSet;
 
 mod path_finder;
-pub use self::path_finder::{Destination, LocationChecker, PathFinder};
+pub use self::path_finder::{ClusterGraph, Destination, LocationChecker, PathFinder};
 
 mod path_finder_grid;
 pub use self::path_finder_grid::PathFinderGrid;
@@ -41,6 +41,7 @@ use base64::Engine;
 
 use sulis_core::image::Image;
 use sulis_core::resource::{ResourceSet, Sprite};
+use sulis_core::ui::Color;
 use sulis_core::util::{unable_to_create_error, Point, Size};
 use sulis_core::io::SoundSource;
 
@@ -56,6 +57,8 @@ pub enum TriggerKind {
     OnPlayerEnter { location: Point, size: Size },
     OnEncounterCleared { encounter_location: Point },
     OnEncounterActivated { encounter_location: Point },
+    OnEncounterFled { encounter_location: Point },
+    OnTimeOfDay { hour: u32 },
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +86,21 @@ pub struct ActorData {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unique_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patrol: Option<Patrol>,
+}
+
+/// A route that an actor walks along outside of combat.  The actor moves
+/// to each waypoint in turn, pausing at each one, and returns to the first
+/// waypoint after reaching the last.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Patrol {
+    pub waypoints: Vec<Point>,
+
+    #[serde(default)]
+    pub pause_millis: u32,
 }
 
 #[derive(Clone)]
@@ -92,6 +110,51 @@ pub struct PropData {
     pub items: Vec<ItemListEntrySaveState>,
     pub enabled: bool,
     pub hover_text: Option<String>,
+    pub lock: Option<Lock>,
+    pub detect_difficulty: Option<i32>,
+}
+
+/// A lock on a door or container prop.  Locked props may be opened with
+/// the key item, by picking the lock, or by bashing it open.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Lock {
+    pub difficulty: i32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Whether the key item is removed from the party stash when used
+    #[serde(default)]
+    pub consume_key: bool,
+}
+
+/// A point light, such as a torch or a glowing spell, which brightens the
+/// surrounding tiles in areas with an ambient light level
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LightSource {
+    pub radius: f32,
+
+    #[serde(default)]
+    pub color: Color,
+}
+
+/// A rectangular region of an area
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub location: Point,
+    pub size: Size,
+}
+
+impl Region {
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= self.location.x
+            && p.y >= self.location.y
+            && p.x < self.location.x + self.size.width
+            && p.y < self.location.y + self.size.height
+    }
 }
 
 #[derive(Clone)]
@@ -100,6 +163,7 @@ pub struct EncounterData {
     pub location: Point,
     pub size: Size,
     pub triggers: Vec<usize>,
+    pub flee_region: Option<Region>,
 }
 
 pub struct Area {
@@ -119,6 +183,7 @@ pub struct Area {
     pub vis_dist_up_one_squared: i32,
     pub world_map_location: Option<String>,
     pub ambient_sound: Option<SoundSource>,
+    pub ambient_light: Option<f32>,
     pub default_music: Option<SoundSource>,
     pub default_combat_music: Option<SoundSource>,
     pub on_rest: OnRest,
@@ -149,6 +214,24 @@ impl Area {
 
         let (triggers, encounters) = Area::read_triggers_and_encounters(&builder)?;
 
+        if let OnRest::Camp {
+            interrupt_chance,
+            ref interrupt_encounters,
+        } = builder.on_rest
+        {
+            if interrupt_chance > 100 {
+                warn!("Rest interrupt chance must be between 0 and 100");
+                return unable_to_create_error("area", &builder.id);
+            }
+
+            for point in interrupt_encounters {
+                if !encounters.iter().any(|enc| enc.location == *point) {
+                    warn!("Invalid rest interrupt encounter at point {:?}", point);
+                    return unable_to_create_error("area", &builder.id);
+                }
+            }
+        }
+
         let visibility_tile = ResourceSet::sprite(&builder.visibility_tile)?;
         let explored_tile = ResourceSet::sprite(&builder.explored_tile)?;
 
@@ -190,6 +273,7 @@ impl Area {
                 * builder.max_vis_up_one_distance,
             world_map_location: builder.world_map_location.clone(),
             ambient_sound,
+            ambient_light: builder.ambient_light.map(|l| l.clamp(0.0, 1.0)),
             default_music,
             default_combat_music,
             on_rest: builder.on_rest.clone(),
@@ -227,7 +311,8 @@ impl Area {
             for (index, trigger) in triggers.iter().enumerate() {
                 match trigger.kind {
                     TriggerKind::OnEncounterCleared { encounter_location }
-                    | TriggerKind::OnEncounterActivated { encounter_location } => {
+                    | TriggerKind::OnEncounterActivated { encounter_location }
+                    | TriggerKind::OnEncounterFled { encounter_location } => {
                         if encounter_location == encounter_builder.location {
                             encounter_triggers.push(index);
                             used_triggers.insert(index);
@@ -242,13 +327,15 @@ impl Area {
                 location: encounter_builder.location,
                 size: encounter_builder.size,
                 triggers: encounter_triggers,
+                flee_region: encounter_builder.flee_region,
             });
         }
 
         for (index, trigger) in triggers.iter().enumerate() {
             match trigger.kind {
                 TriggerKind::OnEncounterCleared { encounter_location }
-                | TriggerKind::OnEncounterActivated { encounter_location } => {
+                | TriggerKind::OnEncounterActivated { encounter_location }
+                | TriggerKind::OnEncounterFled { encounter_location } => {
                     if !used_triggers.contains(&index) {
                         warn!(
                             "Invalid encounter trigger at point {:?}",
@@ -343,6 +430,12 @@ pub struct AreaBuilder {
     pub max_vis_up_one_distance: i32,
     pub world_map_location: Option<String>,
     pub ambient_sound: Option<String>,
+
+    /// The level of light in this area, from 0 for complete darkness to 1
+    /// for fully lit.  If not set, lighting is not used in this area
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient_light: Option<f32>,
+
     pub default_music: Option<String>,
     pub default_combat_music: Option<String>,
     pub on_rest: OnRest,
@@ -369,6 +462,99 @@ pub struct AreaBuilder {
 
     #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
     pub elevation: Vec<u8>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<AreaLevelBuilder>,
+}
+
+impl AreaBuilder {
+    /// Removes the additional levels from this builder, returning a complete
+    /// area builder for each of them.  Each level shares the size and settings
+    /// of this area, and has the id given by `level_id`.
+    pub fn take_levels(&mut self) -> Vec<AreaBuilder> {
+        let levels = std::mem::take(&mut self.levels);
+        levels
+            .into_iter()
+            .map(|level| AreaBuilder {
+                id: level_id(&self.id, &level.id),
+                name: level.name.unwrap_or_else(|| self.name.clone()),
+                width: self.width,
+                height: self.height,
+                visibility_tile: self.visibility_tile.clone(),
+                explored_tile: self.explored_tile.clone(),
+                max_vis_distance: self.max_vis_distance,
+                max_vis_up_one_distance: self.max_vis_up_one_distance,
+                world_map_location: self.world_map_location.clone(),
+                ambient_sound: self.ambient_sound.clone(),
+                ambient_light: self.ambient_light,
+                default_music: self.default_music.clone(),
+                default_combat_music: self.default_combat_music.clone(),
+                on_rest: self.on_rest.clone(),
+                location_kind: self.location_kind,
+                generator: None,
+                layers: level.layers,
+                entity_layer: level.entity_layer,
+                actors: level.actors,
+                props: level.props,
+                encounters: level.encounters,
+                transitions: level.transitions,
+                triggers: level.triggers,
+                terrain: level.terrain,
+                walls: level.walls,
+                layer_set: level.layer_set,
+                elevation: level.elevation,
+                levels: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the id of the area created for the level `level` of the area `area`
+pub fn level_id(area: &str, level: &str) -> String {
+    format!("{area}_{level}")
+}
+
+/// An additional vertical level of an area, such as a basement or the upper
+/// floor of a tower.  Each level is loaded as a separate area sharing the
+/// coordinates and settings of its parent, with its own layers, visibility,
+/// and contents.  Levels are normally connected with `Stairs` transitions.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AreaLevelBuilder {
+    pub id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    pub layers: Vec<String>,
+    pub entity_layer: usize,
+
+    #[serde(default)]
+    pub actors: Vec<ActorData>,
+
+    #[serde(default)]
+    pub props: Vec<PropDataBuilder>,
+
+    #[serde(default)]
+    pub encounters: Vec<EncounterDataBuilder>,
+
+    #[serde(default)]
+    pub transitions: Vec<TransitionBuilder>,
+
+    #[serde(default)]
+    pub triggers: Vec<TriggerBuilder>,
+
+    #[serde(serialize_with = "ser_terrain", deserialize_with = "de_terrain")]
+    pub terrain: Vec<Option<String>>,
+
+    #[serde(serialize_with = "ser_walls", deserialize_with = "de_walls")]
+    pub walls: Vec<(u8, Option<String>)>,
+
+    #[serde(serialize_with = "ser_layer_set", deserialize_with = "de_layer_set")]
+    pub layer_set: HashMap<String, Vec<Vec<u16>>>,
+
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    pub elevation: Vec<u8>,
 }
 
 pub struct GeneratorParams {
@@ -660,6 +846,18 @@ impl LocationKind {
 pub enum OnRest {
     Disabled { message: String },
     FireScript { id: String, func: String },
+
+    /// Rest using the camp supplies and duration specified in the rules.
+    /// Each rest has an `interrupt_chance` percent chance of being interrupted
+    /// by spawning one of the area encounters at `interrupt_encounters`,
+    /// chosen at random
+    Camp {
+        #[serde(default)]
+        interrupt_chance: u32,
+
+        #[serde(default)]
+        interrupt_encounters: Vec<Point>,
+    },
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -691,6 +889,11 @@ pub enum ToKind {
         x_offset: i32,
         y_offset: i32,
     },
+    /// Moves the party to another level sharing this area's coordinates,
+    /// with each member keeping their current position
+    Stairs {
+        id: String,
+    },
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -703,15 +906,20 @@ pub struct TransitionBuilder {
     pub image_display: String,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct EncounterDataBuilder {
     pub id: String,
     pub location: Point,
     pub size: Size,
+
+    /// If set, the party escapes combat with this encounter once every
+    /// party member has left this region
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flee_region: Option<Region>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct PropDataBuilder {
     pub id: String,
@@ -721,6 +929,13 @@ pub struct PropDataBuilder {
     pub enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hover_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Lock>,
+
+    /// If set, the prop is hidden until a party member passes a detection
+    /// check against this difficulty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detect_difficulty: Option<i32>,
 }
 
 pub fn create_prop(builder: &PropDataBuilder) -> Result<PropData, Error> {
@@ -739,5 +954,7 @@ pub fn create_prop(builder: &PropDataBuilder) -> Result<PropData, Error> {
         items: builder.items.clone(),
         enabled,
         hover_text: builder.hover_text.clone(),
+        lock: builder.lock.clone(),
+        detect_difficulty: builder.detect_difficulty,
     })
 }
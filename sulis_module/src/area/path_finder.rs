@@ -14,16 +14,47 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::f32;
 use std::time;
-use std::{f32, ptr};
 
+use crate::area::PathFinderGrid;
 use crate::MOVE_TO_THRESHOLD;
 use sulis_core::util::{self, Point};
 
 const MAX_ITERATIONS: i32 = 2_000;
 
+// the initial value of the g and f scores for squares not yet visited by a search
+const UNVISITED_SCORE: i32 = 0x7f7f_7f7f;
+
+/// The width and height of each cluster in the `ClusterGraph`, in squares
+const CLUSTER_SIZE: i32 = 16;
+
+/// Areas with fewer total squares than this are always searched directly,
+/// without building a `ClusterGraph`
+pub const MIN_CLUSTERED_AREA: i32 = 64 * 64;
+
+/// Entrances between clusters at least this wide get a transition at each
+/// end rather than a single one in the middle
+const LONG_ENTRANCE: usize = 6;
+
+/// Orthogonal neighbors followed by diagonal neighbors
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (1, 0),
+    (-1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// The cost of moving one square on the static grid, matching the base
+/// cost used by the area location checkers
+pub(crate) const STEP_COST: i32 = 10;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Destination {
     pub parent_w: f32,
@@ -86,6 +117,297 @@ pub trait LocationChecker {
     fn passable(&self, x: i32, y: i32) -> bool;
     fn in_friend_space(&self, _current: i32) -> bool { false }
     fn get_cost(&self, _from: i32, _to: i32) -> i32 { 1 }
+
+    /// The static grid being searched.  If it has a `ClusterGraph`, long
+    /// paths are found hierarchically
+    fn path_grid(&self) -> Option<&PathFinderGrid> { None }
+}
+
+struct ClusterNode {
+    index: i32,
+    cluster: usize,
+    edges: Vec<(usize, i32)>,
+}
+
+/// An abstraction over a static passability grid for hierarchical path finding.
+/// The grid is divided into square clusters, with transition nodes placed at
+/// the entrances between adjacent clusters.  Nodes within the same cluster are
+/// connected by edges with the cached cost of the path between them, so a long
+/// path may be found by searching the much smaller graph of nodes and then
+/// refining each step locally.
+///
+/// Costs within a cluster are found with the same eight neighbor movement as
+/// the `PathFinder`, including its rule against cutting the corner of an
+/// impassable square.  Entrances are only placed across cluster edges, as any
+/// diagonal step between clusters may instead be made as two orthogonal steps.
+pub struct ClusterGraph {
+    width: i32,
+    height: i32,
+    clusters_x: i32,
+    diagonal_cost: i32,
+    nodes: Vec<ClusterNode>,
+    node_at: HashMap<i32, usize>,
+    cluster_nodes: Vec<Vec<usize>>,
+}
+
+impl ClusterGraph {
+    /// Creates the graph for the specified `passable` grid.  `diagonal_cost` is
+    /// the cost of a diagonal step, relative to a cost of 10 for an orthogonal one
+    pub fn new(width: i32, height: i32, passable: &[bool], diagonal_cost: i32) -> ClusterGraph {
+        let start_time = time::Instant::now();
+
+        let clusters_x = (width + CLUSTER_SIZE - 1) / CLUSTER_SIZE;
+        let clusters_y = (height + CLUSTER_SIZE - 1) / CLUSTER_SIZE;
+
+        let mut graph = ClusterGraph {
+            width,
+            height,
+            clusters_x,
+            diagonal_cost,
+            nodes: Vec::new(),
+            node_at: HashMap::new(),
+            cluster_nodes: vec![Vec::new(); (clusters_x * clusters_y) as usize],
+        };
+
+        for cy in 0..clusters_y {
+            let min_y = cy * CLUSTER_SIZE;
+            let max_y = cmp::min(height, min_y + CLUSTER_SIZE);
+            for cx in 0..clusters_x - 1 {
+                let x = (cx + 1) * CLUSTER_SIZE - 1;
+                let pairs: Vec<_> = (min_y..max_y)
+                    .map(|y| (x + y * width, x + 1 + y * width))
+                    .collect();
+                graph.add_entrances(passable, &pairs);
+            }
+        }
+
+        for cx in 0..clusters_x {
+            let min_x = cx * CLUSTER_SIZE;
+            let max_x = cmp::min(width, min_x + CLUSTER_SIZE);
+            for cy in 0..clusters_y - 1 {
+                let y = (cy + 1) * CLUSTER_SIZE - 1;
+                let pairs: Vec<_> = (min_x..max_x)
+                    .map(|x| (x + y * width, x + (y + 1) * width))
+                    .collect();
+                graph.add_entrances(passable, &pairs);
+            }
+        }
+
+        for cluster in 0..graph.cluster_nodes.len() {
+            let cluster_nodes = graph.cluster_nodes[cluster].clone();
+            for &node in cluster_nodes.iter() {
+                let costs = graph.cluster_costs(passable, graph.nodes[node].index);
+                let edges: Vec<_> = cluster_nodes
+                    .iter()
+                    .filter(|&&other| other != node)
+                    .filter_map(|&other| {
+                        costs
+                            .get(&graph.nodes[other].index)
+                            .map(|cost| (other, *cost))
+                    })
+                    .collect();
+                graph.nodes[node].edges.extend(edges);
+            }
+        }
+
+        debug!(
+            "Built cluster graph with {} nodes in {} secs",
+            graph.nodes.len(),
+            util::format_elapsed_secs(start_time.elapsed())
+        );
+
+        graph
+    }
+
+    fn add_entrances(&mut self, passable: &[bool], pairs: &[(i32, i32)]) {
+        let mut run: Vec<(i32, i32)> = Vec::new();
+        for (i, &(a, b)) in pairs.iter().enumerate() {
+            if passable[a as usize] && passable[b as usize] {
+                run.push((a, b));
+                if i < pairs.len() - 1 {
+                    continue;
+                }
+            }
+
+            if run.is_empty() {
+                continue;
+            }
+
+            if run.len() >= LONG_ENTRANCE {
+                self.add_transition(run[0]);
+                self.add_transition(run[run.len() - 1]);
+            } else {
+                self.add_transition(run[run.len() / 2]);
+            }
+            run.clear();
+        }
+    }
+
+    fn add_transition(&mut self, (a, b): (i32, i32)) {
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        self.nodes[a].edges.push((b, STEP_COST));
+        self.nodes[b].edges.push((a, STEP_COST));
+    }
+
+    fn add_node(&mut self, index: i32) -> usize {
+        if let Some(node) = self.node_at.get(&index) {
+            return *node;
+        }
+
+        let cluster = self.cluster_of(index);
+        let node = self.nodes.len();
+        self.nodes.push(ClusterNode {
+            index,
+            cluster,
+            edges: Vec::new(),
+        });
+        self.node_at.insert(index, node);
+        self.cluster_nodes[cluster].push(node);
+        node
+    }
+
+    fn cluster_of(&self, index: i32) -> usize {
+        let x = index % self.width;
+        let y = index / self.width;
+        (x / CLUSTER_SIZE + (y / CLUSTER_SIZE) * self.clusters_x) as usize
+    }
+
+    /// Computes the cost to reach each square in the cluster containing `from`,
+    /// without leaving that cluster
+    fn cluster_costs(&self, passable: &[bool], from: i32) -> HashMap<i32, i32> {
+        let cluster = self.cluster_of(from) as i32;
+        let min_x = (cluster % self.clusters_x) * CLUSTER_SIZE;
+        let min_y = (cluster / self.clusters_x) * CLUSTER_SIZE;
+        let max_x = cmp::min(self.width, min_x + CLUSTER_SIZE);
+        let max_y = cmp::min(self.height, min_y + CLUSTER_SIZE);
+
+        let in_cluster = |x: i32, y: i32| x >= min_x && x < max_x && y >= min_y && y < max_y;
+        let is_passable = |x: i32, y: i32| passable[(x + y * self.width) as usize];
+
+        let mut costs = HashMap::new();
+        costs.insert(from, 0);
+        let mut open = BinaryHeap::new();
+        open.push(OpenEntry::new(from, 0));
+
+        while let Some(entry) = open.pop() {
+            let current = entry.index;
+            if entry.f_score > costs[&current] {
+                continue;
+            }
+
+            let (x, y) = (current % self.width, current / self.width);
+            for &(dx, dy) in NEIGHBOR_OFFSETS.iter() {
+                let (n_x, n_y) = (x + dx, y + dy);
+                if !in_cluster(n_x, n_y) || !is_passable(n_x, n_y) {
+                    continue;
+                }
+
+                let cost = if dx != 0 && dy != 0 {
+                    // don't cut across the corner of an impassable square
+                    if !is_passable(n_x, y) || !is_passable(x, n_y) {
+                        continue;
+                    }
+                    entry.f_score + self.diagonal_cost
+                } else {
+                    entry.f_score + STEP_COST
+                };
+
+                let neighbor = n_x + n_y * self.width;
+                if cost >= *costs.get(&neighbor).unwrap_or(&i32::MAX) {
+                    continue;
+                }
+
+                costs.insert(neighbor, cost);
+                open.push(OpenEntry::new(neighbor, cost));
+            }
+        }
+
+        costs
+    }
+
+    /// A lower bound on the cost of moving between the two points, allowing
+    /// diagonal steps
+    fn estimate_cost(&self, from: i32, to: i32) -> i32 {
+        let dx = (from % self.width - to % self.width).abs();
+        let dy = (from / self.width - to / self.width).abs();
+        let diagonal_cost = cmp::min(self.diagonal_cost, 2 * STEP_COST);
+        let (long, short) = (cmp::max(dx, dy), cmp::min(dx, dy));
+        STEP_COST * (long - short) + diagonal_cost * short
+    }
+
+    /// Searches the cluster graph for a series of transition points leading from
+    /// `start` to the cluster containing `dest`.  Returns `None` if both are in
+    /// the same cluster, or if no route exists on the static grid.
+    fn find_waypoints(&self, passable: &[bool], start: i32, dest: i32) -> Option<Vec<Point>> {
+        let start_cluster = self.cluster_of(start);
+        let dest_cluster = self.cluster_of(dest);
+        if start_cluster == dest_cluster {
+            return None;
+        }
+
+        let heuristic = |index: i32| self.estimate_cost(index, dest);
+
+        // the start is a temporary node, connected to each node in its cluster
+        let start_node = self.nodes.len();
+        let start_costs = self.cluster_costs(passable, start);
+        let start_edges: Vec<_> = self.cluster_nodes[start_cluster]
+            .iter()
+            .filter_map(|&node| {
+                start_costs
+                    .get(&self.nodes[node].index)
+                    .map(|cost| (node, *cost))
+            })
+            .collect();
+
+        let mut open = BinaryHeap::new();
+        let mut closed = HashSet::new();
+        let mut g_score: HashMap<usize, i32> = HashMap::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+
+        g_score.insert(start_node, 0);
+        open.push(OpenEntry::new(start_node as i32, heuristic(start)));
+
+        while let Some(entry) = open.pop() {
+            let current = entry.index as usize;
+            if !closed.insert(current) {
+                continue;
+            }
+
+            if current != start_node && self.nodes[current].cluster == dest_cluster {
+                let mut waypoints = Vec::new();
+                let mut node = current;
+                while node != start_node {
+                    let index = self.nodes[node].index;
+                    waypoints.push(Point::new(index % self.width, index / self.width));
+                    node = came_from[&node];
+                }
+                waypoints.reverse();
+                return Some(waypoints);
+            }
+
+            let edges = if current == start_node {
+                &start_edges
+            } else {
+                &self.nodes[current].edges
+            };
+
+            let g = g_score[&current];
+            for &(neighbor, cost) in edges.iter() {
+                let tentative_g_score = g + cost;
+                if tentative_g_score >= *g_score.get(&neighbor).unwrap_or(&i32::MAX) {
+                    continue;
+                }
+
+                g_score.insert(neighbor, tentative_g_score);
+                came_from.insert(neighbor, current);
+                let f_score = tentative_g_score + heuristic(self.nodes[neighbor].index);
+                open.push(OpenEntry::new(neighbor as i32, f_score));
+            }
+        }
+
+        None
+    }
 }
 
 pub struct PathFinder {
@@ -98,6 +420,7 @@ pub struct PathFinder {
     open_set: HashSet<i32>,
     closed: HashSet<i32>,
     came_from: HashMap<i32, i32>,
+    touched: Vec<i32>,
 
     dest_x: f32,
     dest_y: f32,
@@ -114,12 +437,13 @@ impl PathFinder {
         PathFinder {
             width,
             height,
-            f_score: vec![0; (width * height) as usize],
-            g_score: vec![0; (width * height) as usize],
+            f_score: vec![UNVISITED_SCORE; (width * height) as usize],
+            g_score: vec![UNVISITED_SCORE; (width * height) as usize],
             open: BinaryHeap::new(),
             open_set: HashSet::default(),
             closed: HashSet::default(),
             came_from: HashMap::default(),
+            touched: Vec::new(),
             dest_x: 0.0,
             dest_y: 0.0,
             dest_w_over2: 0.0,
@@ -143,6 +467,10 @@ impl PathFinder {
     /// efficient manner.  Returns `None` if no path exists to reach the destination.
     /// Will return a vec of length zero if the dest is already reached by the
     /// requester.
+    ///
+    /// If the checker's grid has a `ClusterGraph`, a route is first found through
+    /// the graph and then refined one cluster at a time, falling back to a search
+    /// over the full grid if the refinement is blocked.
     pub fn find<T: LocationChecker>(
         &mut self,
         checker: &T,
@@ -157,6 +485,114 @@ impl PathFinder {
             return None;
         }
 
+        self.set_dest(dest);
+        let start = start_x + start_y * self.width;
+        if self.dist_squared(start) <= (dest.dist * dest.dist) as i32 {
+            debug!("Mover is already inside the destination");
+            return None;
+        }
+
+        if let Some(grid) = checker.path_grid() {
+            if let Some(clusters) = grid.clusters.as_ref() {
+                let dest_x = cmp::min(self.width - 1, (dest.x + dest.w / 2.0) as i32);
+                let dest_y = cmp::min(self.height - 1, (dest.y + dest.h / 2.0) as i32);
+                let dest_index = dest_x + dest_y * self.width;
+
+                if let Some(waypoints) = clusters.find_waypoints(&grid.passable, start, dest_index)
+                {
+                    let start_time = time::Instant::now();
+                    let path = self.refine(checker, start_x, start_y, &waypoints, dest);
+                    trace!(
+                        "Hierarchical path refine time: {}",
+                        util::format_elapsed_secs(start_time.elapsed())
+                    );
+
+                    if path.is_some() {
+                        return path;
+                    }
+                    debug!("Unable to refine hierarchical path, searching full grid");
+                }
+            }
+        }
+
+        self.find_direct(checker, start_x, start_y, dest)
+    }
+
+    /// Connects each of the `waypoints` in turn with a local search, and then
+    /// searches from the final waypoint to the destination.
+    fn refine<T: LocationChecker>(
+        &mut self,
+        checker: &T,
+        start_x: i32,
+        start_y: i32,
+        waypoints: &[Point],
+        dest: Destination,
+    ) -> Option<Vec<Point>> {
+        let mut path = vec![Point::new(start_x, start_y)];
+        let (mut x, mut y) = (start_x, start_y);
+
+        for waypoint in waypoints {
+            if waypoint.x == x && waypoint.y == y {
+                continue;
+            }
+
+            let waypoint_dest = Destination {
+                parent_w: 0.0,
+                parent_h: 0.0,
+                x: waypoint.x as f32,
+                y: waypoint.y as f32,
+                w: 0.0,
+                h: 0.0,
+                dist: 0.0,
+                max_path_len: None,
+            };
+            let leg = self.find_direct(checker, x, y, waypoint_dest)?;
+            path.extend(leg.into_iter().skip(1));
+            x = waypoint.x;
+            y = waypoint.y;
+        }
+
+        let final_dest = Destination {
+            max_path_len: None,
+            ..dest
+        };
+        self.set_dest(final_dest);
+        let dest_dist_squared = (dest.dist * dest.dist) as i32;
+        if !self.is_goal(checker, x + y * self.width, dest_dist_squared) {
+            let leg = self.find_direct(checker, x, y, final_dest)?;
+            path.extend(leg.into_iter().skip(1));
+        }
+
+        if let Some(max_path_len) = dest.max_path_len {
+            if path.len() > max_path_len as usize {
+                debug!(
+                    "Found path with too many moves: {} > {}",
+                    path.len(),
+                    max_path_len
+                );
+                return None;
+            }
+        }
+
+        Some(path)
+    }
+
+    fn set_dest(&mut self, dest: Destination) {
+        self.dest_x = dest.x + dest.w / 2.0;
+        self.dest_y = dest.y + dest.h / 2.0;
+        self.dest_w_over2 = dest.w / 2.0;
+        self.dest_h_over2 = dest.h / 2.0;
+        self.parent_w_over2 = dest.parent_w / 2.0;
+        self.parent_h_over2 = dest.parent_h / 2.0;
+    }
+
+    fn find_direct<T: LocationChecker>(
+        &mut self,
+        checker: &T,
+        start_x: i32,
+        start_y: i32,
+        dest: Destination,
+    ) -> Option<Vec<Point>> {
         trace!(
             "Finding path from {},{} to within {} of {},{},{},{}",
             start_x,
@@ -169,12 +605,7 @@ impl PathFinder {
         );
 
         // let start_time = time::Instant::now();
-        self.dest_x = dest.x + dest.w / 2.0;
-        self.dest_y = dest.y + dest.h / 2.0;
-        self.dest_w_over2 = dest.w / 2.0;
-        self.dest_h_over2 = dest.h / 2.0;
-        self.parent_w_over2 = dest.parent_w / 2.0;
-        self.parent_h_over2 = dest.parent_h / 2.0;
+        self.set_dest(dest);
         let dest_dist_squared = (dest.dist * dest.dist) as i32;
         let start = start_x + start_y * self.width;
         let initial_dist_squared = self.dist_squared(start);
@@ -194,15 +625,14 @@ impl PathFinder {
         // for each node, the node it can be most efficiently reached from
         self.came_from.clear();
 
-        // let f_g_init_time = time::Instant::now();
-        unsafe {
-            // memset g_score and f_score to a large integer value
-            // benchmarking revealed that setting these values using the naive
-            // approach is the majority of time spent for most path finds
-            ptr::write_bytes(self.g_score.as_mut_ptr(), 127, self.g_score.len());
-            ptr::write_bytes(self.f_score.as_mut_ptr(), 127, self.f_score.len());
+        // reset only the scores set by the previous search, rather than the
+        // entire grid, which would dominate the time spent on short searches
+        for index in self.touched.drain(..) {
+            self.g_score[index as usize] = UNVISITED_SCORE;
+            self.f_score[index as usize] = UNVISITED_SCORE;
         }
 
+        self.touched.push(start);
         self.g_score[start as usize] = 0;
         self.f_score[start as usize] = self.dist_squared(start);
         // info!("F and G score init: {}", util::format_elapsed_secs(f_g_init_time.elapsed()));
@@ -272,6 +702,18 @@ impl PathFinder {
                     continue;
                 }
 
+                // don't allow diagonal moves to cut across the corner of an
+                // impassable square
+                let current_x = current % self.width;
+                let current_y = current / self.width;
+                if neighbor_x != current_x
+                    && neighbor_y != current_y
+                    && (!checker.passable(neighbor_x, current_y)
+                        || !checker.passable(current_x, neighbor_y))
+                {
+                    continue;
+                }
+
                 let tentative_g_score =
                     self.g_score[current as usize] + checker.get_cost(current, neighbor);
                 if tentative_g_score >= self.g_score[neighbor as usize] {
@@ -282,6 +724,7 @@ impl PathFinder {
 
                 self.came_from.insert(neighbor, current);
 
+                self.touched.push(neighbor);
                 self.g_score[neighbor as usize] = tentative_g_score;
                 self.f_score[neighbor as usize] = tentative_g_score + self.dist_squared(neighbor);
                 self.push_to_open_set(neighbor, self.f_score[neighbor as usize]);
@@ -331,27 +774,16 @@ impl PathFinder {
 
     #[inline]
     // using an array here instead of a vec is much faster
-    fn get_neighbors(&self, point: i32) -> [i32; 4] {
-        let width = self.width;
-        let height = self.height;
-
-        let top = point - width;
-        let right = point + 1;
-        let left = point - 1;
-        let bottom = point + width;
-
-        let mut neighbors = [-1; 4];
-        if top > 0 {
-            neighbors[0] = top;
-        }
-        if bottom < width * height {
-            neighbors[1] = bottom;
-        }
-        if right % width != point % width {
-            neighbors[2] = right;
-        }
-        if left % width != point % width {
-            neighbors[3] = left;
+    fn get_neighbors(&self, point: i32) -> [i32; 8] {
+        let x = point % self.width;
+        let y = point / self.width;
+
+        let mut neighbors = [-1; 8];
+        for (neighbor, (dx, dy)) in neighbors.iter_mut().zip(NEIGHBOR_OFFSETS.iter()) {
+            let (n_x, n_y) = (x + dx, y + dy);
+            if n_x >= 0 && n_x < self.width && n_y >= 0 && n_y < self.height {
+                *neighbor = n_x + n_y * self.width;
+            }
         }
 
         //trace!("Got neighbors for {}: {:?}", point, neighbors);
@@ -395,3 +827,64 @@ impl PathFinder {
         (dx * dx + dy * dy) as i32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAGONAL_COST: i32 = 15;
+
+    fn open_grid(width: i32, height: i32) -> Vec<bool> {
+        vec![true; (width * height) as usize]
+    }
+
+    #[test]
+    fn cluster_costs_allow_diagonal_steps() {
+        let passable = open_grid(64, 64);
+        let graph = ClusterGraph::new(64, 64, &passable, DIAGONAL_COST);
+
+        let costs = graph.cluster_costs(&passable, 0);
+        assert_eq!(costs[&1], STEP_COST);
+        assert_eq!(costs[&(1 + 64)], DIAGONAL_COST);
+        assert_eq!(costs[&(3 + 3 * 64)], 3 * DIAGONAL_COST);
+        assert_eq!(costs[&(5 + 2 * 64)], 3 * STEP_COST + 2 * DIAGONAL_COST);
+    }
+
+    #[test]
+    fn cluster_costs_do_not_cut_corners() {
+        let mut passable = open_grid(64, 64);
+        passable[1] = false;
+        let graph = ClusterGraph::new(64, 64, &passable, DIAGONAL_COST);
+
+        // the diagonal step from (0, 0) to (1, 1) would cut the corner of (1, 0)
+        let costs = graph.cluster_costs(&passable, 0);
+        assert_eq!(costs[&(1 + 64)], 2 * STEP_COST);
+    }
+
+    #[test]
+    fn waypoints_pass_through_the_gap_in_a_wall() {
+        let mut passable = open_grid(64, 64);
+        for y in 0..64 {
+            if y != 40 {
+                passable[(20 + y * 64) as usize] = false;
+            }
+        }
+        let graph = ClusterGraph::new(64, 64, &passable, DIAGONAL_COST);
+
+        // the route must detour through the row of clusters containing the gap
+        let waypoints = graph.find_waypoints(&passable, 2 + 2 * 64, 60 + 2 * 64).unwrap();
+        assert!(waypoints.iter().any(|p| p.y >= 32 && p.y < 48));
+        assert!(waypoints.iter().all(|p| passable[(p.x + p.y * 64) as usize]));
+    }
+
+    #[test]
+    fn waypoints_not_found_through_a_solid_wall() {
+        let mut passable = open_grid(64, 64);
+        for y in 0..64 {
+            passable[(20 + y * 64) as usize] = false;
+        }
+        let graph = ClusterGraph::new(64, 64, &passable, DIAGONAL_COST);
+
+        assert!(graph.find_waypoints(&passable, 2 + 2 * 64, 60 + 2 * 64).is_none());
+    }
+}
@@ -17,13 +17,18 @@
 use std::fmt;
 use std::rc::Rc;
 
-use crate::ObjectSize;
+use crate::area::path_finder::{ClusterGraph, MIN_CLUSTERED_AREA, STEP_COST};
+use crate::{Module, ObjectSize};
 
 pub struct PathFinderGrid {
     pub size: Rc<ObjectSize>,
     pub passable: Vec<bool>,
     pub width: i32,
     pub height: i32,
+
+    /// The cluster graph used for hierarchical path finding.  Only present
+    /// for large areas
+    pub clusters: Option<ClusterGraph>,
 }
 
 impl fmt::Debug for PathFinderGrid {
@@ -65,11 +70,19 @@ impl PathFinderGrid {
             }
         }
 
+        let clusters = if width * height >= MIN_CLUSTERED_AREA {
+            let diagonal_cost = STEP_COST as f32 * Module::rules().diagonal_move_cost;
+            Some(ClusterGraph::new(width, height, &passable, diagonal_cost.round() as i32))
+        } else {
+            None
+        };
+
         PathFinderGrid {
             size,
             passable,
             width,
             height,
+            clusters,
         }
     }
 
@@ -25,7 +25,7 @@ use sulis_core::image::Image;
 use sulis_core::resource::ResourceSet;
 use sulis_core::util::{unable_to_create_error, Point};
 
-use crate::{on_trigger, Conversation, Module};
+use crate::{on_trigger, Conversation, EncounterTable, Module};
 
 pub struct WorldMap {
     pub size: (f32, f32),
@@ -45,6 +45,9 @@ pub struct WorldMapLocation {
     pub linked_area_pos: Point,
 
     pub travel_times: HashMap<String, u32>,
+
+    /// Random encounters which may occur when traveling to this location
+    pub encounter_table: Option<Rc<EncounterTable>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -93,8 +96,38 @@ pub struct Campaign {
     pub on_party_death_script: on_trigger::ScriptData,
     pub on_tick_script: Option<on_trigger::ScriptData>,
     pub on_round_elapsed_script: Option<on_trigger::ScriptData>,
+    pub on_rest_script: Option<on_trigger::ScriptData>,
+    pub on_rest_interrupted_script: Option<on_trigger::ScriptData>,
+    pub permadeath: bool,
     pub world_map: WorldMap,
     pub group: Option<CampaignGroup>,
+    pub script_limits: ScriptLimits,
+}
+
+/// Limits on the resources used by a single script call.  Calls exceeding
+/// any limit are aborted with an error, so that a runaway script cannot
+/// freeze the game.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields, default)]
+pub struct ScriptLimits {
+    /// The approximate number of Lua instructions
+    pub instructions: u32,
+
+    /// The maximum memory used by each script state, in kilobytes
+    pub memory_kb: usize,
+
+    /// The wall clock time, in milliseconds
+    pub millis: f64,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> ScriptLimits {
+        ScriptLimits {
+            instructions: 50_000,
+            memory_kb: 10_240,
+            millis: 50.0,
+        }
+    }
 }
 
 impl Campaign {
@@ -120,6 +153,17 @@ impl Campaign {
                 Some(img) => img,
             };
 
+            let encounter_table = match location.encounter_table {
+                None => None,
+                Some(ref table_id) => match Module::encounter_table(table_id) {
+                    None => {
+                        warn!("Invalid encounter table for '{}': '{}'", id, table_id);
+                        return unable_to_create_error("module", &builder.name);
+                    }
+                    Some(table) => Some(table),
+                },
+            };
+
             locations.push(WorldMapLocation {
                 id,
                 name: location.name,
@@ -130,6 +174,7 @@ impl Campaign {
                 linked_area: location.linked_area,
                 linked_area_pos: location.linked_area_pos,
                 travel_times: location.travel_times,
+                encounter_table,
             });
         }
 
@@ -146,6 +191,10 @@ impl Campaign {
             on_party_death_script: builder.on_party_death_script,
             on_tick_script: builder.on_tick_script,
             on_round_elapsed_script: builder.on_round_elapsed_script,
+            on_rest_script: builder.on_rest_script,
+            on_rest_interrupted_script: builder.on_rest_interrupted_script,
+            permadeath: builder.permadeath,
+            script_limits: builder.script_limits,
             world_map: WorldMap {
                 size: builder.world_map.size,
                 offset: builder.world_map.offset,
@@ -170,7 +219,21 @@ pub struct CampaignBuilder {
     pub on_party_death_script: on_trigger::ScriptData,
     pub on_tick_script: Option<on_trigger::ScriptData>,
     pub on_round_elapsed_script: Option<on_trigger::ScriptData>,
+
+    /// Called after the party completes a camp rest
+    pub on_rest_script: Option<on_trigger::ScriptData>,
+
+    /// Called after a camp rest is interrupted, once the encounter has spawned
+    pub on_rest_interrupted_script: Option<on_trigger::ScriptData>,
     pub world_map: WorldMapBuilder,
+
+    /// If set, party members reduced to zero hit points always die, even
+    /// when the rules specify injuries
+    #[serde(default)]
+    pub permadeath: bool,
+
+    #[serde(default)]
+    pub script_limits: ScriptLimits,
 }
 
 #[derive(Deserialize, Debug)]
@@ -191,6 +254,9 @@ pub struct WorldMapLocationBuilder {
 
     #[serde(default)]
     pub travel_times: HashMap<String, u32>,
+
+    #[serde(default)]
+    pub encounter_table: Option<String>,
 }
 
 fn bool_true() -> bool {
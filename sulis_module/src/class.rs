@@ -51,6 +51,10 @@ pub struct Class {
     starting_abilities: Vec<Rc<Ability>>,
     pub kits: Vec<Kit>,
     pub stats: Vec<ClassStat>,
+
+    /// Whether members of this class make a free attack against hostiles
+    /// moving out of their reach
+    pub opportunity_attacks: bool,
 }
 
 impl PartialEq for Class {
@@ -163,6 +167,7 @@ impl Class {
             max_level_upgrades,
             starting_abilities: abilities,
             stats: builder.stats,
+            opportunity_attacks: builder.opportunity_attacks,
         })
     }
 
@@ -251,4 +256,7 @@ pub struct ClassBuilder {
 
     #[serde(default)]
     pub stats: Vec<ClassStat>,
+
+    #[serde(default)]
+    pub opportunity_attacks: bool,
 }
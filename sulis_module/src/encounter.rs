@@ -18,10 +18,10 @@ use std::collections::{HashMap, HashSet};
 use std::io::Error;
 use std::rc::Rc;
 
-use crate::{Actor, Module};
+use crate::{Actor, ItemState, LootList, Module};
 use sulis_core::io::SoundSource;
 use sulis_core::resource::ResourceSet;
-use sulis_core::util::{gen_rand, unable_to_create_error};
+use sulis_core::util::{gen_rand, invalid_data_error, unable_to_create_error, ReproducibleRandom};
 
 struct Entry {
     actor: Rc<Actor>,
@@ -31,10 +31,47 @@ struct Entry {
     limit: Option<u32>,
 }
 
+/// Loot rolled when an encounter is defeated, placed in a lootable remains
+/// prop at the location where the last enemy fell
+pub struct EncounterLoot {
+    coins: [u32; 2],
+    loot_list: Option<Rc<LootList>>,
+    chance: u32,
+}
+
+impl EncounterLoot {
+    /// Rolls the coins and items for this loot using the specified `rand`.
+    /// Coins are returned as a quantity of the rules coin item
+    pub fn generate(&self, rand: &mut ReproducibleRandom) -> Vec<(u32, ItemState)> {
+        let mut items = Vec::new();
+
+        let coins = if self.coins[0] >= self.coins[1] {
+            self.coins[0]
+        } else {
+            rand.gen(self.coins[0], self.coins[1] + 1)
+        };
+
+        if coins > 0 {
+            match Module::item(&Module::rules().coins_item) {
+                None => warn!("Unable to find coins item for encounter loot"),
+                Some(item) => items.push((coins, ItemState::new(item, None))),
+            }
+        }
+
+        if let Some(ref loot_list) = self.loot_list {
+            items.extend(loot_list.generate_with_chance_rand(self.chance, rand));
+        }
+
+        items
+    }
+}
+
 pub struct Encounter {
     pub id: String,
     pub music: Option<SoundSource>,
     pub auto_spawn: bool,
+    pub xp: u32,
+    pub loot: Option<EncounterLoot>,
     min_gen_actors: u32,
     max_gen_actors: u32,
     entries: Vec<Entry>,
@@ -64,8 +101,10 @@ impl Encounter {
 
             let actor = match module.actors.get(&entry.id) {
                 None => {
-                    warn!("no actor '{}' found", entry.id);
-                    return unable_to_create_error("encounter", &builder.id);
+                    return invalid_data_error(&format!(
+                        "References missing actor '{}'",
+                        entry.id
+                    ));
                 }
                 Some(actor) => Rc::clone(actor),
             };
@@ -95,10 +134,34 @@ impl Encounter {
             Some(id) => Some(ResourceSet::sound(id)?),
         };
 
+        let loot = match builder.loot {
+            None => None,
+            Some(loot) => {
+                let loot_list = match loot.loot_list {
+                    None => None,
+                    Some(id) => match module.loot_lists.get(&id) {
+                        None => {
+                            warn!("No loot list found with id '{}'", id);
+                            return unable_to_create_error("encounter", &builder.id);
+                        }
+                        Some(list) => Some(Rc::clone(list)),
+                    },
+                };
+
+                Some(EncounterLoot {
+                    coins: loot.coins,
+                    loot_list,
+                    chance: loot.chance,
+                })
+            }
+        };
+
         Ok(Encounter {
             id: builder.id,
             music,
             auto_spawn: builder.auto_spawn,
+            xp: builder.xp,
+            loot,
             min_gen_actors: builder.min_gen_actors,
             max_gen_actors: builder.max_gen_actors,
             entries,
@@ -186,11 +249,36 @@ pub struct EncounterBuilder {
     pub id: String,
     pub music: Option<String>,
     pub auto_spawn: bool,
+
+    #[serde(default)]
+    pub xp: u32,
+
+    #[serde(default)]
+    loot: Option<EncounterLootBuilder>,
     min_gen_actors: u32,
     max_gen_actors: u32,
     entries: Vec<EntryBuilder>,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EncounterLootBuilder {
+    /// The minimum and maximum number of coins dropped, in display units
+    #[serde(default)]
+    coins: [u32; 2],
+
+    #[serde(default)]
+    loot_list: Option<String>,
+
+    /// The percent chance that items are generated from the loot list
+    #[serde(default = "default_loot_chance")]
+    chance: u32,
+}
+
+fn default_loot_chance() -> u32 {
+    100
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct EntryBuilder {
@@ -0,0 +1,132 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::io::Error;
+
+use sulis_core::util::{gen_rand, unable_to_create_error, Point};
+
+use crate::Module;
+
+/// A possible encounter while traveling the world map.  The `area` must be
+/// a generated area.  It is generated on the first encounter and kept
+/// afterwards, with any defeated encounters in it spawned again each time
+/// the encounter occurs
+#[derive(Debug)]
+pub struct EncounterTableEntry {
+    pub area: String,
+    pub location: Point,
+    weight: u32,
+}
+
+/// The random encounters that may occur while traveling to a world map
+/// region.  Each trip has a `chance` out of 100 of rolling an encounter.
+#[derive(Debug)]
+pub struct EncounterTable {
+    pub id: String,
+    pub chance: u32,
+    entries: Vec<EncounterTableEntry>,
+    total_weight: u32,
+}
+
+impl EncounterTable {
+    pub fn new(builder: EncounterTableBuilder) -> Result<EncounterTable, Error> {
+        if builder.chance > 100 {
+            warn!("Encounter table chance must be between 0 and 100");
+            return unable_to_create_error("encounter_table", &builder.id);
+        }
+
+        let mut entries = Vec::new();
+        let mut total_weight = 0;
+        for entry in builder.entries {
+            match Module::area(&entry.area) {
+                None => {
+                    warn!("Unable to find area '{}'", entry.area);
+                    return unable_to_create_error("encounter_table", &builder.id);
+                }
+                Some(area) => {
+                    if area.generator.is_none() {
+                        warn!("Encounter table area '{}' must be generated", entry.area);
+                        return unable_to_create_error("encounter_table", &builder.id);
+                    }
+                }
+            }
+
+            if entry.weight == 0 {
+                continue;
+            }
+
+            total_weight += entry.weight;
+            entries.push(EncounterTableEntry {
+                area: entry.area,
+                location: entry.location,
+                weight: entry.weight,
+            });
+        }
+
+        if entries.is_empty() {
+            warn!("Encounter table must have at least one entry with non-zero weight");
+            return unable_to_create_error("encounter_table", &builder.id);
+        }
+
+        Ok(EncounterTable {
+            id: builder.id,
+            chance: builder.chance,
+            entries,
+            total_weight,
+        })
+    }
+
+    /// Rolls for a random encounter, returning the entry for the encounter
+    /// that occurs, if any
+    pub fn roll(&self) -> Option<&EncounterTableEntry> {
+        if gen_rand(0, 100) >= self.chance {
+            return None;
+        }
+
+        let roll = gen_rand(0, self.total_weight);
+        let mut cur_weight = 0;
+        for entry in self.entries.iter() {
+            cur_weight += entry.weight;
+            if roll < cur_weight {
+                return Some(entry);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EncounterTableBuilder {
+    pub id: String,
+    pub chance: u32,
+    pub entries: Vec<EncounterTableEntryBuilder>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EncounterTableEntryBuilder {
+    pub area: String,
+    pub location: Point,
+
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
@@ -0,0 +1,114 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::io::Error;
+
+use crate::actor;
+use sulis_core::util::invalid_data_error;
+
+pub const MIN_STANDING: i32 = -100;
+pub const MAX_STANDING: i32 = 100;
+
+/// A group of actors that the party holds a single shared standing with.
+/// Actors reference a faction via their `faction_id`, and the attitude of
+/// every member towards the party is derived from the current standing.
+#[derive(Debug)]
+pub struct FactionDefinition {
+    pub id: String,
+    pub name: String,
+    pub initial_standing: i32,
+
+    /// Members become hostile when standing drops below this value
+    pub hostile_below: i32,
+
+    /// Members become friendly when standing is at or above this value.  If
+    /// not specified, members never become friendly due to standing alone.
+    pub friendly_at: Option<i32>,
+
+    /// The fraction merchant prices are improved by at maximum standing, and
+    /// worsened by at minimum standing.
+    pub price_adjustment: f32,
+}
+
+impl FactionDefinition {
+    pub fn new(builder: FactionBuilder) -> Result<FactionDefinition, Error> {
+        let in_range = |val: i32| (MIN_STANDING..=MAX_STANDING).contains(&val);
+
+        if !in_range(builder.initial_standing) || !in_range(builder.hostile_below) {
+            return invalid_data_error(&format!(
+                "Faction standings must be between {MIN_STANDING} and {MAX_STANDING}"
+            ));
+        }
+
+        if let Some(friendly_at) = builder.friendly_at {
+            if !in_range(friendly_at) || friendly_at < builder.hostile_below {
+                return invalid_data_error("Faction friendly_at must be above hostile_below");
+            }
+        }
+
+        if builder.price_adjustment < 0.0 || builder.price_adjustment >= 1.0 {
+            return invalid_data_error("Faction price_adjustment must be in [0.0, 1.0)");
+        }
+
+        Ok(FactionDefinition {
+            id: builder.id,
+            name: builder.name,
+            initial_standing: builder.initial_standing,
+            hostile_below: builder.hostile_below,
+            friendly_at: builder.friendly_at,
+            price_adjustment: builder.price_adjustment,
+        })
+    }
+
+    /// Returns the attitude members of this faction take towards the party
+    /// at the specified `standing`
+    pub fn disposition(&self, standing: i32) -> actor::Faction {
+        if standing < self.hostile_below {
+            return actor::Faction::Hostile;
+        }
+
+        match self.friendly_at {
+            Some(friendly_at) if standing >= friendly_at => actor::Faction::Friendly,
+            _ => actor::Faction::Neutral,
+        }
+    }
+
+    /// Returns the multiplier applied to the price of items the party buys
+    /// from merchants of this faction at the specified `standing`.  Prices
+    /// for items the party sells are divided by this amount.
+    pub fn price_factor(&self, standing: i32) -> f32 {
+        1.0 - self.price_adjustment * standing as f32 / MAX_STANDING as f32
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FactionBuilder {
+    pub id: String,
+    pub name: String,
+
+    #[serde(default)]
+    pub initial_standing: i32,
+
+    #[serde(default)]
+    pub hostile_below: i32,
+
+    #[serde(default)]
+    pub friendly_at: Option<i32>,
+
+    #[serde(default)]
+    pub price_adjustment: f32,
+}
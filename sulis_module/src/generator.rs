@@ -17,20 +17,30 @@
 mod area_generator;
 pub use self::area_generator::AreaGenerator;
 
+mod cave_gen;
+use self::cave_gen::GenAlgorithm;
+
 mod encounter_gen;
 pub(crate) use self::encounter_gen::{EncounterGen, EncounterParams, EncounterParamsBuilder};
 
 mod feature_gen;
 use self::feature_gen::{FeatureGen, FeatureParams, FeatureParamsBuilder};
 
+mod hazard_gen;
+use self::hazard_gen::{HazardGen, HazardParams, HazardParamsBuilder};
+
 mod maze;
 use self::maze::{Maze, TileKind};
 
 mod prop_gen;
 pub(crate) use self::prop_gen::{PropGen, PropParams, PropParamsBuilder};
 
+mod room_template;
+pub use self::room_template::{RoomTemplate, RoomTemplateBuilder};
+use self::room_template::{PlacedTemplate, TemplateParams, TemplateParamsBuilder};
+
 mod terrain_gen;
-use self::terrain_gen::{TerrainGen, TerrainParams, TerrainParamsBuilder};
+use self::terrain_gen::{TerrainGen, TerrainParams, TerrainParamsBuilder, TerrainTheme, ThemeMap};
 
 mod terrain_tiles;
 pub use self::terrain_tiles::{EdgesList, TerrainTiles};
@@ -38,6 +48,9 @@ pub use self::terrain_tiles::{EdgesList, TerrainTiles};
 mod tiles_model;
 pub use self::tiles_model::{is_removal, TilesModel};
 
+mod town_gen;
+use self::town_gen::{BuildingParams, TownGen, TownParams};
+
 mod transition_gen;
 use self::transition_gen::{
     TransitionGen, TransitionOutput, TransitionParams, TransitionParamsBuilder,
@@ -46,10 +59,14 @@ use self::transition_gen::{
 mod wall_tiles;
 pub use self::wall_tiles::WallTiles;
 
+mod water_gen;
+use self::water_gen::{WaterGen, WaterOutput, WaterParams, WaterParamsBuilder};
+
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 use std::rc::Rc;
 
+use crate::area::tile::TerrainKind;
 use crate::area::{EncounterDataBuilder, Layer, LocationChecker, PathFinderGrid, PropDataBuilder};
 use crate::{ObjectSize, WallKind};
 use sulis_core::util::{Point, ReproducibleRandom};
@@ -261,6 +278,22 @@ impl GenModel {
         TileIter::new(self)
     }
 
+    /// Returns the index of the specified terrain kind within the model's
+    /// terrain kinds.  Panics if the kind is not present, as this is a bug.
+    fn terrain_index(&self, kind: &TerrainKind) -> Option<usize> {
+        let index = self
+            .model
+            .terrain_kinds()
+            .iter()
+            .position(|possible| possible.id == kind.id);
+
+        if index.is_none() {
+            error!("Invalid terrain kind '{}'.  This is a bug.", kind.id);
+            panic!();
+        }
+        index
+    }
+
     pub fn rand(&self) -> &ReproducibleRandom {
         &self.rand
     }
@@ -338,11 +371,55 @@ pub struct GeneratorBuilder {
     grid_width: u32,
     grid_height: u32,
     rooms: RoomParams,
+
+    #[serde(default)]
+    algorithm: GenAlgorithm,
+
     terrain: TerrainParamsBuilder,
     props: PropParamsBuilder,
     encounters: EncounterParamsBuilder,
     features: FeatureParamsBuilder,
     transitions: TransitionParamsBuilder,
+
+    #[serde(default)]
+    templates: TemplateParamsBuilder,
+
+    #[serde(default)]
+    water: Option<WaterParamsBuilder>,
+
+    #[serde(default)]
+    hazards: Option<HazardParamsBuilder>,
+
+    #[serde(default)]
+    validation: ValidationParams,
+}
+
+/// Checks run on each newly generated area.  An area failing them is
+/// generated again from a new seed
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationParams {
+    /// The number of times the area is generated before the last attempt
+    /// is used regardless of the checks
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// The minimum fraction of the area, from 0 to 1, that must be passable
+    #[serde(default)]
+    pub min_open_fraction: f32,
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+impl Default for ValidationParams {
+    fn default() -> ValidationParams {
+        ValidationParams {
+            max_attempts: default_max_attempts(),
+            min_open_fraction: 0.0,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
@@ -365,6 +442,49 @@ pub(crate) struct RoomParams {
     gen_corridors: bool,
     room_edge_overfill_chance: u32,
     corridor_edge_overfill_chance: u32,
+
+    /// Special rooms picked from the generated rooms, which prop and
+    /// encounter passes may target with their `room_tags`
+    #[serde(default)]
+    designations: Option<RoomDesignations>,
+}
+
+/// A special purpose assigned to a generated room
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub enum RoomTag {
+    Entrance,
+    Boss,
+    Treasure,
+}
+
+/// The entrance is the first transition room, or a random room if there
+/// are no transitions.  Distances are measured in maze cells, along the
+/// corridors and doorways connecting the rooms
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RoomDesignations {
+    /// Boss rooms are picked from the rooms furthest from the entrance
+    #[serde(default)]
+    boss: Option<DesignationRule>,
+
+    /// Treasure rooms are picked randomly from the rooms meeting the rule
+    #[serde(default)]
+    treasure: Option<DesignationRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DesignationRule {
+    /// The maximum number of rooms tagged
+    count: u32,
+
+    /// The minimum distance from the entrance
+    min_entrance_distance: u32,
+
+    /// The minimum distance from any other tagged room
+    #[serde(default)]
+    min_spacing: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
@@ -375,10 +495,11 @@ pub enum RegionKind {
     Room,
     TransitionRoom,
     Doorway,
+    TemplateRoom,
 }
 
 pub struct RegionKinds {
-    allowed: [bool; 5],
+    allowed: [bool; 6],
 }
 
 impl RegionKinds {
@@ -389,6 +510,7 @@ impl RegionKinds {
             src.contains(&RegionKind::Room),
             src.contains(&RegionKind::TransitionRoom),
             src.contains(&RegionKind::Doorway),
+            src.contains(&RegionKind::TemplateRoom),
         ];
 
         RegionKinds { allowed }
@@ -411,8 +533,14 @@ impl RegionKinds {
         let index = match kind {
             Some(TileKind::Wall) => 0,
             Some(TileKind::Corridor(_)) => 1,
-            Some(TileKind::Room { transition, .. }) => {
-                if transition {
+            Some(TileKind::Room {
+                transition,
+                template,
+                ..
+            }) => {
+                if template {
+                    5
+                } else if transition {
                     3
                 } else {
                     2
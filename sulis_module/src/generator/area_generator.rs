@@ -19,10 +19,12 @@ use std::io::{Error, ErrorKind};
 use std::rc::Rc;
 
 use crate::generator::{
-    EncounterGen, EncounterParams, FeatureGen, FeatureParams, GenModel, GeneratorBuilder,
-    GeneratorOutput, LayerListLocationChecker, Maze, PropGen, PropParams, RoomParams, TerrainGen,
-    TerrainParams, TileIter, TileKind, TilesModel, TransitionGen, TransitionOutput,
-    TransitionParams, WallKinds, WeightedList,
+    EncounterGen, EncounterParams, FeatureGen, FeatureParams, GenAlgorithm, GenModel,
+    GeneratorBuilder, GeneratorOutput, HazardGen, HazardParams, LayerListLocationChecker, Maze,
+    PropGen, PropParams, RoomParams, TemplateParams, TerrainGen, TerrainParams, ThemeMap,
+    TileIter, TileKind, TilesModel, TownGen, TownParams, TransitionGen, TransitionOutput,
+    TransitionParams, ValidationParams, WallKinds, WaterGen, WaterOutput, WaterParams,
+    WeightedList,
 };
 use crate::{
     area::{
@@ -39,11 +41,17 @@ pub struct AreaGenerator {
     grid_width: u32,
     grid_height: u32,
     room_params: RoomParams,
+    algorithm: GenAlgorithm,
     terrain_params: TerrainParams,
     prop_params: PropParams,
     encounter_params: EncounterParams,
     feature_params: FeatureParams,
     transition_params: TransitionParams,
+    template_params: TemplateParams,
+    water_params: Option<WaterParams>,
+    hazard_params: Option<HazardParams>,
+    town_params: Option<TownParams>,
+    validation: ValidationParams,
 }
 
 impl AreaGenerator {
@@ -62,6 +70,26 @@ impl AreaGenerator {
         let min_passable_size =
             Rc::clone(module.sizes.get(&builder.min_passable_size).ok_or(error)?);
 
+        if builder.rooms.invert && matches!(builder.algorithm, GenAlgorithm::Caves(_)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Cave generator {} may not invert rooms", builder.id),
+            ));
+        }
+
+        let town_params = match &builder.algorithm {
+            GenAlgorithm::Town(buildings) => {
+                if builder.rooms.invert {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Town generator {} may not invert rooms", builder.id),
+                    ));
+                }
+                Some(TownParams::new(buildings, module)?)
+            }
+            _ => None,
+        };
+
         Ok(AreaGenerator {
             id: builder.id,
             min_passable_size,
@@ -71,14 +99,37 @@ impl AreaGenerator {
             grid_width: builder.grid_width,
             grid_height: builder.grid_height,
             room_params: builder.rooms,
+            algorithm: builder.algorithm,
             terrain_params: TerrainParams::new(builder.terrain, module)?,
             prop_params: PropParams::with_module(builder.props, module)?,
             encounter_params: EncounterParams::with_module(builder.encounters, module)?,
             feature_params: FeatureParams::new(builder.features, module)?,
             transition_params: TransitionParams::new(builder.transitions, module)?,
+            template_params: TemplateParams::new(builder.templates, module)?,
+            water_params: match builder.water {
+                None => None,
+                Some(water) => Some(WaterParams::new(water, module)?),
+            },
+            hazard_params: match builder.hazards {
+                None => None,
+                Some(hazards) => Some(HazardParams::new(hazards, module)?),
+            },
+            town_params,
+            validation: builder.validation,
         })
     }
 
+    /// The checks each area generated by this generator must pass
+    pub fn validation(&self) -> ValidationParams {
+        self.validation
+    }
+
+    /// The smallest creature size that must be able to move through the
+    /// generated areas
+    pub fn min_passable_size(&self) -> &Rc<ObjectSize> {
+        &self.min_passable_size
+    }
+
     pub fn generate_transitions(
         &self,
         width: i32,
@@ -121,45 +172,96 @@ impl AreaGenerator {
                 Point::new(x, y)
             })
             .collect();
-        maze.generate(&self.room_params, model.rand_mut(), &open_locs);
+        maze.generate(
+            &self.room_params,
+            &self.algorithm,
+            &self.template_params,
+            model.rand_mut(),
+            &open_locs,
+        );
         info!("Maze generated {:?}", model.rand());
 
         self.add_walls(&mut model, &maze);
 
+        let themes = ThemeMap::new(&maze, &self.terrain_params.themes);
+
         info!("Generating terrain {:?}", model.rand());
-        let mut gen = TerrainGen::new(&mut model, &self.terrain_params, &maze);
+        let mut gen = TerrainGen::new(&mut model, &self.terrain_params, &maze, &themes);
         gen.generate();
 
-        for (tile, x, y) in tiles_to_add {
-            model.model.add(tile, x, y);
+        if let Some(town_params) = &self.town_params {
+            TownGen::new(&mut model, town_params, &maze).paint_streets();
         }
-        // add the tiles to the model
-        for p in model.tiles() {
-            model.model.check_add_wall_border(p.x, p.y);
-            model.model.check_add_terrain(p.x, p.y);
-            model.model.check_add_terrain_border(p.x, p.y);
+
+        let water = match &self.water_params {
+            None => WaterOutput::default(),
+            Some(params) => {
+                info!("Generating water {:?}", model.rand());
+                WaterGen::new(&mut model, params, &maze).generate()
+            }
+        };
+
+        // stamp in the tiles of each room template
+        let mut tiles_to_add = tiles_to_add;
+        for placed in maze.templates() {
+            let offset = Point::from(model.from_region_coords(placed.x, placed.y));
+            tiles_to_add.extend(placed.template.tiles(offset));
         }
 
+        add_tiles(&mut model, &tiles_to_add, &water.tiles);
+
         // pre-gen layers for use in the next step
         info!(
             "Tile generation complete.  Pre-Gen layers {:?}",
             model.rand()
         );
-        let layers = self.create_layers(width, height, &model.model)?;
+        let mut layers = self.create_layers(width, height, &model.model)?;
 
-        self.check_connectivity(&layers, &model, &maze)?;
+        if let Err(e) = self.check_connectivity(&layers, &model, &maze) {
+            if water.is_empty() {
+                return Err(e);
+            }
+
+            warn!("Generated water disconnects the area, removing it");
+            warn!("{}", e);
+            water.remove(&mut model.model);
+            model.model.clear();
+            add_tiles(&mut model, &tiles_to_add, &[]);
+            layers = self.create_layers(width, height, &model.model)?;
+            self.check_connectivity(&layers, &model, &maze)?;
+        }
 
         info!("Generating features {:?}", model.rand());
         let mut gen = FeatureGen::new(&mut model, &layers, &self.feature_params, &maze);
         gen.generate()?;
 
         info!("Generating props {:?}", model.rand());
-        let mut gen = PropGen::new(&mut model, &layers, &self.prop_params, &maze);
-        let props = gen.generate(&params.props.passes);
+        let mut gen = PropGen::new(&mut model, &layers, &self.prop_params, &maze, &themes);
+        let mut props = gen.generate(&params.props.passes, &self.terrain_params.themes);
+
+        if let Some(hazard_params) = &self.hazard_params {
+            info!("Generating hazards {:?}", model.rand());
+            let mut gen = HazardGen::new(&mut model, &layers, hazard_params, &maze);
+            props.extend(gen.generate());
+        }
 
         info!("Generating encounters {:?}", model.rand());
-        let mut gen = EncounterGen::new(&mut model, &layers, &self.encounter_params, &maze);
-        let encounters = gen.generate(&params.encounters.passes);
+        let mut gen =
+            EncounterGen::new(&mut model, &layers, &self.encounter_params, &maze, &themes);
+        let mut encounters = gen.generate(&params.encounters.passes, &self.terrain_params.themes);
+
+        if let Some(town_params) = &self.town_params {
+            info!("Furnishing buildings {:?}", model.rand());
+            let (doors, occupants) = TownGen::new(&mut model, town_params, &maze).furnish();
+            props.extend(doors);
+            encounters.extend(occupants);
+        }
+
+        for placed in maze.templates() {
+            let offset = Point::from(model.from_region_coords(placed.x, placed.y));
+            props.extend(placed.template.props(offset));
+            encounters.extend(placed.template.encounters(offset));
+        }
 
         info!("Final Layer Gen {:?}", model.rand());
         let layers = self.create_layers(width, height, &model.model)?;
@@ -420,6 +522,22 @@ impl AreaGenerator {
     }
 }
 
+fn add_tiles(
+    model: &mut GenModel,
+    tiles_to_add: &[(Rc<Tile>, i32, i32)],
+    overlay: &[(Rc<Tile>, i32, i32)],
+) {
+    for (tile, x, y) in tiles_to_add.iter().chain(overlay) {
+        model.model.add(Rc::clone(tile), *x, *y);
+    }
+
+    for p in model.tiles() {
+        model.model.check_add_wall_border(p.x, p.y);
+        model.model.check_add_terrain(p.x, p.y);
+        model.model.check_add_terrain_border(p.x, p.y);
+    }
+}
+
 struct WallParams {
     offset: Point,
     step: Point,
@@ -0,0 +1,170 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2019 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use crate::generator::BuildingParams;
+use sulis_core::util::{Point, ReproducibleRandom};
+
+/// The algorithm used to lay out the rooms and passages of the area
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) enum GenAlgorithm {
+    /// Rectangular rooms connected by a maze of corridors
+    #[default]
+    Maze,
+
+    /// Natural caverns grown with a cellular automaton.  Rooms are placed
+    /// in the open parts of the caves, for use by encounters and props
+    Caves(CaveParams),
+
+    /// A settlement of walled buildings separated by a grid of streets.
+    /// Each building's interior is a room, for use by encounters and props
+    Town(BuildingParams),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CaveParams {
+    /// The percentage chance of each maze cell starting as a wall
+    pub fill_chance: u32,
+
+    /// The number of smoothing steps.  More steps create smoother walls
+    pub iterations: u32,
+
+    /// During each step, a cell becomes a wall if at least this many of
+    /// its eight neighbors are walls, and is opened otherwise
+    #[serde(default = "default_wall_threshold")]
+    pub wall_threshold: u32,
+
+    /// Open areas with fewer than this many cells are filled in, rather
+    /// than connected to the rest of the caves
+    #[serde(default)]
+    pub min_region_size: u32,
+
+    /// The number of attempts at placing rooms within the caves
+    pub room_placement_attempts: u32,
+}
+
+fn default_wall_threshold() -> u32 {
+    5
+}
+
+/// Grows the caves, returning whether each maze cell is open.  The edges
+/// of the maze are always walls
+pub(crate) fn gen_open_cells(
+    width: i32,
+    height: i32,
+    params: &CaveParams,
+    rand: &mut ReproducibleRandom,
+) -> Vec<bool> {
+    let is_edge = |x: i32, y: i32| x == 0 || y == 0 || x == width - 1 || y == height - 1;
+
+    let mut open = vec![false; (width * height) as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if is_edge(x, y) {
+                continue;
+            }
+
+            open[(x + y * width) as usize] = rand.gen(1, 101) > params.fill_chance;
+        }
+    }
+
+    for _ in 0..params.iterations {
+        let mut next = vec![false; open.len()];
+        for y in 0..height {
+            for x in 0..width {
+                if is_edge(x, y) {
+                    continue;
+                }
+
+                let mut walls = 0;
+                for yi in (y - 1)..=(y + 1) {
+                    for xi in (x - 1)..=(x + 1) {
+                        if (xi, yi) != (x, y) && !open[(xi + yi * width) as usize] {
+                            walls += 1;
+                        }
+                    }
+                }
+
+                next[(x + y * width) as usize] = walls < params.wall_threshold;
+            }
+        }
+        open = next;
+    }
+
+    open
+}
+
+/// Finds each separate group of cells for which `open` is true, with cells
+/// joined by their four direct neighbors
+pub(crate) fn find_regions<F>(width: i32, height: i32, open: F) -> Vec<Vec<Point>>
+where
+    F: Fn(i32, i32) -> bool,
+{
+    let mut visited = vec![false; (width * height) as usize];
+    let mut regions = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if visited[(x + y * width) as usize] || !open(x, y) {
+                continue;
+            }
+
+            visited[(x + y * width) as usize] = true;
+            let mut region = vec![Point::new(x, y)];
+            let mut frontier = vec![Point::new(x, y)];
+            while let Some(p) = frontier.pop() {
+                for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                    let (nx, ny) = (p.x + dx, p.y + dy);
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        continue;
+                    }
+
+                    let index = (nx + ny * width) as usize;
+                    if visited[index] || !open(nx, ny) {
+                        continue;
+                    }
+
+                    visited[index] = true;
+                    region.push(Point::new(nx, ny));
+                    frontier.push(Point::new(nx, ny));
+                }
+            }
+
+            regions.push(region);
+        }
+    }
+
+    regions
+}
+
+/// The pair of points, one from each of `from` and `to`, closest to each
+/// other
+pub(crate) fn closest_points(from: &[Point], to: &[Point]) -> Option<(Point, Point)> {
+    let mut closest = None;
+    let mut closest_dist = i32::MAX;
+    for a in from {
+        for b in to {
+            let dist = (a.x - b.x).abs() + (a.y - b.y).abs();
+            if dist < closest_dist {
+                closest_dist = dist;
+                closest = Some((*a, *b));
+            }
+        }
+    }
+
+    closest
+}
@@ -19,8 +19,8 @@ use std::io::Error;
 use std::rc::Rc;
 
 use crate::generator::{
-    maze::Room, overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, WeightedEntry,
-    WeightedList,
+    maze::Room, overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, RoomTag,
+    TerrainTheme, ThemeMap, WeightedEntry, WeightedList,
 };
 use crate::{
     area::{EncounterDataBuilder, Layer},
@@ -32,6 +32,7 @@ pub struct EncounterGen<'a, 'b> {
     model: &'b mut GenModel,
     params: &'a EncounterParams,
     maze: &'b Maze,
+    themes: &'b ThemeMap,
 }
 
 impl<'a, 'b> EncounterGen<'a, 'b> {
@@ -40,21 +41,32 @@ impl<'a, 'b> EncounterGen<'a, 'b> {
         _layers: &'b [Layer],
         params: &'a EncounterParams,
         maze: &'b Maze,
+        themes: &'b ThemeMap,
     ) -> EncounterGen<'a, 'b> {
         EncounterGen {
             model,
             params,
             maze,
+            themes,
         }
     }
 
+    /// Generates encounters from the base passes and `addn_passes` outside
+    /// of any theme, and from the passes of each of the `theme_params` within
+    /// that theme
     pub(crate) fn generate(
         &mut self,
         addn_passes: &[EncounterPass],
+        theme_params: &[TerrainTheme],
     ) -> Vec<EncounterDataBuilder> {
         let mut encounters = Vec::new();
 
-        for pass in self.params.passes.iter().chain(addn_passes) {
+        let base = self.params.passes.iter().chain(addn_passes).map(|pass| (pass, None));
+        let themed = theme_params.iter().enumerate().flat_map(|(index, theme)| {
+            theme.encounters.passes.iter().map(move |pass| (pass, Some(index)))
+        });
+
+        for (pass, theme) in base.chain(themed) {
             for room in self.maze.rooms() {
                 let encounter = pass.kinds.pick(&mut self.model.rand);
 
@@ -75,6 +87,14 @@ impl<'a, 'b> EncounterGen<'a, 'b> {
                     continue;
                 }
 
+                if !self.maze.check_room_tags(&pass.room_tags, p1, p2) {
+                    continue;
+                }
+
+                if !self.themes.check_coords(theme, p1, p2) {
+                    continue;
+                }
+
                 if overlaps_any(&data, &encounters, pass.spacing as i32) {
                     continue;
                 }
@@ -89,6 +109,7 @@ impl<'a, 'b> EncounterGen<'a, 'b> {
                 id: encounter.encounter.id.to_string(),
                 location: Point::new(encounter.x, encounter.y),
                 size: Size::new(encounter.w, encounter.h),
+                flee_region: None,
             });
         }
         out
@@ -175,6 +196,7 @@ impl EncounterParams {
                 spacing: pass.spacing,
                 chance_per_room: pass.chance_per_room,
                 allowable_regions: regions,
+                room_tags: pass.room_tags,
                 size: Point::new(pass.size.0 as i32, pass.size.1 as i32),
             });
         }
@@ -187,6 +209,7 @@ pub struct EncounterPass {
     spacing: u32,
     chance_per_room: u32,
     allowable_regions: RegionKinds,
+    room_tags: Vec<RoomTag>,
     size: Point,
 }
 
@@ -204,4 +227,9 @@ pub struct EncounterPassBuilder {
     chance_per_room: u32,
     allowable_regions: Vec<RegionKind>,
     size: (u32, u32),
+
+    /// If specified, encounters are only placed in rooms with one of these
+    /// tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    room_tags: Vec<RoomTag>,
 }
@@ -0,0 +1,400 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2019 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+
+use crate::generator::{overlaps_any, GenModel, Maze, Rect, TileKind, WeightedEntry, WeightedList};
+use crate::{
+    area::{Layer, PropDataBuilder},
+    prop::Interactive,
+    Module, Prop,
+};
+use sulis_core::util::{Point, ReproducibleRandom};
+
+/// Places hidden traps in the corridors of the maze and hidden secret doors
+/// in the doorways between rooms.  Both are found by the party with
+/// detection checks against their difficulty
+pub struct HazardGen<'a, 'b> {
+    model: &'b mut GenModel,
+    layers: &'b [Layer],
+    params: &'a HazardParams,
+    maze: &'b Maze,
+}
+
+impl<'a, 'b> HazardGen<'a, 'b> {
+    pub(crate) fn new(
+        model: &'b mut GenModel,
+        layers: &'b [Layer],
+        params: &'a HazardParams,
+        maze: &'b Maze,
+    ) -> HazardGen<'a, 'b> {
+        HazardGen {
+            model,
+            layers,
+            params,
+            maze,
+        }
+    }
+
+    pub(crate) fn generate(&mut self) -> Vec<PropDataBuilder> {
+        let params = self.params;
+        let mut out = Vec::new();
+
+        if let Some(traps) = &params.traps {
+            self.gen_traps(traps, &mut out);
+        }
+
+        if let Some(doors) = &params.secret_doors {
+            self.gen_secret_doors(doors, &mut out);
+        }
+
+        info!("Generated {} traps and secret doors", out.len());
+        out
+    }
+
+    fn gen_traps(&mut self, pass: &TrapPass, out: &mut Vec<PropDataBuilder>) {
+        let (cell_w, cell_h) = (self.model.total_grid_size.x, self.model.total_grid_size.y);
+        let mut traps: Vec<HazardData> = Vec::new();
+
+        for y in 0..self.maze.height() {
+            for x in 0..self.maze.width() {
+                if !matches!(self.maze.tile(x, y), TileKind::Corridor(_)) {
+                    continue;
+                }
+
+                if self.model.rand.gen(1, 101) > pass.chance {
+                    continue;
+                }
+
+                let prop = pass.kinds.pick(&mut self.model.rand);
+                let (w, h) = (prop.size.width, prop.size.height);
+                if w > cell_w || h > cell_h {
+                    continue;
+                }
+
+                let (offset_x, offset_y) = self.model.from_region_coords(x, y);
+                let data = HazardData {
+                    prop: Rc::clone(prop),
+                    x: offset_x + self.model.rand.gen(0, cell_w - w + 1),
+                    y: offset_y + self.model.rand.gen(0, cell_h - h + 1),
+                };
+
+                if !data.is_passable(self.layers) {
+                    continue;
+                }
+
+                if overlaps_any(&data, &traps, pass.spacing as i32) {
+                    continue;
+                }
+
+                let difficulty = pass.difficulty.pick(&mut self.model.rand);
+                out.push(data.to_builder(difficulty));
+                traps.push(data);
+            }
+        }
+    }
+
+    fn gen_secret_doors(&mut self, pass: &SecretDoorPass, out: &mut Vec<PropDataBuilder>) {
+        let (cell_w, cell_h) = (self.model.total_grid_size.x, self.model.total_grid_size.y);
+        let mut blocked = Vec::new();
+
+        for y in 0..self.maze.height() {
+            for x in 0..self.maze.width() {
+                let neighbors = self.maze.neighbors(x, y);
+                if neighbors[0] != Some(TileKind::DoorWay) {
+                    continue;
+                }
+
+                let kinds = if is_room(neighbors[1]) && is_room(neighbors[3]) {
+                    &pass.north_south
+                } else if is_room(neighbors[2]) && is_room(neighbors[4]) {
+                    &pass.east_west
+                } else {
+                    continue;
+                };
+
+                if self.model.rand.gen(1, 101) > pass.chance {
+                    continue;
+                }
+
+                // never hide a door the party must pass through
+                blocked.push(Point::new(x, y));
+                if !self.is_connected(&blocked) {
+                    blocked.pop();
+                    continue;
+                }
+
+                let prop = kinds.pick(&mut self.model.rand);
+                let (offset_x, offset_y) = self.model.from_region_coords(x, y);
+                let data = HazardData {
+                    prop: Rc::clone(prop),
+                    x: offset_x + (cell_w - prop.size.width) / 2,
+                    y: offset_y + (cell_h - prop.size.height) / 2,
+                };
+
+                let difficulty = pass.difficulty.pick(&mut self.model.rand);
+                out.push(data.to_builder(difficulty));
+            }
+        }
+    }
+
+    /// Whether every open cell of the maze can be reached from every other
+    /// with the `blocked` cells closed off
+    fn is_connected(&self, blocked: &[Point]) -> bool {
+        let (width, height) = (self.maze.width(), self.maze.height());
+        let is_open = |x: i32, y: i32| {
+            self.maze.tile(x, y) != TileKind::Wall && !blocked.contains(&Point::new(x, y))
+        };
+
+        let mut total = 0;
+        let mut start = None;
+        for y in 0..height {
+            for x in 0..width {
+                if is_open(x, y) {
+                    total += 1;
+                    if start.is_none() {
+                        start = Some(Point::new(x, y));
+                    }
+                }
+            }
+        }
+
+        let start = match start {
+            None => return true,
+            Some(start) => start,
+        };
+
+        let mut visited = vec![false; (width * height) as usize];
+        visited[(start.x + start.y * width) as usize] = true;
+        let mut frontier = vec![start];
+        let mut reached = 1;
+        while let Some(p) = frontier.pop() {
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (x, y) = (p.x + dx, p.y + dy);
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    continue;
+                }
+
+                let index = (x + y * width) as usize;
+                if visited[index] || !is_open(x, y) {
+                    continue;
+                }
+
+                visited[index] = true;
+                reached += 1;
+                frontier.push(Point::new(x, y));
+            }
+        }
+
+        reached == total
+    }
+}
+
+fn is_room(kind: Option<TileKind>) -> bool {
+    matches!(kind, Some(TileKind::Room { .. }))
+}
+
+struct HazardData {
+    prop: Rc<Prop>,
+    x: i32,
+    y: i32,
+}
+
+impl Rect for HazardData {
+    fn x(&self) -> i32 {
+        self.x
+    }
+    fn y(&self) -> i32 {
+        self.y
+    }
+    fn w(&self) -> i32 {
+        self.prop.size.width
+    }
+    fn h(&self) -> i32 {
+        self.prop.size.height
+    }
+}
+
+impl HazardData {
+    fn to_builder(&self, difficulty: i32) -> PropDataBuilder {
+        PropDataBuilder {
+            id: self.prop.id.to_string(),
+            location: Point::new(self.x, self.y),
+            items: Vec::new(),
+            enabled: None,
+            hover_text: None,
+            lock: None,
+            detect_difficulty: Some(difficulty),
+        }
+    }
+}
+
+pub(crate) struct HazardParams {
+    traps: Option<TrapPass>,
+    secret_doors: Option<SecretDoorPass>,
+}
+
+impl HazardParams {
+    pub(crate) fn new(
+        builder: HazardParamsBuilder,
+        module: &Module,
+    ) -> Result<HazardParams, Error> {
+        let traps = match builder.traps {
+            None => None,
+            Some(traps) => Some(TrapPass {
+                kinds: prop_kinds(traps.kinds, module, "trap", |i| {
+                    matches!(i, Interactive::Trap { .. })
+                })?,
+                chance: traps.chance,
+                spacing: traps.spacing,
+                difficulty: traps.difficulty,
+            }),
+        };
+
+        let secret_doors = match builder.secret_doors {
+            None => None,
+            Some(doors) => {
+                let is_door = |i: &Interactive| matches!(i, Interactive::Door { .. });
+                let north_south = prop_kinds(doors.north_south, module, "door", is_door)?;
+                let east_west = prop_kinds(doors.east_west, module, "door", is_door)?;
+
+                // a hidden door must be drawn as the wall it is concealed in,
+                // rather than leaving an empty but impassable doorway
+                let entries = north_south.entries.iter().chain(east_west.entries.iter());
+                for (id, prop, _) in entries {
+                    if prop.hidden_image.is_none() {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Secret door prop '{id}' must have a hidden_image"),
+                        ));
+                    }
+                }
+
+                Some(SecretDoorPass {
+                    north_south,
+                    east_west,
+                    chance: doors.chance,
+                    difficulty: doors.difficulty,
+                })
+            }
+        };
+
+        Ok(HazardParams {
+            traps,
+            secret_doors,
+        })
+    }
+}
+
+fn prop_kinds<F>(
+    kinds: HashMap<String, WeightedEntry>,
+    module: &Module,
+    name: &str,
+    valid: F,
+) -> Result<WeightedList<Rc<Prop>>, Error>
+where
+    F: Fn(&Interactive) -> bool,
+{
+    let kinds = WeightedList::new(kinds, "Prop", |id| module.props.get(id).map(Rc::clone))?;
+
+    for (id, prop, _) in kinds.entries.iter() {
+        if !valid(&prop.interactive) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Prop '{id}' must be a {name} to be placed as a {name}"),
+            ));
+        }
+    }
+
+    Ok(kinds)
+}
+
+struct TrapPass {
+    kinds: WeightedList<Rc<Prop>>,
+    chance: u32,
+    spacing: u32,
+    difficulty: DifficultyRange,
+}
+
+struct SecretDoorPass {
+    north_south: WeightedList<Rc<Prop>>,
+    east_west: WeightedList<Rc<Prop>>,
+    chance: u32,
+    difficulty: DifficultyRange,
+}
+
+/// The range of detection difficulties, inclusive, of the placed props
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct DifficultyRange {
+    min: i32,
+    max: i32,
+}
+
+impl DifficultyRange {
+    fn pick(&self, rand: &mut ReproducibleRandom) -> i32 {
+        if self.max <= self.min {
+            return self.min;
+        }
+
+        rand.gen(self.min, self.max + 1)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct HazardParamsBuilder {
+    #[serde(default)]
+    traps: Option<TrapPassBuilder>,
+
+    #[serde(default)]
+    secret_doors: Option<SecretDoorPassBuilder>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrapPassBuilder {
+    /// The trap props, each of which must be a trap
+    kinds: HashMap<String, WeightedEntry>,
+
+    /// The percentage chance of placing a trap in each corridor cell of
+    /// the maze
+    chance: u32,
+
+    /// The minimum distance, in tiles, between traps
+    spacing: u32,
+    difficulty: DifficultyRange,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SecretDoorPassBuilder {
+    /// Door props for doorways between rooms to the north and south.  The
+    /// doors should be sized to block the doorway, and are placed at its
+    /// center.  Each must have a `hidden_image`, drawn while it is hidden
+    north_south: HashMap<String, WeightedEntry>,
+
+    /// Door props for doorways between rooms to the east and west
+    east_west: HashMap<String, WeightedEntry>,
+
+    /// The percentage chance of hiding a door in each doorway between two
+    /// rooms.  A door is only hidden if every room of the area can still
+    /// be reached without passing through it
+    chance: u32,
+    difficulty: DifficultyRange,
+}
@@ -14,18 +14,29 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::VecDeque;
+use std::rc::Rc;
 
 use indexmap::{IndexMap, IndexSet};
 
-use crate::generator::{Rect, RoomParams};
+use crate::generator::cave_gen::{self, CaveParams, GenAlgorithm};
+use crate::generator::town_gen::{Building, BuildingParams};
+use crate::generator::{
+    DesignationRule, PlacedTemplate, Rect, RoomDesignations, RoomParams, RoomTag, RoomTemplate,
+    TemplateParams,
+};
 use sulis_core::util::{Point, ReproducibleRandom};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TileKind {
     Wall,
     Corridor(usize),
-    Room { region: usize, transition: bool },
+    Room {
+        region: usize,
+        transition: bool,
+        template: bool,
+    },
     DoorWay,
 }
 
@@ -61,8 +72,15 @@ pub struct Maze {
     width: i32,
     height: i32,
     rooms: Vec<Room>,
+    templates: Vec<PlacedTemplate>,
     tiles: Vec<TileKind>,
     cur_region: usize,
+
+    /// The street cells of towns, empty for other algorithms
+    streets: Vec<bool>,
+
+    /// The index of each building's room, along with the building
+    buildings: Vec<(usize, Building)>,
 }
 
 impl Maze {
@@ -71,18 +89,46 @@ impl Maze {
             width: width as i32,
             height: height as i32,
             rooms: Vec::new(),
+            templates: Vec::new(),
             tiles: vec![TileKind::Wall; (width * height) as usize],
             cur_region: 0,
+            streets: Vec::new(),
+            buildings: Vec::new(),
         }
     }
 
     pub(crate) fn generate(
         &mut self,
         params: &RoomParams,
+        algorithm: &GenAlgorithm,
+        templates: &TemplateParams,
         rand: &mut ReproducibleRandom,
         open_locs: &[Point],
     ) {
-        self.generate_rooms(params, open_locs, rand);
+        match algorithm {
+            GenAlgorithm::Maze => self.generate_maze(params, templates, rand, open_locs),
+            GenAlgorithm::Caves(caves) => {
+                self.generate_caves(params, caves, templates, rand, open_locs)
+            }
+            GenAlgorithm::Town(town) => {
+                self.generate_town(params, town, templates, rand, open_locs)
+            }
+        }
+
+        if let Some(designations) = &params.designations {
+            self.designate_rooms(designations, rand);
+            info!("  Designated rooms {:?}", rand);
+        }
+    }
+
+    fn generate_maze(
+        &mut self,
+        params: &RoomParams,
+        templates: &TemplateParams,
+        rand: &mut ReproducibleRandom,
+        open_locs: &[Point],
+    ) {
+        self.generate_rooms(params, templates, open_locs, rand);
         info!("Generated {} total rooms {:?}", self.rooms.len(), rand);
 
         if params.gen_corridors {
@@ -97,14 +143,445 @@ impl Maze {
         }
     }
 
+    /// Grows the open caves as a single corridor region, then places the
+    /// transition and template rooms, tunnels between any separate caves,
+    /// and finally places rooms within the open cave cells
+    fn generate_caves(
+        &mut self,
+        params: &RoomParams,
+        caves: &CaveParams,
+        templates: &TemplateParams,
+        rand: &mut ReproducibleRandom,
+        open_locs: &[Point],
+    ) {
+        let open = cave_gen::gen_open_cells(self.width, self.height, caves, rand);
+        let cave_region = self.cur_region;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if open[(x + y * self.width) as usize] {
+                    self.set_tile(x, y, TileKind::Corridor(cave_region));
+                }
+            }
+        }
+        self.cur_region += 1;
+        info!("Grew caves {:?}", rand);
+
+        for loc in open_locs {
+            let room = Room::center_on(self.width, self.height, params, *loc, rand);
+            self.add_room(room, true, None);
+        }
+        self.place_templates(params, templates, open_locs, rand);
+
+        self.connect_caves(caves, cave_region);
+        info!("  Connected caves {:?}", rand);
+
+        for _ in 0..caves.room_placement_attempts {
+            let room = Room::gen(self.width, self.height, params, rand);
+            let spacing = params.min_spacing as i32;
+            if self.rooms.iter().any(|other| room.overlaps(other, spacing)) {
+                continue;
+            }
+
+            let in_cave = (room.y..(room.y + room.height)).all(|y| {
+                (room.x..(room.x + room.width))
+                    .all(|x| self.tile_checked(x, y) == Some(TileKind::Corridor(cave_region)))
+            });
+            if !in_cave {
+                continue;
+            }
+
+            self.add_room(room, false, None);
+        }
+        info!("Generated {} total rooms {:?}", self.rooms.len(), rand);
+    }
+
+    /// Opens the entire maze as a single corridor region, lays a grid of
+    /// streets across it, then places the transition and template rooms
+    /// and finally the buildings, each with a door on to a street
+    fn generate_town(
+        &mut self,
+        params: &RoomParams,
+        town: &BuildingParams,
+        templates: &TemplateParams,
+        rand: &mut ReproducibleRandom,
+        open_locs: &[Point],
+    ) {
+        let town_region = self.cur_region;
+        let spacing = town.street_spacing.max(1) as i32;
+        self.streets = vec![false; (self.width * self.height) as usize];
+        for y in 1..(self.height - 1) {
+            for x in 1..(self.width - 1) {
+                self.set_tile(x, y, TileKind::Corridor(town_region));
+                if (x - 1) % spacing == 0 || (y - 1) % spacing == 0 {
+                    self.streets[(x + y * self.width) as usize] = true;
+                }
+            }
+        }
+        self.cur_region += 1;
+
+        for loc in open_locs {
+            let room = Room::center_on(self.width, self.height, params, *loc, rand);
+            self.add_room(room, true, None);
+        }
+        self.place_templates(params, templates, open_locs, rand);
+
+        let min_size = Point::new(town.min_size.x.max(3), town.min_size.y.max(3));
+        let max_size = Point::new(town.max_size.x.max(min_size.x), town.max_size.y.max(min_size.y));
+        for _ in 0..town.placement_attempts {
+            let size = Point::new(
+                rand.gen(min_size.x, max_size.x + 1),
+                rand.gen(min_size.y, max_size.y + 1),
+            );
+            let outer = match Room::gen_sized(self.width, self.height, size, rand) {
+                None => continue,
+                Some(room) => room,
+            };
+
+            if outer.x + outer.width >= self.width || outer.y + outer.height >= self.height {
+                continue;
+            }
+
+            // buildings are kept as their interior rooms, so space the new
+            // building from their walls instead.  `overlaps` can miss rects
+            // to the right of the first one, so check both ways
+            let spacing = town.spacing.max(1) as i32;
+            let blocked = self.rooms.iter().enumerate().any(|(index, other)| {
+                if !self.buildings.iter().any(|(building, _)| *building == index) {
+                    return outer.overlaps(other, spacing) || other.overlaps(&outer, spacing);
+                }
+
+                let walls = Room {
+                    x: other.x - 1,
+                    y: other.y - 1,
+                    width: other.width + 2,
+                    height: other.height + 2,
+                    tag: None,
+                };
+                outer.overlaps(&walls, spacing) || walls.overlaps(&outer, spacing)
+            });
+            if blocked {
+                continue;
+            }
+
+            let on_street = (outer.y..(outer.y + outer.height)).any(|y| {
+                (outer.x..(outer.x + outer.width)).any(|x| self.is_street(x, y))
+            });
+            if on_street {
+                continue;
+            }
+
+            let (door, north_south) = match self.find_door(&outer, rand) {
+                None => continue,
+                Some(door) => door,
+            };
+
+            self.add_building(outer, door, north_south);
+        }
+
+        info!("Placed {} buildings {:?}", self.buildings.len(), rand);
+    }
+
+    /// Picks a random cell in the wall of the `outer` building rect, other
+    /// than the corners, that is next to a street
+    fn find_door(&self, outer: &Room, rand: &mut ReproducibleRandom) -> Option<(Point, bool)> {
+        let (x1, y1) = (outer.x, outer.y);
+        let (x2, y2) = (outer.x + outer.width - 1, outer.y + outer.height - 1);
+
+        let mut candidates = Vec::new();
+        for x in (x1 + 1)..x2 {
+            if self.is_street(x, y1 - 1) {
+                candidates.push((Point::new(x, y1), true));
+            }
+            if self.is_street(x, y2 + 1) {
+                candidates.push((Point::new(x, y2), true));
+            }
+        }
+
+        for y in (y1 + 1)..y2 {
+            if self.is_street(x1 - 1, y) {
+                candidates.push((Point::new(x1, y), false));
+            }
+            if self.is_street(x2 + 1, y) {
+                candidates.push((Point::new(x2, y), false));
+            }
+        }
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        Some(candidates[rand.gen(0, candidates.len())])
+    }
+
+    /// Walls in the `outer` rect, with a room for the interior and a
+    /// doorway at `door`
+    fn add_building(&mut self, outer: Room, door: Point, north_south: bool) {
+        for y in outer.y..(outer.y + outer.height) {
+            for x in outer.x..(outer.x + outer.width) {
+                self.set_tile(x, y, TileKind::Wall);
+            }
+        }
+
+        let interior = Room {
+            x: outer.x + 1,
+            y: outer.y + 1,
+            width: outer.width - 2,
+            height: outer.height - 2,
+            tag: None,
+        };
+        self.add_room(interior, false, None);
+        self.set_tile(door.x, door.y, TileKind::DoorWay);
+
+        let index = self.rooms.len() - 1;
+        self.buildings.push((index, Building { door, north_south }));
+    }
+
+    /// Whether the maze cell at `x`, `y` is a town street
+    pub(crate) fn is_street(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return false;
+        }
+
+        self.streets
+            .get((x + y * self.width) as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn buildings(&self) -> impl Iterator<Item = (&Room, &Building)> {
+        self.buildings
+            .iter()
+            .map(move |(index, building)| (&self.rooms[*index], building))
+    }
+
+    /// Fills in small separate caves, then tunnels from each remaining cave
+    /// to the nearest cave already connected to the largest
+    fn connect_caves(&mut self, caves: &CaveParams, cave_region: usize) {
+        let mut regions = cave_gen::find_regions(self.width, self.height, |x, y| {
+            self.tile(x, y) != TileKind::Wall
+        });
+
+        regions.retain(|region| {
+            let has_room = region
+                .iter()
+                .any(|p| matches!(self.tile(p.x, p.y), TileKind::Room { .. }));
+            if has_room || region.len() >= caves.min_region_size as usize {
+                return true;
+            }
+
+            for p in region.iter() {
+                self.set_tile(p.x, p.y, TileKind::Wall);
+            }
+            false
+        });
+
+        // sort by size, largest first, keeping the generated order for ties
+        regions.sort_by_key(|region| Reverse(region.len()));
+
+        let mut regions = regions.into_iter();
+        let mut connected = match regions.next() {
+            None => return,
+            Some(region) => region,
+        };
+
+        for region in regions {
+            let (from, to) = match cave_gen::closest_points(&region, &connected) {
+                None => continue,
+                Some(points) => points,
+            };
+
+            let mut p = from;
+            while p != to {
+                if p.x != to.x {
+                    p.x += (to.x - p.x).signum();
+                } else {
+                    p.y += (to.y - p.y).signum();
+                }
+
+                if self.tile(p.x, p.y) == TileKind::Wall {
+                    self.set_tile(p.x, p.y, TileKind::Corridor(cave_region));
+                    connected.push(p);
+                }
+            }
+
+            connected.extend(region);
+        }
+    }
+
+    /// Tags the entrance room, then the boss and treasure rooms according
+    /// to the `params`
+    fn designate_rooms(&mut self, params: &RoomDesignations, rand: &mut ReproducibleRandom) {
+        if self.rooms.is_empty() {
+            return;
+        }
+
+        let entrance = match self.rooms.iter().position(|room| self.is_transition(room)) {
+            Some(index) => index,
+            None => rand.gen(0, self.rooms.len()),
+        };
+        self.rooms[entrance].tag = Some(RoomTag::Entrance);
+        let mut designated = vec![self.distances_from(entrance)];
+
+        if let Some(rule) = &params.boss {
+            self.designate(RoomTag::Boss, rule, &mut designated, None);
+        }
+
+        if let Some(rule) = &params.treasure {
+            self.designate(RoomTag::Treasure, rule, &mut designated, Some(rand));
+        }
+    }
+
+    /// Tags up to `rule.count` rooms with the `tag`, each meeting the
+    /// distance requirements of the `rule`.  The rooms are picked with
+    /// `rand` if it is specified, otherwise the furthest from the entrance
+    /// are picked
+    fn designate(
+        &mut self,
+        tag: RoomTag,
+        rule: &DesignationRule,
+        designated: &mut Vec<Vec<i32>>,
+        mut rand: Option<&mut ReproducibleRandom>,
+    ) {
+        let mut count = 0;
+        for _ in 0..rule.count {
+            let mut candidates = Vec::new();
+            for (index, room) in self.rooms.iter().enumerate() {
+                if room.tag.is_some() || self.is_transition(room) {
+                    continue;
+                }
+
+                let entrance_dist = match self.room_dist(room, &designated[0]) {
+                    None => continue,
+                    Some(dist) => dist,
+                };
+
+                if entrance_dist < rule.min_entrance_distance as i32 {
+                    continue;
+                }
+
+                let spacing = rule.min_spacing as i32;
+                if designated[1..]
+                    .iter()
+                    .any(|dists| self.room_dist(room, dists).is_some_and(|dist| dist < spacing))
+                {
+                    continue;
+                }
+
+                candidates.push((index, entrance_dist));
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            let index = match rand.as_mut() {
+                Some(rand) => candidates[rand.gen(0, candidates.len())].0,
+                None => candidates.iter().max_by_key(|(_, dist)| *dist).unwrap().0,
+            };
+
+            self.rooms[index].tag = Some(tag);
+            designated.push(self.distances_from(index));
+            count += 1;
+        }
+
+        info!("Designated {} {:?} rooms", count, tag);
+    }
+
+    fn is_transition(&self, room: &Room) -> bool {
+        matches!(
+            self.tile(room.x, room.y),
+            TileKind::Room {
+                transition: true,
+                ..
+            }
+        )
+    }
+
+    /// The distance of each maze cell from the room at `index`, moving
+    /// through any non wall cells, or -1 for cells that cannot be reached
+    fn distances_from(&self, index: usize) -> Vec<i32> {
+        let room = &self.rooms[index];
+        let mut dists = vec![-1; (self.width * self.height) as usize];
+        let mut frontier = VecDeque::new();
+        for y in room.y..(room.y + room.height) {
+            for x in room.x..(room.x + room.width) {
+                dists[(x + y * self.width) as usize] = 0;
+                frontier.push_back(Point::new(x, y));
+            }
+        }
+
+        while let Some(p) = frontier.pop_front() {
+            let dist = dists[(p.x + p.y * self.width) as usize];
+            for dir in DIRECTIONS.iter() {
+                let next = dir.add(p, 1);
+                if next.x < 0 || next.y < 0 || next.x >= self.width || next.y >= self.height {
+                    continue;
+                }
+
+                let next_index = (next.x + next.y * self.width) as usize;
+                if dists[next_index] != -1 || self.tile(next.x, next.y) == TileKind::Wall {
+                    continue;
+                }
+
+                dists[next_index] = dist + 1;
+                frontier.push_back(next);
+            }
+        }
+
+        dists
+    }
+
+    /// The smallest of the `dists` of the maze cells in the `room`, or None
+    /// if none of them are reachable
+    fn room_dist(&self, room: &Room, dists: &[i32]) -> Option<i32> {
+        let mut min = None;
+        for y in room.y..(room.y + room.height) {
+            for x in room.x..(room.x + room.width) {
+                let dist = dists[(x + y * self.width) as usize];
+                if dist >= 0 && min.is_none_or(|min| dist < min) {
+                    min = Some(dist);
+                }
+            }
+        }
+        min
+    }
+
+    /// The tag of the room containing the maze cell at `x`, `y`, if any
+    pub(crate) fn room_tag(&self, x: i32, y: i32) -> Option<RoomTag> {
+        self.rooms
+            .iter()
+            .find(|room| room.contains_cell(x, y))
+            .and_then(|room| room.tag)
+    }
+
+    /// Whether all maze cells between `p1` and `p2` are within rooms tagged
+    /// with one of the `tags`.  Always true if `tags` is empty
+    pub(crate) fn check_room_tags(&self, tags: &[RoomTag], p1: Point, p2: Point) -> bool {
+        if tags.is_empty() {
+            return true;
+        }
+
+        for y in p1.y..=p2.y {
+            for x in p1.x..=p2.x {
+                match self.room_tag(x, y) {
+                    Some(tag) if tags.contains(&tag) => (),
+                    _ => return false,
+                }
+            }
+        }
+
+        true
+    }
+
     fn remove_dead_ends(&mut self, params: &RoomParams, rand: &mut ReproducibleRandom) {
         let mut did_work = true;
         while did_work {
             did_work = false;
             for y in 0..self.height {
                 for x in 0..self.width {
-                    if self.tile(x, y) == TileKind::Wall {
-                        continue;
+                    match self.tile(x, y) {
+                        TileKind::Wall | TileKind::Room { template: true, .. } => continue,
+                        _ => (),
                     }
 
                     let mut exits = 0;
@@ -232,14 +709,18 @@ impl Maze {
     fn generate_rooms(
         &mut self,
         params: &RoomParams,
+        templates: &TemplateParams,
         open_locs: &[Point],
         rand: &mut ReproducibleRandom,
     ) {
         if !params.invert {
             for loc in open_locs {
                 let room = Room::center_on(self.width, self.height, params, *loc, rand);
-                self.add_room(room, true);
+                self.add_room(room, true, None);
             }
+
+            // place templates before other rooms so they are not crowded out
+            self.place_templates(params, templates, open_locs, rand);
         }
 
         debug!(
@@ -269,7 +750,45 @@ impl Maze {
                 continue;
             }
 
-            self.add_room(room, false);
+            self.add_room(room, false, None);
+        }
+    }
+
+    fn place_templates(
+        &mut self,
+        params: &RoomParams,
+        templates: &TemplateParams,
+        open_locs: &[Point],
+        rand: &mut ReproducibleRandom,
+    ) {
+        for pass in templates.passes.iter() {
+            let mut count = 0;
+            for _ in 0..pass.placement_attempts {
+                if count >= pass.max_count {
+                    break;
+                }
+
+                let template = pass.kinds.pick(rand);
+                let room = match Room::gen_sized(self.width, self.height, template.size, rand) {
+                    None => continue,
+                    Some(room) => room,
+                };
+
+                let spacing = params.min_spacing as i32;
+                if self.rooms.iter().any(|other| room.overlaps(other, spacing)) {
+                    continue;
+                }
+
+                let min_dist = pass.min_transition_distance;
+                if open_locs.iter().any(|p| room.dist(*p) < min_dist) {
+                    continue;
+                }
+
+                self.add_room(room, false, Some(Rc::clone(template)));
+                count += 1;
+            }
+
+            info!("Placed {} room templates", count);
         }
     }
 
@@ -343,7 +862,7 @@ impl Maze {
         }
     }
 
-    fn add_room(&mut self, room: Room, transition: bool) {
+    fn add_room(&mut self, room: Room, transition: bool, template: Option<Rc<RoomTemplate>>) {
         for yi in room.y..(room.y + room.height) {
             for xi in room.x..(room.x + room.width) {
                 self.set_tile(
@@ -352,6 +871,7 @@ impl Maze {
                     TileKind::Room {
                         region: self.cur_region,
                         transition,
+                        template: template.is_some(),
                     },
                 );
             }
@@ -359,6 +879,14 @@ impl Maze {
 
         self.cur_region += 1;
 
+        if let Some(template) = template {
+            self.templates.push(PlacedTemplate {
+                template,
+                x: room.x,
+                y: room.y,
+            });
+        }
+
         self.rooms.push(room);
     }
 
@@ -366,6 +894,10 @@ impl Maze {
         self.rooms.iter()
     }
 
+    pub(crate) fn templates(&self) -> impl Iterator<Item = &PlacedTemplate> {
+        self.templates.iter()
+    }
+
     /// Returns an array of the tilekind of the specified tile and its 4 neighbors.
     /// In order: self (center), North, East, South, West
     pub fn neighbors(&self, x: i32, y: i32) -> [Option<TileKind>; 5] {
@@ -418,6 +950,7 @@ pub struct Room {
     pub y: i32,
     pub width: i32,
     pub height: i32,
+    pub tag: Option<RoomTag>,
 }
 
 impl Rect for Room {
@@ -453,7 +986,44 @@ impl Room {
             y,
             width,
             height,
+            tag: None,
+        }
+    }
+
+    /// Generates a room of exactly the specified `size`, or None if it
+    /// does not fit in the area
+    fn gen_sized(
+        area_width: i32,
+        area_height: i32,
+        size: Point,
+        rand: &mut ReproducibleRandom,
+    ) -> Option<Room> {
+        if area_width - size.x < 2 || area_height - size.y < 2 {
+            return None;
         }
+
+        let x = (rand.gen(0, area_width - size.x) / 2) * 2 + 1;
+        let y = (rand.gen(0, area_height - size.y) / 2) * 2 + 1;
+
+        Some(Room {
+            x,
+            y,
+            width: size.x,
+            height: size.y,
+            tag: None,
+        })
+    }
+
+    pub(crate) fn contains_cell(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// The distance from the nearest point in this room to `p`, counting
+    /// diagonal steps as one
+    fn dist(&self, p: Point) -> i32 {
+        let dx = (self.x - p.x).max(p.x - (self.x + self.width - 1)).max(0);
+        let dy = (self.y - p.y).max(p.y - (self.y + self.height - 1)).max(0);
+        dx.max(dy)
     }
 
     fn center_on(
@@ -19,7 +19,8 @@ use std::io::Error;
 use std::rc::Rc;
 
 use crate::generator::{
-    overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, WeightedEntry, WeightedList,
+    overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, RoomTag, TerrainTheme, ThemeMap,
+    WeightedEntry, WeightedList,
 };
 use crate::{
     area::{Layer, PropDataBuilder},
@@ -32,6 +33,7 @@ pub struct PropGen<'a, 'b> {
     layers: &'b [Layer],
     params: &'a PropParams,
     maze: &'b Maze,
+    themes: &'b ThemeMap,
 }
 
 impl<'a, 'b> PropGen<'a, 'b> {
@@ -40,22 +42,33 @@ impl<'a, 'b> PropGen<'a, 'b> {
         layers: &'b [Layer],
         params: &'a PropParams,
         maze: &'b Maze,
+        themes: &'b ThemeMap,
     ) -> PropGen<'a, 'b> {
         PropGen {
             model,
             layers,
             params,
             maze,
+            themes,
         }
     }
 
+    /// Generates props from the base passes and `addn_passes` outside of
+    /// any theme, and from the passes of each of the `theme_params` within
+    /// that theme
     pub(crate) fn generate(
         &mut self,
         addn_passes: &[PropPass],
+        theme_params: &[TerrainTheme],
     ) -> Vec<PropDataBuilder> {
         let mut props = Vec::new();
 
-        for pass in self.params.passes.iter().chain(addn_passes) {
+        let base = self.params.passes.iter().chain(addn_passes).map(|pass| (pass, None));
+        let themed = theme_params.iter().enumerate().flat_map(|(index, theme)| {
+            theme.props.passes.iter().map(move |pass| (pass, Some(index)))
+        });
+
+        for (pass, theme) in base.chain(themed) {
             for _ in 0..pass.placement_attempts {
                 let prop = pass.kinds.pick(&mut self.model.rand);
                 let (w, h) = (self.model.area_width, self.model.area_height);
@@ -75,6 +88,14 @@ impl<'a, 'b> PropGen<'a, 'b> {
                     continue;
                 }
 
+                if !self.maze.check_room_tags(&pass.room_tags, p1, p2) {
+                    continue;
+                }
+
+                if !self.themes.check_coords(theme, p1, p2) {
+                    continue;
+                }
+
                 if overlaps_any(&data, &props, pass.spacing as i32) {
                     continue;
                 }
@@ -91,6 +112,8 @@ impl<'a, 'b> PropGen<'a, 'b> {
                 items: Vec::new(),
                 enabled: None,
                 hover_text: None,
+                lock: None,
+                detect_difficulty: None,
             });
         }
         out
@@ -161,6 +184,7 @@ impl PropParams {
                 spacing: pass.spacing,
                 placement_attempts: pass.placement_attempts,
                 allowable_regions: regions,
+                room_tags: pass.room_tags,
                 require_passable: pass.require_passable,
             });
         }
@@ -173,6 +197,7 @@ pub struct PropPass {
     spacing: u32,
     placement_attempts: u32,
     allowable_regions: RegionKinds,
+    room_tags: Vec<RoomTag>,
     require_passable: bool,
 }
 
@@ -190,6 +215,10 @@ pub struct PropPassBuilder {
     placement_attempts: u32,
     allowable_regions: Vec<RegionKind>,
 
+    /// If specified, props are only placed in rooms with one of these tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    room_tags: Vec<RoomTag>,
+
     #[serde(default)]
     require_passable: bool,
 }
@@ -0,0 +1,208 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2019 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+
+use crate::generator::{WeightedEntry, WeightedList};
+use crate::{
+    area::{EncounterDataBuilder, PropDataBuilder, Tile},
+    Module,
+};
+use sulis_core::util::Point;
+
+/// A hand authored room, which is placed in the maze as a whole room by
+/// generators that reference it.  Tiles, props, and encounters are
+/// positioned in tiles relative to the top left corner of the room
+pub struct RoomTemplate {
+    pub id: String,
+
+    /// The size of the room, in maze cells.  Both dimensions are odd, so
+    /// the room lines up with generated corridors
+    pub size: Point,
+    tiles: Vec<(Rc<Tile>, Point)>,
+    props: Vec<PropDataBuilder>,
+    encounters: Vec<EncounterDataBuilder>,
+}
+
+impl RoomTemplate {
+    pub fn new(builder: RoomTemplateBuilder, module: &Module) -> Result<RoomTemplate, Error> {
+        if builder.size.x < 1 || builder.size.y < 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Room template size must be positive in '{}'", builder.id),
+            ));
+        }
+
+        if builder.size.x % 2 == 0 || builder.size.y % 2 == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Room template size must be odd in '{}'", builder.id),
+            ));
+        }
+
+        let mut tiles = Vec::new();
+        for (id, points) in builder.tiles {
+            let tile = module.tiles.get(&id).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid tile '{}' in room template '{}'", id, builder.id),
+                )
+            })?;
+
+            for p in points {
+                tiles.push((Rc::clone(tile), p));
+            }
+        }
+        // sort so the tiles are added in the same order on each generation
+        tiles.sort_by(|a, b| (&a.0.id, a.1.y, a.1.x).cmp(&(&b.0.id, b.1.y, b.1.x)));
+
+        for prop in builder.props.iter() {
+            if !module.props.contains_key(&prop.id) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid prop '{}' in room template '{}'", prop.id, builder.id),
+                ));
+            }
+        }
+
+        for enc in builder.encounters.iter() {
+            if !module.encounters.contains_key(&enc.id) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid encounter '{}' in room template '{}'", enc.id, builder.id),
+                ));
+            }
+        }
+
+        Ok(RoomTemplate {
+            id: builder.id,
+            size: builder.size,
+            tiles,
+            props: builder.props,
+            encounters: builder.encounters,
+        })
+    }
+
+    /// The tiles of this template, offset to the room with its top left
+    /// corner at `offset`
+    pub fn tiles(&self, offset: Point) -> impl Iterator<Item = (Rc<Tile>, i32, i32)> + '_ {
+        self.tiles
+            .iter()
+            .map(move |(tile, p)| (Rc::clone(tile), p.x + offset.x, p.y + offset.y))
+    }
+
+    /// The props of this template, offset to the room with its top left
+    /// corner at `offset`
+    pub fn props(&self, offset: Point) -> impl Iterator<Item = PropDataBuilder> + '_ {
+        self.props.iter().map(move |prop| {
+            let mut prop = prop.clone();
+            prop.location.add_mut(offset.x, offset.y);
+            prop
+        })
+    }
+
+    /// The encounters of this template, offset to the room with its top
+    /// left corner at `offset`
+    pub fn encounters(&self, offset: Point) -> impl Iterator<Item = EncounterDataBuilder> + '_ {
+        self.encounters.iter().map(move |enc| {
+            let mut enc = enc.clone();
+            enc.location.add_mut(offset.x, offset.y);
+            enc
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoomTemplateBuilder {
+    pub id: String,
+    pub size: Point,
+
+    /// The positions of each tile, keyed by tile ID
+    #[serde(default)]
+    pub tiles: HashMap<String, Vec<Point>>,
+
+    #[serde(default)]
+    pub props: Vec<PropDataBuilder>,
+
+    #[serde(default)]
+    pub encounters: Vec<EncounterDataBuilder>,
+}
+
+/// A room template placed in the maze, at the specified maze coordinates
+pub(crate) struct PlacedTemplate {
+    pub template: Rc<RoomTemplate>,
+    pub x: i32,
+    pub y: i32,
+}
+
+pub(crate) struct TemplateParams {
+    pub passes: Vec<TemplatePass>,
+}
+
+impl TemplateParams {
+    pub(crate) fn new(
+        builder: TemplateParamsBuilder,
+        module: &Module,
+    ) -> Result<TemplateParams, Error> {
+        let mut passes = Vec::new();
+
+        for pass in builder.passes {
+            let kinds = WeightedList::new(pass.kinds, "RoomTemplate", |id| {
+                module.room_templates.get(id).map(Rc::clone)
+            })?;
+
+            passes.push(TemplatePass {
+                kinds,
+                placement_attempts: pass.placement_attempts,
+                max_count: pass.max_count,
+                min_transition_distance: pass.min_transition_distance as i32,
+            });
+        }
+
+        Ok(TemplateParams { passes })
+    }
+}
+
+pub(crate) struct TemplatePass {
+    pub kinds: WeightedList<Rc<RoomTemplate>>,
+    pub placement_attempts: u32,
+    pub max_count: u32,
+    pub min_transition_distance: i32,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TemplateParamsBuilder {
+    passes: Vec<TemplatePassBuilder>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TemplatePassBuilder {
+    kinds: HashMap<String, WeightedEntry>,
+    placement_attempts: u32,
+
+    /// The maximum number of templates placed by this pass
+    max_count: u32,
+
+    /// The minimum distance, in maze cells, between a placed template and
+    /// any transition
+    #[serde(default)]
+    min_transition_distance: u32,
+}
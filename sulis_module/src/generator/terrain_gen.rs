@@ -19,7 +19,8 @@ use std::io::Error;
 
 use crate::area::tile::TerrainKind;
 use crate::generator::{
-    overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, WeightedEntry, WeightedList,
+    overlaps_any, EncounterParams, EncounterParamsBuilder, GenModel, Maze, PropParams,
+    PropParamsBuilder, Rect, RegionKind, RegionKinds, TileKind, WeightedEntry, WeightedList,
 };
 use crate::Module;
 use sulis_core::ui::Border;
@@ -29,6 +30,7 @@ pub struct TerrainGen<'a, 'b> {
     model: &'b mut GenModel,
     params: &'a TerrainParams,
     maze: &'b Maze,
+    themes: &'b ThemeMap,
 }
 
 impl<'a, 'b> TerrainGen<'a, 'b> {
@@ -36,30 +38,54 @@ impl<'a, 'b> TerrainGen<'a, 'b> {
         model: &'b mut GenModel,
         params: &'a TerrainParams,
         maze: &'b Maze,
+        themes: &'b ThemeMap,
     ) -> TerrainGen<'a, 'b> {
         TerrainGen {
             model,
             params,
             maze,
+            themes,
         }
     }
 
     pub fn generate(&mut self) {
-        let picks = self.params.base_kinds.pick(&mut self.model.rand);
-        let base_terrain = self.get_terrain_tiles(picks);
+        let params = self.params;
+        let picks = params.base_kinds.pick(&mut self.model.rand);
+        let base_terrain = self.model.terrain_index(picks);
+
+        let mut theme_terrain = Vec::new();
+        for theme in params.themes.iter() {
+            let picks = theme.base_kinds.pick(&mut self.model.rand);
+            theme_terrain.push(self.model.terrain_index(picks));
+        }
+
         for p in self.model.tiles() {
-            self.model.model.set_terrain_index(p.x, p.y, base_terrain);
+            let (x, y) = self.model.to_region_coords(p.x, p.y);
+            let terrain = match self.themes.theme(x, y) {
+                None => base_terrain,
+                Some(index) => theme_terrain[index],
+            };
+            self.model.model.set_terrain_index(p.x, p.y, terrain);
         }
 
         let mut patches = Vec::new();
-        for i in 0..self.params.patch_passes.len() {
-            self.gen_patch_pass(i, &mut patches);
+        for pass in params.patch_passes.iter() {
+            self.gen_patch_pass(pass, None, &mut patches);
         }
-    }
 
-    fn gen_patch_pass(&mut self, pass: usize, patches: &mut Vec<Feature>) {
-        let pass = &self.params.patch_passes[pass];
+        for (index, theme) in params.themes.iter().enumerate() {
+            for pass in theme.patch_passes.iter() {
+                self.gen_patch_pass(pass, Some(index), &mut patches);
+            }
+        }
+    }
 
+    fn gen_patch_pass(
+        &mut self,
+        pass: &FeaturePass,
+        theme: Option<usize>,
+        patches: &mut Vec<Feature>,
+    ) {
         let gw = self.model.model.grid_width;
         let gh = self.model.model.grid_height;
 
@@ -80,6 +106,10 @@ impl<'a, 'b> TerrainGen<'a, 'b> {
                 continue;
             }
 
+            if !self.themes.check_coords(theme, p1, p2) {
+                continue;
+            }
+
             if overlaps_any(&patch, patches, pass.spacing as i32) {
                 continue;
             }
@@ -89,7 +119,7 @@ impl<'a, 'b> TerrainGen<'a, 'b> {
 
         for patch in patches.iter().skip(skip) {
             let picks = pass.kinds.pick(&mut self.model.rand);
-            let terrain = self.get_terrain_tiles(picks);
+            let terrain = self.model.terrain_index(picks);
 
             self.do_patch_area(
                 patch,
@@ -200,24 +230,30 @@ impl<'a, 'b> TerrainGen<'a, 'b> {
             false
         }
     }
-
-    fn get_terrain_tiles(&self, kind: &TerrainKind) -> Option<usize> {
-        let model = &self.model.model;
-
-        for (index, possible_kind) in model.terrain_kinds().iter().enumerate() {
-            if kind.id == possible_kind.id {
-                return Some(index);
-            }
-        }
-
-        error!("Invalid terrain kind '{}'.  This is a bug.", kind.id);
-        panic!()
-    }
 }
 
 pub(crate) struct TerrainParams {
     base_kinds: WeightedList<TerrainKind>,
     patch_passes: Vec<FeaturePass>,
+    pub themes: Vec<TerrainTheme>,
+}
+
+/// A set of terrain, props, and encounters used in place of the base
+/// terrain and the generator's props and encounters, within part of the area
+pub(crate) struct TerrainTheme {
+    id: String,
+    min: (f32, f32),
+    max: (f32, f32),
+    base_kinds: WeightedList<TerrainKind>,
+    patch_passes: Vec<FeaturePass>,
+    pub props: PropParams,
+    pub encounters: EncounterParams,
+}
+
+impl TerrainTheme {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.min.0 && x < self.max.0 && y >= self.min.1 && y < self.max.1
+    }
 }
 
 pub(crate) struct FeaturePass {
@@ -240,35 +276,156 @@ impl TerrainParams {
             module.terrain_kind(id)
         })?;
 
-        let mut passes = Vec::new();
-        for pass_bldr in builder.patch_passes {
-            let kinds =
-                WeightedList::new(pass_bldr.kinds, "TerrainKind", |id| module.terrain_kind(id))?;
-
-            passes.push(FeaturePass {
-                kinds,
-                min_size: Point::new(pass_bldr.min_size.0 as i32, pass_bldr.min_size.1 as i32),
-                max_size: Point::new(pass_bldr.max_size.0 as i32, pass_bldr.max_size.1 as i32),
-                spacing: pass_bldr.spacing,
-                placement_attempts: pass_bldr.placement_attempts,
-                edge_underfill_chance: pass_bldr.edge_underfill_chance,
-                allowable_regions: RegionKinds::new(pass_bldr.allowable_regions),
-                border_walls_by: pass_bldr.border_walls_by,
+        let mut themes = Vec::new();
+        for theme in builder.themes {
+            let base_kinds =
+                WeightedList::new(theme.base_kinds, "TerrainKind", |id| module.terrain_kind(id))?;
+
+            themes.push(TerrainTheme {
+                id: theme.id,
+                min: theme.min,
+                max: theme.max,
+                base_kinds,
+                patch_passes: build_passes(theme.patch_passes, module)?,
+                props: PropParams::with_module(theme.props, module)?,
+                encounters: EncounterParams::with_module(theme.encounters, module)?,
             });
         }
 
         Ok(TerrainParams {
             base_kinds,
-            patch_passes: passes,
+            patch_passes: build_passes(builder.patch_passes, module)?,
+            themes,
         })
     }
 }
 
+fn build_passes(
+    builders: Vec<FeaturePassBuilder>,
+    module: &Module,
+) -> Result<Vec<FeaturePass>, Error> {
+    let mut passes = Vec::new();
+    for pass_bldr in builders {
+        let kinds =
+            WeightedList::new(pass_bldr.kinds, "TerrainKind", |id| module.terrain_kind(id))?;
+
+        passes.push(FeaturePass {
+            kinds,
+            min_size: Point::new(pass_bldr.min_size.0 as i32, pass_bldr.min_size.1 as i32),
+            max_size: Point::new(pass_bldr.max_size.0 as i32, pass_bldr.max_size.1 as i32),
+            spacing: pass_bldr.spacing,
+            placement_attempts: pass_bldr.placement_attempts,
+            edge_underfill_chance: pass_bldr.edge_underfill_chance,
+            allowable_regions: RegionKinds::new(pass_bldr.allowable_regions),
+            border_walls_by: pass_bldr.border_walls_by,
+        });
+    }
+    Ok(passes)
+}
+
+/// The terrain theme assigned to each maze cell.  Cells of a room are all
+/// assigned based on the center of the room, so that a room is never split
+/// between themes.  Cells with no theme use the base terrain
+pub(crate) struct ThemeMap {
+    width: i32,
+    height: i32,
+    themes: Vec<Option<usize>>,
+}
+
+impl ThemeMap {
+    pub(crate) fn new(maze: &Maze, themes: &[TerrainTheme]) -> ThemeMap {
+        let (width, height) = (maze.width(), maze.height());
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (cx, cy) = ThemeMap::cell_center(maze, x, y);
+                let (fx, fy) = (cx / width as f32, cy / height as f32);
+                cells.push(themes.iter().position(|theme| theme.contains(fx, fy)));
+            }
+        }
+
+        for (index, theme) in themes.iter().enumerate() {
+            let count = cells.iter().filter(|t| **t == Some(index)).count();
+            info!("Assigned {} cells to terrain theme '{}'", count, theme.id);
+        }
+
+        ThemeMap {
+            width,
+            height,
+            themes: cells,
+        }
+    }
+
+    fn cell_center(maze: &Maze, x: i32, y: i32) -> (f32, f32) {
+        if let TileKind::Room { .. } = maze.tile(x, y) {
+            if let Some(room) = maze.rooms().find(|room| room.contains_cell(x, y)) {
+                let cx = room.x as f32 + room.width as f32 / 2.0;
+                let cy = room.y as f32 + room.height as f32 / 2.0;
+                return (cx, cy);
+            }
+        }
+
+        (x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    /// The theme of the maze cell at `x`, `y`.  Coordinates outside the
+    /// maze use the nearest cell
+    pub(crate) fn theme(&self, x: i32, y: i32) -> Option<usize> {
+        if self.themes.is_empty() {
+            return None;
+        }
+
+        let x = x.max(0).min(self.width - 1);
+        let y = y.max(0).min(self.height - 1);
+        self.themes[(x + y * self.width) as usize]
+    }
+
+    /// Returns true if every maze cell between `p1` and `p2`, inclusive,
+    /// has the specified `theme`
+    pub(crate) fn check_coords(&self, theme: Option<usize>, p1: Point, p2: Point) -> bool {
+        for y in p1.y..=p2.y {
+            for x in p1.x..=p2.x {
+                if self.theme(x, y) != theme {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct TerrainParamsBuilder {
     base_kinds: HashMap<String, WeightedEntry>,
     patch_passes: Vec<FeaturePassBuilder>,
+
+    #[serde(default)]
+    themes: Vec<TerrainThemeBuilder>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TerrainThemeBuilder {
+    id: String,
+
+    /// The top left and bottom right corners of the part of the area using
+    /// this theme, as fractions of the area size.  Where themes overlap, the
+    /// first listed is used
+    min: (f32, f32),
+    max: (f32, f32),
+
+    base_kinds: HashMap<String, WeightedEntry>,
+
+    #[serde(default)]
+    patch_passes: Vec<FeaturePassBuilder>,
+
+    #[serde(default)]
+    props: PropParamsBuilder,
+
+    #[serde(default)]
+    encounters: EncounterParamsBuilder,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,244 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2019 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+
+use crate::area::{tile::TerrainKind, EncounterDataBuilder, PropDataBuilder};
+use crate::generator::{GenModel, Maze, WeightedEntry, WeightedList};
+use crate::{prop::Interactive, Encounter, Module, Prop};
+use sulis_core::util::{Point, Size};
+
+/// Furnishes the buildings laid out by the town algorithm, placing a door
+/// in each building's doorway and spawning its occupants, and paints the
+/// streets between the buildings
+pub struct TownGen<'a, 'b> {
+    model: &'b mut GenModel,
+    params: &'a TownParams,
+    maze: &'b Maze,
+}
+
+impl<'a, 'b> TownGen<'a, 'b> {
+    pub(crate) fn new(
+        model: &'b mut GenModel,
+        params: &'a TownParams,
+        maze: &'b Maze,
+    ) -> TownGen<'a, 'b> {
+        TownGen {
+            model,
+            params,
+            maze,
+        }
+    }
+
+    /// Sets the terrain of the street cells, if street terrain is specified
+    pub(crate) fn paint_streets(&mut self) {
+        let kinds = match &self.params.street_kinds {
+            None => return,
+            Some(kinds) => kinds,
+        };
+
+        let kind = kinds.pick(&mut self.model.rand);
+        let index = self.model.terrain_index(kind);
+
+        for p in self.model.tiles() {
+            let (x, y) = self.model.to_region_coords(p.x, p.y);
+            if self.maze.is_street(x, y) {
+                self.model.model.set_terrain_index(p.x, p.y, index);
+            }
+        }
+    }
+
+    /// Returns the door props and occupant encounters of each building
+    pub(crate) fn furnish(&mut self) -> (Vec<PropDataBuilder>, Vec<EncounterDataBuilder>) {
+        let (cell_w, cell_h) = (self.model.total_grid_size.x, self.model.total_grid_size.y);
+        let mut props = Vec::new();
+        let mut encounters = Vec::new();
+
+        for (room, building) in self.maze.buildings() {
+            let kinds = if building.north_south {
+                &self.params.north_south
+            } else {
+                &self.params.east_west
+            };
+
+            let door = kinds.pick(&mut self.model.rand);
+            let (door_x, door_y) = self.model.from_region_coords(building.door.x, building.door.y);
+            props.push(PropDataBuilder {
+                id: door.id.to_string(),
+                location: Point::new(
+                    door_x + (cell_w - door.size.width) / 2,
+                    door_y + (cell_h - door.size.height) / 2,
+                ),
+                items: Vec::new(),
+                enabled: None,
+                hover_text: None,
+                lock: None,
+                detect_difficulty: None,
+            });
+
+            let (x, y) = self.model.from_region_coords(room.x, room.y);
+            for pass in self.params.occupants.iter() {
+                let encounter = pass.kinds.pick(&mut self.model.rand);
+                if self.model.rand.gen(1, 101) > pass.chance_per_building {
+                    continue;
+                }
+
+                encounters.push(EncounterDataBuilder {
+                    id: encounter.id.to_string(),
+                    location: Point::new(x, y),
+                    size: Size::new(room.width * cell_w, room.height * cell_h),
+                    flee_region: None,
+                });
+            }
+        }
+
+        info!("Furnished {} buildings", props.len());
+        (props, encounters)
+    }
+}
+
+pub(crate) struct TownParams {
+    street_kinds: Option<WeightedList<TerrainKind>>,
+    north_south: WeightedList<Rc<Prop>>,
+    east_west: WeightedList<Rc<Prop>>,
+    occupants: Vec<OccupantPass>,
+}
+
+impl TownParams {
+    pub(crate) fn new(builder: &BuildingParams, module: &Module) -> Result<TownParams, Error> {
+        let street_kinds = if builder.street_kinds.is_empty() {
+            None
+        } else {
+            Some(WeightedList::new(
+                builder.street_kinds.clone(),
+                "TerrainKind",
+                |id| module.terrain_kind(id),
+            )?)
+        };
+
+        let mut occupants = Vec::new();
+        for pass in builder.occupants.iter() {
+            let kinds = WeightedList::new(pass.kinds.clone(), "Encounter", |id| {
+                module.encounters.get(id).map(Rc::clone)
+            })?;
+
+            occupants.push(OccupantPass {
+                kinds,
+                chance_per_building: pass.chance_per_building,
+            });
+        }
+
+        Ok(TownParams {
+            street_kinds,
+            north_south: door_kinds(&builder.doors.north_south, module)?,
+            east_west: door_kinds(&builder.doors.east_west, module)?,
+            occupants,
+        })
+    }
+}
+
+fn door_kinds(
+    kinds: &HashMap<String, WeightedEntry>,
+    module: &Module,
+) -> Result<WeightedList<Rc<Prop>>, Error> {
+    let kinds = WeightedList::new(kinds.clone(), "Prop", |id| module.props.get(id).map(Rc::clone))?;
+
+    for (id, prop, _) in kinds.entries.iter() {
+        if !matches!(prop.interactive, Interactive::Door { .. }) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Building door prop '{id}' must be a door"),
+            ));
+        }
+    }
+
+    Ok(kinds)
+}
+
+struct OccupantPass {
+    kinds: WeightedList<Rc<Encounter>>,
+    chance_per_building: u32,
+}
+
+/// A building laid out by the town algorithm.  Its interior is a room of
+/// the maze, surrounded by a one cell wall
+pub(crate) struct Building {
+    /// The maze cell in the building's wall holding the door
+    pub door: Point,
+
+    /// Whether the door is in the north or south wall, rather than the
+    /// east or west
+    pub north_south: bool,
+}
+
+/// Parameters for the town algorithm, which lays out a grid of streets
+/// with rectangular buildings between them.  Each building has a single
+/// door opening on to a street.  All sizes are in maze cells
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BuildingParams {
+    /// The distance from each street to the next parallel street
+    pub street_spacing: u32,
+
+    /// The minimum size of a building, including its walls.  Must be at
+    /// least 3 by 3
+    pub min_size: Point,
+
+    /// The maximum size of a building, including its walls
+    pub max_size: Point,
+
+    pub placement_attempts: u32,
+
+    /// The minimum open space between buildings.  At least one cell is
+    /// always kept open
+    #[serde(default)]
+    pub spacing: u32,
+
+    /// The terrain kinds painted on the streets.  If not specified, the
+    /// streets use the terrain of the rest of the area
+    #[serde(default)]
+    street_kinds: HashMap<String, WeightedEntry>,
+
+    doors: BuildingDoors,
+
+    /// Encounters spawned inside the buildings, such as townsfolk and
+    /// merchants
+    #[serde(default)]
+    occupants: Vec<OccupantPassBuilder>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuildingDoors {
+    /// Door props for doors in the north or south wall of a building
+    north_south: HashMap<String, WeightedEntry>,
+
+    /// Door props for doors in the east or west wall of a building
+    east_west: HashMap<String, WeightedEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OccupantPassBuilder {
+    /// The encounters, one of which is picked for each building.  The
+    /// encounter covers the whole interior of the building
+    kinds: HashMap<String, WeightedEntry>,
+
+    /// The percentage chance of spawning an encounter in each building
+    chance_per_building: u32,
+}
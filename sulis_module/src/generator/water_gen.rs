@@ -0,0 +1,332 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2019 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::rc::Rc;
+
+use crate::area::{tile::TerrainKind, Tile};
+use crate::generator::{
+    GenModel, Maze, RegionKind, RegionKinds, TileKind, TilesModel, WeightedEntry, WeightedList,
+};
+use crate::Module;
+use sulis_core::util::{Point, ReproducibleRandom};
+
+/// Carves lakes and rivers into the terrain of the area.  Water crossing a
+/// corridor or doorway is covered with a bridge, so the rooms of the maze
+/// stay connected
+pub struct WaterGen<'a, 'b> {
+    model: &'b mut GenModel,
+    params: &'a WaterParams,
+    maze: &'b Maze,
+}
+
+impl<'a, 'b> WaterGen<'a, 'b> {
+    pub(crate) fn new(
+        model: &'b mut GenModel,
+        params: &'a WaterParams,
+        maze: &'b Maze,
+    ) -> WaterGen<'a, 'b> {
+        WaterGen {
+            model,
+            params,
+            maze,
+        }
+    }
+
+    pub(crate) fn generate(&mut self) -> WaterOutput {
+        let params = self.params;
+        let kind = params.kinds.pick(&mut self.model.rand);
+        let water_terrain = self.model.terrain_index(kind);
+        let shore_terrain = match &params.shore_kinds {
+            None => None,
+            Some(kinds) => {
+                let kind = kinds.pick(&mut self.model.rand);
+                self.model.terrain_index(kind)
+            }
+        };
+
+        let gw = self.model.model.grid_width;
+        let gh = self.model.model.grid_height;
+        let width = self.model.area_width / gw + 1;
+        let height = self.model.area_height / gh + 1;
+        let scale = params.noise_scale as f32;
+        let lakes = ValueNoise::new(&mut self.model.rand, width, height, scale);
+        let rivers = ValueNoise::new(&mut self.model.rand, width, height, scale);
+
+        let mut output = WaterOutput::default();
+        let mut water = vec![false; (width * height) as usize];
+        for p in self.model.tiles() {
+            let (x, y) = (p.x / gw, p.y / gh);
+            if !self.can_fill(p) || !params.is_water(&lakes, &rivers, x, y) {
+                continue;
+            }
+
+            let overlay = if self.on_path(p) {
+                match &params.bridge {
+                    None => continue,
+                    Some(bridge) => Some(bridge),
+                }
+            } else {
+                params.swim.as_ref()
+            };
+
+            water[(x + y * width) as usize] = true;
+            output.set_terrain(&mut self.model.model, p, water_terrain);
+            if let Some(tile) = overlay {
+                output.tiles.push((Rc::clone(tile), p.x, p.y));
+            }
+        }
+
+        if shore_terrain.is_some() {
+            let dist = params.shore_width as i32;
+            for p in self.model.tiles() {
+                let (x, y) = (p.x / gw, p.y / gh);
+                if water[(x + y * width) as usize] || !self.can_fill(p) {
+                    continue;
+                }
+
+                let mut near_water = false;
+                for yi in (y - dist).max(0)..=(y + dist).min(height - 1) {
+                    for xi in (x - dist).max(0)..=(x + dist).min(width - 1) {
+                        near_water |= water[(xi + yi * width) as usize];
+                    }
+                }
+
+                if near_water {
+                    output.set_terrain(&mut self.model.model, p, shore_terrain);
+                }
+            }
+        }
+
+        info!("Generated {} water and shore cells", output.terrain.len());
+        output
+    }
+
+    fn can_fill(&self, p: Point) -> bool {
+        if self.model.model.is_wall(p.x, p.y) {
+            return false;
+        }
+
+        let (x, y) = self.model.to_region_coords(p.x, p.y);
+        self.params
+            .allowable_regions
+            .is_allowable(self.maze.tile_checked(x, y))
+    }
+
+    fn on_path(&self, p: Point) -> bool {
+        let (x, y) = self.model.to_region_coords(p.x, p.y);
+        matches!(
+            self.maze.tile_checked(x, y),
+            Some(TileKind::Corridor(_)) | Some(TileKind::DoorWay)
+        )
+    }
+}
+
+/// The changes made by the water pass, which may be undone if the water
+/// disconnects the rooms of the area
+#[derive(Default)]
+pub(crate) struct WaterOutput {
+    /// The original terrain of each changed terrain cell
+    terrain: Vec<(Point, Option<usize>)>,
+
+    /// Bridge and swim tiles to be added over the water
+    pub tiles: Vec<(Rc<Tile>, i32, i32)>,
+}
+
+impl WaterOutput {
+    fn set_terrain(&mut self, model: &mut TilesModel, p: Point, index: Option<usize>) {
+        self.terrain.push((p, model.terrain_index_at(p.x, p.y)));
+        model.set_terrain_index(p.x, p.y, index);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.terrain.is_empty()
+    }
+
+    /// Restores the terrain changed by the water pass.  Any tiles already
+    /// added to the model must be cleared separately
+    pub(crate) fn remove(self, model: &mut TilesModel) {
+        for (p, index) in self.terrain.into_iter().rev() {
+            model.set_terrain_index(p.x, p.y, index);
+        }
+    }
+}
+
+/// Random values on a lattice, smoothly interpolated between the lattice
+/// points, which are `scale` cells apart
+struct ValueNoise {
+    width: i32,
+    scale: f32,
+    values: Vec<f32>,
+}
+
+impl ValueNoise {
+    fn new(rand: &mut ReproducibleRandom, width: i32, height: i32, scale: f32) -> ValueNoise {
+        let width = (width as f32 / scale).ceil() as i32 + 2;
+        let height = (height as f32 / scale).ceil() as i32 + 2;
+        let values = (0..width * height).map(|_| rand.gen(0.0f32, 1.0)).collect();
+
+        ValueNoise {
+            width,
+            scale,
+            values,
+        }
+    }
+
+    /// The noise value, from 0 to 1, at the cell `x`, `y`
+    fn get(&self, x: i32, y: i32) -> f32 {
+        let fx = (x as f32 + 0.5) / self.scale;
+        let fy = (y as f32 + 0.5) / self.scale;
+        let (x0, y0) = (fx.floor() as i32, fy.floor() as i32);
+        let tx = smooth(fx - x0 as f32);
+        let ty = smooth(fy - y0 as f32);
+
+        let top = lerp(self.value(x0, y0), self.value(x0 + 1, y0), tx);
+        let bottom = lerp(self.value(x0, y0 + 1), self.value(x0 + 1, y0 + 1), tx);
+        lerp(top, bottom, ty)
+    }
+
+    fn value(&self, x: i32, y: i32) -> f32 {
+        self.values[(x + y * self.width) as usize]
+    }
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+pub(crate) struct WaterParams {
+    kinds: WeightedList<TerrainKind>,
+    shore_kinds: Option<WeightedList<TerrainKind>>,
+    shore_width: u32,
+    noise_scale: u32,
+    lake_threshold: u32,
+    river_width: u32,
+    allowable_regions: RegionKinds,
+    bridge: Option<Rc<Tile>>,
+    swim: Option<Rc<Tile>>,
+}
+
+impl WaterParams {
+    pub(crate) fn new(builder: WaterParamsBuilder, module: &Module) -> Result<WaterParams, Error> {
+        let kinds = WeightedList::new(builder.kinds, "TerrainKind", |id| module.terrain_kind(id))?;
+
+        let shore_kinds = if builder.shore_kinds.is_empty() {
+            None
+        } else {
+            Some(WeightedList::new(builder.shore_kinds, "TerrainKind", |id| {
+                module.terrain_kind(id)
+            })?)
+        };
+
+        if builder.noise_scale == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Water noise scale must be positive",
+            ));
+        }
+
+        Ok(WaterParams {
+            kinds,
+            shore_kinds,
+            shore_width: builder.shore_width,
+            noise_scale: builder.noise_scale,
+            lake_threshold: builder.lake_threshold,
+            river_width: builder.river_width,
+            allowable_regions: RegionKinds::new(builder.allowable_regions),
+            bridge: overlay_tile(builder.bridge, module)?,
+            swim: overlay_tile(builder.swim, module)?,
+        })
+    }
+
+    fn is_water(&self, lakes: &ValueNoise, rivers: &ValueNoise, x: i32, y: i32) -> bool {
+        if self.lake_threshold < 100 && lakes.get(x, y) * 100.0 >= self.lake_threshold as f32 {
+            return true;
+        }
+
+        let river_dist = (rivers.get(x, y) - 0.5).abs() * 100.0;
+        river_dist < self.river_width as f32 / 2.0
+    }
+}
+
+fn overlay_tile(id: Option<String>, module: &Module) -> Result<Option<Rc<Tile>>, Error> {
+    let id = match id {
+        None => return Ok(None),
+        Some(id) => id,
+    };
+
+    let tile = module.tiles.get(&id).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid water tile '{id}'"),
+        )
+    })?;
+
+    if !tile.override_impass {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Water tile '{id}' must override impassability"),
+        ));
+    }
+
+    Ok(Some(Rc::clone(tile)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WaterParamsBuilder {
+    /// The terrain kinds used for water, which should be impassable
+    kinds: HashMap<String, WeightedEntry>,
+
+    /// The terrain kinds laid around the water.  Shorelines are drawn by
+    /// the terrain borders between these and the water and base terrain
+    #[serde(default)]
+    shore_kinds: HashMap<String, WeightedEntry>,
+
+    /// The width of the shore, in terrain cells
+    #[serde(default)]
+    shore_width: u32,
+
+    /// The distance, in terrain cells, over which the water noise varies.
+    /// Larger values create larger lakes and straighter rivers
+    noise_scale: u32,
+
+    /// Cells where the lake noise is at least this value, from 0 to 100,
+    /// become lakes.  100 for no lakes
+    lake_threshold: u32,
+
+    /// Cells where the river noise is within half this value of its
+    /// midpoint, from 0 to 100, become rivers.  0 for no rivers
+    river_width: u32,
+
+    allowable_regions: Vec<RegionKind>,
+
+    /// A tile overriding impassability, placed over water crossing the
+    /// corridors and doorways of the maze.  If not set, water is not placed
+    /// in corridors or doorways
+    #[serde(default)]
+    bridge: Option<String>,
+
+    /// A tile overriding impassability, placed over all other water to make
+    /// it passable by swimming.  If not set, the water is impassable
+    #[serde(default)]
+    swim: Option<String>,
+}
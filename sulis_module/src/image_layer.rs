@@ -90,6 +90,26 @@ impl ImageLayer {
     }
 }
 
+/// An image drawn in an `ImageLayer` in place of the base actor image, such as
+/// from an equipped item.  Multiple inserts in the same layer are drawn on top
+/// of each other in ascending `order`, each shifted by its `offset` in tiles
+#[derive(Clone, Debug)]
+pub struct LayerInsert {
+    pub image: Rc<dyn Image>,
+    pub order: i32,
+    pub offset: (f32, f32),
+}
+
+impl LayerInsert {
+    pub fn new(image: Rc<dyn Image>) -> LayerInsert {
+        LayerInsert {
+            image,
+            order: 0,
+            offset: (0.0, 0.0),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ImageLayerSet {
     images: HashMap<Sex, HashMap<ImageLayer, Rc<dyn Image>>>,
@@ -152,14 +172,15 @@ impl ImageLayerSet {
     }
 
     /// Gets the list of images from this ImageLayerSet for the given Sex,
-    /// with the additional images inserted
+    /// with the additional images inserted.  Any inserts for a layer replace
+    /// the base image for that layer
     pub fn get_list_with(
         &self,
         sex: Sex,
         race: &Rc<Race>,
         hair: Option<Color>,
         skin: Option<Color>,
-        insert: HashMap<ImageLayer, Rc<dyn Image>>,
+        insert: HashMap<ImageLayer, Vec<LayerInsert>>,
     ) -> Vec<Layer> {
         let mut list = Vec::new();
 
@@ -230,28 +251,35 @@ fn get_color(layer: ImageLayer, hair: Option<Color>, skin: Option<Color>) -> Opt
 
 fn insert_for_race_sex(
     list: &mut Vec<Layer>,
-    insert: &HashMap<ImageLayer, Rc<dyn Image>>,
+    insert: &HashMap<ImageLayer, Vec<LayerInsert>>,
     sex: Sex,
     race: &Rc<Race>,
     layer: ImageLayer,
     base_size: Option<(f32, f32)>,
 ) -> bool {
-    let (mut x, mut y) = match race.get_image_layer_offset(layer) {
+    let (base_x, base_y) = match race.get_image_layer_offset(layer) {
         None => return true,
         Some((x, y)) => (*x, *y),
     };
 
-    match insert.get(&layer) {
-        Some(image) => {
-            if let Some((width_base, height_base)) = base_size {
-                x -= (image.get_width_f32() - width_base) / 2.0;
-                y -= (image.get_height_f32() - height_base) / 2.0;
-            }
+    let mut inserts: Vec<&LayerInsert> = match insert.get(&layer) {
+        None => return false,
+        Some(inserts) if inserts.is_empty() => return false,
+        Some(inserts) => inserts.iter().collect(),
+    };
+    inserts.sort_by_key(|insert| insert.order);
 
-            let out = Layer::new(x, y, None, race.image_for_sex(sex, image));
-            list.push(out);
-            true
+    for insert in inserts {
+        let image = &insert.image;
+        let mut x = base_x + insert.offset.0;
+        let mut y = base_y + insert.offset.1;
+        if let Some((width_base, height_base)) = base_size {
+            x -= (image.get_width_f32() - width_base) / 2.0;
+            y -= (image.get_height_f32() - height_base) / 2.0;
         }
-        None => false,
+
+        let out = Layer::new(x, y, None, race.image_for_sex(sex, image));
+        list.push(out);
     }
+    true
 }
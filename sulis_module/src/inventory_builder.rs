@@ -45,6 +45,9 @@ pub struct ItemSaveState {
 
     #[serde(default)]
     pub variant: Option<usize>,
+
+    #[serde(default)]
+    pub wear: u32,
 }
 
 impl ItemSaveState {
@@ -60,6 +63,7 @@ impl ItemSaveState {
             id: item.item.original_id.clone(),
             adjectives,
             variant: item.variant,
+            wear: item.wear,
         }
     }
 }
@@ -170,7 +174,7 @@ impl InventoryBuilder {
                 return None;
             }
 
-            Some((slot, ItemState::new(item, item_save.variant)))
+            Some((slot, ItemState::load(item, item_save)))
         })
     }
 
@@ -208,7 +212,7 @@ impl InventoryBuilder {
                 }
             }
 
-            Some((slot, ItemState::new(item, item_save.variant)))
+            Some((slot, ItemState::load(item, item_save)))
         })
     }
 }
\ No newline at end of file
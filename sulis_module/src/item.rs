@@ -27,7 +27,8 @@ use sulis_core::util::unable_to_create_error;
 
 use crate::{
     ability::{AIData, Duration},
-    Actor, ImageLayer, ItemAdjective, Module, PrereqList, PrereqListBuilder,
+    Actor, ImageLayer, ItemAdjective, LayerInsert, LightSource, Module, PrereqList,
+    PrereqListBuilder, Race,
 };
 
 #[derive(Deserialize, Debug, Clone)]
@@ -38,6 +39,10 @@ pub struct Equippable {
     pub blocks_slot: Option<Slot>,
     pub bonuses: BonusList,
     pub attack: Option<AttackBuilder>,
+
+    /// A light emitted by the wearer while this item is equipped
+    #[serde(default)]
+    pub light: Option<LightSource>,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +92,15 @@ pub struct Item {
     image: HashMap<ImageLayer, Rc<dyn Image>>,
     alternate_image: HashMap<ImageLayer, Rc<dyn Image>>,
     variants: Vec<Variant>,
+
+    /// The order this item's images are drawn in, relative to other equipped
+    /// items with images in the same layer
+    pub image_order: i32,
+    race_image_offsets: HashMap<String, (f32, f32)>,
+
+    /// The maximum number of this item which may be held in a single stack,
+    /// or None for no limit
+    pub max_stack: Option<u32>,
 }
 
 fn build_hash_map(
@@ -164,7 +178,10 @@ impl Item {
             original_equippable: item.original_equippable.clone(),
             builder_adjectives: item.builder_adjectives.clone(),
             added_adjectives,
+            image_order: item.image_order,
+            race_image_offsets: item.race_image_offsets.clone(),
             variants: item.variants.clone(),
+            max_stack: item.max_stack,
         }
     }
 
@@ -260,9 +277,18 @@ impl Item {
             builder_adjectives: adjectives,
             added_adjectives: Vec::new(),
             variants,
+            image_order: builder.image_order,
+            race_image_offsets: builder.race_image_offsets,
+            max_stack: builder.max_stack.map(|max| max.max(1)),
         })
     }
 
+    /// Returns the maximum number of this item which may be held in a
+    /// single stack
+    pub fn max_stack(&self) -> u32 {
+        self.max_stack.unwrap_or(u32::MAX)
+    }
+
     pub fn num_variants(&self) -> usize {
         self.variants.len()
     }
@@ -296,6 +322,38 @@ impl Item {
         }
     }
 
+    /// Returns the images to draw for this item when equipped by an actor of
+    /// the specified `race`.  If `alternate` is true, the item is in its
+    /// alternate slot and the alternate images are used.
+    pub fn layer_inserts(
+        &self,
+        variant: Option<usize>,
+        alternate: bool,
+        race: &Race,
+    ) -> Vec<(ImageLayer, LayerInsert)> {
+        let offset = self
+            .race_image_offsets
+            .get(&race.id)
+            .copied()
+            .unwrap_or((0.0, 0.0));
+
+        let iter = if alternate {
+            self.alt_image_iter(variant)
+        } else {
+            self.image_iter(variant)
+        };
+
+        iter.map(|(layer, image)| {
+            let insert = LayerInsert {
+                image: Rc::clone(image),
+                order: self.image_order,
+                offset,
+            };
+            (*layer, insert)
+        })
+        .collect()
+    }
+
     pub fn image_iter(&self, variant: Option<usize>) -> Iter<ImageLayer, Rc<dyn Image>> {
         match variant {
             None => self.image.iter(),
@@ -440,6 +498,20 @@ pub struct ItemBuilder {
 
     #[serde(default)]
     variants: Vec<VariantBuilder>,
+
+    /// The order this item's images are drawn in, relative to other equipped
+    /// items with images in the same layer.  Lower values are drawn first
+    #[serde(default)]
+    image_order: i32,
+
+    /// Offsets, in tiles, applied to this item's images for each race ID
+    #[serde(default)]
+    race_image_offsets: HashMap<String, (f32, f32)>,
+
+    /// The maximum number of this item held in a single inventory stack.
+    /// Additional items are placed in new stacks.  Unlimited if not specified
+    #[serde(default)]
+    max_stack: Option<u32>,
 }
 
 pub fn format_item_value(value: i32) -> String {
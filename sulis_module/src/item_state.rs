@@ -17,18 +17,24 @@
 use std::collections::hash_map::Iter;
 use std::rc::Rc;
 
-use crate::{ImageLayer, Item, Module};
+use crate::{ImageLayer, Item, ItemKind, ItemSaveState, LayerInsert, Module, Race};
 use sulis_core::image::Image;
 
 #[derive(Debug, Clone)]
 pub struct ItemState {
     pub item: Rc<Item>,
     pub variant: Option<usize>,
+
+    /// The amount of durability this item has lost.  Only armor degrades,
+    /// and only when the rules specify durability
+    pub wear: u32,
 }
 
 impl PartialEq for ItemState {
     fn eq(&self, other: &ItemState) -> bool {
-        Rc::ptr_eq(&self.item, &other.item) && self.variant == other.variant
+        Rc::ptr_eq(&self.item, &other.item)
+            && self.variant == other.variant
+            && self.wear == other.wear
     }
 }
 
@@ -38,6 +44,7 @@ impl ItemState {
             None => ItemState {
                 item,
                 variant: None,
+                wear: 0,
             },
             Some(idx) => {
                 if idx >= item.num_variants() {
@@ -45,14 +52,83 @@ impl ItemState {
                     ItemState {
                         item,
                         variant: None,
+                        wear: 0,
                     }
                 } else {
-                    ItemState { item, variant }
+                    ItemState {
+                        item,
+                        variant,
+                        wear: 0,
+                    }
                 }
             }
         }
     }
 
+    /// Creates the item state for the already created `item`, with the variant
+    /// and wear from the `save`
+    pub fn load(item: Rc<Item>, save: &ItemSaveState) -> ItemState {
+        let mut item_state = ItemState::new(item, save.variant);
+        item_state.wear = save.wear;
+        item_state
+    }
+
+    pub fn has_durability(&self) -> bool {
+        Module::rules().durability.is_some() && matches!(self.item.kind, ItemKind::Armor { .. })
+    }
+
+    /// Returns the current and maximum durability of this item, or None if
+    /// it does not degrade
+    pub fn durability(&self) -> Option<(u32, u32)> {
+        if !self.has_durability() {
+            return None;
+        }
+
+        let rules = Module::rules();
+        let max = rules.durability.as_ref()?.max;
+        Some((max.saturating_sub(self.wear), max))
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.has_durability() && self.wear > 0
+    }
+
+    /// Removes up to `amount` durability from this item, if it degrades
+    pub fn add_wear(&mut self, amount: u32) {
+        if let Some((_, max)) = self.durability() {
+            self.wear = (self.wear + amount).min(max);
+        }
+    }
+
+    /// Restores `amount` durability, or all durability if `amount` is None
+    pub fn repair(&mut self, amount: Option<u32>) {
+        match amount {
+            None => self.wear = 0,
+            Some(amount) => self.wear = self.wear.saturating_sub(amount),
+        }
+    }
+
+    /// The multiplier applied to the armor bonuses of this item based
+    /// on its condition
+    pub fn armor_multiplier(&self) -> f32 {
+        let rules = Module::rules();
+        match (&rules.durability, self.durability()) {
+            (Some(durability), Some((cur, _))) => durability.armor_multiplier(cur),
+            _ => 1.0,
+        }
+    }
+
+    /// The base cost to fully repair this item, before any merchant modifiers
+    pub fn repair_cost(&self) -> f32 {
+        let rules = Module::rules();
+        match &rules.durability {
+            Some(durability) if self.is_damaged() => {
+                self.item.value as f32 * durability.repair_cost_per_point * self.wear as f32
+            }
+            _ => 0.0,
+        }
+    }
+
     pub fn from(id: &str) -> Option<ItemState> {
         Module::item(id).map(|i| ItemState::new(i, None))
     }
@@ -65,6 +141,10 @@ impl ItemState {
         self.item.alt_image_iter(self.variant)
     }
 
+    pub fn layer_inserts(&self, alternate: bool, race: &Race) -> Vec<(ImageLayer, LayerInsert)> {
+        self.item.layer_inserts(self.variant, alternate, race)
+    }
+
     pub fn icon(&self) -> Rc<dyn Image> {
         self.item.icon(self.variant)
     }
@@ -24,6 +24,9 @@ extern crate serde_derive;
 pub mod ability;
 pub use self::ability::Ability;
 
+pub mod achievement;
+pub use self::achievement::Achievement;
+
 pub mod ability_list;
 pub use self::ability_list::AbilityList;
 
@@ -31,13 +34,16 @@ pub mod actor;
 pub use self::actor::Actor;
 pub use self::actor::ActorBuilder;
 pub use self::actor::Faction;
+pub use self::actor::Facing;
 pub use self::actor::Sex;
+pub use self::actor::SpriteState;
 
 pub mod ai;
 pub use self::ai::AITemplate;
 
 pub mod area;
 pub use self::area::Area;
+pub use self::area::LightSource;
 
 pub mod class;
 pub use self::class::Class;
@@ -62,13 +68,20 @@ pub use self::encounter::Encounter;
 pub mod campaign;
 pub use self::campaign::Campaign;
 pub use self::campaign::CampaignGroup;
+pub use self::campaign::ScriptLimits;
+
+pub mod encounter_table;
+pub use self::encounter_table::EncounterTable;
+
+pub mod faction;
 
 pub mod generator;
-use self::generator::{AreaGenerator, GeneratorBuilder};
+use self::generator::{AreaGenerator, GeneratorBuilder, RoomTemplate, RoomTemplateBuilder};
 
 pub mod image_layer;
 pub use self::image_layer::ImageLayer;
 pub use self::image_layer::ImageLayerSet;
+pub use self::image_layer::LayerInsert;
 
 pub mod inventory_builder;
 pub use self::inventory_builder::InventoryBuilder;
@@ -92,6 +105,9 @@ pub use self::loot_list::LootList;
 pub mod modification;
 pub use self::modification::ModificationInfo;
 
+pub mod module_manifest;
+pub use self::module_manifest::ModuleManifest;
+
 pub mod prereq_list;
 pub use self::prereq_list::PrereqList;
 pub use self::prereq_list::PrereqListBuilder;
@@ -109,8 +125,9 @@ pub mod rules;
 pub use self::rules::bonus;
 pub use self::rules::{
     AccuracyKind, Armor, ArmorKind, Attack, AttackBonuses, AttackKind, Attribute, AttributeList,
-    Bonus, BonusKind, BonusList, Damage, DamageKind, DamageList, HitFlags, HitKind, ItemKind,
-    QuickSlot, Resistance, Rules, Slot, StatList, Time, WeaponKind, WeaponStyle, ROUND_TIME_MILLIS,
+    Bonus, BonusKind, BonusList, Cover, Damage, DamageKind, DamageList, Difficulty,
+    DifficultyLevel, DifficultyScaling, HitFlags, HitKind, ItemKind, QuickSlot, Resistance, Rules,
+    Slot, StatList, Time, WeaponKind, WeaponStyle, XpDistribution, ROUND_TIME_MILLIS,
 };
 
 use std::cell::RefCell;
@@ -119,7 +136,7 @@ use std::ffi::OsStr;
 use std::fmt::{self, Display};
 use std::fs;
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time;
 
@@ -134,12 +151,15 @@ use self::area::{
     tile::{Feature, TerrainKind, TerrainRules, WallKind, WallRules},
     Tileset,
 };
+use self::achievement::AchievementBuilder;
 use self::area::{AreaBuilder, Tile};
 use self::campaign::CampaignBuilder;
 use self::class::ClassBuilder;
 use self::conversation::ConversationBuilder;
 use self::cutscene::CutsceneBuilder;
 use self::encounter::EncounterBuilder;
+use self::encounter_table::EncounterTableBuilder;
+use self::faction::{FactionBuilder, FactionDefinition};
 use self::item::ItemBuilder;
 use self::loot_list::LootListBuilder;
 use self::object_size::ObjectSizeBuilder;
@@ -158,6 +178,7 @@ pub struct Module {
     campaign: Option<Rc<Campaign>>,
     abilities: HashMap<String, Rc<Ability>>,
     ability_lists: HashMap<String, Rc<AbilityList>>,
+    achievements: HashMap<String, Rc<Achievement>>,
     actors: HashMap<String, Rc<Actor>>,
     ai_templates: HashMap<String, Rc<AITemplate>>,
     areas: HashMap<String, Rc<Area>>,
@@ -165,6 +186,8 @@ pub struct Module {
     conversations: HashMap<String, Rc<Conversation>>,
     cutscenes: HashMap<String, Rc<Cutscene>>,
     encounters: HashMap<String, Rc<Encounter>>,
+    encounter_tables: HashMap<String, Rc<EncounterTable>>,
+    factions: HashMap<String, Rc<FactionDefinition>>,
     items: HashMap<String, Rc<Item>>,
     item_adjectives: HashMap<String, Rc<ItemAdjective>>,
     loot_lists: HashMap<String, Rc<LootList>>,
@@ -182,6 +205,7 @@ pub struct Module {
     wall_kinds: Vec<WallKind>,
 
     generators: HashMap<String, Rc<AreaGenerator>>,
+    room_templates: HashMap<String, Rc<RoomTemplate>>,
 
     root_dir: Option<String>,
     init: bool,
@@ -194,6 +218,7 @@ pub struct ModuleInfo {
     pub name: String,
     pub description: String,
     pub group: CampaignGroup,
+    pub manifest: ModuleManifest,
 }
 
 impl ModuleInfo {
@@ -212,12 +237,14 @@ impl ModuleInfo {
             Some(group) => group,
         };
 
+        let manifest = ModuleManifest::from_dir(&path_str);
         Ok(ModuleInfo {
             id: campaign.id,
             dir: path_str,
             name: campaign.name,
             description: campaign.description,
             group,
+            manifest,
         })
     }
 }
@@ -228,6 +255,15 @@ impl Display for ModuleInfo {
     }
 }
 
+/// A reason an exported character may not be used to start the current campaign
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidCharacter {
+    Level { level: u32, max_level: u32 },
+    Race(String),
+    Class(String),
+    Attribute { attribute: Attribute, value: u8 },
+}
+
 macro_rules! getters {
     ($($name:ident, $plural:ident, $kind:ty);*) => {
         $(
@@ -267,6 +303,8 @@ impl Module {
             Err(e) => warn!("Unable to read modules from '{:?}': {}", user_dir, e),
         }
 
+        dirs.append(&mut modification::user_content().modules);
+
         for dir in dirs {
             match ModuleInfo::from_dir(dir.clone()) {
                 Ok(module) => modules.push(module),
@@ -363,9 +401,71 @@ impl Module {
         actors
     }
 
+    /// Checks that the specified character, typically imported from another
+    /// campaign, is valid under the current campaign and its rules.  The
+    /// character's level must not exceed the maximum starting level, its race
+    /// and classes must be selectable, and its attributes must be in the range
+    /// available from character creation and level ups.
+    pub fn validate_character(actor: &Actor) -> Result<(), InvalidCharacter> {
+        let max_level = Module::campaign().max_starting_level;
+        if actor.total_level > max_level {
+            return Err(InvalidCharacter::Level {
+                level: actor.total_level,
+                max_level,
+            });
+        }
+
+        let rules = Module::rules();
+        if !rules.selectable_races.contains(&actor.race.id) {
+            return Err(InvalidCharacter::Race(actor.race.name.to_string()));
+        }
+
+        for (class, _) in actor.levels.iter() {
+            if !rules.selectable_classes.contains(&class.id) {
+                return Err(InvalidCharacter::Class(class.name.to_string()));
+            }
+        }
+
+        let level_up_points = rules
+            .level_up_attribute_levels
+            .iter()
+            .filter(|level| **level <= actor.total_level)
+            .count() as i32;
+        let min = rules.builder_min_attribute;
+        let max = rules.builder_max_attribute + level_up_points;
+        for attribute in Attribute::iter() {
+            let value = actor.attributes.get(*attribute);
+            if (value as i32) < min || (value as i32) > max {
+                return Err(InvalidCharacter::Attribute {
+                    attribute: *attribute,
+                    value,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The directories to load for the module at `dir`.  These are the directories
+    /// of any base modules listed in the module's manifest, recursively, followed
+    /// by `dir` itself.  Later directories override or extend the resources of
+    /// earlier ones, so expansions and patches only need to contain their changes
+    pub fn module_directories(dir: &str) -> Result<Vec<String>, Error> {
+        let available = Module::get_available_modules();
+        let mut dirs = Vec::new();
+        add_module_directories(dir, &available, &mut Vec::new(), &mut dirs)?;
+        Ok(dirs)
+    }
+
+    /// Loads the module from the specified directories.  The first directory is
+    /// the core data directory.  The remaining directories are module and mod
+    /// directories in load order, as returned by `module_directories`, followed
+    /// by any mods.  The last module directory with a campaign file is the
+    /// primary module, whose campaign is used.
     pub fn load_resources(mut yaml: YamlResourceSet, dirs: Vec<String>) -> Result<(), Error> {
         assert!(dirs.len() > 1);
         debug!("Creating module from parsed data.");
+        let primary_dir = primary_module_dir(&dirs);
 
         let file_key = serde_yaml::Value::String(yaml_resource_set::FILE_VAL_STR.to_string());
 
@@ -377,8 +477,9 @@ impl Module {
                     None => return invalid_data_error("No rules file defined"),
                     Some(yaml) => yaml,
                 };
+                map.remove(module_manifest::MANIFEST_ID);
 
-                let mut campaign_yaml = None;
+                let mut candidates = Vec::new();
                 for (id, yaml) in map {
                     if let serde_yaml::Value::Mapping(ref map) = yaml {
                         if let Some(serde_yaml::Value::Sequence(files)) = map.get(&file_key) {
@@ -395,20 +496,28 @@ impl Module {
                         }
                     }
 
-                    if campaign_yaml.is_some() {
+                    candidates.push((id, yaml));
+                }
+
+                // base modules each define their own campaign, so prefer the
+                // campaign with a file in the primary module
+                if candidates.len() > 1 {
+                    candidates.retain(|(_, yaml)| has_file_in(yaml, &file_key, &primary_dir));
+                }
+
+                let campaign_yaml = match candidates.len() {
+                    0 => return invalid_data_error("No campaign file found at top level"),
+                    1 => candidates.pop().unwrap().1,
+                    _ => {
                         return invalid_data_error(&format!(
                             "Multiple potential campaign files \
-                             detected at top level: '{id}'"
+                             detected at top level: '{}'",
+                            candidates[1].0
                         ));
                     }
-                    campaign_yaml = Some(yaml);
-                }
-
-                if campaign_yaml.is_none() {
-                    return invalid_data_error("No campaign file found at top level");
-                }
+                };
 
-                (rules_yaml, campaign_yaml.unwrap())
+                (rules_yaml, campaign_yaml)
             }
         };
 
@@ -418,10 +527,11 @@ impl Module {
         let campaign_builder: CampaignBuilder = read_builder(campaign_yaml)?;
 
         let builder_set = ModuleBuilder::from_yaml(&mut yaml)?;
-        let area_builders = MODULE.with(|module| {
+        let (area_builders, encounter_table_builders) = MODULE.with(|module| {
             let mut module = module.borrow_mut();
             module.abilities.clear();
             module.ability_lists.clear();
+            module.achievements.clear();
             module.actors.clear();
             module.ai_templates.clear();
             module.areas.clear();
@@ -429,6 +539,8 @@ impl Module {
             module.conversations.clear();
             module.cutscenes.clear();
             module.encounters.clear();
+            module.encounter_tables.clear();
+            module.factions.clear();
             module.items.clear();
             module.item_adjectives.clear();
             module.loot_lists.clear();
@@ -439,6 +551,7 @@ impl Module {
             module.tiles.clear();
             module.scripts.clear();
             module.generators.clear();
+            module.room_templates.clear();
             module.features.clear();
             module.terrain_rules = None;
             module.terrain_kinds.clear();
@@ -449,7 +562,7 @@ impl Module {
             module.scripts = read_to_string(&dirs, "scripts");
             expand_include_directives(&mut module.scripts);
 
-            module.root_dir = Some(dirs[1].to_string());
+            module.root_dir = Some(primary_dir);
 
             for (id, builder) in builder_set.item_adjectives {
                 insert_if_ok(
@@ -460,6 +573,15 @@ impl Module {
                 );
             }
 
+            for (id, builder) in builder_set.faction_builders {
+                insert_if_ok(
+                    "faction",
+                    id,
+                    FactionDefinition::new(builder),
+                    &mut module.factions,
+                );
+            }
+
             for (id, quest) in builder_set.quests {
                 trace!(
                     "Inserting resource of type quest with key {} \
@@ -503,7 +625,7 @@ impl Module {
 
             for (id, builder) in builder_set.loot_builders.into_iter() {
                 insert_if_ok(
-                    "loot list",
+                    "loot_list",
                     id,
                     LootList::new(builder, &module),
                     &mut module.loot_lists,
@@ -556,6 +678,15 @@ impl Module {
                 );
             }
 
+            for (id, builder) in builder_set.achievement_builders {
+                insert_if_ok(
+                    "achievement",
+                    id,
+                    Achievement::new(builder, &module),
+                    &mut module.achievements,
+                );
+            }
+
             for (id, builder) in builder_set.cutscene_builders {
                 insert_if_ok(
                     "cutscene",
@@ -565,6 +696,16 @@ impl Module {
                 );
             }
 
+            // generators reference room templates, so must be created after them
+            for (id, builder) in builder_set.room_template_builders {
+                insert_if_ok(
+                    "room_template",
+                    id,
+                    RoomTemplate::new(builder, &module),
+                    &mut module.room_templates,
+                );
+            }
+
             for (id, builder) in builder_set.generator_builders {
                 insert_if_ok(
                     "generator",
@@ -574,12 +715,21 @@ impl Module {
                 );
             }
 
-            builder_set.area_builders
+            (builder_set.area_builders, builder_set.encounter_table_builders)
         });
 
         // do all area creation outside of with block to allow access to Module:: methods
 
-        for (id, builder) in area_builders {
+        for (id, mut builder) in area_builders {
+            for level in builder.take_levels() {
+                let level_id = level.id.to_string();
+                let area = Area::new(level);
+                MODULE.with(|module| {
+                    let mut module = module.borrow_mut();
+                    insert_if_ok("area", level_id, area, &mut module.areas);
+                });
+            }
+
             let area = Area::new(builder);
             MODULE.with(|module| {
                 let mut module = module.borrow_mut();
@@ -587,6 +737,15 @@ impl Module {
             });
         }
 
+        // encounter tables reference areas, so must be created after them
+        for (id, builder) in encounter_table_builders {
+            let table = EncounterTable::new(builder);
+            MODULE.with(|module| {
+                let mut module = module.borrow_mut();
+                insert_if_ok("encounter_table", id, table, &mut module.encounter_tables);
+            });
+        }
+
         let campaign = Campaign::new(campaign_builder)?;
 
         MODULE.with(move |m| {
@@ -679,6 +838,18 @@ impl Module {
         MODULE.with(|m| Rc::clone(m.borrow().campaign.as_ref().unwrap()))
     }
 
+    /// The script limits of the current campaign, or the defaults if no
+    /// campaign is loaded
+    pub fn script_limits() -> ScriptLimits {
+        MODULE.with(|m| {
+            let m = m.borrow();
+            m.campaign
+                .as_ref()
+                .map(|campaign| campaign.script_limits)
+                .unwrap_or_default()
+        })
+    }
+
     pub fn rules() -> Rc<Rules> {
         MODULE.with(|m| Rc::clone(m.borrow().rules.as_ref().unwrap()))
     }
@@ -746,6 +917,7 @@ impl Module {
     getters!(
         ability, abilities, Ability;
         ability_list, ability_lists, AbilityList;
+        achievement, achievements, Achievement;
         actor, actors, Actor;
         ai_template, ai_templates, AITemplate;
         area, areas, Area;
@@ -753,6 +925,8 @@ impl Module {
         conversation, conversations, Conversation;
         cutscene, cutscenes, Cutscene;
         encounter, encounters, Encounter;
+        encounter_table, encounter_tables, EncounterTable;
+        faction, factions, FactionDefinition;
         item, items, Item;
         item_adjective, item_adjectives, ItemAdjective;
         loot_list, loot_lists, LootList;
@@ -762,6 +936,7 @@ impl Module {
         race, races, Race;
         tile, tiles, Tile;
         generator, generators, AreaGenerator;
+        room_template, room_templates, RoomTemplate;
         size, sizes, ObjectSize;
         feature, features, Feature
         );
@@ -784,6 +959,10 @@ impl Module {
         })
     }
 
+    pub fn all_achievements() -> Vec<Rc<Achievement>> {
+        MODULE.with(|r| all_resources(&r.borrow().achievements))
+    }
+
     pub fn all_actors() -> Vec<Rc<Actor>> {
         MODULE.with(|r| all_resources(&r.borrow().actors))
     }
@@ -798,6 +977,10 @@ impl Module {
         })
     }
 
+    pub fn all_areas() -> Vec<Rc<Area>> {
+        MODULE.with(|r| all_resources(&r.borrow().areas))
+    }
+
     pub fn all_classes() -> Vec<Rc<Class>> {
         MODULE.with(|r| all_resources(&r.borrow().classes))
     }
@@ -806,6 +989,10 @@ impl Module {
         MODULE.with(|r| all_resources(&r.borrow().encounters))
     }
 
+    pub fn all_factions() -> Vec<Rc<FactionDefinition>> {
+        MODULE.with(|r| all_resources(&r.borrow().factions))
+    }
+
     pub fn all_features() -> Vec<Rc<Feature>> {
         MODULE.with(|r| all_resources(&r.borrow().features))
     }
@@ -830,6 +1017,7 @@ impl Module {
 struct ModuleBuilder {
     ability_builders: HashMap<String, AbilityBuilder>,
     ability_list_builders: HashMap<String, AbilityListBuilder>,
+    achievement_builders: HashMap<String, AchievementBuilder>,
     actor_builders: HashMap<String, ActorBuilder>,
     ai_builders: HashMap<String, AITemplate>,
     area_builders: HashMap<String, AreaBuilder>,
@@ -837,6 +1025,8 @@ struct ModuleBuilder {
     cutscene_builders: HashMap<String, CutsceneBuilder>,
     conversation_builders: HashMap<String, ConversationBuilder>,
     encounter_builders: HashMap<String, EncounterBuilder>,
+    encounter_table_builders: HashMap<String, EncounterTableBuilder>,
+    faction_builders: HashMap<String, FactionBuilder>,
     item_builders: HashMap<String, ItemBuilder>,
     loot_builders: HashMap<String, LootListBuilder>,
     prop_builders: HashMap<String, PropBuilder>,
@@ -844,6 +1034,7 @@ struct ModuleBuilder {
     size_builders: HashMap<String, ObjectSizeBuilder>,
     tile_builders: HashMap<String, Tileset>,
     generator_builders: HashMap<String, GeneratorBuilder>,
+    room_template_builders: HashMap<String, RoomTemplateBuilder>,
 
     item_adjectives: HashMap<String, ItemAdjectiveBuilder>,
     quests: HashMap<String, Quest>,
@@ -855,6 +1046,7 @@ impl ModuleBuilder {
         Ok(ModuleBuilder {
             ability_builders: read_builders(resources, Ability)?,
             ability_list_builders: read_builders(resources, AbilityList)?,
+            achievement_builders: read_builders(resources, Achievement)?,
             actor_builders: read_builders(resources, Actor)?,
             ai_builders: read_builders(resources, AiTemplate)?,
             area_builders: read_builders(resources, Area)?,
@@ -862,8 +1054,10 @@ impl ModuleBuilder {
             conversation_builders: read_builders(resources, Conversation)?,
             cutscene_builders: read_builders(resources, Cutscene)?,
             encounter_builders: read_builders(resources, Encounter)?,
+            encounter_table_builders: read_builders(resources, EncounterTable)?,
+            faction_builders: read_builders(resources, Faction)?,
             item_builders: read_builders(resources, Item)?,
-            item_adjectives: read_builders(resources, ItemAdjective)?,
+            item_adjectives: read_builders_serial(resources, ItemAdjective)?,
             loot_builders: read_builders(resources, LootList)?,
             prop_builders: read_builders(resources, Prop)?,
             quests: read_builders(resources, Quest)?,
@@ -871,10 +1065,69 @@ impl ModuleBuilder {
             size_builders: read_builders(resources, Size)?,
             tile_builders: read_builders(resources, Tile)?,
             generator_builders: read_builders(resources, Generator)?,
+            room_template_builders: read_builders(resources, RoomTemplate)?,
         })
     }
 }
 
+fn add_module_directories(
+    dir: &str,
+    available: &[ModuleInfo],
+    visiting: &mut Vec<String>,
+    dirs: &mut Vec<String>,
+) -> Result<(), Error> {
+    if dirs.iter().any(|d| d == dir) {
+        return Ok(());
+    }
+
+    if visiting.iter().any(|d| d == dir) {
+        return invalid_data_error(&format!("Circular base modules at '{dir}'"));
+    }
+    visiting.push(dir.to_string());
+
+    let manifest = ModuleManifest::from_dir(dir);
+    for base in manifest.base_modules.iter() {
+        let base_dir = match available.iter().find(|m| &m.id == base) {
+            None => {
+                return invalid_data_error(&format!(
+                    "Base module '{base}' required by '{dir}' is not installed"
+                ));
+            }
+            Some(module) => &module.dir,
+        };
+        add_module_directories(base_dir, available, visiting, dirs)?;
+    }
+
+    visiting.pop();
+    dirs.push(dir.to_string());
+    Ok(())
+}
+
+fn primary_module_dir(dirs: &[String]) -> String {
+    dirs[1..]
+        .iter()
+        .rev()
+        .find(|dir| {
+            ["yml", "json"]
+                .iter()
+                .any(|ext| vfs::is_file(format!("{dir}/campaign.{ext}")))
+        })
+        .unwrap_or(&dirs[1])
+        .to_string()
+}
+
+fn has_file_in(yaml: &serde_yaml::Value, file_key: &serde_yaml::Value, dir: &str) -> bool {
+    let files = match yaml.get(file_key) {
+        Some(serde_yaml::Value::Sequence(files)) => files,
+        _ => return false,
+    };
+
+    files.iter().any(|file| match file {
+        serde_yaml::Value::String(file) => Path::new(file).starts_with(dir),
+        _ => false,
+    })
+}
+
 struct IncludeExpansion {
     start_index: usize,
     end_index: usize,
@@ -14,17 +14,88 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::cell::RefCell;
 use std::fmt::{self, Display};
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use sulis_core::config::{self, Config};
-use sulis_core::resource::{read_single_resource, subdirs};
+use sulis_core::resource::{read_single_resource, subdirs, vfs};
+use sulis_core::util::ActiveResources;
+
+thread_local! {
+    static USER_CONTENT: RefCell<Option<UserContent>> = RefCell::new(None);
+}
+
+/// The modules and mods found in the user mods directory.  This directory
+/// is laid out workshop-style: each entry is a downloaded item, which is
+/// either a mod, a module, or a directory bundling several of these.
+#[derive(Debug, Clone, Default)]
+pub struct UserContent {
+    pub modules: Vec<PathBuf>,
+    pub mods: Vec<PathBuf>,
+}
+
+/// Scans the user mods directory for modules and mods.  This is done once at
+/// startup; the results are then used by `user_content`.
+pub fn scan_user_mods_dir() -> UserContent {
+    let mut dir = config::USER_DIR.clone();
+    dir.push(Config::resources_config().mods_directory);
+
+    let mut content = UserContent::default();
+    match subdirs(&dir) {
+        Ok(items) => {
+            for item in items {
+                add_user_item(&item, &mut content, true);
+            }
+        }
+        Err(e) => warn!("Unable to read mods from '{:?}': {}", dir, e),
+    }
+
+    info!(
+        "Found {} modules and {} mods in '{:?}'",
+        content.modules.len(),
+        content.mods.len(),
+        dir
+    );
+    USER_CONTENT.with(|c| *c.borrow_mut() = Some(content.clone()));
+    content
+}
+
+/// The content found by the last call to `scan_user_mods_dir`, scanning
+/// the directory if it hasn't been yet
+pub fn user_content() -> UserContent {
+    let content = USER_CONTENT.with(|c| c.borrow().clone());
+    content.unwrap_or_else(scan_user_mods_dir)
+}
+
+fn has_file(dir: &Path, id: &str) -> bool {
+    ["yml", "json"]
+        .iter()
+        .any(|ext| vfs::is_file(dir.join(format!("{id}.{ext}"))))
+}
+
+fn add_user_item(dir: &Path, content: &mut UserContent, top_level: bool) {
+    if has_file(dir, "mod") {
+        content.mods.push(dir.to_path_buf());
+    } else if has_file(dir, "campaign") {
+        content.modules.push(dir.to_path_buf());
+    } else if top_level {
+        match subdirs(dir) {
+            Ok(children) => {
+                for child in children {
+                    add_user_item(&child, content, false);
+                }
+            }
+            Err(e) => warn!("Unable to read mods from '{:?}': {}", dir, e),
+        }
+    } else {
+        debug!("Skipping '{:?}' as it is not a mod or module", dir);
+    }
+}
 
 pub fn get_available_modifications() -> Vec<ModificationInfo> {
     let root_dir = Config::resources_config().mods_directory;
-    let mut user_dir = config::USER_DIR.clone();
-    user_dir.push(&root_dir);
 
     let mut mods = Vec::new();
 
@@ -34,10 +105,9 @@ pub fn get_available_modifications() -> Vec<ModificationInfo> {
         Err(e) => warn!("Unable to read mods from '{}': {}", root_dir, e),
     }
 
-    match subdirs(&user_dir) {
-        Ok(mut subdirs) => dirs.append(&mut subdirs),
-        Err(e) => warn!("Unable to read mods from '{:?}': {}", user_dir, e),
-    }
+    // the user mods directory may also contain modules, so only the
+    // directories found to be mods are used
+    dirs.append(&mut user_content().mods);
 
     for dir in dirs {
         match ModificationInfo::from_dir(dir.clone()) {
@@ -49,6 +119,19 @@ pub fn get_available_modifications() -> Vec<ModificationInfo> {
     mods
 }
 
+/// The currently active mods, in load order
+pub fn get_active_modifications() -> Vec<ModificationInfo> {
+    let mut mods = Vec::new();
+    for dir in ActiveResources::read().mods {
+        match ModificationInfo::from_dir(PathBuf::from(&dir)) {
+            Ok(modi) => mods.push(modi),
+            Err(e) => warn!("Error reading active mod from '{}': {}", dir, e),
+        }
+    }
+
+    mods
+}
+
 #[derive(Debug, Clone)]
 pub struct ModificationInfo {
     pub id: String,
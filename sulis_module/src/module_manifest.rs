@@ -0,0 +1,83 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use sulis_core::config::Config;
+use sulis_core::resource::{read_single_resource, vfs};
+
+/// The top level id of manifest files.  Manifests are not module resources,
+/// and are skipped when loading a module.
+pub const MANIFEST_ID: &str = "manifest";
+
+/// Optional metadata describing a module, read from the `manifest` file at the
+/// top level of the module directory.  The core data directory may also have
+/// a manifest, which defines the ruleset that modules are checked against.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleManifest {
+    pub id: String,
+
+    #[serde(default)]
+    pub author: Option<String>,
+
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// For a module, the ruleset it requires.  For the core data directory,
+    /// the ruleset it provides.
+    #[serde(default)]
+    pub ruleset: Option<String>,
+
+    /// The ids of modules that this module builds on.  These are loaded before
+    /// this module, which may then override or extend their resources
+    #[serde(default)]
+    pub base_modules: Vec<String>,
+}
+
+impl ModuleManifest {
+    /// Reads the manifest in the specified directory.  If there is no manifest,
+    /// or it cannot be read, an empty manifest is returned.
+    pub fn from_dir(dir: &str) -> ModuleManifest {
+        let filename = format!("{dir}/{MANIFEST_ID}");
+        let exists = ["yml", "json"]
+            .iter()
+            .any(|ext| vfs::is_file(format!("{filename}.{ext}")));
+        if !exists {
+            return ModuleManifest::default();
+        }
+
+        match read_single_resource(&filename) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                warn!("Error reading manifest in '{}': {}", dir, e);
+                ModuleManifest::default()
+            }
+        }
+    }
+
+    /// The manifest of the core data directory
+    pub fn core() -> ModuleManifest {
+        ModuleManifest::from_dir(&Config::resources_config().directory)
+    }
+
+    /// Whether a module with this manifest may be played with the specified
+    /// core manifest.  Modules that don't specify a ruleset are always allowed
+    pub fn is_supported_by(&self, core: &ModuleManifest) -> bool {
+        match &self.ruleset {
+            None => true,
+            Some(ruleset) => core.ruleset.as_ref() == Some(ruleset),
+        }
+    }
+}
@@ -39,6 +39,31 @@ pub struct MerchantData {
 
     #[serde(default)]
     pub refresh_time: Time,
+
+    #[serde(default)]
+    pub faction: Option<String>,
+
+    #[serde(default)]
+    pub hours: Option<MerchantHours>,
+}
+
+/// The range of hours during which a merchant is open for trade.  If `close`
+/// is before `open`, the merchant is open overnight, across the day boundary.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct MerchantHours {
+    pub open: u32,
+    pub close: u32,
+}
+
+impl MerchantHours {
+    pub fn is_open(&self, hour: u32) -> bool {
+        if self.open <= self.close {
+            hour >= self.open && hour < self.close
+        } else {
+            hour >= self.open || hour < self.close
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -74,6 +99,56 @@ pub struct MenuData {
     pub cb_parent: usize,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum WindowElement {
+    Label(String),
+    Button(ScriptMenuChoice),
+}
+
+/// A custom window built by a script.  Elements are laid out by the window
+/// theme, in rows from top to bottom and left to right within each row.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WindowData {
+    pub title: String,
+    pub rows: Vec<Vec<WindowElement>>,
+    pub cb_func: String,
+    pub cb_kind: Kind,
+    pub cb_parent: usize,
+}
+
+/// A single step in a scripted cutscene sequence.  Steps are run one after
+/// another, with each step completing before the next begins.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum CutsceneStep {
+    MoveEntity {
+        entity: usize,
+        x: i32,
+        y: i32,
+    },
+    ShowText {
+        entity: usize,
+        text: String,
+        millis: u32,
+    },
+    PanTo {
+        x: i32,
+        y: i32,
+    },
+    FadeOut,
+    Wait {
+        millis: u32,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CutsceneData {
+    pub steps: Vec<CutsceneStep>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct NumFlagData {
@@ -128,6 +203,7 @@ pub struct ModuleLoadData {
 pub enum OnTrigger {
     BlockUI(u32), // block user interface for specified number of millis
     PlayerCoins(i32),
+    AwardXp(u32),
     PartyMember(String),
     PartyItem(String),
     PlayerNumFlag(NumFlagData),
@@ -141,6 +217,7 @@ pub enum OnTrigger {
     PlayerFlag(String),
     ShowMerchant(MerchantData),
     ShowCutscene(String),
+    PlayCutscene(CutsceneData),
     StartConversation(String),
     FireScript(ScriptData),
     SayLine(String),
@@ -151,6 +228,9 @@ pub enum OnTrigger {
     LoadModule(ModuleLoadData),
     ShowConfirm(DialogData),
     ShowMenu(MenuData),
+    ShowChoice(MenuData),
+    ShowWindow(WindowData),
+    CloseWindow,
     QuestState(QuestStateData),
     NotQuestState(QuestStateData),
     FadeOutIn,
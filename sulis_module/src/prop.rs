@@ -24,7 +24,7 @@ use sulis_core::ui::AnimationState;
 use sulis_core::util::{unable_to_create_error, Offset, Point, Rect};
 
 use crate::area::tile::verify_point;
-use crate::{LootList, Module, ObjectSize, OnTrigger};
+use crate::{LightSource, LootList, Module, ObjectSize, OnTrigger};
 
 #[derive(Debug)]
 pub enum Interactive {
@@ -40,6 +40,12 @@ pub enum Interactive {
         fire_more_than_once: bool,
     },
     Hover,
+
+    /// Fires `on_activate` the first time a party member steps onto the
+    /// prop, which is then disarmed
+    Trap {
+        on_activate: Vec<OnTrigger>,
+    },
 }
 
 #[derive(Debug)]
@@ -48,6 +54,7 @@ pub struct Prop {
     pub name: String,
     pub icon: Rc<dyn Image>,
     pub image: Rc<dyn Image>,
+    pub hidden_image: Option<Rc<dyn Image>>,
     pub random_millis_offset: u32,
     pub size: Rc<ObjectSize>,
     pub impass: Vec<Point>,
@@ -55,6 +62,7 @@ pub struct Prop {
     pub interactive: Interactive,
     pub aerial: bool,
     pub status_text: Option<String>,
+    pub light: Option<LightSource>,
 }
 
 impl Prop {
@@ -75,6 +83,17 @@ impl Prop {
             Some(image) => image,
         };
 
+        let hidden_image = match builder.hidden_image {
+            None => None,
+            Some(ref id) => match ResourceSet::image(id) {
+                None => {
+                    warn!("No image found for hidden image '{}'", id);
+                    return unable_to_create_error("prop", &builder.id);
+                }
+                Some(image) => Some(image),
+            },
+        };
+
         let size = match module.sizes.get(&builder.size) {
             None => {
                 warn!("No size found with id '{}'", builder.size);
@@ -154,6 +173,7 @@ impl Prop {
                 on_activate,
                 fire_more_than_once,
             },
+            InteractiveBuilder::Trap { on_activate } => Interactive::Trap { on_activate },
         };
 
         Ok(Prop {
@@ -161,6 +181,7 @@ impl Prop {
             name: builder.name,
             icon,
             image,
+            hidden_image,
             random_millis_offset: builder.random_millis_offset,
             size,
             impass,
@@ -168,6 +189,7 @@ impl Prop {
             interactive,
             aerial: builder.aerial,
             status_text: builder.status_text,
+            light: builder.light,
         })
     }
 
@@ -178,15 +200,31 @@ impl Prop {
         offset: Offset,
         millis: u32,
     ) {
-        let rect = Rect {
+        self.image
+            .append_to_draw_list(draw_list, state, self.rect(offset), millis);
+    }
+
+    /// Appends the image drawn in place of this prop while it is hidden, if
+    /// there is one
+    pub fn append_hidden_to_draw_list(
+        &self,
+        draw_list: &mut DrawList,
+        state: &AnimationState,
+        offset: Offset,
+        millis: u32,
+    ) {
+        if let Some(ref image) = self.hidden_image {
+            image.append_to_draw_list(draw_list, state, self.rect(offset), millis);
+        }
+    }
+
+    fn rect(&self, offset: Offset) -> Rect {
+        Rect {
             x: offset.x,
             y: offset.y,
             w: self.size.width as f32,
             h: self.size.height as f32,
-        };
-
-        self.image
-            .append_to_draw_list(draw_list, state, rect, millis);
+        }
     }
 }
 
@@ -209,6 +247,9 @@ pub enum InteractiveBuilder {
         fire_more_than_once: bool,
     },
     Hover,
+    Trap {
+        on_activate: Vec<OnTrigger>,
+    },
 }
 
 #[derive(Deserialize, Debug)]
@@ -218,6 +259,11 @@ pub struct PropBuilder {
     pub name: String,
     pub icon: String,
     pub image: String,
+
+    /// The image drawn in place of this prop while it is hidden, such as a
+    /// section of wall covering a secret door
+    #[serde(default)]
+    pub hidden_image: Option<String>,
     #[serde(default)]
     pub random_millis_offset: u32,
     pub size: String,
@@ -229,4 +275,8 @@ pub struct PropBuilder {
     pub aerial: bool,
     pub interactive: InteractiveBuilder,
     pub status_text: Option<String>,
+
+    /// A light emitted by this prop in areas with lighting
+    #[serde(default)]
+    pub light: Option<LightSource>,
 }
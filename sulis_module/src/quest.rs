@@ -29,4 +29,9 @@ pub struct Quest {
 #[serde(deny_unknown_fields)]
 pub struct QuestEntry {
     pub description: String,
+
+    /// The ID of the world map location this entry directs the player to.
+    /// While the entry is open, the location is marked on the world map
+    #[serde(default)]
+    pub location: Option<String>,
 }
@@ -25,7 +25,7 @@ use sulis_core::resource::ResourceSet;
 use sulis_core::ui::Color;
 use sulis_core::util::{gen_rand, unable_to_create_error, Point};
 
-use crate::actor::Sex;
+use crate::actor::{Facing, Sex, SpriteState};
 
 use crate::{ImageLayer, ImageLayerSet, Module, ObjectSize, Prop};
 
@@ -49,6 +49,8 @@ pub struct Race {
     pub hair_colors: Vec<Color>,
     pub skin_colors: Vec<Color>,
     pub ticker_offset: (f32, f32),
+    pub sprite_images: HashMap<SpriteState, Rc<dyn Image>>,
+    pub facing_sprite_images: HashMap<Facing, HashMap<SpriteState, Rc<dyn Image>>>,
     default_images: ImageLayerSet,
     image_layer_offsets: HashMap<ImageLayer, (f32, f32)>,
     image_layer_postfix: HashMap<Sex, String>,
@@ -125,6 +127,36 @@ impl Race {
             editor_creator_images.push((layer, images));
         }
 
+        let mut sprite_images = HashMap::new();
+        for (state, image_id) in builder.sprite_images {
+            match ResourceSet::image(&image_id) {
+                None => {
+                    warn!("No image found with id '{}'", image_id);
+                    return unable_to_create_error("race", &builder.id);
+                }
+                Some(image) => {
+                    sprite_images.insert(state, image);
+                }
+            }
+        }
+
+        let mut facing_sprite_images = HashMap::new();
+        for (facing, images) in builder.facing_sprite_images {
+            let mut facing_images = HashMap::new();
+            for (state, image_id) in images {
+                match ResourceSet::image(&image_id) {
+                    None => {
+                        warn!("No image found with id '{}'", image_id);
+                        return unable_to_create_error("race", &builder.id);
+                    }
+                    Some(image) => {
+                        facing_images.insert(state, image);
+                    }
+                }
+            }
+            facing_sprite_images.insert(facing, facing_images);
+        }
+
         let pc_death_prop = match builder.pc_death_prop {
             None => None,
             Some(id) => match module.props.get(&id) {
@@ -157,6 +189,8 @@ impl Race {
             hair_colors,
             skin_colors,
             ticker_offset: builder.ticker_offset,
+            sprite_images,
+            facing_sprite_images,
             editor_creator_images,
             pc_death_prop,
         })
@@ -247,6 +281,12 @@ pub struct RaceBuilder {
     #[serde(default)]
     pub default_images_by_sex: HashMap<Sex, HashMap<ImageLayer, String>>,
 
+    #[serde(default)]
+    pub sprite_images: HashMap<SpriteState, String>,
+
+    #[serde(default)]
+    pub facing_sprite_images: HashMap<Facing, HashMap<SpriteState, String>>,
+
     #[serde(default)]
     pub male_random_names: Vec<String>,
 
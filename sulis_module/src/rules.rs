@@ -52,8 +52,9 @@ pub mod stat_list;
 pub use self::stat_list::StatList;
 
 use crate::area::LocationKind;
+use crate::Module;
 use sulis_core::ui::{color, Color};
-use sulis_core::util::{gen_rand, invalid_data_error};
+use sulis_core::util::{combat_roll, gen_rand, invalid_data_error};
 
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
@@ -69,6 +70,12 @@ pub struct Rules {
     pub attack_ap: u32,
     pub display_ap: u32,
     pub swap_weapons_ap: u32,
+
+    /// The number of times each character may use items from their quick
+    /// slots in a single combat.  Unlimited if not specified
+    #[serde(default)]
+    pub quick_item_uses_per_combat: Option<u32>,
+
     pub initiative_roll_max: i32,
     pub base_flanking_angle: i32,
     pub graze_percentile: u32,
@@ -78,20 +85,63 @@ pub struct Rules {
     pub flanking_accuracy_bonus: i32,
     pub hidden_accuracy_bonus: i32,
 
+    /// Accuracy bonus for attacks made from behind the target, based on the
+    /// direction it is facing
+    #[serde(default)]
+    pub backstab_accuracy_bonus: i32,
+
+    /// Whether attacks made from behind the target also count as flanking
+    #[serde(default)]
+    pub backstab_counts_as_flanking: bool,
+
+    /// Accuracy penalties applied to ranged attacks against targets in cover
+    #[serde(default)]
+    pub soft_cover_accuracy_penalty: i32,
+    #[serde(default)]
+    pub hard_cover_accuracy_penalty: i32,
+
+    /// Accuracy bonus for ranged attacks against targets at a lower elevation
+    #[serde(default)]
+    pub high_ground_accuracy_bonus: i32,
+
+    /// The number of additional squares of movement charged for each level
+    /// of elevation climbed
+    #[serde(default)]
+    pub climb_move_squares: u32,
+
+    /// The cost of a diagonal move, as a multiple of the cost of an
+    /// orthogonal move
+    #[serde(default = "default_diagonal_move_cost")]
+    pub diagonal_move_cost: f32,
+
     pub graze_damage_multiplier: f32,
     pub crit_damage_multiplier: f32,
 
     pub dual_wield_damage_multiplier: f32,
 
+    /// Accuracy penalty applied to the off hand attack when dual wielding
+    #[serde(default)]
+    pub off_hand_accuracy_penalty: i32,
+
     pub base_attribute: i32,
     pub builder_max_attribute: i32,
     pub builder_min_attribute: i32,
     pub builder_attribute_points: i32,
 
+    /// The total levels at which a character gains an attribute point to
+    /// spend during level up
+    #[serde(default)]
+    pub level_up_attribute_levels: Vec<u32>,
+
     pub max_dialog_distance: f32,
     pub max_transition_distance: f32,
     pub max_prop_distance: f32,
 
+    /// If set, every living party member must be within this distance of
+    /// a transition before the party may use it
+    #[serde(default)]
+    pub transition_gather_distance: Option<f32>,
+
     pub selectable_races: Vec<String>,
     pub selectable_classes: Vec<String>,
     pub ability_groups: Vec<String>,
@@ -99,7 +149,37 @@ pub struct Rules {
     pub experience_factor: f32,
     pub experience_for_level: Vec<u32>,
 
+    /// How XP awarded to the party is shared between party members
+    #[serde(default)]
+    pub xp_distribution: XpDistribution,
+
+    /// For `Proximity` XP distribution, the maximum distance from the source
+    /// of the XP a party member may be and still receive a share
+    #[serde(default)]
+    pub xp_share_distance: f32,
+
     pub combat_run_away_vis_factor: f32,
+
+    /// The number of consecutive rounds in which no active hostile can see
+    /// any party member, or be seen by them, before the party escapes combat.
+    /// A value of zero disables escaping by breaking line of sight
+    #[serde(default)]
+    pub combat_flee_los_rounds: u32,
+
+    /// The number of rounds after the party escapes an encounter before that
+    /// encounter may be activated again
+    #[serde(default)]
+    pub combat_flee_cooldown_rounds: u32,
+
+    /// The maximum distance at which a hostile sighting will start combat.  A
+    /// value of zero means any hostile within sight will start combat
+    #[serde(default)]
+    pub combat_engage_distance: f32,
+
+    /// Whether an attack from stealth which starts combat grants the attacker's
+    /// side a free round, skipping the turns of those surprised
+    #[serde(default)]
+    pub surprise_round: bool,
     pub loot_drop_prop: String,
 
     pub item_weight_display_factor: f32,
@@ -107,6 +187,11 @@ pub struct Rules {
 
     pub coins_item: String,
 
+    /// The maximum number of distinct item stacks which may be kept in the
+    /// party storage.  Unlimited if not specified
+    #[serde(default)]
+    pub party_storage_capacity: Option<u32>,
+
     armor_damage_reduction_cap: Vec<u32>,
 
     pub rounds_per_hour: u32,
@@ -118,6 +203,188 @@ pub struct Rules {
     pub hints: Vec<String>,
 
     pub main_menu_music: Option<String>,
+
+    /// The AI template used for party members set to fight automatically,
+    /// if they do not have an AI of their own
+    #[serde(default)]
+    pub party_ai: Option<String>,
+
+    /// Carried weight limits.  If not specified, characters may carry any
+    /// amount of weight without penalty
+    #[serde(default)]
+    pub encumbrance: Option<Encumbrance>,
+
+    /// Skill checks for opening locked doors and containers.  If not
+    /// specified, locks may only be opened with their key
+    #[serde(default)]
+    pub locks: Option<LockRules>,
+
+    /// Checks for spotting hidden traps and secret doors.  If not
+    /// specified, hidden props are never found
+    #[serde(default)]
+    pub detection: Option<DetectionRules>,
+
+    /// Injuries for party members reduced to zero hit points.  If specified,
+    /// party members are incapacitated with a random injury instead of dying,
+    /// and recover once combat ends, unless the campaign enforces permadeath
+    #[serde(default)]
+    pub injuries: Option<InjuryRules>,
+
+    /// Resting in areas which allow camping.  Required for those areas
+    #[serde(default)]
+    pub rest: Option<RestRules>,
+
+    /// The scaling applied for each of the preset difficulty levels.  Levels
+    /// not specified apply no scaling
+    #[serde(default)]
+    pub difficulty: HashMap<DifficultyLevel, DifficultyScaling>,
+
+    /// Wear on equipped armor from hits.  If not specified, armor
+    /// never degrades
+    #[serde(default)]
+    pub durability: Option<DurabilityRules>,
+}
+
+fn default_diagonal_move_cost() -> f32 {
+    1.5
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RestRules {
+    /// The number of hours that pass while resting
+    pub hours: u32,
+
+    /// The item consumed from the party stash by each rest.  If not
+    /// specified, resting is free
+    #[serde(default)]
+    pub supply_item: Option<String>,
+
+    /// The number of supply items consumed by each rest
+    pub supplies_per_rest: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DurabilityRules {
+    /// The durability of an undamaged armor item
+    pub max: u32,
+
+    /// Durability lost by each equipped armor item when the wearer is hit
+    pub loss_per_hit: u32,
+
+    /// Durability lost by each equipped armor item when the wearer is
+    /// critically hit
+    pub loss_per_crit: u32,
+
+    /// Reductions to the armor provided by an item once its durability
+    /// falls below a percentage of the maximum
+    pub thresholds: Vec<DurabilityThreshold>,
+
+    /// The fraction of an item's value charged by merchants to repair
+    /// each point of durability
+    pub repair_cost_per_point: f32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DurabilityThreshold {
+    pub below_percent: u32,
+    pub armor_multiplier: f32,
+}
+
+impl DurabilityRules {
+    /// Returns the multiplier applied to armor bonuses of an item with
+    /// the specified remaining `durability`
+    pub fn armor_multiplier(&self, durability: u32) -> f32 {
+        let percent = durability * 100 / self.max.max(1);
+        self.thresholds
+            .iter()
+            .filter(|threshold| percent < threshold.below_percent)
+            .map(|threshold| threshold.armor_multiplier)
+            .fold(1.0, f32::min)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct InjuryRules {
+    /// The effect tag applied to each injury, so they may be removed
+    /// when resting
+    pub tag: String,
+
+    /// The possible injuries, one of which is chosen at random
+    pub injuries: Vec<Injury>,
+}
+
+impl InjuryRules {
+    pub fn random_injury(&self) -> Option<&Injury> {
+        if self.injuries.is_empty() {
+            return None;
+        }
+
+        Some(&self.injuries[combat_roll(0, self.injuries.len())])
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Injury {
+    pub name: String,
+    pub icon: String,
+    pub bonuses: BonusList,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LockRules {
+    /// The class whose levels add to lockpicking checks
+    pub pick_class: String,
+
+    /// Lockpicking bonus for each level in the pick class
+    pub pick_bonus_per_level: i32,
+
+    /// Bonus for each point of dexterity (when picking) or strength (when
+    /// bashing) above the base attribute
+    pub bonus_per_attribute: i32,
+
+    /// Added to the lock difficulty when attempting to bash it open
+    pub bash_difficulty_penalty: i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DetectionRules {
+    /// Party members check for hidden props within this distance, in tiles,
+    /// each time they move
+    pub distance: f32,
+
+    /// The class whose levels add to detection checks
+    pub search_class: String,
+
+    /// Detection bonus for each level in the search class
+    pub bonus_per_level: i32,
+
+    /// Bonus for each point of perception above the base attribute
+    pub bonus_per_attribute: i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Encumbrance {
+    /// The weight a character with the base strength attribute may carry
+    pub base_weight: i32,
+
+    /// The additional weight allowed for each point of strength above the
+    /// base attribute.  Weaker characters are allowed correspondingly less
+    pub weight_per_strength: i32,
+
+    /// Movement rate is multiplied by this factor while encumbered
+    pub movement_rate_factor: f32,
+
+    /// Abilities that may not be activated while encumbered
+    #[serde(default)]
+    pub disabled_abilities: Vec<String>,
 }
 
 impl Rules {
@@ -244,7 +511,8 @@ impl Rules {
     /// Computes the amount of damage that this damage list will apply to the given
     /// `armor`.  Each damage component of this list is rolled randomly, with the resulting
     /// damage then multiplied by the `multiplier`, rounded down.  The damage is then
-    /// modified by the percentage resistance, if any.  The armor against
+    /// modified by the percentage resistance or vulnerability, if any, and ignored
+    /// entirely for damage kinds the target is immune to.  The armor against
     /// the base damage kind of this damage is then subtracted from the damage, capped
     /// by the armor damage reduction cap for that armor value.  The
     /// resulting vector may be an empty vector to indicate no damage, or a vector of
@@ -272,7 +540,10 @@ impl Rules {
         for damage in damage.iter() {
             let kind = damage.kind.unwrap();
 
-            let resistance = (100 - resistance.amount(kind)) as f32 / 100.0;
+            let resistance = resistance.damage_factor(kind);
+            if resistance == 0.0 {
+                continue;
+            }
             let amount = damage.roll() as f32 * multiplier * resistance;
 
             let armor = max(0, armor.amount(kind) - damage.ap as i32) as u32;
@@ -301,6 +572,75 @@ impl Rules {
             .unwrap_or(&100)
     }
 
+    /// Returns the maximum weight a character with the given strength can
+    /// carry without becoming encumbered, or None if encumbrance is not in use
+    pub fn max_carry_weight(&self, strength: u8) -> Option<i32> {
+        let enc = self.encumbrance.as_ref()?;
+        let delta = strength as i32 - self.base_attribute;
+        Some((enc.base_weight + delta * enc.weight_per_strength).max(0))
+    }
+
+    pub fn is_disabled_when_encumbered(&self, ability_id: &str) -> bool {
+        match self.encumbrance {
+            None => false,
+            Some(ref enc) => enc.disabled_abilities.iter().any(|id| id == ability_id),
+        }
+    }
+
+    /// Rolls a lockpicking check against the difficulty, for a character with
+    /// the specified dexterity and levels in the pick class
+    pub fn pick_lock_roll(&self, difficulty: i32, dexterity: u8, class_levels: u32) -> bool {
+        let locks = match self.locks.as_ref() {
+            None => return false,
+            Some(locks) => locks,
+        };
+
+        let bonus = (dexterity as i32 - self.base_attribute) * locks.bonus_per_attribute
+            + class_levels as i32 * locks.pick_bonus_per_level;
+        let roll = gen_rand(1, 101);
+        debug!("Pick lock roll: {} + {} against {}", roll, bonus, difficulty);
+        roll + bonus >= difficulty
+    }
+
+    /// Rolls a check to spot a hidden prop with the specified difficulty,
+    /// for a character with the specified perception and levels in the
+    /// search class
+    pub fn detect_roll(&self, difficulty: i32, perception: u8, class_levels: u32) -> bool {
+        let detection = match self.detection.as_ref() {
+            None => return false,
+            Some(detection) => detection,
+        };
+
+        let bonus = (perception as i32 - self.base_attribute) * detection.bonus_per_attribute
+            + class_levels as i32 * detection.bonus_per_level;
+        let roll = gen_rand(1, 101);
+        debug!("Detect roll: {} + {} against {}", roll, bonus, difficulty);
+        roll + bonus >= difficulty
+    }
+
+    /// Rolls a check to bash open a lock with the specified difficulty
+    pub fn bash_lock_roll(&self, difficulty: i32, strength: u8) -> bool {
+        let locks = match self.locks.as_ref() {
+            None => return false,
+            Some(locks) => locks,
+        };
+
+        let bonus = (strength as i32 - self.base_attribute) * locks.bonus_per_attribute;
+        let difficulty = difficulty + locks.bash_difficulty_penalty;
+        let roll = gen_rand(1, 101);
+        debug!("Bash lock roll: {} + {} against {}", roll, bonus, difficulty);
+        roll + bonus >= difficulty
+    }
+
+    /// Returns the number of attribute points gained when reaching the
+    /// specified total level
+    pub fn level_up_attribute_points(&self, level: u32) -> i32 {
+        self.level_up_attribute_levels
+            .iter()
+            .filter(|l| **l == level)
+            .count() as i32
+    }
+
     pub fn get_xp_for_next_level(&self, cur_level: u32) -> u32 {
         if cur_level < 1 {
             return 0;
@@ -312,11 +652,19 @@ impl Rules {
         self.experience_for_level[(cur_level - 1) as usize]
     }
 
+    pub fn cover_accuracy_penalty(&self, cover: Cover) -> i32 {
+        match cover {
+            Cover::None => 0,
+            Cover::Soft => self.soft_cover_accuracy_penalty,
+            Cover::Hard => self.hard_cover_accuracy_penalty,
+        }
+    }
+
     pub fn concealment_roll(&self, concealment: i32) -> bool {
         if concealment == 0 {
             return true;
         }
-        let roll = gen_rand(1, 101);
+        let roll = combat_roll(1, 101);
         debug!("Concealment roll: {} against {}", roll, concealment);
         roll > concealment
     }
@@ -492,9 +840,115 @@ pub enum ItemKind {
     Other,
 }
 
+/// The selectable difficulty levels
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum DifficultyLevel {
+    Story,
+
+    #[default]
+    Normal,
+
+    Hard,
+
+    /// Uses the scaling chosen by the player rather than one from the rules
+    Custom,
+}
+
+impl DifficultyLevel {
+    pub fn iter() -> impl Iterator<Item = &'static DifficultyLevel> {
+        use self::DifficultyLevel::*;
+        [Story, Normal, Hard, Custom].iter()
+    }
+
+    pub fn to_str(self) -> &'static str {
+        use self::DifficultyLevel::*;
+        match self {
+            Story => "story",
+            Normal => "normal",
+            Hard => "hard",
+            Custom => "custom",
+        }
+    }
+}
+
+/// Multiplicative modifiers applied to the rules for a difficulty level
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DifficultyScaling {
+    /// Maximum hit points of hostile creatures
+    pub enemy_hp: f32,
+
+    /// Damage dealt by hostile creatures
+    pub enemy_damage: f32,
+
+    /// Experience awarded to the party
+    pub xp: f32,
+
+    /// Prices paid when buying from merchants.  Sale prices are divided
+    /// by this factor
+    pub merchant_prices: f32,
+}
+
+impl Default for DifficultyScaling {
+    fn default() -> DifficultyScaling {
+        DifficultyScaling {
+            enemy_hp: 1.0,
+            enemy_damage: 1.0,
+            xp: 1.0,
+            merchant_prices: 1.0,
+        }
+    }
+}
+
+/// The difficulty chosen for a game, along with the scaling used when
+/// the level is `Custom`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Difficulty {
+    pub level: DifficultyLevel,
+    pub custom: DifficultyScaling,
+}
+
+impl Difficulty {
+    pub fn scaling(&self) -> DifficultyScaling {
+        match self.level {
+            DifficultyLevel::Custom => self.custom,
+            level => Module::rules()
+                .difficulty
+                .get(&level)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// How XP awarded to the party is divided among its members
+#[derive(Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum XpDistribution {
+    /// Each party member receives the full amount
+    #[default]
+    Full,
+
+    /// The amount is divided evenly between all party members
+    Split,
+
+    /// Each party member within the share distance of the source receives
+    /// the full amount
+    Proximity,
+}
+
+/// The cover a target has from a ranged attacker, based on line of sight
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Cover {
+    None,
+    Soft,
+    Hard,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Default)]
 pub struct HitFlags {
     pub flanking: bool,
+    pub backstab: bool,
     pub sneak_attack: bool,
     pub concealment: bool,
 }
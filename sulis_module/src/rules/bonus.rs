@@ -30,6 +30,7 @@ pub enum BonusKind {
     Armor(i32),
     ArmorKind { kind: DamageKind, amount: i32 },
     Resistance { kind: DamageKind, amount: i32 },
+    Immunity(DamageKind),
     Damage(Damage),
     ArmorProficiency(ArmorKind),
     WeaponProficiency(WeaponKind),
@@ -162,6 +163,19 @@ impl BonusList {
             apply_modifiers(bonus, penalty_mod, bonus_mod);
         }
     }
+
+    /// Scales the armor bonuses in this list by `multiplier`, rounding down.
+    /// Used for degraded equipment
+    pub fn apply_armor_multiplier(&mut self, multiplier: f32) {
+        for bonus in self.0.iter_mut() {
+            match &mut bonus.kind {
+                BonusKind::Armor(amount) | BonusKind::ArmorKind { amount, .. } => {
+                    *amount = (*amount as f32 * multiplier) as i32;
+                }
+                _ => (),
+            }
+        }
+    }
 }
 
 macro_rules! get_mod {
@@ -228,6 +242,7 @@ fn apply_modifiers(bonus: &mut Bonus, neg: f32, pos: f32) {
         Attribute { attribute, amount } => get_mod!(Attribute { attribute, amount }: i8, neg, pos),
         ArmorProficiency(_)
         | WeaponProficiency(_)
+        | Immunity(_)
         | MoveDisabled
         | AttackDisabled
         | Hidden
@@ -328,6 +343,7 @@ pub fn merge_if_dup(first: &Bonus, sec: &Bonus) -> Option<Bonus> {
             _ => None,
         },
         ArmorProficiency(kind) => merge_dup!(ArmorProficiency(kind): sec, test_name, when),
+        Immunity(kind) => merge_dup!(Immunity(kind): sec, test_name, when),
         WeaponProficiency(kind) => merge_dup!(WeaponProficiency(kind): sec, test_name, when),
 
         MoveDisabled => merge_dup!(MoveDisabled: sec, when),
@@ -17,7 +17,7 @@
 use std::fmt::{self, Display};
 use std::slice::Iter;
 
-use sulis_core::util::gen_rand;
+use sulis_core::util::combat_roll;
 
 #[derive(Clone)]
 pub struct DamageList {
@@ -295,6 +295,6 @@ impl Damage {
     }
 
     pub fn roll(&self) -> u32 {
-        gen_rand(self.min, self.max + 1)
+        combat_roll(self.min, self.max + 1)
     }
 }
\ No newline at end of file
@@ -20,6 +20,9 @@ use crate::rules::DamageKind;
 #[serde(deny_unknown_fields)]
 pub struct Resistance {
     kinds: [i32; 8],
+
+    #[serde(default)]
+    immune: [bool; 8],
 }
 
 impl Resistance {
@@ -32,8 +35,17 @@ impl Resistance {
         self.kinds[index] += amount;
     }
 
+    pub fn add_immunity(&mut self, kind: DamageKind) {
+        if kind == DamageKind::Raw {
+            return;
+        }
+
+        self.immune[kind.index()] = true;
+    }
+
     /// Returns the amount of damage resistance that this armor value
-    /// applies to the specified damage kind.
+    /// applies to the specified damage kind.  A negative amount is a
+    /// vulnerability, increasing the damage taken.
     pub fn amount(&self, check_kind: DamageKind) -> i32 {
         if check_kind == DamageKind::Raw {
             return 0;
@@ -42,13 +54,37 @@ impl Resistance {
         self.kinds[check_kind.index()]
     }
 
+    /// Returns true if damage of the specified kind is ignored entirely,
+    /// regardless of the resistance amount.
+    pub fn is_immune(&self, check_kind: DamageKind) -> bool {
+        if check_kind == DamageKind::Raw {
+            return false;
+        }
+
+        self.immune[check_kind.index()]
+    }
+
+    pub fn is_vulnerable(&self, check_kind: DamageKind) -> bool {
+        !self.is_immune(check_kind) && self.amount(check_kind) < 0
+    }
+
+    /// Returns the multiplier applied to damage of the specified kind,
+    /// zero for immunities and resistances of 100% or more.
+    pub fn damage_factor(&self, check_kind: DamageKind) -> f32 {
+        if self.is_immune(check_kind) {
+            return 0.0;
+        }
+
+        ((100 - self.amount(check_kind)) as f32 / 100.0).max(0.0)
+    }
+
     pub fn is_empty(&self) -> bool {
         for val in self.kinds.iter() {
-            if *val > 0 {
+            if *val != 0 {
                 return false;
             }
         }
 
-        true
+        !self.immune.iter().any(|immune| *immune)
     }
 }
@@ -19,12 +19,12 @@ use std::rc::Rc;
 
 use crate::rules::bonus::{AttackBonuses, AttackBuilder, Bonus, BonusKind, BonusList};
 use crate::rules::{
-    AccuracyKind, Armor, ArmorKind, Attack, AttributeList, Damage, HitKind, Resistance, Slot,
-    WeaponKind, WeaponStyle,
+    AccuracyKind, Armor, ArmorKind, Attack, Attribute, AttributeList, Damage, HitKind, Resistance,
+    Slot, WeaponKind, WeaponStyle,
 };
 use crate::{Actor, Module};
 use sulis_core::image::Image;
-use sulis_core::util::{gen_rand, ExtInt};
+use sulis_core::util::{combat_roll, ExtInt};
 
 #[derive(Clone)]
 pub struct StatList {
@@ -81,6 +81,8 @@ pub struct StatList {
     pub crit_immunity: bool,
     pub free_ability_group_use: bool,
     pub caster_level: i32,
+    pub carried_weight: i32,
+    pub encumbered: bool,
     has_shield: bool,
     group_uses_per_encounter: HashMap<String, ExtInt>,
     group_uses_per_day: HashMap<String, ExtInt>,
@@ -136,6 +138,8 @@ impl StatList {
             crit_immunity: false,
             free_ability_group_use: false,
             caster_level: 0,
+            carried_weight: 0,
+            encumbered: false,
             has_shield: false,
             group_uses_per_encounter: HashMap::new(),
             group_uses_per_day: HashMap::new(),
@@ -192,7 +196,7 @@ impl StatList {
             AccuracyKind::Ranged => self.ranged_accuracy + bonuses.ranged_accuracy,
             AccuracyKind::Spell => self.spell_accuracy + bonuses.spell_accuracy,
         };
-        let roll = gen_rand(1, 101);
+        let roll = combat_roll(1, 101);
         debug!(
             "Attack roll: {} with accuracy {} against {}",
             roll, accuracy, defense
@@ -205,7 +209,7 @@ impl StatList {
         let result = roll + accuracy - defense;
 
         if !crit_immunity && (100 - roll) < self.crit_chance + bonuses.crit_chance {
-            let roll2 = gen_rand(1, 101);
+            let roll2 = combat_roll(1, 101);
             let result2 = roll2 + accuracy - defense;
             if result2 > self.graze_threshold + bonuses.graze_threshold {
                 HitKind::Crit
@@ -339,6 +343,7 @@ impl StatList {
             Armor(amount) => self.armor.add_base(amount * times_i32),
             ArmorKind { kind, amount } => self.armor.add_kind(*kind, amount * times_i32),
             Resistance { kind, amount } => self.resistance.add_kind(*kind, amount * times_i32),
+            Immunity(kind) => self.resistance.add_immunity(*kind),
             Damage(damage) => self.bonus_damage.push(damage.mult(times)),
             ArmorProficiency(kind) => {
                 if !self.armor_proficiencies.contains(kind) {
@@ -453,8 +458,14 @@ impl StatList {
         let is_melee = attacks[0].0.is_melee();
 
         let mut attack_range = None;
-        for (builder, weapon_kind) in attacks {
-            let attack = Attack::new(builder, self, weapon_kind).mult(multiplier);
+        for (index, (builder, weapon_kind)) in attacks.into_iter().enumerate() {
+            let mut attack = Attack::new(builder, self, weapon_kind).mult(multiplier);
+
+            // the off hand weapon is always the second attack
+            if index > 0 {
+                attack.bonuses.melee_accuracy -= rules.off_hand_accuracy_penalty;
+                attack.bonuses.ranged_accuracy -= rules.off_hand_accuracy_penalty;
+            }
 
             if attack_range.is_none() {
                 attack_range = Some(attack.distance());
@@ -533,4 +544,30 @@ impl StatList {
             self.attack_range += size_bonus;
         }
     }
+
+    /// Returns the maximum weight that can be carried without becoming
+    /// encumbered, based on this stat list's current strength
+    pub fn max_carry_weight(&self) -> Option<i32> {
+        Module::rules().max_carry_weight(self.attributes.get(Attribute::Strength))
+    }
+
+    /// Sets the carried weight and applies the encumbrance penalties from the
+    /// rules if it exceeds the carry limit.  Must be called after `finalize`
+    pub fn apply_encumbrance(&mut self, carried_weight: i32) {
+        self.carried_weight = carried_weight;
+
+        let max_weight = match self.max_carry_weight() {
+            None => return,
+            Some(weight) => weight,
+        };
+
+        if carried_weight <= max_weight {
+            return;
+        }
+
+        self.encumbered = true;
+        if let Some(ref enc) = Module::rules().encumbrance {
+            self.movement_rate *= enc.movement_rate_factor;
+        }
+    }
 }
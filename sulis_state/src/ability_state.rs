@@ -35,11 +35,14 @@ pub enum DisabledReason {
     RequiresActiveMode,
     CombatOnly,
     OnCooldown,
+    GroupOnCooldown,
+    Encumbered,
 }
 
 pub struct AbilityState {
     pub ability: Rc<Ability>,
     pub group: String,
+    pub cooldown_group: Option<String>,
     pub(crate) remaining_duration: ExtInt,
     pub combat_only: bool,
     pub requires_melee: bool,
@@ -70,24 +73,27 @@ fn get_modes(ability: &Ability, input: &[String]) -> Vec<Rc<Ability>> {
 
 impl AbilityState {
     pub fn new(ability: &Rc<Ability>) -> AbilityState {
-        let (group, combat_only, modes, melee, ranged, shield) = match ability.active {
-            None => panic!(),
-            Some(ref active) => {
-                let modes = get_modes(ability, &active.requires_active_mode);
-                (
-                    active.group.name(),
-                    active.combat_only,
-                    modes,
-                    active.requires_melee,
-                    active.requires_ranged,
-                    active.requires_shield,
-                )
-            }
-        };
+        let (group, cooldown_group, combat_only, modes, melee, ranged, shield) =
+            match ability.active {
+                None => panic!(),
+                Some(ref active) => {
+                    let modes = get_modes(ability, &active.requires_active_mode);
+                    (
+                        active.group.name(),
+                        active.cooldown_group.clone(),
+                        active.combat_only,
+                        modes,
+                        active.requires_melee,
+                        active.requires_ranged,
+                        active.requires_shield,
+                    )
+                }
+            };
 
         AbilityState {
             ability: Rc::clone(ability),
             group,
+            cooldown_group,
             remaining_duration: ExtInt::Int(0),
             combat_only,
             cur_duration: 0,
@@ -111,6 +117,10 @@ impl AbilityState {
         }
     }
 
+    pub fn cooldown_millis(&self) -> u32 {
+        self.ability.active.as_ref().unwrap().cooldown * ROUND_TIME_MILLIS
+    }
+
     pub fn activate_ap(&self) -> u32 {
         self.ability.active.as_ref().unwrap().ap
     }
@@ -127,6 +137,9 @@ impl AbilityState {
         if self.requires_ranged && !stats.attack_is_ranged() {
             return RequiresRanged;
         }
+        if stats.encumbered && Module::rules().is_disabled_when_encumbered(&self.ability.id) {
+            return Encumbered;
+        }
 
         if !self.requires_active_mode.is_empty() {
             let mut found = false;
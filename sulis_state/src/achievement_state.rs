@@ -0,0 +1,236 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use sulis_core::config::{self, FeedbackChannel};
+use sulis_core::resource::{read_single_resource_path, write_to_file};
+use sulis_core::tr;
+use sulis_module::{achievement::AchievementKind, Achievement, Module};
+
+use crate::{
+    area_feedback_text::ColorKind, AreaFeedbackText, ChangeListener, GameEvent, GameState,
+};
+
+const PROFILE_FILENAME: &str = "achievements.yml";
+
+/// How often changed progress is written to the profile file.  Unlocking an
+/// achievement writes the file immediately
+const PROFILE_WRITE_INTERVAL_MILLIS: u32 = 10_000;
+
+thread_local! {
+    static PROFILE: RefCell<Option<AchievementProfile>> = RefCell::new(None);
+    static WRITE_TIMER: Cell<u32> = Cell::new(0);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AchievementProgress {
+    pub count: u32,
+    pub unlocked: bool,
+}
+
+/// Achievement progress for all campaigns, keyed by campaign ID and then
+/// achievement ID.  This is stored in the user directory rather than
+/// in save files, so progress is kept across every playthrough.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct AchievementProfile {
+    campaigns: HashMap<String, HashMap<String, AchievementProgress>>,
+
+    #[serde(skip)]
+    dirty: bool,
+}
+
+fn profile_file_path() -> PathBuf {
+    let mut path = config::USER_DIR.clone();
+    path.push(PROFILE_FILENAME);
+    path
+}
+
+impl AchievementProfile {
+    fn read() -> AchievementProfile {
+        let path = profile_file_path();
+        if !path.is_file() {
+            return AchievementProfile::default();
+        }
+
+        match read_single_resource_path(&path) {
+            Ok(profile) => profile,
+            Err(e) => {
+                warn!("Error reading achievements file");
+                warn!("{}", e);
+                AchievementProfile::default()
+            }
+        }
+    }
+
+    fn write(&mut self) {
+        self.dirty = false;
+        if let Err(e) = write_to_file(profile_file_path(), self) {
+            warn!("Error writing achievements file");
+            warn!("{}", e);
+        }
+    }
+
+    fn current_campaign(&mut self) -> &mut HashMap<String, AchievementProgress> {
+        let campaign_id = Module::campaign().id.to_string();
+        self.campaigns.entry(campaign_id).or_default()
+    }
+}
+
+fn with_profile<T, F: FnOnce(&mut AchievementProfile) -> T>(f: F) -> T {
+    PROFILE.with(|profile| {
+        let mut profile = profile.borrow_mut();
+        f(profile.get_or_insert_with(AchievementProfile::read))
+    })
+}
+
+/// Tracks progress toward the achievements defined by the current campaign,
+/// by listening to game events.
+pub struct AchievementState;
+
+impl AchievementState {
+    /// Adds the game event listener which updates achievement progress.  This
+    /// must be called each time the game state is created, as doing so
+    /// clears all event listeners.
+    pub(crate) fn add_listener() {
+        GameState::add_event_listener(ChangeListener::new(
+            "achievements",
+            Box::new(AchievementState::handle_event),
+        ));
+    }
+
+    /// Writes any progress changed since the last write to the profile
+    /// file, once enough time has passed since the last write
+    pub(crate) fn update(millis: u32) {
+        let elapsed = WRITE_TIMER.with(|t| t.get()) + millis;
+        if elapsed < PROFILE_WRITE_INTERVAL_MILLIS {
+            WRITE_TIMER.with(|t| t.set(elapsed));
+            return;
+        }
+
+        AchievementState::write_profile();
+    }
+
+    /// Writes any progress changed since the last write to the profile
+    /// file.  This should be called when leaving the game
+    pub fn write_profile() {
+        WRITE_TIMER.with(|t| t.set(0));
+        PROFILE.with(|profile| {
+            if let Some(profile) = profile.borrow_mut().as_mut() {
+                if profile.dirty {
+                    profile.write();
+                }
+            }
+        });
+    }
+
+    pub fn progress(id: &str) -> AchievementProgress {
+        with_profile(|profile| {
+            let progress = profile.current_campaign();
+            progress.get(id).copied().unwrap_or_default()
+        })
+    }
+
+    fn handle_event(event: &GameEvent) {
+        let achievements = Module::all_achievements();
+        if achievements.is_empty() {
+            return;
+        }
+
+        let party_kill = match event {
+            GameEvent::EntityDied { killer, .. } => {
+                let mgr = GameState::turn_manager();
+                let killer = mgr.borrow().entity_checked(*killer);
+                killer.is_some_and(|killer| killer.borrow().is_party_member())
+            }
+            _ => false,
+        };
+
+        let unlocked: Vec<Rc<Achievement>> = with_profile(|profile| {
+            let all_progress = profile.current_campaign();
+            let mut changed = false;
+            let mut unlocked = Vec::new();
+            for achievement in achievements {
+                let progress = all_progress.entry(achievement.id.to_string()).or_default();
+                if progress.unlocked {
+                    continue;
+                }
+
+                let count = match (&achievement.kind, event) {
+                    (AchievementKind::Kills { actor }, GameEvent::EntityDied { actor: id, .. })
+                        if party_kill =>
+                    {
+                        match actor {
+                            Some(actor) if actor != id => continue,
+                            _ => progress.count + 1,
+                        }
+                    }
+                    (
+                        AchievementKind::QuestComplete { quest },
+                        GameEvent::QuestCompleted { quest: id },
+                    ) if quest == id => progress.count + 1,
+                    (AchievementKind::Coins { .. }, GameEvent::CoinsChanged { total }) => {
+                        progress.count.max(*total.max(&0) as u32)
+                    }
+                    _ => continue,
+                };
+
+                if count == progress.count {
+                    continue;
+                }
+
+                changed = true;
+                progress.count = count;
+                if progress.count >= achievement.target() {
+                    progress.unlocked = true;
+                    unlocked.push(achievement);
+                }
+            }
+
+            if changed {
+                profile.dirty = true;
+            }
+            if !unlocked.is_empty() {
+                profile.write();
+            }
+            unlocked
+        });
+
+        for achievement in unlocked {
+            info!("Unlocked achievement '{}'", achievement.id);
+            AchievementState::show_unlocked(&achievement);
+        }
+    }
+
+    fn show_unlocked(achievement: &Achievement) {
+        let player = GameState::player();
+        let area = GameState::area_state();
+        if area.borrow().area.area.id != player.borrow().location.area_id {
+            return;
+        }
+
+        let mut feedback = AreaFeedbackText::with_target(&player.borrow(), &area.borrow());
+        feedback.set_channel(FeedbackChannel::Status);
+        let text = tr!("feedback.achievement", name = achievement.name);
+        feedback.add_entry(text, ColorKind::Info);
+        area.borrow_mut().add_feedback_text(feedback);
+    }
+}
@@ -15,6 +15,7 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::io::Error;
 use std::rc::Rc;
@@ -27,8 +28,9 @@ use crate::{
 use sulis_core::image::{Image, LayeredImage};
 use sulis_core::io::GraphicsRenderer;
 use sulis_core::util::{invalid_data_error, ExtInt, Offset, Scale};
-use sulis_module::{Ability, Actor, ActorBuilder, Faction, ImageLayer, Module};
-use sulis_module::{BonusList, ItemKind, ItemState, QuickSlot, Slot, StatList};
+use sulis_module::{Ability, Actor, ActorBuilder, Attribute, AttributeList, Class, Faction};
+use sulis_module::{ImageLayer, LayerInsert, Module};
+use sulis_module::{BonusList, ItemKind, ItemState, QuickSlot, Slot, StatList, ROUND_TIME_MILLIS};
 
 pub struct ActorState {
     pub actor: Rc<Actor>,
@@ -100,6 +102,8 @@ impl ActorState {
 
         let mut inventory = Inventory::empty();
         inventory.load(save.equipped, save.quick)?;
+        // there are only two weapon sets
+        inventory.weapon_set = cmp::min(save.weapon_set, 1);
 
         save.p_stats.load(actor.base_class());
 
@@ -228,6 +232,44 @@ impl ActorState {
         self.p_stats.is_threatened()
     }
 
+    /// Returns the indices of all entities currently threatening this one
+    pub fn threatened_by(&self) -> Vec<usize> {
+        self.p_stats.threatened_by().to_vec()
+    }
+
+    /// Returns true if this actor is currently able to make an attack of
+    /// opportunity.  This requires one of its classes to allow them, a usable
+    /// melee attack, and that its reaction for this round is unused.
+    pub fn can_make_opportunity_attack(&self) -> bool {
+        if self.is_dead() || self.stats.attack_disabled || !self.stats.attack_is_melee() {
+            return false;
+        }
+
+        if self.p_stats.is_reaction_used() {
+            return false;
+        }
+
+        self.actor
+            .levels
+            .iter()
+            .any(|(class, _)| class.opportunity_attacks)
+    }
+
+    /// Makes a free attack of opportunity against the target, using up
+    /// this actor's reaction until the start of its next turn
+    pub fn opportunity_attack(
+        parent: &Rc<RefCell<EntityState>>,
+        target: &Rc<RefCell<EntityState>>,
+    ) {
+        info!(
+            "'{}' makes an attack of opportunity against '{}'",
+            parent.borrow().actor.actor.name,
+            target.borrow().actor.actor.name
+        );
+        parent.borrow_mut().actor.p_stats.set_reaction_used(true);
+        EntityState::attack(parent, target, None, false);
+    }
+
     pub fn add_threatening(&mut self, index: usize) {
         self.p_stats.add_threatening(index);
     }
@@ -305,6 +347,15 @@ impl ActorState {
         self.ability_states.get_mut(id)
     }
 
+    /// Returns the owned upgrade tier of the specified ability, starting at 1
+    /// for the base ability, or 0 if the ability is not owned
+    pub fn ability_tier(&self, id: &str) -> u32 {
+        match self.actor.ability_level(id) {
+            None => 0,
+            Some(level) => level + 1,
+        }
+    }
+
     /// Returns true if the parent can swap weapons, false otherwise
     pub fn can_swap_weapons(&self) -> bool {
         if self.p_stats.is_inventory_locked() {
@@ -317,12 +368,27 @@ impl ActorState {
     /// Returns true if this actor can use the item in the specified quick slot
     /// now - which includes having sufficient AP, false otherwise
     pub fn can_use_quick(&self, slot: QuickSlot) -> bool {
+        if GameState::is_combat_active() && self.quick_item_uses_remaining() == Some(0) {
+            return false;
+        }
+
         match self.inventory.quick(slot) {
             None => false,
             Some(item) => self.can_use(item),
         }
     }
 
+    /// Returns the number of quick slot item uses remaining in the current
+    /// combat, or None if uses are unlimited
+    pub fn quick_item_uses_remaining(&self) -> Option<u32> {
+        self.p_stats.quick_item_uses_remaining()
+    }
+
+    pub(crate) fn add_quick_item_use(&mut self) {
+        self.p_stats.add_quick_item_use();
+        self.listeners.notify(self);
+    }
+
     /// Returns true if this actor can use the item at some point - not
     /// taking AP into consideration, false otherwise
     pub fn can_use_sometime(&self, item_state: &ItemState) -> bool {
@@ -387,7 +453,16 @@ impl ActorState {
                     return NotEnoughClassStat;
                 }
 
-                state.is_available(&self.stats, &self.current_active_modes())
+                match state.is_available(&self.stats, &self.current_active_modes()) {
+                    Enabled => {
+                        if self.group_cooldown_rounds(id) > 0 {
+                            GroupOnCooldown
+                        } else {
+                            Enabled
+                        }
+                    }
+                    reason => reason,
+                }
             }
         }
     }
@@ -412,6 +487,10 @@ impl ActorState {
                     return false;
                 }
 
+                if self.group_cooldown_rounds(id) > 0 {
+                    return false;
+                }
+
                 state.is_available(&self.stats, &self.current_active_modes())
                     == DisabledReason::Enabled
             }
@@ -437,6 +516,21 @@ impl ActorState {
         }
     }
 
+    /// Returns the number of rounds remaining on the shared cooldown of the
+    /// cooldown group of the specified ability, or zero if it has no group
+    pub fn group_cooldown_rounds(&self, id: &str) -> u32 {
+        let group = match self.ability_states.get(id) {
+            None => return 0,
+            Some(state) => match state.cooldown_group {
+                None => return 0,
+                Some(ref group) => group,
+            },
+        };
+
+        let millis = self.p_stats.group_cooldown(group);
+        (millis as f32 / ROUND_TIME_MILLIS as f32).ceil() as u32
+    }
+
     pub fn activate_ability_state(&mut self, id: &str) {
         let state = match self.ability_states.get_mut(id) {
             None => return,
@@ -444,6 +538,19 @@ impl ActorState {
         };
         state.activate();
 
+        if let Some(group) = state.cooldown_group.clone() {
+            self.p_stats
+                .set_group_cooldown(&group, state.cooldown_millis());
+
+            for other in self.ability_states.values_mut() {
+                if other.cooldown_group.as_ref() == Some(&group) {
+                    other.listeners.notify(other);
+                }
+            }
+        }
+
+        let state = self.ability_states.get(id).unwrap();
+
         let decrement_uses = !self.stats.free_ability_group_use;
 
         if decrement_uses {
@@ -486,6 +593,62 @@ impl ActorState {
         self.effects.iter().map(|(index, _)| index)
     }
 
+    /// Levels up this actor by one level in the specified `class`, adding the
+    /// specified `abilities` and setting the base `attributes`.  Any attribute
+    /// increases must add up to the points granted by the rules for the new level,
+    /// otherwise an error is returned and the actor is not modified.  Fewer
+    /// points may be spent only if every attribute is already at the maximum.
+    pub fn level_up(
+        &mut self,
+        class: Rc<Class>,
+        abilities: Vec<Rc<Ability>>,
+        attributes: AttributeList,
+    ) -> Result<(), Error> {
+        let level = self.actor.total_level + 1;
+        let mut spent = 0;
+        for attr in Attribute::iter() {
+            let delta = attributes.get(*attr) as i32 - self.actor.attributes.get(*attr) as i32;
+            if delta < 0 {
+                return invalid_data_error(&format!(
+                    "Level up of '{}' decreases attribute {:?}",
+                    self.actor.id, attr
+                ));
+            }
+            spent += delta;
+        }
+
+        let rules = Module::rules();
+        let points = rules.level_up_attribute_points(level);
+        let all_at_max = Attribute::iter().all(|attr| {
+            let max = cmp::max(
+                self.actor.attributes.get(*attr) as i32,
+                rules.builder_max_attribute,
+            );
+            attributes.get(*attr) as i32 >= max
+        });
+        if spent > points || (spent < points && !all_at_max) {
+            return invalid_data_error(&format!(
+                "Level up of '{}' spends {} attribute points, but {} are granted",
+                self.actor.id, spent, points
+            ));
+        }
+
+        let mut new_actor = Actor::from(
+            &self.actor,
+            Some((class, 1)),
+            self.xp(),
+            abilities,
+            Vec::new(),
+            self.actor.inventory.clone(),
+        );
+
+        new_actor.attributes = attributes;
+
+        self.replace_actor(new_actor);
+        self.init_day();
+        Ok(())
+    }
+
     pub fn replace_actor(&mut self, new_actor: Actor) {
         self.actor = Rc::new(new_actor);
 
@@ -552,7 +715,7 @@ impl ActorState {
     }
 
     pub fn has_ap_for_any_action(&self) -> bool {
-        self.ap() >= self.get_move_ap_cost(1) || self.has_ap_to_attack()
+        self.ap() >= self.get_move_ap_cost(1, 0) || self.has_ap_to_attack()
     }
 
     /// Sets the specified item as the item at the quick slot.  Returns the
@@ -638,6 +801,38 @@ impl ActorState {
         &self.inventory
     }
 
+    /// Degrades each equipped item with durability by the specified `amount`,
+    /// recomputing stats if the armor provided by any item changes
+    pub fn wear_equipment(&mut self, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+
+        let mut armor_changed = false;
+        for item_state in self.inventory.equipped.values_mut() {
+            let multiplier = item_state.armor_multiplier();
+            item_state.add_wear(amount);
+            armor_changed |= item_state.armor_multiplier() != multiplier;
+        }
+
+        if armor_changed {
+            self.compute_stats();
+        }
+    }
+
+    /// Restores `amount` durability to the item equipped in `slot`, or all
+    /// durability if `amount` is None.  Returns false if there was no damaged
+    /// item to repair
+    pub fn repair_equipped(&mut self, slot: Slot, amount: Option<u32>) -> bool {
+        match self.inventory.equipped.get_mut(&slot) {
+            Some(item_state) if item_state.is_damaged() => item_state.repair(amount),
+            _ => return false,
+        }
+
+        self.compute_stats();
+        true
+    }
+
     pub fn is_dead(&self) -> bool {
         self.hp() <= 0
     }
@@ -663,9 +858,7 @@ impl ActorState {
             parent.borrow().actor.actor.id
         );
         if parent.borrow().is_party_member() {
-            for member in GameState::party().iter() {
-                member.borrow_mut().add_xp(reward.xp);
-            }
+            GameState::award_xp(reward.xp, target);
         } else {
             parent.borrow_mut().add_xp(reward.xp);
         }
@@ -727,9 +920,13 @@ impl ActorState {
         self.p_stats.ap()
     }
 
-    pub fn get_move_ap_cost(&self, squares: u32) -> u32 {
+    /// The AP cost to move the specified number of orthogonal `squares` and
+    /// `diagonals` squares
+    pub fn get_move_ap_cost(&self, squares: u32, diagonals: u32) -> u32 {
         let rules = Module::rules();
-        (((rules.movement_ap as f32) / self.stats.movement_rate) as u32 * squares).max(1)
+        let square_cost = (rules.movement_ap as f32) / self.stats.movement_rate;
+        let diagonal_cost = square_cost * rules.diagonal_move_cost;
+        (square_cost as u32 * squares + diagonal_cost as u32 * diagonals).max(1)
     }
 
     pub fn set_overflow_ap(&mut self, ap: i32) {
@@ -781,6 +978,7 @@ impl ActorState {
         for (_, ability_state) in self.ability_states.iter_mut() {
             ability_state.update(millis_elapsed);
         }
+        self.p_stats.elapse_group_cooldowns(millis_elapsed);
 
         let start_len = self.effects.len();
         self.effects
@@ -836,11 +1034,10 @@ impl ActorState {
 
     pub fn compute_stats(&mut self) {
         debug!("Compute stats for '{}'", self.actor.name);
-        self.stats = StatList::new(self.actor.attributes);
 
-        let mut layers_override = self.inventory().get_image_layers();
+        let mut layers_override = self.inventory().get_image_layers(&self.actor.race);
         for (layer, image) in self.anim_image_layers.iter() {
-            layers_override.insert(*layer, Rc::clone(image));
+            layers_override.insert(*layer, vec![LayerInsert::new(Rc::clone(image))]);
         }
 
         let layers = self.actor.image_layers().get_list_with(
@@ -852,31 +1049,49 @@ impl ActorState {
         );
         self.image = LayeredImage::new(layers, self.actor.hue);
 
-        self.stats.add(&self.actor.race.base_stats);
+        self.stats = self.stats_with_inventory(&self.inventory);
+
+        self.p_stats.recompute_level_up(&self.actor);
+
+        self.listeners.notify(self);
+    }
+
+    /// Computes the stats this actor would have if the specified item were
+    /// equipped, replacing whatever currently occupies its slot.  The actor
+    /// itself is not modified.
+    pub fn stats_if_equipped(&self, item: &ItemState) -> StatList {
+        let mut inventory = self.inventory.clone();
+        let _unequipped = inventory.equip(item.clone(), None);
+        self.stats_with_inventory(&inventory)
+    }
+
+    fn stats_with_inventory(&self, inventory: &Inventory) -> StatList {
+        let mut stats = StatList::new(self.actor.attributes);
+
+        stats.add(&self.actor.race.base_stats);
 
         for &(ref class, level) in self.actor.levels.iter() {
-            self.stats.add_multiple(&class.bonuses_per_level, level);
+            stats.add_multiple(&class.bonuses_per_level, level);
             for (ref group_id, amount) in class.group_uses_per_encounter(level).iter() {
-                self.stats
-                    .add_single_group_uses_per_encounter(group_id, *amount);
+                stats.add_single_group_uses_per_encounter(group_id, *amount);
             }
 
             for (ref group_id, amount) in class.group_uses_per_day(level).iter() {
-                self.stats.add_single_group_uses_per_day(group_id, *amount);
+                stats.add_single_group_uses_per_day(group_id, *amount);
             }
 
             for (stat_id, amount) in class.stats_max(level) {
-                self.stats.add_single_class_stat_max(stat_id.to_string(), *amount);
+                stats.add_single_class_stat_max(stat_id.to_string(), *amount);
             }
         }
 
         for ability in self.actor.abilities.iter() {
             let level = ability.level;
-            ability.ability.add_bonuses_to(level, &mut self.stats);
+            ability.ability.add_bonuses_to(level, &mut stats);
         }
 
         let mut attacks_list = Vec::new();
-        for item_state in self.inventory.equipped_iter() {
+        for item_state in inventory.equipped_iter() {
             let equippable = match &item_state.item.equippable {
                 None => continue,
                 Some(equippable) => {
@@ -897,26 +1112,33 @@ impl ActorState {
                 }
             };
 
-            self.stats.add(&equippable.bonuses);
+            let armor_multiplier = item_state.armor_multiplier();
+            if armor_multiplier < 1.0 {
+                let mut bonuses = equippable.bonuses.clone();
+                bonuses.apply_armor_multiplier(armor_multiplier);
+                stats.add(&bonuses);
+            } else {
+                stats.add(&equippable.bonuses);
+            }
         }
 
         for (_, ref bonuses) in self.effects.iter() {
-            self.stats.add(bonuses);
+            stats.add(bonuses);
         }
 
         let mut equipped_armor = HashMap::new();
         for slot in Slot::iter() {
-            if let Some(item_state) = self.inventory.equipped(*slot) {
+            if let Some(item_state) = inventory.equipped(*slot) {
                 if let ItemKind::Armor { kind } = item_state.item.kind {
                     equipped_armor.insert(*slot, kind);
                 }
             }
         }
 
-        let weapon_style = self.inventory.weapon_style();
+        let weapon_style = inventory.weapon_style();
         let is_threatened = self.is_threatened();
 
-        self.stats.finalize(
+        stats.finalize(
             &self.actor,
             attacks_list,
             equipped_armor,
@@ -924,8 +1146,13 @@ impl ActorState {
             is_threatened,
         );
 
-        self.p_stats.recompute_level_up(&self.actor);
+        stats.apply_encumbrance(inventory.weight());
 
-        self.listeners.notify(self);
+        if self.p_stats.faction == Faction::Hostile {
+            let factor = GameState::difficulty().scaling().enemy_hp;
+            stats.max_hp = ((stats.max_hp as f32 * factor).round() as i32).max(1);
+        }
+
+        stats
     }
 }
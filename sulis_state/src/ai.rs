@@ -15,11 +15,13 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::script::script_callback;
 use crate::{animation::Anim, EntityState, GameState, Script};
-use sulis_module::ai::FuncKind;
+use sulis_module::ai::{AITemplate, FuncKind};
+use sulis_module::Module;
 use sulis_core::config::Config;
 
 pub struct AI {
@@ -46,9 +48,13 @@ impl AI {
             return;
         }
 
-        if entity.borrow().is_party_member() {
-            self.ai = None;
-            return;
+        {
+            let entity = entity.borrow();
+            let auto = entity.is_auto_combat() && GameState::is_combat_active();
+            if entity.is_party_member() && !auto {
+                self.ai = None;
+                return;
+            }
         }
 
         let assign = match self.ai {
@@ -99,6 +105,94 @@ pub enum State {
 
 impl rlua::UserData for State {}
 
+/// The fraction of threat retained by an entity at the start of each of its turns
+const THREAT_DECAY_PER_ROUND: f32 = 0.75;
+
+/// Threat below this amount is dropped from the table entirely
+const MIN_THREAT: f32 = 0.5;
+
+/// The fraction of healing done that is added as threat towards the healer
+const HEALING_THREAT_FACTOR: f32 = 0.5;
+
+/// The threat an entity holds towards each other entity, keyed by
+/// entity index.  Threat is accumulated from damage, healing, and taunts,
+/// and decays each round.  AI scripts use it to prioritize their targets.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ThreatTable {
+    threat: HashMap<usize, f32>,
+}
+
+impl ThreatTable {
+    pub fn threat(&self, index: usize) -> f32 {
+        self.threat.get(&index).copied().unwrap_or(0.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threat.is_empty()
+    }
+
+    /// Returns the index of the entity with the highest threat, if any
+    pub fn highest(&self) -> Option<usize> {
+        self.threat
+            .iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| *index)
+    }
+
+    /// Adds the specified amount of threat towards the entity with `index`.
+    /// The amount may be negative, but total threat will not go below zero.
+    pub fn add(&mut self, index: usize, amount: f32) {
+        let threat = (self.threat(index) + amount).max(0.0);
+        if threat < MIN_THREAT {
+            self.threat.remove(&index);
+        } else {
+            self.threat.insert(index, threat);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.threat.remove(&index);
+    }
+
+    pub fn clear(&mut self) {
+        self.threat.clear();
+    }
+
+    pub(crate) fn decay(&mut self) {
+        for threat in self.threat.values_mut() {
+            *threat *= THREAT_DECAY_PER_ROUND;
+        }
+        self.threat.retain(|_, threat| *threat >= MIN_THREAT);
+    }
+}
+
+/// Adds threat towards the `healer` for every entity that is hostile to, and
+/// already holds threat towards, the healed `target`.
+pub(crate) fn add_healing_threat(
+    healer: &Rc<RefCell<EntityState>>,
+    target: &Rc<RefCell<EntityState>>,
+    amount: f32,
+) {
+    let healer_index = healer.borrow().index();
+    let threat = amount * HEALING_THREAT_FACTOR;
+
+    let mgr = GameState::turn_manager();
+    for entity in mgr.borrow().entity_iter() {
+        if Rc::ptr_eq(&entity, healer) || Rc::ptr_eq(&entity, target) {
+            continue;
+        }
+
+        let mut entity = entity.borrow_mut();
+        let target = target.borrow();
+        if !entity.is_hostile(&target) || entity.threat.threat(target.index()) == 0.0 {
+            continue;
+        }
+
+        entity.threat.add(healer_index, threat);
+    }
+}
+
 const MAX_ACTIONS: u32 = 10;
 const MAX_WAIT_TIME: u32 = 200;
 
@@ -147,15 +241,57 @@ impl EntityAI {
             return State::End;
         }
 
-        let ai_template = match &self.entity.borrow().actor.actor.ai {
+        let func = match script_data(&self.entity.borrow()) {
             None => return State::End,
-            Some(template) => Rc::clone(template),
+            Some(data) => data.action_func,
         };
 
-        let func = ai_template.hooks.get(&FuncKind::AiAction).map(|f| f.as_str()).unwrap_or("ai_action");
-
         self.actions_taken_this_turn += 1;
 
-        Script::ai(&self.entity, func)
+        Script::ai(&self.entity, &func)
+    }
+}
+
+/// The script used to run an entity's AI, along with the params passed to
+/// it and the function called to take each action
+pub(crate) struct ScriptData {
+    pub(crate) script: String,
+    pub(crate) params: HashMap<String, i32>,
+    pub(crate) action_func: String,
+}
+
+/// Finds the AI script for the specified entity.  Party members fighting
+/// automatically without an AI of their own use the rules' party AI.
+pub(crate) fn script_data(entity: &EntityState) -> Option<ScriptData> {
+    let actor = &entity.actor.actor;
+    let template = match actor.ai {
+        Some(ref ai) => Some(Rc::clone(ai)),
+        None if entity.is_auto_combat() && actor.ai_script.is_none() => party_ai_template(),
+        None => None,
+    };
+
+    let script = match actor.ai_script_id() {
+        Some(script) => script.to_string(),
+        None => template.as_ref()?.script.to_string(),
+    };
+
+    let (params, action_func) = match template {
+        None => (HashMap::new(), None),
+        Some(ai) => (ai.params.clone(), ai.hooks.get(&FuncKind::AiAction).cloned()),
+    };
+
+    Some(ScriptData {
+        script,
+        params,
+        action_func: action_func.unwrap_or_else(|| "ai_action".to_string()),
+    })
+}
+
+fn party_ai_template() -> Option<Rc<AITemplate>> {
+    let id = Module::rules().party_ai.clone()?;
+    let template = Module::ai_template(&id);
+    if template.is_none() {
+        warn!("Party AI template '{}' not found", id);
     }
+    template
 }
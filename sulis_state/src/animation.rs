@@ -35,12 +35,15 @@ pub mod move_animation;
 
 pub mod particle_generator;
 
+pub mod projectile_animation;
+
 pub mod ranged_attack_animation;
 
 use self::melee_attack_animation::MeleeAttackAnimModel;
 use self::move_animation::MoveAnimModel;
 use self::particle_generator::Param;
 use self::particle_generator::{GeneratorModel, GeneratorState};
+use self::projectile_animation::ProjectileAnimModel;
 use self::ranged_attack_animation::RangedAttackAnimModel;
 use crate::{ChangeListener, Effect, EntityState, ScriptCallback};
 use sulis_core::{
@@ -48,7 +51,7 @@ use sulis_core::{
     io::GraphicsRenderer,
     util::{self, ExtInt, Offset, Scale},
 };
-use sulis_module::ImageLayer;
+use sulis_module::{ImageLayer, SpriteState};
 
 pub struct AnimState {
     no_draw_anims: Vec<Anim>,
@@ -95,7 +98,7 @@ impl AnimState {
             };
 
             match anim.kind {
-                RangedAttack { .. } => self.above_anims.push(anim),
+                RangedAttack { .. } | Projectile { .. } => self.above_anims.push(anim),
                 ParticleGenerator { .. } => {
                     if draw_above {
                         self.above_anims.push(anim);
@@ -177,6 +180,13 @@ impl AnimState {
         }
     }
 
+    /// Removes the particle generators placed at fixed points in the area with
+    /// `area_id`, without firing any further callbacks
+    pub fn remove_area_anims(&mut self, area_id: &str) {
+        AnimState::remove_area_vec(&mut self.below_anims, area_id);
+        AnimState::remove_area_vec(&mut self.above_anims, area_id);
+    }
+
     pub fn has_any_blocking_anims(&self) -> bool {
         AnimState::has_any_blocking_vec(&self.no_draw_anims)
             || AnimState::has_any_blocking_vec(&self.below_anims)
@@ -273,6 +283,16 @@ impl AnimState {
         }
     }
 
+    fn remove_area_vec(vec: &mut [Anim], area_id: &str) {
+        for anim in vec.iter_mut() {
+            if anim.is_in_area(area_id) {
+                anim.mark_for_removal();
+                anim.completion_callbacks.clear();
+                anim.update_callbacks.clear();
+            }
+        }
+    }
+
     fn update_vec(
         vec: &mut Vec<Anim>,
         elapsed: u32,
@@ -336,6 +356,9 @@ pub(in crate::animation) enum AnimKind {
     /// An attack with a ranged weapon
     RangedAttack { model: RangedAttackAnimModel },
 
+    /// A projectile or beam from a script, traveling along a set of waypoints
+    Projectile { model: ProjectileAnimModel },
+
     /// Movement of a single entity within an area
     Move { model: MoveAnimModel },
 
@@ -366,6 +389,11 @@ impl Anim {
         Anim::new(owner, ExtInt::Int(duration_millis), AnimKind::Wait)
     }
 
+    /// Creates a blocking wait that lasts until it is marked for removal
+    pub fn new_indefinite_wait(owner: &Rc<RefCell<EntityState>>) -> Anim {
+        Anim::new(owner, ExtInt::Infinity, AnimKind::Wait)
+    }
+
     pub fn new_entity_image_layer(
         owner: &Rc<RefCell<EntityState>>,
         duration_millis: ExtInt,
@@ -432,6 +460,14 @@ impl Anim {
         )
     }
 
+    pub(in crate::animation) fn new_projectile(
+        owner: &Rc<RefCell<EntityState>>,
+        duration_millis: ExtInt,
+        model: ProjectileAnimModel,
+    ) -> Anim {
+        Anim::new(owner, duration_millis, AnimKind::Projectile { model })
+    }
+
     pub(in crate::animation) fn new_move(
         mover: &Rc<RefCell<EntityState>>,
         duration_millis: u32,
@@ -583,6 +619,7 @@ impl Anim {
             RangedAttack { ref mut model } => {
                 ranged_attack_animation::update(&self.owner, model, frac)
             }
+            Projectile { ref mut model } => projectile_animation::update(model, millis),
             Move { ref mut model } => {
                 move_animation::update(&self.owner, &self.marked_for_removal, model, millis)
             }
@@ -598,6 +635,14 @@ impl Anim {
             ),
             _ => (),
         }
+
+        let sprite_state = match self.kind {
+            Move { .. } => SpriteState::Walk,
+            MeleeAttack { .. } | RangedAttack { .. } => SpriteState::Attack,
+            EntityDeath { .. } => SpriteState::Death,
+            _ => return,
+        };
+        self.owner.borrow_mut().set_sprite_state(sprite_state, millis);
     }
 
     fn cleanup_kind(&mut self) {
@@ -612,9 +657,18 @@ impl Anim {
             EntityColor { .. } => entity_color_animation::cleanup(&self.owner),
             EntitySubpos { .. } => entity_subpos_animation::cleanup(&self.owner),
             EntityScale { .. } => entity_scale_animation::cleanup(&self.owner),
-            MeleeAttack { .. } => melee_attack_animation::cleanup(&self.owner),
-            RangedAttack { .. } => ranged_attack_animation::cleanup(&self.owner),
-            Move { ref mut model } => move_animation::cleanup(&self.owner, model),
+            MeleeAttack { .. } => {
+                melee_attack_animation::cleanup(&self.owner);
+                self.owner.borrow_mut().set_sprite_state(SpriteState::Idle, 0);
+            }
+            RangedAttack { .. } => {
+                ranged_attack_animation::cleanup(&self.owner);
+                self.owner.borrow_mut().set_sprite_state(SpriteState::Idle, 0);
+            }
+            Move { ref mut model } => {
+                move_animation::cleanup(&self.owner, model);
+                self.owner.borrow_mut().set_sprite_state(SpriteState::Idle, 0);
+            }
             EntityDeath { .. } => {
                 entity_color_animation::cleanup(&self.owner);
                 self.owner.borrow_mut().marked_for_removal = true;
@@ -635,6 +689,9 @@ impl Anim {
             RangedAttack { ref model } => {
                 ranged_attack_animation::draw(model, renderer, offset, scale, millis)
             }
+            Projectile { ref model } => {
+                projectile_animation::draw(model, renderer, offset, scale, millis)
+            }
             ParticleGenerator {
                 ref state,
                 ref model,
@@ -692,6 +749,7 @@ impl Anim {
             EntityScale { .. } | EntityImageLayer { .. } => !self.duration_millis.is_infinite(),
             MeleeAttack { .. } => true,
             RangedAttack { .. } => true,
+            Projectile { model } => model.blocking,
             Move { .. } => true,
             Wait => true,
             NonBlockingWait => false,
@@ -699,6 +757,15 @@ impl Anim {
             EntityDeath { .. } => true,
         }
     }
+
+    /// Returns true if this is a particle generator placed at a fixed point
+    /// in the area with `area_id`
+    pub fn is_in_area(&self, area_id: &str) -> bool {
+        match &self.kind {
+            AnimKind::ParticleGenerator { model, .. } => model.area.as_deref() == Some(area_id),
+            _ => false,
+        }
+    }
 }
 
 /// Helper function to return the number of frames elapsed for the 'elapsed'
@@ -130,8 +130,15 @@ impl AnimSaveState {
                     Some(image) => image,
                 };
 
+                let extra_images = model
+                    .extra_images
+                    .iter()
+                    .filter_map(|id| ResourceSet::image(id))
+                    .collect();
+
                 let state = GeneratorState {
                     image,
+                    extra_images,
                     particles: state.particles,
                     gen_overflow: state.gen_overflow,
                     previous_secs: state.previous_secs,
@@ -18,11 +18,12 @@ use std::cell::{Cell, RefCell};
 use std::cmp;
 use std::rc::Rc;
 
-use crate::{animation::Anim, EntityState, GameState, animation::particle_generator::Param};
+use crate::path_finder::grid_line;
+use crate::{animation::Anim, ActorState, EntityState, GameState, animation::particle_generator::Param};
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::ui::animation_state;
 use sulis_core::util::{Offset, Point, Rect, Scale, ExtInt};
-use sulis_module::ObjectSize;
+use sulis_module::{Facing, ObjectSize};
 
 fn check_immediate_cancel(mover: &Rc<RefCell<EntityState>>, model: &mut MoveAnimModel) -> bool {
     let cur_area_id = mover.borrow().location.area_id.to_string();
@@ -46,6 +47,64 @@ fn check_immediate_cancel(mover: &Rc<RefCell<EntityState>>, model: &mut MoveAnim
     false
 }
 
+/// Any entity which threatened the mover prior to its last step but no longer
+/// does gets an attack of opportunity.  Returns true if any attacks were made,
+/// in which case the move is interrupted.
+fn check_opportunity_attacks(mover: &Rc<RefCell<EntityState>>, threatened_by: &[usize]) -> bool {
+    let mgr = GameState::turn_manager();
+    let mut attacked = false;
+    for index in threatened_by.iter() {
+        if mover.borrow().actor.p_stats().is_threatened_by(*index) {
+            continue;
+        }
+
+        let attacker = match mgr.borrow().entity_checked(*index) {
+            None => continue,
+            Some(entity) => entity,
+        };
+
+        {
+            let attacker = attacker.borrow();
+            if !attacker.actor.can_make_opportunity_attack()
+                || !attacker.is_hostile(&mover.borrow())
+            {
+                continue;
+            }
+        }
+
+        ActorState::opportunity_attack(&attacker, mover);
+        attacked = true;
+    }
+
+    attacked
+}
+
+/// The maximum number of frames a party member waits for another to move out
+/// of its way before passing through
+const MAX_WAIT_FRAMES: u32 = 8;
+
+/// Whether another party member is in the way of the `mover` at `p`
+fn is_blocked_by_party(mover: &Rc<RefCell<EntityState>>, p: Point) -> bool {
+    let mover = mover.borrow();
+    if !mover.is_party_member() {
+        return false;
+    }
+
+    let (w, h) = (mover.size.width, mover.size.height);
+    GameState::party().iter().any(|member| {
+        let member = member.borrow();
+        if member.index() == mover.index() {
+            return false;
+        }
+
+        let loc = &member.location;
+        p.x < loc.x + member.size.width
+            && loc.x < p.x + w
+            && p.y < loc.y + member.size.height
+            && loc.y < p.y + h
+    })
+}
+
 pub(in crate::animation) fn update(
     mover: &Rc<RefCell<EntityState>>,
     marked_for_removal: &Rc<Cell<bool>>,
@@ -57,6 +116,21 @@ pub(in crate::animation) fn update(
         return;
     }
 
+    // party members moving together out of combat wait for each other to
+    // get out of the way, holding at their current square, rather than
+    // passing through
+    let mut millis = millis.saturating_sub(model.wait_millis);
+    let next = model.last_frame_index as usize + 1;
+    if !model.combat_mode
+        && next < model.path.len()
+        && model.wait_millis < MAX_WAIT_FRAMES * model.frame_time_millis
+        && is_blocked_by_party(mover, model.path[next])
+    {
+        let hold = model.last_frame_index as u32 * model.frame_time_millis;
+        model.wait_millis += millis.saturating_sub(hold);
+        millis = hold;
+    }
+
     let frame_index = cmp::min(
         (millis / model.frame_time_millis) as usize,
         model.path.len() - 1,
@@ -67,6 +141,10 @@ pub(in crate::animation) fn update(
         let dx = model.smoothed_path[frame_index + 1].0 - model.smoothed_path[frame_index].0;
         let dy = model.smoothed_path[frame_index + 1].1 - model.smoothed_path[frame_index].1;
 
+        if let Some(facing) = Facing::from_direction(dx, dy) {
+            mover.borrow_mut().set_facing(facing);
+        }
+
         let dx = dx * frame_frac;
         let dy = dy * frame_frac;
 
@@ -79,19 +157,34 @@ pub(in crate::animation) fn update(
     if frame_index as i32 == model.last_frame_index {
         return;
     }
-    let move_ap = frame_index as i32 - model.last_frame_index;
+
+    let (mut squares, mut diagonals) = (0, 0);
+    for step in model.path[model.last_frame_index as usize..=frame_index].windows(2) {
+        if step[0].x != step[1].x && step[0].y != step[1].y {
+            diagonals += 1;
+        } else {
+            squares += 1;
+        }
+    }
     model.last_frame_index = frame_index as i32;
 
+    let threatened_by = mover.borrow().actor.threatened_by();
+
     let p = model.path[frame_index];
     let area_state = GameState::get_area_state(&mover.borrow().location.area_id).unwrap();
     if !area_state
         .borrow_mut()
-        .move_entity(mover, p.x, p.y, move_ap as u32)
+        .move_entity(mover, p.x, p.y, squares, diagonals)
     {
         marked_for_removal.set(true);
         return;
     }
 
+    if model.combat_mode && check_opportunity_attacks(mover, &threatened_by) {
+        marked_for_removal.set(true);
+        return;
+    }
+
     if frame_index == model.path.len() - 1 {
         marked_for_removal.set(true);
     }
@@ -157,7 +250,7 @@ pub(in crate::animation) fn cleanup(mover: &Rc<RefCell<EntityState>>, model: &mu
     }
 
     if let Some(p) = target {
-        area.borrow_mut().move_entity(mover, p.x, p.y, 0);
+        area.borrow_mut().move_entity(mover, p.x, p.y, 0, 0);
     }
 
     let new_pos = mover.borrow().location.to_point();
@@ -183,33 +276,7 @@ pub fn new(mover: &Rc<RefCell<EntityState>>, path: Vec<Point>, base_time_millis:
     let frame_time_millis = base_time_millis as f32 / mover.borrow().actor.stats.move_anim_rate;
     let frame_time_millis = frame_time_millis.round() as u32;
 
-    let mut smoothed_path = Vec::new();
-    let mut prev2 = path[0];
-    let mut prev = path[0];
-    let mut next = path[path.len() - 1];
-    let mut next2 = path[path.len() - 1];
-
-    for i in 0..(path.len() as i32) {
-        let cur: Point = path[i as usize];
-        if i < path.len() as i32 - 2 {
-            next = path[i as usize + 1];
-            next2 = path[i as usize + 2];
-        }
-        if i < path.len() as i32 - 1 {
-            // next2 is already set to the final point
-            next = path[i as usize + 1];
-        } else {
-            // next and next2 are already set to the final point
-        }
-
-        let avg_x = (prev2.x + prev.x + cur.x + next.x + next2.x) as f32 / 5.0;
-        let avg_y = (prev2.y + prev.y + cur.y + next.y + next2.y) as f32 / 5.0;
-
-        smoothed_path.push((avg_x, avg_y));
-
-        prev2 = prev;
-        prev = cur;
-    }
+    let smoothed_path = straighten_path(&path);
 
     let duration_millis = frame_time_millis * path.len() as u32;
     let area_id = mover.borrow().location.area_id.to_string();
@@ -218,6 +285,7 @@ pub fn new(mover: &Rc<RefCell<EntityState>>, path: Vec<Point>, base_time_millis:
         area_id,
         path,
         last_frame_index: 0,
+        wait_millis: 0,
         frame_time_millis,
         smoothed_path,
         owner_size: Rc::clone(&mover.borrow().size),
@@ -226,12 +294,45 @@ pub fn new(mover: &Rc<RefCell<EntityState>>, path: Vec<Point>, base_time_millis:
     Anim::new_move(mover, duration_millis, model)
 }
 
+/// Computes the drawn position for each point in the path.  Wherever the path
+/// follows the stepped line between two points, as in paths that have been
+/// smoothed, the points are placed evenly along the straight line between them.
+fn straighten_path(path: &[Point]) -> Vec<(f32, f32)> {
+    let mut straightened = vec![(path[0].x as f32, path[0].y as f32)];
+
+    let mut start = 0;
+    while start < path.len() - 1 {
+        let mut end = start + 1;
+        while end + 1 < path.len() && grid_line(path[start], path[end + 1]) == path[start..=end + 1]
+        {
+            end += 1;
+        }
+
+        let (from, to) = (path[start], path[end]);
+        let steps = (end - start) as f32;
+        for i in 1..=(end - start) {
+            let frac = i as f32 / steps;
+            straightened.push((
+                from.x as f32 + frac * (to.x - from.x) as f32,
+                from.y as f32 + frac * (to.y - from.y) as f32,
+            ));
+        }
+        start = end;
+    }
+
+    straightened
+}
+
 pub struct MoveAnimModel {
     area_id: String,
     combat_mode: bool, // whether this move was created in or out of combat.  a change in
     // this status will cancel the move
     pub(in crate::animation) path: Vec<Point>,
     pub(in crate::animation) last_frame_index: i32,
+
+    /// The total time spent waiting for other party members to move out of
+    /// the way, which delays the rest of the path
+    wait_millis: u32,
     frame_time_millis: u32,
     smoothed_path: Vec<(f32, f32)>,
     owner_size: Rc<ObjectSize>,
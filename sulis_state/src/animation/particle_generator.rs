@@ -323,8 +323,9 @@ pub(in crate::animation) fn update(
 
     state.gen_overflow = num_to_gen.fract();
 
+    let num_images = 1 + state.extra_images.len();
     for _ in 0..(num_to_gen.trunc() as i32) {
-        let particle = model.generate_particle();
+        let particle = model.generate_particle(num_images);
         state.particles.push(particle);
     }
 
@@ -385,28 +386,50 @@ pub(in crate::animation) fn draw(
         (offset.x, offset.y)
     };
 
+    let color = Color::new(
+        model.red.value,
+        model.green.value,
+        model.blue.value,
+        model.alpha.value,
+    );
+
+    // particles that fade must each be drawn with their own color
+    if let Some(end_color) = model.particle_end_color {
+        for particle in state.particles.iter() {
+            let mut draw_list = DrawList::empty_sprite();
+            particle.append_to_draw_list(state, &mut draw_list, offset_x, offset_y);
+            let frac = (particle.current_duration / particle.total_duration).clamp(0.0, 1.0);
+            let fade = |start: f32, end: f32| start * (1.0 + frac * (end - 1.0));
+            draw_list.set_color(Color::new(
+                fade(color.r, end_color[0]),
+                fade(color.g, end_color[1]),
+                fade(color.b, end_color[2]),
+                fade(color.a, end_color[3]),
+            ));
+            draw_particles(model, renderer, draw_list, offset_x, offset_y, scale);
+        }
+        return;
+    }
+
     let mut draw_list = DrawList::empty_sprite();
     for particle in state.particles.iter() {
-        let rect = Rect {
-            x: particle.position.0.value + offset_x,
-            y: particle.position.1.value + offset_y,
-            w: particle.width,
-            h: particle.height,
-        };
-        let millis = (particle.current_duration * 1000.0) as u32;
-        state
-            .image
-            .append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+        particle.append_to_draw_list(state, &mut draw_list, offset_x, offset_y);
     }
 
+    draw_list.set_color(color);
+    draw_particles(model, renderer, draw_list, offset_x, offset_y, scale);
+}
+
+fn draw_particles(
+    model: &GeneratorModel,
+    renderer: &mut dyn GraphicsRenderer,
+    mut draw_list: DrawList,
+    offset_x: f32,
+    offset_y: f32,
+    scale: Scale,
+) {
     if !draw_list.is_empty() {
         draw_list.set_scale(scale);
-        draw_list.set_color(Color::new(
-            model.red.value,
-            model.green.value,
-            model.blue.value,
-            model.alpha.value,
-        ));
         if let Some(ref rotation) = model.rotation {
             if let Some(ref centroid) = model.centroid {
                 let x = centroid.0.value + offset_x;
@@ -421,9 +444,15 @@ pub(in crate::animation) fn draw(
     }
 }
 
-pub fn new(owner: &Rc<RefCell<EntityState>>, image: Rc<dyn Image>, model: GeneratorModel) -> Anim {
+pub fn new(
+    owner: &Rc<RefCell<EntityState>>,
+    image: Rc<dyn Image>,
+    extra_images: Vec<Rc<dyn Image>>,
+    model: GeneratorModel,
+) -> Anim {
     let state = GeneratorState {
         image,
+        extra_images,
         particles: Vec::new(),
         gen_overflow: model.initial_overflow,
         previous_secs: 0.0,
@@ -433,6 +462,10 @@ pub fn new(owner: &Rc<RefCell<EntityState>>, image: Rc<dyn Image>, model: Genera
 }
 pub(in crate::animation) struct GeneratorState {
     pub(in crate::animation) image: Rc<dyn Image>,
+
+    /// Additional images that particles are randomly drawn with, along
+    /// with `image`
+    pub(in crate::animation) extra_images: Vec<Rc<dyn Image>>,
     pub(in crate::animation) particles: Vec<Particle>,
     pub(in crate::animation) gen_overflow: f32,
     pub(in crate::animation) previous_secs: f32,
@@ -470,9 +503,23 @@ pub struct GeneratorModel {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub particle_size_dist: Option<(Dist, Dist)>,
 
+    /// The color each particle fades to over its lifetime, as a multiple of
+    /// the generator color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub particle_end_color: Option<[f32; 4]>,
+
+    /// The IDs of additional images to randomly select from for each particle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_images: Vec<String>,
+
     pub draw_above_entities: bool,
 
     pub is_blocking: bool,
+
+    /// The area a generator at a fixed point was created in.  It is removed
+    /// when the party leaves that area
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area: Option<String>,
 }
 
 impl UserData for GeneratorModel {}
@@ -497,12 +544,15 @@ impl GeneratorModel {
             particle_duration_dist: None,
             particle_frame_time_offset_dist: None,
             particle_size_dist: None,
+            particle_end_color: None,
+            extra_images: Vec::new(),
             draw_above_entities: true,
             is_blocking: blocking,
+            area: None,
         }
     }
 
-    fn generate_particle(&self) -> Particle {
+    fn generate_particle(&self, num_images: usize) -> Particle {
         let mut position = self.position; // inherit position from generator
         position.0.initial_value = position.0.value;
         position.1.initial_value = position.1.value;
@@ -553,12 +603,19 @@ impl GeneratorModel {
             Some(dist) => dist.generate(),
         };
 
+        let image = if num_images > 1 {
+            gen_rand(0, num_images)
+        } else {
+            0
+        };
+
         Particle {
             position,
             total_duration,
             current_duration: initial_duration,
             width,
             height,
+            image,
         }
     }
 }
@@ -571,9 +628,37 @@ pub(in crate::animation) struct Particle {
     current_duration: f32,
     width: f32,
     height: f32,
+
+    #[serde(default)]
+    image: usize,
 }
 
 impl Particle {
+    fn append_to_draw_list(
+        &self,
+        state: &GeneratorState,
+        draw_list: &mut DrawList,
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let image = match self.image {
+            0 => &state.image,
+            index => match state.extra_images.get(index - 1) {
+                None => &state.image,
+                Some(image) => image,
+            },
+        };
+
+        let rect = Rect {
+            x: self.position.0.value + offset_x,
+            y: self.position.1.value + offset_y,
+            w: self.width,
+            h: self.height,
+        };
+        let millis = (self.current_duration * 1000.0) as u32;
+        image.append_to_draw_list(draw_list, &animation_state::NORMAL, rect, millis);
+    }
+
     fn update(&mut self, frame_time: f32) -> bool {
         self.current_duration += frame_time;
 
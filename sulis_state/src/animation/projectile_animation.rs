@@ -0,0 +1,191 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{animation::Anim, EntityState, ScriptCallback};
+use sulis_core::image::Image;
+use sulis_core::io::{DrawList, GraphicsRenderer};
+use sulis_core::ui::animation_state;
+use sulis_core::util::{ExtInt, Offset, Rect, Scale};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectileKind {
+    /// A sprite traveling in straight lines between the waypoints, rotated
+    /// to face its direction of travel
+    Straight,
+
+    /// A sprite lobbed in an arc reaching the specified height, in tiles,
+    /// at the midpoint of its flight
+    Arc { height: f32 },
+
+    /// A beam stretched from the starting point to the current position,
+    /// which remains visible for `hold_millis` after reaching the end
+    Beam { hold_millis: u32 },
+}
+
+/// A point along the path of a projectile, and the time in millis since the
+/// start of the animation at which the projectile reaches it
+#[derive(Debug, Clone, Copy)]
+struct PathPoint {
+    x: f32,
+    y: f32,
+    millis: u32,
+}
+
+pub(in crate::animation) fn update(model: &mut ProjectileAnimModel, millis: u32) {
+    let (x, y) = model.position(millis);
+    let (next_x, next_y) = model.position(millis + 1);
+    if next_x != x || next_y != y {
+        model.angle = (next_y - y).atan2(next_x - x);
+    }
+    model.cur_pos = (x, y);
+}
+
+pub(in crate::animation) fn draw(
+    model: &ProjectileAnimModel,
+    renderer: &mut dyn GraphicsRenderer,
+    offset: Offset,
+    scale: Scale,
+    millis: u32,
+) {
+    let image = &model.image;
+    let (w, h) = (image.get_width_f32(), image.get_height_f32());
+
+    let rect = match model.kind {
+        ProjectileKind::Beam { .. } => {
+            let start = &model.path[0];
+            let (dx, dy) = (model.cur_pos.0 - start.x, model.cur_pos.1 - start.y);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len == 0.0 {
+                return;
+            }
+
+            Rect {
+                x: start.x + (dx - len) / 2.0 + offset.x,
+                y: start.y + dy / 2.0 - h / 2.0 + offset.y,
+                w: len,
+                h,
+            }
+        }
+        _ => Rect {
+            x: model.cur_pos.0 - w / 2.0 + offset.x,
+            y: model.cur_pos.1 - h / 2.0 + offset.y,
+            w,
+            h,
+        },
+    };
+
+    let mut draw_list = DrawList::empty_sprite();
+    image.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+    draw_list.set_scale(scale);
+    draw_list.rotate(model.angle);
+    renderer.draw(draw_list);
+}
+
+/// Creates a new projectile animation starting at the center of the `owner`,
+/// and traveling through each of the `waypoints`, specified as `(x, y, speed)`
+/// with the speed in tiles per second.  `impact_callbacks` are fired when the
+/// projectile reaches the waypoint with the associated index.
+pub fn new(
+    owner: &Rc<RefCell<EntityState>>,
+    image: Rc<dyn Image>,
+    kind: ProjectileKind,
+    waypoints: &[(f32, f32, f32)],
+    impact_callbacks: Vec<(usize, Box<dyn ScriptCallback>)>,
+    blocking: bool,
+) -> Anim {
+    let start = {
+        let owner = owner.borrow();
+        PathPoint {
+            x: owner.location.x as f32 + owner.size.width as f32 / 2.0,
+            y: owner.location.y as f32 + owner.size.height as f32 / 2.0,
+            millis: 0,
+        }
+    };
+
+    let mut path = vec![start];
+    for &(x, y, speed) in waypoints {
+        let prev = path[path.len() - 1];
+        let (dx, dy) = (x - prev.x, y - prev.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let millis = (dist / speed.max(0.01) * 1000.0) as u32;
+        path.push(PathPoint {
+            x,
+            y,
+            millis: prev.millis + millis,
+        });
+    }
+
+    let travel_millis = path[path.len() - 1].millis;
+    let duration = match kind {
+        ProjectileKind::Beam { hold_millis } => travel_millis + hold_millis,
+        _ => travel_millis,
+    };
+
+    let model = ProjectileAnimModel {
+        kind,
+        image,
+        cur_pos: (start.x, start.y),
+        angle: 0.0,
+        path: path.clone(),
+        blocking,
+    };
+
+    let mut anim = Anim::new_projectile(owner, ExtInt::Int(duration), model);
+    for (index, cb) in impact_callbacks {
+        // waypoint indices start after the starting point in the path
+        let millis = match path.get(index + 1) {
+            None => travel_millis,
+            Some(point) => point.millis,
+        };
+        anim.add_update_callback(cb, millis);
+    }
+    anim
+}
+
+pub(in crate::animation) struct ProjectileAnimModel {
+    kind: ProjectileKind,
+    image: Rc<dyn Image>,
+    path: Vec<PathPoint>,
+    cur_pos: (f32, f32),
+    angle: f32,
+    pub(in crate::animation) blocking: bool,
+}
+
+impl ProjectileAnimModel {
+    fn position(&self, millis: u32) -> (f32, f32) {
+        let last = self.path[self.path.len() - 1];
+        if millis >= last.millis {
+            return (last.x, last.y);
+        }
+
+        let end = self.path.iter().position(|p| p.millis > millis).unwrap();
+        let (from, to) = (self.path[end - 1], self.path[end]);
+        let frac = (millis - from.millis) as f32 / (to.millis - from.millis) as f32;
+        let x = from.x + frac * (to.x - from.x);
+        let y = from.y + frac * (to.y - from.y);
+
+        match self.kind {
+            ProjectileKind::Arc { height } => {
+                let total = millis as f32 / last.millis as f32;
+                (x, y - 4.0 * height * total * (1.0 - total))
+            }
+            _ => (x, y),
+        }
+    }
+}
@@ -17,19 +17,30 @@
 use std::rc::Rc;
 use std::time::Instant;
 
-use sulis_core::config::Config;
+use sulis_core::config::{Config, FeedbackChannel};
 use sulis_core::image::Image;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::resource::{Font, ResourceSet};
+use sulis_core::tr;
 use sulis_core::ui::{animation_state, Color, LineRenderer};
 use sulis_core::util::{self, Offset, Point, Rect, Scale};
 use sulis_module::{DamageKind, HitFlags, HitKind};
 
 use crate::{AreaState, EntityState};
 
+/// Per channel style overrides.  When a font or color is not specified, the
+/// base font and the entry's normal color are used.
+#[derive(Default, Clone)]
+pub struct ChannelParams {
+    pub font: Option<Rc<Font>>,
+    pub color: Option<Color>,
+}
+
 pub struct Params {
     pub font: Rc<Font>,
     pub scale: f32,
+    pub crit_scale: f32,
+    pub channels: [ChannelParams; 5],
     pub ap_scale: f32,
     pub ap_color: Color,
     pub info_color: Color,
@@ -52,6 +63,8 @@ impl Default for Params {
         Params {
             font: ResourceSet::default_font(),
             scale: 1.0,
+            crit_scale: 1.5,
+            channels: Default::default(),
             ap_scale: 1.0,
             ap_color: LIGHT_GRAY,
             info_color: LIGHT_GRAY,
@@ -86,6 +99,9 @@ struct Entry {
     color_kind: ColorKind,
 }
 
+// texts closer than this horizontally are stacked vertically
+const STACK_WIDTH: f32 = 3.0;
+
 #[derive(Copy, Clone)]
 pub enum IconKind {
     Concealment,
@@ -110,6 +126,8 @@ pub struct AreaFeedbackText {
 
     total_text: String,
     entries: Vec<Entry>,
+    channel: FeedbackChannel,
+    is_crit: bool,
 }
 
 impl AreaFeedbackText {
@@ -121,8 +139,14 @@ impl AreaFeedbackText {
         damage: &[(DamageKind, u32)],
     ) -> AreaFeedbackText {
         let mut text = AreaFeedbackText::with_target(target, area);
+        text.channel = if hit_kind == HitKind::Miss {
+            FeedbackChannel::Miss
+        } else {
+            FeedbackChannel::Damage
+        };
+        text.is_crit = hit_kind == HitKind::Crit;
 
-        if hit_flags.sneak_attack {
+        if hit_flags.sneak_attack || hit_flags.backstab {
             text.add_icon_entry(IconKind::Backstab, ColorKind::Info);
         } else if hit_flags.flanking {
             text.add_icon_entry(IconKind::Flanking, ColorKind::Info);
@@ -148,7 +172,7 @@ impl AreaFeedbackText {
             HitKind::Graze => text.add_icon_entry(IconKind::Graze, ColorKind::Info),
             HitKind::Hit => text.add_icon_entry(IconKind::Hit, ColorKind::Info),
             HitKind::Crit => text.add_icon_entry(IconKind::Crit, ColorKind::Info),
-            HitKind::Miss => text.add_entry("Miss".to_string(), ColorKind::Miss),
+            HitKind::Miss => text.add_entry(tr!("feedback.miss"), ColorKind::Miss),
             HitKind::Auto => (),
         }
 
@@ -158,11 +182,19 @@ impl AreaFeedbackText {
     pub fn with_target(target: &EntityState, area: &AreaState) -> AreaFeedbackText {
         let move_rate = 3.0;
         let mut area_pos = target.location.to_point();
+        let width = target.size.width as f32;
+        let pos_x = area_pos.x as f32 + width / 2.0;
+
+        // stack on top of any nearby text so that simultaneous texts don't overlap
         loop {
             let mut valid = true;
 
             let pos_y = area_pos.y as f32;
             for text in area.feedback_text_iter() {
+                if (pos_x - text.pos_x).abs() > STACK_WIDTH {
+                    continue;
+                }
+
                 let text_pos_y = text.area_pos().y as f32 - text.cur_hover_y();
                 if (pos_y - text_pos_y).abs() < 0.7 {
                     area_pos.y -= 1;
@@ -176,8 +208,6 @@ impl AreaFeedbackText {
             }
         }
 
-        let width = target.size.width as f32;
-        let pos_x = area_pos.x as f32 + width / 2.0;
         let pos_y = area_pos.y as f32 - 1.5;
 
         AreaFeedbackText::new(area_pos, pos_x, pos_y, move_rate)
@@ -196,9 +226,19 @@ impl AreaFeedbackText {
             hover_y: 0.0,
             alpha: 1.0,
             entries: Vec::new(),
+            channel: FeedbackChannel::Status,
+            is_crit: false,
         }
     }
 
+    pub fn channel(&self) -> FeedbackChannel {
+        self.channel
+    }
+
+    pub fn set_channel(&mut self, channel: FeedbackChannel) {
+        self.channel = channel;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
@@ -256,35 +296,44 @@ impl AreaFeedbackText {
         scale: Scale,
         millis: u32,
     ) {
+        let channel = &params.channels[self.channel.index()];
+        let font = channel.font.as_ref().unwrap_or(&params.font);
+        let text_scale = if self.is_crit {
+            params.scale * params.crit_scale
+        } else {
+            params.scale
+        };
+
         // creating the line renderer here is not ideal but is a low cost operation
-        let font_renderer = LineRenderer::new(&params.font);
+        let font_renderer = LineRenderer::new(font);
         if self.text_width == 0.0 {
-            self.text_width =
-                params.font.get_width(&self.total_text) as f32 / params.font.line_height as f32;
+            self.text_width = font.get_width(&self.total_text) as f32 / font.line_height as f32;
         }
 
-        let mut pos_x = offset.x + self.pos_x - params.scale * self.text_width / 2.0;
+        let mut pos_x = offset.x + self.pos_x - text_scale * self.text_width / 2.0;
         if pos_x < 0.0 {
             pos_x = 0.0;
         }
         let pos_y = offset.y + self.pos_y - self.hover_y;
 
         for entry in &self.entries {
-            let mut color = match entry.color_kind {
-                ColorKind::Info => params.info_color,
-                ColorKind::Miss => params.miss_color,
-                ColorKind::Hit => params.hit_color,
-                ColorKind::Heal => params.heal_color,
-                ColorKind::Damage { kind } => {
+            // damage type colors always take precedence over the channel color
+            let mut color = match (entry.color_kind, channel.color) {
+                (ColorKind::Damage { kind }, _) => {
                     let index = kind.index();
                     params.damage_colors[index]
                 }
+                (_, Some(color)) => color,
+                (ColorKind::Info, None) => params.info_color,
+                (ColorKind::Miss, None) => params.miss_color,
+                (ColorKind::Hit, None) => params.hit_color,
+                (ColorKind::Heal, None) => params.heal_color,
             };
             color.a *= self.alpha;
 
             if let Some(icon) = entry.icon {
-                let w = params.scale / 1.5;
-                let h = params.scale / 1.5;
+                let w = text_scale / 1.5;
+                let h = text_scale / 1.5;
 
                 let state = &animation_state::NORMAL;
                 let image = match icon {
@@ -298,7 +347,7 @@ impl AreaFeedbackText {
 
                 let rect = Rect {
                     x: pos_x,
-                    y: pos_y + params.scale * 0.15,
+                    y: pos_y + text_scale * 0.15,
                     w,
                     h,
                 };
@@ -308,12 +357,12 @@ impl AreaFeedbackText {
                 draw_list.set_color(color);
                 renderer.draw(draw_list);
 
-                pos_x += 1.5 * params.scale / params.font.line_height as f32
-                    * params.font.get_char_width('w') as f32;
+                pos_x += 1.5 * text_scale / font.line_height as f32
+                    * font.get_char_width('w') as f32;
             } else {
                 let offset = Offset { x: pos_x, y: pos_y };
                 let (mut draw_list, next_x) =
-                    font_renderer.get_draw_list(&entry.text, offset, params.scale);
+                    font_renderer.get_draw_list(&entry.text, offset, text_scale);
                 draw_list.set_scale(scale);
                 draw_list.set_color(color);
                 renderer.draw(draw_list);
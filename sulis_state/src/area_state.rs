@@ -14,23 +14,33 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+mod light_map;
+pub use light_map::{LightMap, PointLight};
+
 mod prop_handler;
 use prop_handler::PropHandler;
 
 use std::cell::RefCell;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::io::Error;
 use std::rc::Rc;
 use std::time;
 
+use crate::area_feedback_text::ColorKind;
 use crate::save_state::AreaSaveState;
 use crate::script::AreaTargeter;
 use crate::*;
 use sulis_core::io::Audio;
 use sulis_core::config::Config;
-use sulis_core::util::{self, gen_rand, invalid_data_error, Point, Size};
+use sulis_core::tr;
+use sulis_core::util::{self, gen_rand, invalid_data_error, Point, ReproducibleRandom, Size};
 use sulis_module::area::{Transition, TriggerKind, Trigger};
-use sulis_module::{Actor, Area, LootList, Module, ObjectSize, Time};
+use sulis_module::{
+    Actor, Area, Attribute, Cover, LootList, Module, ObjectSize, OnTrigger, Time,
+};
 
 pub struct TriggerState {
     pub(crate) fired: bool,
@@ -75,10 +85,19 @@ pub struct AreaState {
     pc_vis: Vec<bool>,
 
     feedback_text: Vec<AreaFeedbackText>,
-    scroll_to_callback: Option<Rc<RefCell<EntityState>>>,
+    camera: Camera,
 
     targeter: Option<Rc<RefCell<AreaTargeter>>>,
     range_indicators: RangeIndicatorHandler,
+
+    // hash of the area save written by the last save, cleared whenever
+    // the saved portion of this area may have changed
+    save_hash: Option<String>,
+
+    // the total hours elapsed when time of day triggers were last checked
+    time_of_day_hours: Option<u32>,
+
+    light_map: Option<LightMap>,
 }
 
 impl PartialEq for AreaState {
@@ -87,19 +106,90 @@ impl PartialEq for AreaState {
     }
 }
 
-fn gen_area(area: Rc<Area>, seed: Option<u128>) -> Result<(GeneratedArea, u128), Error> {
-    let pregen_output = PregenOutput::new(&area, seed)?;
-    let seed = match &pregen_output {
-        None => 0,
-        Some(out) => out.seed(),
-    };
+/// How the seed used to generate an area is chosen
+#[derive(Debug, Clone, Copy)]
+pub enum AreaSeed {
+    /// A new random seed for each attempt
+    Random,
+
+    /// Exactly the specified seed, which is used even if the area fails validation
+    Exact(u128),
+
+    /// A seed derived from the specified base seed and the attempt number,
+    /// so that every attempt is reproducible
+    Derived(u64),
+}
+
+impl AreaSeed {
+    fn attempt_seed(&self, attempt: u32) -> Option<u128> {
+        match self {
+            AreaSeed::Random => None,
+            AreaSeed::Exact(seed) => Some(*seed),
+            AreaSeed::Derived(base) => {
+                Some(util::mix_seed(base.wrapping_add(attempt as u64)) as u128)
+            }
+        }
+    }
+}
+
+impl From<Option<u128>> for AreaSeed {
+    fn from(seed: Option<u128>) -> AreaSeed {
+        match seed {
+            None => AreaSeed::Random,
+            Some(seed) => AreaSeed::Exact(seed),
+        }
+    }
+}
+
+/// Generates the area, validating the result.  Unless an exact `seed` is
+/// specified, an area failing generation or validation is generated again
+/// from the next seed, up to the generator's maximum attempts.  An exact
+/// seed always produces the same area, so it is used as is.  Returns the
+/// area along with the seed it was generated from
+fn gen_area(area: Rc<Area>, seed: AreaSeed) -> Result<(GeneratedArea, u128), Error> {
+    let mut attempt = 1;
+    loop {
+        let pregen_output = PregenOutput::new(&area, seed.attempt_seed(attempt))?;
+        let (gen_seed, generator) = match &pregen_output {
+            None => return Ok((GeneratedArea::new(area, None)?, 0)),
+            Some(out) => (out.seed(), Rc::clone(out.generator())),
+        };
+
+        let validation = generator.validation();
+        let is_exact = matches!(seed, AreaSeed::Exact(_));
+        let last_attempt = is_exact || attempt >= validation.max_attempts;
+
+        let gened = match GeneratedArea::new(Rc::clone(&area), pregen_output) {
+            Err(e) if !last_attempt => {
+                warn!("Generating '{}' with seed {} failed, retrying", area.id, gen_seed);
+                warn!("{}", e);
+                attempt += 1;
+                continue;
+            }
+            result => result?,
+        };
+
+        let problems = gened.validate(generator.min_passable_size(), &validation);
+        if problems.is_empty() {
+            return Ok((gened, gen_seed));
+        }
+
+        warn!("Generated '{}' with seed {} failed validation:", area.id, gen_seed);
+        for problem in problems.iter() {
+            warn!("  {}", problem);
+        }
+
+        if last_attempt {
+            warn!("Using '{}' after {} attempts", area.id, attempt);
+            return Ok((gened, gen_seed));
+        }
 
-    let area = GeneratedArea::new(area, pregen_output)?;
-    Ok((area, seed))
+        attempt += 1;
+    }
 }
 
 impl AreaState {
-    pub fn new(area: Rc<Area>, seed: Option<u128>) -> Result<AreaState, Error> {
+    pub fn new(area: Rc<Area>, seed: AreaSeed) -> Result<AreaState, Error> {
         let (gened, area_gen_seed) = gen_area(Rc::clone(&area), seed)?;
 
         let dim = (gened.area.width * gened.area.height) as usize;
@@ -128,11 +218,14 @@ impl AreaState {
             pc_explored,
             pc_vis_redraw: PCVisRedraw::Not,
             feedback_text: Vec::new(),
-            scroll_to_callback: None,
+            camera: Camera::default(),
             targeter: None,
             range_indicators: RangeIndicatorHandler::default(),
             merchants: Vec::new(),
             on_load_fired: false,
+            save_hash: None,
+            time_of_day_hours: None,
+            light_map: None,
         })
     }
 
@@ -142,7 +235,7 @@ impl AreaState {
             Some(area) => Ok(area),
         }?;
 
-        let mut area_state = AreaState::new(area, Some(save.seed))?;
+        let mut area_state = AreaState::new(area, AreaSeed::Exact(save.seed))?;
 
         area_state.on_load_fired = save.on_load_fired;
 
@@ -189,9 +282,24 @@ impl AreaState {
     }
 
     pub fn props_mut(&mut self) -> &mut PropHandler {
+        self.mark_save_dirty();
         &mut self.props
     }
 
+    /// Returns the hash of this area's save data as of the last save, or
+    /// `None` if it has been modified since then and must be written again
+    pub(crate) fn save_hash(&self) -> Option<&str> {
+        self.save_hash.as_deref()
+    }
+
+    pub(crate) fn set_save_hash(&mut self, hash: String) {
+        self.save_hash = Some(hash);
+    }
+
+    pub(crate) fn mark_save_dirty(&mut self) {
+        self.save_hash = None;
+    }
+
     fn pc_vis_partial_redraw(&mut self, x: i32, y: i32) {
         if let PCVisRedraw::Not = self.pc_vis_redraw {
             self.pc_vis_redraw = PCVisRedraw::Partial {
@@ -224,6 +332,7 @@ impl AreaState {
     }
 
     pub fn get_merchant_mut(&mut self, id: &str) -> Option<&mut MerchantState> {
+        self.mark_save_dirty();
         let mut index = None;
         for (i, merchant) in self.merchants.iter().enumerate() {
             if merchant.id == id {
@@ -261,7 +370,12 @@ impl AreaState {
             let location = Location::from_point(actor_data.location, &area);
             debug!("Adding actor '{}' at '{:?}'", actor.id, location);
             match self.add_actor(actor, location, Some(unique_id), false, None) {
-                Ok(_) => (),
+                Ok(index) => {
+                    if let Some(patrol) = actor_data.patrol.as_ref() {
+                        let entity = GameState::turn_manager().borrow().entity(index);
+                        entity.borrow_mut().set_patrol(PatrolState::new(patrol));
+                    }
+                }
                 Err(e) => {
                     warn!("Error adding actor to area: {}", e);
                 }
@@ -301,6 +415,7 @@ impl AreaState {
         sell_frac: f32,
         refresh_time: Time,
     ) -> &mut MerchantState {
+        self.mark_save_dirty();
         let mut index = None;
         for (i, merchant) in self.merchants.iter().enumerate() {
             if merchant.id == id {
@@ -367,12 +482,8 @@ impl AreaState {
         self.targeter = Some(Rc::new(RefCell::new(targeter)));
     }
 
-    pub fn push_scroll_to_callback(&mut self, entity: Rc<RefCell<EntityState>>) {
-        self.scroll_to_callback = Some(entity);
-    }
-
-    pub fn pop_scroll_to_callback(&mut self) -> Option<Rc<RefCell<EntityState>>> {
-        self.scroll_to_callback.take()
+    pub fn camera(&mut self) -> &mut Camera {
+        &mut self.camera
     }
 
     fn add_transitions_from_area(&mut self) {
@@ -439,6 +550,14 @@ impl AreaState {
         info!("OnEncounterCleared for {}", index);
 
         let player = GameState::player();
+
+        let xp = self.area.encounters[index].encounter.xp;
+        if xp > 0 {
+            GameState::add_ui_callback(vec![OnTrigger::AwardXp(xp)], &player, target);
+        }
+
+        self.drop_encounter_loot(index, target);
+
         for trigger_index in self.area.encounters[index].triggers.iter() {
             let trigger = &self.area.area.triggers[*trigger_index];
             self.triggers[*trigger_index].fired = true;
@@ -450,6 +569,129 @@ impl AreaState {
         }
     }
 
+    /// Rolls the loot for the encounter at `index`, and places it in a remains
+    /// prop at the location of `target`.  The roll is seeded from the campaign
+    /// seed, the area ID, and the area's generation seed, so it is the same each
+    /// time for a given area in a campaign, but differs between campaigns
+    fn drop_encounter_loot(&mut self, index: usize, target: &Rc<RefCell<EntityState>>) {
+        let encounter = Rc::clone(&self.area.encounters[index].encounter);
+        let loot = match encounter.loot {
+            None => return,
+            Some(ref loot) => loot,
+        };
+
+        let mut hasher = DefaultHasher::new();
+        GameState::campaign_seed().hash(&mut hasher);
+        self.area.area.id.hash(&mut hasher);
+        let seed = self.area_gen_seed ^ hasher.finish() as u128 ^ ((index as u128 + 1) << 64);
+        let mut rand = ReproducibleRandom::new(Some(seed));
+        let items = loot.generate(&mut rand);
+        if items.is_empty() {
+            return;
+        }
+
+        let p = target.borrow().location.to_point();
+        if let Some(prop_index) = self.props_mut().check_or_create_container(p.x, p.y) {
+            self.props_mut().get_mut(prop_index).add_items(items);
+        }
+    }
+
+    pub fn fire_on_encounter_fled(&mut self, index: usize, target: &Rc<RefCell<EntityState>>) {
+        info!("OnEncounterFled for {}", index);
+
+        let player = GameState::player();
+        for trigger_index in self.area.encounters[index].triggers.iter() {
+            let trigger = &self.area.area.triggers[*trigger_index];
+
+            if *trigger_index >= self.triggers.len() {
+                continue;
+            }
+
+            if let TriggerKind::OnEncounterFled { .. } = trigger.kind {
+                if !self.triggers[*trigger_index].can_fire(trigger) {
+                    continue;
+                }
+                self.triggers[*trigger_index].fired = true;
+
+                info!("    Calling OnEncounterFled");
+                GameState::add_ui_callback(trigger.on_activate.clone(), &player, target);
+            }
+        }
+    }
+
+    /// Starts tracking the time of day afresh, so that only hours beginning
+    /// after this point will fire `OnTimeOfDay` triggers
+    pub(crate) fn reset_time_of_day(&mut self) {
+        self.time_of_day_hours = None;
+    }
+
+    /// Fires the `OnTimeOfDay` triggers for each hour that has begun since the
+    /// previous check, up to and including the current hour, given by the
+    /// `total_hours` elapsed in the game.  Each trigger fires at most once per
+    /// check, even when a day or more has passed, such as during a long rest
+    pub(crate) fn check_time_of_day_triggers(&mut self, total_hours: u32) {
+        let prev = match self.time_of_day_hours.replace(total_hours) {
+            None => return,
+            Some(prev) => prev,
+        };
+
+        if total_hours <= prev {
+            return;
+        }
+
+        let hours_per_day = Module::rules().hours_per_day;
+        let elapsed = total_hours - prev;
+        let prev = prev % hours_per_day;
+
+        let player = GameState::player();
+        for (index, trigger) in self.area.area.triggers.iter().enumerate() {
+            let trigger_hour = match trigger.kind {
+                TriggerKind::OnTimeOfDay { hour } => hour,
+                _ => continue,
+            };
+
+            if trigger_hour >= hours_per_day || index >= self.triggers.len() {
+                continue;
+            }
+
+            let since_prev = (trigger_hour + hours_per_day - prev) % hours_per_day;
+            let since_prev = if since_prev == 0 {
+                hours_per_day
+            } else {
+                since_prev
+            };
+            if since_prev > elapsed {
+                continue;
+            }
+
+            if !self.triggers[index].can_fire(trigger) {
+                continue;
+            }
+            self.triggers[index].fired = true;
+
+            info!("Calling OnTimeOfDay for hour {}", trigger_hour);
+            GameState::add_ui_callback(trigger.on_activate.clone(), &player, &player);
+        }
+    }
+
+    /// Spawns the automatically spawning encounters again, as happens
+    /// when the area is first loaded.  Encounters with surviving actors,
+    /// such as those the party fled from, are left as they are
+    pub(crate) fn respawn_encounters(&mut self) {
+        let mgr = GameState::turn_manager();
+        for index in 0..self.area.encounters.len() {
+            if !self.area.encounters[index].encounter.auto_spawn {
+                continue;
+            }
+
+            if mgr.borrow().encounter_has_survivors(&self.area.area.id, index) {
+                continue;
+            }
+
+            self.spawn_encounter(index, true);
+        }
+    }
+
     pub fn spawn_encounter_at(&mut self, x: i32, y: i32) -> bool {
         let mut enc_index = None;
         for (index, data) in self.area.encounters.iter().enumerate() {
@@ -648,34 +890,115 @@ impl AreaState {
         if !self.props.toggle_active(index) {
             return;
         }
+        self.mark_save_dirty();
+
+        // only party members who can potentially see the prop are affected
+        let prop_bounds = {
+            let prop = self.props.get(index);
+            VisBounds {
+                center_x: prop.location.x,
+                center_y: prop.location.y,
+                min_x: prop.location.x,
+                min_y: prop.location.y,
+                max_x: prop.location.x + prop.prop.size.width,
+                max_y: prop.location.y + prop.prop.size.height,
+            }
+        };
 
         self.pc_vis_partial_redraw(0, 0);
+        let mut dirty: Option<VisBounds> = None;
         for member in GameState::party().iter() {
-            self.compute_pc_visibility(member, 0, 0);
+            let affected = match member.borrow().pc_vis_bounds() {
+                None => true,
+                Some(bounds) => bounds.intersects(&prop_bounds),
+            };
+
+            if !affected {
+                continue;
+            }
+
+            if let Some(bounds) = self.recompute_pc_visibility(member) {
+                dirty = Some(dirty.map_or(bounds, |d| d.union(&bounds)));
+            }
+        }
+
+        if let Some(dirty) = dirty {
+            self.update_view_visibility_in(dirty);
         }
-        self.update_view_visibility();
     }
 
     pub fn has_visibility(&self, parent: &EntityState, target: &EntityState) -> bool {
         has_visibility(&self.area, self.props.entire_vis_grid(), parent, target)
     }
 
+    /// The elevation at the specified coordinates.
+    /// No bounds checking is done
+    pub fn elevation(&self, x: i32, y: i32) -> u8 {
+        self.area.layer_set.elevation(x, y)
+    }
+
+    /// The elevation at the center of the specified entity
+    pub fn entity_elevation(&self, entity: &EntityState) -> u8 {
+        let x = entity.location.x + entity.size.width / 2;
+        let y = entity.location.y + entity.size.height / 2;
+        self.elevation(x, y)
+    }
+
+    /// Whether the parent is standing at a higher elevation than the target
+    pub fn has_high_ground(&self, parent: &EntityState, target: &EntityState) -> bool {
+        self.entity_elevation(parent) > self.entity_elevation(target)
+    }
+
+    /// The cover the target has against ranged attacks from the parent
+    pub fn cover(&self, parent: &EntityState, target: &EntityState) -> Cover {
+        calculate_cover(
+            &self.area,
+            self.props.entire_vis_grid(),
+            self.props.entire_pass_grid(),
+            parent,
+            target,
+        )
+    }
+
+    /// Computes the line of sight for the specified party member.  If the
+    /// member has not moved since its last computation, nothing is done.
+    /// Returns the region of the area affected by the computation, if any.
+    /// The view visibility should be updated for the returned region.
     pub fn compute_pc_visibility(
         &mut self,
         entity: &Rc<RefCell<EntityState>>,
-        delta_x: i32,
-        delta_y: i32,
-    ) {
+    ) -> Option<VisBounds> {
+        {
+            let entity = entity.borrow();
+            if let Some(bounds) = entity.pc_vis_bounds() {
+                let x = entity.location.x + entity.size.width / 2;
+                let y = entity.location.y + entity.size.height / 2;
+                if bounds.center_x == x && bounds.center_y == y {
+                    return None;
+                }
+            }
+        }
+
+        self.recompute_pc_visibility(entity)
+    }
+
+    /// Computes the line of sight for the specified party member, regardless
+    /// of whether it has moved.  Returns the region of the area affected.
+    pub fn recompute_pc_visibility(
+        &mut self,
+        entity: &Rc<RefCell<EntityState>>,
+    ) -> Option<VisBounds> {
         let start_time = time::Instant::now();
 
+        let old_bounds = entity.borrow().pc_vis_bounds();
+
         let props_vis = calculate_los(
             &mut self.pc_explored,
             &self.area,
             self.props.entire_vis_grid(),
             self.props.grid(),
+            self.light_map.as_ref().map(|map| map.lit_grid()),
             &mut entity.borrow_mut(),
-            delta_x,
-            delta_y,
         );
 
         // set explored to true for any partially visible props
@@ -691,16 +1014,28 @@ impl AreaState {
             "Visibility compute time: {}",
             util::format_elapsed_secs(start_time.elapsed())
         );
+
+        let new_bounds = entity.borrow().pc_vis_bounds()?;
+        Some(match old_bounds {
+            None => new_bounds,
+            Some(old) => new_bounds.union(&old),
+        })
     }
 
+    /// Recomputes the combined party visibility over the entire area
     pub fn update_view_visibility(&mut self) {
         unsafe { std::ptr::write_bytes(self.pc_vis.as_mut_ptr(), 0, self.pc_vis.len()) }
 
         for entity in GameState::party().iter() {
             let entity = entity.borrow();
+            // squares outside the bounds are never visible to this member
+            let bounds = match entity.pc_vis_bounds() {
+                None => continue,
+                Some(bounds) => bounds,
+            };
             let new_vis = entity.pc_vis();
-            for y in 0..self.area.height {
-                for x in 0..self.area.width {
+            for y in bounds.min_y..bounds.max_y {
+                for x in bounds.min_x..bounds.max_x {
                     let index = (x + y * self.area.width) as usize;
                     self.pc_vis[index] = self.pc_vis[index] || new_vis[index]
                 }
@@ -708,6 +1043,45 @@ impl AreaState {
         }
     }
 
+    /// Recomputes the combined party visibility, only within the specified
+    /// `region`.  Party members whose vis bounds do not overlap the region
+    /// are skipped.
+    pub fn update_view_visibility_in(&mut self, region: VisBounds) {
+        let width = self.area.width;
+        for y in region.min_y..region.max_y {
+            let start = (region.min_x + y * width) as usize;
+            let end = (region.max_x + y * width) as usize;
+            for vis in self.pc_vis[start..end].iter_mut() {
+                *vis = false;
+            }
+        }
+
+        for entity in GameState::party().iter() {
+            let entity = entity.borrow();
+            let bounds = match entity.pc_vis_bounds() {
+                None => continue,
+                Some(bounds) => bounds,
+            };
+
+            if !bounds.intersects(&region) {
+                continue;
+            }
+
+            let min_x = cmp::max(bounds.min_x, region.min_x);
+            let max_x = cmp::min(bounds.max_x, region.max_x);
+            let min_y = cmp::max(bounds.min_y, region.min_y);
+            let max_y = cmp::min(bounds.max_y, region.max_y);
+
+            let new_vis = entity.pc_vis();
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let index = (x + y * width) as usize;
+                    self.pc_vis[index] = self.pc_vis[index] || new_vis[index]
+                }
+            }
+        }
+    }
+
     pub fn set_trigger_enabled_at(&mut self, x: i32, y: i32, enabled: bool) -> bool {
         if !self.area.area.coords_valid(x, y) {
             warn!("Invalid coords to enable trigger at {},{}", x, y);
@@ -720,6 +1094,7 @@ impl AreaState {
         };
 
         self.triggers[index].enabled = enabled;
+        self.mark_save_dirty();
         true
     }
 
@@ -745,16 +1120,98 @@ impl AreaState {
         );
     }
 
+    /// Springs any armed traps under the `entity`
+    fn check_traps(&mut self, entity: &Rc<RefCell<EntityState>>) {
+        let mut indices = Vec::new();
+        for p in entity.borrow().location_points() {
+            for index in &self.props.grid()[(p.x + p.y * self.area.width) as usize] {
+                if !indices.contains(index) {
+                    indices.push(*index);
+                }
+            }
+        }
+
+        for index in indices {
+            let on_activate = match self.props.get_mut(index).spring_trap() {
+                None => continue,
+                Some(on_activate) => on_activate,
+            };
+
+            let mut text = AreaFeedbackText::with_target(&entity.borrow(), self);
+            text.add_entry(tr!("feedback.trap"), ColorKind::Miss);
+            self.add_feedback_text(text);
+            self.mark_save_dirty();
+            GameState::add_ui_callback(on_activate, entity, entity);
+        }
+    }
+
+    /// Gives the `entity` a single detection check against each hidden prop
+    /// within the detection distance, revealing those it passes
+    fn check_hidden_props(&mut self, entity: &Rc<RefCell<EntityState>>) {
+        let rules = Module::rules();
+        let detection = match rules.detection.as_ref() {
+            None => return,
+            Some(detection) => detection,
+        };
+
+        let mut found = Vec::new();
+        {
+            let entity = entity.borrow();
+            let perception = entity.actor.stats.attributes.get(Attribute::Perception);
+            let levels = match Module::class(&detection.search_class) {
+                None => 0,
+                Some(class) => entity.actor.actor.levels(&class),
+            };
+
+            for index in 0..self.props.len() {
+                if !self.props.index_valid(index) {
+                    continue;
+                }
+
+                let prop = self.props.get_mut(index);
+                let difficulty = match prop.detect_difficulty() {
+                    None => continue,
+                    Some(difficulty) => difficulty,
+                };
+
+                if !is_within(&*entity, &*prop, detection.distance) {
+                    continue;
+                }
+
+                if !prop.add_search(entity.unique_id()) {
+                    continue;
+                }
+
+                if rules.detect_roll(difficulty, perception, levels) {
+                    prop.reveal();
+                    found.push(prop.name().to_string());
+                }
+            }
+        }
+
+        if found.is_empty() {
+            return;
+        }
+
+        let mut text = AreaFeedbackText::with_target(&entity.borrow(), self);
+        for name in found {
+            text.add_entry(tr!("feedback.detected", name = name), ColorKind::Info);
+        }
+        self.add_feedback_text(text);
+        self.mark_save_dirty();
+    }
+
     /// whether the pc has current visibility to the specified coordinations
     /// No bounds checking is done on the `x` and `y` arguments
     pub fn is_pc_visible(&self, x: i32, y: i32) -> bool {
         self.pc_vis[(x + y * self.area.width) as usize]
     }
 
-    /// whether the pc has current explored vis to the specified coordinates
+    /// whether the pc has current explored vis to the specified coordinates.
+    /// Always true if fog of war is disabled in the config.
     /// No bounds checking is done
     pub fn is_pc_explored(&self, x: i32, y: i32) -> bool {
-        self.pc_explored[(x + y * self.area.width) as usize]
+        !Config::fog_of_war() || self.pc_explored[(x + y * self.area.width) as usize]
     }
 
     fn point_size_passable(&self, x: i32, y: i32) -> bool {
@@ -800,6 +1257,11 @@ impl AreaState {
         is_pc: bool,
         ai_group: Option<usize>,
     ) -> Result<usize, Error> {
+        let disposition = match actor.faction_id {
+            Some(_) if !is_pc => Some(GameState::faction_disposition(&actor)),
+            _ => None,
+        };
+
         let entity = Rc::new(RefCell::new(EntityState::new(
             actor,
             unique_id,
@@ -807,6 +1269,10 @@ impl AreaState {
             is_pc,
             ai_group,
         )));
+
+        if let Some(disposition) = disposition {
+            entity.borrow_mut().actor.set_faction(disposition);
+        }
         match self.add_entity(&entity, location) {
             Ok(index) => Ok(index),
             Err(e) => {
@@ -969,7 +1435,7 @@ impl AreaState {
         }
 
         if entity.borrow().is_party_member() {
-            self.compute_pc_visibility(entity, 0, 0);
+            self.compute_pc_visibility(entity);
         }
 
         Ok(index)
@@ -981,10 +1447,22 @@ impl AreaState {
         x: i32,
         y: i32,
         squares: u32,
+        diagonals: u32,
     ) -> bool {
         let old_x = entity.borrow().location.x;
         let old_y = entity.borrow().location.y;
-        if !entity.borrow_mut().move_to(x, y, squares) {
+
+        let squares = if squares > 0 || diagonals > 0 {
+            let (w, h) = (entity.borrow().size.width, entity.borrow().size.height);
+            let old_elev = self.elevation(old_x + w / 2, old_y + h / 2);
+            let new_elev = self.elevation(x + w / 2, y + h / 2);
+            let climbed = new_elev.saturating_sub(old_elev) as u32;
+            squares + climbed * Module::rules().climb_move_squares
+        } else {
+            squares
+        };
+
+        if !entity.borrow_mut().move_to(x, y, squares, diagonals) {
             return false;
         }
 
@@ -1055,11 +1533,14 @@ impl AreaState {
         let is_pc = entity.borrow().is_party_member();
 
         if is_pc {
-            self.pc_vis_partial_redraw(d_x, d_y);
-            self.compute_pc_visibility(entity, d_x, d_y);
-            self.update_view_visibility();
+            if let Some(dirty) = self.compute_pc_visibility(entity) {
+                self.pc_vis_partial_redraw(d_x, d_y);
+                self.update_view_visibility_in(dirty);
+            }
 
             self.check_trigger_grid(entity);
+            self.check_traps(entity);
+            self.check_hidden_props(entity);
         }
 
         mgr.fire_on_moved_next_update(entity_index);
@@ -1101,7 +1582,10 @@ impl AreaState {
     }
 
     pub(crate) fn update(&mut self) {
+        // the current area is treated as modified for as long as it is active
+        self.mark_save_dirty();
         self.props.update();
+        self.update_lighting();
 
         self.feedback_text.iter_mut().for_each(|f| f.update());
         self.feedback_text.retain(|f| f.retain());
@@ -1117,6 +1601,82 @@ impl AreaState {
         }
     }
 
+    /// The light map for this area, if lighting is currently in use
+    pub fn light_map(&mut self) -> Option<&mut LightMap> {
+        self.light_map.as_mut()
+    }
+
+    /// Recomputes the light map if any lights have changed, updating the
+    /// party's visibility to match.  Lighting is only used in areas with an
+    /// ambient light level, and may be disabled in the config.
+    fn update_lighting(&mut self) {
+        let ambient = match self.area.area.ambient_light {
+            Some(ambient) if Config::lighting() => ambient,
+            _ => {
+                if self.light_map.take().is_some() {
+                    self.recompute_party_visibility();
+                }
+                return;
+            }
+        };
+
+        let lights = self.point_lights();
+        let (width, height) = (self.area.width, self.area.height);
+        let map = self
+            .light_map
+            .get_or_insert_with(|| LightMap::new(width, height, ambient));
+        if map.update(lights) {
+            self.recompute_party_visibility();
+        }
+    }
+
+    fn point_lights(&self) -> Vec<PointLight> {
+        let mut lights = Vec::new();
+        for prop in self.props.iter() {
+            let light = match prop.prop.light {
+                None => continue,
+                Some(light) => light,
+            };
+
+            if !prop.is_enabled() {
+                continue;
+            }
+
+            lights.push(PointLight {
+                x: prop.location.x as f32 + prop.prop.size.width as f32 / 2.0,
+                y: prop.location.y as f32 + prop.prop.size.height as f32 / 2.0,
+                light,
+            });
+        }
+
+        let mgr = GameState::turn_manager();
+        let mgr = mgr.borrow();
+        for index in self.entities.iter() {
+            let entity = mgr.entity(*index);
+            let entity = entity.borrow();
+            if let Some(light) = entity.light() {
+                lights.push(PointLight {
+                    x: entity.location.x as f32 + entity.size.width as f32 / 2.0,
+                    y: entity.location.y as f32 + entity.size.height as f32 / 2.0,
+                    light,
+                });
+            }
+        }
+
+        lights
+    }
+
+    fn recompute_party_visibility(&mut self) {
+        for member in GameState::party() {
+            if member.borrow().location.area_id != self.area.area.id {
+                continue;
+            }
+            self.recompute_pc_visibility(&member);
+        }
+        self.update_view_visibility();
+        self.pc_vis_full_redraw();
+    }
+
     #[must_use]
     pub fn remove_entity(
         &mut self,
@@ -1144,7 +1704,7 @@ impl AreaState {
     }
 
     pub fn add_feedback_text(&mut self, text: AreaFeedbackText) {
-        if text.is_empty() {
+        if text.is_empty() || !Config::feedback_channel_enabled(text.channel()) {
             return;
         }
 
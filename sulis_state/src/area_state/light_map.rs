@@ -0,0 +1,137 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use sulis_core::ui::Color;
+use sulis_module::LightSource;
+
+/// The light level, in the brightest color channel, at which a tile is
+/// considered lit for the purposes of line of sight
+const LIT_THRESHOLD: f32 = 0.4;
+
+/// A light source positioned in an area, in tile coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub x: f32,
+    pub y: f32,
+    pub light: LightSource,
+}
+
+/// The per tile light color of an area, computed from its ambient light
+/// level and all point lights.  Only recomputed when the lights change.
+pub struct LightMap {
+    width: i32,
+    height: i32,
+    ambient: f32,
+    lights: Vec<PointLight>,
+    colors: Vec<Color>,
+    lit: Vec<bool>,
+    redraw: bool,
+}
+
+impl LightMap {
+    pub(crate) fn new(width: i32, height: i32, ambient: f32) -> LightMap {
+        let dim = (width * height) as usize;
+        let mut map = LightMap {
+            width,
+            height,
+            ambient,
+            lights: Vec::new(),
+            colors: vec![Color::new(ambient, ambient, ambient, 1.0); dim],
+            lit: vec![ambient >= LIT_THRESHOLD; dim],
+            redraw: true,
+        };
+        map.compute();
+        map
+    }
+
+    /// Sets the current point `lights`, recomputing the map if they have
+    /// changed.  Returns true if the map was recomputed.
+    pub(crate) fn update(&mut self, lights: Vec<PointLight>) -> bool {
+        if lights == self.lights {
+            return false;
+        }
+
+        self.lights = lights;
+        self.compute();
+        true
+    }
+
+    fn compute(&mut self) {
+        let ambient = self.ambient;
+        for color in self.colors.iter_mut() {
+            *color = Color::new(ambient, ambient, ambient, 1.0);
+        }
+
+        for light in self.lights.iter() {
+            let radius = light.light.radius;
+            let min_x = ((light.x - radius).floor() as i32).max(0);
+            let min_y = ((light.y - radius).floor() as i32).max(0);
+            let max_x = ((light.x + radius).ceil() as i32).min(self.width);
+            let max_y = ((light.y + radius).ceil() as i32).min(self.height);
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let dx = x as f32 + 0.5 - light.x;
+                    let dy = y as f32 + 0.5 - light.y;
+                    let frac = 1.0 - (dx * dx + dy * dy).sqrt() / radius;
+                    if frac <= 0.0 {
+                        continue;
+                    }
+
+                    let color = &mut self.colors[(x + y * self.width) as usize];
+                    color.r += frac * light.light.color.r;
+                    color.g += frac * light.light.color.g;
+                    color.b += frac * light.light.color.b;
+                }
+            }
+        }
+
+        for (color, lit) in self.colors.iter_mut().zip(self.lit.iter_mut()) {
+            color.r = color.r.min(1.0);
+            color.g = color.g.min(1.0);
+            color.b = color.b.min(1.0);
+            *lit = color.r.max(color.g).max(color.b) >= LIT_THRESHOLD;
+        }
+
+        self.redraw = true;
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The light color of the tile at `x`, `y`.  No bounds checking is done
+    pub fn color(&self, x: i32, y: i32) -> Color {
+        self.colors[(x + y * self.width) as usize]
+    }
+
+    /// Whether each tile of the area is bright enough to be seen from
+    /// beyond the dark vision distance
+    pub fn lit_grid(&self) -> &[bool] {
+        &self.lit
+    }
+
+    /// Returns true if the map has changed since it was last drawn
+    pub fn take_redraw(&mut self) -> bool {
+        let redraw = self.redraw;
+        self.redraw = false;
+        redraw
+    }
+}
@@ -73,6 +73,8 @@ impl PropHandler {
             items: Vec::new(),
             enabled: data.enabled,
             hover_text: None,
+            lock: data.lock,
+            detect_difficulty: data.detect_difficulty,
         };
 
         let index = self.add(&prop_data, location, false)?;
@@ -102,7 +104,7 @@ impl PropHandler {
         for prop_index in &self.prop_grid[index] {
             use prop_state::Interactive::*;
             match self.props[*prop_index].as_ref().unwrap().interactive {
-                Not | Door { .. } | Hover { .. } => (),
+                Not | Door { .. } | Hover { .. } | Trap { .. } => (),
                 Container { .. } => return Some(*prop_index),
             }
         }
@@ -170,6 +172,8 @@ impl PropHandler {
             location: location.to_point(),
             items: Vec::new(),
             hover_text: None,
+            lock: None,
+            detect_difficulty: None,
         };
 
         match self.add(&data, location, true) {
@@ -197,6 +201,8 @@ impl PropHandler {
             location: Point::new(x, y),
             items: Vec::new(),
             hover_text,
+            lock: None,
+            detect_difficulty: None,
         };
 
         if let Err(e) = self.add(&data, location, true) {
@@ -300,7 +306,10 @@ impl PropHandler {
         }
 
         let index = (x + y * self.area.width) as usize;
-        self.prop_grid[index].first().copied()
+        self.prop_grid[index]
+            .iter()
+            .find(|i| !self.get(**i).is_hidden())
+            .copied()
     }
 
     pub fn get(&self, index: usize) -> &PropState {
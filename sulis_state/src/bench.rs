@@ -0,0 +1,193 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Entry points for benchmarking area generation, path finding, and line of
+//! sight, which are the most expensive per area and per move operations.
+//! Only available with the `bench` feature.  See `sulis_bench/benches/hot_paths.rs`.
+
+use std::cell::RefCell;
+use std::io::Error;
+use std::rc::Rc;
+
+use sulis_core::config::Config;
+use sulis_core::resource::ResourceSet;
+use sulis_core::util::{unable_to_create_error, Point};
+use sulis_module::area::{Destination, LocationChecker, PathFinder};
+use sulis_module::{Area, Module};
+
+use crate::area_state::AreaSeed;
+use crate::{AreaState, EntityState, GeneratedArea, Location, PregenOutput, VisBounds};
+
+/// Loads the core data and the module in `module_dir`, as the game does
+/// on startup.  This must be called before any area or actor is used
+pub fn load_module(module_dir: &str) -> Result<(), Error> {
+    let mut dirs = vec![Config::resources_config().directory];
+    dirs.append(&mut Module::module_directories(module_dir)?);
+
+    let yaml = ResourceSet::load_resources(dirs.clone())?;
+    Module::load_resources(yaml, dirs)
+}
+
+fn area(area_id: &str) -> Result<Rc<Area>, Error> {
+    match Module::area(area_id) {
+        None => unable_to_create_error("area", area_id),
+        Some(area) => Ok(area),
+    }
+}
+
+/// Runs the full generation of the area with the specified ID, using the
+/// `seed` so that each run generates the same area.  The area must have
+/// a generator
+pub fn generate_area(area_id: &str, seed: u128) -> Result<GeneratedArea, Error> {
+    let area = area(area_id)?;
+    let pregen = PregenOutput::new(&area, Some(seed))?;
+    if pregen.is_none() {
+        return unable_to_create_error("generated area", area_id);
+    }
+
+    GeneratedArea::new(area, pregen)
+}
+
+struct GridChecker {
+    width: i32,
+    passable: Vec<bool>,
+}
+
+impl LocationChecker for GridChecker {
+    fn passable(&self, x: i32, y: i32) -> bool {
+        self.passable[(x + y * self.width) as usize]
+    }
+}
+
+/// A square grid designed to be as expensive as possible to path across
+pub struct PathFixture {
+    finder: PathFinder,
+    checker: GridChecker,
+    dest: Destination,
+}
+
+impl PathFixture {
+    /// A grid of horizontal walls, each with a single gap at alternating
+    /// ends, so the only path from the top left to the bottom right corner
+    /// winds back and forth across the entire grid
+    pub fn serpentine(size: i32) -> PathFixture {
+        let mut passable = vec![true; (size * size) as usize];
+        for (wall, y) in (1..size - 1).step_by(2).enumerate() {
+            let gap = if wall % 2 == 0 { size - 1 } else { 0 };
+            for x in (0..size).filter(|x| *x != gap) {
+                passable[(x + y * size) as usize] = false;
+            }
+        }
+
+        PathFixture::new(size, passable, size - 1)
+    }
+
+    /// An open grid with the destination walled in, so every reachable
+    /// point is searched before the path finder gives up
+    pub fn unreachable(size: i32) -> PathFixture {
+        let mut passable = vec![true; (size * size) as usize];
+        let center = size / 2;
+        for y in center - 2..=center + 2 {
+            for x in center - 2..=center + 2 {
+                if (x - center).abs() == 2 || (y - center).abs() == 2 {
+                    passable[(x + y * size) as usize] = false;
+                }
+            }
+        }
+
+        PathFixture::new(size, passable, center)
+    }
+
+    fn new(size: i32, passable: Vec<bool>, dest: i32) -> PathFixture {
+        let mut finder = PathFinder::new(size, size);
+        finder.set_max_iterations(size * size);
+
+        PathFixture {
+            finder,
+            checker: GridChecker {
+                width: size,
+                passable,
+            },
+            dest: Destination {
+                parent_w: 1.0,
+                parent_h: 1.0,
+                x: dest as f32,
+                y: dest as f32,
+                w: 1.0,
+                h: 1.0,
+                dist: 0.1,
+                max_path_len: None,
+            },
+        }
+    }
+
+    /// Finds a path from the top left corner to the destination
+    pub fn find(&mut self) -> Option<Vec<Point>> {
+        self.finder.find(&self.checker, 0, 0, self.dest)
+    }
+}
+
+/// An area with a single party member, whose line of sight is recomputed
+/// from positions spread across the area
+pub struct LosFixture {
+    area_state: AreaState,
+    viewer: Rc<RefCell<EntityState>>,
+    positions: Vec<Point>,
+}
+
+impl LosFixture {
+    /// Creates the fixture for the area with `area_id`, with an instance of
+    /// the actor with `actor_id` as the viewer.  Generated areas are created
+    /// using the `seed`
+    pub fn new(area_id: &str, actor_id: &str, seed: u128) -> Result<LosFixture, Error> {
+        let area = area(area_id)?;
+        let actor = match Module::actor(actor_id) {
+            None => return unable_to_create_error("actor", actor_id),
+            Some(actor) => actor,
+        };
+
+        let area_state = AreaState::new(Rc::clone(&area), AreaSeed::Exact(seed))?;
+        let location = Location::new(0, 0, &area);
+        let viewer = EntityState::new(actor, None, location, true, None);
+
+        let step = (area.vis_dist / 2).max(1) as usize;
+        let mut positions = Vec::new();
+        for y in (0..area.height).step_by(step) {
+            for x in (0..area.width).step_by(step) {
+                positions.push(Point::new(x, y));
+            }
+        }
+
+        Ok(LosFixture {
+            area_state,
+            viewer: Rc::new(RefCell::new(viewer)),
+            positions,
+        })
+    }
+
+    /// Moves the viewer to each position in turn, recomputing its line of
+    /// sight.  Returns the region affected by each computation
+    pub fn recompute_all(&mut self) -> Vec<VisBounds> {
+        let mut bounds = Vec::new();
+        for pos in self.positions.iter() {
+            self.viewer.borrow_mut().location.move_to(pos.x, pos.y);
+            if let Some(region) = self.area_state.recompute_pc_visibility(&self.viewer) {
+                bounds.push(region);
+            }
+        }
+        bounds
+    }
+}
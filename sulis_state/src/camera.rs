@@ -0,0 +1,83 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::EntityState;
+
+/// A smooth scroll of the camera to a point, in area coordinates.  If
+/// `millis` is not specified, the duration is based on the scroll distance
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollTarget {
+    pub x: f32,
+    pub y: f32,
+    pub millis: Option<u32>,
+}
+
+/// Camera control for an area.  Game logic and scripts push requests
+/// here, which are then consumed and carried out by the area view on
+/// the following frame.
+#[derive(Default)]
+pub struct Camera {
+    center_on: Option<Rc<RefCell<EntityState>>>,
+    scroll_to: Option<ScrollTarget>,
+    follow: Option<Rc<RefCell<EntityState>>>,
+    shake: bool,
+}
+
+impl Camera {
+    /// Immediately centers the camera on the specified entity, without any
+    /// smooth scrolling
+    pub fn center_on(&mut self, entity: Rc<RefCell<EntityState>>) {
+        self.center_on = Some(entity);
+        self.scroll_to = None;
+    }
+
+    pub fn take_center_on(&mut self) -> Option<Rc<RefCell<EntityState>>> {
+        self.center_on.take()
+    }
+
+    /// Smoothly scrolls the camera to center on the specified point, over
+    /// `millis` milliseconds if specified
+    pub fn scroll_to(&mut self, x: f32, y: f32, millis: Option<u32>) {
+        self.scroll_to = Some(ScrollTarget { x, y, millis });
+    }
+
+    pub fn take_scroll_to(&mut self) -> Option<ScrollTarget> {
+        self.scroll_to.take()
+    }
+
+    /// Causes the camera to keep the specified entity centered as it moves.
+    /// Passing `None` stops following.
+    pub fn follow(&mut self, entity: Option<Rc<RefCell<EntityState>>>) {
+        self.follow = entity;
+    }
+
+    pub fn following(&self) -> Option<&Rc<RefCell<EntityState>>> {
+        self.follow.as_ref()
+    }
+
+    pub fn shake(&mut self) {
+        self.shake = true;
+    }
+
+    pub fn take_shake(&mut self) -> bool {
+        let shake = self.shake;
+        self.shake = false;
+        shake
+    }
+}
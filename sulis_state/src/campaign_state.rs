@@ -0,0 +1,112 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+
+use crate::game_event::{GameEventKind, ScriptObserver};
+
+/// Campaign wide flags set by scripts, used to track story decisions
+/// independently of any particular area or entity.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignState {
+    flags: HashMap<String, String>,
+
+    #[serde(default)]
+    observers: Vec<ScriptObserver>,
+
+    #[serde(default)]
+    scheduled: Vec<ScheduledScript>,
+}
+
+/// A script function which is called once, when the total elapsed game time
+/// reaches `due_millis`.  Scheduled functions are stored with the campaign
+/// state, so they persist across saves.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledScript {
+    pub script: String,
+    pub func: String,
+    pub due_millis: usize,
+}
+
+impl CampaignState {
+    /// Adds the observer, replacing any existing observer with the same id
+    pub fn add_observer(&mut self, observer: ScriptObserver) {
+        self.remove_observer(&observer.id);
+        self.observers.push(observer);
+    }
+
+    pub fn remove_observer(&mut self, id: &str) {
+        self.observers.retain(|observer| observer.id != id);
+    }
+
+    pub fn observers(&self, kind: GameEventKind) -> impl Iterator<Item = &ScriptObserver> {
+        self.observers.iter().filter(move |observer| observer.kind == kind)
+    }
+
+    pub fn schedule(&mut self, scheduled: ScheduledScript) {
+        self.scheduled.push(scheduled);
+    }
+
+    /// Removes and returns all scheduled functions that are due at the
+    /// specified total elapsed millis, in the order they are due
+    pub fn take_due_scheduled(&mut self, millis: usize) -> Vec<ScheduledScript> {
+        let (mut due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled)
+            .into_iter()
+            .partition(|scheduled| scheduled.due_millis <= millis);
+        self.scheduled = pending;
+        due.sort_by_key(|scheduled| scheduled.due_millis);
+        due
+    }
+
+    pub fn flags(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.flags.iter()
+    }
+
+    pub fn set_flag(&mut self, flag: &str, value: &str) {
+        self.flags.insert(flag.to_string(), value.to_string());
+    }
+
+    pub fn clear_flag(&mut self, flag: &str) {
+        self.flags.remove(flag);
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains_key(flag)
+    }
+
+    pub fn get_flag(&self, flag: &str) -> Option<String> {
+        self.flags.get(flag).cloned()
+    }
+
+    /// Returns the integer value of the flag, or 0 if it is not set or
+    /// is not an integer
+    pub fn get_flag_int(&self, flag: &str) -> i32 {
+        match self.flags.get(flag) {
+            None => 0,
+            Some(val) => val.parse::<i32>().unwrap_or(0),
+        }
+    }
+
+    /// Adds `amount` to the integer value of the flag, treating a flag that
+    /// is not set as 0.  Returns the new value
+    pub fn add_flag_int(&mut self, flag: &str, amount: i32) -> i32 {
+        let value = self.get_flag_int(flag) + amount;
+        self.set_flag(flag, &value.to_string());
+        value
+    }
+}
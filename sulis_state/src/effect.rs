@@ -42,10 +42,34 @@ pub struct Icon {
     pub text: String,
 }
 
+/// Determines what happens when an effect is applied to an entity that
+/// already has one or more effects with the same name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StackingPolicy {
+    /// Each effect applies independently of any others
+    #[default]
+    Independent,
+
+    /// Existing effects are removed, so only the newest applies
+    Refresh,
+
+    /// Up to `cap` effects apply at once.  When the cap is exceeded, the
+    /// oldest effects are removed
+    Stack { cap: u32 },
+
+    /// Only the effect with the highest strength applies.  A new effect
+    /// weaker than an existing one is not applied at all
+    Strongest { strength: i32 },
+}
+
 pub struct Effect {
     pub name: String,
     pub tag: String,
 
+    /// Additional tags beyond the primary `tag`
+    pub tags: Vec<String>,
+    pub stacking: StackingPolicy,
+
     pub ui_visible: bool,
 
     pub(crate) cur_duration: u32,
@@ -54,6 +78,10 @@ pub struct Effect {
     pub(crate) deactivate_with_ability: Option<String>,
     pub(crate) surface: Option<Surface>,
     pub(crate) entity: Option<usize>,
+
+    /// Creatures summoned by the owning entity, which are removed along
+    /// with this effect
+    pub(crate) summons: Vec<usize>,
     pub(crate) callbacks: Vec<Rc<CallbackData>>,
     pub(crate) icon: Option<Icon>,
 
@@ -96,9 +124,19 @@ impl Effect {
             }
         }
 
+        let mut summons = Vec::new();
+        for index in data.summons {
+            match entities.get(&index) {
+                None => warn!("Invalid summon {} for effect", index),
+                Some(entity) => summons.push(entity.borrow().index()),
+            }
+        }
+
         Ok(Effect {
             name: data.name,
             tag: data.tag,
+            tags: data.tags,
+            stacking: data.stacking,
             ui_visible: data.ui_visible,
             cur_duration: data.cur_duration,
             total_duration: data.total_duration,
@@ -106,6 +144,7 @@ impl Effect {
             deactivate_with_ability: data.deactivate_with_ability,
             surface,
             entity: data.entity,
+            summons,
             icon: data.icon,
 
             squares_moved: HashMap::new(),
@@ -125,6 +164,8 @@ impl Effect {
         Effect {
             name: name.to_string(),
             tag: tag.to_string(),
+            tags: Vec::new(),
+            stacking: StackingPolicy::Independent,
             ui_visible: true,
             cur_duration: 0,
             total_duration: duration,
@@ -135,6 +176,7 @@ impl Effect {
             deactivate_with_ability,
             surface: None,
             entity: None,
+            summons: Vec::new(),
             icon: None,
             squares_moved: HashMap::new(),
         }
@@ -174,6 +216,21 @@ impl Effect {
         })
     }
 
+    /// Returns true if this effect has the specified tag, either as its
+    /// primary tag or one of its additional tags
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tag == tag || self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns true if this effect has been marked for removal or its
+    /// duration has otherwise run out
+    pub fn is_expired(&self) -> bool {
+        match self.total_duration {
+            ExtInt::Infinity => false,
+            ExtInt::Int(total_duration) => self.cur_duration >= total_duration,
+        }
+    }
+
     pub fn icon(&self) -> Option<&Icon> {
         self.icon.as_ref()
     }
@@ -186,6 +243,10 @@ impl Effect {
         self.entity = Some(entity);
     }
 
+    pub fn summons(&self) -> &[usize] {
+        &self.summons
+    }
+
     pub fn is_surface(&self) -> bool {
         self.surface.is_some()
     }
@@ -19,13 +19,33 @@ use std::rc::Rc;
 
 use sulis_core::io::Audio;
 use crate::{center, is_threat, ActorState, EntityState, GameState};
-use sulis_module::{AccuracyKind, Attack, AttackKind, DamageKind, HitFlags, HitKind, Module,
-    OnTrigger};
+use sulis_module::{AccuracyKind, Attack, AttackKind, Cover, DamageKind, HitFlags, HitKind,
+    Module, OnTrigger};
+
+/// Hits dealing at least this fraction of the target's maximum hit points
+/// shake the screen, in the same way as critical hits
+const BIG_HIT_FRACTION: f32 = 0.5;
+
+fn is_big_hit(target: &EntityState, damage: &[(DamageKind, u32)]) -> bool {
+    let total: u32 = damage.iter().map(|(_, amount)| amount).sum();
+    let max_hp = target.actor.stats.max_hp.max(1) as f32;
+    total as f32 >= max_hp * BIG_HIT_FRACTION
+}
 
 fn is_sneak_attack(parent: &EntityState, target: &EntityState) -> bool {
     parent.actor.stats.hidden && !target.actor.stats.sneak_attack_immunity
 }
 
+/// Attacking from stealth outside of combat may grant a surprise round
+fn check_ambush(parent: &EntityState) {
+    if !parent.actor.stats.hidden {
+        return;
+    }
+
+    let mgr = GameState::turn_manager();
+    mgr.borrow_mut().set_ambusher(parent.index());
+}
+
 fn is_flanking(parent: &EntityState, target: &EntityState) -> bool {
     if target.actor.stats.flanked_immunity {
         return false;
@@ -76,6 +96,51 @@ fn is_flanking(parent: &EntityState, target: &EntityState) -> bool {
     false
 }
 
+/// Returns true if the parent is attacking from behind the target, within 45
+/// degrees of directly opposite the direction the target is facing.  A target
+/// that has not yet moved or attacked has no facing, and cannot be backstabbed
+pub(crate) fn is_backstab(parent: &EntityState, target: &EntityState) -> bool {
+    if target.actor.stats.flanked_immunity {
+        return false;
+    }
+
+    let (fx, fy) = match target.facing() {
+        None => return false,
+        Some(facing) => facing.direction(),
+    };
+
+    let p_target = center(target);
+    let p_parent = center(parent);
+    let (dx, dy) = (p_parent.0 - p_target.0, p_parent.1 - p_target.1);
+    let dist = dx.hypot(dy);
+    if dist == 0.0 {
+        return false;
+    }
+
+    let cos_angle = (dx * fx + dy * fy) / dist;
+    cos_angle < -std::f32::consts::FRAC_1_SQRT_2
+}
+
+/// Returns true if the attack is flanking, or is a backstab and the rules
+/// count backstabs as flanking
+fn is_flanking_or_backstab(parent: &EntityState, target: &EntityState, backstab: bool) -> bool {
+    (backstab && Module::rules().backstab_counts_as_flanking) || is_flanking(parent, target)
+}
+
+fn cover(parent: &EntityState, target: &EntityState) -> Cover {
+    match GameState::get_area_state(&parent.location.area_id) {
+        None => Cover::None,
+        Some(area) => area.borrow().cover(parent, target),
+    }
+}
+
+fn has_high_ground(parent: &EntityState, target: &EntityState) -> bool {
+    match GameState::get_area_state(&parent.location.area_id) {
+        None => false,
+        Some(area) => area.borrow().has_high_ground(parent, target),
+    }
+}
+
 type AttackResult = Vec<(HitKind, HitFlags, Vec<(DamageKind, u32)>)>;
 
 pub fn weapon_attack(
@@ -94,10 +159,13 @@ pub fn weapon_attack(
 
     let attacks = parent.borrow().actor.stats.attacks.clone();
 
-    let is_flanking = is_flanking(&parent.borrow(), &target.borrow());
+    parent.borrow_mut().face_towards(&target.borrow());
+    let is_backstab = is_backstab(&parent.borrow(), &target.borrow());
+    let is_flanking = is_flanking_or_backstab(&parent.borrow(), &target.borrow(), is_backstab);
     let is_sneak_attack = is_sneak_attack(&parent.borrow(), &target.borrow());
+    check_ambush(&parent.borrow());
 
-    let mut had_crit = false;
+    let mut shake = false;
     let mut result = Vec::new();
     for attack in attacks {
         let mut attack = if is_flanking {
@@ -106,11 +174,17 @@ pub fn weapon_attack(
             attack
         };
 
-        let (hit_kind, hit_flags, damage) =
-            attack_internal(parent, target, &mut attack, is_flanking, is_sneak_attack);
+        let (hit_kind, hit_flags, damage) = attack_internal(
+            parent,
+            target,
+            &mut attack,
+            is_flanking,
+            is_backstab,
+            is_sneak_attack,
+        );
 
-        if hit_kind == HitKind::Crit {
-            had_crit = true;
+        if hit_kind == HitKind::Crit || is_big_hit(&target.borrow(), &damage) {
+            shake = true;
         }
 
         let sound = attack.sounds.sound(hit_kind);
@@ -121,7 +195,7 @@ pub fn weapon_attack(
         result.push((hit_kind, hit_flags, damage));
     }
 
-    if had_crit {
+    if shake {
         GameState::add_ui_callback(vec![OnTrigger::ScreenShake], parent, target);
     }
 
@@ -144,11 +218,20 @@ pub fn attack(
         target.borrow().actor.actor.name
     );
 
-    let is_flanking = is_flanking(&parent.borrow(), &target.borrow());
+    parent.borrow_mut().face_towards(&target.borrow());
+    let is_backstab = is_backstab(&parent.borrow(), &target.borrow());
+    let is_flanking = is_flanking_or_backstab(&parent.borrow(), &target.borrow(), is_backstab);
     let is_sneak_attack = is_sneak_attack(&parent.borrow(), &target.borrow());
-
-    let (hit_kind, hit_flags, damage) =
-        attack_internal(parent, target, attack, is_flanking, is_sneak_attack);
+    check_ambush(&parent.borrow());
+
+    let (hit_kind, hit_flags, damage) = attack_internal(
+        parent,
+        target,
+        attack,
+        is_flanking,
+        is_backstab,
+        is_sneak_attack,
+    );
 
     ActorState::check_death(parent, target);
 
@@ -160,6 +243,7 @@ fn attack_internal(
     target: &Rc<RefCell<EntityState>>,
     attack: &mut Attack,
     flanking: bool,
+    backstab: bool,
     sneak_attack: bool,
 ) -> (HitKind, HitFlags, Vec<(DamageKind, u32)>) {
     let rules = Module::rules();
@@ -206,8 +290,24 @@ fn attack_internal(
         attack.bonuses.spell_accuracy += rules.hidden_accuracy_bonus;
     }
 
+    if backstab {
+        attack.bonuses.melee_accuracy += rules.backstab_accuracy_bonus;
+        attack.bonuses.ranged_accuracy += rules.backstab_accuracy_bonus;
+        attack.bonuses.spell_accuracy += rules.backstab_accuracy_bonus;
+    }
+
+    if let AttackKind::Ranged { .. } = attack.kind {
+        let cover = cover(&parent.borrow(), &target.borrow());
+        attack.bonuses.ranged_accuracy -= rules.cover_accuracy_penalty(cover);
+
+        if has_high_ground(&parent.borrow(), &target.borrow()) {
+            attack.bonuses.ranged_accuracy += rules.high_ground_accuracy_bonus;
+        }
+    }
+
     let hit_flags = HitFlags {
         flanking,
+        backstab,
         sneak_attack,
         concealment: false,
     };
@@ -229,11 +329,12 @@ fn attack_internal(
         (hit_kind, damage_multiplier)
     };
 
-    let damage = {
+    let mut damage = {
         let target = &target.borrow().actor.stats;
         let damage = &attack.damage;
         rules.roll_damage(damage, &target.armor, &target.resistance, damage_multiplier)
     };
+    GameState::scale_enemy_damage(&parent.borrow(), &mut damage);
 
     debug!("{:?}. {:?} damage", hit_kind, damage);
 
@@ -246,5 +347,23 @@ fn attack_internal(
         EntityState::remove_hp(target, parent, hit_kind, damage.clone());
     }
 
+    wear_armor(target, hit_kind);
+
     (hit_kind, hit_flags, damage)
 }
+
+fn wear_armor(target: &Rc<RefCell<EntityState>>, hit_kind: HitKind) {
+    let rules = Module::rules();
+    let durability = match &rules.durability {
+        None => return,
+        Some(durability) => durability,
+    };
+
+    let amount = match hit_kind {
+        HitKind::Hit => durability.loss_per_hit,
+        HitKind::Crit => durability.loss_per_crit,
+        _ => return,
+    };
+
+    target.borrow_mut().actor.wear_equipment(amount);
+}
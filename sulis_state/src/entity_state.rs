@@ -15,6 +15,7 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io::Error;
 use std::ptr;
@@ -27,20 +28,27 @@ use crate::animation::{self, Anim};
 use crate::save_state::EntitySaveState;
 use crate::script::{self, CallbackData, ScriptEntitySet};
 use crate::{
-    entity_attack_handler::weapon_attack, entity_texture_cache::Slot, is_within_attack_dist,
-    ActorState, AreaState, ChangeListenerList, EntityTextureCache, EntityTextureSlot, GameState,
-    Location, ScriptCallback, TurnManager,
+    center, entity_attack_handler::weapon_attack, entity_texture_cache::Slot,
+    is_within_attack_dist, ActorState, AreaState, ChangeListenerList, EntityTextureCache,
+    EntityTextureSlot, GameEvent, GameState, Location, PatrolState, ScriptCallback, ThreatTable,
+    TurnManager, VisBounds,
 };
-use sulis_core::io::GraphicsRenderer;
-use sulis_core::ui::{color, Color};
-use sulis_core::util::{invalid_data_error, Offset, Scale, Size, Point};
+use sulis_core::io::{DrawList, GraphicsRenderer};
+use sulis_core::ui::{animation_state, color, Color};
+use sulis_core::util::{invalid_data_error, Offset, Point, Rect, Scale, Size};
 use sulis_module::area::MAX_AREA_SIZE;
 use sulis_module::{
-    actor::Faction, ai, Actor, DamageKind, HitKind, Module, ObjectSize, ObjectSizeIterator,
+    actor::Faction, ai, Actor, DamageKind, Facing, HitKind, LightSource, Module, ObjectSize,
+    ObjectSizeIterator, SpriteState,
 };
 
 enum AIState {
-    Player { vis: Vec<bool>, show_portrait: bool },
+    Player {
+        vis: Vec<bool>,
+        vis_bounds: Option<VisBounds>,
+        show_portrait: bool,
+        auto_combat: bool,
+    },
     AI { group: Option<usize>, active: bool },
 }
 
@@ -54,17 +62,25 @@ pub struct EntityState {
     pub scale: f32,
     pub listeners: ChangeListenerList<EntityState>,
 
+    sprite_state: SpriteState,
+    sprite_millis: u32,
+    facing: Option<Facing>,
+
     ai_state: AIState,
     ai_callbacks: Option<Rc<CallbackData>>,
     pub(crate) marked_for_removal: bool,
     texture_cache_slot: Option<EntityTextureSlot>,
 
     custom_flags: HashMap<String, String>,
+    pub(crate) threat: ThreatTable,
 
     index: usize,      // index in vec of the owning manager
     unique_id: String, // assigned when setting the index and persisted on save
 
     collapsed_groups: Vec<String>,
+
+    patrol: Option<PatrolState>,
+    light: Option<LightSource>,
 }
 
 impl PartialEq for EntityState {
@@ -87,7 +103,9 @@ impl EntityState {
                 let dim = (MAX_AREA_SIZE * MAX_AREA_SIZE) as usize;
                 AIState::Player {
                     vis: vec![false; dim],
+                    vis_bounds: None,
                     show_portrait: save.show_portrait,
+                    auto_combat: save.auto_combat,
                 }
             }
         };
@@ -120,11 +138,17 @@ impl EntityState {
             color_sec: Color::new(0.0, 0.0, 0.0, 0.0),
             scale: 1.0,
             listeners: ChangeListenerList::default(),
+            sprite_state: SpriteState::Idle,
+            sprite_millis: 0,
+            facing: save.facing,
             ai_state,
             marked_for_removal: false,
             texture_cache_slot: None,
             custom_flags: save.custom_flags,
+            threat: save.threat,
             collapsed_groups: save.collapsed_groups,
+            patrol: save.patrol,
+            light: save.light,
         })
     }
 
@@ -139,7 +163,9 @@ impl EntityState {
             let dim = (MAX_AREA_SIZE * MAX_AREA_SIZE) as usize;
             AIState::Player {
                 vis: vec![false; dim],
+                vis_bounds: None,
                 show_portrait: true,
+                auto_combat: false,
             }
         } else {
             AIState::AI {
@@ -168,14 +194,83 @@ impl EntityState {
             index: usize::MAX,
             unique_id,
             listeners: ChangeListenerList::default(),
+            sprite_state: SpriteState::Idle,
+            sprite_millis: 0,
+            facing: None,
             marked_for_removal: false,
             ai_state,
             texture_cache_slot: None,
             custom_flags: HashMap::new(),
+            threat: ThreatTable::default(),
             collapsed_groups: Vec::new(),
+            patrol: None,
+            light: None,
         }
     }
 
+    pub fn sprite_state(&self) -> SpriteState {
+        self.sprite_state
+    }
+
+    /// Sets the sprite state this entity is drawn in, with `millis` being the time
+    /// elapsed since the animation driving the state began
+    pub(crate) fn set_sprite_state(&mut self, state: SpriteState, millis: u32) {
+        self.sprite_state = state;
+        self.sprite_millis = millis;
+    }
+
+    /// The direction this entity is facing, or `None` if it has not yet
+    /// moved or attacked
+    pub fn facing(&self) -> Option<Facing> {
+        self.facing
+    }
+
+    pub fn set_facing(&mut self, facing: Facing) {
+        self.facing = Some(facing);
+    }
+
+    /// Turns this entity to face the center of the `target`, if it is not
+    /// at the same position
+    pub fn face_towards(&mut self, target: &EntityState) {
+        let (x, y) = center(self);
+        let (target_x, target_y) = center(target);
+        if let Some(facing) = Facing::from_direction(target_x - x, target_y - y) {
+            self.facing = Some(facing);
+        }
+    }
+
+    pub fn patrol(&self) -> Option<&PatrolState> {
+        self.patrol.as_ref()
+    }
+
+    pub(crate) fn patrol_mut(&mut self) -> Option<&mut PatrolState> {
+        self.patrol.as_mut()
+    }
+
+    pub(crate) fn set_patrol(&mut self, patrol: Option<PatrolState>) {
+        self.patrol = patrol;
+    }
+
+    /// The light emitted by this entity, from either a script or its equipped
+    /// items.  If there are several, the one with the largest radius is used
+    pub fn light(&self) -> Option<LightSource> {
+        self.actor
+            .inventory()
+            .equipped_iter()
+            .filter_map(|item| item.item.equippable.as_ref().and_then(|e| e.light))
+            .chain(self.light)
+            .max_by(|a, b| a.radius.partial_cmp(&b.radius).unwrap_or(Ordering::Equal))
+    }
+
+    /// The light set on this entity by a script, if any
+    pub fn script_light(&self) -> Option<LightSource> {
+        self.light
+    }
+
+    pub fn set_script_light(&mut self, light: Option<LightSource>) {
+        self.light = light;
+    }
+
     pub fn add_collapsed_group(&mut self, group: String) {
         self.collapsed_groups.push(group);
     }
@@ -323,10 +418,31 @@ impl EntityState {
         let dim = (MAX_AREA_SIZE * MAX_AREA_SIZE) as usize;
         self.ai_state = AIState::Player {
             vis: vec![false; dim],
+            vis_bounds: None,
             show_portrait,
+            auto_combat: false,
         };
     }
 
+    /// Returns true if this is a party member whose combat turns are taken
+    /// by the AI, false otherwise
+    pub fn is_auto_combat(&self) -> bool {
+        match self.ai_state {
+            AIState::Player { auto_combat, .. } => auto_combat,
+            AIState::AI { .. } => false,
+        }
+    }
+
+    pub fn set_auto_combat(&mut self, auto: bool) {
+        if let AIState::Player {
+            ref mut auto_combat,
+            ..
+        } = self.ai_state
+        {
+            *auto_combat = auto;
+        }
+    }
+
     pub fn remove_from_party(&mut self) {
         self.ai_state = AIState::AI {
             group: None,
@@ -343,13 +459,37 @@ impl EntityState {
 
     pub fn clear_pc_vis(&mut self) {
         match self.ai_state {
-            AIState::Player { ref mut vis, .. } => unsafe {
-                ptr::write_bytes(vis.as_mut_ptr(), 0, vis.len());
-            },
+            AIState::Player {
+                ref mut vis,
+                ref mut vis_bounds,
+                ..
+            } => {
+                unsafe { ptr::write_bytes(vis.as_mut_ptr(), 0, vis.len()) }
+                *vis_bounds = None;
+            }
             _ => panic!(),
         }
     }
 
+    /// The region covered by the most recent line of sight calculation for
+    /// this party member, or `None` if it has not been computed since the
+    /// vis was last cleared
+    pub fn pc_vis_bounds(&self) -> Option<VisBounds> {
+        match self.ai_state {
+            AIState::Player { vis_bounds, .. } => vis_bounds,
+            AIState::AI { .. } => None,
+        }
+    }
+
+    pub(crate) fn set_pc_vis_bounds(&mut self, bounds: VisBounds) {
+        match self.ai_state {
+            AIState::Player {
+                ref mut vis_bounds, ..
+            } => *vis_bounds = Some(bounds),
+            AIState::AI { .. } => panic!(),
+        }
+    }
+
     pub fn pc_vis_mut(&mut self) -> &mut Vec<bool> {
         match self.ai_state {
             AIState::Player { ref mut vis, .. } => vis,
@@ -433,7 +573,7 @@ impl EntityState {
             return false;
         }
 
-        self.actor.ap() >= self.actor.get_move_ap_cost(1)
+        self.actor.ap() >= self.actor.get_move_ap_cost(1, 0)
     }
 
     /// Returns true if this entity can attack the specified target with its
@@ -491,8 +631,22 @@ impl EntityState {
         damage: Vec<(DamageKind, u32)>,
     ) {
         let hp_amount = damage.iter().map(|(_, amount)| amount).sum();
+        let was_alive = entity.borrow().actor.hp() > 0;
         entity.borrow_mut().actor.remove_hp(hp_amount);
 
+        let (target_index, attacker_index) = (entity.borrow().index(), attacker.borrow().index());
+        GameState::publish_event(GameEvent::DamageDealt {
+            source: attacker_index,
+            target: target_index,
+            hit_kind,
+            damage: damage.clone(),
+        });
+
+        if !Rc::ptr_eq(entity, attacker) {
+            let attacker_index = attacker.borrow().index();
+            entity.borrow_mut().threat.add(attacker_index, hp_amount as f32);
+        }
+
         let targets = ScriptEntitySet::from_pair(entity, attacker);
 
         let mgr = GameState::turn_manager();
@@ -502,6 +656,14 @@ impl EntityState {
             .for_each(|cb| cb.on_damaged(&targets, hit_kind, damage.clone()));
 
         let hp = entity.borrow().actor.hp();
+        if hp <= 0 && was_alive {
+            GameState::publish_event(GameEvent::EntityDied {
+                entity: target_index,
+                actor: entity.borrow().actor.actor.id.to_string(),
+                killer: attacker_index,
+            });
+        }
+
         if hp <= 0 {
             debug!(
                 "Entity '{}' has zero hit points.  Playing death animation",
@@ -514,7 +676,9 @@ impl EntityState {
         }
     }
 
-    pub fn move_to(&mut self, x: i32, y: i32, squares: u32) -> bool {
+    /// Moves this entity to the specified position, charging AP in combat for
+    /// the specified number of orthogonal `squares` and `diagonals` squares
+    pub fn move_to(&mut self, x: i32, y: i32, squares: u32, diagonals: u32) -> bool {
         trace!("Move to {},{}", x, y);
         if !self.location.coords_valid(x, y) {
             return false;
@@ -531,8 +695,8 @@ impl EntityState {
         }
 
         let mgr = GameState::turn_manager();
-        if mgr.borrow().is_combat_active() && squares > 0 {
-            let ap_cost = self.actor.get_move_ap_cost(squares);
+        if mgr.borrow().is_combat_active() && (squares > 0 || diagonals > 0) {
+            let ap_cost = self.actor.get_move_ap_cost(squares, diagonals);
             if self.actor.ap() < ap_cost {
                 return false;
             }
@@ -633,7 +797,7 @@ impl AreaDrawable for EntityState {
         scale: Scale,
         x: f32,
         y: f32,
-        _millis: u32,
+        millis: u32,
         color: Color,
     ) {
         // don't draw invisible hostiles
@@ -660,6 +824,31 @@ impl AreaDrawable for EntityState {
             x: offset_x,
             y: offset_y,
         };
+
+        let facing = self.facing.unwrap_or_default();
+        if let Some(image) = self.actor.actor.sprite_image(self.sprite_state, facing) {
+            let millis = match self.sprite_state {
+                SpriteState::Idle => millis,
+                _ => self.sprite_millis,
+            };
+            let w = image.get_width_f32() * self.scale;
+            let h = image.get_height_f32() * self.scale;
+            let rect = Rect {
+                x: x + (self.size.width as f32 - w) / 2.0,
+                y: y + (self.size.height as f32 - h) / 2.0,
+                w,
+                h,
+            };
+
+            let mut list = DrawList::empty_sprite();
+            image.append_to_draw_list(&mut list, &animation_state::NORMAL, rect, millis);
+            list.set_scale(scale);
+            list.set_color(color);
+            list.set_color_sec(self.color_sec);
+            renderer.draw(list);
+            return;
+        }
+
         let slot_loc = Slot { x, y };
         if let Some(ref slot) = self.texture_cache_slot {
             slot.draw(renderer, slot_loc, offset, scale, color, self.color_sec);
@@ -0,0 +1,75 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+
+use sulis_module::faction::{MAX_STANDING, MIN_STANDING};
+use sulis_module::{Actor, Faction, Module};
+
+/// Tracks the party's standing with each faction.  Factions that have never
+/// been adjusted are not stored, and use their initial standing.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FactionState {
+    standings: HashMap<String, i32>,
+}
+
+impl FactionState {
+    pub fn standing(&self, faction_id: &str) -> i32 {
+        if let Some(standing) = self.standings.get(faction_id) {
+            return *standing;
+        }
+
+        match Module::faction(faction_id) {
+            None => {
+                warn!("Faction '{}' not found when querying standing", faction_id);
+                0
+            }
+            Some(faction) => faction.initial_standing,
+        }
+    }
+
+    /// Adds `amount` to the standing with the specified faction, clamping to
+    /// the valid range.  Returns the new standing
+    pub(crate) fn adjust(&mut self, faction_id: &str, amount: i32) -> i32 {
+        let standing = self.standing(faction_id) + amount;
+        let standing = standing.clamp(MIN_STANDING, MAX_STANDING);
+        self.standings.insert(faction_id.to_string(), standing);
+        standing
+    }
+
+    /// Returns the attitude the specified actor should take based on the
+    /// standing with its faction, or the actor's own faction if it does not
+    /// belong to one
+    pub fn disposition(&self, actor: &Actor) -> Faction {
+        let faction = match actor.faction_id.as_ref().and_then(|id| Module::faction(id)) {
+            None => return actor.faction(),
+            Some(faction) => faction,
+        };
+
+        faction.disposition(self.standing(&faction.id))
+    }
+
+    pub fn price_factor(&self, faction_id: &str) -> f32 {
+        match Module::faction(faction_id) {
+            None => {
+                warn!("Faction '{}' not found when computing prices", faction_id);
+                1.0
+            }
+            Some(faction) => faction.price_factor(self.standing(faction_id)),
+        }
+    }
+}
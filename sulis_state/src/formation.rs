@@ -19,19 +19,30 @@ use std::f32;
 use std::rc::Rc;
 
 use crate::{EntityState, GameState};
-use sulis_core::util;
+use sulis_core::util::{self, Point};
 use sulis_module::area::Destination;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(deny_unknown_fields)]
-pub struct Formation {
-    positions: Vec<(f32, f32)>,
+/// The preset arrangements a party formation may be built from.  `Custom` is
+/// used once the player has moved any individual position by hand.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FormationLayout {
+    #[default]
+    Custom,
+    Line,
+    Wedge,
+    Column,
 }
 
-impl Default for Formation {
-    fn default() -> Self {
-        Formation {
-            positions: vec![
+impl FormationLayout {
+    pub fn iter() -> impl Iterator<Item = &'static FormationLayout> {
+        use FormationLayout::*;
+        [Line, Wedge, Column].iter()
+    }
+
+    fn positions(self) -> Vec<(f32, f32)> {
+        use FormationLayout::*;
+        match self {
+            Custom => vec![
                 (-2.0, 0.0),
                 (2.0, 0.0),
                 (-2.0, 3.0),
@@ -39,10 +50,49 @@ impl Default for Formation {
                 (-2.0, 6.0),
                 (2.0, 6.0),
             ],
+            Line => vec![
+                (-1.0, 0.0),
+                (1.0, 0.0),
+                (-3.0, 0.0),
+                (3.0, 0.0),
+                (-5.0, 0.0),
+                (5.0, 0.0),
+            ],
+            Wedge => vec![
+                (0.0, 0.0),
+                (-3.0, 3.0),
+                (3.0, 3.0),
+                (-6.0, 6.0),
+                (6.0, 6.0),
+                (0.0, 6.0),
+            ],
+            Column => vec![
+                (0.0, 0.0),
+                (0.0, 2.0),
+                (0.0, 4.0),
+                (0.0, 6.0),
+                (0.0, 8.0),
+                (0.0, 10.0),
+            ],
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Formation {
+    positions: Vec<(f32, f32)>,
+
+    #[serde(default)]
+    layout: FormationLayout,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Formation::new(FormationLayout::Custom)
+    }
+}
+
 fn center_of_group(entities: &[Rc<RefCell<EntityState>>]) -> (f32, f32) {
     let mut x = 0.0;
     let mut y = 0.0;
@@ -59,7 +109,45 @@ fn center_of_group(entities: &[Rc<RefCell<EntityState>>]) -> (f32, f32) {
     (x, y)
 }
 
+/// A rectangular set of squares claimed as the final position of a mover
+#[derive(Clone, Copy)]
+struct Footprint {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+}
+
+impl Footprint {
+    fn new(entity: &EntityState, p: Point) -> Footprint {
+        Footprint {
+            x: p.x,
+            y: p.y,
+            w: entity.size.width,
+            h: entity.size.height,
+        }
+    }
+
+    fn overlaps(&self, other: &Footprint) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+}
+
 impl Formation {
+    pub fn new(layout: FormationLayout) -> Formation {
+        Formation {
+            positions: layout.positions(),
+            layout,
+        }
+    }
+
+    pub fn layout(&self) -> FormationLayout {
+        self.layout
+    }
+
     pub fn positions_iter(&self) -> impl Iterator<Item = &(f32, f32)> {
         self.positions.iter()
     }
@@ -69,9 +157,18 @@ impl Formation {
             return;
         }
 
+        if self.positions[index] != pos {
+            self.layout = FormationLayout::Custom;
+        }
         self.positions[index] = pos;
     }
 
+    /// Moves all of the specified entities towards the destination at once,
+    /// with each entity taking its place in the formation.  Each mover claims
+    /// the position at the end of its path so that no two movers end up
+    /// overlapping, and along the way movers briefly wait for each other to
+    /// get out of the way.  If hostiles are sighted during the move, combat
+    /// starts and the remaining moves are cancelled.
     pub fn move_group(
         &self,
         entities_to_move: &[Rc<RefCell<EntityState>>],
@@ -96,6 +193,7 @@ impl Formation {
         };
 
         let start_time = ::std::time::Instant::now();
+        let mut reserved: Vec<Footprint> = Vec::new();
         for (index, to_move) in to_move.iter().enumerate() {
             // first rotate the stored positions by 90 degrees
             let xi = -self.positions[index].1;
@@ -107,6 +205,7 @@ impl Formation {
             let parent_w = to_move.borrow().size.width as f32;
             let parent_h = to_move.borrow().size.height as f32;
 
+            let mut end = None;
             for dist_increase in 0..3 {
                 let dist = dest.dist + dist_increase as f32 * 1.0;
                 let dest = Destination {
@@ -119,19 +218,35 @@ impl Formation {
                     dist,
                     max_path_len: None,
                 };
-                if GameState::can_move_towards_dest(
+                let path = match GameState::can_move_towards_dest(
                     &to_move.borrow(),
                     entities_to_ignore,
                     dest,
-                )
-                .is_none()
-                {
+                ) {
+                    None => continue,
+                    Some(path) => path,
+                };
+
+                let footprint = match path.last() {
+                    None => continue,
+                    Some(p) => Footprint::new(&to_move.borrow(), *p),
+                };
+                if reserved.iter().any(|r| r.overlaps(&footprint)) {
                     continue;
                 }
 
-                GameState::move_towards_dest(to_move, entities_to_ignore, dest, None);
-                break;
+                if GameState::move_towards_dest(to_move, entities_to_ignore, dest, None) {
+                    end = Some(footprint);
+                    break;
+                }
             }
+
+            // a member that doesn't move keeps its current position
+            let end = end.unwrap_or_else(|| {
+                let entity = to_move.borrow();
+                Footprint::new(&entity, entity.location.to_point())
+            });
+            reserved.push(end);
         }
 
         debug!(
@@ -0,0 +1,115 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use sulis_module::{DamageKind, HitKind};
+
+/// The kind of a `GameEvent`, used by observers to select which events
+/// they are interested in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub enum GameEventKind {
+    DamageDealt,
+    EntityDied,
+    ItemAcquired,
+    AreaEntered,
+    QuestCompleted,
+    CoinsChanged,
+}
+
+impl GameEventKind {
+    pub fn unwrap_from_str(s: &str) -> Option<GameEventKind> {
+        use GameEventKind::*;
+        match s {
+            "DamageDealt" => Some(DamageDealt),
+            "EntityDied" => Some(EntityDied),
+            "ItemAcquired" => Some(ItemAcquired),
+            "AreaEntered" => Some(AreaEntered),
+            "QuestCompleted" => Some(QuestCompleted),
+            "CoinsChanged" => Some(CoinsChanged),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        use GameEventKind::*;
+        match self {
+            DamageDealt => "DamageDealt",
+            EntityDied => "EntityDied",
+            ItemAcquired => "ItemAcquired",
+            AreaEntered => "AreaEntered",
+            QuestCompleted => "QuestCompleted",
+            CoinsChanged => "CoinsChanged",
+        }
+    }
+}
+
+/// A structured notification of something that happened in the game.  Events
+/// are published with `GameState::publish_event` and delivered to all
+/// listeners and script observers on the next game update.  Entities are
+/// referred to by index, as they may have been removed by the time the
+/// event is delivered.
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    DamageDealt {
+        source: usize,
+        target: usize,
+        hit_kind: HitKind,
+        damage: Vec<(DamageKind, u32)>,
+    },
+    EntityDied {
+        entity: usize,
+        actor: String,
+        killer: usize,
+    },
+    ItemAcquired {
+        item: String,
+        quantity: u32,
+    },
+    AreaEntered {
+        area: String,
+    },
+    QuestCompleted {
+        quest: String,
+    },
+    CoinsChanged {
+        total: i32,
+    },
+}
+
+impl GameEvent {
+    pub fn kind(&self) -> GameEventKind {
+        match self {
+            GameEvent::DamageDealt { .. } => GameEventKind::DamageDealt,
+            GameEvent::EntityDied { .. } => GameEventKind::EntityDied,
+            GameEvent::ItemAcquired { .. } => GameEventKind::ItemAcquired,
+            GameEvent::AreaEntered { .. } => GameEventKind::AreaEntered,
+            GameEvent::QuestCompleted { .. } => GameEventKind::QuestCompleted,
+            GameEvent::CoinsChanged { .. } => GameEventKind::CoinsChanged,
+        }
+    }
+}
+
+/// A script function which is called for each event of the specified kind.
+/// Observers are stored with the campaign state, so they persist across
+/// saves.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptObserver {
+    pub id: String,
+    pub kind: GameEventKind,
+    pub script: String,
+    pub func: String,
+}
@@ -19,21 +19,33 @@ use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 use std::rc::Rc;
 
-use sulis_core::config::Config;
+use sulis_core::config::{Config, FeedbackChannel};
 use sulis_core::io::{GraphicsRenderer};
-use sulis_core::util::{invalid_data_error, ExtInt, Offset, Point, Scale};
+use sulis_core::tr;
+use sulis_core::util::{
+    self, invalid_data_error, ExtInt, Offset, Point, ReproducibleRandom, Scale,
+};
 use sulis_module::on_trigger::QuestEntryState;
 use sulis_module::{
     area::{Destination, PathFinder, Trigger, TriggerKind},
-    Actor, ItemState, Module, OnTrigger, Time, MOVE_TO_THRESHOLD,
+    campaign::WorldMapLocation,
+    Actor, DamageKind, Difficulty, Faction, ItemListEntrySaveState, ItemState, Module, OnTrigger,
+    Time, XpDistribution, MOVE_TO_THRESHOLD,
 };
 
 use crate::animation::{particle_generator::Param, Anim, AnimSaveState, AnimState};
-use crate::script::{script_cache, script_callback, Script, ScriptCallback, ScriptEntity};
+use crate::area_state::AreaSeed;
+use crate::game_event::{GameEvent, ScriptObserver};
+use crate::script::{
+    script_cache, script_callback, Script, ScriptCallback, ScriptEntity, ScriptGameEvent,
+};
 use crate::{
-    path_finder, transition_handler, AreaState, ChangeListener, ChangeListenerList, Effect,
-    EntityState, Formation, ItemList, Location, PartyStash, QuestStateSet, SaveState, TurnManager,
-    UICallback, WorldMapState, AI,
+    area_feedback_text::ColorKind, dist, lock_handler, path_finder, replay, rest_handler,
+    save_file, transition_handler, AchievementState, AreaFeedbackText, AreaState, CampaignState,
+    ChangeListener, ChangeListenerList, Effect, EntityState, FactionState, Formation,
+    FormationLayout, ItemList, Location, PartyStash, PatrolState, QuestStateSet, QueuedOrder,
+    ReplayCommand, RestResult, SaveState, ScheduledScript, TravelState, TurnManager, UICallback,
+    UnlockKind, WorldMapState, AI,
 };
 
 thread_local! {
@@ -45,6 +57,15 @@ thread_local! {
     static ANIMATIONS: RefCell<AnimState> = RefCell::new(AnimState::new());
     static ANIMS_TO_ADD: RefCell<Vec<Anim>> = RefCell::new(Vec::new());
     static COMBAT_INACTIVE_TIME: Cell<u32> = Cell::new(0);
+    static FACTIONS: RefCell<FactionState> = RefCell::new(FactionState::default());
+    static DIFFICULTY: Cell<Difficulty> = Cell::new(Difficulty::default());
+    static IRONMAN_SLOT: RefCell<Option<String>> = RefCell::new(None);
+    static CAMPAIGN_SEED: Cell<u64> = Cell::new(0);
+    static SEEDED_CAMPAIGN: Cell<bool> = Cell::new(false);
+    static QUEUED_ORDERS: RefCell<HashMap<usize, Vec<QueuedOrder>>> = RefCell::new(HashMap::new());
+    static EVENT_QUEUE: RefCell<Vec<GameEvent>> = RefCell::new(Vec::new());
+    static EVENT_LISTENERS: RefCell<ChangeListenerList<GameEvent>> =
+        RefCell::new(ChangeListenerList::default());
 }
 
 pub struct GameState {
@@ -52,6 +73,8 @@ pub struct GameState {
     area_state: Rc<RefCell<AreaState>>,
     world_map: WorldMapState,
     quests: QuestStateSet,
+    campaign: CampaignState,
+    travel: Option<TravelState>,
     selected: Vec<Rc<RefCell<EntityState>>>,
     user_zoom: f32,
     party: Vec<Rc<RefCell<EntityState>>>,
@@ -73,6 +96,10 @@ const MAX_ZOOM: f32 = 2.0;
 
 impl GameState {
     pub fn load(save_state: SaveState) -> Result<(), Error> {
+        if let Some(path) = &save_state.save_path {
+            replay::start_recording(path, Config::fixed_combat_rolls());
+        }
+
         TURN_MANAGER.with(|mgr| {
             mgr.borrow_mut().load(save_state.total_elapsed_millis);
         });
@@ -81,13 +108,26 @@ impl GameState {
         CLEAR_ANIMS.with(|c| c.set(false));
         MODAL_LOCKED.with(|c| c.set(false));
         ANIMS_TO_ADD.with(|anims| anims.borrow_mut().clear());
+        QUEUED_ORDERS.with(|orders| orders.borrow_mut().clear());
+        EVENT_QUEUE.with(|events| events.borrow_mut().clear());
+        EVENT_LISTENERS.with(|l| *l.borrow_mut() = ChangeListenerList::default());
+        AchievementState::add_listener();
         AI.with(|ai| *ai.borrow_mut() = AI::new());
+        util::set_combat_rand(GameState::load_combat_rand(&save_state));
+        FACTIONS.with(|f| *f.borrow_mut() = save_state.factions);
+        DIFFICULTY.with(|d| d.set(save_state.difficulty));
+        IRONMAN_SLOT.with(|s| *s.borrow_mut() = save_state.ironman.clone());
+        CAMPAIGN_SEED.with(|s| s.set(save_state.campaign_seed));
+        SEEDED_CAMPAIGN.with(|s| s.set(save_state.seeded_campaign));
         script_cache::setup().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
 
         let game_state: Result<GameState, Error> = {
             let mut areas = HashMap::new();
             for (id, area_save) in save_state.areas {
-                let area_state = AreaState::load(&id, area_save)?;
+                let mut area_state = AreaState::load(&id, area_save)?;
+                if let Some(hash) = save_state.area_refs.get(&id) {
+                    area_state.set_save_hash(hash.to_string());
+                }
 
                 areas.insert(id, Rc::new(RefCell::new(area_state)));
             }
@@ -147,6 +187,7 @@ impl GameState {
 
             let mgr = GameState::turn_manager();
             mgr.borrow_mut().cur_ai_group_index = save_state.manager.cur_ai_group_index;
+            mgr.borrow_mut().combat_start_suppressed = save_state.manager.combat_start_suppressed;
             for (key, value) in save_state.manager.ai_groups {
                 let index = match key.parse::<usize>() {
                     Ok(val) => val,
@@ -158,6 +199,17 @@ impl GameState {
                 mgr.borrow_mut().ai_groups.insert(index, value);
             }
 
+            for (key, value) in save_state.manager.fled_groups {
+                let index = match key.parse::<usize>() {
+                    Ok(val) => val,
+                    Err(e) => {
+                        let err = Error::new(ErrorKind::InvalidInput, e);
+                        return Err(err);
+                    }
+                };
+                mgr.borrow_mut().fled_groups.insert(index, value);
+            }
+
             for effect_save in save_state.manager.effects {
                 let old_index = effect_save.index;
                 let new_index = mgr.borrow().get_next_effect_index();
@@ -222,18 +274,8 @@ impl GameState {
 
             let party_coins = save_state.coins;
 
-            let mut stash = ItemList::default();
-            for item_save in save_state.stash {
-                let item = &item_save.item;
-                let item = match Module::create_get_item(&item.id, &item.adjectives) {
-                    None => invalid_data_error(&format!("No item with ID '{}'", item_save.item.id)),
-                    Some(item) => Ok(item),
-                }?;
-
-                let item = ItemState::new(item, item_save.item.variant);
-
-                stash.add_quantity(item_save.quantity, item);
-            }
+            let stash = load_item_list(save_state.stash)?;
+            let storage = load_item_list(save_state.storage)?;
 
             let quests = QuestStateSet::load(save_state.quests);
             let mut world_map = save_state.world_map;
@@ -252,12 +294,14 @@ impl GameState {
                 user_zoom: save_state.zoom,
                 party_formation: Rc::new(RefCell::new(formation)),
                 party_coins,
-                party_stash: Rc::new(RefCell::new(PartyStash::new(stash))),
+                party_stash: Rc::new(RefCell::new(PartyStash::new(stash, storage))),
                 party_listeners: ChangeListenerList::default(),
                 party_death_listeners: ChangeListenerList::default(),
                 ui_callbacks: Vec::new(),
                 world_map,
                 quests,
+                campaign: save_state.campaign,
+                travel: save_state.travel,
             })
         };
 
@@ -270,21 +314,67 @@ impl GameState {
         let area_state = GameState::area_state();
         let mut area_state = area_state.borrow_mut();
         area_state.update_view_visibility();
-        area_state.push_scroll_to_callback(pc);
+        area_state.camera().center_on(pc);
 
         Ok(())
     }
 
+    /// The combat roll generator for a loaded game.  Unless combat rolls are
+    /// fixed, a new generator is created so that reloading rerolls them
+    fn load_combat_rand(save_state: &SaveState) -> ReproducibleRandom {
+        let restore = replay::restore_combat_rand().unwrap_or_else(Config::fixed_combat_rolls);
+        let rand = match save_state.combat_rand {
+            Some(ref rand) if restore => rand,
+            _ => return ReproducibleRandom::new(None),
+        };
+
+        match rand.load() {
+            Ok(rand) => rand,
+            Err(e) => {
+                warn!("Unable to load combat roll state, using a new seed");
+                warn!("{}", e);
+                ReproducibleRandom::new(None)
+            }
+        }
+    }
+
+    /// Starts a new game with the specified player character and party.  In
+    /// an `ironman` game, the game is saved to a single slot which is written
+    /// automatically and deleted if the party is defeated.  If a `seed` is
+    /// specified, it becomes the campaign seed and each procedurally generated
+    /// area is generated from a seed derived from it and the area's ID.
+    /// Otherwise, the campaign seed is random and areas are generated freely.
     pub fn init(
         pc_actor: Rc<Actor>,
         party_actors: Vec<Rc<Actor>>,
         flags: HashMap<String, String>,
+        ironman: bool,
+        seed: Option<u128>,
     ) -> Result<(), Error> {
         ANIMATIONS.with(|anims| anims.borrow_mut().clear());
         CLEAR_ANIMS.with(|c| c.set(false));
         MODAL_LOCKED.with(|c| c.set(false));
         ANIMS_TO_ADD.with(|anims| anims.borrow_mut().clear());
+        QUEUED_ORDERS.with(|orders| orders.borrow_mut().clear());
+        EVENT_QUEUE.with(|events| events.borrow_mut().clear());
+        EVENT_LISTENERS.with(|l| *l.borrow_mut() = ChangeListenerList::default());
+        AchievementState::add_listener();
         AI.with(|ai| *ai.borrow_mut() = AI::new());
+        FACTIONS.with(|f| *f.borrow_mut() = FactionState::default());
+        DIFFICULTY.with(|d| d.set(Difficulty::default()));
+        let slot = if ironman {
+            Some(save_file::new_ironman_slot())
+        } else {
+            None
+        };
+        IRONMAN_SLOT.with(|s| *s.borrow_mut() = slot);
+        let campaign_seed = match seed {
+            Some(seed) => seed as u64,
+            None => util::new_rand_seed(),
+        };
+        CAMPAIGN_SEED.with(|s| s.set(campaign_seed));
+        SEEDED_CAMPAIGN.with(|s| s.set(seed.is_some()));
+        util::set_combat_rand(ReproducibleRandom::new(None));
 
         TURN_MANAGER.with(|mgr| {
             let rules = Module::rules();
@@ -301,9 +391,7 @@ impl GameState {
         let pc = GameState::player();
         let area_state = GameState::area_state();
         area_state.borrow_mut().update_view_visibility();
-        area_state
-            .borrow_mut()
-            .push_scroll_to_callback(Rc::clone(&pc));
+        area_state.borrow_mut().camera().center_on(Rc::clone(&pc));
         area_state.borrow_mut().on_load_fired = true;
         let area_state = area_state.borrow();
         GameState::add_ui_callbacks_of_kind(
@@ -339,7 +427,8 @@ impl GameState {
 
         let campaign = Module::campaign();
 
-        let area_state = GameState::setup_area_state(&campaign.starting_area)?;
+        let seed = GameState::area_seed(&campaign.starting_area);
+        let area_state = GameState::setup_area_state(&campaign.starting_area, seed)?;
 
         debug!(
             "Setting up PC {}, with {:?}",
@@ -422,12 +511,14 @@ impl GameState {
             party,
             party_formation: Rc::new(RefCell::new(Formation::default())),
             party_coins,
-            party_stash: Rc::new(RefCell::new(PartyStash::new(party_stash))),
+            party_stash: Rc::new(RefCell::new(PartyStash::new(party_stash, ItemList::default()))),
             party_listeners: ChangeListenerList::default(),
             party_death_listeners: ChangeListenerList::default(),
             ui_callbacks: Vec::new(),
             world_map: WorldMapState::new(),
             quests: QuestStateSet::default(),
+            campaign: CampaignState::default(),
+            travel: None,
         })
     }
 
@@ -498,6 +589,10 @@ impl GameState {
             let state = state.as_mut().unwrap();
             state.quests.set_state(&quest, entry_state);
         });
+
+        if entry_state == QuestEntryState::Complete {
+            GameState::publish_event(GameEvent::QuestCompleted { quest });
+        }
     }
 
     pub fn set_quest_entry_state(quest: String, entry: String, entry_state: QuestEntryState) {
@@ -508,6 +603,385 @@ impl GameState {
         })
     }
 
+    pub fn add_quest_entry(quest: String, entry: String) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.quests.add_entry(&quest, &entry);
+        })
+    }
+
+    pub fn advance_quest(quest: String, entry: String) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.quests.advance(&quest, &entry);
+        })
+    }
+
+    pub fn complete_quest(quest: String) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.quests.complete(&quest);
+        });
+
+        GameState::publish_event(GameEvent::QuestCompleted { quest });
+    }
+
+    pub fn travel_state() -> Option<TravelState> {
+        STATE.with(|state| {
+            let state = state.borrow();
+            let state = state.as_ref().unwrap();
+            state.travel.clone()
+        })
+    }
+
+    fn set_travel_state(travel: Option<TravelState>) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.travel = travel;
+        })
+    }
+
+    /// Travels to the specified world map `location`, taking `hours` of game
+    /// time.  The location's encounter table may interrupt the trip halfway
+    /// with a skirmish, after which the party continues on automatically.
+    pub fn travel_to(location: &WorldMapLocation, hours: u32) {
+        let area_id = match &location.linked_area {
+            None => {
+                warn!("Unable to travel to '{}' with no linked area", location.id);
+                return;
+            }
+            Some(id) => id.to_string(),
+        };
+        let pos = location.linked_area_pos;
+
+        let entry = match location.encounter_table.as_ref().and_then(|t| t.roll()) {
+            None => {
+                let time = travel_time(hours);
+                GameState::transition_to(Some(&area_id), Some(pos), Point::default(), time);
+                return;
+            }
+            Some(entry) => entry,
+        };
+
+        info!("Travel to '{}' interrupted in '{}'", location.id, entry.area);
+
+        // the skirmish area is only generated on the first visit, so
+        // repopulate it on later ones
+        if let Some(area) = GameState::get_area_state(&entry.area) {
+            area.borrow_mut().respawn_encounters();
+        }
+
+        let hours_before = hours / 2;
+        GameState::set_travel_state(Some(TravelState {
+            skirmish_area: entry.area.to_string(),
+            destination: area_id,
+            location: pos,
+            remaining_hours: hours - hours_before,
+        }));
+
+        GameState::transition_to(
+            Some(&entry.area),
+            Some(entry.location),
+            Point::default(),
+            travel_time(hours_before),
+        );
+    }
+
+    /// Continues any interrupted world map trip once combat is over and
+    /// no hostiles remain in the skirmish area
+    fn check_travel_encounter_complete() {
+        let travel = match GameState::travel_state() {
+            None => return,
+            Some(travel) => travel,
+        };
+
+        let area_id = GameState::area_state().borrow().area.area.id.to_string();
+        if area_id != travel.skirmish_area {
+            // the party left the skirmish some other way
+            GameState::set_travel_state(None);
+            return;
+        }
+
+        if GameState::is_combat_active() {
+            return;
+        }
+
+        let pc = GameState::player();
+        let mgr = GameState::turn_manager();
+        let hostiles_remain = mgr.borrow().entity_iter().any(|entity| {
+            let entity = entity.borrow();
+            entity.location.area_id == area_id
+                && !entity.actor.is_dead()
+                && entity.is_hostile(&pc.borrow())
+        });
+
+        if hostiles_remain {
+            return;
+        }
+
+        info!("Skirmish complete, continuing travel to '{}'", travel.destination);
+        GameState::set_travel_state(None);
+        GameState::transition_to(
+            Some(&travel.destination),
+            Some(travel.location),
+            Point::default(),
+            travel_time(travel.remaining_hours),
+        );
+    }
+
+    pub fn campaign_state() -> CampaignState {
+        STATE.with(|state| {
+            let state = state.borrow();
+            let state = state.as_ref().unwrap();
+            state.campaign.clone()
+        })
+    }
+
+    pub fn get_campaign_flag(flag: &str) -> Option<String> {
+        STATE.with(|state| {
+            let state = state.borrow();
+            let state = state.as_ref().unwrap();
+            state.campaign.get_flag(flag)
+        })
+    }
+
+    pub fn get_campaign_flag_int(flag: &str) -> i32 {
+        STATE.with(|state| {
+            let state = state.borrow();
+            let state = state.as_ref().unwrap();
+            state.campaign.get_flag_int(flag)
+        })
+    }
+
+    pub fn has_campaign_flag(flag: &str) -> bool {
+        STATE.with(|state| {
+            let state = state.borrow();
+            let state = state.as_ref().unwrap();
+            state.campaign.has_flag(flag)
+        })
+    }
+
+    pub fn set_campaign_flag(flag: &str, value: &str) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.set_flag(flag, value);
+        })
+    }
+
+    pub fn clear_campaign_flag(flag: &str) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.clear_flag(flag);
+        })
+    }
+
+    pub fn add_campaign_flag_int(flag: &str, amount: i32) -> i32 {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.add_flag_int(flag, amount)
+        })
+    }
+
+    /// Queues the event for delivery to all event listeners and script
+    /// observers at the next game update
+    pub fn publish_event(event: GameEvent) {
+        EVENT_QUEUE.with(|events| events.borrow_mut().push(event));
+    }
+
+    /// Adds a listener for all game events.  Listeners must not add or remove
+    /// event listeners from within their callback.
+    pub fn add_event_listener(listener: ChangeListener<GameEvent>) {
+        EVENT_LISTENERS.with(|l| l.borrow_mut().add(listener));
+    }
+
+    pub fn remove_event_listener(id: &str) {
+        EVENT_LISTENERS.with(|l| l.borrow_mut().remove(id));
+    }
+
+    pub fn add_script_observer(observer: ScriptObserver) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.add_observer(observer);
+        })
+    }
+
+    pub fn remove_script_observer(id: &str) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.remove_observer(id);
+        })
+    }
+
+    pub fn schedule_script(scheduled: ScheduledScript) {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.schedule(scheduled);
+        })
+    }
+
+    /// Removes and returns the scheduled scripts that are due by `millis`
+    pub(crate) fn take_due_scheduled_scripts(millis: usize) -> Vec<ScheduledScript> {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.campaign.take_due_scheduled(millis)
+        })
+    }
+
+    fn fire_scheduled_scripts(millis: usize) {
+        let player = GameState::player();
+        for scheduled in GameState::take_due_scheduled_scripts(millis) {
+            Script::trigger(&scheduled.script, &scheduled.func, ScriptEntity::from(&player));
+        }
+    }
+
+    fn dispatch_events() {
+        let events: Vec<GameEvent> =
+            EVENT_QUEUE.with(|events| events.borrow_mut().drain(..).collect());
+
+        for event in events {
+            EVENT_LISTENERS.with(|l| l.borrow().notify(&event));
+
+            let observers: Vec<ScriptObserver> = STATE.with(|state| {
+                let state = state.borrow();
+                let state = state.as_ref().unwrap();
+                state.campaign.observers(event.kind()).cloned().collect()
+            });
+
+            for observer in observers {
+                let arg = ScriptGameEvent::new(event.clone());
+                Script::trigger(&observer.script, &observer.func, arg);
+            }
+        }
+    }
+
+    /// Whether the current game is in ironman mode, where manual saves are
+    /// disabled
+    pub fn is_ironman() -> bool {
+        IRONMAN_SLOT.with(|s| s.borrow().is_some())
+    }
+
+    /// The file name of the single save slot of the current ironman game
+    pub fn ironman_slot() -> Option<String> {
+        IRONMAN_SLOT.with(|s| s.borrow().clone())
+    }
+
+    /// The seed chosen when this campaign was started, which varies the
+    /// rolls that should otherwise be the same each time they are made,
+    /// such as encounter loot
+    pub fn campaign_seed() -> u64 {
+        CAMPAIGN_SEED.with(|s| s.get())
+    }
+
+    /// Whether the campaign seed was entered when this campaign was started,
+    /// rather than chosen randomly
+    pub(crate) fn is_seeded_campaign() -> bool {
+        SEEDED_CAMPAIGN.with(|s| s.get())
+    }
+
+    /// The seed the area with the specified ID is generated from.  In a
+    /// seeded campaign, this is derived from the campaign seed and area ID, so
+    /// each area is the same every time the campaign is played with that seed.
+    /// Otherwise, the area is generated from a random seed
+    fn area_seed(area_id: &str) -> AreaSeed {
+        if !GameState::is_seeded_campaign() {
+            return AreaSeed::Random;
+        }
+
+        let campaign_seed = GameState::campaign_seed();
+        AreaSeed::Derived(util::mix_seed(campaign_seed ^ util::hash_str(area_id)))
+    }
+
+    pub fn difficulty() -> Difficulty {
+        DIFFICULTY.with(|d| d.get())
+    }
+
+    /// Sets the difficulty for the current game, recomputing the stats of
+    /// all entities so that the new scaling takes effect immediately
+    pub fn set_difficulty(difficulty: Difficulty) {
+        DIFFICULTY.with(|d| d.set(difficulty));
+
+        let mgr = GameState::turn_manager();
+        let entities: Vec<_> = mgr.borrow().entity_iter().collect();
+        for entity in entities {
+            entity.borrow_mut().actor.compute_stats();
+        }
+    }
+
+    /// Scales damage dealt by a hostile `attacker` for the current difficulty
+    pub(crate) fn scale_enemy_damage(attacker: &EntityState, damage: &mut [(DamageKind, u32)]) {
+        if attacker.actor.faction() != Faction::Hostile {
+            return;
+        }
+
+        let factor = GameState::difficulty().scaling().enemy_damage;
+        for (_, amount) in damage.iter_mut() {
+            *amount = (*amount as f32 * factor).round() as u32;
+        }
+    }
+
+    pub fn faction_state() -> FactionState {
+        FACTIONS.with(|f| f.borrow().clone())
+    }
+
+    pub fn faction_standing(faction_id: &str) -> i32 {
+        FACTIONS.with(|f| f.borrow().standing(faction_id))
+    }
+
+    pub fn faction_disposition(actor: &Actor) -> Faction {
+        FACTIONS.with(|f| f.borrow().disposition(actor))
+    }
+
+    pub fn faction_price_factor(faction_id: &str) -> f32 {
+        FACTIONS.with(|f| f.borrow().price_factor(faction_id))
+    }
+
+    /// Adjusts the party standing with the specified faction, updating the
+    /// attitude of all members that are currently loaded
+    pub fn adjust_faction(faction_id: &str, amount: i32) {
+        let standing = FACTIONS.with(|f| f.borrow_mut().adjust(faction_id, amount));
+        info!("Standing with faction '{}' is now {}", faction_id, standing);
+
+        let mgr = GameState::turn_manager();
+        let area_state = GameState::area_state();
+        let area_id = area_state.borrow().area.area.id.to_string();
+
+        let entities: Vec<_> = mgr.borrow().entity_iter().collect();
+        for entity in entities {
+            {
+                let mut entity = entity.borrow_mut();
+                if entity.is_party_member() {
+                    continue;
+                }
+
+                if entity.actor.actor.faction_id.as_deref() != Some(faction_id) {
+                    continue;
+                }
+
+                let disposition = GameState::faction_disposition(&entity.actor.actor);
+                if disposition == entity.actor.faction() {
+                    continue;
+                }
+                entity.actor.set_faction(disposition);
+            }
+
+            if entity.borrow().location.area_id == area_id {
+                mgr.borrow_mut()
+                    .check_ai_activation(&entity, &mut area_state.borrow_mut());
+            }
+        }
+    }
+
     pub fn set_user_zoom(mut zoom: f32) {
         STATE.with(|state| {
             let mut state = state.borrow_mut();
@@ -535,6 +1009,31 @@ impl GameState {
         GameState::select_party_members(vec![entity]);
     }
 
+    /// Adds the specified party member to the current selection, or removes
+    /// it if it is already selected
+    pub fn toggle_selected_party_member(entity: Rc<RefCell<EntityState>>) {
+        let mut selected = GameState::selected();
+        let len = selected.len();
+        selected.retain(|member| !Rc::ptr_eq(member, &entity));
+        if selected.len() == len {
+            selected.push(entity);
+        }
+
+        GameState::select_party_members(selected);
+    }
+
+    /// Adds all of the specified party members to the current selection
+    pub fn add_selected_party_members(members: Vec<Rc<RefCell<EntityState>>>) {
+        let mut selected = GameState::selected();
+        for member in members {
+            if !selected.iter().any(|cur| Rc::ptr_eq(cur, &member)) {
+                selected.push(member);
+            }
+        }
+
+        GameState::select_party_members(selected);
+    }
+
     pub fn clear_selected_party_member() {
         GameState::select_party_members(Vec::new());
     }
@@ -551,6 +1050,9 @@ impl GameState {
 
         members.retain(|e| !e.borrow().actor.is_dead());
 
+        let indices = members.iter().map(|e| e.borrow().index()).collect();
+        replay::record(ReplayCommand::Select { indices });
+
         STATE.with(|state| {
             let mut state = state.borrow_mut();
             let state = state.as_mut().unwrap();
@@ -648,6 +1150,64 @@ impl GameState {
         }
     }
 
+    /// Disables the dead party member so that they recover after combat,
+    /// and applies a random injury, if the rules specify injuries and the
+    /// campaign does not enforce permadeath.  Summons are never incapacitated
+    fn incapacitate_party_member(member: &Rc<RefCell<EntityState>>) {
+        if Module::campaign().permadeath {
+            return;
+        }
+
+        let rules = Module::rules();
+        let injuries = match rules.injuries {
+            None => return,
+            Some(ref injuries) => injuries,
+        };
+
+        if member.borrow().has_custom_flag("__is_summoned_party_member") {
+            return;
+        }
+
+        member.borrow_mut().actor.set_disabled(true);
+
+        // don't add injuries outside of combat
+        if !GameState::is_combat_active() {
+            return;
+        }
+
+        let injury = match injuries.random_injury() {
+            None => return,
+            Some(injury) => injury,
+        };
+
+        info!(
+            "'{}' incapacitated with '{}'",
+            member.borrow().actor.actor.name,
+            injury.name
+        );
+        let mut effect = Effect::new(
+            &injury.name,
+            &injuries.tag,
+            ExtInt::Infinity,
+            injury.bonuses.clone(),
+            None,
+        );
+        effect.set_icon(injury.icon.clone(), injury.name.clone());
+
+        let mgr = GameState::turn_manager();
+        mgr.borrow_mut()
+            .add_effect(effect, member, Vec::new(), Vec::new());
+
+        let area = GameState::area_state();
+        if area.borrow().area.area.id != member.borrow().location.area_id {
+            return;
+        }
+
+        let mut feedback = AreaFeedbackText::with_target(&member.borrow(), &area.borrow());
+        feedback.add_entry(tr!("feedback.injured", name = injury.name), ColorKind::Miss);
+        area.borrow_mut().add_feedback_text(feedback);
+    }
+
     fn remove_disabled_party_members() -> bool {
         let mut notify = false;
         for member in GameState::party().iter() {
@@ -661,6 +1221,8 @@ impl GameState {
                 }
             }
 
+            GameState::incapacitate_party_member(member);
+
             let script = &Module::campaign().on_party_death_script;
             Script::trigger(&script.id, &script.func, ScriptEntity::from(member));
 
@@ -747,10 +1309,7 @@ impl GameState {
             let state = state.as_mut().unwrap();
 
             entity.borrow_mut().add_to_party(show_portrait);
-            state
-                .area_state
-                .borrow_mut()
-                .compute_pc_visibility(&entity, 0, 0);
+            state.area_state.borrow_mut().compute_pc_visibility(&entity);
             state.party.push(Rc::clone(&entity));
 
             let entity = state.selected.first().map(Rc::clone);
@@ -800,12 +1359,50 @@ impl GameState {
         transition_handler::transition_to(area_id, p, offset, time);
     }
 
+    /// Moves the party to another level of the current area, such as via
+    /// stairs.  Each party member keeps their position in the shared coordinates
+    pub fn transition_to_level(area_id: &str, time: Time) {
+        transition_handler::transition_to_level(area_id, time);
+    }
+
+    /// Returns true if the party has the key to the locked prop at `index`
+    pub fn has_prop_key(index: usize) -> bool {
+        lock_handler::has_key(index)
+    }
+
+    /// Attempts to unlock the locked prop at `index` in the current area.
+    /// Returns true if the prop is now unlocked
+    pub fn try_unlock_prop(
+        entity: &Rc<RefCell<EntityState>>,
+        index: usize,
+        kind: UnlockKind,
+    ) -> bool {
+        lock_handler::try_unlock(entity, index, kind)
+    }
+
+    /// Returns the number of camp supplies held by the party, or None if
+    /// resting does not require supplies
+    pub fn camp_supplies() -> Option<u32> {
+        rest_handler::supplies_available()
+    }
+
+    /// Returns true if the party is able to camp in the current area.  The
+    /// party storage may be accessed while camping
+    pub fn is_at_camp() -> bool {
+        rest_handler::can_camp()
+    }
+
+    /// Attempts to camp and rest in the current area
+    pub fn rest() -> RestResult {
+        rest_handler::rest()
+    }
+
     pub(crate) fn preload_area(area_id: &str) -> Result<(), Error> {
         if GameState::get_area_state(area_id).is_some() {
             return Ok(());
         }
 
-        let area_state = GameState::setup_area_state(area_id)?;
+        let area_state = GameState::setup_area_state(area_id, GameState::area_seed(area_id))?;
 
         STATE.with(|state| {
             let mut state = state.borrow_mut();
@@ -816,6 +1413,48 @@ impl GameState {
         Ok(())
     }
 
+    /// Generates the current area again from `seed`, or from a random seed
+    /// if none is specified, and moves the party into the new area at their
+    /// current positions.  All other creatures and props of the old area are
+    /// discarded.  This is intended for tuning generator parameters during
+    /// development.  Returns the seed of the new area
+    pub fn regenerate_area(seed: Option<u128>) -> Result<u128, Error> {
+        if GameState::is_combat_active() {
+            return invalid_data_error("Unable to regenerate the area during combat");
+        }
+
+        let old_area = GameState::area_state();
+        let area_id = old_area.borrow().area.area.id.to_string();
+        if old_area.borrow().area.area.generator.is_none() {
+            return invalid_data_error(&format!("Area '{area_id}' is not generated"));
+        }
+
+        info!("Regenerating area '{}' with seed {:?}", area_id, seed);
+        let area_state = GameState::setup_area_state(&area_id, AreaSeed::from(seed))?;
+        let area_gen_seed = area_state.borrow().area_gen_seed;
+
+        // remove the old creatures while the old area is still registered
+        let mgr = GameState::turn_manager();
+        let to_remove: Vec<usize> = old_area
+            .borrow()
+            .entity_iter()
+            .filter(|index| !mgr.borrow().entity(**index).borrow().is_party_member())
+            .copied()
+            .collect();
+        for index in to_remove {
+            mgr.borrow_mut().remove_entity(index);
+        }
+
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.areas.insert(area_id.to_string(), area_state);
+        });
+
+        transition_handler::transition_to_level(&area_id, Time::default());
+        Ok(area_gen_seed)
+    }
+
     #[must_use]
     pub(crate) fn set_current_area(area: &Rc<RefCell<AreaState>>) -> bool {
         STATE.with(|state| {
@@ -835,7 +1474,10 @@ impl GameState {
         })
     }
 
-    fn setup_area_state(area_id: &str) -> Result<Rc<RefCell<AreaState>>, Error> {
+    fn setup_area_state(
+        area_id: &str,
+        seed: AreaSeed,
+    ) -> Result<Rc<RefCell<AreaState>>, Error> {
         debug!("Setting up area state from {}", &area_id);
 
         let area = Module::area(area_id);
@@ -847,7 +1489,7 @@ impl GameState {
             }
         };
 
-        let state = AreaState::new(area, None)?;
+        let state = AreaState::new(area, seed)?;
         let area_state = Rc::new(RefCell::new(state));
         area_state.borrow_mut().populate();
 
@@ -933,6 +1575,8 @@ impl GameState {
 
     #[must_use]
     pub fn update(millis: u32) -> Option<UICallback> {
+        let millis = replay::on_update(millis);
+
         let ui_cb = STATE.with(|s| {
             let mut state = s.borrow_mut();
             let state = state.as_mut().unwrap();
@@ -950,6 +1594,11 @@ impl GameState {
             .into_iter()
             .for_each(|cb| cb.on_anim_complete());
 
+        GameState::fire_queued_orders();
+
+        GameState::dispatch_events();
+        AchievementState::update(millis);
+
         let mgr = GameState::turn_manager();
         let update_cbs = mgr.borrow_mut().update(millis);
         script_callback::fire_cbs(update_cbs);
@@ -960,10 +1609,23 @@ impl GameState {
         let cbs = mgr.borrow_mut().update_entity_move_callbacks();
         script_callback::fire_on_moved(cbs);
 
+        let round = mgr.borrow().current_round();
+        script_cache::update_coroutines(round);
+
+        let millis_now = mgr.borrow().total_elapsed_millis();
+        GameState::fire_scheduled_scripts(millis_now);
+
+        GameState::update_patrols(millis);
+
+        GameState::check_travel_encounter_complete();
+
         {
+            let time = mgr.borrow().current_time();
+            let total_hours = time.day * Module::rules().hours_per_day + time.hour;
             let area_state = GameState::area_state();
             let mut area_state = area_state.borrow_mut();
             area_state.update();
+            area_state.check_time_of_day_triggers(total_hours);
         }
 
         if GameState::check_clear_anims() {
@@ -1018,6 +1680,27 @@ impl GameState {
         ui_cb
     }
 
+    fn update_patrols(millis: u32) {
+        let mgr = GameState::turn_manager();
+        if mgr.borrow().is_combat_active() {
+            return;
+        }
+
+        let area_id = GameState::area_state().borrow().area.area.id.to_string();
+        let patrollers: Vec<_> = mgr
+            .borrow()
+            .entity_iter()
+            .filter(|e| {
+                let e = e.borrow();
+                e.patrol().is_some() && e.location.is_in_area_id(&area_id)
+            })
+            .collect();
+
+        for entity in patrollers {
+            PatrolState::update(&entity, millis);
+        }
+    }
+
     pub fn draw_above_entities(
         renderer: &mut dyn GraphicsRenderer,
         offset: Offset,
@@ -1054,6 +1737,50 @@ impl GameState {
         ANIMATIONS.with(|a| a.borrow().has_blocking_anims(entity))
     }
 
+    /// Queues an order to be fired once the entity has no remaining blocking
+    /// animations.  Orders for the same entity fire in the order they were queued
+    pub fn queue_order(entity: &Rc<RefCell<EntityState>>, order: QueuedOrder) {
+        let index = entity.borrow().index();
+        QUEUED_ORDERS.with(|q| q.borrow_mut().entry(index).or_default().push(order));
+    }
+
+    pub fn queued_orders(entity: &Rc<RefCell<EntityState>>) -> Vec<QueuedOrder> {
+        let index = entity.borrow().index();
+        QUEUED_ORDERS.with(|q| q.borrow().get(&index).cloned().unwrap_or_default())
+    }
+
+    pub fn clear_queued_orders(entity: &Rc<RefCell<EntityState>>) {
+        let index = entity.borrow().index();
+        QUEUED_ORDERS.with(|q| q.borrow_mut().remove(&index));
+    }
+
+    fn fire_queued_orders() {
+        let mgr = GameState::turn_manager();
+        let ready: Vec<QueuedOrder> = QUEUED_ORDERS.with(|q| {
+            let mut q = q.borrow_mut();
+            let mut ready = Vec::new();
+            q.retain(|index, orders| {
+                let entity = match mgr.borrow().entity_checked(*index) {
+                    None => return false,
+                    Some(entity) => entity,
+                };
+
+                if entity.borrow().actor.is_dead() {
+                    return false;
+                }
+
+                if !GameState::has_blocking_animations(&entity) {
+                    ready.push(orders.remove(0));
+                }
+
+                !orders.is_empty()
+            });
+            ready
+        });
+
+        ready.into_iter().for_each(|order| order.cb.on_anim_complete());
+    }
+
     pub fn remove_blocking_animations(entity: &Rc<RefCell<EntityState>>) {
         ANIMATIONS.with(|a| a.borrow_mut().clear_blocking_anims(entity));
     }
@@ -1062,6 +1789,13 @@ impl GameState {
         ANIMATIONS.with(|a| a.borrow_mut().clear_all_blocking_anims());
     }
 
+    /// Removes the particle generators placed at fixed points in the area
+    /// with `area_id`, so that they do not follow the party out of it
+    pub(crate) fn remove_area_animations(area_id: &str) {
+        ANIMS_TO_ADD.with(|a| a.borrow_mut().retain(|anim| !anim.is_in_area(area_id)));
+        ANIMATIONS.with(|a| a.borrow_mut().remove_area_anims(area_id));
+    }
+
     pub fn add_animation(anim: Anim) {
         ANIMS_TO_ADD.with(|a| {
             let mut anims = a.borrow_mut();
@@ -1081,11 +1815,15 @@ impl GameState {
         mgr.is_combat_active()
     }
 
-    /// Returns true if the PC has the current turn, false otherwise
+    /// Returns true if the PC has the current turn, false otherwise.  Party
+    /// members in automatic combat do not count, as the AI takes their turns.
     pub fn is_pc_current() -> bool {
         let mgr = GameState::turn_manager();
-        if let Some(entity) = mgr.borrow().current() {
-            return entity.borrow().is_party_member();
+        let mgr = mgr.borrow();
+        if let Some(entity) = mgr.current() {
+            let entity = entity.borrow();
+            let auto = entity.is_auto_combat() && mgr.is_combat_active();
+            return entity.is_party_member() && !auto;
         }
 
         false
@@ -1216,7 +1954,62 @@ impl GameState {
     }
 
     pub fn add_party_coins(amount: i32) {
-        STATE.with(|s| s.borrow_mut().as_mut().unwrap().party_coins += amount);
+        let total = STATE.with(|s| {
+            let mut state = s.borrow_mut();
+            let state = state.as_mut().unwrap();
+            state.party_coins += amount;
+            state.party_coins
+        });
+
+        GameState::publish_event(GameEvent::CoinsChanged { total });
+    }
+
+    /// Awards the specified amount of XP to the party, shared between party
+    /// members according to the rules `xp_distribution`.  The `source` is the
+    /// entity the XP was earned from, used for proximity based sharing.
+    pub fn award_xp(amount: u32, source: &Rc<RefCell<EntityState>>) {
+        let rules = Module::rules();
+        let party = GameState::party();
+
+        let recipients: Vec<_> = match rules.xp_distribution {
+            XpDistribution::Full | XpDistribution::Split => party,
+            XpDistribution::Proximity => {
+                let source = source.borrow();
+                party
+                    .into_iter()
+                    .filter(|member| {
+                        let member = member.borrow();
+                        member.location.area_id == source.location.area_id
+                            && dist(&*member, &*source) <= rules.xp_share_distance
+                    })
+                    .collect()
+            }
+        };
+
+        if recipients.is_empty() {
+            return;
+        }
+
+        let share = match rules.xp_distribution {
+            XpDistribution::Split => amount / recipients.len() as u32,
+            XpDistribution::Full | XpDistribution::Proximity => amount,
+        };
+        let share = (share as f32 * GameState::difficulty().scaling().xp) as u32;
+
+        let gained = (share as f32 * rules.experience_factor) as u32;
+        let area = GameState::area_state();
+        for member in recipients {
+            member.borrow_mut().add_xp(share);
+
+            if gained == 0 || area.borrow().area.area.id != member.borrow().location.area_id {
+                continue;
+            }
+
+            let mut feedback = AreaFeedbackText::with_target(&member.borrow(), &area.borrow());
+            feedback.set_channel(FeedbackChannel::Xp);
+            feedback.add_entry(tr!("feedback.xp", amount = gained), ColorKind::Info);
+            area.borrow_mut().add_feedback_text(feedback);
+        }
     }
 
     pub fn party_formation() -> Rc<RefCell<Formation>> {
@@ -1227,4 +2020,33 @@ impl GameState {
             Rc::clone(&state.party_formation)
         })
     }
+
+    /// Replaces the current party formation with the preset `layout`.  The
+    /// formation is shared, so existing references see the new positions.
+    pub fn set_formation(layout: FormationLayout) {
+        let formation = GameState::party_formation();
+        *formation.borrow_mut() = Formation::new(layout);
+    }
+}
+
+fn travel_time(hours: u32) -> Time {
+    let mut time = Time::from_hours(hours);
+    Module::rules().canonicalize_time(&mut time);
+    time
+}
+
+fn load_item_list(entries: Vec<ItemListEntrySaveState>) -> Result<ItemList, Error> {
+    let mut list = ItemList::default();
+    for item_save in entries {
+        let item = &item_save.item;
+        let item = match Module::create_get_item(&item.id, &item.adjectives) {
+            None => invalid_data_error(&format!("No item with ID '{}'", item_save.item.id)),
+            Some(item) => Ok(item),
+        }?;
+
+        let item = ItemState::load(item, &item_save.item);
+
+        list.add_stack(item_save.quantity, item);
+    }
+    Ok(list)
 }
@@ -19,13 +19,13 @@ use std::io::{Error, ErrorKind};
 use std::rc::Rc;
 
 use sulis_core::resource::ResourceSet;
-use sulis_core::util::{self, unable_to_create_error, ReproducibleRandom};
+use sulis_core::util::{self, unable_to_create_error, Point, ReproducibleRandom};
 use sulis_module::area::{
     create_prop, Area, EncounterData, LayerSet, PathFinderGrid, PropData, Tile, Transition,
     TransitionBuilder,
 };
-use sulis_module::generator::AreaGenerator;
-use sulis_module::Module;
+use sulis_module::generator::{AreaGenerator, ValidationParams};
+use sulis_module::{Module, ObjectSize};
 
 pub struct GeneratedArea {
     pub area: Rc<Area>,
@@ -95,6 +95,7 @@ impl GeneratedArea {
                 location: builder.location,
                 size: builder.size,
                 triggers: Vec::new(),
+                flee_region: builder.flee_region,
             });
         }
 
@@ -169,6 +170,113 @@ impl GeneratedArea {
     pub fn path_grid(&self, size_id: &str) -> &PathFinderGrid {
         &self.path_grids[size_id]
     }
+
+    /// Checks the quality of this generated area, for creatures of the
+    /// specified `size`.  Every transition must be reachable from every
+    /// other, every encounter must have a reachable point, and the area
+    /// must be sufficiently open.  Returns a description of each problem
+    /// found, or an empty list if the area passed
+    pub fn validate(&self, size: &ObjectSize, params: &ValidationParams) -> Vec<String> {
+        let mut problems = Vec::new();
+        let grid = self.path_grid(&size.id);
+
+        let total = self.layer_set.passable.len();
+        let open = self.layer_set.passable.iter().filter(|p| **p).count();
+        let open_fraction = open as f32 / total.max(1) as f32;
+        if open_fraction < params.min_open_fraction {
+            problems.push(format!(
+                "Open fraction {:.2} is below the minimum {:.2}",
+                open_fraction, params.min_open_fraction
+            ));
+        }
+
+        let start = self.transitions.iter().find_map(|t| {
+            self.passable_point(grid, t.from, t.size.width, t.size.height, None)
+        });
+        let reached = match start {
+            // an area without transitions has nowhere to check reachability from
+            None if self.transitions.is_empty() => return problems,
+            // no transition has a passable point, so each is unreachable
+            None => vec![false; grid.passable.len()],
+            Some(start) => flood_fill(grid, start),
+        };
+
+        for (index, t) in self.transitions.iter().enumerate() {
+            let (w, h) = (t.size.width, t.size.height);
+            if self.passable_point(grid, t.from, w, h, Some(&reached)).is_none() {
+                problems.push(format!("Transition {} at {:?} is not reachable", index, t.from));
+            }
+        }
+
+        for enc in self.encounters.iter() {
+            let (w, h) = (enc.size.width, enc.size.height);
+            if self.passable_point(grid, enc.location, w, h, Some(&reached)).is_none() {
+                problems.push(format!(
+                    "Encounter '{}' at {:?} has no reachable points",
+                    enc.encounter.id, enc.location
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Finds a passable point in the rect at `from` with the `width` and
+    /// `height`, which must also be in `reached` if specified
+    fn passable_point(
+        &self,
+        grid: &PathFinderGrid,
+        from: Point,
+        width: i32,
+        height: i32,
+        reached: Option<&[bool]>,
+    ) -> Option<Point> {
+        let min_x = from.x.max(0);
+        let min_y = from.y.max(0);
+        let max_x = (from.x + width).min(self.width);
+        let max_y = (from.y + height).min(self.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let index = (x + y * self.width) as usize;
+                if !grid.passable[index] {
+                    continue;
+                }
+
+                if reached.is_none_or(|reached| reached[index]) {
+                    return Some(Point::new(x, y));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the points of the `grid` reachable from the `start`
+fn flood_fill(grid: &PathFinderGrid, start: Point) -> Vec<bool> {
+    let mut reached = vec![false; grid.passable.len()];
+    reached[(start.x + start.y * grid.width) as usize] = true;
+    let mut frontier = vec![start];
+
+    while let Some(p) = frontier.pop() {
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            let (x, y) = (p.x + dx, p.y + dy);
+            if x < 0 || y < 0 || x >= grid.width || y >= grid.height {
+                continue;
+            }
+
+            let index = (x + y * grid.width) as usize;
+            if reached[index] || !grid.passable[index] {
+                continue;
+            }
+
+            reached[index] = true;
+            frontier.push(Point::new(x, y));
+        }
+    }
+
+    reached
 }
 
 pub struct PregenOutput {
@@ -221,4 +329,8 @@ impl PregenOutput {
     pub fn seed(&self) -> u128 {
         self.rand.seed()
     }
+
+    pub fn generator(&self) -> &Rc<AreaGenerator> {
+        &self.generator
+    }
 }
@@ -19,17 +19,19 @@ use std::io::Error;
 use std::rc::Rc;
 use std::slice::Iter;
 
-use sulis_core::image::Image;
 use sulis_core::util::invalid_data_error;
 use sulis_module::{
-    bonus::AttackKindBuilder, Actor, ImageLayer, ItemKind, ItemSaveState, ItemState, Module,
-    QuickSlot, Slot, StatList, WeaponStyle,
+    bonus::AttackKindBuilder, Actor, ImageLayer, ItemKind, ItemSaveState, ItemState,
+    LayerInsert, Module, QuickSlot, Race, Slot, StatList, WeaponStyle,
 };
 
 #[derive(Clone)]
 pub struct Inventory {
     pub equipped: HashMap<Slot, ItemState>,
     pub quick: HashMap<QuickSlot, ItemState>,
+
+    /// Which of the two weapon sets is currently held, 0 or 1
+    pub(crate) weapon_set: usize,
 }
 
 impl Inventory {
@@ -37,6 +39,7 @@ impl Inventory {
         Inventory {
             equipped: HashMap::new(),
             quick: HashMap::new(),
+            weapon_set: 0,
         }
     }
 
@@ -56,10 +59,9 @@ impl Inventory {
                 Some(item) => item,
             };
 
-            let variant = item.variant;
             let item_state = match Module::create_get_item(&item.id, &item.adjectives) {
                 None => invalid_data_error(&format!("No item with ID '{}'", item.id)),
-                Some(item) => Ok(ItemState::new(item, variant)),
+                Some(created) => Ok(ItemState::load(created, item)),
             }?;
 
             {
@@ -98,10 +100,9 @@ impl Inventory {
                 Some(item) => item,
             };
 
-            let variant = item.variant;
             let item_state = match Module::create_get_item(&item.id, &item.adjectives) {
                 None => invalid_data_error(&format!("No item with ID '{}'", item.id)),
-                Some(item) => Ok(ItemState::new(item, variant)),
+                Some(created) => Ok(ItemState::load(created, item)),
             }?;
 
             self.quick.insert(quick_slot, item_state);
@@ -177,7 +178,22 @@ impl Inventory {
         }
     }
 
+    /// Returns the total weight of all items carried by the owner of this
+    /// inventory, including equipped items, the alternate weapon set, and
+    /// quick slot items
+    pub fn weight(&self) -> i32 {
+        let equipped: i32 = self.equipped.values().map(|item| item.item.weight).sum();
+        let quick: i32 = self.quick.values().map(|item| item.item.weight).sum();
+        equipped + quick
+    }
+
+    pub fn weapon_set(&self) -> usize {
+        self.weapon_set
+    }
+
     pub fn swap_weapon_set(&mut self) {
+        self.weapon_set = 1 - self.weapon_set;
+
         let cur_main = self.equipped.remove(&Slot::HeldMain);
         let cur_off = self.equipped.remove(&Slot::HeldOff);
 
@@ -406,25 +422,27 @@ impl Inventory {
         result
     }
 
-    pub fn get_image_layers(&self) -> HashMap<ImageLayer, Rc<dyn Image>> {
-        let mut layers = HashMap::new();
+    /// Returns the images drawn by each equipped item, for an actor of the
+    /// specified `race`
+    pub fn get_image_layers(&self, race: &Race) -> HashMap<ImageLayer, Vec<LayerInsert>> {
+        let mut layers: HashMap<ImageLayer, Vec<LayerInsert>> = HashMap::new();
+
+        for slot in Slot::iter() {
+            let item_state = match self.equipped.get(slot) {
+                None => continue,
+                Some(item_state) => item_state,
+            };
 
-        for (slot, item_state) in self.equipped.iter() {
             let equippable = match item_state.item.equippable {
                 None => unreachable!(),
                 Some(ref equippable) => equippable,
             };
 
-            let iter = if equippable.slot == *slot {
-                // the item is in it's primary slot
-                item_state.image_iter()
-            } else {
-                // the item must be in it's secondary slot
-                item_state.alt_image_iter()
-            };
+            // the item is in its alternate slot if not in its primary slot
+            let alternate = equippable.slot != *slot;
 
-            for (layer, image) in iter {
-                layers.insert(*layer, Rc::clone(image));
+            for (layer, insert) in item_state.layer_inserts(alternate, race) {
+                layers.entry(layer).or_default().push(insert);
             }
         }
 
@@ -52,14 +52,13 @@ impl ItemList {
         self.items.get(index)
     }
 
+    /// Returns the total quantity of the specified item, across all stacks
     pub fn get_quantity(&self, item: &ItemState) -> u32 {
-        for &(qty, ref item_in_list) in self.items.iter() {
-            if item == item_in_list {
-                return qty;
-            }
-        }
-
-        0
+        self.items
+            .iter()
+            .filter(|(_, item_in_list)| item == item_in_list)
+            .map(|(qty, _)| qty)
+            .sum()
     }
 
     pub fn find_index(&self, state: &ItemState) -> Option<usize> {
@@ -73,23 +72,75 @@ impl ItemList {
     }
 
     /// Adds the specified count of this item, and returns the index
-    /// the item was placed at
-    pub fn add_quantity(&mut self, qty: u32, item_state: ItemState) -> usize {
-        match self.find_index(&item_state) {
-            Some(index) => {
-                self.items[index].0 += qty;
-                index
+    /// the item was placed at, or None if `qty` is zero.  The items are
+    /// merged into existing stacks of the same item, with new stacks created
+    /// as needed so that no stack exceeds the item's maximum stack size
+    pub fn add_quantity(&mut self, mut qty: u32, item_state: ItemState) -> Option<usize> {
+        let max = item_state.item.max_stack();
+
+        let mut last_index = None;
+        for (index, (stack_qty, item)) in self.items.iter_mut().enumerate() {
+            if qty == 0 {
+                break;
             }
-            None => {
-                self.items.push((qty, item_state));
-                self.items.len() - 1
+
+            if *item != item_state || *stack_qty >= max {
+                continue;
             }
+
+            let amount = qty.min(max - *stack_qty);
+            *stack_qty += amount;
+            qty -= amount;
+            last_index = Some(index);
+        }
+
+        while qty > 0 {
+            let amount = qty.min(max);
+            self.items.push((amount, item_state.clone()));
+            qty -= amount;
+            last_index = Some(self.items.len() - 1);
+        }
+
+        last_index
+    }
+
+    /// Adds the specified stack as a new entry at the end of this list,
+    /// without merging it into any existing stacks.  Used when loading,
+    /// so that stacks which were split are preserved
+    pub fn add_stack(&mut self, qty: u32, item_state: ItemState) -> usize {
+        self.items.push((qty, item_state));
+        self.items.len() - 1
+    }
+
+    /// Returns whether the stack at the specified index may be split.  Only
+    /// items with a maximum stack size may be split, as other items are
+    /// always merged back into a single stack when more are added
+    pub fn can_split(&self, index: usize) -> bool {
+        match self.items.get(index) {
+            None => false,
+            Some((qty, item_state)) => *qty > 1 && item_state.item.max_stack.is_some(),
         }
     }
 
+    /// Splits the stack at the specified index in half, placing the new
+    /// stack immediately after it.  Returns the index of the new stack, or
+    /// None if the stack could not be split
+    pub fn split(&mut self, index: usize) -> Option<usize> {
+        if !self.can_split(index) {
+            return None;
+        }
+
+        let (qty, item_state) = self.items[index].clone();
+
+        let split_qty = qty / 2;
+        self.items[index].0 -= split_qty;
+        self.items.insert(index + 1, (split_qty, item_state));
+        Some(index + 1)
+    }
+
     /// Adds one count of the specified item, and returns the index that
     /// the item was placed at
-    pub fn add(&mut self, item_state: ItemState) -> usize {
+    pub fn add(&mut self, item_state: ItemState) -> Option<usize> {
         self.add_quantity(1, item_state)
     }
 
@@ -102,6 +153,21 @@ impl ItemList {
         Some(self.items.remove(index))
     }
 
+    /// Removes up to the specified quantity of items at the specified index.
+    /// Returns the quantity actually removed along with the item
+    pub fn remove_quantity(&mut self, index: usize, qty: u32) -> Option<(u32, ItemState)> {
+        if index >= self.items.len() || qty == 0 {
+            return None;
+        }
+
+        if self.items[index].0 <= qty {
+            return self.remove_all_at(index);
+        }
+
+        self.items[index].0 -= qty;
+        Some((qty, self.items[index].1.clone()))
+    }
+
     /// Remove an item from the list at the specified index and returns it.
     /// Only removes one count, so the item may still exist if there is more
     /// than one
@@ -22,11 +22,14 @@ extern crate log;
 extern crate serde_derive;
 
 mod ai;
-pub use self::ai::AI;
+pub use self::ai::{ThreatTable, AI};
 
 pub mod ability_state;
 pub use self::ability_state::AbilityState;
 
+mod achievement_state;
+pub use self::achievement_state::{AchievementProgress, AchievementState};
+
 mod actor_state;
 pub use self::actor_state::ActorState;
 
@@ -38,6 +41,15 @@ pub use self::area_feedback_text::AreaFeedbackText;
 pub mod area_state;
 pub use self::area_state::AreaState;
 
+#[cfg(feature = "bench")]
+pub mod bench;
+
+mod camera;
+pub use self::camera::{Camera, ScrollTarget};
+
+mod campaign_state;
+pub use self::campaign_state::{CampaignState, ScheduledScript};
+
 mod change_listener;
 pub use self::change_listener::ChangeListener;
 pub use self::change_listener::ChangeListenerList;
@@ -61,8 +73,14 @@ mod entity_texture_cache;
 pub use self::entity_texture_cache::EntityTextureCache;
 pub use self::entity_texture_cache::EntityTextureSlot;
 
+mod faction_state;
+pub use self::faction_state::FactionState;
+
 mod formation;
-pub use self::formation::Formation;
+pub use self::formation::{Formation, FormationLayout};
+
+pub mod game_event;
+pub use self::game_event::{GameEvent, GameEventKind};
 
 mod game_state;
 pub use self::game_state::GameState;
@@ -79,15 +97,25 @@ pub use self::item_list::ItemList;
 mod location;
 pub use self::location::Location;
 
+mod lock_handler;
+pub use self::lock_handler::UnlockKind;
+
 mod los_calculator;
+pub use self::los_calculator::calculate_cover;
 pub use self::los_calculator::calculate_los;
 pub use self::los_calculator::has_visibility;
+pub use self::los_calculator::VisBounds;
 
 mod merchant_state;
 pub use self::merchant_state::MerchantState;
 
+pub mod module_validator;
+
 mod path_finder;
 
+mod patrol_state;
+pub use self::patrol_state::PatrolState;
+
 mod party_bump_handler;
 
 mod party_stash;
@@ -106,10 +134,16 @@ pub use self::quest_state::QuestStateSet;
 mod range_indicator;
 pub use self::range_indicator::{RangeIndicator, RangeIndicatorHandler, RangeIndicatorImageSet};
 
+pub mod replay;
+pub use self::replay::{Replay, ReplayCommand};
+
 pub mod save_file;
 pub use self::save_file::SaveFile;
 pub use self::save_file::SaveFileMetaData;
 
+mod rest_handler;
+pub use self::rest_handler::RestResult;
+
 mod save_state;
 pub use self::save_state::SaveState;
 
@@ -125,26 +159,55 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use sulis_module::{Actor, Module, OnTrigger};
+use sulis_core::util::Point;
+use sulis_module::{Actor, Module, ObjectSize, OnTrigger};
 
 #[derive(Debug)]
 pub enum NextGameStep {
     Exit,
     NewCampaign {
         pc_actor: Rc<Actor>,
+        ironman: bool,
+        seed: Option<u128>,
     },
     LoadCampaign {
         save_state: Box<SaveState>,
     },
+    LoadModsAndCampaign {
+        mods: Vec<String>,
+        save_file: Box<SaveFileMetaData>,
+    },
     LoadModuleAndNewCampaign {
         pc_actor: Rc<Actor>,
         party_actors: Vec<Rc<Actor>>,
         flags: HashMap<String, String>,
         module_dir: String,
+        ironman: bool,
+    },
+    PlayReplay {
+        replay: Box<Replay>,
     },
     MainMenu,
     MainMenuReloadResources,
     RecreateIO,
+    RecreateWindow,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OrderKind {
+    Move,
+    Attack,
+}
+
+/// A move or attack order issued while the entity was still busy with
+/// a blocking animation.  Queued orders are fired in turn once the entity's
+/// blocking animations complete.
+#[derive(Clone)]
+pub struct QueuedOrder {
+    pub kind: OrderKind,
+    pub location: Point,
+    pub size: Rc<ObjectSize>,
+    pub cb: Rc<dyn ScriptCallback>,
 }
 
 pub struct UICallback {
@@ -236,3 +299,15 @@ struct WorldMapLocationState {
     pub visible: bool,
     pub enabled: bool,
 }
+
+/// A world map trip that has been interrupted by a random encounter.  Once
+/// the skirmish area is clear of hostiles, the party continues on to the
+/// destination.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TravelState {
+    skirmish_area: String,
+    destination: String,
+    location: Point,
+    remaining_hours: u32,
+}
@@ -0,0 +1,141 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::tr;
+use sulis_module::{Attribute, Module};
+
+use crate::area_feedback_text::ColorKind;
+use crate::{AreaFeedbackText, EntityState, GameState};
+
+/// The ways in which a locked door or container may be opened
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnlockKind {
+    Key,
+    Pick,
+    Bash,
+}
+
+/// Returns true if the party stash holds the key for the lock on the prop
+/// at the specified index
+pub fn has_key(index: usize) -> bool {
+    let area_state = GameState::area_state();
+    let area_state = area_state.borrow();
+    if !area_state.props().index_valid(index) {
+        return false;
+    }
+
+    let key = match area_state.props().get(index).lock() {
+        None => return false,
+        Some(lock) => match lock.key {
+            None => return false,
+            Some(ref key) => key.to_string(),
+        },
+    };
+
+    let stash = GameState::party_stash();
+    let stash = stash.borrow();
+    stash.has_item(&key)
+}
+
+/// Attempts to unlock the prop at the specified index.  Picking or bashing
+/// costs an attack's worth of AP in combat.  Returns true if the prop was
+/// unlocked
+pub fn try_unlock(entity: &Rc<RefCell<EntityState>>, index: usize, kind: UnlockKind) -> bool {
+    let area_state = GameState::area_state();
+    let lock = {
+        let area_state = area_state.borrow();
+        if !area_state.props().index_valid(index) {
+            return false;
+        }
+
+        match area_state.props().get(index).lock() {
+            None => return true,
+            Some(lock) => lock.clone(),
+        }
+    };
+
+    let rules = Module::rules();
+    let success = match kind {
+        UnlockKind::Key => {
+            if !has_key(index) {
+                return false;
+            }
+
+            if lock.consume_key {
+                if let Some(ref key) = lock.key {
+                    remove_key(key);
+                }
+            }
+            true
+        }
+        UnlockKind::Pick | UnlockKind::Bash => {
+            if GameState::is_combat_active() {
+                if !entity.borrow().actor.has_ap_to_attack() {
+                    return false;
+                }
+
+                let ap = entity.borrow().actor.stats.attack_cost as u32;
+                entity.borrow_mut().actor.remove_ap(ap);
+            }
+
+            let entity = entity.borrow();
+            let stats = &entity.actor.stats;
+            if kind == UnlockKind::Pick {
+                let dex = stats.attributes.get(Attribute::Dexterity);
+                let levels = match rules.locks.as_ref() {
+                    None => 0,
+                    Some(locks) => match Module::class(&locks.pick_class) {
+                        None => 0,
+                        Some(class) => entity.actor.actor.levels(&class),
+                    },
+                };
+                rules.pick_lock_roll(lock.difficulty, dex, levels)
+            } else {
+                let strength = stats.attributes.get(Attribute::Strength);
+                rules.bash_lock_roll(lock.difficulty, strength)
+            }
+        }
+    };
+
+    let mut area_state = area_state.borrow_mut();
+    let mut text = AreaFeedbackText::with_target(&entity.borrow(), &area_state);
+    if success {
+        area_state.props_mut().get_mut(index).unlock();
+        area_state.mark_save_dirty();
+        text.add_entry(tr!("feedback.unlocked"), ColorKind::Info);
+    } else {
+        text.add_entry(tr!("feedback.lock_failed"), ColorKind::Miss);
+    }
+    area_state.add_feedback_text(text);
+
+    success
+}
+
+fn remove_key(key: &str) {
+    let stash = GameState::party_stash();
+    let mut stash = stash.borrow_mut();
+    let index = stash
+        .items()
+        .iter()
+        .position(|(_, item)| item.item.id == key);
+
+    if let Some(index) = index {
+        let _ = stash.remove_item(index);
+    }
+}
@@ -18,60 +18,128 @@ use std::cmp;
 use std::collections::HashSet;
 
 use crate::{EntityState, GeneratedArea};
+use sulis_module::Cover;
 
+/// In areas with lighting, tiles within this distance of a viewer are
+/// visible even when they are not lit
+const DARK_VIS_DIST_SQUARED: i32 = 3 * 3;
+
+/// The rectangular region of an area covered by a party member's line of
+/// sight calculation, along with the point the calculation was made from.
+/// The `max_x` and `max_y` bounds are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisBounds {
+    pub center_x: i32,
+    pub center_y: i32,
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl VisBounds {
+    /// The bounds for an entity centered at `x`, `y` in the specified area
+    pub fn new(area: &GeneratedArea, x: i32, y: i32) -> VisBounds {
+        let max_dist = area.area.vis_dist;
+        VisBounds {
+            center_x: x,
+            center_y: y,
+            min_x: cmp::max(0, x - max_dist),
+            min_y: cmp::max(0, y - max_dist),
+            max_x: cmp::min(area.width, x + max_dist),
+            max_y: cmp::min(area.height, y + max_dist),
+        }
+    }
+
+    /// The smallest bounds containing both this and `other`.  The center
+    /// of the result is the center of this bounds
+    pub fn union(&self, other: &VisBounds) -> VisBounds {
+        VisBounds {
+            center_x: self.center_x,
+            center_y: self.center_y,
+            min_x: cmp::min(self.min_x, other.min_x),
+            min_y: cmp::min(self.min_y, other.min_y),
+            max_x: cmp::max(self.max_x, other.max_x),
+            max_y: cmp::max(self.max_y, other.max_y),
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.min_x && x < self.max_x && y >= self.min_y && y < self.max_y
+    }
+
+    pub fn intersects(&self, other: &VisBounds) -> bool {
+        self.min_x < other.max_x
+            && other.min_x < self.max_x
+            && self.min_y < other.max_y
+            && other.min_y < self.max_y
+    }
+}
+
+/// Computes the line of sight for the specified party member, updating its
+/// vis and bounds, and marking any visible squares as explored.  Squares
+/// covered by the previous calculation but not the current one are cleared.
+/// If a `lit_grid` is specified, only lit squares are visible beyond a short
+/// distance.  Returns the set of props that are at least partially visible.
 #[must_use]
 pub fn calculate_los(
     exp: &mut [bool],
     area: &GeneratedArea,
     prop_vis_grid: &[bool],
     prop_grid: &[Vec<usize>],
+    lit_grid: Option<&[bool]>,
     entity: &mut EntityState,
-    delta_x: i32,
-    delta_y: i32,
 ) -> HashSet<usize> {
-    let max_dist = area.area.vis_dist;
     let entity_x = entity.location.x + entity.size.width / 2;
     let entity_y = entity.location.y + entity.size.height / 2;
 
-    let los = entity.pc_vis_mut();
-
-    let min_x = cmp::max(
-        0,
-        entity_x - max_dist + if delta_x < 0 { delta_x } else { 0 },
-    );
-    let max_x = cmp::min(
-        area.width,
-        entity_x + max_dist + if delta_x > 0 { delta_x } else { 0 },
-    );
-    let min_y = cmp::max(
-        0,
-        entity_y - max_dist + if delta_y < 0 { delta_y } else { 0 },
-    );
-    let max_y = cmp::min(
-        area.height,
-        entity_y + max_dist + if delta_y > 0 { delta_y } else { 0 },
-    );
+    let bounds = VisBounds::new(area, entity_x, entity_y);
+    let old_bounds = entity.pc_vis_bounds();
 
     let src_elev = area.layer_set.elevation(entity_x, entity_y);
 
     let mut props_vis: HashSet<usize> = HashSet::new();
 
-    for y in min_y..max_y {
-        for x in min_x..max_x {
-            let index = (x + y * area.width) as usize;
-            if check_vis(area, prop_vis_grid, entity_x, entity_y, x, y, src_elev) {
-                los[index] = true;
-                exp[index] = true;
+    {
+        let los = entity.pc_vis_mut();
 
-                for prop in &prop_grid[index] {
-                    props_vis.insert(*prop);
+        if let Some(old) = old_bounds {
+            for y in old.min_y..old.max_y {
+                for x in old.min_x..old.max_x {
+                    if !bounds.contains(x, y) {
+                        los[(x + y * area.width) as usize] = false;
+                    }
+                }
+            }
+        }
+
+        for y in bounds.min_y..bounds.max_y {
+            for x in bounds.min_x..bounds.max_x {
+                let index = (x + y * area.width) as usize;
+                let (dx, dy) = (x - entity_x, y - entity_y);
+                let lit = match lit_grid {
+                    None => true,
+                    Some(lit_grid) => {
+                        lit_grid[index] || dx * dx + dy * dy <= DARK_VIS_DIST_SQUARED
+                    }
+                };
+
+                if lit && check_vis(area, prop_vis_grid, entity_x, entity_y, x, y, src_elev) {
+                    los[index] = true;
+                    exp[index] = true;
+
+                    for prop in &prop_grid[index] {
+                        props_vis.insert(*prop);
+                    }
+                } else {
+                    los[index] = false;
                 }
-            } else {
-                los[index] = false;
             }
         }
     }
 
+    entity.set_pc_vis_bounds(bounds);
+
     props_vis
 }
 
@@ -94,6 +162,47 @@ pub fn has_visibility(
     false
 }
 
+/// Computes the cover the target has against attacks from the entity.  A
+/// target that is only partially visible, such as one standing at the edge
+/// of a wall, has hard cover.  A fully visible target with an impassable
+/// but transparent prop between it and the entity has soft cover.
+pub fn calculate_cover(
+    area: &GeneratedArea,
+    prop_vis_grid: &[bool],
+    prop_pass_grid: &[bool],
+    entity: &EntityState,
+    target: &EntityState,
+) -> Cover {
+    let start_x = entity.location.x + entity.size.width / 2;
+    let start_y = entity.location.y + entity.size.height / 2;
+    let src_elev = area.layer_set.elevation(start_x, start_y);
+
+    let mut visible = 0;
+    let mut total = 0;
+    for p in target.location_points() {
+        total += 1;
+        if check_vis(area, prop_vis_grid, start_x, start_y, p.x, p.y, src_elev) {
+            visible += 1;
+        }
+    }
+
+    if visible < total {
+        return Cover::Hard;
+    }
+
+    let end_x = target.location.x + target.size.width / 2;
+    let end_y = target.location.y + target.size.height / 2;
+    let clear = trace_ray(start_x, start_y, end_x, end_y, |x, y| {
+        prop_pass_grid[(x + y * area.width) as usize]
+    });
+
+    if clear {
+        Cover::None
+    } else {
+        Cover::Soft
+    }
+}
+
 fn check_vis(
     area: &GeneratedArea,
     prop_vis_grid: &[bool],
@@ -106,7 +215,13 @@ fn check_vis(
     let dist_squared =
         (start_x - end_x) * (start_x - end_x) + (start_y - end_y) * (start_y - end_y);
 
-    if dist_squared < area.area.vis_dist_up_one_squared {
+    // from higher ground, entities can see over one level of elevation
+    // out to their full vision distance
+    let high_ground = src_elev > area.layer_set.elevation(end_x, end_y);
+
+    if dist_squared < area.area.vis_dist_up_one_squared
+        || (high_ground && dist_squared < area.area.vis_dist_squared)
+    {
         cast_ray(
             area,
             prop_vis_grid,
@@ -139,51 +254,34 @@ fn cast_ray(
     end_x: i32,
     end_y: i32,
     src_elev: u8,
+) -> bool {
+    trace_ray(start_x, start_y, end_x, end_y, |x, y| {
+        check(area, prop_vis_grid, x, y, src_elev)
+    })
+}
+
+/// Walks the line between the start and end points, calling `check` for
+/// each point along it, excluding the start and end points.  Returns false
+/// as soon as any point fails the check.
+fn trace_ray<F: Fn(i32, i32) -> bool>(
+    start_x: i32,
+    start_y: i32,
+    end_x: i32,
+    end_y: i32,
+    check: F,
 ) -> bool {
     #[allow(clippy::collapsible_else_if)] // this block is logically easier to read when not collapsed
     if (end_y - start_y).abs() < (end_x - start_x).abs() {
         if start_x > end_x {
-            cast_low(
-                area,
-                prop_vis_grid,
-                end_x,
-                end_y,
-                start_x,
-                start_y,
-                src_elev,
-            )
+            cast_low(end_x, end_y, start_x, start_y, check)
         } else {
-            cast_low(
-                area,
-                prop_vis_grid,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-                src_elev,
-            )
+            cast_low(start_x, start_y, end_x, end_y, check)
         }
     } else {
         if start_y > end_y {
-            cast_high(
-                area,
-                prop_vis_grid,
-                end_x,
-                end_y,
-                start_x,
-                start_y,
-                src_elev,
-            )
+            cast_high(end_x, end_y, start_x, start_y, check)
         } else {
-            cast_high(
-                area,
-                prop_vis_grid,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-                src_elev,
-            )
+            cast_high(start_x, start_y, end_x, end_y, check)
         }
     }
 }
@@ -196,14 +294,12 @@ fn check(area: &GeneratedArea, prop_vis_grid: &[bool], x: i32, y: i32, src_elev:
         && area.layer_set.elevation_index(index) <= src_elev
 }
 
-fn cast_high(
-    area: &GeneratedArea,
-    prop_vis_grid: &[bool],
+fn cast_high<F: Fn(i32, i32) -> bool>(
     start_x: i32,
     start_y: i32,
     end_x: i32,
     end_y: i32,
-    src_elev: u8,
+    check: F,
 ) -> bool {
     let mut delta_x = end_x - start_x;
     let delta_y = end_y - start_y;
@@ -222,7 +318,7 @@ fn cast_high(
     for y in start_y..end_y {
         if first {
             first = false;
-        } else if !check(area, prop_vis_grid, x, y, src_elev) {
+        } else if !check(x, y) {
             return false;
         }
 
@@ -236,14 +332,12 @@ fn cast_high(
     true
 }
 
-fn cast_low(
-    area: &GeneratedArea,
-    prop_vis_grid: &[bool],
+fn cast_low<F: Fn(i32, i32) -> bool>(
     start_x: i32,
     start_y: i32,
     end_x: i32,
     end_y: i32,
-    src_elev: u8,
+    check: F,
 ) -> bool {
     let delta_x = end_x - start_x;
     let mut delta_y = end_y - start_y;
@@ -262,7 +356,7 @@ fn cast_low(
     for x in start_x..end_x {
         if first {
             first = false;
-        } else if !check(area, prop_vis_grid, x, y, src_elev) {
+        } else if !check(x, y) {
             return false;
         }
 
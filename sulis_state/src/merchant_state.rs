@@ -32,6 +32,9 @@ pub struct MerchantState {
     pub loot_list_id: Option<String>,
     pub refresh_rate_millis: usize,
     pub last_refresh_millis: usize,
+
+    /// prices are adjusted based on the party's standing with this faction
+    pub faction: Option<String>,
 }
 
 impl MerchantState {
@@ -39,13 +42,12 @@ impl MerchantState {
         let mut items = ItemList::default();
         for item_save in save.items {
             let item = item_save.item;
-            let variant = item.variant;
-            let item = match Module::create_get_item(&item.id, &item.adjectives) {
+            let created = match Module::create_get_item(&item.id, &item.adjectives) {
                 None => invalid_data_error(&format!("No item with ID '{}'", item.id)),
-                Some(item) => Ok(item),
+                Some(created) => Ok(created),
             }?;
 
-            items.add_quantity(item_save.quantity, ItemState::new(item, variant));
+            items.add_stack(item_save.quantity, ItemState::load(created, &item));
         }
 
         Ok(MerchantState {
@@ -57,6 +59,7 @@ impl MerchantState {
             items,
             refresh_rate_millis: save.refresh_rate_millis,
             last_refresh_millis: save.last_refresh_millis,
+            faction: save.faction,
         })
     }
 
@@ -86,6 +89,7 @@ impl MerchantState {
             listeners: ChangeListenerList::default(),
             last_refresh_millis,
             refresh_rate_millis,
+            faction: None,
         }
     }
 
@@ -129,12 +133,27 @@ impl MerchantState {
         }
     }
 
+    fn price_factor(&self) -> f32 {
+        let difficulty = GameState::difficulty().scaling().merchant_prices;
+        match self.faction {
+            None => difficulty,
+            Some(ref faction) => GameState::faction_price_factor(faction) * difficulty,
+        }
+    }
+
     pub fn get_buy_price(&self, item_state: &ItemState) -> i32 {
-        ((item_state.item.value as f32) * self.buy_frac).ceil() as i32
+        let frac = self.buy_frac * self.price_factor();
+        ((item_state.item.value as f32) * frac).ceil() as i32
+    }
+
+    /// Returns the price charged by this merchant to fully repair the item
+    pub fn get_repair_price(&self, item_state: &ItemState) -> i32 {
+        (item_state.repair_cost() * self.price_factor()).ceil() as i32
     }
 
     pub fn get_sell_price(&self, item_state: &ItemState) -> i32 {
-        ((item_state.item.value as f32) * self.sell_frac).floor() as i32
+        let frac = self.sell_frac / self.price_factor();
+        ((item_state.item.value as f32) * frac).floor() as i32
     }
 
     pub fn add(&mut self, item_state: ItemState) {
@@ -143,6 +162,12 @@ impl MerchantState {
         self.listeners.notify(self);
     }
 
+    pub fn add_quantity(&mut self, qty: u32, item_state: ItemState) {
+        self.items.add_quantity(qty, item_state);
+
+        self.listeners.notify(self);
+    }
+
     /// removes one copy of the item at the specified index
     pub fn remove(&mut self, index: usize) -> Option<ItemState> {
         let result = self.items.remove(index);
@@ -154,6 +179,18 @@ impl MerchantState {
         result
     }
 
+    /// removes up to the specified quantity of the item at the specified index,
+    /// returning the quantity removed along with the item
+    pub fn remove_quantity(&mut self, index: usize, qty: u32) -> Option<(u32, ItemState)> {
+        let result = self.items.remove_quantity(index, qty);
+
+        if result.is_some() {
+            self.listeners.notify(self);
+        }
+
+        result
+    }
+
     pub fn items(&self) -> &ItemList {
         &self.items
     }
@@ -0,0 +1,385 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Loads a module and checks it for broken or dangling references, reporting
+//! any problems found along with the file and line responsible, where known.
+//! This is run via the `--validate` command line flag.
+
+use std::path::{Path, PathBuf};
+
+use rlua::Lua;
+
+use sulis_core::resource::{take_load_errors, vfs, LoadError, ResourceSet};
+use sulis_module::area::ToKind;
+use sulis_module::{Area, Module};
+
+/// A single problem found in the module.  `kind` and `id` identify the
+/// resource, such as `area` and `wilderness`, while `file` and `line` point
+/// to its source when they can be determined.  `column` and `key_path` are
+/// only known for files that failed to parse.
+#[derive(Serialize, Debug, Clone)]
+pub struct ValidationError {
+    pub kind: String,
+    pub id: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Prints each error to standard output as a single line JSON object
+    pub fn print(&self) {
+        for error in self.errors.iter() {
+            match serde_json::to_string(error) {
+                Ok(line) => println!("{line}"),
+                Err(e) => warn!("Unable to serialize validation error: {}", e),
+            }
+        }
+    }
+
+    fn add(&mut self, kind: &str, id: &str, message: String) {
+        self.errors.push(ValidationError {
+            kind: kind.to_string(),
+            id: id.to_string(),
+            file: None,
+            line: None,
+            column: None,
+            key_path: None,
+            message,
+        });
+    }
+}
+
+/// Loads the module at `module_dir` along with all of its base modules on top
+/// of the `data_dir`, and runs all checks on the result
+pub fn validate(data_dir: &str, module_dir: &str) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let mut dirs = vec![data_dir.to_string()];
+    match Module::module_directories(module_dir) {
+        Ok(module_dirs) => dirs.extend(module_dirs),
+        Err(e) => {
+            report.add("module", module_dir, e.to_string());
+            return report;
+        }
+    }
+
+    let yaml = match ResourceSet::load_resources(dirs.clone()) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            add_load_errors(&mut report, &[]);
+            report.add("module", module_dir, e.to_string());
+            return report;
+        }
+    };
+
+    let files = yaml.resource_files();
+    let result = Module::load_resources(yaml, dirs.clone());
+    add_load_errors(&mut report, &files);
+    if let Err(e) = result {
+        report.add("module", module_dir, e.to_string());
+        return report;
+    }
+
+    check_transitions(&mut report, &files);
+    check_world_map(&mut report, &files);
+    check_scripts(&mut report, &dirs);
+
+    report
+}
+
+fn add_load_errors(report: &mut ValidationReport, files: &[(String, String, Vec<String>)]) {
+    for error in take_load_errors() {
+        let LoadError {
+            kind,
+            id,
+            file,
+            line,
+            column,
+            key_path,
+            message,
+            ..
+        } = error;
+
+        let (file, line) = match file {
+            Some(file) => (Some(file), line),
+            None => find_source(files, &kind, &id, quoted_name(&message)),
+        };
+
+        report.errors.push(ValidationError {
+            kind,
+            id,
+            file,
+            line,
+            column,
+            key_path,
+            message,
+        });
+    }
+}
+
+fn check_transitions(report: &mut ValidationReport, files: &[(String, String, Vec<String>)]) {
+    for area in Module::all_areas() {
+        for transition in area.transitions.iter() {
+            let from = transition.from;
+            if !in_bounds(area.width, area.height, from.x, from.y) {
+                let message = format!(
+                    "Transition at {},{} is outside the area bounds",
+                    from.x, from.y
+                );
+                add_with_source(report, files, "area", &area.id, None, message);
+            }
+
+            let (target, pos) = match transition.to {
+                ToKind::Area { ref id, x, y } => (id, Some((x, y))),
+                ToKind::FindLink { ref id, .. } => (id, None),
+                ToKind::CurArea { x, y } => (&area.id, Some((x, y))),
+                ToKind::Stairs { ref id } => (id, Some((from.x, from.y))),
+                ToKind::WorldMap => continue,
+            };
+
+            let target_area = match Module::area(target) {
+                None => {
+                    let message = format!("Transition references missing area '{target}'");
+                    add_with_source(report, files, "area", &area.id, Some(target), message);
+                    continue;
+                }
+                Some(target_area) => target_area,
+            };
+
+            match pos {
+                Some((x, y)) => {
+                    if in_bounds(target_area.width, target_area.height, x, y) {
+                        continue;
+                    }
+                    let message = format!(
+                        "Transition to {x},{y} is outside the bounds of area '{target}'"
+                    );
+                    add_with_source(report, files, "area", &area.id, Some(target), message);
+                }
+                None => {
+                    // generated areas create their transitions when generated
+                    if target_area.generator.is_some() || has_link(&target_area, &area.id) {
+                        continue;
+                    }
+                    let message = format!(
+                        "Transition to area '{target}' has no matching transition back"
+                    );
+                    add_with_source(report, files, "area", &area.id, Some(target), message);
+                }
+            }
+        }
+    }
+}
+
+fn has_link(area: &Area, from: &str) -> bool {
+    area.transitions.iter().any(|transition| match transition.to {
+        ToKind::Area { ref id, .. } | ToKind::FindLink { ref id, .. } => id == from,
+        _ => false,
+    })
+}
+
+fn check_world_map(report: &mut ValidationReport, files: &[(String, String, Vec<String>)]) {
+    let campaign = Module::campaign();
+
+    if Module::area(&campaign.starting_area).is_none() {
+        let message = format!("Starting area '{}' not found", campaign.starting_area);
+        let area = Some(campaign.starting_area.as_str());
+        add_with_source(report, files, "campaign", &campaign.id, area, message);
+    }
+
+    for location in campaign.world_map.locations.iter() {
+        let area_id = match location.linked_area {
+            None => continue,
+            Some(ref area_id) => area_id,
+        };
+
+        let message = match Module::area(area_id) {
+            None => format!(
+                "World map location '{}' references missing area '{}'",
+                location.id, area_id
+            ),
+            Some(area) => {
+                let pos = location.linked_area_pos;
+                if in_bounds(area.width, area.height, pos.x, pos.y) {
+                    continue;
+                }
+                format!(
+                    "World map location '{}' position {},{} is outside area '{}'",
+                    location.id, pos.x, pos.y, area_id
+                )
+            }
+        };
+        let loc = Some(location.id.as_str());
+        add_with_source(report, files, "campaign", &campaign.id, loc, message);
+    }
+}
+
+fn check_scripts(report: &mut ValidationReport, dirs: &[String]) {
+    let mut paths = Vec::new();
+    for dir in dirs {
+        find_scripts(&Path::new(dir).join("scripts"), &mut paths);
+    }
+
+    let lua = Lua::new();
+    for path in paths {
+        let path_str = path.to_string_lossy().to_string();
+        let id = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let src = match vfs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(e) => {
+                push_error(report, "script", &id, path_str, None, e.to_string());
+                continue;
+            }
+        };
+
+        let result: Result<(), rlua::Error> = lua.context(|lua| {
+            lua.load(&src).set_name(&id)?.into_function()?;
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            let message = e.to_string();
+            let line = lua_error_line(&message);
+            push_error(report, "script", &id, path_str, line, message);
+        }
+    }
+}
+
+fn find_scripts(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let entries = match vfs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for path in entries {
+        if vfs::is_dir(&path) {
+            find_scripts(&path, paths);
+        } else if path.extension().is_some_and(|ext| ext == "lua") {
+            paths.push(path);
+        }
+    }
+}
+
+fn push_error(
+    report: &mut ValidationReport,
+    kind: &str,
+    id: &str,
+    file: String,
+    line: Option<usize>,
+    message: String,
+) {
+    report.errors.push(ValidationError {
+        kind: kind.to_string(),
+        id: id.to_string(),
+        file: Some(file),
+        line,
+        column: None,
+        key_path: None,
+        message,
+    });
+}
+
+fn add_with_source(
+    report: &mut ValidationReport,
+    files: &[(String, String, Vec<String>)],
+    kind: &str,
+    id: &str,
+    needle: Option<&str>,
+    message: String,
+) {
+    let (file, line) = find_source(files, kind, id, needle);
+    report.errors.push(ValidationError {
+        kind: kind.to_string(),
+        id: id.to_string(),
+        file,
+        line,
+        column: None,
+        key_path: None,
+        message,
+    });
+}
+
+/// Finds the file the specified resource was read from, and the first line
+/// in that file containing `needle`, or the resource's id if there is no needle
+/// or it is not found.  Resource kinds such as "image" that are shared by
+/// several directories match any kind ending in that name.
+fn find_source(
+    files: &[(String, String, Vec<String>)],
+    kind: &str,
+    id: &str,
+    needle: Option<&str>,
+) -> (Option<String>, Option<usize>) {
+    let candidates: Vec<&String> = files
+        .iter()
+        .filter(|(file_kind, file_id, _)| file_id == id && file_kind.ends_with(kind))
+        .flat_map(|(_, _, files)| files.iter())
+        .collect();
+
+    let needles = needle.into_iter().chain(std::iter::once(id));
+    for needle in needles {
+        for file in candidates.iter() {
+            if let Some(line) = find_line(file, needle) {
+                return (Some(file.to_string()), Some(line));
+            }
+        }
+    }
+
+    (candidates.first().map(|file| file.to_string()), None)
+}
+
+fn find_line(file: &str, needle: &str) -> Option<usize> {
+    let data = vfs::read_to_string(file).ok()?;
+    data.lines()
+        .position(|line| line.contains(needle))
+        .map(|index| index + 1)
+}
+
+/// Returns the first single quoted name in an error message, which by
+/// convention is the missing or invalid reference
+fn quoted_name(message: &str) -> Option<&str> {
+    let start = message.find('\'')? + 1;
+    let len = message[start..].find('\'')?;
+    Some(&message[start..start + len])
+}
+
+/// Lua errors are of the form `[string "id"]:line: message`
+fn lua_error_line(message: &str) -> Option<usize> {
+    let start = message.find("\"]:")? + 3;
+    let len = message[start..].find(':')?;
+    message[start..start + len].parse().ok()
+}
+
+fn in_bounds(width: i32, height: i32, x: i32, y: i32) -> bool {
+    x >= 0 && y >= 0 && x < width && y < height
+}
@@ -45,11 +45,20 @@ pub struct PStats {
 
     #[serde(default)]
     pub(crate) current_class_stats: HashMap<String, ExtInt>,
+
+    #[serde(default)]
+    group_cooldowns: HashMap<String, u32>,
     pub(crate) faction: Faction,
 
     #[serde(default)]
     disabled: bool,
 
+    #[serde(default)]
+    reaction_used: bool,
+
+    #[serde(default)]
+    quick_item_uses: u32,
+
     #[serde(skip)]
     base_class: Option<Rc<Class>>,
 }
@@ -68,8 +77,11 @@ impl PStats {
             current_group_uses_per_encounter: HashMap::new(),
             current_group_uses_per_day: HashMap::new(),
             current_class_stats: HashMap::new(),
+            group_cooldowns: HashMap::new(),
             faction: actor.faction(),
             disabled: false,
+            reaction_used: false,
+            quick_item_uses: 0,
             base_class: Some(actor.base_class()),
         }
     }
@@ -135,6 +147,24 @@ impl PStats {
         true
     }
 
+    /// Returns the remaining cooldown in millis for the specified cooldown group
+    pub fn group_cooldown(&self, group: &str) -> u32 {
+        *self.group_cooldowns.get(group).unwrap_or(&0)
+    }
+
+    pub fn set_group_cooldown(&mut self, group: &str, millis: u32) {
+        let cur = self.group_cooldown(group);
+        self.group_cooldowns
+            .insert(group.to_string(), std::cmp::max(cur, millis));
+    }
+
+    pub fn elapse_group_cooldowns(&mut self, millis: u32) {
+        for remaining in self.group_cooldowns.values_mut() {
+            *remaining = remaining.saturating_sub(millis);
+        }
+        self.group_cooldowns.retain(|_, remaining| *remaining > 0);
+    }
+
     pub fn set_disabled(&mut self, disabled: bool) {
         self.disabled = disabled;
     }
@@ -153,6 +183,31 @@ impl PStats {
         !self.threatened_by.is_empty()
     }
 
+    pub fn threatened_by(&self) -> &[usize] {
+        &self.threatened_by
+    }
+
+    /// Returns true if the parent entity has already made its reaction, such
+    /// as an attack of opportunity, since the start of its last turn
+    pub fn is_reaction_used(&self) -> bool {
+        self.reaction_used
+    }
+
+    pub fn set_reaction_used(&mut self, used: bool) {
+        self.reaction_used = used;
+    }
+
+    /// Returns the number of quick slot item uses remaining in the current
+    /// combat, or None if uses are unlimited
+    pub fn quick_item_uses_remaining(&self) -> Option<u32> {
+        let max = Module::rules().quick_item_uses_per_combat?;
+        Some(max.saturating_sub(self.quick_item_uses))
+    }
+
+    pub fn add_quick_item_use(&mut self) {
+        self.quick_item_uses += 1;
+    }
+
     pub fn add_threatening(&mut self, index: usize) {
         if !self.threatening.contains(&index) {
             self.threatening.push(index);
@@ -301,6 +356,7 @@ impl PStats {
     /// Called once at the end of each combat encounter - does per encounter
     /// actions as well as `init_turn`
     pub fn end_encounter(&mut self, stats: &StatList) {
+        self.quick_item_uses = 0;
         for (group, amount) in stats.uses_per_encounter_iter() {
             self.current_group_uses_per_encounter
                 .insert(group.to_string(), *amount);
@@ -344,6 +400,7 @@ impl PStats {
         }
 
         self.ap = ap;
+        self.reaction_used = false;
     }
 
     pub fn end_turn(&mut self) {
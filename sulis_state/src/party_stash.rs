@@ -14,36 +14,93 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
-use crate::{ChangeListenerList, GameState, ItemList};
+use crate::{ChangeListenerList, GameEvent, GameState, ItemList};
 use sulis_module::{ItemListEntrySaveState, ItemState, Module};
 
 pub struct PartyStash {
     items: ItemList,
+    storage: ItemList,
     coins_id: String,
     pub listeners: ChangeListenerList<PartyStash>,
 }
 
 impl PartyStash {
-    pub(crate) fn new(items: ItemList) -> PartyStash {
+    pub(crate) fn new(items: ItemList, storage: ItemList) -> PartyStash {
         let coins_id = Module::rules().coins_item.to_string();
         PartyStash {
             items,
+            storage,
             coins_id,
             listeners: ChangeListenerList::default(),
         }
     }
 
     pub(crate) fn save(&self) -> Vec<ItemListEntrySaveState> {
-        self.items
-            .iter()
-            .map(|(q, ref i)| ItemListEntrySaveState::new(*q, i))
-            .collect()
+        save_list(&self.items)
+    }
+
+    pub(crate) fn save_storage(&self) -> Vec<ItemListEntrySaveState> {
+        save_list(&self.storage)
     }
 
     pub fn items(&self) -> &ItemList {
         &self.items
     }
 
+    /// The items kept in the party storage, which are not carried by the
+    /// party and may only be accessed at camps and merchants
+    pub fn storage(&self) -> &ItemList {
+        &self.storage
+    }
+
+    /// Returns whether the specified item can be placed in the party storage,
+    /// either by stacking with an existing entry or by adding a new one
+    pub fn storage_has_room(&self, item_state: &ItemState) -> bool {
+        let max = item_state.item.max_stack();
+        let can_stack = self
+            .storage
+            .iter()
+            .any(|(qty, item)| item == item_state && *qty < max);
+        if can_stack {
+            return true;
+        }
+
+        match Module::rules().party_storage_capacity {
+            None => true,
+            Some(capacity) => (self.storage.len() as u32) < capacity,
+        }
+    }
+
+    /// Moves the entire stack at the specified index of the stash into the
+    /// party storage.  Returns false if the storage is full
+    pub fn store(&mut self, index: usize) -> bool {
+        match self.items.get(index) {
+            None => return false,
+            Some((_, item_state)) => {
+                if item_state.item.quest || !self.storage_has_room(item_state) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some((qty, item_state)) = self.items.remove_all_at(index) {
+            self.storage.add_quantity(qty, item_state);
+        }
+
+        self.listeners.notify(self);
+        true
+    }
+
+    /// Moves the entire stack at the specified index of the party storage
+    /// back into the stash
+    pub fn retrieve(&mut self, index: usize) {
+        if let Some((qty, item_state)) = self.storage.remove_all_at(index) {
+            self.items.add_quantity(qty, item_state);
+        }
+
+        self.listeners.notify(self);
+    }
+
     pub fn add_item(&mut self, quantity: u32, item_state: ItemState) -> Option<usize> {
         if quantity == 0 {
             return None;
@@ -55,11 +112,13 @@ impl PartyStash {
             return None;
         }
 
+        let item = item_state.item.id.to_string();
         let index = self.items.add_quantity(quantity, item_state);
 
         self.listeners.notify(self);
+        GameState::publish_event(GameEvent::ItemAcquired { item, quantity });
 
-        Some(index)
+        index
     }
 
     /// Returns whether or not this stash has at least one item
@@ -85,6 +144,27 @@ impl PartyStash {
         result
     }
 
+    /// Removes up to the specified quantity of items from the stack at the
+    /// specified index, returning the quantity removed along with the item
+    #[must_use]
+    pub fn remove_quantity(&mut self, index: usize, qty: u32) -> Option<(u32, ItemState)> {
+        let result = self.items.remove_quantity(index, qty);
+
+        if result.is_some() {
+            self.listeners.notify(self);
+        }
+
+        result
+    }
+
+    /// Splits the stack at the specified index into two stacks of
+    /// roughly equal size
+    pub fn split_stack(&mut self, index: usize) {
+        if self.items.split(index).is_some() {
+            self.listeners.notify(self);
+        }
+    }
+
     /// Takes all items out of the specified prop and into this stash
     pub fn take_all(&mut self, prop_index: usize) {
         let area_state = GameState::area_state();
@@ -125,4 +205,23 @@ impl PartyStash {
 
         self.listeners.notify(self);
     }
+
+    /// takes a single item out of the stack at the item-index of the specified
+    /// prop, leaving the remainder of the stack in the prop
+    pub fn take_one(&mut self, prop_index: usize, item_index: usize) {
+        let area_state = GameState::area_state();
+        let mut area_state = area_state.borrow_mut();
+        let prop_state = area_state.props_mut().get_mut(prop_index);
+
+        if let Some(item_state) = prop_state.remove_one_at(item_index) {
+            self.add_item(1, item_state);
+            self.listeners.notify(self);
+        }
+    }
+}
+
+fn save_list(list: &ItemList) -> Vec<ItemListEntrySaveState> {
+    list.iter()
+        .map(|(q, ref i)| ItemListEntrySaveState::new(*q, i))
+        .collect()
 }
@@ -22,12 +22,16 @@ use sulis_core::{
     config::Config,
     util::{self, Point},
 };
-use sulis_module::area::{Destination, LocationChecker, PathFinder, PathFinderGrid};
+use sulis_module::area::{Destination, LayerSet, LocationChecker, PathFinder, PathFinderGrid};
+use sulis_module::Module;
 
 pub struct StateLocationChecker<'a, 'b> {
     width: i32,
     grid: &'a PathFinderGrid,
     explored: Option<&'a [bool]>,
+    layer_set: &'a LayerSet,
+    climb_move_squares: i32,
+    diagonal_cost: i32,
     prop_grid: &'a [bool],
     entity_grid: &'a [Vec<usize>],
     requester: &'b EntityState,
@@ -45,16 +49,20 @@ impl<'a, 'b> StateLocationChecker<'a, 'b> {
         let grid = &area_state.area.path_grid(requester.size());
         let prop_grid = area_state.props().entire_pass_grid();
         let entity_grid = &area_state.entity_grid;
-        let explored = if use_explored {
+        let explored = if use_explored && Config::fog_of_war() {
             Some(area_state.pc_explored.as_slice())
         } else {
             None
         };
 
+        let rules = Module::rules();
         StateLocationChecker {
             width,
             grid,
             explored,
+            layer_set: &area_state.area.layer_set,
+            climb_move_squares: rules.climb_move_squares as i32,
+            diagonal_cost: (10.0 * rules.diagonal_move_cost).round() as i32,
             prop_grid,
             entity_grid,
             requester,
@@ -107,12 +115,23 @@ impl<'a, 'b> LocationChecker for StateLocationChecker<'a, 'b> {
         })
     }
 
-    fn get_cost(&self, _from: i32, to: i32) -> i32 {
-        if self.entity_grid[to as usize].is_empty() {
-            10
+    fn get_cost(&self, from: i32, to: i32) -> i32 {
+        let diagonal = from % self.width != to % self.width && from / self.width != to / self.width;
+        let base = if diagonal { self.diagonal_cost } else { 10 };
+        let base = if self.entity_grid[to as usize].is_empty() {
+            base
         } else {
-            11
-        }
+            base + 1
+        };
+
+        let from_elev = self.layer_set.elevation_index(from as usize);
+        let to_elev = self.layer_set.elevation_index(to as usize);
+        let climbed = to_elev.saturating_sub(from_elev) as i32;
+        base + 10 * climbed * self.climb_move_squares
+    }
+
+    fn path_grid(&self) -> Option<&PathFinderGrid> {
+        Some(self.grid)
     }
 }
 
@@ -192,7 +211,8 @@ fn find_path(
     );
 
     if check_ap {
-        if entity.actor.stats.move_disabled || entity.actor.ap() < entity.actor.get_move_ap_cost(1)
+        if entity.actor.stats.move_disabled
+            || entity.actor.ap() < entity.actor.get_move_ap_cost(1, 0)
         {
             return None;
         }
@@ -202,7 +222,9 @@ fn find_path(
 
     let start_time = std::time::Instant::now();
 
-    let path = path_finder.find(&checker, entity.location.x, entity.location.y, dest);
+    let path = path_finder
+        .find(&checker, entity.location.x, entity.location.y, dest)
+        .map(|path| smooth_path(&checker, path));
 
     debug!(
         "Pathing complete in {} secs",
@@ -210,3 +232,78 @@ fn find_path(
     );
     path
 }
+
+/// Straightens the path by string pulling - each point is connected to the
+/// furthest later point reachable in a straight line that costs no more to
+/// move along than the path it replaces, with the line then converted back to
+/// single steps.  Diagonal steps cost more than orthogonal ones, so a shorter
+/// line is not necessarily cheaper.  The resulting path never costs more than
+/// the original.
+fn smooth_path(checker: &StateLocationChecker, path: Vec<Point>) -> Vec<Point> {
+    if path.len() < 3 {
+        return path;
+    }
+
+    let mut smoothed = vec![path[0]];
+    let mut anchor = 0;
+    while anchor < path.len() - 1 {
+        let mut next = anchor + 1;
+        for candidate in (anchor + 2)..path.len() {
+            if !is_line_passable(checker, path[anchor], path[candidate]) {
+                break;
+            }
+
+            let line = grid_line(path[anchor], path[candidate]);
+            if path_cost(checker, &line) > path_cost(checker, &path[anchor..=candidate]) {
+                continue;
+            }
+            next = candidate;
+        }
+
+        smoothed.extend(grid_line(path[anchor], path[next]).into_iter().skip(1));
+        anchor = next;
+    }
+
+    smoothed
+}
+
+/// The total movement cost of the steps along `points`
+fn path_cost(checker: &StateLocationChecker, points: &[Point]) -> i32 {
+    let index = |p: Point| p.x + p.y * checker.width;
+    points
+        .windows(2)
+        .map(|step| checker.get_cost(index(step[0]), index(step[1])))
+        .sum()
+}
+
+fn is_line_passable(checker: &StateLocationChecker, from: Point, to: Point) -> bool {
+    let line = grid_line(from, to);
+    line.windows(2).all(|step| {
+        let (cur, next) = (step[0], step[1]);
+        if !checker.passable(next.x, next.y) {
+            return false;
+        }
+
+        // diagonal steps may not cut across the corner of an impassable square
+        cur.x == next.x
+            || cur.y == next.y
+            || (checker.passable(next.x, cur.y) && checker.passable(cur.x, next.y))
+    })
+}
+
+/// The squares along a straight line from `from` to `to`, inclusive, where
+/// each square is one orthogonal or diagonal step from the previous
+pub(crate) fn grid_line(from: Point, to: Point) -> Vec<Point> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let steps = dx.abs().max(dy.abs());
+
+    let mut line = Vec::with_capacity(steps as usize + 1);
+    line.push(from);
+    for i in 1..=steps {
+        let frac = i as f32 / steps as f32;
+        let x = from.x + (dx as f32 * frac).round() as i32;
+        let y = from.y + (dy as f32 * frac).round() as i32;
+        line.push(Point::new(x, y));
+    }
+    line
+}
@@ -0,0 +1,95 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::util::Point;
+use sulis_module::area::Patrol;
+
+use crate::{EntityState, GameState};
+
+/// The time to wait before trying again when a waypoint cannot be reached
+const BLOCKED_RETRY_MILLIS: u32 = 2000;
+
+/// Tracks an entity's progress along its patrol route
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PatrolState {
+    waypoints: Vec<Point>,
+    pause_millis: u32,
+    next_waypoint: usize,
+    remaining_pause: u32,
+}
+
+impl PatrolState {
+    pub fn new(patrol: &Patrol) -> Option<PatrolState> {
+        if patrol.waypoints.is_empty() {
+            return None;
+        }
+
+        Some(PatrolState {
+            waypoints: patrol.waypoints.clone(),
+            pause_millis: patrol.pause_millis,
+            next_waypoint: 0,
+            remaining_pause: 0,
+        })
+    }
+
+    pub fn next_waypoint(&self) -> Point {
+        self.waypoints[self.next_waypoint]
+    }
+
+    /// Advances the specified entity along its patrol, if it has one.  Should
+    /// only be called outside of combat.  If combat interrupts the patrol,
+    /// the entity heads back to its next waypoint once combat ends.
+    pub(crate) fn update(entity: &Rc<RefCell<EntityState>>, millis: u32) {
+        if entity.borrow().actor.is_dead() || GameState::has_blocking_animations(entity) {
+            return;
+        }
+
+        let dest = {
+            let mut entity = entity.borrow_mut();
+            let location = entity.location.to_point();
+            let patrol = match entity.patrol_mut() {
+                None => return,
+                Some(patrol) => patrol,
+            };
+
+            if patrol.remaining_pause > 0 {
+                patrol.remaining_pause = patrol.remaining_pause.saturating_sub(millis);
+                return;
+            }
+
+            if location == patrol.next_waypoint() {
+                patrol.next_waypoint = (patrol.next_waypoint + 1) % patrol.waypoints.len();
+                patrol.remaining_pause = patrol.pause_millis;
+                return;
+            }
+
+            let waypoint = patrol.next_waypoint();
+            GameState::get_point_dest(&entity, waypoint.x as f32, waypoint.y as f32)
+        };
+
+        if !GameState::move_towards_dest(entity, &[], dest, None) {
+            let mut entity = entity.borrow_mut();
+            if let Some(patrol) = entity.patrol_mut() {
+                patrol.next_waypoint = (patrol.next_waypoint + 1) % patrol.waypoints.len();
+                patrol.remaining_pause = BLOCKED_RETRY_MILLIS;
+            }
+        }
+    }
+}
@@ -21,7 +21,7 @@ use std::rc::Rc;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::ui::{animation_state, AnimationState, Color};
 use sulis_core::util::{self, invalid_data_error, Offset, Scale, Size};
-use sulis_module::area::PropData;
+use sulis_module::area::{Lock, PropData};
 use sulis_module::{prop, ItemState, LootList, Module, ObjectSizeIterator, Prop, OnTrigger};
 
 use crate::entity_state::AreaDrawable;
@@ -45,6 +45,10 @@ pub enum Interactive {
     Hover {
         text: String,
     },
+    Trap {
+        armed: bool,
+        on_activate: Vec<OnTrigger>,
+    },
 }
 
 pub struct PropState {
@@ -54,6 +58,11 @@ pub struct PropState {
     pub listeners: ChangeListenerList<PropState>,
     pub(crate) interactive: Interactive,
     enabled: bool,
+    lock: Option<Lock>,
+    detect_difficulty: Option<i32>,
+
+    /// Party members who have already failed to detect this prop
+    searched_by: Vec<String>,
 
     marked_for_removal: bool,
 
@@ -119,6 +128,20 @@ impl PropState {
                     fire_more_than_once: *fire_more_than_once,
                 }
             }
+            prop::Interactive::Trap { ref on_activate } => Interactive::Trap {
+                armed: true,
+                on_activate: on_activate.clone(),
+            },
+        };
+
+        let lock = match interactive {
+            Interactive::Container { .. } | Interactive::Door { .. } => prop_data.lock.clone(),
+            _ => {
+                if prop_data.lock.is_some() {
+                    warn!("Attempted to add a lock to non door or container prop");
+                }
+                None
+            }
         };
 
         let millis_offset_range = prop_data.prop.random_millis_offset;
@@ -131,6 +154,9 @@ impl PropState {
         PropState {
             prop: Rc::clone(&prop_data.prop),
             enabled: prop_data.enabled,
+            lock,
+            detect_difficulty: prop_data.detect_difficulty,
+            searched_by: Vec::new(),
             location,
             interactive,
             animation_state: anim_state,
@@ -169,16 +195,15 @@ impl PropState {
                 let mut item_list = ItemList::default();
                 for item_save_state in items {
                     let item = &item_save_state.item;
-                    let variant = item.variant;
-                    let item = match Module::create_get_item(&item.id, &item.adjectives) {
+                    let created = match Module::create_get_item(&item.id, &item.adjectives) {
                         None => invalid_data_error(&format!(
                             "No item with ID '{}'",
                             item_save_state.item.id
                         )),
-                        Some(item) => Ok(item),
+                        Some(created) => Ok(created),
                     }?;
 
-                    item_list.add_quantity(item_save_state.quantity, ItemState::new(item, variant));
+                    item_list.add_stack(item_save_state.quantity, ItemState::load(created, item));
                 }
 
                 let loot = match loot_to_generate {
@@ -223,6 +248,14 @@ impl PropState {
 
                 self.interactive = Interactive::Hover { text };
             }
+            PropInteractiveSaveState::Trap { armed } => {
+                if let prop::Interactive::Trap { on_activate } = &self.prop.interactive {
+                    self.interactive = Interactive::Trap {
+                        armed,
+                        on_activate: on_activate.clone(),
+                    };
+                }
+            }
         }
 
         Ok(())
@@ -247,6 +280,74 @@ impl PropState {
         self.enabled = enabled;
     }
 
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    pub fn lock(&self) -> Option<&Lock> {
+        self.lock.as_ref()
+    }
+
+    pub(crate) fn unlock(&mut self) {
+        self.lock = None;
+        self.listeners.notify(self);
+    }
+
+    /// Whether this prop is hidden, and so may not be interacted with until
+    /// it is detected
+    pub fn is_hidden(&self) -> bool {
+        self.detect_difficulty.is_some()
+    }
+
+    /// Whether this prop is drawn - a hidden prop is only drawn if it has a
+    /// hidden image to show in its place
+    pub fn is_drawn(&self) -> bool {
+        !self.is_hidden() || self.prop.hidden_image.is_some()
+    }
+
+    pub fn detect_difficulty(&self) -> Option<i32> {
+        self.detect_difficulty
+    }
+
+    /// Records a detection attempt by the party member with `unique_id`.
+    /// Returns false if that party member has already attempted to detect
+    /// this prop
+    pub(crate) fn add_search(&mut self, unique_id: &str) -> bool {
+        if self.searched_by.iter().any(|id| id == unique_id) {
+            return false;
+        }
+
+        self.searched_by.push(unique_id.to_string());
+        true
+    }
+
+    /// Reveals this hidden prop.  A revealed trap is disarmed
+    pub(crate) fn reveal(&mut self) {
+        self.detect_difficulty = None;
+        if let Interactive::Trap { ref mut armed, .. } = self.interactive {
+            *armed = false;
+        }
+        self.listeners.notify(self);
+    }
+
+    /// If this prop is an armed trap, disarms and reveals it, returning the
+    /// triggers to fire
+    pub(crate) fn spring_trap(&mut self) -> Option<Vec<OnTrigger>> {
+        let on_activate = match self.interactive {
+            Interactive::Trap {
+                ref mut armed,
+                ref on_activate,
+            } if *armed => {
+                *armed = false;
+                on_activate.clone()
+            }
+            _ => return None,
+        };
+
+        self.reveal();
+        Some(on_activate)
+    }
+
     pub(crate) fn is_marked_for_removal(&self) -> bool {
         self.marked_for_removal
     }
@@ -288,7 +389,7 @@ impl PropState {
         let is_active = self.is_active();
 
         match self.interactive {
-            Interactive::Not | Interactive::Hover { .. } => (),
+            Interactive::Not | Interactive::Hover { .. } | Interactive::Trap { .. } => (),
             Interactive::Container {
                 ref mut items,
                 ref mut loot_to_generate,
@@ -411,8 +512,13 @@ impl PropState {
     }
 
     pub fn append_to_draw_list(&self, draw_list: &mut DrawList, offset: Offset, millis: u32) {
-        self.prop
-            .append_to_draw_list(draw_list, &self.animation_state, offset, millis);
+        if self.is_hidden() {
+            self.prop
+                .append_hidden_to_draw_list(draw_list, &self.animation_state, offset, millis);
+        } else {
+            self.prop
+                .append_to_draw_list(draw_list, &self.animation_state, offset, millis);
+        }
     }
 }
 
@@ -147,6 +147,72 @@ impl QuestStateSet {
         self.set_current_quest_and_notify(quest_id);
     }
 
+    /// Marks the specified `entry` as active in the given quest, making the quest
+    /// itself active if it was not already
+    pub fn add_entry(&mut self, quest_id: &str, entry: &str) {
+        let quest = self.get_or_insert(quest_id);
+        quest.set_entry_state(entry, QuestEntryState::Active);
+        if quest.state != QuestEntryState::Complete {
+            quest.state = QuestEntryState::Active;
+        }
+        self.set_current_quest_and_notify(quest_id);
+    }
+
+    /// Completes all currently visible or active entries in the quest and then
+    /// adds the specified `entry` as the new active entry
+    pub fn advance(&mut self, quest_id: &str, entry: &str) {
+        self.get_or_insert(quest_id).complete_open_entries();
+        self.add_entry(quest_id, entry);
+    }
+
+    /// Completes the quest along with all of its open entries
+    pub fn complete(&mut self, quest_id: &str) {
+        let quest = self.get_or_insert(quest_id);
+        quest.complete_open_entries();
+        quest.state = QuestEntryState::Complete;
+        self.set_current_quest_and_notify(quest_id);
+    }
+
+    fn get_or_insert(&mut self, quest_id: &str) -> &mut QuestState {
+        self.quests
+            .entry(quest_id.to_string())
+            .or_insert_with(|| QuestState::new(quest_id.to_string()))
+    }
+
+    /// Returns the world map locations that open quest entries direct the
+    /// player to, mapped to the name of the associated quest
+    pub fn destinations(&self) -> HashMap<String, String> {
+        let mut result = HashMap::new();
+        for (id, quest_state) in self.quests.iter() {
+            match quest_state.state {
+                QuestEntryState::Visible | QuestEntryState::Active => (),
+                _ => continue,
+            }
+
+            let quest = match Module::quest(id) {
+                None => continue,
+                Some(quest) => quest,
+            };
+
+            for (entry_id, state) in quest_state.iter() {
+                match state {
+                    QuestEntryState::Visible | QuestEntryState::Active => (),
+                    _ => continue,
+                }
+
+                let location = quest
+                    .entries
+                    .get(entry_id)
+                    .and_then(|entry| entry.location.as_ref());
+                if let Some(location) = location {
+                    result.insert(location.to_string(), quest.name.to_string());
+                }
+            }
+        }
+
+        result
+    }
+
     pub fn quests_iter(self) -> impl Iterator<Item = (String, QuestState)> {
         self.quests.into_iter()
     }
@@ -200,6 +266,17 @@ impl QuestState {
         self.entries.push((entry.to_string(), state));
     }
 
+    fn complete_open_entries(&mut self) {
+        for (_, ref mut state) in self.entries.iter_mut() {
+            match state {
+                QuestEntryState::Visible | QuestEntryState::Active => {
+                    *state = QuestEntryState::Complete;
+                }
+                _ => (),
+            }
+        }
+    }
+
     pub fn state(&self) -> QuestEntryState {
         self.state
     }
@@ -0,0 +1,551 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Recording and playback of player commands.  Whenever a game is loaded or
+//! saved, the random number generator is reseeded and a new recording is
+//! started from that save.  The recording holds the elapsed time of every
+//! game update along with the player commands issued between updates, and is
+//! written next to the save, with the `replay` extension.  New updates and
+//! commands are appended to the file every few seconds while playing, and a
+//! final time when the next save is made or the game is exited, so a crash
+//! loses at most the last few seconds.
+//!
+//! A replay is played back by loading its save, reseeding with the recorded
+//! seed, and feeding the recorded update times and commands back through the
+//! game state.  Once playback completes, a checksum of the party state is
+//! compared against the one recorded, to detect rules changes that alter
+//! the outcome.
+//!
+//! Scripts draw their random numbers from the combat roll generator, via
+//! `math.random`, so they replay identically as well.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{Error, Write};
+use std::path::{Path, PathBuf};
+
+use crate::script::ScriptItemKind;
+use crate::GameState;
+use sulis_core::serde_json;
+use sulis_core::util::{self, invalid_data_error};
+use sulis_module::{AttributeList, QuickSlot, Slot};
+
+/// The number of game updates between each write of the current recording
+const FLUSH_FRAMES: usize = 300;
+
+/// A single player command, as issued through the UI
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum ReplayCommand {
+    /// Selects the party members with the specified entity indices
+    Select {
+        indices: Vec<usize>,
+    },
+
+    /// Fires the action at the specified area position, such as a move,
+    /// attack, or interaction with a prop or transition
+    Action {
+        x: f32,
+        y: f32,
+    },
+
+    /// Activates the current targeter at the specified area position
+    Target {
+        x: i32,
+        y: i32,
+    },
+
+    /// Cancels the current targeter
+    CancelTarget,
+
+    /// Activates or toggles an ability of the specified entity
+    Ability {
+        entity: usize,
+        ability: String,
+    },
+
+    /// Uses an item on behalf of the specified entity
+    UseItem {
+        entity: usize,
+        item: ScriptItemKind,
+    },
+
+    EndTurn,
+
+    /// Selects the response at the specified index, counting only the
+    /// viewable responses, in the open conversation
+    Response {
+        index: usize,
+    },
+
+    /// Takes the stack, or a single item when `one` is set, at `index` in
+    /// the specified prop's inventory
+    TakeItem {
+        prop: usize,
+        index: usize,
+        one: bool,
+    },
+
+    /// Takes every item in the specified prop's inventory
+    TakeAll {
+        prop: usize,
+    },
+
+    /// Moves the stash item at `index` into party storage
+    StoreItem {
+        index: usize,
+    },
+
+    /// Moves the party storage item at `index` into the stash
+    RetrieveItem {
+        index: usize,
+    },
+
+    /// Splits the stash stack at `index` in two
+    SplitStack {
+        index: usize,
+    },
+
+    /// Equips the stash item at `index` on the specified entity
+    EquipItem {
+        entity: usize,
+        index: usize,
+    },
+
+    /// Moves the item in the specified slot of the entity to the stash
+    UnequipItem {
+        entity: usize,
+        slot: Slot,
+    },
+
+    /// Drops the stash item at `index` into the specified prop, or on the
+    /// ground at the entity's feet when there is no prop
+    DropItem {
+        entity: usize,
+        index: usize,
+        prop: Option<usize>,
+    },
+
+    /// Unequips the item in `slot` and drops it as with `DropItem`
+    UnequipAndDropItem {
+        entity: usize,
+        slot: Slot,
+        prop: Option<usize>,
+    },
+
+    /// Moves the stash item at `index` into a quick slot of the entity
+    SetQuickSlot {
+        entity: usize,
+        index: usize,
+    },
+
+    /// Moves the item in the quick slot of the entity back to the stash
+    ClearQuickSlot {
+        entity: usize,
+        slot: QuickSlot,
+    },
+
+    /// Buys the item, or as much of the stack as the party can afford
+    /// when `stack` is set, at `index` from the merchant
+    BuyItem {
+        merchant: String,
+        index: usize,
+        stack: bool,
+    },
+
+    /// Sells the item, or the whole stack when `stack` is set, at `index`
+    /// in the stash to the merchant
+    SellItem {
+        merchant: String,
+        index: usize,
+        stack: bool,
+    },
+
+    /// Repairs the item in `slot` of the entity at the merchant
+    RepairItem {
+        entity: usize,
+        merchant: String,
+        slot: Slot,
+    },
+
+    /// Rests in the current area, after any confirmation of making camp
+    Rest,
+
+    /// Travels to the world map location with the specified ID, taking
+    /// `hours` of game time
+    Travel {
+        location: String,
+        hours: u32,
+    },
+
+    /// Levels up the specified entity in `class`, with the chosen
+    /// abilities and new attributes
+    LevelUp {
+        entity: usize,
+        class: String,
+        abilities: Vec<String>,
+        attributes: AttributeList,
+    },
+}
+
+/// The first line of a replay file, describing where the replay starts
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ReplayHeader {
+    save: PathBuf,
+    seed: u64,
+    #[serde(default)]
+    restore_combat_rand: bool,
+}
+
+/// Each line of a replay file after the header.  Entries are appended as
+/// the recording progresses, so the file is never rewritten
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+enum ReplayEntry {
+    /// The elapsed millis passed to each of a run of game updates
+    Frames(Vec<u32>),
+
+    /// A command along with the number of updates that preceded it
+    Command(usize, ReplayCommand),
+
+    /// The party state checksum after all preceding updates.  Only the
+    /// last checksum in the file is used
+    Checksum(String),
+}
+
+#[derive(Debug)]
+pub struct Replay {
+    /// The path of the save this replay starts from
+    pub save: PathBuf,
+    pub seed: u64,
+
+    /// Whether the combat roll state stored in the save is restored when
+    /// it is loaded, rather than reseeded
+    restore_combat_rand: bool,
+
+    /// The elapsed millis passed to each game update.  While recording,
+    /// only those not yet written
+    frames: Vec<u32>,
+
+    /// Each command along with the number of updates that preceded it.
+    /// While recording, only those not yet written
+    commands: Vec<(usize, ReplayCommand)>,
+
+    /// The party state checksum at the end of the recording
+    checksum: String,
+}
+
+enum ReplayMode {
+    Off,
+    Recording {
+        replay: Replay,
+
+        /// The total number of frames recorded
+        frame: usize,
+
+        /// Whether the replay file has been created
+        started: bool,
+    },
+    Playback {
+        replay: Replay,
+        frame: usize,
+        command: usize,
+    },
+}
+
+thread_local! {
+    static MODE: RefCell<ReplayMode> = RefCell::new(ReplayMode::Off);
+}
+
+fn replay_path(save: &Path) -> PathBuf {
+    save.with_extension("replay")
+}
+
+/// Removes the replay recorded from the save at `save`, if there is one
+pub(crate) fn delete_replay(save: &Path) {
+    let path = replay_path(save);
+    if !path.is_file() {
+        return;
+    }
+
+    if let Err(e) = fs::remove_file(&path) {
+        warn!("Unable to remove replay {:?}", path);
+        warn!("{}", e);
+    }
+}
+
+/// Finishes any current recording and starts a new one from the save at
+/// `save`.  `restore_combat_rand` is whether the game continues from the
+/// combat roll state in the save.  Does nothing during playback, which has
+/// already been seeded
+pub(crate) fn start_recording(save: &Path, restore_combat_rand: bool) {
+    if is_playing() {
+        return;
+    }
+
+    finish_recording();
+
+    let seed = util::new_rand_seed();
+    util::reseed_rand(seed);
+    let replay = Replay {
+        save: save.to_path_buf(),
+        seed,
+        restore_combat_rand,
+        frames: Vec::new(),
+        commands: Vec::new(),
+        checksum: String::new(),
+    };
+    MODE.with(|m| {
+        *m.borrow_mut() = ReplayMode::Recording {
+            replay,
+            frame: 0,
+            started: false,
+        }
+    });
+}
+
+/// Stops the current recording or playback.  The rest of a recording is
+/// written next to its save, unless it has no commands
+pub fn finish_recording() {
+    let (mut replay, mut started) = match MODE.with(|m| m.replace(ReplayMode::Off)) {
+        ReplayMode::Recording {
+            replay, started, ..
+        } => (replay, started),
+        _ => return,
+    };
+
+    write_replay(&mut replay, &mut started);
+}
+
+/// Appends the unwritten frames and commands of the `replay` to the file
+/// next to its save, along with the current state checksum.  The file is
+/// created, with its header, on the first write.  Nothing is written until
+/// the replay has a command
+fn write_replay(replay: &mut Replay, started: &mut bool) {
+    if !*started && replay.commands.is_empty() {
+        return;
+    }
+
+    let path = replay_path(&replay.save);
+    let mut lines = Vec::new();
+    if !*started {
+        let header = ReplayHeader {
+            save: replay.save.clone(),
+            seed: replay.seed,
+            restore_combat_rand: replay.restore_combat_rand,
+        };
+        lines.push(serde_json::to_string(&header));
+    }
+
+    let frames = std::mem::take(&mut replay.frames);
+    let commands = std::mem::take(&mut replay.commands);
+    let num_commands = commands.len();
+    lines.push(serde_json::to_string(&ReplayEntry::Frames(frames)));
+    for (frame, command) in commands {
+        lines.push(serde_json::to_string(&ReplayEntry::Command(frame, command)));
+    }
+    let checksum = ReplayEntry::Checksum(state_checksum());
+    lines.push(serde_json::to_string(&checksum));
+
+    let mut data = String::new();
+    for line in lines {
+        match line {
+            Ok(line) => {
+                data.push_str(&line);
+                data.push('\n');
+            }
+            Err(e) => {
+                warn!("Unable to serialize replay");
+                warn!("{}", e);
+                return;
+            }
+        }
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(*started)
+        .truncate(!*started)
+        .open(&path);
+    if let Err(e) = file.and_then(|mut file| file.write_all(data.as_bytes())) {
+        warn!("Unable to write replay to {:?}", path);
+        warn!("{}", e);
+        return;
+    }
+    *started = true;
+
+    debug!("Wrote {} replay commands to {:?}", num_commands, path);
+}
+
+/// Records the specified player command, if a recording is active
+pub fn record(command: ReplayCommand) {
+    MODE.with(|m| {
+        if let ReplayMode::Recording { replay, frame, .. } = &mut *m.borrow_mut() {
+            replay.commands.push((*frame, command));
+        }
+    });
+}
+
+pub fn is_playing() -> bool {
+    MODE.with(|m| matches!(*m.borrow(), ReplayMode::Playback { .. }))
+}
+
+/// During playback, whether the combat roll state in the save should be
+/// restored, matching the recording.  None when not playing back
+pub(crate) fn restore_combat_rand() -> Option<bool> {
+    MODE.with(|m| match &*m.borrow() {
+        ReplayMode::Playback { replay, .. } => Some(replay.restore_combat_rand),
+        _ => None,
+    })
+}
+
+pub fn read_replay(path: &Path) -> Result<Replay, Error> {
+    let data = fs::read_to_string(path)?;
+    let mut lines = data.lines().filter(|line| !line.trim().is_empty());
+    let header: ReplayHeader = match lines.next() {
+        None => return invalid_data_error(&format!("Replay {:?} is empty", path)),
+        Some(line) => serde_json::from_str(line)?,
+    };
+    if !header.save.is_file() {
+        return invalid_data_error(&format!("Replay save {:?} does not exist", header.save));
+    }
+
+    let mut replay = Replay {
+        save: header.save,
+        seed: header.seed,
+        restore_combat_rand: header.restore_combat_rand,
+        frames: Vec::new(),
+        commands: Vec::new(),
+        checksum: String::new(),
+    };
+    for line in lines {
+        match serde_json::from_str(line)? {
+            ReplayEntry::Frames(frames) => replay.frames.extend(frames),
+            ReplayEntry::Command(frame, command) => replay.commands.push((frame, command)),
+            ReplayEntry::Checksum(checksum) => replay.checksum = checksum,
+        }
+    }
+    Ok(replay)
+}
+
+/// Begins playback of the `replay`.  This should be called just before its
+/// save is loaded, so that the load uses the recorded seed
+pub fn start_playback(replay: Replay) {
+    finish_recording();
+
+    info!(
+        "Playing back {} commands over {} updates from {:?}",
+        replay.commands.len(),
+        replay.frames.len(),
+        replay.save
+    );
+    util::reseed_rand(replay.seed);
+    MODE.with(|m| {
+        *m.borrow_mut() = ReplayMode::Playback {
+            replay,
+            frame: 0,
+            command: 0,
+        }
+    });
+}
+
+/// Returns the recorded commands that are due to be executed before the
+/// next game update
+pub fn take_commands() -> Vec<ReplayCommand> {
+    MODE.with(|m| match &mut *m.borrow_mut() {
+        ReplayMode::Playback {
+            replay,
+            frame,
+            command,
+        } => {
+            let mut commands = Vec::new();
+            while let Some((cmd_frame, cmd)) = replay.commands.get(*command) {
+                if *cmd_frame > *frame {
+                    break;
+                }
+                commands.push(cmd.clone());
+                *command += 1;
+            }
+            commands
+        }
+        _ => Vec::new(),
+    })
+}
+
+/// Called at the start of each game update with the real elapsed `millis`.
+/// Returns the millis the update should use, which are the recorded millis
+/// during playback
+pub(crate) fn on_update(millis: u32) -> u32 {
+    let (millis, complete) = MODE.with(|m| match &mut *m.borrow_mut() {
+        ReplayMode::Off => (millis, None),
+        ReplayMode::Recording {
+            replay,
+            frame,
+            started,
+        } => {
+            if replay.frames.len() >= FLUSH_FRAMES {
+                write_replay(replay, started);
+            }
+            replay.frames.push(millis);
+            *frame += 1;
+            (millis, None)
+        }
+        ReplayMode::Playback { replay, frame, .. } => match replay.frames.get(*frame) {
+            Some(recorded) => {
+                *frame += 1;
+                (*recorded, None)
+            }
+            None => (millis, Some(replay.checksum.clone())),
+        },
+    });
+
+    if let Some(checksum) = complete {
+        MODE.with(|m| *m.borrow_mut() = ReplayMode::Off);
+        if checksum == state_checksum() {
+            info!("Replay playback complete, the final state matches the recording");
+        } else {
+            warn!("Replay playback complete, but the final state differs from the recording");
+        }
+    }
+
+    millis
+}
+
+/// A hash of the current area and the location, hit points, and experience
+/// of each party member
+fn state_checksum() -> String {
+    let mut hasher = DefaultHasher::new();
+    GameState::area_state()
+        .borrow()
+        .area
+        .area
+        .id
+        .hash(&mut hasher);
+    GameState::party_coins().hash(&mut hasher);
+    for member in GameState::party() {
+        let member = member.borrow();
+        member.actor.actor.id.hash(&mut hasher);
+        member.location.x.hash(&mut hasher);
+        member.location.y.hash(&mut hasher);
+        member.actor.hp().hash(&mut hasher);
+        member.actor.xp().hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
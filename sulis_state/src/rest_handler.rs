@@ -0,0 +1,172 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::rc::Rc;
+
+use sulis_core::util::gen_rand;
+use sulis_module::{area::OnRest, Module, Time};
+
+use crate::script::{Script, ScriptEntity};
+use crate::GameState;
+
+/// The outcome of an attempt to camp and rest
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RestResult {
+    /// The party rested for the full duration
+    Rested,
+
+    /// The rest was interrupted by an encounter
+    Interrupted,
+
+    /// Camping is not possible in this area, or during combat
+    NotAllowed,
+
+    /// The party does not have enough camp supplies
+    NoSupplies,
+}
+
+/// Returns the number of camp supply items held in the party stash, or
+/// None if resting does not require supplies
+pub fn supplies_available() -> Option<u32> {
+    let rules = Module::rules();
+    let id = rules.rest.as_ref()?.supply_item.as_ref()?;
+
+    let stash = GameState::party_stash();
+    let stash = stash.borrow();
+    let count = stash
+        .items()
+        .iter()
+        .filter(|(_, item)| item.item.id == *id)
+        .map(|(qty, _)| *qty)
+        .sum();
+    Some(count)
+}
+
+fn consume_supplies(id: &str, amount: u32) {
+    let stash = GameState::party_stash();
+    let mut stash = stash.borrow_mut();
+    for _ in 0..amount {
+        let index = match stash.items().iter().position(|(_, item)| item.item.id == id) {
+            None => return,
+            Some(index) => index,
+        };
+        let _ = stash.remove_item(index);
+    }
+}
+
+fn hours(hour: u32) -> Time {
+    Time {
+        day: 0,
+        hour,
+        round: 0,
+        millis: 0,
+    }
+}
+
+/// Returns true if the current area allows the party to camp, and
+/// combat is not active
+pub fn can_camp() -> bool {
+    if GameState::is_combat_active() {
+        return false;
+    }
+
+    let area_state = GameState::area_state();
+    let area_state = area_state.borrow();
+    matches!(area_state.area.area.on_rest, OnRest::Camp { .. })
+}
+
+/// Attempts to camp and rest in the current area, consuming camp supplies.
+/// A successful rest restores the party's hit points and ability uses,
+/// removes injuries, and advances the clock.  The rest may instead be
+/// interrupted partway through by one of the area's rest encounters
+pub fn rest() -> RestResult {
+    if GameState::is_combat_active() {
+        return RestResult::NotAllowed;
+    }
+
+    let area_state = GameState::area_state();
+    let area = Rc::clone(&area_state.borrow().area.area);
+    let (chance, encounters) = match area.on_rest {
+        OnRest::Camp {
+            interrupt_chance,
+            ref interrupt_encounters,
+        } => (interrupt_chance, interrupt_encounters),
+        _ => return RestResult::NotAllowed,
+    };
+
+    let rules = Module::rules();
+    let rest = match rules.rest {
+        None => {
+            warn!("Area '{}' allows camping but no rest rules are defined", area.id);
+            return RestResult::NotAllowed;
+        }
+        Some(ref rest) => rest,
+    };
+
+    if let Some(ref id) = rest.supply_item {
+        if supplies_available().unwrap_or(0) < rest.supplies_per_rest {
+            return RestResult::NoSupplies;
+        }
+        consume_supplies(id, rest.supplies_per_rest);
+    }
+
+    let mgr = GameState::turn_manager();
+    let campaign = Module::campaign();
+    let player = GameState::player();
+
+    if !encounters.is_empty() && gen_rand(0, 100) < chance {
+        // the interruption comes partway through the rest
+        mgr.borrow_mut().add_time(hours(gen_rand(0, rest.hours.max(1))));
+
+        let point = encounters[gen_rand(0, encounters.len())];
+        info!("Rest interrupted by encounter at {:?}", point);
+        area_state.borrow_mut().spawn_encounter_at(point.x, point.y);
+
+        if let Some(ref script) = campaign.on_rest_interrupted_script {
+            Script::trigger(&script.id, &script.func, ScriptEntity::from(&player));
+        }
+
+        mgr.borrow_mut()
+            .check_ai_activation_for_party(&mut area_state.borrow_mut());
+        return RestResult::Interrupted;
+    }
+
+    for member in GameState::party() {
+        member.borrow_mut().actor.init_day();
+
+        let injuries = match rules.injuries {
+            None => continue,
+            Some(ref injuries) => injuries,
+        };
+
+        let member = member.borrow();
+        let mut mgr = mgr.borrow_mut();
+        for index in member.actor.effects_iter() {
+            let effect = mgr.effect_mut(*index);
+            if effect.has_tag(&injuries.tag) {
+                effect.mark_for_removal();
+            }
+        }
+    }
+
+    mgr.borrow_mut().add_time(hours(rest.hours));
+
+    if let Some(ref script) = campaign.on_rest_script {
+        Script::trigger(&script.id, &script.func, ScriptEntity::from(&player));
+    }
+
+    RestResult::Rested
+}
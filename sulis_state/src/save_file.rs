@@ -14,17 +14,27 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{Error, Read};
+use std::hash::{Hash, Hasher};
+use std::io::{Error, Read, Write};
 use std::path::{Path, PathBuf};
 use std::time;
 
 use chrono::prelude::*;
 
-use crate::{GameState, SaveState};
-use sulis_core::resource::{read_single_resource_path, write_json_to_file};
-use sulis_core::util::invalid_data_error;
+use crate::save_state::AreaSaveState;
+use crate::{replay, GameState, SaveState};
+use sulis_core::extern_image::{self, imageops};
+use sulis_core::io::Screenshot;
+use sulis_core::config::Config;
+use sulis_core::resource::read_single_resource_path;
+use sulis_core::util::{invalid_data_error, ActiveResources};
 use sulis_core::{config, serde_json, util};
+use sulis_module::modification::{get_active_modifications, get_available_modifications};
 use sulis_module::Module;
 
 #[derive(Serialize, Deserialize)]
@@ -45,7 +55,7 @@ impl SaveFile {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct SaveFileMetaData {
     pub player_name: String,
@@ -56,6 +66,46 @@ pub struct SaveFileMetaData {
     pub datetime: String,
     pub current_area_name: String,
 
+    /// The mods that were active when the game was saved.  This is `None`
+    /// for saves from before mods were recorded
+    #[serde(default)]
+    pub mods: Option<Vec<SavedMod>>,
+
+    /// A name for the save given by the player
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The time of the save in seconds since the unix epoch, used for sorting.
+    /// Saves from before this was recorded use the file modification time
+    #[serde(default)]
+    pub timestamp: i64,
+
+    /// The file name of the thumbnail screenshot, in the same directory as
+    /// the save file
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+
+    /// The portrait image IDs of each party member that has one
+    #[serde(default)]
+    pub portraits: Vec<String>,
+
+    /// The total game time elapsed, in milliseconds
+    #[serde(default)]
+    pub play_time_millis: Option<usize>,
+
+    /// The name of the quest the player is currently tracking
+    #[serde(default)]
+    pub current_quest: Option<String>,
+
+    /// Whether this is the save slot of an ironman game
+    #[serde(default)]
+    pub ironman: bool,
+
+    /// Whether this ironman game was abandoned by quitting during combat,
+    /// in which case it can no longer be loaded
+    #[serde(default)]
+    pub abandoned: bool,
+
     #[serde(skip)]
     path: PathBuf,
 
@@ -63,31 +113,528 @@ pub struct SaveFileMetaData {
     pub error: Option<String>,
 }
 
+/// A mod that was active when a game was saved
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SavedMod {
+    pub id: String,
+    pub name: String,
+}
+
+/// The mods to activate in order to load a save
+pub struct SaveModStatus {
+    /// The directories of the installed mods used by the save, in load order
+    pub dirs: Vec<String>,
+
+    /// The names of mods used by the save that are not installed
+    pub missing: Vec<String>,
+
+    /// Whether `dirs` differs from the currently active mods, in which case
+    /// resources must be reloaded before loading the save
+    pub changed: bool,
+}
+
+impl SaveFileMetaData {
+    /// The name given to this save, or the player name if there is none
+    pub fn display_name(&self) -> &str {
+        match &self.name {
+            None => &self.player_name,
+            Some(name) => name,
+        }
+    }
+
+    /// Reads the thumbnail screenshot of this save, if it has one
+    pub fn load_thumbnail(&self) -> Option<Screenshot> {
+        let path = self.path.with_file_name(self.thumbnail.as_ref()?);
+        match extern_image::open(&path) {
+            Ok(image) => Some(image.to_rgba8()),
+            Err(e) => {
+                warn!("Unable to read save thumbnail {:?}", path);
+                warn!("{}", e);
+                None
+            }
+        }
+    }
+
+    pub fn mod_status(&self) -> SaveModStatus {
+        let active = ActiveResources::read().mods;
+        let saved = match &self.mods {
+            None => {
+                return SaveModStatus {
+                    dirs: active,
+                    missing: Vec::new(),
+                    changed: false,
+                }
+            }
+            Some(saved) => saved,
+        };
+
+        let available = get_available_modifications();
+        let mut dirs = Vec::new();
+        let mut missing = Vec::new();
+        for saved_mod in saved.iter() {
+            match available.iter().find(|modif| modif.id == saved_mod.id) {
+                None => missing.push(saved_mod.name.to_string()),
+                Some(modif) => dirs.push(modif.dir.to_string()),
+            }
+        }
+
+        let changed = dirs != active;
+        SaveModStatus {
+            dirs,
+            missing,
+            changed,
+        }
+    }
+}
+
+/// Provides the directory that saves are stored in.  The default provider may
+/// be replaced with `set_save_path_provider`, for example to store saves in a
+/// location managed by a cloud sync service.
+pub trait SavePathProvider {
+    /// The root save directory.  Saves for each campaign are stored in a
+    /// subdirectory named by the campaign ID
+    fn save_root(&self) -> PathBuf;
+}
+
+/// Uses the save directory from the config if one is set, and otherwise
+/// the platform data directory
+pub struct DefaultSavePathProvider;
+
+impl SavePathProvider for DefaultSavePathProvider {
+    fn save_root(&self) -> PathBuf {
+        if let Some(dir) = Config::save_directory() {
+            return dir;
+        }
+
+        let mut path = config::platform_data_dir();
+        path.push("save");
+
+        // saves were previously kept in the user config directory, keep
+        // using it if it has saves and the data directory does not
+        let mut legacy = config::USER_DIR.clone();
+        legacy.push("save");
+        if !path.is_dir() && legacy.is_dir() {
+            return legacy;
+        }
+
+        path
+    }
+}
+
+thread_local! {
+    static SAVE_PATH_PROVIDER: RefCell<Box<dyn SavePathProvider>> =
+        RefCell::new(Box::new(DefaultSavePathProvider));
+}
+
+pub fn set_save_path_provider(provider: Box<dyn SavePathProvider>) {
+    SAVE_PATH_PROVIDER.with(|p| *p.borrow_mut() = provider);
+}
+
 fn get_save_dir() -> PathBuf {
-    let mut path = config::USER_DIR.clone();
-    path.push("save");
+    let mut path = SAVE_PATH_PROVIDER.with(|p| p.borrow().save_root());
     path.push(&Module::campaign().id);
     path
 }
 
+fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(".");
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn backup_path(path: &Path, index: u32) -> PathBuf {
+    path_with_suffix(path, &format!("bak{index}"))
+}
+
+/// The paths of the existing backups of the file at `path`, newest first
+fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|index| backup_path(path, index))
+        .take_while(|path| path.is_file())
+        .collect()
+}
+
+/// Writes `data` to the file at `path`.  The data is first fully written to a
+/// temporary file, which is then renamed over the target, so an interrupted
+/// write never leaves a partially written file in place.  If the file already
+/// exists, up to `backups` of its previous versions are kept.
+fn write_atomic(path: &Path, data: &[u8], backups: u32) -> Result<(), Error> {
+    let temp_path = path_with_suffix(path, "tmp");
+    {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    if backups > 0 && path.is_file() {
+        if let Err(e) = rotate_backups(path, backups) {
+            warn!("Unable to back up {:?}", path);
+            warn!("{}", e);
+        }
+    }
+
+    fs::rename(&temp_path, path)
+}
+
+fn rotate_backups(path: &Path, backups: u32) -> Result<(), Error> {
+    for index in (1..backups).rev() {
+        let from = backup_path(path, index);
+        if from.is_file() {
+            fs::rename(&from, backup_path(path, index + 1))?;
+        }
+    }
+
+    // copy rather than move, so the target is never missing
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+fn write_save_file(path: &Path, save_file: &SaveFile) -> Result<(), Error> {
+    let data = match serde_json::to_vec(save_file) {
+        Ok(data) => data,
+        Err(e) => return invalid_data_error(&format!("{e}")),
+    };
+
+    // backups of an ironman save would allow reverting to an earlier point
+    let backups = if save_file.meta.ironman {
+        0
+    } else {
+        Config::save_backups()
+    };
+    write_atomic(path, &data, backups)
+}
+
+fn get_area_save_dir() -> PathBuf {
+    let mut path = get_save_dir();
+    path.push("areas");
+    path
+}
+
+fn area_save_path(dir: &Path, hash: &str) -> PathBuf {
+    let mut path = dir.to_path_buf();
+    path.push(format!("{hash}.json"));
+    path
+}
+
+/// Thumbnails are scaled down to fit within this size
+const THUMBNAIL_SIZE: (u32, u32) = (192, 108);
+
 pub fn delete_save(save_file: &SaveFileMetaData) -> Result<(), Error> {
     let path = save_file.path.as_path();
-    fs::remove_file(path)
+    fs::remove_file(path)?;
+
+    for backup in backup_paths(path) {
+        if let Err(e) = fs::remove_file(&backup) {
+            warn!("Unable to remove save backup {:?}", backup);
+            warn!("{}", e);
+        }
+    }
+
+    if let Some(thumbnail) = &save_file.thumbnail {
+        if let Err(e) = fs::remove_file(path.with_file_name(thumbnail)) {
+            warn!("Unable to remove save thumbnail {}", thumbnail);
+            warn!("{}", e);
+        }
+    }
+
+    replay::delete_replay(path);
+
+    remove_unreferenced_area_saves();
+    Ok(())
+}
+
+/// Removes any area saves in the area save directory that are no longer
+/// referenced by at least one save file or save backup.  This reads every
+/// save, so it is only done on manual saves and deletes, and when quitting an
+/// ironman game.
+pub fn remove_unreferenced_area_saves() {
+    let dir = get_area_save_dir();
+    if !dir.is_dir() {
+        return;
+    }
+
+    let mut referenced = HashSet::new();
+    let paths = save_file_paths().into_iter().flat_map(|path| {
+        let backups = backup_paths(&path);
+        std::iter::once(path).chain(backups)
+    });
+    for path in paths {
+        match read_save_file(&path) {
+            Ok(save_file) => referenced.extend(save_file.state.area_refs.into_values()),
+            Err(e) => {
+                // don't risk deleting area data that an unreadable save needs
+                warn!("Unable to read save file: {}", path.to_string_lossy());
+                warn!("{}", e);
+                return;
+            }
+        }
+    }
+
+    let dir_entries = match fs::read_dir(&dir) {
+        Err(_) => return,
+        Ok(entries) => entries,
+    };
+
+    for entry in dir_entries.flatten() {
+        let path = entry.path();
+        let hash = match path.file_stem() {
+            None => continue,
+            Some(stem) => stem.to_string_lossy().to_string(),
+        };
+
+        if referenced.contains(&hash) {
+            continue;
+        }
+
+        debug!("Removing unreferenced area save {:?}", path);
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Unable to remove area save {:?}", path);
+            warn!("{}", e);
+        }
+    }
+}
+
+/// Sets the player given name of the specified save, rewriting the save file.
+/// An empty `name` clears it.
+pub fn rename_save(save_file: &mut SaveFileMetaData, name: &str) -> Result<(), Error> {
+    let name = name.trim();
+    let name = if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    };
+
+    let path = save_file.path.clone();
+    let mut data = read_save_file(&path)?;
+    data.meta.name = name.clone();
+    write_save_file(&path, &data)?;
+
+    save_file.name = name;
+    Ok(())
 }
 
 pub fn load_state(save_file: &SaveFileMetaData) -> Result<SaveState, Error> {
-    let path = save_file.path.as_path();
+    if save_file.abandoned {
+        return invalid_data_error("The ironman game in this save was abandoned");
+    }
+
+    load_state_at(save_file.path.as_path())
+}
+
+/// Reads the save state from the save file at `path`
+pub fn load_state_at(path: &Path) -> Result<SaveState, Error> {
     let save_file: SaveFile = read_single_resource_path(path)?;
+    let mut state = save_file.state;
 
-    Ok(save_file.state)
+    let dir = get_area_save_dir();
+    for (id, hash) in state.area_refs.iter() {
+        let path = area_save_path(&dir, hash);
+        let area_save: AreaSaveState = match read_single_resource_path(&path) {
+            Ok(area_save) => area_save,
+            Err(e) => {
+                return invalid_data_error(&format!(
+                    "Unable to read save data for area '{id}' from {path:?}: {e}"
+                ));
+            }
+        };
+        state.areas.insert(id.to_string(), area_save);
+    }
+
+    state.save_path = Some(path.to_path_buf());
+    Ok(state)
 }
 
-pub fn create_save() -> Result<(), Error> {
-    let start_time = time::Instant::now();
-    info!("Start save");
+/// Writes the save data for each area that has changed since it was last
+/// saved.  Areas are written to the area save directory, named by a hash of
+/// their content, so unchanged areas are written only once and shared by
+/// every save that references them.  Returns the map of area IDs to hashes.
+fn write_area_saves() -> Result<HashMap<String, String>, Error> {
+    let dir = get_area_save_dir();
+    if !dir.is_dir() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    let mut area_refs = HashMap::new();
+    let mut written = 0;
+    for id in GameState::area_state_ids() {
+        let area_state = match GameState::get_area_state(&id) {
+            None => continue,
+            Some(area_state) => area_state,
+        };
+
+        let prev_hash = area_state.borrow().save_hash().map(|h| h.to_string());
+        if let Some(hash) = prev_hash {
+            if area_save_path(&dir, &hash).is_file() {
+                area_refs.insert(id, hash);
+                continue;
+            }
+        }
+
+        let area_save = AreaSaveState::new(id.to_string());
+        let data = match serde_json::to_string(&area_save) {
+            Ok(data) => data,
+            Err(e) => return invalid_data_error(&format!("{e}")),
+        };
+
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let hash = format!("{:016x}", hasher.finish());
+
+        let path = area_save_path(&dir, &hash);
+        if !path.is_file() {
+            write_atomic(&path, data.as_bytes(), 0)?;
+            written += 1;
+        }
+
+        area_state.borrow_mut().set_save_hash(hash.clone());
+        area_refs.insert(id, hash);
+    }
+
+    info!(
+        "  Wrote {} of {} areas, the rest are unchanged",
+        written,
+        area_refs.len()
+    );
+
+    Ok(area_refs)
+}
+
+/// Writes the current game to a new save file, returning its path.  A
+/// thumbnail may be added afterwards with `attach_thumbnail`.  Not allowed
+/// in ironman games.  Also cleans up area saves no longer referenced by any
+/// save, such as those only replaced autosaves used.
+pub fn create_save() -> Result<Option<PathBuf>, Error> {
+    if GameState::is_ironman() {
+        return invalid_data_error("Manual saves are disabled in ironman mode");
+    }
 
     let utc = Utc::now();
     let filename = format!("save_{}.json", utc.format("%Y%m%d-%H%M%S%.3f"));
+    let path = write_save(&filename, utc)?;
+    remove_unreferenced_area_saves();
+    Ok(path)
+}
+
+/// Writes the current game to the autosave file, replacing any previous
+/// autosave.  Area saves only the old autosave referenced are left until
+/// the next manual save or delete, to keep autosaving quick.
+pub fn create_autosave() -> Result<Option<PathBuf>, Error> {
+    write_save(AUTOSAVE_FILENAME, Utc::now())
+}
+
+const AUTOSAVE_FILENAME: &str = "autosave.json";
+
+/// The file name for the save slot of a new ironman game
+pub(crate) fn new_ironman_slot() -> String {
+    format!("ironman_{}.json", Utc::now().format("%Y%m%d-%H%M%S%.3f"))
+}
+
+/// Writes the current ironman game to its save slot, replacing the
+/// previous save
+pub fn create_ironman_save() -> Result<Option<PathBuf>, Error> {
+    let slot = match GameState::ironman_slot() {
+        None => return invalid_data_error("The current game is not an ironman game"),
+        Some(slot) => slot,
+    };
+
+    write_save(&slot, Utc::now())
+}
+
+/// Marks the save slot of the current ironman game as abandoned, so that it
+/// cannot be loaded again.  Used when quitting during combat, which could
+/// otherwise be used to escape a losing fight
+pub fn abandon_ironman_save() -> Result<(), Error> {
+    let slot = match GameState::ironman_slot() {
+        None => return Ok(()),
+        Some(slot) => slot,
+    };
+
+    let mut path = get_save_dir();
+    path.push(slot);
+    if !path.is_file() {
+        return Ok(());
+    }
+
+    let mut data = read_save_file(&path)?;
+    data.meta.abandoned = true;
+    write_save_file(&path, &data)
+}
+
+/// Deletes the save slot of the current ironman game, if it has been written
+pub fn delete_ironman_save() -> Result<(), Error> {
+    let slot = match GameState::ironman_slot() {
+        None => return Ok(()),
+        Some(slot) => slot,
+    };
+
+    let mut path = get_save_dir();
+    path.push(slot);
+    if !path.is_file() {
+        return Ok(());
+    }
+
+    let mut meta = read_save_file(&path)?.meta;
+    meta.path = path;
+    delete_save(&meta)
+}
+
+/// Writes a scaled down copy of the `screenshot` as a png next to the save
+/// at `save_path`, returning the thumbnail file name
+fn write_thumbnail(save_path: &Path, screenshot: &Screenshot) -> Option<String> {
+    let (w, h) = screenshot.dimensions();
+    if w == 0 || h == 0 {
+        return None;
+    }
+
+    let scale = (THUMBNAIL_SIZE.0 as f32 / w as f32).min(THUMBNAIL_SIZE.1 as f32 / h as f32);
+    let thumb_w = ((w as f32 * scale) as u32).max(1);
+    let thumb_h = ((h as f32 * scale) as u32).max(1);
+    let thumbnail = imageops::thumbnail(screenshot, thumb_w, thumb_h);
+
+    let path = save_path.with_extension("png");
+    if let Err(e) = thumbnail.save(&path) {
+        warn!("Unable to write save thumbnail {:?}", path);
+        warn!("{}", e);
+        return None;
+    }
+
+    path.file_name().map(|name| name.to_string_lossy().to_string())
+}
+
+/// Adds a thumbnail of the `screenshot` to the save already written at
+/// `save_path`.  Saves are written immediately, and their thumbnail attached
+/// once the screenshot has been captured on a later frame
+pub fn attach_thumbnail(save_path: &Path, screenshot: &Screenshot) -> Result<(), Error> {
+    let thumbnail = match write_thumbnail(save_path, screenshot) {
+        None => return Ok(()),
+        Some(thumbnail) => thumbnail,
+    };
+
+    let mut data = read_save_file(save_path)?;
+    data.meta.thumbnail = Some(thumbnail);
+
+    let data = match serde_json::to_vec(&data) {
+        Ok(data) => data,
+        Err(e) => return invalid_data_error(&format!("{e}")),
+    };
+
+    // the save was backed up when it was written, so no further backup
+    write_atomic(save_path, &data, 0)
+}
+
+/// Writes the current game to `filename` in the save directory, returning
+/// the path written, or None if saving is skipped during replay playback
+fn write_save(filename: &str, utc: DateTime<Utc>) -> Result<Option<PathBuf>, Error> {
+    // playback must not overwrite the save it started from
+    if replay::is_playing() {
+        info!("Skipping save during replay playback");
+        return Ok(None);
+    }
+
+    let start_time = time::Instant::now();
+    info!("Start save");
 
     let mut path = get_save_dir();
     if !path.is_dir() {
@@ -97,14 +644,15 @@ pub fn create_save() -> Result<(), Error> {
 
     path.push(filename);
 
-    let meta = create_meta_data(utc.format("%c").to_string());
+    let meta = create_meta_data(utc);
 
     info!(
         "  Filename and meta data creation complete in {} secs",
         util::format_elapsed_secs(start_time.elapsed())
     );
 
-    let state = SaveState::create();
+    let area_refs = write_area_saves()?;
+    let state = SaveState::create(area_refs);
 
     let save = SaveFile { meta, state };
 
@@ -113,28 +661,63 @@ pub fn create_save() -> Result<(), Error> {
         util::format_elapsed_secs(start_time.elapsed())
     );
 
-    let result = write_json_to_file(path.as_path(), &save);
+    write_save_file(&path, &save)?;
 
     info!(
         "  Save to disk complete in {} secs",
         util::format_elapsed_secs(start_time.elapsed())
     );
 
-    result
+    // any replay of the overwritten save no longer matches it
+    replay::finish_recording();
+    replay::delete_replay(&path);
+
+    // the game continues with the combat roll state just saved
+    replay::start_recording(&path, true);
+    Ok(Some(path))
 }
 
-fn create_meta_data(datetime: String) -> SaveFileMetaData {
+fn create_meta_data(utc: DateTime<Utc>) -> SaveFileMetaData {
     let cur_area = GameState::area_state();
     let cur_area = cur_area.borrow();
     let player = GameState::player();
     let player = player.borrow();
 
+    let portraits = GameState::party()
+        .iter()
+        .filter_map(|member| member.borrow().actor.actor.portrait.as_ref().map(|p| p.id()))
+        .collect();
+
+    let current_quest = GameState::quest_state()
+        .current_quest()
+        .and_then(|id| Module::quest(id))
+        .map(|quest| quest.name.to_string());
+
+    let play_time_millis = GameState::turn_manager().borrow().total_elapsed_millis();
+
     SaveFileMetaData {
         player_name: player.actor.actor.name.to_string(),
         level: Some(player.actor.actor.total_level),
         class: Some(player.actor.actor.base_class().name.to_string()),
-        datetime,
+        datetime: utc.format("%c").to_string(),
         current_area_name: cur_area.area.area.name.to_string(),
+        mods: Some(
+            get_active_modifications()
+                .into_iter()
+                .map(|modif| SavedMod {
+                    id: modif.id,
+                    name: modif.name,
+                })
+                .collect(),
+        ),
+        name: None,
+        timestamp: utc.timestamp(),
+        thumbnail: None,
+        portraits,
+        play_time_millis: Some(play_time_millis),
+        current_quest,
+        ironman: GameState::is_ironman(),
+        abandoned: false,
         path: Default::default(),
         error: None,
     }
@@ -177,6 +760,28 @@ pub fn has_available_save_files() -> bool {
     false
 }
 
+fn save_file_paths() -> Vec<PathBuf> {
+    let dir_entries = match fs::read_dir(get_save_dir()) {
+        Err(_) => return Vec::new(),
+        Ok(entries) => entries,
+    };
+
+    let mut paths = Vec::new();
+    for entry in dir_entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        match path.extension() {
+            Some(ext) if ext == "json" => paths.push(path),
+            _ => (),
+        }
+    }
+
+    paths
+}
+
 fn read_save_file(path: &Path) -> Result<SaveFile, Error> {
     let mut file = File::open(path)?;
 
@@ -214,6 +819,15 @@ fn create_error_meta(path: PathBuf, error: Error) -> SaveFileMetaData {
         class: None,
         datetime,
         current_area_name: "Unknown Area".to_string(),
+        mods: None,
+        name: None,
+        timestamp: time.timestamp(),
+        thumbnail: None,
+        portraits: Vec::new(),
+        play_time_millis: None,
+        current_quest: None,
+        ironman: false,
+        abandoned: false,
         path,
         error: Some(error.to_string()),
     }
@@ -263,28 +877,28 @@ pub fn get_available_save_files() -> Result<Vec<SaveFileMetaData>, Error> {
 
         let mut meta = save_file.meta;
         meta.path = path_buf;
+        if meta.timestamp == 0 {
+            meta.timestamp = time_modified(&meta);
+        }
 
         results.push(meta);
     }
 
-    results.sort_by(|f1, f2| {
-        let t1 = time_modified(f1);
-        let t2 = time_modified(f2);
-
-        t2.cmp(&t1)
-    });
+    results.sort_by_key(|f| Reverse(f.timestamp));
 
     Ok(results)
 }
 
-fn time_modified(data: &SaveFileMetaData) -> time::SystemTime {
+fn time_modified(data: &SaveFileMetaData) -> i64 {
     let metadata = fs::metadata(data.path.as_path());
 
-    match metadata {
+    let time = match metadata {
         Ok(metadata) => match metadata.modified() {
             Ok(time) => time,
             Err(_) => time::UNIX_EPOCH,
         },
         Err(_) => time::UNIX_EPOCH,
-    }
+    };
+
+    DateTime::<Utc>::from(time).timestamp()
 }
@@ -17,21 +17,25 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::Error;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::u64;
 
-use sulis_core::util::{ExtInt, Point};
+use sulis_core::util::{self, ExtInt, Point, ReproducibleRandom};
 use sulis_module::{
     actor::{ActorBuilder, RewardBuilder},
-    BonusList, ItemListEntrySaveState, ItemSaveState, QuickSlot, Slot,
+    area::Lock,
+    BonusList, Difficulty, Facing, ItemListEntrySaveState, ItemSaveState, LightSource, QuickSlot,
+    Slot,
 };
 
 use crate::animation::AnimSaveState;
 use crate::area_state::TriggerState;
 use crate::script::CallbackData;
 use crate::{
-    effect, prop_state::Interactive, turn_manager::EncounterRef, ActorState, Effect, EntityState,
-    Formation, GameState, Location, MerchantState, PStats, PropState, QuestState, WorldMapState,
+    effect, prop_state::Interactive, turn_manager::EncounterRef, ActorState, CampaignState, Effect,
+    EntityState, FactionState, Formation, GameState, Location, MerchantState, PStats, PatrolState,
+    PropState, QuestState, ThreatTable, TravelState, WorldMapState,
 };
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -41,6 +45,9 @@ pub struct SaveState {
     pub(crate) formation: Formation,
     pub(crate) coins: i32,
     pub(crate) stash: Vec<ItemListEntrySaveState>,
+
+    #[serde(default)]
+    pub(crate) storage: Vec<ItemListEntrySaveState>,
     pub(crate) selected: Vec<usize>,
 
     #[serde(default = "default_zoom")]
@@ -48,12 +55,48 @@ pub struct SaveState {
     pub(crate) current_area: String,
     pub(crate) world_map: WorldMapState,
     pub(crate) quests: QuestSaveState,
+
+    #[serde(default)]
+    pub(crate) factions: FactionState,
+
+    #[serde(default)]
+    pub(crate) difficulty: Difficulty,
+
+    /// The save slot file name, if this is an ironman game
+    #[serde(default)]
+    pub(crate) ironman: Option<String>,
+
+    #[serde(default)]
+    pub(crate) campaign_seed: u64,
+
+    #[serde(default)]
+    pub(crate) seeded_campaign: bool,
+
+    #[serde(default)]
+    pub(crate) campaign: CampaignState,
+
+    #[serde(default)]
+    pub(crate) travel: Option<TravelState>,
+
+    // areas are stored separately, keyed by a hash of their content, so that
+    // unchanged areas are shared between saves.  Older saves store them inline
+    #[serde(default)]
     pub(crate) areas: HashMap<String, AreaSaveState>,
+
+    #[serde(default)]
+    pub(crate) area_refs: HashMap<String, String>,
     pub(crate) manager: ManagerSaveState,
     pub(crate) anims: Vec<AnimSaveState>,
 
     #[serde(default)]
     pub(crate) total_elapsed_millis: usize,
+
+    #[serde(default)]
+    pub(crate) combat_rand: Option<RandSaveState>,
+
+    /// The save file this state was read from, where replays are recorded
+    #[serde(skip)]
+    pub(crate) save_path: Option<PathBuf>,
 }
 
 fn default_zoom() -> f32 {
@@ -61,13 +104,10 @@ fn default_zoom() -> f32 {
 }
 
 impl SaveState {
-    pub fn create() -> SaveState {
-        let mut areas = HashMap::new();
-
-        for id in GameState::area_state_ids() {
-            areas.insert(id.to_string(), AreaSaveState::new(id));
-        }
-
+    /// Creates the save state for the current game.  The areas themselves are
+    /// not included; `area_refs` maps each area ID to the hash of its separately
+    /// written `AreaSaveState`
+    pub fn create(area_refs: HashMap<String, String>) -> SaveState {
         let area_state = GameState::area_state();
         let current_area = area_state.borrow().area.area.id.to_string();
 
@@ -85,7 +125,10 @@ impl SaveState {
         let formation = formation.borrow().clone();
 
         let stash = GameState::party_stash();
-        let stash = stash.borrow().save();
+        let (stash, storage) = {
+            let stash = stash.borrow();
+            (stash.save(), stash.save_storage())
+        };
 
         let quest_state = GameState::quest_state();
         let current_quest = quest_state.current_quest_stack();
@@ -103,7 +146,8 @@ impl SaveState {
         let total_elapsed_millis = mgr.borrow().total_elapsed_millis();
 
         SaveState {
-            areas,
+            areas: HashMap::new(),
+            area_refs,
             current_area,
             party,
             selected,
@@ -111,11 +155,21 @@ impl SaveState {
             formation,
             coins: GameState::party_coins(),
             stash,
+            storage,
             manager: ManagerSaveState::new(),
             anims: GameState::save_anims(),
             world_map: GameState::world_map(),
             quests: quest_state,
+            factions: GameState::faction_state(),
+            difficulty: GameState::difficulty(),
+            ironman: GameState::ironman_slot(),
+            campaign_seed: GameState::campaign_seed(),
+            seeded_campaign: GameState::is_seeded_campaign(),
+            campaign: GameState::campaign_state(),
+            travel: GameState::travel_state(),
             total_elapsed_millis,
+            combat_rand: Some(RandSaveState::new(&util::combat_rand())),
+            save_path: None,
         }
     }
 
@@ -124,6 +178,28 @@ impl SaveState {
     }
 }
 
+/// The state of a `ReproducibleRandom`, so that it continues the same
+/// sequence of values when loaded
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RandSaveState {
+    pub(crate) seed: u64,
+    pub(crate) state: String,
+}
+
+impl RandSaveState {
+    pub fn new(rand: &ReproducibleRandom) -> RandSaveState {
+        RandSaveState {
+            seed: rand.seed() as u64,
+            state: rand.state(),
+        }
+    }
+
+    pub fn load(&self) -> Result<ReproducibleRandom, Error> {
+        ReproducibleRandom::from_state(self.seed as u128, &self.state)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct QuestSaveState {
@@ -138,6 +214,13 @@ pub struct ManagerSaveState {
     pub(crate) effects: Vec<EffectSaveState>,
     pub(crate) cur_ai_group_index: usize,
     pub(crate) ai_groups: HashMap<String, EncounterRef>,
+
+    #[serde(default)]
+    pub(crate) combat_start_suppressed: bool,
+
+    /// The total elapsed millis at which the party fled each AI group
+    #[serde(default)]
+    pub(crate) fled_groups: HashMap<String, usize>,
 }
 
 impl ManagerSaveState {
@@ -165,11 +248,18 @@ impl ManagerSaveState {
             ai_groups.insert(key.to_string(), value.clone());
         }
 
+        let mut fled_groups = HashMap::new();
+        for (key, value) in mgr.fled_groups.iter() {
+            fled_groups.insert(key.to_string(), *value);
+        }
+
         ManagerSaveState {
             entities,
             effects,
             cur_ai_group_index,
             ai_groups,
+            combat_start_suppressed: mgr.combat_start_suppressed,
+            fled_groups,
         }
     }
 }
@@ -180,6 +270,13 @@ pub struct EffectSaveState {
     pub(crate) index: usize,
     pub(crate) name: String,
     pub(crate) tag: String,
+
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+
+    #[serde(default)]
+    pub(crate) stacking: effect::StackingPolicy,
+
     pub(crate) cur_duration: u32,
     pub(crate) total_duration: ExtInt,
     pub(crate) deactivate_with_ability: Option<String>,
@@ -193,6 +290,9 @@ pub struct EffectSaveState {
 
     #[serde(default = "default_true")]
     pub(crate) ui_visible: bool,
+
+    #[serde(default)]
+    pub(crate) summons: Vec<usize>,
 }
 
 fn default_true() -> bool {
@@ -211,6 +311,8 @@ impl EffectSaveState {
             index,
             name: effect.name.to_string(),
             tag: effect.tag.to_string(),
+            tags: effect.tags.clone(),
+            stacking: effect.stacking,
             cur_duration: effect.cur_duration,
             total_duration: effect.total_duration,
             deactivate_with_ability: effect.deactivate_with_ability.clone(),
@@ -220,6 +322,7 @@ impl EffectSaveState {
             callbacks,
             icon: effect.icon.clone(),
             ui_visible: effect.ui_visible,
+            summons: effect.summons.clone(),
         }
     }
 }
@@ -298,6 +401,12 @@ pub struct PropSaveState {
     pub(crate) location: Point,
     pub(crate) active: bool,
     pub(crate) enabled: bool,
+
+    #[serde(default)]
+    pub(crate) lock: Option<Lock>,
+
+    #[serde(default)]
+    pub(crate) detect_difficulty: Option<i32>,
 }
 
 impl PropSaveState {
@@ -327,6 +436,7 @@ impl PropSaveState {
             }
             Interactive::Door { open, activate_fired, .. } => Door { open, activate_fired },
             Interactive::Hover { ref text } => Hover { text: text.clone() },
+            Interactive::Trap { armed, .. } => Trap { armed },
         };
 
         PropSaveState {
@@ -335,6 +445,8 @@ impl PropSaveState {
             location,
             active: prop_state.is_active(),
             enabled: prop_state.is_enabled(),
+            lock: prop_state.lock().cloned(),
+            detect_difficulty: prop_state.detect_difficulty(),
         }
     }
 }
@@ -357,6 +469,9 @@ pub enum PropInteractiveSaveState {
     Hover {
         text: String,
     },
+    Trap {
+        armed: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -389,6 +504,9 @@ pub struct MerchantSaveState {
 
     #[serde(default)]
     pub(crate) loot_list_id: Option<String>,
+
+    #[serde(default)]
+    pub(crate) faction: Option<String>,
 }
 
 impl MerchantSaveState {
@@ -407,6 +525,7 @@ impl MerchantSaveState {
             items,
             refresh_rate_millis: merchant.refresh_rate_millis,
             last_refresh_millis: merchant.last_refresh_millis,
+            faction: merchant.faction.clone(),
         }
     }
 }
@@ -433,6 +552,21 @@ pub struct EntitySaveState {
 
     #[serde(default)]
     pub(crate) collapsed_groups: Vec<String>,
+
+    #[serde(default)]
+    pub(crate) threat: ThreatTable,
+
+    #[serde(default)]
+    pub(crate) auto_combat: bool,
+
+    #[serde(default)]
+    pub(crate) patrol: Option<PatrolState>,
+
+    #[serde(default)]
+    pub(crate) facing: Option<Facing>,
+
+    #[serde(default)]
+    pub(crate) light: Option<LightSource>,
 }
 
 impl EntitySaveState {
@@ -474,7 +608,10 @@ impl EntitySaveState {
                 attributes: actor.attributes,
                 conversation: actor.conversation.as_ref().map(|c| c.id.to_string()),
                 faction: Some(actor.faction()),
+                faction_id: actor.faction_id.clone(),
                 images: actor.builder_images.clone(),
+                sprite_images: actor.builder_sprite_images.clone(),
+                facing_sprite_images: actor.builder_facing_sprite_images.clone(),
                 hue: actor.hue,
                 hair_color: actor.hair_color,
                 skin_color: actor.skin_color,
@@ -484,6 +621,7 @@ impl EntitySaveState {
                 reward,
                 abilities,
                 ai,
+                ai_script: actor.ai_script.clone(),
             })
         } else {
             None
@@ -506,6 +644,11 @@ impl EntitySaveState {
             show_portrait: entity.show_portrait(),
             actor_base,
             collapsed_groups: entity.collapsed_groups(),
+            threat: entity.threat.clone(),
+            auto_combat: entity.is_auto_combat(),
+            patrol: entity.patrol().cloned(),
+            facing: entity.facing(),
+            light: entity.script_light(),
         }
     }
 }
@@ -536,6 +679,9 @@ pub struct ActorSaveState {
     pub(crate) quick: Vec<Option<ItemSaveState>>,
     pub(crate) ability_states: HashMap<String, AbilitySaveState>,
     pub(crate) p_stats: PStats,
+
+    #[serde(default)]
+    pub(crate) weapon_set: usize,
 }
 
 impl ActorSaveState {
@@ -574,6 +720,7 @@ impl ActorSaveState {
             quick,
             ability_states,
             p_stats: actor_state.clone_p_stats(),
+            weapon_set: actor_state.inventory().weapon_set(),
         }
     }
 }
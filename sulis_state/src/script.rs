@@ -20,8 +20,10 @@
 //!
 //! There are currently four kinds of scripts:
 //!
-//! 1. AI Scripts:  These are attached to a given actor in their resource definition under `ai`.
-//!    Whenever the parent entity is active, the `ai_action(parent, state)` method is called.
+//! 1. AI Scripts:  These are attached to a given actor in their resource definition under `ai`,
+//!    which references an AI template, or directly with `ai_script`, which overrides the
+//!    template's script.  Whenever the parent entity is active, the `ai_action(parent, params)`
+//!    method is called, with the template's params, if any.
 //! 2. Area / Trigger Scripts: These are called by triggers, conversations, and cutscenes.
 //!    Named script functions are called, via a `fire_script` type containing an `id` for the
 //!    script and a `func`.
@@ -39,10 +41,21 @@
 //! The entire contents of the referenced script will be placed into the script file in place
 //! of the --INCLUDE directive at evaluation time.
 //! --INCLUDE directives are only evaluated one level deep at this time.
+//!
+//! Ability and trigger script functions are run as Lua coroutines.  Calling
+//! `coroutine.yield(rounds)` suspends the function, which is then resumed
+//! where it left off once the given number of rounds has elapsed (one round if
+//! not specified).  This allows sequenced encounters to be written linearly.
+//! Suspended functions cannot be saved, so the game may not be saved while any
+//! are waiting.  Only scripts that contain `coroutine.yield` are run this way;
+//! other scripts are called directly.
 
 mod area_targeter;
 pub use self::area_targeter::AreaTargeter;
 
+mod console_completion;
+pub use self::console_completion::Completions;
+
 mod module_export;
 pub use self::module_export::ModuleExport;
 
@@ -56,11 +69,17 @@ pub use self::script_callback::{
     CallbackData, FuncKind, ScriptCallback, ScriptHitKind, TriggeredCallback,
 };
 
+pub mod script_errors;
+use self::script_errors::with_context;
+
 mod script_effect;
 pub use self::script_effect::{
     ScriptActiveSurface, ScriptAppliedEffect, ScriptEffect, ScriptMenuSelection,
 };
 
+mod script_cutscene;
+pub use self::script_cutscene::ScriptCutscene;
+
 mod script_entity;
 pub use self::script_entity::ScriptEntity;
 
@@ -70,6 +89,9 @@ pub use self::script_entity_set::ScriptEntitySet;
 mod script_interface;
 pub use self::script_interface::{entity_with_id, ScriptInterface};
 
+mod script_game_event;
+pub use self::script_game_event::ScriptGameEvent;
+
 mod script_inventory;
 pub use self::script_inventory::{ScriptInventory, ScriptStashItem, ScriptUsableItem};
 
@@ -79,6 +101,9 @@ pub use self::script_item::{ScriptItem, ScriptItemKind};
 mod script_menu;
 pub use self::script_menu::ScriptMenu;
 
+mod script_ui;
+pub use self::script_ui::{ScriptUI, ScriptWindow};
+
 mod script_color_animation;
 pub use self::script_color_animation::ScriptColorAnimation;
 
@@ -88,12 +113,18 @@ pub use self::script_image_layer_animation::ScriptImageLayerAnimation;
 mod script_particle_generator;
 pub use self::script_particle_generator::ScriptParticleGenerator;
 
+mod script_projectile_animation;
+pub use self::script_projectile_animation::ScriptProjectileAnimation;
+
 mod script_scale_animation;
 pub use self::script_scale_animation::ScriptScaleAnimation;
 
 mod script_subpos_animation;
 pub use self::script_subpos_animation::ScriptSubposAnimation;
 
+mod script_timeline;
+pub use self::script_timeline::ScriptTimeline;
+
 pub mod targeter;
 pub use self::targeter::TargeterData;
 
@@ -102,11 +133,17 @@ use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::time;
 
-use rlua::{self, FromLuaMulti, Function, Lua, ToLuaMulti};
+use rlua::{
+    self, Context, FromLuaMulti, Function, Lua, MultiValue, RegistryKey, Thread, ThreadStatus,
+    ToLuaMulti, Value, Variadic,
+};
 
 use crate::{ai, EntityState, GameState};
-use sulis_core::{config::Config, util::Point};
-use sulis_module::{Ability, DamageKind, HitKind, Module, QuickSlot};
+use sulis_core::{
+    config::Config,
+    util::{combat_roll, Point},
+};
+use sulis_module::{Ability, DamageKind, HitKind, Module, QuickSlot, ScriptLimits};
 
 pub type Result<T> = std::result::Result<T, rlua::Error>;
 
@@ -115,7 +152,10 @@ pub struct Script {}
 
 impl Script {
     pub fn ai(parent: &Rc<RefCell<EntityState>>, func: &str) -> ai::State {
-        match script_cache::ai_script(parent, func) {
+        match with_context(
+            || entity_context(parent),
+            || script_cache::ai_script(parent, func),
+        ) {
             Err(e) => {
                 warn!("Error in lua AI script: '{}'", e);
                 ai::State::End
@@ -126,7 +166,10 @@ impl Script {
 
     pub fn entity(parent: &Rc<RefCell<EntityState>>, targets: ScriptEntitySet, func: &str) {
         let t: Option<usize> = None;
-        if let Err(e) = script_cache::entity_script(parent, targets, t, func) {
+        if let Err(e) = with_context(
+            || entity_context(parent),
+            || script_cache::entity_script(parent, targets, t, func),
+        ) {
             warn!("Error in entity script '{}': {}", func, e);
         }
     }
@@ -139,7 +182,10 @@ impl Script {
         func: &str,
     ) {
         let t = Some(ScriptHitKind::new(kind, damage));
-        if let Err(e) = script_cache::entity_script(parent, targets, t, func) {
+        if let Err(e) = with_context(
+            || entity_context(parent),
+            || script_cache::entity_script(parent, targets, t, func),
+        ) {
             warn!("Error in entity with attack data script '{}': {}", func, e);
         }
     }
@@ -152,13 +198,19 @@ impl Script {
     ) where
         T: rlua::UserData + Send + 'static,
     {
-        if let Err(e) = script_cache::entity_script(parent, targets, Some(arg), func) {
+        if let Err(e) = with_context(
+            || entity_context(parent),
+            || script_cache::entity_script(parent, targets, Some(arg), func),
+        ) {
             warn!("Error in entity with arg script '{}': {}", func, e);
         }
     }
 
     pub fn item_on_activate(parent: &Rc<RefCell<EntityState>>, func: String, kind: ScriptItemKind) {
-        if let Err(e) = script_cache::item_on_activate(parent, func, kind) {
+        if let Err(e) = with_context(
+            || item_context(parent),
+            || script_cache::item_on_activate(parent, func, kind),
+        ) {
             warn!("Error in item on_activate script: {}", e);
         }
     }
@@ -170,7 +222,10 @@ impl Script {
         func: &str,
     ) {
         let t: Option<usize> = None;
-        if let Err(e) = script_cache::item_script(parent, kind, targets, t, func) {
+        if let Err(e) = with_context(
+            || item_context(parent),
+            || script_cache::item_script(parent, kind, targets, t, func),
+        ) {
             warn!("Error in item script '{}': {}", func, e);
         }
     }
@@ -184,7 +239,10 @@ impl Script {
         func: &str,
     ) {
         let t = Some(ScriptHitKind::new(kind, damage));
-        if let Err(e) = script_cache::item_script(parent, i_kind, targets, t, func) {
+        if let Err(e) = with_context(
+            || item_context(parent),
+            || script_cache::item_script(parent, i_kind, targets, t, func),
+        ) {
             warn!("Error in item with attack data script '{}': {}", func, e);
         }
     }
@@ -198,7 +256,10 @@ impl Script {
     ) where
         T: rlua::UserData + Send + 'static,
     {
-        if let Err(e) = script_cache::item_script(parent, i_kind, targets, Some(arg), func) {
+        if let Err(e) = with_context(
+            || item_context(parent),
+            || script_cache::item_script(parent, i_kind, targets, Some(arg), func),
+        ) {
             warn!("Error in item with arg script '{}': {}", func, e);
         }
     }
@@ -212,27 +273,38 @@ impl Script {
         func: &str,
         custom_target: Option<Rc<RefCell<EntityState>>>,
     ) {
-        if let Err(e) = script_cache::item_on_target_select(
-            parent,
-            kind,
-            targets,
-            selected_point,
-            affected_points,
-            func,
-            custom_target,
+        if let Err(e) = with_context(
+            || item_context(parent),
+            || {
+                script_cache::item_on_target_select(
+                    parent,
+                    kind,
+                    targets,
+                    selected_point,
+                    affected_points,
+                    func,
+                    custom_target,
+                )
+            },
         ) {
             warn!("Error in item on target select: {}", e);
         }
     }
 
     pub fn ability_on_deactivate(parent: usize, ability: &Rc<Ability>) {
-        if let Err(e) = script_cache::ability_on_deactivate(parent, ability) {
+        if let Err(e) = with_context(
+            || ability_context_index(parent, ability),
+            || script_cache::ability_on_deactivate(parent, ability),
+        ) {
             warn!("Error in ability on_deactivate: {}", e);
         }
     }
 
     pub fn ability_on_activate(parent: usize, func: String, ability: &Rc<Ability>) {
-        if let Err(e) = script_cache::ability_on_activate(parent, func, ability) {
+        if let Err(e) = with_context(
+            || ability_context_index(parent, ability),
+            || script_cache::ability_on_activate(parent, func, ability),
+        ) {
             warn!("Error in ability on_activate: {}", e);
         }
     }
@@ -246,14 +318,19 @@ impl Script {
         func: &str,
         custom_target: Option<Rc<RefCell<EntityState>>>,
     ) {
-        if let Err(e) = script_cache::ability_on_target_select(
-            parent,
-            ability,
-            targets,
-            selected_point,
-            affected_points,
-            func,
-            custom_target,
+        if let Err(e) = with_context(
+            || ability_context(parent, ability),
+            || {
+                script_cache::ability_on_target_select(
+                    parent,
+                    ability,
+                    targets,
+                    selected_point,
+                    affected_points,
+                    func,
+                    custom_target,
+                )
+            },
         ) {
             warn!("Error in ability on target select '{}': {}", func, e);
         }
@@ -268,7 +345,10 @@ impl Script {
         func: &str,
     ) {
         let t = Some(ScriptHitKind::new(kind, damage));
-        if let Err(e) = script_cache::ability_script(parent, ability, targets, t, func) {
+        if let Err(e) = with_context(
+            || ability_context(parent, ability),
+            || script_cache::ability_script(parent, ability, targets, t, func),
+        ) {
             warn!("Error in ability script '{}': {}", func, e);
         }
     }
@@ -282,7 +362,10 @@ impl Script {
     ) where
         T: rlua::UserData + Send + 'static,
     {
-        if let Err(e) = script_cache::ability_script(parent, ability, targets, Some(arg), func) {
+        if let Err(e) = with_context(
+            || ability_context(parent, ability),
+            || script_cache::ability_script(parent, ability, targets, Some(arg), func),
+        ) {
             warn!("Error in ability script with arg '{}': {}", func, e);
         }
     }
@@ -294,7 +377,10 @@ impl Script {
         func: &str,
     ) {
         let t: Option<usize> = None;
-        if let Err(e) = script_cache::ability_script(parent, ability, targets, t, func) {
+        if let Err(e) = with_context(
+            || ability_context(parent, ability),
+            || script_cache::ability_script(parent, ability, targets, t, func),
+        ) {
             warn!("Error in ability script '{}': {}", func, e);
         }
     }
@@ -309,14 +395,47 @@ impl Script {
     }
 }
 
-const MEM_LIMIT: usize = 10_485_760;
-const INSTRUCTION_LIMIT: u32 = 50_000;
 const INSTRUCTIONS_PER_CHECK: u32 = 50;
-const MILLIS_LIMIT: f64 = 50.0;
+
+thread_local! {
+    static ABORTED_SCRIPTS: RefCell<Vec<AbortedScript>> = RefCell::new(Vec::new());
+    static CURRENT_SCRIPTS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+/// A script call that was aborted for exceeding one of the campaign's
+/// `ScriptLimits`
+#[derive(Debug, Clone)]
+pub struct AbortedScript {
+    pub script: String,
+    pub func: String,
+    pub reason: String,
+}
+
+/// The ID of the script that is currently executing, if any
+pub(in crate::script) fn current_script_id() -> Option<String> {
+    CURRENT_SCRIPTS.with(|scripts| scripts.borrow().last().cloned())
+}
+
+/// Returns and clears the script calls aborted since the last call, so
+/// they can be reported to the player
+pub fn take_aborted_scripts() -> Vec<AbortedScript> {
+    ABORTED_SCRIPTS.with(|aborted| std::mem::take(&mut *aborted.borrow_mut()))
+}
 
 pub struct InstructionState {
     count: u32,
     start_time: time::Instant,
+
+    /// Set when the hook aborts the current call for exceeding a limit
+    aborted: Option<String>,
+}
+
+/// A script function suspended with `coroutine.yield`, waiting for some
+/// number of rounds to elapse before it is resumed
+struct Coroutine {
+    func: String,
+    thread: RegistryKey,
+    rounds: u32,
 }
 
 /// A script state, containing a complete lua state.
@@ -325,16 +444,31 @@ pub struct ScriptState {
     id: String,
     instructions: Arc<Mutex<InstructionState>>,
     current_depth: Cell<u32>,
+    coroutines: RefCell<Vec<Coroutine>>,
+
+    /// Whether the script source uses `coroutine.yield`, and so needs its
+    /// functions run as coroutines
+    yields: bool,
 }
 
 impl Default for ScriptState {
     fn default() -> ScriptState {
+        ScriptState::new(Module::script_limits())
+    }
+}
+
+impl ScriptState {
+    pub fn new(limits: ScriptLimits) -> ScriptState {
         let lua = Lua::new_with(get_rlua_std_lib());
-        lua.set_memory_limit(Some(MEM_LIMIT));
+        lua.set_memory_limit(Some(limits.memory_kb * 1024));
 
         lua.context(|lua| {
             let globals = lua.globals();
-            match globals.set("game", ScriptInterface {}) {
+            let result = globals
+                .set("game", ScriptInterface {})
+                .and_then(|_| globals.set("ui", ScriptUI {}))
+                .and_then(|_| replace_lua_random(lua));
+            match result {
                 Ok(()) => (),
                 Err(e) => {
                     warn!("Error setting up Lua globals");
@@ -346,12 +480,15 @@ impl Default for ScriptState {
         let instructions = Arc::new(Mutex::new(InstructionState {
             count: 0,
             start_time: time::Instant::now(),
+            aborted: None,
         }));
         let state = ScriptState {
             lua,
             instructions,
             id: String::new(),
             current_depth: Cell::new(0),
+            coroutines: RefCell::new(Vec::new()),
+            yields: false,
         };
 
         let instructions = Arc::clone(&state.instructions);
@@ -362,31 +499,26 @@ impl Default for ScriptState {
             },
             move |_, _| {
                 let state = &mut *instructions.lock().unwrap();
-                state.count += INSTRUCTIONS_PER_CHECK;
-
-                if state.count > INSTRUCTION_LIMIT {
-                    return Err(rlua::Error::RuntimeError(format!(
-                        "Instruction limit of \
-                         {INSTRUCTION_LIMIT} reached"
-                    )));
-                }
-
-                if get_elapsed_millis(state.start_time.elapsed()) > MILLIS_LIMIT {
-                    return Err(rlua::Error::RuntimeError(format!(
-                        "Script time limit of \
-                         {MILLIS_LIMIT} millis reached"
-                    )));
-                }
-
-                Ok(())
+                state.count = state.count.saturating_add(INSTRUCTIONS_PER_CHECK);
+
+                let reason = if state.count > limits.instructions {
+                    format!("Instruction limit of {} reached", limits.instructions)
+                } else if get_elapsed_millis(state.start_time.elapsed()) > limits.millis {
+                    format!("Script time limit of {} millis reached", limits.millis)
+                } else {
+                    return Ok(());
+                };
+
+                // keep failing on each check, so the script can't recover
+                // by catching the error with pcall
+                state.aborted = Some(reason.clone());
+                Err(rlua::Error::RuntimeError(reason))
             },
         );
 
         state
     }
-}
 
-impl ScriptState {
     fn print_report(&self, func: &str) {
         let (count, time) = {
             let inst = &(*self.instructions.lock().unwrap());
@@ -404,10 +536,37 @@ impl ScriptState {
         let instructions = &mut *self.instructions.lock().unwrap();
         instructions.count = 0;
         instructions.start_time = time::Instant::now();
+        instructions.aborted = None;
+    }
+
+    /// Records the current call as aborted if it failed by exceeding one
+    /// of the limits
+    fn check_aborted<T>(&self, func: &str, result: &Result<T>) {
+        let error = match result {
+            Ok(_) => return,
+            Err(e) => e,
+        };
+
+        let aborted = self.instructions.lock().unwrap().aborted.take();
+        let reason = match aborted {
+            Some(reason) => reason,
+            None if is_memory_error(error) => "Memory limit reached".to_string(),
+            None => return,
+        };
+
+        warn!("Aborted script '{}:{}': {}", self.id, func, reason);
+        ABORTED_SCRIPTS.with(|aborted| {
+            aborted.borrow_mut().push(AbortedScript {
+                script: self.id.to_string(),
+                func: func.to_string(),
+                reason,
+            })
+        });
     }
 
     pub(in crate::script) fn load(&mut self, id: &str, script: &str) -> Result<()> {
         self.id = id.to_string();
+        self.yields = script.contains("coroutine.yield");
         self.lua
             .context(|lua| lua.load(&script).set_name(&id)?.exec())
     }
@@ -422,6 +581,103 @@ impl ScriptState {
         Args: for<'a> ToLuaMulti<'a>,
         Ret: for<'a> FromLuaMulti<'a>,
     {
+        self.run(function, report, |lua| {
+            let func: Function = lua.globals().get(function)?;
+            func.call(args)
+        })
+    }
+
+    /// Calls the specified `function` as a coroutine.  If the function
+    /// yields, it is kept and resumed by `elapse_rounds` once the number of
+    /// rounds it yielded has passed.  Scripts that never yield have the
+    /// function called directly instead.
+    pub(in crate::script) fn exec_coroutine<Args>(
+        &self,
+        function: &str,
+        args: Args,
+        report: bool,
+    ) -> Result<()>
+    where
+        Args: for<'a> ToLuaMulti<'a>,
+    {
+        if !self.yields {
+            return self.exec_func(function, args, report);
+        }
+
+        self.run(function, report, |lua| {
+            let func: Function = lua.globals().get(function)?;
+            let thread = lua.create_thread(func)?;
+            let result: MultiValue = thread.resume(args)?;
+            if thread.status() == ThreadStatus::Resumable {
+                let coroutine = Coroutine {
+                    func: function.to_string(),
+                    thread: lua.create_registry_value(thread)?,
+                    rounds: yielded_rounds(result),
+                };
+                self.coroutines.borrow_mut().push(coroutine);
+            }
+            Ok(())
+        })
+    }
+
+    /// Whether any coroutine of this script is suspended
+    pub(in crate::script) fn has_coroutines(&self) -> bool {
+        !self.coroutines.borrow().is_empty()
+    }
+
+    /// Counts down the wait of each suspended coroutine by the specified
+    /// number of `rounds`, resuming any that are ready.  Returns the function
+    /// name and result for each resumed coroutine.
+    pub(in crate::script) fn elapse_rounds(
+        &self,
+        rounds: u32,
+        report: bool,
+    ) -> Vec<(String, Result<()>)> {
+        // resumed coroutines may start new ones, so remove the ready
+        // coroutines before resuming any of them
+        let ready: Vec<Coroutine> = {
+            let mut coroutines = self.coroutines.borrow_mut();
+            let mut ready = Vec::new();
+            let mut index = 0;
+            while index < coroutines.len() {
+                let coroutine = &mut coroutines[index];
+                coroutine.rounds = coroutine.rounds.saturating_sub(rounds);
+                if coroutine.rounds == 0 {
+                    ready.push(coroutines.remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+            ready
+        };
+
+        let mut results = Vec::new();
+        for coroutine in ready {
+            let func = coroutine.func.to_string();
+            let result = self.run(&func, report, |lua| {
+                let thread: Thread = lua.registry_value(&coroutine.thread)?;
+                let result: MultiValue = thread.resume(())?;
+                if thread.status() == ThreadStatus::Resumable {
+                    let rounds = yielded_rounds(result);
+                    self.coroutines
+                        .borrow_mut()
+                        .push(Coroutine { rounds, ..coroutine });
+                } else {
+                    lua.remove_registry_value(coroutine.thread)?;
+                }
+                Ok(())
+            });
+            results.push((func, result));
+        }
+        results
+    }
+
+    fn run<Ret>(
+        &self,
+        function: &str,
+        report: bool,
+        f: impl for<'lua> FnOnce(Context<'lua>) -> Result<Ret>,
+    ) -> Result<Ret> {
         let cur_depth = self.current_depth.get();
         if cur_depth == 0 {
             self.reset_instruction_state();
@@ -435,22 +691,44 @@ impl ScriptState {
                 cur_depth + 1
             );
         }
-        let result = self.lua.context(|lua| {
-            let func: Function = lua.globals().get(function)?;
-            func.call(args)
-        });
+        CURRENT_SCRIPTS.with(|scripts| scripts.borrow_mut().push(self.id.to_string()));
+        let result = self.lua.context(f);
+        CURRENT_SCRIPTS.with(|scripts| scripts.borrow_mut().pop());
         self.lua.gc_collect()?;
         if report {
             self.print_report(function);
         }
         self.current_depth.set(cur_depth);
+        if cur_depth == 0 {
+            self.check_aborted(function, &result);
+        }
         result
     }
 
-    pub fn console(&self, script: String, party: &[Rc<RefCell<EntityState>>]) -> Result<String> {
+    /// Evaluates the specified `script` from the developer console.  Returns
+    /// any lines written with `print` by the script, along with the result of
+    /// the evaluation.
+    pub fn console(
+        &self,
+        script: String,
+        party: &[Rc<RefCell<EntityState>>],
+    ) -> (Vec<String>, Result<String>) {
         assert!(!party.is_empty());
         self.reset_instruction_state();
+        let printed = Arc::new(Mutex::new(Vec::new()));
         let result = self.lua.context(|lua| {
+            let output = Arc::clone(&printed);
+            let print = lua.create_function(move |lua, args: Variadic<Value>| {
+                let tostring: Function = lua.globals().get("tostring")?;
+                let mut line = Vec::new();
+                for arg in args {
+                    line.push(tostring.call::<_, String>(arg)?);
+                }
+                output.lock().unwrap().push(line.join("\t"));
+                Ok(())
+            })?;
+            lua.globals().set("print", print)?;
+
             lua.globals().set("player", ScriptEntity::from(&party[0]))?;
 
             let party_table = lua.create_table()?;
@@ -463,7 +741,35 @@ impl ScriptState {
             lua.load(&script).eval::<String>()
         });
         self.print_report("console");
-        result
+        let printed = std::mem::take(&mut *printed.lock().unwrap());
+        (printed, result)
+    }
+
+    /// Finds the names of globals, table keys, or methods that could complete
+    /// the identifier at the end of `text`
+    pub fn console_completions(&self, text: &str) -> Result<Completions> {
+        self.lua
+            .context(|lua| console_completion::complete(lua, text))
+    }
+}
+
+fn entity_context(entity: &Rc<RefCell<EntityState>>) -> String {
+    let entity = entity.borrow();
+    format!("entity '{}' ({})", entity.actor.actor.name, entity.unique_id())
+}
+
+fn item_context(parent: &Rc<RefCell<EntityState>>) -> String {
+    format!("item used by {}", entity_context(parent))
+}
+
+fn ability_context(parent: &Rc<RefCell<EntityState>>, ability: &Ability) -> String {
+    format!("ability '{}' of {}", ability.id, entity_context(parent))
+}
+
+fn ability_context_index(parent: usize, ability: &Ability) -> String {
+    match ScriptEntity::new(parent).try_unwrap() {
+        Ok(parent) => ability_context(&parent, ability),
+        Err(_) => format!("ability '{}' of entity {}", ability.id, parent),
     }
 }
 
@@ -483,10 +789,63 @@ fn get_targeter() -> Result<Rc<RefCell<AreaTargeter>>> {
     }
 }
 
+fn is_memory_error(error: &rlua::Error) -> bool {
+    match error {
+        rlua::Error::MemoryError(_) => true,
+        rlua::Error::CallbackError { cause, .. } => is_memory_error(cause),
+        _ => false,
+    }
+}
+
+/// The number of rounds to wait from the values passed to `coroutine.yield`,
+/// defaulting to one round
+fn yielded_rounds(values: MultiValue) -> u32 {
+    match values.into_iter().next() {
+        Some(Value::Integer(rounds)) => rounds.max(1) as u32,
+        Some(Value::Number(rounds)) => rounds.max(1.0) as u32,
+        _ => 1,
+    }
+}
+
 fn get_rlua_std_lib() -> rlua::StdLib {
     use rlua::StdLib;
 
-    StdLib::BASE | StdLib::TABLE | StdLib::STRING | StdLib::MATH
+    StdLib::BASE | StdLib::COROUTINE | StdLib::TABLE | StdLib::STRING | StdLib::MATH
+}
+
+/// Replaces `math.random` with rolls from the combat generator, which is
+/// stored in the save and restored by replays.  `math.randomseed` becomes
+/// a no-op so scripts cannot desync it.
+fn replace_lua_random(lua: Context) -> rlua::Result<()> {
+    let math: rlua::Table = lua.globals().get("math")?;
+
+    let random = lua.create_function(|_, (m, n): (Option<i64>, Option<i64>)| {
+        let value = match (m, n) {
+            (None, _) => Value::Number(combat_roll(0.0, 1.0)),
+            (Some(max), None) => {
+                if max < 1 {
+                    return Err(rlua::Error::RuntimeError(
+                        "bad argument #1 to 'random' (interval is empty)".to_string(),
+                    ));
+                }
+                Value::Integer(combat_roll(1, max + 1))
+            }
+            (Some(min), Some(max)) => {
+                if max < min {
+                    return Err(rlua::Error::RuntimeError(
+                        "bad argument #2 to 'random' (interval is empty)".to_string(),
+                    ));
+                }
+                Value::Integer(combat_roll(min, max + 1))
+            }
+        };
+        Ok(value)
+    })?;
+    math.set("random", random)?;
+
+    let randomseed = lua.create_function(|_, _: MultiValue| Ok(()))?;
+    math.set("randomseed", randomseed)?;
+    Ok(())
 }
 
 fn get_elapsed_millis(elapsed: time::Duration) -> f64 {
@@ -1076,6 +1076,11 @@ impl AreaTargeter {
         self.cancel = true;
     }
 
+    /// The area position last passed to `on_mouse_move`
+    pub fn cursor_pos(&self) -> Point {
+        self.cursor_pos
+    }
+
     pub fn is_free_select(&self) -> bool {
         self.free_select.is_some()
     }
@@ -0,0 +1,232 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Tab completion of names for the developer console.  Global names and
+//! table keys are read directly from the Lua state.  Methods on userdata
+//! are not visible from Lua, so they are gathered by running each type's
+//! `add_methods` against a collector that records only the method names.
+
+use rlua::{
+    AnyUserData, Context, FromLuaMulti, MetaMethod, Result, Table, ToLuaMulti, UserData,
+    UserDataMethods, Value,
+};
+
+use crate::script::{
+    ScriptAbility, ScriptAbilitySet, ScriptAppliedEffect, ScriptCutscene, ScriptEffect,
+    ScriptEntity, ScriptEntitySet, ScriptInterface, ScriptInventory, ScriptItem, ScriptMenu,
+    ScriptUI, ScriptWindow,
+};
+
+/// The names available to complete the identifier at the end of `text`,
+/// along with the byte index in `text` where that identifier begins.
+pub struct Completions {
+    pub start: usize,
+    pub names: Vec<String>,
+}
+
+pub fn complete(lua: Context, text: &str) -> Result<Completions> {
+    let expr_start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_' || *c == '.' || *c == ':')
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+    let expr = &text[expr_start..];
+
+    let (path, method, partial) = match expr.rfind(['.', ':']) {
+        None => ("", false, expr),
+        Some(i) => (&expr[..i], &expr[i..=i] == ":", &expr[i + 1..]),
+    };
+
+    let mut names = if path.is_empty() {
+        if method {
+            Vec::new()
+        } else {
+            table_keys(lua.globals(), false)
+        }
+    } else {
+        match lookup(lua, path)? {
+            Value::Table(table) => table_keys(table, method),
+            Value::UserData(data) if method => userdata_methods(&data),
+            _ => Vec::new(),
+        }
+    };
+
+    names.retain(|name| name.starts_with(partial));
+    names.sort();
+    names.dedup();
+
+    Ok(Completions {
+        start: text.len() - partial.len(),
+        names,
+    })
+}
+
+fn lookup<'lua>(lua: Context<'lua>, path: &str) -> Result<Value<'lua>> {
+    let mut value = Value::Table(lua.globals());
+    for key in path.split('.') {
+        value = match value {
+            Value::Table(table) => table.get(key)?,
+            _ => return Ok(Value::Nil),
+        };
+    }
+
+    Ok(value)
+}
+
+fn table_keys(table: Table, functions_only: bool) -> Vec<String> {
+    let mut keys = Vec::new();
+    for pair in table.pairs::<Value, Value>() {
+        let (key, value) = match pair {
+            Err(_) => continue,
+            Ok(pair) => pair,
+        };
+
+        if functions_only && !matches!(value, Value::Function(_)) {
+            continue;
+        }
+
+        if let Value::String(key) = key {
+            if let Ok(key) = key.to_str() {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    keys
+}
+
+fn userdata_methods(data: &AnyUserData) -> Vec<String> {
+    if data.is::<ScriptInterface>() {
+        method_names::<ScriptInterface>()
+    } else if data.is::<ScriptEntity>() {
+        method_names::<ScriptEntity>()
+    } else if data.is::<ScriptEntitySet>() {
+        method_names::<ScriptEntitySet>()
+    } else if data.is::<ScriptInventory>() {
+        method_names::<ScriptInventory>()
+    } else if data.is::<ScriptItem>() {
+        method_names::<ScriptItem>()
+    } else if data.is::<ScriptAbility>() {
+        method_names::<ScriptAbility>()
+    } else if data.is::<ScriptAbilitySet>() {
+        method_names::<ScriptAbilitySet>()
+    } else if data.is::<ScriptEffect>() {
+        method_names::<ScriptEffect>()
+    } else if data.is::<ScriptAppliedEffect>() {
+        method_names::<ScriptAppliedEffect>()
+    } else if data.is::<ScriptMenu>() {
+        method_names::<ScriptMenu>()
+    } else if data.is::<ScriptUI>() {
+        method_names::<ScriptUI>()
+    } else if data.is::<ScriptWindow>() {
+        method_names::<ScriptWindow>()
+    } else if data.is::<ScriptCutscene>() {
+        method_names::<ScriptCutscene>()
+    } else {
+        Vec::new()
+    }
+}
+
+fn method_names<T: UserData>() -> Vec<String> {
+    let mut collector = MethodNames { names: Vec::new() };
+    T::add_methods(&mut collector);
+    collector.names
+}
+
+struct MethodNames {
+    names: Vec<String>,
+}
+
+impl MethodNames {
+    fn add<S: ?Sized + AsRef<[u8]>>(&mut self, name: &S) {
+        self.names
+            .push(String::from_utf8_lossy(name.as_ref()).to_string());
+    }
+}
+
+impl<'lua, T: UserData> UserDataMethods<'lua, T> for MethodNames {
+    fn add_method<S, A, R, M>(&mut self, name: &S, _method: M)
+    where
+        S: ?Sized + AsRef<[u8]>,
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        M: 'static + Send + Fn(Context<'lua>, &T, A) -> Result<R>,
+    {
+        self.add(name);
+    }
+
+    fn add_method_mut<S, A, R, M>(&mut self, name: &S, _method: M)
+    where
+        S: ?Sized + AsRef<[u8]>,
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        M: 'static + Send + FnMut(Context<'lua>, &mut T, A) -> Result<R>,
+    {
+        self.add(name);
+    }
+
+    fn add_function<S, A, R, F>(&mut self, name: &S, _function: F)
+    where
+        S: ?Sized + AsRef<[u8]>,
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        F: 'static + Send + Fn(Context<'lua>, A) -> Result<R>,
+    {
+        self.add(name);
+    }
+
+    fn add_function_mut<S, A, R, F>(&mut self, name: &S, _function: F)
+    where
+        S: ?Sized + AsRef<[u8]>,
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        F: 'static + Send + FnMut(Context<'lua>, A) -> Result<R>,
+    {
+        self.add(name);
+    }
+
+    fn add_meta_method<A, R, M>(&mut self, _meta: MetaMethod, _method: M)
+    where
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        M: 'static + Send + Fn(Context<'lua>, &T, A) -> Result<R>,
+    {
+    }
+
+    fn add_meta_method_mut<A, R, M>(&mut self, _meta: MetaMethod, _method: M)
+    where
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        M: 'static + Send + FnMut(Context<'lua>, &mut T, A) -> Result<R>,
+    {
+    }
+
+    fn add_meta_function<A, R, F>(&mut self, _meta: MetaMethod, _function: F)
+    where
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        F: 'static + Send + Fn(Context<'lua>, A) -> Result<R>,
+    {
+    }
+
+    fn add_meta_function_mut<A, R, F>(&mut self, _meta: MetaMethod, _function: F)
+    where
+        A: FromLuaMulti<'lua>,
+        R: ToLuaMulti<'lua>,
+        F: 'static + Send + FnMut(Context<'lua>, A) -> Result<R>,
+    {
+    }
+}
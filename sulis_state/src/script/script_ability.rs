@@ -273,6 +273,15 @@ impl UserData for ScriptAbilitySet {
 /// Returns the duration, in rounds of this ability as defined in its resource file.
 /// How this duration is used is up to the ability's script.
 ///
+/// # `level(parent: ScriptEntity) -> Int`
+/// Returns the upgrade tier of this ability owned by the `parent`, starting at 1 for
+/// the base ability, or 0 if the parent does not own it.  Scripts can use this to
+/// scale damage, duration, and other effects with each upgrade.
+/// ## Examples
+/// ```lua
+///   local duration = ability:duration() + ability:level(parent) - 1
+/// ```
+///
 /// # `create_callback(parent: ScriptEntity) -> ScriptCallback`
 /// Creates a script callback from this ability for the `parent`.  Methods
 /// can then be added to the ScriptCallback, which are called when certain conditions
@@ -398,6 +407,11 @@ impl UserData for ScriptAbility {
         );
         methods.add_method("name", |_, ability, ()| Ok(ability.name.to_string()));
         methods.add_method("duration", |_, ability, ()| Ok(ability.duration));
+        methods.add_method("level", |_, ability, parent: ScriptEntity| {
+            let parent = parent.try_unwrap()?;
+            let parent = parent.borrow();
+            Ok(parent.actor.ability_tier(&ability.id))
+        });
 
         methods.add_method("create_callback", |_, ability, parent: ScriptEntity| {
             ability.error_if_not_active()?;
@@ -21,16 +21,18 @@ use std::time::{Duration, Instant};
 
 use rlua::{self, FromLuaMulti, ToLua, ToLuaMulti};
 
+use crate::script::script_errors::{self, ScriptError};
 use crate::script::{
     Result, ScriptAbility, ScriptEntity, ScriptEntitySet, ScriptItem, ScriptItemKind, ScriptState,
 };
 use crate::{ai, EntityState};
 use sulis_core::util::Point;
-use sulis_module::{ai::AITemplate, Ability, Item, Module};
+use sulis_module::{Ability, Item, Module};
 
 thread_local! {
     static SCRIPT_CACHE: RefCell<HashMap<String, Rc<ScriptState>>> = RefCell::new(HashMap::new());
     static REPORTING: Cell<bool> = Cell::new(true);
+    static LAST_ROUND: Cell<Option<u32>> = Cell::new(None);
 }
 
 pub fn setup() -> Result<()> {
@@ -39,6 +41,7 @@ pub fn setup() -> Result<()> {
         let mut cache = cache.borrow_mut();
 
         cache.clear();
+        LAST_ROUND.with(|last| last.set(None));
         for id in Module::all_scripts() {
             let script = get_script_from_id(&id)?;
             let mut state = ScriptState::default();
@@ -125,35 +128,88 @@ where
     Args: for<'a> ToLuaMulti<'a>,
     Ret: for<'a> FromLuaMulti<'a>,
 {
-    let state = SCRIPT_CACHE.with(|cache| {
+    let state = get_state(id)?;
+    let reporting = REPORTING.with(|r| r.get());
+    let result = state.exec_func(func, args, reporting);
+    check_error(&state, func, result)
+}
+
+/// Executes the specified function as a coroutine, which may suspend itself
+/// across rounds with `coroutine.yield`
+pub fn exec_coroutine<Args>(id: &str, func: &str, args: Args) -> Result<()>
+where
+    Args: for<'a> ToLuaMulti<'a>,
+{
+    let state = get_state(id)?;
+    let reporting = REPORTING.with(|r| r.get());
+    let result = state.exec_coroutine(func, args, reporting);
+    check_error(&state, func, result)
+}
+
+/// Advances suspended coroutines to the specified round, resuming any
+/// that have finished waiting.  This should be called regularly with the
+/// current round.
+pub fn update_coroutines(round: u32) {
+    let last_round = LAST_ROUND.with(|last| last.replace(Some(round)));
+    let elapsed = match last_round {
+        Some(last_round) if round > last_round => round - last_round,
+        _ => return,
+    };
+
+    let states: Vec<Rc<ScriptState>> =
+        SCRIPT_CACHE.with(|cache| cache.borrow().values().map(Rc::clone).collect());
+    let reporting = REPORTING.with(|r| r.get());
+    for state in states {
+        for (func, result) in state.elapse_rounds(elapsed, reporting) {
+            if let Err(e) = check_error(&state, &func, result) {
+                warn!("Error resuming script '{}/{}': {}", state.id, func, e);
+            }
+        }
+    }
+}
+
+/// Whether any script function is suspended, waiting to be resumed.  These
+/// cannot be saved, so the game should not be saved while this is true
+pub fn has_suspended_coroutines() -> bool {
+    SCRIPT_CACHE.with(|cache| cache.borrow().values().any(|s| s.has_coroutines()))
+}
+
+fn get_state(id: &str) -> Result<Rc<ScriptState>> {
+    SCRIPT_CACHE.with(|cache| {
         let cache = cache.borrow();
 
-        //setup the script if it does not already exist
-        if !cache.contains_key(id) {
-            return Err(rlua::Error::ToLuaConversionError {
+        match cache.get(id) {
+            None => Err(rlua::Error::ToLuaConversionError {
                 from: "String",
                 to: "Script",
                 message: Some(format!("Script '{id}' does not exist")),
-            });
+            }),
+            Some(state) => Ok(Rc::clone(state)),
         }
+    })
+}
 
-        Ok(Rc::clone(cache.get(id).unwrap()))
-    })?;
-
-    let reporting = REPORTING.with(|r| r.get());
-
+fn check_error<Ret>(state: &ScriptState, func: &str, result: Result<Ret>) -> Result<Ret> {
     use rlua::Error::*;
-    match state.exec_func(func, args, reporting) {
+    match result {
         Ok(ret) => Ok(ret),
-        Err(CallbackError { traceback, cause }) => {
-            let (output, line_num) = print_nearby_lines(&state, &traceback);
-            warn!(
-                "Script Error:\n{}\n{}.lua:{} Called '{}'\n{}",
-                cause, state.id, line_num, func, output
-            );
-            Err(CallbackError { traceback, cause })
+        Err(e) => {
+            let traceback = match &e {
+                CallbackError { traceback, .. } => traceback.to_string(),
+                e => e.to_string(),
+            };
+            let (output, line_num) = print_nearby_lines(state, &traceback);
+            if let CallbackError { cause, .. } = &e {
+                warn!(
+                    "Script Error:\n{}\n{}.lua:{} Called '{}'\n{}",
+                    cause, state.id, line_num, func, output
+                );
+            }
+
+            let source = if line_num > 0 { output } else { String::new() };
+            script_errors::record(ScriptError::new(&state.id, func, &e, source));
+            Err(e)
         }
-        Err(e) => Err(e),
     }
 }
 
@@ -163,7 +219,7 @@ pub fn ai_script(parent: &Rc<RefCell<EntityState>>, func: &str) -> Result<ai::St
     exec_func(
         &script_data.script,
         func,
-        (parent, script_data.params.clone()),
+        (parent, script_data.params),
     )
 }
 
@@ -239,7 +295,7 @@ pub fn ability_on_activate(parent: usize, func: String, ability: &Rc<Ability>) -
     let parent = ScriptEntity::new(parent);
     let ability = ScriptAbility::from(ability);
 
-    exec_func(&script, &func, (parent, ability))
+    exec_coroutine(&script, &func, (parent, ability))
 }
 
 pub fn ability_on_deactivate(parent: usize, ability: &Rc<Ability>) -> Result<()> {
@@ -288,26 +344,26 @@ where
     let script = get_ability_script_id(ability)?;
     let parent = ScriptEntity::from(parent);
     let ability = ScriptAbility::from(ability);
-    exec_func(&script, func, (parent, ability, targets, arg))
+    exec_coroutine(&script, func, (parent, ability, targets, arg))
 }
 
 pub fn trigger_script<Args>(script_id: &str, func: &str, args: Args) -> Result<()>
 where
     Args: for<'a> ToLuaMulti<'a>,
 {
-    exec_func(script_id, func, args)
+    exec_coroutine(script_id, func, args)
 }
 
-fn get_script_data_from_entity(entity: &Rc<RefCell<EntityState>>) -> Result<Rc<AITemplate>> {
+fn get_script_data_from_entity(entity: &Rc<RefCell<EntityState>>) -> Result<ai::ScriptData> {
     let entity = entity.borrow();
     let id = entity.unique_id();
-    match &entity.actor.actor.ai {
+    match ai::script_data(&entity) {
         None => Err(rlua::Error::ToLuaConversionError {
             from: "Entity",
             to: "Script",
             message: Some(format!("Script called for entity '{id}' with no AI")),
         }),
-        Some(ai) => Ok(Rc::clone(ai)),
+        Some(data) => Ok(data),
     }
 }
 
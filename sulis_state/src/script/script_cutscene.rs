@@ -0,0 +1,127 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use rlua::{UserData, UserDataMethods};
+
+use crate::script::ScriptEntity;
+use crate::GameState;
+use sulis_module::on_trigger::{CutsceneData, CutsceneStep, OnTrigger};
+
+/// A scripted cutscene sequence being created by a script.  Normally created
+/// by `game:create_cutscene()`.  Steps are added in order and then run one after
+/// another once `play()` is called.  While the cutscene plays, player input is
+/// locked.  The player may skip the remainder of the cutscene by pressing Escape,
+/// in which case any remaining movement is completed instantly.
+///
+/// # `move_entity(entity: ScriptEntity, x: Int, y: Int)`
+/// Adds a step moving the `entity` to the specified `x`, `y` coordinates using
+/// a normal movement animation.  The step completes when the entity arrives.
+///
+/// # `show_text(entity: ScriptEntity, text: String, duration: Float (Optional))`
+/// Adds a step showing the specified `text` above the `entity`.  The cutscene
+/// waits for `duration` seconds, or 2 seconds if not specified, before continuing.
+///
+/// # `pan_to(x: Int, y: Int)`
+/// Adds a step smoothly scrolling the view to center on the `x`, `y` coordinates.
+///
+/// # `fade_out()`
+/// Adds a step fading the screen to black and then back in.  The cutscene
+/// continues once the fade is complete.
+///
+/// # `wait(duration: Float)`
+/// Adds a step that waits for `duration` seconds before continuing.
+///
+/// # `play()`
+/// Begins playing this cutscene.  The cutscene is launched asynchronously on the next
+/// frame, so the remainder of the current script will execute immediately.
+#[derive(Clone)]
+pub struct ScriptCutscene {
+    steps: Vec<CutsceneStep>,
+}
+
+impl ScriptCutscene {
+    pub fn new() -> ScriptCutscene {
+        ScriptCutscene { steps: Vec::new() }
+    }
+}
+
+impl Default for ScriptCutscene {
+    fn default() -> ScriptCutscene {
+        ScriptCutscene::new()
+    }
+}
+
+fn to_millis(secs: f32) -> u32 {
+    (secs.max(0.0) * 1000.0) as u32
+}
+
+impl UserData for ScriptCutscene {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "move_entity",
+            |_, cutscene, (entity, x, y): (ScriptEntity, i32, i32)| {
+                let entity = entity.try_unwrap()?;
+                let entity = entity.borrow().index();
+                cutscene
+                    .steps
+                    .push(CutsceneStep::MoveEntity { entity, x, y });
+                Ok(())
+            },
+        );
+
+        methods.add_method_mut(
+            "show_text",
+            |_, cutscene, (entity, text, duration): (ScriptEntity, String, Option<f32>)| {
+                let entity = entity.try_unwrap()?;
+                let entity = entity.borrow().index();
+                let millis = to_millis(duration.unwrap_or(2.0));
+                cutscene.steps.push(CutsceneStep::ShowText {
+                    entity,
+                    text,
+                    millis,
+                });
+                Ok(())
+            },
+        );
+
+        methods.add_method_mut("pan_to", |_, cutscene, (x, y): (i32, i32)| {
+            cutscene.steps.push(CutsceneStep::PanTo { x, y });
+            Ok(())
+        });
+
+        methods.add_method_mut("fade_out", |_, cutscene, ()| {
+            cutscene.steps.push(CutsceneStep::FadeOut);
+            Ok(())
+        });
+
+        methods.add_method_mut("wait", |_, cutscene, duration: f32| {
+            let millis = to_millis(duration);
+            cutscene.steps.push(CutsceneStep::Wait { millis });
+            Ok(())
+        });
+
+        methods.add_method("play", |_, cutscene, ()| {
+            let data = CutsceneData {
+                steps: cutscene.steps.clone(),
+            };
+
+            let pc = GameState::player();
+            let cb = OnTrigger::PlayCutscene(data);
+            GameState::add_ui_callback(vec![cb], &pc, &pc);
+            Ok(())
+        });
+    }
+}
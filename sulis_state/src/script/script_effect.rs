@@ -27,12 +27,14 @@ use sulis_module::{
 };
 
 use crate::script::{
-    script_color_animation, script_image_layer_animation, script_particle_generator,
-    script_scale_animation, script_subpos_animation, CallbackData, Result, ScriptAbility,
-    ScriptCallback, ScriptColorAnimation, ScriptEntity, ScriptImageLayerAnimation,
-    ScriptParticleGenerator, ScriptScaleAnimation, ScriptSubposAnimation,
+    current_script_id, script_color_animation, script_image_layer_animation,
+    script_particle_generator, script_scale_animation, script_subpos_animation, CallbackData,
+    FuncKind, Result, ScriptAbility, ScriptCallback, ScriptColorAnimation, ScriptEntity,
+    ScriptImageLayerAnimation, ScriptParticleGenerator, ScriptScaleAnimation,
+    ScriptSubposAnimation,
 };
-use crate::{effect, Effect, GameState};
+use crate::effect::StackingPolicy;
+use crate::{effect, Effect, EntityState, GameState, TurnManager};
 
 /// Represents a surface that already exists, and is being passed into
 /// a Lua script.  Not used during effect creation
@@ -70,6 +72,7 @@ enum Kind {
         points: Vec<(i32, i32)>,
         squares_to_fire_on_moved: u32,
         aura: Option<usize>,
+        removes_tags: Vec<String>,
     },
 }
 
@@ -300,6 +303,18 @@ impl UserData for ScriptAppliedEffect {
 /// must move within a surface in order to trigger an `OnMovedInSurface` script
 /// event.
 ///
+/// # `add_removes_surface_tag(tag: String)`
+/// Only has an effect on surfaces.  When this surface is applied, any existing surface
+/// in the area with the `tag` that covers any of the same points is removed.  This
+/// allows interactions between surfaces, such as a fire surface burning away webs.
+/// ## Examples
+/// ```lua
+///   surface = parent:create_surface("Fire", targets:affected_points(), 3)
+///   surface:set_tag("fire")
+///   surface:add_removes_surface_tag("web")
+///   surface:apply()
+/// ```
+///
 /// # `set_aura(aura_parent: ScriptEntity)`
 /// Only has an effect on surfaces.  Sets whether this effect is an aura.  Auras
 /// are surfaces that move along with the parent.
@@ -332,6 +347,11 @@ impl UserData for ScriptAppliedEffect {
 /// # `add_callback(callback: CallbackData)`
 /// Adds the specified `callback` to fire for entity's with this effect.
 ///
+/// # `set_on_removed_fn(func: String)`
+/// Only has an effect on entity effects.  Calls `func` in the current script, passing
+/// the parent entity, when this effect is removed.  This is a shorthand for adding a
+/// callback created with `game:create_callback`.
+///
 /// # `deactivate_with(ability: ScriptAbility)`
 /// Sets this effect to be removed whenever the specified `ability` is deactivated.
 /// The ability must be a mode.
@@ -344,6 +364,19 @@ impl UserData for ScriptAppliedEffect {
 /// Sets a tag to identify this effect as being of a particular type to other scripts.
 /// Most notably, this is used when calling `remove_effects_with_tag` on a `ScriptEntity`
 ///
+/// # `add_tag(tag: String)`
+/// Adds an additional tag to this effect, beyond the one set with `set_tag`.  This
+/// allows an effect to belong to several categories, such as "poison" and "magic".
+/// All functions checking tags match against any of the effect's tags.
+///
+/// # `set_stacking(policy: String, value: Int (Optional))`
+/// Sets how this effect interacts with effects of the same name already applied to
+/// the parent entity.  Only has an effect on entity effects.  `policy` is one of
+/// `independent` (the default), where each effect applies separately, `refresh`, where
+/// existing effects are removed, `stack`, where up to `value` effects apply at once
+/// with the oldest being removed past that, or `strongest`, where `value` is the
+/// strength of this effect and only the strongest effect applies.
+///
 /// # `add_num_bonus(kind: String, amount: Float, when: String (Optional))`
 /// Adds a numeric bonus that is applied to the parent entity when this effect is active.
 /// Positive values are bonuses, while negative values are penalties.  `when` is optional
@@ -402,14 +435,39 @@ impl UserData for ScriptAppliedEffect {
 /// Adds a percentage damage resistance of `value` against `kind` damage
 /// as a bonus to this effect.  See `add_num_bonus`
 ///
+/// # `add_vulnerability(value: Float, kind: String, when: String (Optional))`
+/// Adds a percentage damage vulnerability of `value` against `kind` damage, increasing
+/// the damage taken.  This is equivalent to a negative resistance.  See `add_num_bonus`
+///
+/// # `add_immunity(kind: String, when: String (Optional))`
+/// Adds an immunity to `kind` damage as a bonus to this effect.  Damage of that
+/// kind is ignored entirely.  See `add_num_bonus`
+///
 /// # `add_attribute_bonus(attr: String, amount: Float, when: String (Optional))`
 /// Adds an attribute bonus for `attr` of `amount` to this effect.  Valid attributes
 /// are `Strength`, `Dexterity`, `Endurance`, `Perception`, `Intellect`, and `Wisdom`
+///
+/// # `add_bonus(kind: String, amount: Float, when: String (Optional))`
+/// Adds a bonus of any of the kinds accepted by `add_num_bonus` or any attribute
+/// accepted by `add_attribute_bonus`.  The kind `accuracy` adds the bonus to melee,
+/// ranged, and spell accuracy.  Unlike the other functions, throws an error if the
+/// kind is not valid.
+/// ## Examples
+/// ```lua
+///   effect = parent:create_effect("Battle Hymn", 3)
+///   effect:add_bonus("accuracy", 10)
+///   effect:add_bonus("strength", 2)
+///   effect:set_icon("abilities/battle_hymn", "+10 Accuracy")
+///   effect:set_on_removed_fn("on_hymn_ended")
+///   effect:apply()
+/// ```
 #[derive(Clone)]
 pub struct ScriptEffect {
     kind: Kind,
     name: String,
     tag: String,
+    tags: Vec<String>,
+    stacking: StackingPolicy,
     ui_visible: bool,
     duration: ExtInt,
     deactivate_with_ability: Option<String>,
@@ -430,9 +488,12 @@ impl ScriptEffect {
                 points,
                 squares_to_fire_on_moved: 1,
                 aura: None,
+                removes_tags: Vec::new(),
             },
             name: name.to_string(),
             tag: "default".to_string(),
+            tags: Vec::new(),
+            stacking: StackingPolicy::Independent,
             ui_visible: true,
             deactivate_with_ability: None,
             duration,
@@ -452,6 +513,8 @@ impl ScriptEffect {
             kind: Kind::Entity(parent),
             name: name.to_string(),
             tag: "default".to_string(),
+            tags: Vec::new(),
+            stacking: StackingPolicy::Independent,
             ui_visible: true,
             deactivate_with_ability: None,
             duration,
@@ -497,6 +560,17 @@ impl UserData for ScriptEffect {
             }
             Ok(())
         });
+        methods.add_method_mut("add_removes_surface_tag", |_, effect, tag: String| {
+            match effect.kind {
+                Kind::Entity(_) => {
+                    warn!("Attempted to add removes surface tag on non-surface effect.");
+                }
+                Kind::Surface { ref mut removes_tags, .. } => {
+                    removes_tags.push(tag);
+                }
+            }
+            Ok(())
+        });
         methods.add_method_mut(
             "add_image_layer_anim",
             |_, effect, anim: ScriptImageLayerAnimation| {
@@ -523,6 +597,28 @@ impl UserData for ScriptEffect {
             effect.pgens.push(pgen);
             Ok(())
         });
+        methods.add_method_mut("set_on_removed_fn", |_, effect, func: String| {
+            let parent = match effect.kind {
+                Kind::Entity(parent) => parent,
+                _ => {
+                    warn!("Attempted to set on removed fn for non entity effect");
+                    return Ok(());
+                }
+            };
+
+            let script = match current_script_id() {
+                None => {
+                    warn!("Attempted to set on removed fn outside of a script");
+                    return Ok(());
+                }
+                Some(script) => script,
+            };
+
+            let mut cb = CallbackData::new_trigger(parent, script);
+            cb.add_func(FuncKind::OnRemoved, func);
+            effect.callbacks.push(cb);
+            Ok(())
+        });
         methods.add_method_mut("add_callback", |_, effect, cb: CallbackData| {
             effect.callbacks.push(cb);
             Ok(())
@@ -535,11 +631,36 @@ impl UserData for ScriptEffect {
             effect.tag = tag;
             Ok(())
         });
+        methods.add_method_mut("add_tag", |_, effect, tag: String| {
+            effect.tags.push(tag);
+            Ok(())
+        });
+        methods.add_method_mut(
+            "set_stacking",
+            |_, effect, (policy, value): (String, Option<i32>)| {
+                effect.stacking = match policy.as_ref() {
+                    "independent" => StackingPolicy::Independent,
+                    "refresh" => StackingPolicy::Refresh,
+                    "stack" => StackingPolicy::Stack {
+                        cap: value.unwrap_or(1).max(1) as u32,
+                    },
+                    "strongest" => StackingPolicy::Strongest {
+                        strength: value.unwrap_or(0),
+                    },
+                    _ => {
+                        warn!("Invalid effect stacking policy '{}'", policy);
+                        return Ok(());
+                    }
+                };
+                Ok(())
+            },
+        );
         methods.add_method_mut("set_ui_visible", |_, effect, vis: bool| {
             effect.ui_visible = vis;
             Ok(())
         });
         methods.add_method_mut("add_num_bonus", &add_num_bonus);
+        methods.add_method_mut("add_bonus", &add_bonus);
         methods.add_method_mut(
             "add_damage",
             |_, effect, (min, max, ap, when): (f32, f32, Option<f32>, Option<String>)| {
@@ -643,6 +764,27 @@ impl UserData for ScriptEffect {
                 Ok(())
             },
         );
+        methods.add_method_mut(
+            "add_vulnerability",
+            |_, effect, (value, kind, when): (f32, String, Option<String>)| {
+                let value = value as i32;
+                let dmg_kind = DamageKind::unwrap_from_str(&kind);
+                let kind = BonusKind::Resistance {
+                    kind: dmg_kind,
+                    amount: -value,
+                };
+                add_bonus_to_effect(effect, kind, when);
+                Ok(())
+            },
+        );
+        methods.add_method_mut(
+            "add_immunity",
+            |_, effect, (kind, when): (String, Option<String>)| {
+                let kind = BonusKind::Immunity(DamageKind::unwrap_from_str(&kind));
+                add_bonus_to_effect(effect, kind, when);
+                Ok(())
+            },
+        );
         methods.add_method_mut(
             "add_attribute_bonus",
             |_, effect, (attr, amount, when): (String, f32, Option<String>)| {
@@ -777,11 +919,25 @@ fn add_num_bonus(
     (name, amount, when): (String, f32, Option<String>),
 ) -> Result<()> {
     let name = name.to_lowercase();
-    let amount_int = amount as i32;
 
     trace!("Adding numeric bonus {} to '{}'", amount, name);
+    let kind = match num_bonus_kind(&name, amount) {
+        None => {
+            warn!("Attempted to add num bonus with invalid type '{}'", name);
+            return Ok(());
+        }
+        Some(kind) => kind,
+    };
+
+    add_bonus_to_effect(effect, kind, when);
+    Ok(())
+}
+
+fn num_bonus_kind(name: &str, amount: f32) -> Option<BonusKind> {
+    let amount_int = amount as i32;
+
     use sulis_module::bonus::BonusKind::*;
-    let kind = match name.as_ref() {
+    Some(match name {
         "ability_ap_cost" => AbilityActionPointCost(amount_int),
         "armor" => Armor(amount_int),
         "ap" => ActionPoints(amount_int),
@@ -809,20 +965,116 @@ fn add_num_bonus(
         "attack_cost" => AttackCost(amount_int),
         "caster_level" => CasterLevel(amount_int),
         "flanking_angle" => FlankingAngle(amount_int),
-        _ => {
-            warn!("Attempted to add num bonus with invalid type '{}'", name);
-            return Ok(());
+        _ => return None,
+    })
+}
+
+fn add_bonus(
+    _lua: Context,
+    effect: &mut ScriptEffect,
+    (name, amount, when): (String, f32, Option<String>),
+) -> Result<()> {
+    let name = name.to_lowercase();
+
+    let attribute = Attribute::iter().find(|attr| attr.name().to_lowercase() == name);
+    if let Some(attribute) = attribute {
+        let kind = BonusKind::Attribute {
+            attribute: *attribute,
+            amount: amount as i8,
+        };
+        add_bonus_to_effect(effect, kind, when);
+        return Ok(());
+    }
+
+    if name == "accuracy" {
+        for kind in &["melee_accuracy", "ranged_accuracy", "spell_accuracy"] {
+            if let Some(kind) = num_bonus_kind(kind, amount) {
+                add_bonus_to_effect(effect, kind, when.clone());
+            }
+        }
+        return Ok(());
+    }
+
+    match num_bonus_kind(&name, amount) {
+        None => Err(rlua::Error::FromLuaConversionError {
+            from: "String",
+            to: "BonusKind",
+            message: Some(format!("Invalid bonus kind '{name}'")),
+        }),
+        Some(kind) => {
+            add_bonus_to_effect(effect, kind, when);
+            Ok(())
+        }
+    }
+}
+
+/// Removes any existing effects on the entity as required by the stacking
+/// policy of the new effect.  Returns false if the new effect should not be
+/// applied at all.
+fn check_stacking(
+    mgr: &mut TurnManager,
+    entity: &EntityState,
+    name: &str,
+    stacking: StackingPolicy,
+) -> bool {
+    if stacking == StackingPolicy::Independent {
+        return true;
+    }
+
+    let existing: Vec<usize> = entity
+        .actor
+        .effects_iter()
+        .copied()
+        .filter(|index| {
+            let effect = mgr.effect(*index);
+            effect.name == name && !effect.is_expired()
+        })
+        .collect();
+
+    let to_remove = match stacking {
+        StackingPolicy::Independent => return true,
+        StackingPolicy::Refresh => existing,
+        StackingPolicy::Stack { cap } => {
+            let excess = (existing.len() + 1).saturating_sub(cap as usize);
+            existing.into_iter().take(excess).collect()
+        }
+        StackingPolicy::Strongest { strength } => {
+            let stronger = existing.iter().any(|index| match mgr.effect(*index).stacking {
+                StackingPolicy::Strongest { strength: other } => other > strength,
+                _ => false,
+            });
+            if stronger {
+                debug!("Not applying '{}' as a stronger effect is active", name);
+                return false;
+            }
+            existing
         }
     };
 
-    add_bonus_to_effect(effect, kind, when);
-    Ok(())
+    for index in to_remove {
+        mgr.effect_mut(index).mark_for_removal();
+    }
+
+    true
 }
 
 fn apply(effect_data: &ScriptEffect) -> Result<()> {
     let mgr = GameState::turn_manager();
     let duration = effect_data.duration * ROUND_TIME_MILLIS;
 
+    if let Kind::Entity(parent) = effect_data.kind {
+        let entity = mgr.borrow().entity(parent);
+        let apply = check_stacking(
+            &mut mgr.borrow_mut(),
+            &entity.borrow(),
+            &effect_data.name,
+            effect_data.stacking,
+        );
+        if !apply {
+            return Ok(());
+        }
+    }
+
     debug!(
         "Apply effect with {}, {}, {}",
         effect_data.name, effect_data.tag, duration
@@ -835,6 +1087,8 @@ fn apply(effect_data: &ScriptEffect) -> Result<()> {
         effect_data.deactivate_with_ability.clone(),
     );
     effect.ui_visible = effect_data.ui_visible;
+    effect.tags = effect_data.tags.clone();
+    effect.stacking = effect_data.stacking;
     if let Some(icon) = &effect_data.icon {
         effect.set_icon(icon.icon.clone(), icon.text.clone());
     }
@@ -898,6 +1152,7 @@ fn apply(effect_data: &ScriptEffect) -> Result<()> {
             points,
             squares_to_fire_on_moved,
             aura,
+            removes_tags,
         } => {
             let points: Vec<_> = points.iter().map(|(x, y)| Point::new(*x, *y)).collect();
             for pgen in effect_data.pgens.iter() {
@@ -909,6 +1164,11 @@ fn apply(effect_data: &ScriptEffect) -> Result<()> {
                 }
             }
             let area = GameState::area_state();
+            for tag in removes_tags.iter() {
+                let area_id = &area.borrow().area.area.id;
+                mgr.borrow_mut()
+                    .remove_surfaces_with_tag(area_id, &points, tag);
+            }
             effect.set_surface_for_area(
                 &area.borrow().area.area.id,
                 &points,
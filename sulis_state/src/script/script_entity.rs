@@ -24,13 +24,15 @@ use rlua::{self, Context, UserData, UserDataMethods};
 
 use crate::{ability_state::DisabledReason, dist, is_within_attack_dist, is_within_touch_dist};
 use crate::{ai, animation, entity_attack_handler, script::*, AreaFeedbackText};
-use crate::{area_feedback_text::ColorKind, EntityState, GameState, Location};
-use sulis_core::config::Config;
+use crate::{area_feedback_text::ColorKind, Effect, EntityState, GameState, Location};
+use sulis_core::config::{Config, FeedbackChannel};
 use sulis_core::resource::ResourceSet;
+use sulis_core::ui::Color;
 use sulis_core::util::ExtInt;
 use sulis_module::{
-    ability::AIData, Actor, Attack, AttackKind, Attribute, DamageKind, Faction, HitFlags, HitKind,
-    ImageLayer, InventoryBuilder, MOVE_TO_THRESHOLD, area::Destination,
+    ability::AIData, Actor, Attack, AttackKind, Attribute, BonusList, DamageKind, Facing, Faction,
+    HitFlags, HitKind, ImageLayer, InventoryBuilder, Item, ItemState, LightSource, Module, Slot,
+    MOVE_TO_THRESHOLD, ROUND_TIME_MILLIS, area::Destination,
 };
 
 /// Represents a single entity for Lua scripts.  Also can represent an invalid,
@@ -86,6 +88,15 @@ use sulis_module::{
 /// # `remove_from_party()`
 /// Removes this entity from the player's party
 ///
+/// # `summon(actor_id: String, x: Int, y: Int, duration: Int (Optional)) -> ScriptEntity`
+/// Spawns an instance of the actor with `actor_id` at `x`, `y` in this entity's
+/// area, with the same faction as this entity.  If this entity is a party member,
+/// the summon joins the party without a portrait.  The summon lasts for `duration`
+/// rounds, or indefinitely if not specified, via an effect on this entity.  The
+/// summon is removed when that effect ends, when this entity dies, or when the party
+/// leaves the area.  Returns the summoned ScriptEntity, or the invalid ScriptEntity
+/// if the actor or location is invalid.
+///
 /// # `get_relationship(other: ScriptEntity) -> Int`
 /// Returns a positive 1 if this entity is friendly or neutral to the specified other
 /// entity, or a negative 1 if it is hostile.
@@ -104,6 +115,23 @@ use sulis_module::{
 /// `Hostile`, `Neutral`, or `Friendly`.  Hostiles will attack the player and
 /// friendlies on sight, but will not engage neutrals.
 ///
+/// # `get_facing() -> String`
+/// Returns the direction this entity is currently facing, one of `North`,
+/// `East`, `South`, or `West`.  Entities face the direction they move and the
+/// targets they attack, and face `South` until they first do so.
+///
+/// # `set_facing(facing: String)`
+/// Turns this entity to face the specified direction, one of `North`, `East`,
+/// `South`, or `West`.
+///
+/// # `face_towards(target: ScriptEntity)`
+/// Turns this entity to face the specified `target`.
+///
+/// # `is_behind(target: ScriptEntity) -> Bool`
+/// Returns true if this entity is behind the specified `target`, based on the
+/// direction the target is facing.  Attacks from behind receive backstab and
+/// flanking bonuses.
+///
 /// # `set_flag(flag: String, value: String (Optional))`
 /// Sets a `flag` to be stored on this entity.  This value will persist as part of the
 /// save game and can be used to store custom state.  If the value is not specified,
@@ -131,6 +159,15 @@ use sulis_module::{
 /// numeric and standard flags.  If the flag had not previously been set, does nothing.
 /// After this method, `has_flag(flag)` will return `false`.
 ///
+/// # `set_light(radius: Float, r: Float (Optional), g: Float (Optional), b: Float (Optional))`
+/// Sets this entity to emit a light with the specified `radius` in tiles, and color
+/// components `r`, `g`, and `b`, which default to 1.0.  The light only has an effect
+/// in dark areas, and persists until `clear_light` is called.
+///
+/// # `clear_light()`
+/// Removes any light set on this entity via `set_light`.  Lights from equipped
+/// items are not affected.
+///
 /// # `is_valid() -> Bool`
 /// Returns true if this ScriptEntity references a valid entity that can be queried and
 /// acted on, false otherwise.
@@ -186,6 +223,11 @@ use sulis_module::{
 /// # `remove_effects_with_tag(tag: String)`
 /// Removes all currently active effects applied to this entity that have the specified tag.
 ///
+/// # `dispel(tag: String, count: Int (Optional)) -> Int`
+/// Removes up to `count` currently active effects applied to this entity with the
+/// specified tag, oldest first.  If `count` is not specified, only one effect is removed.
+/// Returns the number of effects that were removed.
+///
 /// # `create_effect(name: String, duration: Int (Optional)) -> ScriptEffect`
 /// Creates a new effect with the specified `name` and `duration`.  If `duration` is not
 /// specified, it is infinite, and will remain until removed or deactivated for a mode.
@@ -214,6 +256,16 @@ use sulis_module::{
 /// `duration` in seconds.  The animation is set up with further calls before
 /// calling `activate()`.
 ///
+/// # `create_projectile_anim(image: String) -> ScriptProjectileAnimation`
+/// Creates a projectile animation using the specified `image`, starting at the center
+/// of this entity.  Waypoints and callbacks are set up with further calls before
+/// calling `activate()`.  See `ScriptProjectileAnimation`.
+///
+/// # `create_timeline() -> ScriptTimeline`
+/// Creates a timeline, which runs other script animations in sequence or in
+/// parallel, with a single completion callback.  Animations are added with further
+/// calls before calling `activate()`.  See `ScriptTimeline`.
+///
 /// # `create_color_anim(duration: Float (Optional)) -> ScriptColorAnimation`
 /// Creates an entity color animation, which changes the primary and secondary
 /// colors of the parent entity.  If `duration` is specified, lasts for that many seconds.
@@ -284,6 +336,13 @@ use sulis_module::{
 /// # `has_visibility(target: ScriptEntity) -> Bool`
 /// Returns true if this entity can see the `target`, false otherwise.
 ///
+/// # `elevation() -> Int`
+/// Returns the terrain elevation at the center of this entity.
+///
+/// # `has_high_ground(target: ScriptEntity) -> Bool`
+/// Returns true if this entity is standing at a higher elevation than the
+/// `target`, false otherwise.
+///
 /// # `can_move() -> Bool`
 /// Returns true if this entity can move at all (even 1 square), false otherwise.
 ///
@@ -334,9 +393,26 @@ use sulis_module::{
 /// based on this entity's armor.  The damage is rolled randomly between `min_damage` and
 /// `max_damage`, with the specified (`ap`) amount of armor piercing.
 ///
-/// # `heal_damage(amount: Float)`
+/// # `heal_damage(amount: Float, healer: ScriptEntity (Optional))`
 /// Adds the specified number of hit points to this entity.  The entity's maximum hit
-/// points cannot be exceeded in this way.
+/// points cannot be exceeded in this way.  If a `healer` is specified, entities
+/// hostile to this entity that are already fighting it gain threat towards the healer.
+///
+/// # `add_threat(target: ScriptEntity, amount: Float)`
+/// Adds the specified `amount` of threat this entity holds towards the `target`.
+/// Threat is also gained automatically from damage and healing, and decays each
+/// round.  A taunt can be implemented by adding a large amount of threat.  The
+/// amount may be negative to reduce threat, but threat will not go below zero.
+///
+/// # `get_threat(target: ScriptEntity) -> Float`
+/// Returns the current threat this entity holds towards the `target`.
+///
+/// # `highest_threat_target() -> ScriptEntity`
+/// Returns the entity this entity currently holds the most threat towards, or
+/// nil if it holds no threat.
+///
+/// # `clear_threat()`
+/// Removes all threat held by this entity.
 ///
 /// # `add_class_stat(stat: String, amount: Float)`
 /// Adds the specified amount of the specified stat for this entity.  The entity's maximum
@@ -406,9 +482,67 @@ use sulis_module::{
 /// Creates and returns a stats table for this entity.  This includes all stats shown on the
 /// character sheet.
 ///
+/// # `resistance(kind: String) -> Int`
+/// Returns this entity's percentage resistance against `kind` damage.  Negative values
+/// are vulnerabilities.
+///
+/// # `is_immune(kind: String) -> Bool`
+/// Returns true if this entity takes no damage of the specified `kind`.
+///
+/// # `damage_factor(kind: String) -> Float`
+/// Returns the multiplier this entity's resistances, vulnerabilities, and immunities
+/// apply to `kind` damage, before armor.  Useful in AI scripts for picking the target
+/// most susceptible to an attack.
+/// ## Examples
+/// ```lua
+///   best = nil
+///   for i = 1, #targets do
+///     if best == nil or targets[i]:damage_factor("Fire") > best:damage_factor("Fire") then
+///       best = targets[i]
+///     end
+///   end
+/// ```
+///
 /// # `inventory() -> ScriptInventory`
 /// Returns a `ScriptInventory` object representing this entity's inventory.
 ///
+/// # `add_item(id: String, quantity: Int (Optional))`
+/// Adds the specified `quantity` (default 1) of the item with `id` to the inventory of
+/// this entity.  Party members share the party stash, so the items are placed there.
+/// Throws an error if this entity is not a party member or the item does not exist.
+///
+/// # `remove_item(id: String, quantity: Int (Optional), allow_quest: Bool (Optional)) -> Int`
+/// Removes up to `quantity` (default 1) of the item with `id`, first from the party stash
+/// if this entity is a party member, and then from the items equipped by this entity.
+/// Returns the number of items removed.  Quest items are protected, and removing one
+/// throws an error unless `allow_quest` is true.
+///
+/// # `has_item(id: String, quantity: Int (Optional)) -> Bool`
+/// Returns true if at least `quantity` (default 1) of the item with `id` are equipped
+/// by this entity, or are in the party stash for party members.
+///
+/// # `equip(slot: String, id: String) -> Bool`
+/// Equips the item with `id` in the specified inventory `slot`.  For party members, the
+/// item is taken from the party stash if present, and any unequipped items are placed in
+/// the stash.  Otherwise, a new item is created.  Returns true if the item was equipped.
+/// If the item cannot be equipped in `slot`, nothing is changed and false is returned.
+/// See `ScriptInventory::has_equipped` for valid slots.
+/// ## Examples
+/// ```lua
+///   if not parent:has_item("toll_token") then return end
+///   parent:remove_item("toll_token")
+///   parent:equip("neck", "amulet_of_passage")
+/// ```
+///
+/// # `durability(slot: String) -> Int`
+/// Returns the remaining durability of the item equipped in `slot`, or nil if there is no
+/// item or the item does not degrade.
+///
+/// # `repair_item(slot: String, amount: Int (Optional)) -> Bool`
+/// Restores `amount` durability to the item equipped in `slot`, or fully repairs it if
+/// `amount` is not specified.  Returns false if there was no damaged item to repair.
+/// Allows crafting abilities to repair gear in the field.
+///
 /// # `race() -> String`
 /// Returns the ID of the race of this entity
 ///
@@ -649,6 +783,14 @@ impl UserData for ScriptEntity {
             Ok(())
         });
 
+        methods.add_method(
+            "summon",
+            |_, entity, (id, x, y, duration): (String, i32, i32, Option<u32>)| {
+                let parent = entity.try_unwrap()?;
+                summon(&parent, &id, x, y, duration)
+            },
+        );
+
         methods.add_method("get_relationship", |_, entity, other: ScriptEntity| {
             let entity = entity.try_unwrap()?;
             let other = other.try_unwrap()?;
@@ -699,6 +841,41 @@ impl UserData for ScriptEntity {
             Ok(())
         });
 
+        methods.add_method("get_facing", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let facing = entity.borrow().facing();
+            Ok(facing.unwrap_or_default().to_str())
+        });
+
+        methods.add_method("set_facing", |_, entity, facing: String| {
+            let entity = entity.try_unwrap()?;
+
+            match Facing::option_from_str(&facing) {
+                None => warn!("Invalid facing '{}' in script", facing),
+                Some(facing) => entity.borrow_mut().set_facing(facing),
+            }
+
+            Ok(())
+        });
+
+        methods.add_method("face_towards", |_, entity, target: ScriptEntity| {
+            let entity = entity.try_unwrap()?;
+            let target = target.try_unwrap()?;
+            if Rc::ptr_eq(&entity, &target) {
+                return Ok(());
+            }
+
+            entity.borrow_mut().face_towards(&target.borrow());
+            Ok(())
+        });
+
+        methods.add_method("is_behind", |_, entity, target: ScriptEntity| {
+            let entity = entity.try_unwrap()?;
+            let target = target.try_unwrap()?;
+            let result = entity_attack_handler::is_backstab(&entity.borrow(), &target.borrow());
+            Ok(result)
+        });
+
         methods.add_method("get_num_flag", |_, entity, flag: String| {
             let entity = entity.try_unwrap()?;
             let val = entity.borrow().get_num_flag(&flag);
@@ -731,6 +908,23 @@ impl UserData for ScriptEntity {
             Ok(())
         });
 
+        methods.add_method(
+            "set_light",
+            |_, entity, (radius, r, g, b): (f32, Option<f32>, Option<f32>, Option<f32>)| {
+                let entity = entity.try_unwrap()?;
+                let color = Color::new(r.unwrap_or(1.0), g.unwrap_or(1.0), b.unwrap_or(1.0), 1.0);
+                let light = LightSource { radius, color };
+                entity.borrow_mut().set_script_light(Some(light));
+                Ok(())
+            },
+        );
+
+        methods.add_method("clear_light", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            entity.borrow_mut().set_script_light(None);
+            Ok(())
+        });
+
         methods.add_method("has_flag", |_, entity, flag: String| {
             let entity = entity.try_unwrap()?;
             let result = entity.borrow().has_custom_flag(&flag);
@@ -832,7 +1026,7 @@ impl UserData for ScriptEntity {
             let mut result = Vec::new();
             for effect_index in entity.actor.effects_iter() {
                 let effect = mgr.effect(*effect_index);
-                if effect.tag != tag {
+                if !effect.has_tag(&tag) {
                     continue;
                 }
 
@@ -853,7 +1047,7 @@ impl UserData for ScriptEntity {
             let mut result = Vec::new();
             for effect_index in indices {
                 let effect = mgr.effect(effect_index);
-                if effect.tag != tag {
+                if !effect.has_tag(&tag) {
                     continue;
                 }
                 let sae = ScriptAppliedEffect::new(effect, effect_index);
@@ -870,7 +1064,7 @@ impl UserData for ScriptEntity {
 
             for effect_index in entity.actor.effects_iter() {
                 let effect = mgr.effect(*effect_index);
-                if effect.tag == tag {
+                if effect.has_tag(&tag) {
                     return Ok(true);
                 }
             }
@@ -887,7 +1081,7 @@ impl UserData for ScriptEntity {
 
             for effect_index in entity.actor.effects_iter() {
                 let effect = mgr.effect_mut(*effect_index);
-                if effect.tag == tag {
+                if effect.has_tag(&tag) {
                     effect.mark_for_removal();
                 }
             }
@@ -895,6 +1089,30 @@ impl UserData for ScriptEntity {
             Ok(())
         });
 
+        methods.add_method("dispel", |_, entity, (tag, count): (String, Option<u32>)| {
+            let entity = entity.try_unwrap()?;
+            let entity = entity.borrow();
+
+            let mgr = GameState::turn_manager();
+            let mut mgr = mgr.borrow_mut();
+
+            let count = count.unwrap_or(1);
+            let mut removed = 0;
+            for effect_index in entity.actor.effects_iter() {
+                if removed >= count {
+                    break;
+                }
+
+                let effect = mgr.effect_mut(*effect_index);
+                if effect.has_tag(&tag) && !effect.is_expired() {
+                    effect.mark_for_removal();
+                    removed += 1;
+                }
+            }
+
+            Ok(removed)
+        });
+
         methods.add_method(
             "create_surface",
             |_, _, (name, points, duration): (String, Vec<HashMap<String, i32>>, Option<u32>)| {
@@ -962,6 +1180,16 @@ impl UserData for ScriptEntity {
             },
         );
 
+        methods.add_method("create_projectile_anim", |_, entity, image: String| {
+            let index = entity.try_unwrap_index()?;
+            Ok(ScriptProjectileAnimation::new(index, image))
+        });
+
+        methods.add_method("create_timeline", |_, entity, ()| {
+            let index = entity.try_unwrap_index()?;
+            Ok(ScriptTimeline::new(index))
+        });
+
         methods.add_method(
             "create_color_anim",
             |_, entity, duration_secs: Option<f32>| {
@@ -1084,6 +1312,23 @@ impl UserData for ScriptEntity {
             Ok(result)
         });
 
+        methods.add_method("elevation", |_, entity, ()| {
+            let parent = entity.try_unwrap()?;
+            let area_state = GameState::area_state();
+            let area_state = area_state.borrow();
+            let result = area_state.entity_elevation(&parent.borrow());
+            Ok(result)
+        });
+
+        methods.add_method("has_high_ground", |_, entity, target: ScriptEntity| {
+            let parent = entity.try_unwrap()?;
+            let target = target.try_unwrap()?;
+            let area_state = GameState::area_state();
+            let area_state = area_state.borrow();
+            let result = area_state.has_high_ground(&parent.borrow(), &target.borrow());
+            Ok(result)
+        });
+
         methods.add_method("can_move", |_, entity, ()| {
             let parent = entity.try_unwrap()?;
             let result = parent.borrow().can_move();
@@ -1119,7 +1364,7 @@ impl UserData for ScriptEntity {
                 }
             } else {
                 let mut area_state = area_state.borrow_mut();
-                area_state.move_entity(&entity, x, y, 0);
+                area_state.move_entity(&entity, x, y, 0, 0);
             }
 
             Ok(())
@@ -1281,7 +1526,7 @@ impl UserData for ScriptEntity {
 
                 let min_damage = min_damage as u32;
                 let max_damage = max_damage as u32;
-                let damage = {
+                let mut damage = {
                     let parent = &parent.borrow().actor.stats;
                     let attack = Attack::special(
                         parent,
@@ -1294,6 +1539,7 @@ impl UserData for ScriptEntity {
                     let damage = &attack.damage;
                     rules.roll_damage(damage, &parent.armor, &parent.resistance, 1.0)
                 };
+                GameState::scale_enemy_damage(&attacker.borrow(), &mut damage);
 
                 if !damage.is_empty() {
                     EntityState::remove_hp(&parent, &attacker, HitKind::Hit, damage.clone());
@@ -1313,23 +1559,62 @@ impl UserData for ScriptEntity {
             },
         );
 
-        methods.add_method("heal_damage", |_, entity, amount: f32| {
-            let amount = amount as u32;
-            let parent = entity.try_unwrap()?;
-            {
-                let mut parent = parent.borrow_mut();
-                if !parent.is_party_member() && parent.actor.hp() == 0 {
-                    return Ok(());
+        methods.add_method(
+            "heal_damage",
+            |_, entity, (amount, healer): (f32, Option<ScriptEntity>)| {
+                let amount = amount as u32;
+                let parent = entity.try_unwrap()?;
+                {
+                    let mut parent = parent.borrow_mut();
+                    if !parent.is_party_member() && parent.actor.hp() == 0 {
+                        return Ok(());
+                    }
+                    parent.actor.add_hp(amount);
                 }
-                parent.actor.add_hp(amount);
-            }
-            let area_state = GameState::area_state();
+                if let Some(healer) = healer {
+                    let healer = healer.try_unwrap()?;
+                    ai::add_healing_threat(&healer, &parent, amount as f32);
+                }
+                let area_state = GameState::area_state();
+
+                let mut feedback =
+                    AreaFeedbackText::with_target(&parent.borrow(), &area_state.borrow());
+                feedback.set_channel(FeedbackChannel::Healing);
+                feedback.add_entry(format!("{amount}"), ColorKind::Heal);
+                area_state.borrow_mut().add_feedback_text(feedback);
+
+                Ok(())
+            },
+        );
+
+        methods.add_method(
+            "add_threat",
+            |_, entity, (target, amount): (ScriptEntity, f32)| {
+                let entity = entity.try_unwrap()?;
+                let target = target.try_unwrap()?;
+                let index = target.borrow().index();
+                entity.borrow_mut().threat.add(index, amount);
+                Ok(())
+            },
+        );
+
+        methods.add_method("get_threat", |_, entity, target: ScriptEntity| {
+            let entity = entity.try_unwrap()?;
+            let target = target.try_unwrap()?;
+            let index = target.borrow().index();
+            let threat = entity.borrow().threat.threat(index);
+            Ok(threat)
+        });
 
-            let mut feedback =
-                AreaFeedbackText::with_target(&parent.borrow(), &area_state.borrow());
-            feedback.add_entry(format!("{amount}"), ColorKind::Heal);
-            area_state.borrow_mut().add_feedback_text(feedback);
+        methods.add_method("highest_threat_target", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let index = entity.borrow().threat.highest();
+            Ok(index.map(ScriptEntity::new))
+        });
 
+        methods.add_method("clear_threat", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            entity.borrow_mut().threat.clear();
             Ok(())
         });
 
@@ -1461,21 +1746,13 @@ impl UserData for ScriptEntity {
         methods.add_method("ability_level_from_id", |_, entity, ability_id: String| {
             let entity = entity.try_unwrap()?;
             let entity = entity.borrow();
-
-            match entity.actor.actor.ability_level(&ability_id) {
-                None => Ok(0),
-                Some(level) => Ok(level + 1),
-            }
+            Ok(entity.actor.ability_tier(&ability_id))
         });
 
         methods.add_method("ability_level", |_, entity, ability: ScriptAbility| {
             let entity = entity.try_unwrap()?;
             let entity = entity.borrow();
-
-            match entity.actor.actor.ability_level(&ability.id) {
-                None => Ok(0),
-                Some(level) => Ok(level + 1),
-            }
+            Ok(entity.actor.ability_tier(&ability.id))
         });
 
         methods.add_method("has_active_mode", |_, entity, ()| {
@@ -1504,6 +1781,27 @@ impl UserData for ScriptEntity {
 
         methods.add_method("stats", create_stats_table);
 
+        methods.add_method("resistance", |_, entity, kind: String| {
+            let entity = entity.try_unwrap()?;
+            let kind = DamageKind::unwrap_from_str(&kind);
+            let amount = entity.borrow().actor.stats.resistance.amount(kind);
+            Ok(amount)
+        });
+
+        methods.add_method("is_immune", |_, entity, kind: String| {
+            let entity = entity.try_unwrap()?;
+            let kind = DamageKind::unwrap_from_str(&kind);
+            let immune = entity.borrow().actor.stats.resistance.is_immune(kind);
+            Ok(immune)
+        });
+
+        methods.add_method("damage_factor", |_, entity, kind: String| {
+            let entity = entity.try_unwrap()?;
+            let kind = DamageKind::unwrap_from_str(&kind);
+            let factor = entity.borrow().actor.stats.resistance.damage_factor(kind);
+            Ok(factor)
+        });
+
         methods.add_method("race", |_, entity, ()| {
             let entity = entity.try_unwrap()?;
             let race_id = entity.borrow().actor.actor.race.id.to_string();
@@ -1540,6 +1838,51 @@ impl UserData for ScriptEntity {
             Ok(ScriptInventory::new(entity.clone()))
         });
 
+        methods.add_method("add_item", |_, entity, (id, qty): (String, Option<u32>)| {
+            let entity = entity.try_unwrap()?;
+            add_item(&entity, &id, qty.unwrap_or(1))
+        });
+
+        methods.add_method(
+            "remove_item",
+            |_, entity, (id, qty, allow_quest): (String, Option<u32>, Option<bool>)| {
+                let entity = entity.try_unwrap()?;
+                remove_item(&entity, &id, qty.unwrap_or(1), allow_quest.unwrap_or(false))
+            },
+        );
+
+        methods.add_method("has_item", |_, entity, (id, qty): (String, Option<u32>)| {
+            let entity = entity.try_unwrap()?;
+            Ok(item_count(&entity, &id) >= qty.unwrap_or(1))
+        });
+
+        methods.add_method("equip", |_, entity, (slot, id): (String, String)| {
+            let entity = entity.try_unwrap()?;
+            equip(&entity, &slot, &id)
+        });
+
+        methods.add_method("durability", |_, entity, slot: String| {
+            let entity = entity.try_unwrap()?;
+            let slot = parse_slot(&slot)?;
+            let entity = entity.borrow();
+            let durability = entity
+                .actor
+                .inventory()
+                .equipped(slot)
+                .and_then(|item| item.durability());
+            Ok(durability.map(|(cur, _)| cur))
+        });
+
+        methods.add_method(
+            "repair_item",
+            |_, entity, (slot, amount): (String, Option<u32>)| {
+                let entity = entity.try_unwrap()?;
+                let slot = parse_slot(&slot)?;
+                let repaired = entity.borrow_mut().actor.repair_equipped(slot, amount);
+                Ok(repaired)
+            },
+        );
+
         methods.add_method("size_str", |_, entity, ()| {
             let entity = entity.try_unwrap()?;
             let entity = entity.borrow();
@@ -1653,6 +1996,244 @@ fn move_towards_dest(parent: Rc<RefCell<EntityState>>, dest: Destination) -> Res
     ))
 }
 
+fn summon(
+    parent: &Rc<RefCell<EntityState>>,
+    id: &str,
+    x: i32,
+    y: i32,
+    duration: Option<u32>,
+) -> Result<ScriptEntity> {
+    let actor = match Module::actor(id) {
+        None => {
+            warn!("Unable to summon actor '{}': not found", id);
+            return Ok(ScriptEntity::invalid());
+        }
+        Some(actor) => actor,
+    };
+
+    let area_state = GameState::get_area_state(&parent.borrow().location.area_id).unwrap();
+    if !area_state.borrow().is_passable_size(&actor.race.size, x, y) {
+        warn!("Unable to summon actor '{}' at {},{}: not passable", id, x, y);
+        return Ok(ScriptEntity::invalid());
+    }
+
+    let location = Location::new(x, y, &area_state.borrow().area.area);
+    let index = match area_state
+        .borrow_mut()
+        .add_actor(actor, location, None, false, None)
+    {
+        Ok(index) => index,
+        Err(e) => {
+            warn!("Error summoning actor in area: {}", e);
+            return Ok(ScriptEntity::invalid());
+        }
+    };
+
+    let mgr = GameState::turn_manager();
+    let summon = mgr.borrow().entity(index);
+    let faction = parent.borrow().actor.faction();
+    summon.borrow_mut().actor.set_faction(faction);
+
+    if parent.borrow().is_party_member() {
+        summon
+            .borrow_mut()
+            .set_custom_flag("__is_summoned_party_member", "true");
+        GameState::add_party_member(Rc::clone(&summon), false);
+    }
+
+    let duration = match duration {
+        None => ExtInt::Infinity,
+        Some(rounds) => ExtInt::Int(rounds * ROUND_TIME_MILLIS),
+    };
+    let name = summon.borrow().actor.actor.name.to_string();
+    let mut effect = Effect::new(&name, "summon", duration, BonusList::default(), None);
+    effect.set_owning_entity(parent.borrow().index());
+    effect.summons.push(index);
+    mgr.borrow_mut()
+        .add_effect(effect, parent, Vec::new(), Vec::new());
+
+    mgr.borrow_mut()
+        .check_ai_activation(&summon, &mut area_state.borrow_mut());
+    mgr.borrow_mut()
+        .check_ai_activation_for_party(&mut area_state.borrow_mut());
+
+    Ok(ScriptEntity::new(index))
+}
+
+fn get_item(id: &str) -> Result<Rc<Item>> {
+    match Module::item(id) {
+        None => Err(rlua::Error::FromLuaConversionError {
+            from: "String",
+            to: "Item",
+            message: Some(format!("Item '{id}' does not exist")),
+        }),
+        Some(item) => Ok(item),
+    }
+}
+
+fn add_item(entity: &Rc<RefCell<EntityState>>, id: &str, qty: u32) -> Result<()> {
+    let item = get_item(id)?;
+    if !entity.borrow().is_party_member() {
+        return Err(rlua::Error::ToLuaConversionError {
+            from: "ScriptEntity",
+            to: "Inventory",
+            message: Some(format!(
+                "Unable to add item '{}' to '{}', which is not a party member",
+                id,
+                entity.borrow().unique_id()
+            )),
+        });
+    }
+
+    let stash = GameState::party_stash();
+    stash.borrow_mut().add_item(qty, ItemState::new(item, None));
+    Ok(())
+}
+
+fn item_count(entity: &Rc<RefCell<EntityState>>, id: &str) -> u32 {
+    let entity = entity.borrow();
+    let equipped = entity
+        .actor
+        .inventory()
+        .equipped_iter()
+        .filter(|item| item.item.id == id)
+        .count() as u32;
+
+    if !entity.is_party_member() {
+        return equipped;
+    }
+
+    let stash = GameState::party_stash();
+    let stash = stash.borrow();
+    let stashed: u32 = stash
+        .items()
+        .iter()
+        .filter(|(_, item)| item.item.id == id)
+        .map(|(qty, _)| *qty)
+        .sum();
+    equipped + stashed
+}
+
+fn remove_item(
+    entity: &Rc<RefCell<EntityState>>,
+    id: &str,
+    qty: u32,
+    allow_quest: bool,
+) -> Result<u32> {
+    let item = get_item(id)?;
+    if item.quest && !allow_quest {
+        return Err(rlua::Error::ToLuaConversionError {
+            from: "ScriptEntity",
+            to: "Inventory",
+            message: Some(format!(
+                "Item '{id}' is a quest item.  Pass allow_quest to remove it"
+            )),
+        });
+    }
+
+    let mut removed = 0;
+    if entity.borrow().is_party_member() {
+        let stash = GameState::party_stash();
+        while removed < qty {
+            let index = stash
+                .borrow()
+                .items()
+                .iter()
+                .position(|(_, item)| item.item.id == id);
+            let index = match index {
+                None => break,
+                Some(index) => index,
+            };
+
+            if stash.borrow_mut().remove_item(index).is_none() {
+                break;
+            }
+            removed += 1;
+        }
+    }
+
+    for slot in Slot::iter() {
+        if removed == qty {
+            break;
+        }
+
+        let equipped = match entity.borrow().actor.inventory().equipped(*slot) {
+            None => false,
+            Some(item) => item.item.id == id,
+        };
+
+        if equipped && entity.borrow_mut().actor.unequip(*slot).is_some() {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+fn parse_slot(slot: &str) -> Result<Slot> {
+    match Slot::from_str(slot) {
+        Err(_) => Err(rlua::Error::FromLuaConversionError {
+            from: "String",
+            to: "Slot",
+            message: Some(format!("Invalid slot '{slot}'")),
+        }),
+        Ok(slot) => Ok(slot),
+    }
+}
+
+fn equip(entity: &Rc<RefCell<EntityState>>, slot: &str, id: &str) -> Result<bool> {
+    let slot = parse_slot(slot)?;
+
+    let is_party_member = entity.borrow().is_party_member();
+    let stash = GameState::party_stash();
+
+    let stash_index = if is_party_member {
+        stash
+            .borrow()
+            .items()
+            .iter()
+            .position(|(_, item)| item.item.id == id)
+    } else {
+        None
+    };
+
+    let item_state = match stash_index {
+        None => ItemState::new(get_item(id)?, None),
+        Some(index) => stash.borrow().items()[index].1.clone(),
+    };
+
+    // check the item fits the slot before taking it out of the stash, so
+    // nothing is changed if it cannot be equipped there
+    let fits_slot = match &item_state.item.equippable {
+        None => false,
+        Some(equippable) => equippable.slot == slot || equippable.alternate_slot == Some(slot),
+    };
+    if !fits_slot || !entity.borrow().actor.can_equip(&item_state) {
+        return Ok(false);
+    }
+
+    let item_state = match stash_index {
+        None => item_state,
+        Some(index) => match stash.borrow_mut().remove_item(index) {
+            None => return Ok(false),
+            Some(item_state) => item_state,
+        },
+    };
+
+    let to_add = entity.borrow_mut().actor.equip(item_state, Some(slot));
+    if is_party_member {
+        for item in to_add {
+            stash.borrow_mut().add_item(1, item);
+        }
+    }
+
+    let equipped = match entity.borrow().actor.inventory().equipped(slot) {
+        None => false,
+        Some(item) => item.item.id == id,
+    };
+    Ok(equipped)
+}
+
 pub fn unwrap_point(point: HashMap<String, i32>) -> Result<(i32, i32)> {
     let x = match point.get("x") {
         None => {
@@ -1743,6 +2324,12 @@ fn create_stats_table<'a>(
     }
     stats.set("resistance", resistance)?;
 
+    let immunity = lua.create_table()?;
+    for kind in DamageKind::iter() {
+        immunity.set(kind.to_str(), src.resistance.is_immune(*kind))?;
+    }
+    stats.set("immunity", immunity)?;
+
     stats.set("level", parent.actor.actor.total_level)?;
     stats.set("caster_level", src.caster_level)?;
     stats.set("bonus_reach", src.bonus_reach)?;
@@ -25,7 +25,7 @@ use crate::script::{Result, ScriptActiveSurface, ScriptEntity};
 use crate::{
     is_threat, is_within, is_within_attack_dist, is_within_touch_dist, EntityState, GameState,
 };
-use sulis_core::util::{gen_rand, invalid_data_error};
+use sulis_core::util::{combat_roll, invalid_data_error};
 use sulis_module::Faction;
 
 /// Represents a set of ScriptEntities, which can be created from a variety of
@@ -248,7 +248,7 @@ impl UserData for ScriptEntitySet {
                 .affected_points
                 .iter()
                 .filter_map(|p| {
-                    let roll = gen_rand(0.0, 1.0);
+                    let roll = combat_roll(0.0, 1.0);
                     if roll > frac {
                         None
                     } else {
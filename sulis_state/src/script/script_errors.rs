@@ -0,0 +1,148 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! A log of the most recent script errors, for display in the developer
+//! overlay.  Errors are recorded as scripts fail, along with a description
+//! of the entity, ability, or item that triggered the script, if known.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// The maximum number of errors that are kept
+pub const MAX_ERRORS: usize = 20;
+
+const TRACEBACK_HEADER: &str = "stack traceback:";
+
+thread_local! {
+    static ERRORS: RefCell<VecDeque<ScriptError>> = RefCell::new(VecDeque::new());
+    static TOTAL: Cell<usize> = Cell::new(0);
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptError {
+    pub script: String,
+    pub func: String,
+    pub context: Option<String>,
+    pub message: String,
+    pub traceback: String,
+
+    /// The lines of the script near the error, if the line is known
+    pub source: String,
+}
+
+impl ScriptError {
+    pub(in crate::script) fn new(
+        script: &str,
+        func: &str,
+        error: &rlua::Error,
+        source: String,
+    ) -> ScriptError {
+        let (message, traceback) = match error {
+            rlua::Error::CallbackError { traceback, cause } => {
+                (cause.to_string(), traceback.to_string())
+            }
+            error => {
+                let message = error.to_string();
+                match message.find(TRACEBACK_HEADER) {
+                    None => (message, String::new()),
+                    Some(index) => (
+                        message[..index].trim_end().to_string(),
+                        message[index..].to_string(),
+                    ),
+                }
+            }
+        };
+
+        ScriptError {
+            script: script.to_string(),
+            func: func.to_string(),
+            context: None,
+            message,
+            traceback,
+            source,
+        }
+    }
+
+    /// The full details of this error as plain text, for copying
+    pub fn details(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Script: {}.lua, function '{}'", self.script, self.func);
+        if let Some(context) = &self.context {
+            let _ = writeln!(out, "Triggered by: {context}");
+        }
+        let _ = writeln!(out, "{}", self.message);
+        if !self.traceback.is_empty() {
+            let _ = writeln!(out, "{}", self.traceback.trim_end());
+        }
+        if !self.source.is_empty() {
+            let _ = writeln!(out, "{}", self.source.trim_end());
+        }
+        out
+    }
+}
+
+pub(in crate::script) fn record(error: ScriptError) {
+    ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        if errors.len() == MAX_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(error);
+    });
+    TOTAL.with(|total| total.set(total.get() + 1));
+}
+
+/// The recorded errors, most recent first
+pub fn errors() -> Vec<ScriptError> {
+    ERRORS.with(|errors| errors.borrow().iter().rev().cloned().collect())
+}
+
+/// The total number of errors recorded, including those no longer kept.
+/// This may be compared with a previous value to check for new errors
+pub fn total() -> usize {
+    TOTAL.with(|total| total.get())
+}
+
+pub fn clear() {
+    ERRORS.with(|errors| errors.borrow_mut().clear());
+}
+
+/// Calls `f`, attributing any script errors during the call that have no
+/// more specific context to the description built by `context`.  The
+/// description is only built if there were errors
+pub(in crate::script) fn with_context<T>(
+    context: impl FnOnce() -> String,
+    f: impl FnOnce() -> T,
+) -> T {
+    let start = total();
+    let result = f();
+    let count = total() - start;
+    if count == 0 {
+        return result;
+    }
+
+    let context = context();
+    ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        for error in errors.iter_mut().rev().take(count) {
+            if error.context.is_none() {
+                error.context = Some(context.clone());
+            }
+        }
+    });
+    result
+}
@@ -0,0 +1,128 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use rlua::{UserData, UserDataMethods};
+
+use crate::game_event::GameEvent;
+use crate::script::{ScriptEntity, ScriptHitKind};
+
+/// A game event passed to a script observer, registered with
+/// `game:add_event_observer()`.  Accessors which do not apply to the
+/// kind of event return nil.
+///
+/// # `kind() -> String`
+/// The kind of this event - one of `DamageDealt`, `EntityDied`, `ItemAcquired`,
+/// `AreaEntered`, `QuestCompleted`, or `CoinsChanged`
+///
+/// # `source() -> ScriptEntity`
+/// For `DamageDealt`, the entity that dealt the damage
+///
+/// # `target() -> ScriptEntity`
+/// For `DamageDealt`, the entity that was damaged
+///
+/// # `hit() -> ScriptHitKind`
+/// For `DamageDealt`, the hit kind and damage that was dealt
+///
+/// # `entity() -> ScriptEntity`
+/// For `EntityDied`, the entity that died
+///
+/// # `killer() -> ScriptEntity`
+/// For `EntityDied`, the entity that dealt the final blow
+///
+/// # `item() -> String`
+/// For `ItemAcquired`, the ID of the item that was added to the party stash
+///
+/// # `quantity() -> Int`
+/// For `ItemAcquired`, the number of items that were added
+///
+/// # `area() -> String`
+/// For `AreaEntered`, the ID of the area the party entered
+///
+/// # `quest() -> String`
+/// For `QuestCompleted`, the ID of the quest that was completed
+///
+/// # `coins() -> Int`
+/// For `CoinsChanged`, the total number of coins now held by the party
+#[derive(Clone)]
+pub struct ScriptGameEvent {
+    event: GameEvent,
+}
+
+impl ScriptGameEvent {
+    pub fn new(event: GameEvent) -> ScriptGameEvent {
+        ScriptGameEvent { event }
+    }
+}
+
+impl UserData for ScriptGameEvent {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("kind", |_, data, ()| Ok(data.event.kind().to_str()));
+
+        methods.add_method("source", |_, data, ()| match data.event {
+            GameEvent::DamageDealt { source, .. } => Ok(Some(ScriptEntity::new(source))),
+            _ => Ok(None),
+        });
+
+        methods.add_method("target", |_, data, ()| match data.event {
+            GameEvent::DamageDealt { target, .. } => Ok(Some(ScriptEntity::new(target))),
+            _ => Ok(None),
+        });
+
+        methods.add_method("hit", |_, data, ()| match data.event {
+            GameEvent::DamageDealt {
+                hit_kind,
+                ref damage,
+                ..
+            } => Ok(Some(ScriptHitKind::new(hit_kind, damage.clone()))),
+            _ => Ok(None),
+        });
+
+        methods.add_method("entity", |_, data, ()| match data.event {
+            GameEvent::EntityDied { entity, .. } => Ok(Some(ScriptEntity::new(entity))),
+            _ => Ok(None),
+        });
+
+        methods.add_method("killer", |_, data, ()| match data.event {
+            GameEvent::EntityDied { killer, .. } => Ok(Some(ScriptEntity::new(killer))),
+            _ => Ok(None),
+        });
+
+        methods.add_method("item", |_, data, ()| match data.event {
+            GameEvent::ItemAcquired { ref item, .. } => Ok(Some(item.to_string())),
+            _ => Ok(None),
+        });
+
+        methods.add_method("quantity", |_, data, ()| match data.event {
+            GameEvent::ItemAcquired { quantity, .. } => Ok(Some(quantity)),
+            _ => Ok(None),
+        });
+
+        methods.add_method("area", |_, data, ()| match data.event {
+            GameEvent::AreaEntered { ref area } => Ok(Some(area.to_string())),
+            _ => Ok(None),
+        });
+
+        methods.add_method("quest", |_, data, ()| match data.event {
+            GameEvent::QuestCompleted { ref quest } => Ok(Some(quest.to_string())),
+            _ => Ok(None),
+        });
+
+        methods.add_method("coins", |_, data, ()| match data.event {
+            GameEvent::CoinsChanged { total } => Ok(Some(total)),
+            _ => Ok(None),
+        });
+    }
+}
@@ -20,10 +20,11 @@ use std::rc::Rc;
 use rlua::{self, UserData, UserDataMethods};
 
 use crate::script::*;
-use crate::{animation::Anim, AreaState, EntityState, GameState, Location};
-use sulis_core::{config::Config};
+use crate::game_event::{GameEventKind, ScriptObserver};
+use crate::{animation::Anim, AreaState, EntityState, GameState, Location, ScheduledScript};
+use sulis_core::{config::Config, util::{self, ExtInt}};
 use sulis_module::on_trigger::{self, QuestEntryState};
-use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
+use sulis_module::{Faction, ItemState, Module, OnTrigger, Time, ROUND_TIME_MILLIS};
 
 /// The ScriptInterface, accessible in all Lua scripts as the global `game`.
 /// The following methods are available on this object (documentation WIP):
@@ -132,6 +133,19 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// is not needed when scripts cause movement, as it is called automatically
 /// in those cases.  The entity should be the one whose state has changed.
 ///
+/// # `set_combat_start_enabled(enabled: Bool)`
+/// Sets whether hostile sightings may start combat.  When disabled, entities
+/// will not activate their AI on seeing a hostile, allowing scripted scenes
+/// to play out without combat interrupting them.  Combat may still be started
+/// with `start_combat`.  This setting is saved with the game.
+///
+/// # `start_combat(entity: ScriptEntity, surprise: Bool (Optional))`
+/// Immediately starts combat between the specified `entity` and all hostile
+/// AI entities in its area, regardless of distance, visibility, or whether
+/// combat start is enabled.  If `surprise` is true, entities hostile to
+/// `entity` lose their turns in the first round.  Does nothing if combat is
+/// already active.
+///
 /// # `fade_out_in()`
 /// Causes the main view to fade out, then back in again.  This duration of the
 /// fades is defined in the theme for the `WindowFade` widget.
@@ -155,6 +169,12 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// on the `accept` and `cancel` buttons.  If the user cancels, no action is taken.  If the
 /// user accepts, the specified `func` is called from the script with `id`.
 ///
+/// # `show_choice(message: String, choices: Table, callback: CallbackData)`
+/// Shows a dialog with the specified `message` and a button for each of the
+/// `choices`, which is a list of Strings.  When the user selects a choice, the
+/// `on_menu_select` function of `callback` is called with a `ScriptMenuSelection`
+/// whose value is the selected String.  If the user cancels, no action is taken.
+///
 /// # `warn(message: String)`
 /// Logs the specified string to the game's output at warn level.
 ///
@@ -195,6 +215,13 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// Creates a new script callback.  This callback will utilize the specified script
 /// file for all methods.  See `ScriptCallback` for more.
 ///
+/// # `create_particle_generator(image: String, x: Float, y: Float, duration: Float (Optional))
+/// -> ScriptParticleGenerator`
+/// Creates a particle generator at the fixed point `x`, `y` in the current area, for
+/// effects such as torches and weather that are not attached to an entity.  The generator
+/// is not blocking, and is removed when the party leaves the area.  If `duration` is not
+/// specified, it lasts until then.  See `ScriptParticleGenerator`.
+///
 /// # `set_quest_state(quest: String, state: String)`
 /// Sets the specified `quest` to the `state`.  `state` must be one of `Hidden`, `Visible`,
 /// `Active`, or `Complete`.  `quest` must be the ID of a valid quest definition.
@@ -211,6 +238,48 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// # `get_quest_entry_state(quest: String, entry: String)`
 /// Returns the current `state` of the specified `entry` in the given `quest`.
 ///
+/// # `add_quest_entry(quest: String, entry: String)`
+/// Sets the specified `entry` within `quest` to `Active`.  The quest itself is
+/// also made `Active` unless it has already been completed.
+///
+/// # `advance_quest(quest: String, entry: String)`
+/// Completes every `Visible` or `Active` entry in the specified `quest`, and then
+/// adds `entry` as the new active entry.  This is the usual way to move a quest
+/// on to its next step from a trigger script.
+///
+/// # `complete_quest(quest: String)`
+/// Sets the specified `quest` and all of its open entries to `Complete`.
+///
+/// # `get_faction(id: String) -> Int`
+/// Returns the party's current standing with the faction with the specified `id`.
+/// Standing ranges from -100 to 100.
+///
+/// # `adjust_faction(id: String, amount: Int)`
+/// Adds `amount` (which may be negative) to the party's standing with the faction
+/// `id`.  All loaded members of the faction update their attitude towards the
+/// party based on the new standing, which may cause combat to start.
+///
+/// # `set_flag(flag: String, value: String (Optional))`
+/// Sets a campaign wide `flag`, which persists as part of the save game.  Unlike
+/// flags set on an entity, these are not tied to any area, so they are the place to
+/// record story decisions.  If the value is not specified, the flag is set to "true".
+///
+/// # `get_flag(flag: String) -> String`
+/// Returns the value of the specified campaign `flag`, or nil if it is not set.
+///
+/// # `get_flag_int(flag: String) -> Int`
+/// Returns the integer value of the specified campaign `flag`, or 0 if it is not set.
+///
+/// # `add_flag_int(flag: String, amount: Int) -> Int`
+/// Adds `amount` to the integer value of the specified campaign `flag`, treating an
+/// unset flag as 0.  Returns the new value.
+///
+/// # `has_flag(flag: String) -> Bool`
+/// Returns true if the specified campaign `flag` is set to any value, false otherwise.
+///
+/// # `clear_flag(flag: String)`
+/// Clears the specified campaign `flag`, as if it had never been set.
+///
 /// # `set_world_map_location_visible(location: String, visible: Bool)`
 /// Sets the specified `location` in the world map to the specified `visible`.  The
 /// location must be defined in the world_map section of the campaign definition file.
@@ -290,6 +359,10 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// asynchronously on the next frame, so the remaineder of this script script will execute
 /// immediately.
 ///
+/// # `create_cutscene() -> ScriptCutscene`
+/// Creates a new, empty `ScriptCutscene`.  Steps such as movement, text, and camera
+/// pans can then be added to it, before it is started with `play()`.
+///
 /// # `exit_to_menu()`
 /// Causes the game to exit to the main menu.
 ///
@@ -298,6 +371,45 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// This done using a smooth scroll effect.  The scroll begins on the next frame, so the
 /// remainder of the current script will continue to execute immediately.
 ///
+/// # `scroll_to(x: Float, y: Float, time: Float (Optional))`
+/// Causes the view of the current area to smoothly scroll, easing in and out, to center
+/// on the specified `x`, `y` coordinates.  If `time` is specified, the scroll takes that
+/// many seconds; otherwise the duration is based on the distance and the user's scroll
+/// speed.  Any camera follow is suspended until the scroll completes.
+///
+/// # `follow_entity(entity: ScriptEntity (Optional))`
+/// Causes the view of the current area to keep the specified `entity` centered as it
+/// moves.  If `entity` is not specified, any current follow is stopped.  The follow is also
+/// stopped if the player manually scrolls the view, or the entity dies or leaves the area.
+///
+/// # `screen_shake()`
+/// Briefly shakes the view of the current area, if the user has screen shake enabled.
+///
+/// # `add_event_observer(id: String, kind: String, script: String, func: String)`
+/// Registers an observer which calls `func` in the specified `script` each time a game
+/// event of the specified `kind` occurs.  Valid kinds are `DamageDealt`, `EntityDied`,
+/// `ItemAcquired`, `AreaEntered`, `QuestCompleted`, and `CoinsChanged`.  The function is
+/// passed a `ScriptGameEvent` describing the event.  Events are delivered on the frame
+/// after they occur.  Observers are saved with the campaign, and adding an observer with
+/// the same `id` as an existing one replaces it.
+///
+/// # `remove_event_observer(id: String)`
+/// Removes the observer with the specified `id`, if it exists.
+///
+/// # `schedule(func: String, millis: Int)`
+/// Calls `func` in the current script once the specified number of milliseconds of
+/// game time have elapsed, passing it the player.  Game time only advances by whole
+/// rounds during combat, with due functions called at the start of each round.
+/// Scheduled functions are saved with the campaign.
+/// ## Examples
+/// ```lua
+///   game:schedule("on_bomb_explode", 3000)
+/// ```
+///
+/// # `schedule_rounds(func: String, rounds: Int)`
+/// Calls `func` in the current script at the start of the round the specified number of
+/// `rounds` from now.  Scheduled functions are saved with the campaign.
+///
 /// # `num_effects_with_tag(tag: String) -> Int`
 /// Returns the number of currently active effects, in any area, with the specified effect
 /// tag.  This can be used in scripts to enforce a global limit on a specific effect type.
@@ -343,6 +455,12 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// Adds the specified amount of XP to the party.  Each current party member is given
 /// this amount of XP.
 ///
+/// # `award_xp(amount: Int, source: ScriptEntity (Optional))`
+/// Awards the specified amount of XP to the party, shared between party members
+/// according to the rules `xp_distribution`.  The `source`, or the player if it is
+/// not specified, is used for proximity based sharing.  Each party member that receives
+/// XP shows floating feedback text.  This is useful for quest and other scripted rewards.
+///
 /// # `transition_party_to(x: Int, y: Int, area: String (Optional))`
 /// Moves the party to the specified coordinates within the specified area.  If an area is not
 /// specified, the transition occurs within the current area.  If the area
@@ -350,6 +468,13 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// the player is forced to load to continue.  The player is moved to the exact coordinates,
 /// whereas other party members are moved to nearby coordinates.
 ///
+/// # `regenerate_area(seed: String (Optional)) -> String`
+/// Generates the current area again from the specified `seed`, or from a random seed if
+/// none is specified, and moves the party into the new area at their current positions.
+/// All other creatures and props in the old area are discarded.  This is intended for
+/// previewing generator parameters from the console.  Returns the seed of the new area.
+/// Throws an error if the area is not procedurally generated or the seed is invalid.
+///
 /// # `start_bench(tag: String (Optional)) -> Handle`
 /// Starts a benchmark run.  Returns a `handle` that can be used to finish the run.
 /// The benchmark may optionally be labeled with a `tag`.
@@ -486,6 +611,27 @@ impl UserData for ScriptInterface {
             Ok(())
         });
 
+        methods.add_method("set_combat_start_enabled", |_, _, enabled: bool| {
+            let mgr = GameState::turn_manager();
+            mgr.borrow_mut().combat_start_suppressed = !enabled;
+            Ok(())
+        });
+
+        methods.add_method(
+            "start_combat",
+            |_, _, (entity, surprise): (ScriptEntity, Option<bool>)| {
+                let entity = entity.try_unwrap()?;
+                let area = GameState::get_area_state(&entity.borrow().location.area_id).unwrap();
+                let mgr = GameState::turn_manager();
+                mgr.borrow_mut().force_combat(
+                    &entity,
+                    &mut area.borrow_mut(),
+                    surprise.unwrap_or(false),
+                );
+                Ok(())
+            },
+        );
+
         methods.add_method("fade_out_in", |_, _, ()| {
             let pc = GameState::player();
             let cb = OnTrigger::FadeOutIn;
@@ -529,6 +675,44 @@ impl UserData for ScriptInterface {
             },
         );
 
+        methods.add_method(
+            "show_choice",
+            |_, _, (msg, choices, cb): (String, Vec<String>, CallbackData)| {
+                let func = match cb.get_func(FuncKind::OnMenuSelect) {
+                    None => {
+                        return Err(rlua::Error::FromLuaConversionError {
+                            from: "CallbackData",
+                            to: "Choice",
+                            message: Some(
+                                "OnMenuSelect must be specified for callback".to_string(),
+                            ),
+                        });
+                    }
+                    Some(func) => func,
+                };
+
+                let choices = choices
+                    .into_iter()
+                    .map(|choice| on_trigger::ScriptMenuChoice {
+                        display: choice.clone(),
+                        value: choice,
+                    })
+                    .collect();
+
+                let data = on_trigger::MenuData {
+                    title: msg,
+                    choices,
+                    cb_func: func,
+                    cb_kind: cb.kind(),
+                    cb_parent: cb.parent(),
+                };
+
+                let pc = GameState::player();
+                GameState::add_ui_callback(vec![OnTrigger::ShowChoice(data)], &pc, &pc);
+                Ok(())
+            },
+        );
+
         methods.add_method("warn", |_, _, val: String| {
             warn!("[LUA WARN]: {}", val);
             Ok(())
@@ -584,6 +768,18 @@ impl UserData for ScriptInterface {
             },
         );
 
+        methods.add_method(
+            "create_particle_generator",
+            |_, _, (image, x, y, duration): (String, f32, f32, Option<f32>)| {
+                let duration = match duration {
+                    None => ExtInt::Infinity,
+                    Some(amount) => ExtInt::Int((amount * 1000.0) as u32),
+                };
+                let parent = GameState::player().borrow().index();
+                Ok(ScriptParticleGenerator::new_at(parent, image, duration, x, y))
+            },
+        );
+
         methods.add_method(
             "create_callback",
             |_, _, (parent, script): (ScriptEntity, String)| {
@@ -654,6 +850,78 @@ impl UserData for ScriptInterface {
             },
         );
 
+        methods.add_method(
+            "add_quest_entry",
+            |_, _, (quest, entry): (String, String)| {
+                warn_if_invalid_quest_entry(&quest, &entry);
+                GameState::add_quest_entry(quest, entry);
+                Ok(())
+            },
+        );
+
+        methods.add_method(
+            "advance_quest",
+            |_, _, (quest, entry): (String, String)| {
+                warn_if_invalid_quest_entry(&quest, &entry);
+                GameState::advance_quest(quest, entry);
+                Ok(())
+            },
+        );
+
+        methods.add_method("complete_quest", |_, _, quest: String| {
+            if Module::quest(&quest).is_none() {
+                warn!("Complete quest for invalid quest '{}'", quest);
+            }
+            GameState::complete_quest(quest);
+            Ok(())
+        });
+
+        methods.add_method("set_flag", |_, _, (flag, val): (String, Option<String>)| {
+            let val = match &val {
+                None => "true",
+                Some(val) => val,
+            };
+            GameState::set_campaign_flag(&flag, val);
+            Ok(())
+        });
+
+        methods.add_method("get_flag", |_, _, flag: String| {
+            Ok(GameState::get_campaign_flag(&flag))
+        });
+
+        methods.add_method("get_flag_int", |_, _, flag: String| {
+            Ok(GameState::get_campaign_flag_int(&flag))
+        });
+
+        methods.add_method("add_flag_int", |_, _, (flag, amount): (String, i32)| {
+            Ok(GameState::add_campaign_flag_int(&flag, amount))
+        });
+
+        methods.add_method("has_flag", |_, _, flag: String| {
+            Ok(GameState::has_campaign_flag(&flag))
+        });
+
+        methods.add_method("clear_flag", |_, _, flag: String| {
+            GameState::clear_campaign_flag(&flag);
+            Ok(())
+        });
+
+        methods.add_method("get_faction", |_, _, id: String| {
+            if Module::faction(&id).is_none() {
+                warn!("Requested standing for invalid faction '{}'", id);
+            }
+            Ok(GameState::faction_standing(&id))
+        });
+
+        methods.add_method("adjust_faction", |_, _, (id, amount): (String, i32)| {
+            if Module::faction(&id).is_none() {
+                warn!("Adjust standing for invalid faction '{}'", id);
+                return Ok(());
+            }
+            GameState::adjust_faction(&id, amount);
+            Ok(())
+        });
+
         methods.add_method(
             "set_world_map_location_visible",
             |_, _, (location, vis): (String, bool)| {
@@ -880,6 +1148,8 @@ impl UserData for ScriptInterface {
             Ok(())
         });
 
+        methods.add_method("create_cutscene", |_, _, ()| Ok(ScriptCutscene::new()));
+
         methods.add_method("exit_to_menu", |_, _, ()| {
             let pc = GameState::player();
             let cb = OnTrigger::ExitToMenu;
@@ -894,6 +1164,73 @@ impl UserData for ScriptInterface {
             Ok(())
         });
 
+        methods.add_method(
+            "scroll_to",
+            |_, _, (x, y, time): (f32, f32, Option<f32>)| {
+                let millis = time.map(|secs| (secs.max(0.0) * 1000.0) as u32);
+                let area = GameState::area_state();
+                area.borrow_mut().camera().scroll_to(x, y, millis);
+                Ok(())
+            },
+        );
+
+        methods.add_method("follow_entity", |_, _, entity: Option<ScriptEntity>| {
+            let entity = match entity {
+                None => None,
+                Some(entity) => Some(entity.try_unwrap()?),
+            };
+            let area = GameState::area_state();
+            area.borrow_mut().camera().follow(entity);
+            Ok(())
+        });
+
+        methods.add_method("screen_shake", |_, _, ()| {
+            let area = GameState::area_state();
+            area.borrow_mut().camera().shake();
+            Ok(())
+        });
+
+        methods.add_method(
+            "add_event_observer",
+            |_, _, (id, kind, script, func): (String, String, String, String)| {
+                let kind = match GameEventKind::unwrap_from_str(&kind) {
+                    None => {
+                        return Err(rlua::Error::FromLuaConversionError {
+                            from: "String",
+                            to: "GameEventKind",
+                            message: Some(format!("Invalid event kind '{kind}'")),
+                        });
+                    }
+                    Some(kind) => kind,
+                };
+
+                GameState::add_script_observer(ScriptObserver {
+                    id,
+                    kind,
+                    script,
+                    func,
+                });
+                Ok(())
+            },
+        );
+
+        methods.add_method("remove_event_observer", |_, _, id: String| {
+            GameState::remove_script_observer(&id);
+            Ok(())
+        });
+
+        methods.add_method("schedule", |_, _, (func, millis): (String, u32)| {
+            let mgr = GameState::turn_manager();
+            let due_millis = mgr.borrow().total_elapsed_millis() + millis as usize;
+            schedule(func, due_millis)
+        });
+
+        methods.add_method("schedule_rounds", |_, _, (func, rounds): (String, u32)| {
+            let mgr = GameState::turn_manager();
+            let round = mgr.borrow().current_round() + rounds;
+            schedule(func, round as usize * ROUND_TIME_MILLIS as usize)
+        });
+
         methods.add_method("num_effects_with_tag", |_, _, tag: String| {
             let mgr = GameState::turn_manager();
             let mgr = mgr.borrow();
@@ -1026,6 +1363,18 @@ impl UserData for ScriptInterface {
             Ok(ScriptStashItem { index })
         });
 
+        methods.add_method(
+            "award_xp",
+            |_, _, (amount, source): (u32, Option<ScriptEntity>)| {
+                let source = match source {
+                    None => GameState::player(),
+                    Some(source) => source.try_unwrap()?,
+                };
+                GameState::award_xp(amount, &source);
+                Ok(())
+            },
+        );
+
         methods.add_method("add_party_xp", |_, _, amount: u32| {
             for member in GameState::party().iter() {
                 member.borrow_mut().add_xp(amount);
@@ -1054,6 +1403,16 @@ impl UserData for ScriptInterface {
             },
         );
 
+        methods.add_method("regenerate_area", |_, _, seed: Option<String>| {
+            let seed = match seed {
+                None => None,
+                Some(seed) => util::parse_seed(&seed).map_err(rlua::Error::external)?,
+            };
+
+            let seed = GameState::regenerate_area(seed).map_err(rlua::Error::external)?;
+            Ok(seed.to_string())
+        });
+
         methods.add_method("start_bench", |_, _, tag: Option<String>| {
             let handle = sulis_core::benchmark::start_bench(tag);
 
@@ -1067,6 +1426,17 @@ impl UserData for ScriptInterface {
     }
 }
 
+fn warn_if_invalid_quest_entry(quest: &str, entry: &str) {
+    match Module::quest(quest) {
+        None => warn!("Invalid quest '{}'", quest),
+        Some(ref quest) => {
+            if !quest.entries.contains_key(entry) {
+                warn!("Invalid entry '{}' in '{:?}'", entry, quest);
+            }
+        }
+    }
+}
+
 fn get_area(id: Option<String>) -> Result<Rc<RefCell<AreaState>>> {
     match id {
         None => Ok(GameState::area_state()),
@@ -1096,6 +1466,26 @@ fn entities_with_ids(ids: Vec<String>) -> Vec<ScriptEntity> {
     result
 }
 
+fn schedule(func: String, due_millis: usize) -> Result<()> {
+    let script = match current_script_id() {
+        None => {
+            return Err(rlua::Error::FromLuaConversionError {
+                from: "String",
+                to: "ScheduledScript",
+                message: Some(format!("Unable to schedule '{func}' outside of a script")),
+            });
+        }
+        Some(script) => script,
+    };
+
+    GameState::schedule_script(ScheduledScript {
+        script,
+        func,
+        due_millis,
+    });
+    Ok(())
+}
+
 pub fn entity_with_id(id: String) -> Option<Rc<RefCell<EntityState>>> {
     let mgr = GameState::turn_manager();
     for entity in mgr.borrow().entity_iter() {
@@ -25,7 +25,7 @@ use sulis_module::{ability, Item, ItemState, Module};
 
 /// A kind of Item, represented by its owner (Stash, QuickSlot, or a generic
 /// item with a specified ID)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ScriptItemKind {
     Stash(usize),
     Quick(QuickSlot),
@@ -180,6 +180,11 @@ fn activate_item(_lua: Context, script_item: &ScriptItem, target: ScriptEntity)
     let mgr = GameState::turn_manager();
     if mgr.borrow().is_combat_active() {
         target.borrow_mut().actor.remove_ap(script_item.ap);
+
+        if let ScriptItemKind::Quick(_) = script_item.kind {
+            let parent = ScriptEntity::new(script_item.parent).try_unwrap()?;
+            parent.borrow_mut().actor.add_quick_item_use();
+        }
     }
 
     let area = GameState::area_state();
@@ -123,6 +123,26 @@ use crate::GameState;
 /// # `set_particle_size_dist(width: Dist, height: Dist)`
 /// Sets the size (where 1.0 equals 1 tile) of particles created by this animation.
 ///
+/// # `set_particle_end_color(r: Float, g: Float, b: Float, a: Float (Optional))`
+/// Sets the color that each particle fades to over its lifetime, as a multiple of
+/// the color set with `set_color`.  The alpha component defaults to 0.0, so particles
+/// fade out completely.
+/// ## Examples
+/// ```lua
+///   gen = parent:create_particle_generator("particles/spark", 2.0)
+///   gen:set_gen_rate(gen:param(40.0))
+///   gen:set_particle_duration_dist(gen:uniform_dist(0.5, 1.0))
+///   gen:set_particle_position_dist(gen:dist_param(gen:zero_dist(), gen:uniform_dist(-1.0, 1.0),
+///     gen:fixed_dist(2.0)))
+///   gen:set_particle_end_color(1.0, 0.3, 0.0)
+///   gen:add_particle_image("particles/spark2")
+///   gen:activate()
+/// ```
+///
+/// # `add_particle_image(image: String)`
+/// Adds an additional image.  Each particle is drawn with an image randomly selected
+/// from the generator's image and any added images.
+///
 /// #`set_particle_frame_time_offset_dist(value: Dist)`
 /// Sets a frame offset time for each particle created by this animation.  This is only
 /// useful for particles that are using a `TimerImage`.  When `value` is a random
@@ -156,6 +176,22 @@ impl ScriptParticleGenerator {
         }
     }
 
+    /// Creates a non blocking generator at a fixed position in the current
+    /// area, independent of the location of the parent
+    pub fn new_at(
+        parent: usize,
+        image: String,
+        duration_millis: ExtInt,
+        x: f32,
+        y: f32,
+    ) -> ScriptParticleGenerator {
+        let mut pgen = ScriptParticleGenerator::new(parent, image, duration_millis);
+        pgen.model.position = (Param::fixed(x), Param::fixed(y));
+        pgen.model.is_blocking = false;
+        pgen.model.area = Some(GameState::area_state().borrow().area.area.id.to_string());
+        pgen
+    }
+
     pub fn new_anim(
         parent: usize,
         image: String,
@@ -268,6 +304,17 @@ impl UserData for ScriptParticleGenerator {
                 Ok(())
             },
         );
+        methods.add_method_mut(
+            "set_particle_end_color",
+            |_, gen, (r, g, b, a): (f32, f32, f32, Option<f32>)| {
+                gen.model.particle_end_color = Some([r, g, b, a.unwrap_or(0.0)]);
+                Ok(())
+            },
+        );
+        methods.add_method_mut("add_particle_image", |_, gen, image: String| {
+            gen.model.extra_images.push(image);
+            Ok(())
+        });
         methods.add_method_mut(
             "set_particle_frame_time_offset_dist",
             |_, gen, value: Dist| {
@@ -329,13 +376,17 @@ pub fn param<T>(
 }
 
 fn activate(_lua: Context, gen: &ScriptParticleGenerator, _args: ()) -> Result<()> {
-    let pgen = create_pgen(gen, gen.model.clone())?;
+    let pgen = create_anim(gen)?;
 
     GameState::add_animation(pgen);
 
     Ok(())
 }
 
+pub fn create_anim(gen: &ScriptParticleGenerator) -> Result<Anim> {
+    create_pgen(gen, gen.model.clone())
+}
+
 pub fn create_surface_pgen(gen: &ScriptParticleGenerator, x: i32, y: i32) -> Result<Anim> {
     let mut model = gen.model.clone();
     let x_param = model.position.0.offset(x as f32);
@@ -364,7 +415,15 @@ pub fn create_pgen(gen: &ScriptParticleGenerator, model: GeneratorModel) -> Resu
         }
     };
 
-    let mut pgen = animation::particle_generator::new(&parent, image, model);
+    let mut extra_images = Vec::new();
+    for id in model.extra_images.iter() {
+        match ResourceSet::image(id) {
+            None => warn!("Unable to locate image '{}' for particle generator", id),
+            Some(image) => extra_images.push(image),
+        }
+    }
+
+    let mut pgen = animation::particle_generator::new(&parent, image, extra_images, model);
 
     if let Some(ref cb) = gen.completion_callback {
         pgen.add_completion_callback(Box::new(cb.clone()));
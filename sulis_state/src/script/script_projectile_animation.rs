@@ -0,0 +1,186 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use rlua::{Context, UserData, UserDataMethods};
+
+use crate::animation::projectile_animation::{self, ProjectileKind};
+use crate::animation::Anim;
+use crate::script::{CallbackData, Result, ScriptCallback};
+use crate::GameState;
+use sulis_core::resource::ResourceSet;
+
+const DEFAULT_SPEED: f32 = 10.0;
+
+/// An animation of a projectile or beam traveling from the parent entity
+/// through a series of waypoints.  Normally created via
+/// `ScriptEntity:create_projectile_anim`.  By default, the projectile blocks
+/// the parent until it lands, so any hits resolved in an impact callback are
+/// applied before the parent's turn continues.
+///
+/// # `activate()`
+/// Activates this animation.
+///
+/// # `add_waypoint(x: Float, y: Float, speed: Float (Optional))`
+/// Adds a point for the projectile to travel to, after any previous waypoints.  `speed`
+/// is the speed in tiles per second while traveling to this waypoint, 10 by default.
+///
+/// # `set_arc(height: Float)`
+/// Lobs the projectile in an arc reaching the specified `height`, in tiles, at the
+/// midpoint of its flight.
+///
+/// # `set_beam(hold: Float (Optional))`
+/// Draws the image as a beam stretched from the parent to the current position,
+/// instead of a traveling sprite.  The beam remains visible for `hold` seconds
+/// after reaching the final waypoint, 0.5 by default.
+///
+/// # `set_blocking(blocking: Bool)`
+/// Sets whether the parent is blocked from further actions until this animation
+/// completes.  Defaults to true.
+///
+/// # `add_impact_callback(callback: CallbackData, waypoint: Int (Optional))`
+/// Fires `on_anim_update` on the `callback` when the projectile reaches the waypoint
+/// with the specified index, starting from 1.  Defaults to the final waypoint.
+///
+/// # `set_completion_callback(callback: CallbackData)`
+/// Sets the specified `callback` to be called when this animation completes.
+/// ## Examples
+/// ```lua
+///   anim = parent:create_projectile_anim("particles/fire_bolt")
+///   anim:add_waypoint(target:center_x(), target:center_y(), 15.0)
+///   cb = ability:create_callback(parent)
+///   cb:add_target(target)
+///   cb:set_on_anim_update_fn("on_impact")
+///   anim:add_impact_callback(cb)
+///   anim:activate()
+/// ```
+#[derive(Clone)]
+pub struct ScriptProjectileAnimation {
+    parent: usize,
+    image: String,
+    kind: ProjectileKind,
+    waypoints: Vec<(f32, f32, f32)>,
+    blocking: bool,
+    impact_callbacks: Vec<(Option<usize>, CallbackData)>,
+    completion_callback: Option<CallbackData>,
+}
+
+impl ScriptProjectileAnimation {
+    pub fn new(parent: usize, image: String) -> ScriptProjectileAnimation {
+        ScriptProjectileAnimation {
+            parent,
+            image,
+            kind: ProjectileKind::Straight,
+            waypoints: Vec::new(),
+            blocking: true,
+            impact_callbacks: Vec::new(),
+            completion_callback: None,
+        }
+    }
+}
+
+impl UserData for ScriptProjectileAnimation {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("activate", activate);
+        methods.add_method_mut(
+            "add_waypoint",
+            |_, anim, (x, y, speed): (f32, f32, Option<f32>)| {
+                anim.waypoints.push((x, y, speed.unwrap_or(DEFAULT_SPEED)));
+                Ok(())
+            },
+        );
+        methods.add_method_mut("set_arc", |_, anim, height: f32| {
+            anim.kind = ProjectileKind::Arc { height };
+            Ok(())
+        });
+        methods.add_method_mut("set_beam", |_, anim, hold: Option<f32>| {
+            let hold_millis = (hold.unwrap_or(0.5) * 1000.0) as u32;
+            anim.kind = ProjectileKind::Beam { hold_millis };
+            Ok(())
+        });
+        methods.add_method_mut("set_blocking", |_, anim, blocking: bool| {
+            anim.blocking = blocking;
+            Ok(())
+        });
+        methods.add_method_mut(
+            "add_impact_callback",
+            |_, anim, (cb, waypoint): (CallbackData, Option<usize>)| {
+                anim.impact_callbacks.push((waypoint, cb));
+                Ok(())
+            },
+        );
+        methods.add_method_mut("set_completion_callback", |_, anim, cb: CallbackData| {
+            anim.completion_callback = Some(cb);
+            Ok(())
+        });
+    }
+}
+
+fn activate(_lua: Context, data: &ScriptProjectileAnimation, _args: ()) -> Result<()> {
+    let anim = create_anim(data)?;
+
+    GameState::add_animation(anim);
+
+    Ok(())
+}
+
+pub fn create_anim(data: &ScriptProjectileAnimation) -> Result<Anim> {
+    if data.waypoints.is_empty() {
+        return Err(rlua::Error::ToLuaConversionError {
+            from: "ScriptProjectileAnimation",
+            to: "Anim",
+            message: Some("Projectile animation must have at least one waypoint".to_string()),
+        });
+    }
+
+    let image = match ResourceSet::image(&data.image) {
+        None => {
+            return Err(rlua::Error::ToLuaConversionError {
+                from: "String",
+                to: "Image",
+                message: Some(format!("Invalid image '{}'", data.image)),
+            });
+        }
+        Some(image) => image,
+    };
+
+    let last = data.waypoints.len() - 1;
+    let impact_callbacks = data
+        .impact_callbacks
+        .iter()
+        .map(|(waypoint, cb)| {
+            let index = waypoint.map_or(last, |w| w.saturating_sub(1).min(last));
+            let cb: Box<dyn ScriptCallback> = Box::new(cb.clone());
+            (index, cb)
+        })
+        .collect();
+
+    let mgr = GameState::turn_manager();
+    let parent = mgr.borrow().entity(data.parent);
+    let mut anim = projectile_animation::new(
+        &parent,
+        image,
+        data.kind,
+        &data.waypoints,
+        impact_callbacks,
+        data.blocking,
+    );
+
+    if let Some(ref cb) = data.completion_callback {
+        anim.add_completion_callback(Box::new(cb.clone()));
+    }
+
+    Ok(anim)
+}
@@ -0,0 +1,290 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use rlua::{self, AnyUserData, Context, UserData, UserDataMethods};
+
+use crate::animation::Anim;
+use crate::script::{
+    script_color_animation, script_image_layer_animation, script_particle_generator,
+    script_projectile_animation, script_scale_animation, script_subpos_animation, CallbackData,
+    Result, ScriptCallback, ScriptColorAnimation, ScriptImageLayerAnimation,
+    ScriptParticleGenerator, ScriptProjectileAnimation, ScriptScaleAnimation,
+    ScriptSubposAnimation,
+};
+use crate::GameState;
+
+/// A composite animation, which runs a series of steps one after another.
+/// Each step consists of one or more animations running in parallel, and
+/// completes once all of its animations have completed.  Animations in each
+/// step are only created once that step begins.  Typically created by
+/// `ScriptEntity:create_timeline`.  By default, the timeline blocks the parent
+/// until all steps have completed.
+///
+/// Any of the script animation types may be added, including particle
+/// generators, projectiles, and color, scale, subpos, and image layer animations.
+/// Animations with an infinite duration will never complete, and so will stall
+/// the timeline.
+///
+/// # `activate()`
+/// Activates this timeline, starting the first step.
+///
+/// # `add(anim: Animation)`
+/// Adds the specified `anim` as a new step, beginning after all previous steps
+/// have completed.
+///
+/// # `add_parallel(anim: Animation)`
+/// Adds the specified `anim` to the most recently added step, running in parallel
+/// with the other animations in that step.  If there are no steps yet, starts
+/// the first step.
+///
+/// # `add_wait(duration: Float)`
+/// Adds a new step which simply waits for the specified `duration` in seconds.
+///
+/// # `set_blocking(blocking: Bool)`
+/// Sets whether the parent is blocked from further actions until this timeline
+/// completes.  Defaults to true.
+///
+/// # `set_completion_callback(callback: CallbackData)`
+/// Sets the specified `callback` to be called when the final step of this timeline
+/// completes.
+/// ## Examples
+/// ```lua
+///   dash = parent:create_subpos_anim(0.3)
+///   dash:set_position(dash:param(0.0, 4.0), dash:param(0.0))
+///
+///   flash = target:create_color_anim(0.5)
+///   flash:set_color(flash:param(1.0), flash:param(0.2), flash:param(0.2), flash:param(1.0))
+///
+///   burst = target:create_particle_generator("particles/circle4", 0.6)
+///
+///   cb = ability:create_callback(parent)
+///   cb:add_target(target)
+///   cb:set_on_anim_complete_fn("on_presentation_complete")
+///
+///   timeline = parent:create_timeline()
+///   timeline:add(dash)
+///   timeline:add(flash)
+///   timeline:add_parallel(burst)
+///   timeline:set_completion_callback(cb)
+///   timeline:activate()
+/// ```
+#[derive(Clone)]
+pub struct ScriptTimeline {
+    parent: usize,
+    steps: Vec<Vec<TimelineAnim>>,
+    blocking: bool,
+    completion_callback: Option<CallbackData>,
+}
+
+impl ScriptTimeline {
+    pub fn new(parent: usize) -> ScriptTimeline {
+        ScriptTimeline {
+            parent,
+            steps: Vec::new(),
+            blocking: true,
+            completion_callback: None,
+        }
+    }
+}
+
+impl UserData for ScriptTimeline {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("activate", activate);
+        methods.add_method_mut("add", |_, timeline, anim: AnyUserData| {
+            let anim = TimelineAnim::from_userdata(&anim)?;
+            timeline.steps.push(vec![anim]);
+            Ok(())
+        });
+        methods.add_method_mut("add_parallel", |_, timeline, anim: AnyUserData| {
+            let anim = TimelineAnim::from_userdata(&anim)?;
+            match timeline.steps.last_mut() {
+                None => timeline.steps.push(vec![anim]),
+                Some(step) => step.push(anim),
+            }
+            Ok(())
+        });
+        methods.add_method_mut("add_wait", |_, timeline, duration: f32| {
+            let millis = (duration * 1000.0) as u32;
+            timeline.steps.push(vec![TimelineAnim::Wait(millis)]);
+            Ok(())
+        });
+        methods.add_method_mut("set_blocking", |_, timeline, blocking: bool| {
+            timeline.blocking = blocking;
+            Ok(())
+        });
+        methods.add_method_mut("set_completion_callback", |_, timeline, cb: CallbackData| {
+            timeline.completion_callback = Some(cb);
+            Ok(())
+        });
+    }
+}
+
+fn activate(_lua: Context, data: &ScriptTimeline, _args: ()) -> Result<()> {
+    let mgr = GameState::turn_manager();
+    let parent = mgr.borrow().entity(data.parent);
+
+    let wait = if data.blocking {
+        let anim = Anim::new_indefinite_wait(&parent);
+        let marked_for_removal = anim.get_marked_for_removal();
+        GameState::add_animation(anim);
+        Some(marked_for_removal)
+    } else {
+        None
+    };
+
+    let runner = Rc::new(RefCell::new(TimelineRunner {
+        parent: data.parent,
+        steps: data.steps.iter().cloned().collect(),
+        remaining: Cell::new(0),
+        wait,
+        completion_callback: data.completion_callback.clone(),
+    }));
+
+    start_next_step(&runner);
+    Ok(())
+}
+
+#[derive(Clone)]
+enum TimelineAnim {
+    Color(ScriptColorAnimation),
+    ImageLayer(ScriptImageLayerAnimation),
+    ParticleGenerator(Box<ScriptParticleGenerator>),
+    Projectile(ScriptProjectileAnimation),
+    Scale(ScriptScaleAnimation),
+    Subpos(ScriptSubposAnimation),
+    Wait(u32),
+}
+
+impl TimelineAnim {
+    fn from_userdata(data: &AnyUserData) -> Result<TimelineAnim> {
+        use self::TimelineAnim::*;
+        if let Ok(anim) = data.borrow::<ScriptColorAnimation>() {
+            Ok(Color(anim.clone()))
+        } else if let Ok(anim) = data.borrow::<ScriptImageLayerAnimation>() {
+            Ok(ImageLayer(anim.clone()))
+        } else if let Ok(anim) = data.borrow::<ScriptParticleGenerator>() {
+            Ok(ParticleGenerator(Box::new(anim.clone())))
+        } else if let Ok(anim) = data.borrow::<ScriptProjectileAnimation>() {
+            Ok(Projectile(anim.clone()))
+        } else if let Ok(anim) = data.borrow::<ScriptScaleAnimation>() {
+            Ok(Scale(anim.clone()))
+        } else if let Ok(anim) = data.borrow::<ScriptSubposAnimation>() {
+            Ok(Subpos(anim.clone()))
+        } else {
+            Err(rlua::Error::FromLuaConversionError {
+                from: "UserData",
+                to: "TimelineAnim",
+                message: Some("Only script animations may be added to a timeline".to_string()),
+            })
+        }
+    }
+
+    fn create_anim(&self, parent: usize) -> Result<Anim> {
+        use self::TimelineAnim::*;
+        match self {
+            Color(data) => script_color_animation::create_anim(data),
+            ImageLayer(data) => script_image_layer_animation::create_anim(data),
+            ParticleGenerator(data) => script_particle_generator::create_anim(data),
+            Projectile(data) => script_projectile_animation::create_anim(data),
+            Scale(data) => script_scale_animation::create_anim(data),
+            Subpos(data) => script_subpos_animation::create_anim(data),
+            Wait(millis) => {
+                let mgr = GameState::turn_manager();
+                let parent = mgr.borrow().entity(parent);
+                Ok(Anim::new_non_blocking_wait(&parent, *millis))
+            }
+        }
+    }
+}
+
+struct TimelineRunner {
+    parent: usize,
+    steps: VecDeque<Vec<TimelineAnim>>,
+    remaining: Cell<usize>,
+    wait: Option<Rc<Cell<bool>>>,
+    completion_callback: Option<CallbackData>,
+}
+
+fn start_next_step(runner: &Rc<RefCell<TimelineRunner>>) {
+    loop {
+        let (parent, step) = {
+            let mut runner = runner.borrow_mut();
+            (runner.parent, runner.steps.pop_front())
+        };
+
+        let step = match step {
+            None => break,
+            Some(step) => step,
+        };
+
+        let mut anims = Vec::new();
+        for anim in step {
+            match anim.create_anim(parent) {
+                Ok(anim) => anims.push(anim),
+                Err(e) => warn!("Unable to create timeline animation: {}", e),
+            }
+        }
+
+        if anims.is_empty() {
+            continue;
+        }
+
+        runner.borrow().remaining.set(anims.len());
+        for mut anim in anims {
+            anim.add_completion_callback(Box::new(StepCallback {
+                runner: Rc::clone(runner),
+            }));
+            GameState::add_animation(anim);
+        }
+        return;
+    }
+
+    let (wait, cb) = {
+        let mut runner = runner.borrow_mut();
+        (runner.wait.take(), runner.completion_callback.take())
+    };
+
+    if let Some(wait) = wait {
+        wait.set(true);
+    }
+
+    if let Some(cb) = cb {
+        cb.on_anim_complete();
+    }
+}
+
+struct StepCallback {
+    runner: Rc<RefCell<TimelineRunner>>,
+}
+
+impl ScriptCallback for StepCallback {
+    fn on_anim_complete(&self) {
+        let step_done = {
+            let runner = self.runner.borrow();
+            let remaining = runner.remaining.get().saturating_sub(1);
+            runner.remaining.set(remaining);
+            remaining == 0
+        };
+
+        if step_done {
+            start_next_step(&self.runner);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use rlua::{UserData, UserDataMethods};
+
+use crate::script::{script_callback::FuncKind, CallbackData, ScriptEntity};
+use crate::GameState;
+use sulis_module::on_trigger::{self, OnTrigger, ScriptMenuChoice, WindowElement};
+
+/// The maximum number of rows in a script window
+pub const MAX_ROWS: usize = 12;
+
+/// The maximum number of labels and buttons in a single row of a script window
+pub const MAX_ROW_ELEMENTS: usize = 8;
+
+/// The user interface API for scripts, available as the global `ui`.  Scripts
+/// may build simple custom windows out of labels and buttons, for minigames,
+/// puzzles, and similar screens.  The layout and appearance of these windows is
+/// controlled by the `script_window` theme.
+///
+/// # `create_window(title: String, callback: CallbackData) -> ScriptWindow`
+/// Creates a new `ScriptWindow`, which can then be built up and finally shown
+/// with `show()`.  The `on_menu_select` func of the `callback` is called with a
+/// `ScriptMenuSelection` when the user clicks a button in the window.
+///
+/// # `close_window()`
+/// Closes the currently shown script window, if there is one.
+#[derive(Clone)]
+pub struct ScriptUI {}
+
+impl UserData for ScriptUI {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "create_window",
+            |_, _, (title, cb): (String, CallbackData)| Ok(ScriptWindow::new(title, cb)),
+        );
+
+        methods.add_method("close_window", |_, _, ()| {
+            let pc = GameState::player();
+            GameState::add_ui_callback(vec![OnTrigger::CloseWindow], &pc, &pc);
+            Ok(())
+        });
+    }
+}
+
+/// A custom window being created by a script.  Normally created by
+/// `ui:create_window()`.  Only one script window is shown at a time; showing
+/// a window replaces any script window that is already open, which allows
+/// a script to update the window in response to a button press.
+///
+/// # `add_label(text: String)`
+/// Adds a label showing `text` to the current row.  The text may
+/// contain markup.
+///
+/// # `add_button(text: String, value: String (Optional))`
+/// Adds a button showing `text` to the current row.  When clicked, the
+/// window callback is called with either `value` if specified, or `text` if not.
+///
+/// # `add_row()`
+/// Starts a new row.  Elements added after this call are placed below the
+/// previous elements.
+///
+/// # `show(parent: ScriptEntity)`
+/// Shows this window.
+#[derive(Clone)]
+pub struct ScriptWindow {
+    title: String,
+    rows: Vec<Vec<WindowElement>>,
+    callback: CallbackData,
+}
+
+impl ScriptWindow {
+    pub fn new(title: String, callback: CallbackData) -> ScriptWindow {
+        ScriptWindow {
+            title,
+            rows: vec![Vec::new()],
+            callback,
+        }
+    }
+
+    fn add_element(&mut self, element: WindowElement) -> rlua::Result<()> {
+        let row = self.rows.last_mut().unwrap();
+        if row.len() >= MAX_ROW_ELEMENTS {
+            return Err(rlua::Error::RuntimeError(format!(
+                "Script windows may have at most {MAX_ROW_ELEMENTS} elements per row"
+            )));
+        }
+        row.push(element);
+        Ok(())
+    }
+}
+
+impl UserData for ScriptWindow {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("add_label", |_, window, text: String| {
+            window.add_element(WindowElement::Label(text))
+        });
+
+        methods.add_method_mut(
+            "add_button",
+            |_, window, (text, value): (String, Option<String>)| {
+                let value = match value {
+                    None => text.clone(),
+                    Some(value) => value,
+                };
+
+                window.add_element(WindowElement::Button(ScriptMenuChoice {
+                    display: text,
+                    value,
+                }))
+            },
+        );
+
+        methods.add_method_mut("add_row", |_, window, ()| {
+            if window.rows.len() >= MAX_ROWS {
+                return Err(rlua::Error::RuntimeError(format!(
+                    "Script windows may have at most {MAX_ROWS} rows"
+                )));
+            }
+            window.rows.push(Vec::new());
+            Ok(())
+        });
+
+        methods.add_method("show", |_, window, parent: ScriptEntity| {
+            let parent = parent.try_unwrap()?;
+            let func = match window.callback.get_func(FuncKind::OnMenuSelect) {
+                None => {
+                    return Err(rlua::Error::FromLuaConversionError {
+                        from: "CallbackData",
+                        to: "Window",
+                        message: Some("OnMenuSelect must be specified for callback".to_string()),
+                    });
+                }
+                Some(func) => func,
+            };
+
+            if !parent.borrow().is_party_member() {
+                warn!("Attempted to show window for a non-player");
+                return Ok(());
+            }
+
+            let data = on_trigger::WindowData {
+                title: window.title.to_string(),
+                rows: window.rows.clone(),
+                cb_func: func,
+                cb_kind: window.callback.kind(),
+                cb_parent: window.callback.parent(),
+            };
+
+            let pc = GameState::player();
+            GameState::add_ui_callback(vec![OnTrigger::ShowWindow(data)], &pc, &pc);
+
+            Ok(())
+        });
+    }
+}
@@ -17,7 +17,7 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{AreaState, EntityState, GameState, Location, TurnManager};
+use crate::{AreaState, EntityState, GameEvent, GameState, Location, TurnManager};
 use sulis_core::{util::Point};
 use sulis_module::{
     area::{ToKind, TriggerKind},
@@ -25,6 +25,27 @@ use sulis_module::{
 };
 
 pub(crate) fn transition_to(area_id: Option<&str>, p: Option<Point>, offset: Point, time: Time) {
+    transition(area_id, p, offset, time, false);
+}
+
+/// Moves the party to the level `area_id`, which shares coordinates with the
+/// current area.  Each party member keeps their current position.
+pub(crate) fn transition_to_level(area_id: &str, time: Time) {
+    let p = {
+        let pc = GameState::player();
+        let pc = pc.borrow();
+        Point::new(pc.location.x, pc.location.y)
+    };
+    transition(Some(area_id), Some(p), Point::default(), time, true);
+}
+
+fn transition(
+    area_id: Option<&str>,
+    p: Option<Point>,
+    offset: Point,
+    time: Time,
+    keep_positions: bool,
+) {
     info!("Area transition to {:?}: {:?}", area_id, p);
 
     if let Some(id) = area_id {
@@ -52,6 +73,13 @@ pub(crate) fn transition_to(area_id: Option<&str>, p: Option<Point>, offset: Poi
 
     // Point of no return - we are actually transitioning now
 
+    dismiss_party_summons();
+
+    let old_area = GameState::area_state();
+    if !Rc::ptr_eq(&old_area, &area) {
+        GameState::remove_area_animations(&old_area.borrow().area.area.id);
+    }
+
     let new_area = GameState::set_current_area(&area);
     GameState::set_clear_anims(); // cleanup anims and surfaces
 
@@ -72,14 +100,19 @@ pub(crate) fn transition_to(area_id: Option<&str>, p: Option<Point>, offset: Poi
 
     mgr.borrow_mut().add_time(time);
 
-    transition_party(&mgr, &area, p, &party);
+    transition_party(&mgr, &area, p, &party, keep_positions);
 
     let pc = GameState::player();
-    area.borrow_mut().push_scroll_to_callback(Rc::clone(&pc));
+    area.borrow_mut().camera().center_on(Rc::clone(&pc));
 
     let mut area = area.borrow_mut();
 
     area.update_view_visibility();
+    area.reset_time_of_day();
+
+    GameState::publish_event(GameEvent::AreaEntered {
+        area: area.area.area.id.to_string(),
+    });
 
     if !area.on_load_fired {
         area.on_load_fired = true;
@@ -108,17 +141,38 @@ pub(crate) fn transition_to(area_id: Option<&str>, p: Option<Point>, offset: Poi
     }
 }
 
+// summoned creatures do not follow their summoner between areas
+fn dismiss_party_summons() {
+    let mgr = GameState::turn_manager();
+    for member in GameState::party() {
+        let summons = mgr.borrow().summons_for(member.borrow().index());
+        for summon in summons {
+            info!("Dismissing summon '{}'", summon.borrow().actor.actor.id);
+            mgr.borrow_mut().dismiss_summon(&summon);
+            if summon.borrow().is_party_member() {
+                GameState::remove_party_member(summon);
+            }
+        }
+    }
+}
+
 fn transition_party(
     mgr: &Rc<RefCell<TurnManager>>,
     area: &Rc<RefCell<AreaState>>,
     p: Point,
     party: &[Rc<RefCell<EntityState>>],
+    keep_positions: bool,
 ) {
     let base_location = Location::new(p.x, p.y, &area.borrow().area.area);
 
     for entity in party {
         entity.borrow_mut().clear_pc_vis();
-        let mut cur_location = base_location.clone();
+        let mut cur_location = if keep_positions {
+            let (x, y) = (entity.borrow().location.x, entity.borrow().location.y);
+            Location::new(x, y, &area.borrow().area.area)
+        } else {
+            base_location.clone()
+        };
         find_transition_location(&mut cur_location, &entity.borrow().size, &area.borrow());
 
         info!(
@@ -19,9 +19,9 @@ use std::collections::{vec_deque::Iter, HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 
 use crate::script::{CallbackData, FuncKind, TriggeredCallback};
-use crate::{AreaState, ChangeListener, ChangeListenerList, Effect, EntityState, GameState};
-use sulis_core::{config::Config, util::{gen_rand, Point}};
-use sulis_module::{Faction, Module, Time, ROUND_TIME_MILLIS, OnTrigger};
+use crate::{dist, AreaState, ChangeListener, ChangeListenerList, Effect, EntityState, GameState};
+use sulis_core::{config::Config, util::{combat_roll, Point}};
+use sulis_module::{Faction, Module, Time, ROUND_TIME_MILLIS};
 
 fn add_campaign_elapsed_callback(cbs: &mut Vec<Rc<CallbackData>>) {
     let script_data = match Module::campaign().on_round_elapsed_script {
@@ -37,6 +37,20 @@ fn add_campaign_elapsed_callback(cbs: &mut Vec<Rc<CallbackData>>) {
     cbs.push(Rc::new(cb));
 }
 
+/// Adds callbacks for the scheduled scripts due by `millis`, so that in combat
+/// they are called at the start of the round along with the other round
+/// elapsed callbacks
+fn add_scheduled_callbacks(millis: usize, cbs: &mut Vec<Rc<CallbackData>>) {
+    let player = GameState::player();
+
+    for scheduled in GameState::take_due_scheduled_scripts(millis) {
+        let mut cb = CallbackData::new_trigger(player.borrow().index(), scheduled.script);
+        cb.add_func(FuncKind::OnRoundElapsed, scheduled.func);
+
+        cbs.push(Rc::new(cb));
+    }
+}
+
 #[derive(Clone, Copy)]
 enum Entry {
     Entity(usize),
@@ -69,6 +83,12 @@ pub struct TurnManager {
     pub(crate) ai_groups: HashMap<usize, EncounterRef>,
     pub(crate) cur_ai_group_index: usize,
 
+    pub(crate) combat_start_suppressed: bool,
+    ambusher: Option<(usize, usize)>,
+    surprised: HashSet<usize>,
+    rounds_out_of_sight: u32,
+    pub(crate) fled_groups: HashMap<usize, usize>,
+
     total_elapsed_millis: usize,
 }
 
@@ -137,6 +157,11 @@ impl TurnManager {
         self.order.clear();
         self.cur_ai_group_index = 0;
         self.ai_groups.clear();
+        self.combat_start_suppressed = false;
+        self.ambusher = None;
+        self.surprised.clear();
+        self.rounds_out_of_sight = 0;
+        self.fled_groups.clear();
         self.total_elapsed_millis = total_elapsed_millis;
     }
 
@@ -179,6 +204,30 @@ impl TurnManager {
         effects
     }
 
+    /// Returns all creatures summoned by the entity with the specified index
+    pub fn summons_for(&self, entity_index: usize) -> Vec<Rc<RefCell<EntityState>>> {
+        let mut summons = Vec::new();
+        for effect in self.effects.iter().flatten() {
+            if effect.entity != Some(entity_index) {
+                continue;
+            }
+
+            for index in effect.summons.iter() {
+                if let Some(Some(entity)) = self.entities.get(*index) {
+                    summons.push(Rc::clone(entity));
+                }
+            }
+        }
+        summons
+    }
+
+    /// Immediately removes the specified summoned creature, rather than
+    /// waiting for the next update
+    pub(crate) fn dismiss_summon(&mut self, summon: &Rc<RefCell<EntityState>>) {
+        let index = summon.borrow().index();
+        self.remove_entity(index);
+    }
+
     pub fn effect_iter(&self) -> EffectIterator {
         EffectIterator {
             mgr: self,
@@ -222,6 +271,29 @@ impl TurnManager {
         value
     }
 
+    /// Returns true if any living entity spawned by the encounter with the
+    /// specified index in the specified area remains
+    pub(crate) fn encounter_has_survivors(&self, area_id: &str, enc_index: usize) -> bool {
+        self.entity_iter().any(|entity| {
+            let entity = entity.borrow();
+            if entity.actor.hp() <= 0 {
+                return false;
+            }
+
+            let group = match entity.ai_group() {
+                None => return false,
+                Some(group) => group,
+            };
+
+            match self.ai_groups.get(&group) {
+                None => false,
+                Some(enc_ref) => {
+                    enc_ref.area_id == area_id && enc_ref.encounter_index == enc_index
+                }
+            }
+        })
+    }
+
     pub fn entity_checked(&self, index: usize) -> Option<Rc<RefCell<EntityState>>> {
         if index >= self.entities.len() {
             return None;
@@ -233,6 +305,28 @@ impl TurnManager {
         Rc::clone(self.entities[index].as_ref().unwrap())
     }
 
+    /// Marks for removal each surface in the specified area with the `tag`
+    /// that covers at least one of the `points`
+    pub fn remove_surfaces_with_tag(&mut self, area_id: &str, points: &[Point], tag: &str) {
+        for index in self.surfaces.iter() {
+            let effect = self.effects[*index].as_mut().unwrap();
+            if !effect.has_tag(tag) {
+                continue;
+            }
+
+            let overlaps = match effect.surface() {
+                None => false,
+                Some((id, surface_points)) => {
+                    id == area_id && surface_points.iter().any(|p| points.contains(p))
+                }
+            };
+
+            if overlaps {
+                effect.mark_for_removal();
+            }
+        }
+    }
+
     #[must_use]
     pub fn drain_triggered_cbs(&mut self) -> Vec<TriggeredCallback> {
         let mut result = Vec::new();
@@ -336,7 +430,18 @@ impl TurnManager {
         };
 
         let cbs = effect.update(elapsed_millis);
-        (effect.is_removal(), cbs)
+
+        // summons are dismissed when their summoner dies
+        let summoner_dead = !effect.summons.is_empty()
+            && match effect.entity {
+                None => false,
+                Some(entity) => match self.entities.get(entity) {
+                    Some(Some(entity)) => entity.borrow().actor.is_dead(),
+                    _ => true,
+                },
+            };
+
+        (effect.is_removal() || summoner_dead, cbs)
     }
 
     #[must_use]
@@ -364,8 +469,12 @@ impl TurnManager {
 
     #[must_use]
     pub fn next(&mut self) -> Vec<Rc<CallbackData>> {
-        if self.is_combat_active() && self.check_combat_run_away() {
-            self.set_combat_active(false);
+        if self.is_combat_active()
+            && (self.check_combat_run_away()
+                || self.check_combat_out_of_sight()
+                || self.check_combat_left_flee_region())
+        {
+            self.flee_combat();
             self.listeners.notify(self);
             return Vec::new();
         }
@@ -403,13 +512,13 @@ impl TurnManager {
                 let loc = &current.borrow().location;
                 (loc.x, loc.y)
             };
-            let cb = OnTrigger::ScrollView(x, y);
-            GameState::add_ui_callback(vec![cb], current, current);
+            area_state.camera().scroll_to(x as f32, y as f32, None);
         }
 
         let mut current = current.borrow_mut();
         current.actor.init_turn();
         current.actor.elapse_time(ROUND_TIME_MILLIS, &self.effects);
+        current.threat.decay();
 
         debug!("'{}' now has the active turn", current.actor.actor.name);
     }
@@ -465,9 +574,13 @@ impl TurnManager {
                     current_ended = true;
                 }
                 Entry::TurnChange => {
+                    // the surprise round is over
+                    self.surprised.clear();
+                    self.update_rounds_out_of_sight();
                     self.add_millis(ROUND_TIME_MILLIS);
                     self.order.push_back(Entry::TurnChange);
                     add_campaign_elapsed_callback(&mut cbs);
+                    add_scheduled_callbacks(self.total_elapsed_millis, &mut cbs);
                 }
             }
         }
@@ -477,6 +590,10 @@ impl TurnManager {
 
     fn current_is_active_entity(&self) -> bool {
         if let Some(Entry::Entity(index)) = self.order.front() {
+            if self.surprised.contains(index) {
+                return false;
+            }
+
             if let Some(entity) = &self.entities[*index] {
                 let entity = entity.borrow();
                 return entity.is_party_member() || entity.is_ai_active();
@@ -501,6 +618,11 @@ impl TurnManager {
             return;
         }
 
+        if !self.combat_active && self.combat_start_suppressed {
+            return;
+        }
+
+        let engage_dist = Module::rules().combat_engage_distance;
         let mut groups_to_activate: HashSet<usize> = HashSet::new();
         let mut state_changed = false;
 
@@ -521,10 +643,13 @@ impl TurnManager {
             if !entity.is_hostile(&mover.borrow()) {
                 continue;
             }
+            if !self.combat_active && self.recently_fled(&entity) {
+                continue;
+            }
             if !entity.location.is_in(area_state) {
                 continue;
             }
-            if entity.actor.actor.ai.is_none() && !entity.is_party_member() {
+            if entity.actor.actor.ai_script_id().is_none() && !entity.is_party_member() {
                 continue;
             }
 
@@ -535,6 +660,10 @@ impl TurnManager {
                 continue;
             }
 
+            if !self.combat_active && engage_dist > 0.0 && dist(&*mover, &*entity) > engage_dist {
+                continue;
+            }
+
             log::warn!("Found ai activation entity: {} at {:?}", entity.unique_id(), entity.location);
             self.activate_entity_ai(&mut entity, &mut groups_to_activate);
             state_changed = true;
@@ -546,6 +675,125 @@ impl TurnManager {
 
         self.activate_entity_ai(&mut mover.borrow_mut(), &mut groups_to_activate);
 
+        let surprise_by = if Module::rules().surprise_round {
+            self.take_ambusher()
+        } else {
+            None
+        };
+        self.activate_groups(mover, area_state, groups_to_activate, surprise_by);
+    }
+
+    /// Starts combat between `initiator` and all hostile AI entities in the
+    /// area, regardless of distance or visibility, and even if combat start
+    /// has been suppressed.  If `surprise` is set, those hostile to the
+    /// `initiator` lose their turns in the first round.
+    pub fn force_combat(
+        &mut self,
+        initiator: &Rc<RefCell<EntityState>>,
+        area_state: &mut AreaState,
+        surprise: bool,
+    ) {
+        if self.combat_active {
+            return;
+        }
+
+        let mut groups_to_activate: HashSet<usize> = HashSet::new();
+        let mut state_changed = false;
+
+        for entity in self.entities.iter() {
+            let entity = match entity {
+                None => continue,
+                Some(ref entity) => entity,
+            };
+
+            if Rc::ptr_eq(initiator, entity) {
+                continue;
+            }
+
+            let mut entity = entity.borrow_mut();
+            if entity.actor.is_dead() {
+                continue;
+            }
+            if !entity.is_hostile(&initiator.borrow()) {
+                continue;
+            }
+            if !entity.location.is_in(area_state) {
+                continue;
+            }
+            if entity.actor.actor.ai_script_id().is_none() && !entity.is_party_member() {
+                continue;
+            }
+
+            self.activate_entity_ai(&mut entity, &mut groups_to_activate);
+            state_changed = true;
+        }
+
+        if !state_changed {
+            return;
+        }
+
+        self.activate_entity_ai(&mut initiator.borrow_mut(), &mut groups_to_activate);
+
+        let surprise_by = if surprise {
+            Some(initiator.borrow().index())
+        } else {
+            None
+        };
+        self.activate_groups(initiator, area_state, groups_to_activate, surprise_by);
+    }
+
+    /// Records that the entity with the specified `index` attacked from stealth
+    /// while out of combat.  If this attack starts combat within the current
+    /// round, the entity's side is granted a surprise round.
+    pub(crate) fn set_ambusher(&mut self, index: usize) {
+        if self.combat_active {
+            return;
+        }
+
+        self.ambusher = Some((index, self.total_elapsed_millis));
+    }
+
+    fn take_ambusher(&mut self) -> Option<usize> {
+        let (index, millis) = self.ambusher.take()?;
+        if self.total_elapsed_millis > millis + ROUND_TIME_MILLIS as usize {
+            return None;
+        }
+
+        Some(index)
+    }
+
+    fn set_surprised_by(&mut self, ambusher: usize) {
+        let ambusher = match self.entity_checked(ambusher) {
+            None => return,
+            Some(entity) => entity,
+        };
+        let ambusher = ambusher.borrow();
+
+        // the ambusher must be able to take the first turn
+        if !ambusher.is_party_member() && !ambusher.is_ai_active() {
+            return;
+        }
+
+        for entity in self.entities.iter() {
+            let entity = match entity {
+                None => continue,
+                Some(ref entity) => entity,
+            };
+
+            let entity = entity.borrow();
+            if entity.is_hostile(&ambusher) {
+                self.surprised.insert(entity.index());
+            }
+        }
+    }
+
+    fn activate_groups(
+        &mut self,
+        mover: &Rc<RefCell<EntityState>>,
+        area_state: &mut AreaState,
+        groups_to_activate: HashSet<usize>,
+        surprise_by: Option<usize>,
+    ) {
         for entity in self.entities.iter() {
             let entity = match entity {
                 None => continue,
@@ -589,6 +837,10 @@ impl TurnManager {
             }).collect();
             area_state.update_music(true, Some(&enc_indices));
 
+            if let Some(ambusher) = surprise_by {
+                self.set_surprised_by(ambusher);
+            }
+
             self.set_combat_active(true);
             loop {
                 if self.current_is_active_entity() {
@@ -668,7 +920,144 @@ impl TurnManager {
         true
     }
 
+    fn check_combat_out_of_sight(&self) -> bool {
+        let flee_rounds = Module::rules().combat_flee_los_rounds;
+        flee_rounds > 0 && self.rounds_out_of_sight >= flee_rounds
+    }
+
+    /// Returns true if each active encounter has a flee region, and every
+    /// party member is outside all of them
+    fn check_combat_left_flee_region(&self) -> bool {
+        let groups = self.active_ai_groups();
+        if groups.is_empty() {
+            return false;
+        }
+
+        let party_pos: Vec<_> = GameState::party()
+            .into_iter()
+            .map(|e| e.borrow().location.to_point())
+            .collect();
+
+        for group in groups {
+            let enc_ref = match self.ai_groups.get(&group) {
+                None => return false,
+                Some(enc_ref) => enc_ref,
+            };
+            let area_state = match GameState::get_area_state(&enc_ref.area_id) {
+                None => return false,
+                Some(area_state) => area_state,
+            };
+
+            let area_state = area_state.borrow();
+            let region = match area_state.area.encounters[enc_ref.encounter_index].flee_region {
+                None => return false,
+                Some(region) => region,
+            };
+
+            if party_pos.iter().any(|p| region.contains(*p)) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn update_rounds_out_of_sight(&mut self) {
+        let party = GameState::party();
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+
+        for entity in self.entities.iter() {
+            let entity = match entity {
+                None => continue,
+                Some(ref entity) => entity,
+            };
+            let entity = entity.borrow();
+            if !entity.is_ai_active() || entity.actor.is_dead() {
+                continue;
+            }
+            if !entity.location.is_in(&area_state) {
+                continue;
+            }
+
+            for member in &party {
+                let member = member.borrow();
+                if area_state.has_visibility(&member, &entity)
+                    || area_state.has_visibility(&entity, &member)
+                {
+                    self.rounds_out_of_sight = 0;
+                    return;
+                }
+            }
+        }
+
+        self.rounds_out_of_sight += 1;
+    }
+
+    /// The AI groups with at least one active member
+    fn active_ai_groups(&self) -> HashSet<usize> {
+        let mut groups = HashSet::new();
+        for entity in self.entities.iter() {
+            let entity = match entity {
+                None => continue,
+                Some(ref entity) => entity,
+            };
+            let entity = entity.borrow();
+            if !entity.is_ai_active() {
+                continue;
+            }
+
+            if let Some(group) = entity.ai_group() {
+                groups.insert(group);
+            }
+        }
+        groups
+    }
+
+    fn recently_fled(&self, entity: &EntityState) -> bool {
+        let group = match entity.ai_group() {
+            None => return false,
+            Some(group) => group,
+        };
+
+        let fled_millis = match self.fled_groups.get(&group) {
+            None => return false,
+            Some(millis) => *millis,
+        };
+
+        let cooldown = Module::rules().combat_flee_cooldown_rounds as usize;
+        self.total_elapsed_millis < fled_millis + cooldown * ROUND_TIME_MILLIS as usize
+    }
+
+    /// Ends combat with the party escaping.  Each active encounter is marked
+    /// as fled, so it will not immediately restart combat, and fires its
+    /// `OnEncounterFled` triggers.
+    fn flee_combat(&mut self) {
+        let groups = self.active_ai_groups();
+
+        info!("Party fled from combat with {} encounters", groups.len());
+        self.set_combat_active(false);
+
+        let player = GameState::player();
+        for group in groups {
+            self.fled_groups.insert(group, self.total_elapsed_millis);
+
+            let enc_ref = match self.ai_groups.get(&group) {
+                None => continue,
+                Some(enc_ref) => enc_ref.clone(),
+            };
+            if let Some(area_state) = GameState::get_area_state(&enc_ref.area_id) {
+                area_state
+                    .borrow_mut()
+                    .fire_on_encounter_fled(enc_ref.encounter_index, &player);
+            }
+        }
+    }
+
     fn end_combat(&mut self) {
+        self.surprised.clear();
+        self.ambusher = None;
+
         for entity in self.entities.iter() {
             let entity = match entity {
                 None => continue,
@@ -678,6 +1067,7 @@ impl TurnManager {
             let mut entity = entity.borrow_mut();
 
             entity.set_ai_active(false);
+            entity.threat.clear();
 
             if !entity.is_party_member() {
                 continue;
@@ -713,6 +1103,8 @@ impl TurnManager {
     }
 
     fn initiate_combat(&mut self) {
+        self.rounds_out_of_sight = 0;
+
         // first, compute initiative for each entry in the list
         let initiative_roll_max = Module::rules().initiative_roll_max;
         let mut initiative = vec![0; self.order.len()];
@@ -729,7 +1121,7 @@ impl TurnManager {
                         .actor
                         .stats
                         .initiative;
-                    last_initiative = base + gen_rand(0, initiative_roll_max);
+                    last_initiative = base + combat_roll(0, initiative_roll_max);
                     initiative[index] = 2 * last_initiative;
                 }
                 Entry::Effect(_) => {
@@ -961,12 +1353,14 @@ impl TurnManager {
     fn remove_effect(&mut self, index: usize) -> Vec<Rc<CallbackData>> {
         let cbs;
         let mut entities = HashSet::new();
+        let mut summons = Vec::new();
         if let Some(effect) = &self.effects[index] {
             if let Some((area_id, points)) = effect.surface() {
                 let area = GameState::get_area_state(area_id).unwrap();
                 entities = area.borrow_mut().remove_surface(index, points);
             }
 
+            summons = effect.summons.clone();
             cbs = effect.callbacks.clone();
         } else {
             cbs = Vec::new();
@@ -975,6 +1369,12 @@ impl TurnManager {
         for entity in entities {
             self.remove_from_surface(entity, index);
         }
+
+        for summon in summons {
+            if let Some(Some(entity)) = self.entities.get(summon) {
+                entity.borrow_mut().marked_for_removal = true;
+            }
+        }
         self.effects[index] = None;
         self.order.retain(|e| match e {
             Entry::Effect(i) => *i != index,
@@ -1018,7 +1418,7 @@ impl TurnManager {
         Some(ai_group)
     }
 
-    fn remove_entity(&mut self, index: usize) {
+    pub(crate) fn remove_entity(&mut self, index: usize) {
         let entity = Rc::clone(self.entities[index].as_ref().unwrap());
         let area_state = GameState::get_area_state(&entity.borrow().location.area_id).unwrap();
         let surfaces = area_state.borrow_mut().remove_entity(&entity, self);
@@ -1038,6 +1438,27 @@ impl TurnManager {
         // self.entities[index] = None;
         entity.borrow_mut().marked_for_removal = false;
 
+        for other in self.entities.iter().flatten() {
+            other.borrow_mut().threat.remove(index);
+        }
+
+        // a summoning effect with no remaining summons is no longer needed
+        for effect_index in 0..self.effects.len() {
+            let effect = match self.effects[effect_index] {
+                None => continue,
+                Some(ref mut effect) => effect,
+            };
+
+            if !effect.summons.contains(&index) {
+                continue;
+            }
+
+            effect.summons.retain(|i| *i != index);
+            if effect.summons.is_empty() {
+                self.effects_remove_next_update.push(effect_index);
+            }
+        }
+
         // can't do this with a collect because of lifetime issues
         let mut effects_to_remove = Vec::new();
         {
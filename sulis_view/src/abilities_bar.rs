@@ -31,7 +31,8 @@ use sulis_module::{
     Ability, Class, Module,
 };
 use sulis_state::{
-    ability_state::DisabledReason, ChangeListener, EntityState, GameState, RangeIndicator, Script,
+    ability_state::DisabledReason, replay, ChangeListener, EntityState, GameState, RangeIndicator,
+    ReplayCommand, Script,
 };
 
 pub const NAME: &str = "abilities_bar";
@@ -557,6 +558,11 @@ impl WidgetKind for AbilityButton {
         widget.do_base_layout();
 
         let can_toggle = self.entity.borrow().actor.can_toggle(&self.ability.id);
+        let group_rounds = self
+            .entity
+            .borrow()
+            .actor
+            .group_cooldown_rounds(&self.ability.id);
 
         if can_toggle == DisabledReason::Enabled {
             widget
@@ -593,6 +599,7 @@ impl WidgetKind for AbilityButton {
             match state.remaining_duration_rounds() {
                 ExtInt::Infinity => child.add_text_arg("duration", "Active"),
                 ExtInt::Int(rounds) => {
+                    let rounds = std::cmp::max(rounds, group_rounds);
                     if rounds != 0 {
                         child.add_text_arg("duration", &rounds.to_string());
                     }
@@ -675,7 +682,12 @@ impl WidgetKind for AbilityButton {
     }
 }
 
-fn activate_ability(entity: &Rc<RefCell<EntityState>>, ability: &Rc<Ability>) -> bool {
+pub(crate) fn activate_ability(entity: &Rc<RefCell<EntityState>>, ability: &Rc<Ability>) -> bool {
+    replay::record(ReplayCommand::Ability {
+        entity: entity.borrow().index(),
+        ability: ability.id.to_string(),
+    });
+
     let can_activate = entity.borrow().actor.can_activate(&ability.id);
     if can_activate {
         let index = entity.borrow().index();
@@ -769,6 +781,8 @@ fn add_disabled_text_arg(
         RequiresActiveMode => "Must first activate a mode",
         CombatOnly => "May only be used in combat",
         OnCooldown => "The cooldown is active",
+        GroupOnCooldown => "A shared cooldown is active",
+        Encumbered => "Carrying too much weight",
     };
     state.add_text_arg("disabled", reason_text);
 }
@@ -0,0 +1,110 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::io::InputActionKind;
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, ScrollDirection, ScrollPane, TextArea};
+use sulis_module::Module;
+use sulis_state::AchievementState;
+
+pub const NAME: &str = "achievements_window";
+
+/// Lists the achievements defined by the current campaign, along with the
+/// progress toward each.  Unlocked achievements are listed first.
+pub struct AchievementsWindow {}
+
+impl AchievementsWindow {
+    pub fn new() -> Rc<RefCell<AchievementsWindow>> {
+        Rc::new(RefCell::new(AchievementsWindow {}))
+    }
+}
+
+impl WidgetKind for AchievementsWindow {
+    widget_kind!(NAME);
+
+    fn on_key_press(&mut self, widget: &Rc<RefCell<Widget>>, key: InputActionKind) -> bool {
+        if key != InputActionKind::Back {
+            return false;
+        }
+
+        widget.borrow_mut().mark_for_removal();
+        true
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<AchievementsWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        let mut achievements: Vec<_> = Module::all_achievements()
+            .into_iter()
+            .map(|achievement| {
+                let progress = AchievementState::progress(&achievement.id);
+                (achievement, progress)
+            })
+            .collect();
+        achievements.sort_by(|(a, a_prog), (b, b_prog)| {
+            b_prog
+                .unlocked
+                .cmp(&a_prog.unlocked)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let unlocked = achievements.iter().filter(|(_, p)| p.unlocked).count();
+        let summary = Widget::with_theme(TextArea::empty(), "summary");
+        {
+            let state = &mut summary.borrow_mut().state;
+            state.add_text_arg("unlocked", &unlocked.to_string());
+            state.add_text_arg("total", &achievements.len().to_string());
+        }
+
+        let list_pane = ScrollPane::new(ScrollDirection::Vertical);
+        let list_widget = Widget::with_theme(list_pane.clone(), "achievements");
+
+        for (achievement, progress) in achievements {
+            let entry = Widget::with_theme(TextArea::empty(), "achievement");
+
+            {
+                let state = &mut entry.borrow_mut().state;
+                state.set_active(progress.unlocked);
+                state.add_text_arg("name", &achievement.name);
+                state.add_text_arg("description", &achievement.description);
+
+                let target = achievement.target();
+                if progress.unlocked {
+                    state.add_text_arg("unlocked", "true");
+                } else if target > 1 {
+                    let count = progress.count.min(target);
+                    state.add_text_arg("progress", &count.to_string());
+                    state.add_text_arg("target", &target.to_string());
+                }
+            }
+
+            list_pane.borrow().add_to_content(entry);
+        }
+
+        vec![close, summary, list_widget]
+    }
+}
@@ -18,15 +18,18 @@ use std::cell::RefCell;
 use std::cmp;
 use std::rc::Rc;
 
-use crate::RootView;
-use sulis_core::ui::{animation_state, Widget};
+use crate::{modal_dialog, ItemActionMenu, ModalDialog, RootView};
+use sulis_core::ui::{animation_state, Callback, Cursor, Widget};
 use sulis_core::util::Point;
 use sulis_module::{
     area::{Destination, ToKind},
     Faction, Module, ObjectSize, OnTrigger, Time, MOVE_TO_THRESHOLD,
 };
 use sulis_state::{can_attack, is_within};
-use sulis_state::{AreaState, EntityState, GameState, PropState, ScriptCallback};
+use sulis_state::{
+    replay, AreaState, EntityState, GameState, OrderKind, PropState, QueuedOrder, ReplayCommand,
+    ScriptCallback, UnlockKind,
+};
 
 pub fn get_action(x_f32: f32, y_f32: f32) -> Box<dyn ActionKind> {
     let (x, y) = (x_f32 as i32, y_f32 as i32);
@@ -59,6 +62,75 @@ pub fn get_action(x_f32: f32, y_f32: f32) -> Box<dyn ActionKind> {
     Box::new(InvalidAction {})
 }
 
+/// Fires the action at the specified location.  If the action is a move or
+/// attack order and the selected party member is still busy with a blocking
+/// animation, the order is instead queued and fired once they are free.
+/// Returns true if the mouse state should be cleared, as with `fire_action`
+pub fn fire_or_queue_action(x: f32, y: f32, widget: &Rc<RefCell<Widget>>) -> bool {
+    replay::record(ReplayCommand::Action { x, y });
+
+    let mut action = get_action(x, y);
+
+    let pc = GameState::selected().first().cloned();
+    if let (Some(kind), Some(pc)) = (action.order_kind(), pc) {
+        // outside of combat, a new move order simply redirects the current one
+        let queue = kind == OrderKind::Attack || GameState::is_combat_active();
+        if queue && GameState::has_blocking_animations(&pc) {
+            if let Some(info) = action.get_hover_info() {
+                let cb = QueuedActionCallback {
+                    entity: Rc::clone(&pc),
+                    kind,
+                    x,
+                    y,
+                    widget: Rc::clone(widget),
+                };
+                let order = QueuedOrder {
+                    kind,
+                    location: Point::new(info.x, info.y),
+                    size: info.size,
+                    cb: Rc::new(cb),
+                };
+                GameState::queue_order(&pc, order);
+                return false;
+            }
+        }
+
+        GameState::clear_queued_orders(&pc);
+    }
+
+    action.fire_action(widget)
+}
+
+struct QueuedActionCallback {
+    entity: Rc<RefCell<EntityState>>,
+    kind: OrderKind,
+    x: f32,
+    y: f32,
+    widget: Rc<RefCell<Widget>>,
+}
+
+impl ScriptCallback for QueuedActionCallback {
+    fn on_anim_complete(&self) {
+        // the action is recomputed so it reflects the current state of
+        // the area, and dropped if the order no longer makes sense
+        match GameState::selected().first() {
+            Some(pc) if Rc::ptr_eq(pc, &self.entity) => (),
+            _ => return,
+        }
+
+        if GameState::is_combat_active() && !GameState::is_current(&self.entity) {
+            return;
+        }
+
+        let mut action = get_action(self.x, self.y);
+        if action.order_kind() != Some(self.kind) {
+            return;
+        }
+
+        action.fire_action(&self.widget);
+    }
+}
+
 fn get_prop_or_transition_action(x: i32, y: i32) -> Option<Box<dyn ActionKind>> {
     let area_state = GameState::area_state();
     let area_state = area_state.borrow();
@@ -72,6 +144,10 @@ fn get_prop_or_transition_action(x: i32, y: i32) -> Option<Box<dyn ActionKind>>
     // an enabled container or a closed door (regardless of enabled) blocks a transition.
     // an open door (regardless of enabled) does not block a transition
 
+    if prop.is_locked() && (prop.is_container() || !prop.is_active()) {
+        return LockedPropAction::create_if_valid(index, prop);
+    }
+
     if prop.is_container() && prop.is_enabled() {
         return LootPropAction::create_if_valid(index, prop);
     }
@@ -98,6 +174,9 @@ pub struct ActionHoverInfo {
     pub x: i32,
     pub y: i32,
     pub path: Vec<(f32, f32)>,
+
+    /// The remainder of the path, which cannot be reached with the available AP
+    pub unreachable_path: Vec<(f32, f32)>,
     pub ap: i32,
     pub total_ap: i32,
 }
@@ -118,6 +197,7 @@ impl ActionHoverInfo {
                         base.ap = append.ap;
                     }
                     base.path = append.path;
+                    base.unreachable_path = append.unreachable_path;
                 }
                 Some(base)
             }
@@ -130,6 +210,7 @@ impl ActionHoverInfo {
             x: point.x,
             y: point.y,
             path: Vec::new(),
+            unreachable_path: Vec::new(),
             ap: 0,
             total_ap: 0,
         }
@@ -146,6 +227,7 @@ impl ActionHoverInfo {
             x: point.x,
             y: point.y,
             path: Vec::new(),
+            unreachable_path: Vec::new(),
             ap,
             total_ap,
         }
@@ -155,6 +237,7 @@ impl ActionHoverInfo {
         entity: &EntityState,
         point: Point,
         path: &[(f32, f32)],
+        unreachable_path: &[(f32, f32)],
         ap: i32,
     ) -> ActionHoverInfo {
         let size = Rc::clone(&entity.size);
@@ -163,6 +246,7 @@ impl ActionHoverInfo {
             x: point.x,
             y: point.y,
             path: path.to_vec(),
+            unreachable_path: unreachable_path.to_vec(),
             ap,
             total_ap: entity.actor.ap() as i32,
         }
@@ -183,6 +267,12 @@ pub trait ActionKind {
     fn ap(&self) -> i32 {
         0
     }
+
+    /// Returns the kind of order this action represents, if it is a move or
+    /// attack that may be queued behind a blocking animation
+    fn order_kind(&self) -> Option<OrderKind> {
+        None
+    }
 }
 
 struct SelectAction {
@@ -225,7 +315,11 @@ impl ActionKind for SelectAction {
 
     fn fire_action(&mut self, _widget: &Rc<RefCell<Widget>>) -> bool {
         trace!("Firing select action.");
-        GameState::set_selected_party_member(Rc::clone(&self.target));
+        if Cursor::is_shift_down() {
+            GameState::toggle_selected_party_member(Rc::clone(&self.target));
+        } else {
+            GameState::set_selected_party_member(Rc::clone(&self.target));
+        }
         false
     }
 }
@@ -360,6 +454,107 @@ impl ActionKind for DoorPropAction {
     }
 }
 
+struct LockedPropAction {
+    index: usize,
+}
+
+impl LockedPropAction {
+    fn create_if_valid(index: usize, prop_state: &PropState) -> Option<Box<dyn ActionKind>> {
+        if !prop_state.is_locked() || !prop_state.is_enabled() {
+            return None;
+        }
+
+        let max_dist = Module::rules().max_prop_distance;
+        let pc = match GameState::selected().first() {
+            None => return None,
+            Some(pc) => Rc::clone(pc),
+        };
+        if !is_within(&*pc.borrow(), prop_state, max_dist) {
+            let cb_action = Box::new(LockedPropAction { index });
+            return MoveThenAction::create_if_valid(
+                &pc,
+                prop_state.location.to_point(),
+                &prop_state.prop.size,
+                max_dist,
+                cb_action,
+                animation_state::Kind::MouseInteract,
+            );
+        }
+
+        Some(Box::new(LockedPropAction { index }))
+    }
+
+    fn unlock_callback(index: usize, kind: UnlockKind) -> Callback {
+        Callback::new(Rc::new(move |widget, _| {
+            let pc = match GameState::selected().first() {
+                None => return,
+                Some(pc) => Rc::clone(pc),
+            };
+
+            if !GameState::try_unlock_prop(&pc, index, kind) {
+                return;
+            }
+
+            let (is_door, is_container) = {
+                let area_state = GameState::area_state();
+                let area_state = area_state.borrow();
+                let prop = area_state.props().get(index);
+                (prop.is_door(), prop.is_container())
+            };
+
+            if is_door {
+                DoorPropAction { index }.fire_action(widget);
+            } else if is_container && !GameState::is_combat_active() {
+                LootPropAction { index }.fire_action(widget);
+            }
+        }))
+    }
+}
+
+impl ActionKind for LockedPropAction {
+    fn cursor_state(&self) -> animation_state::Kind {
+        animation_state::Kind::MouseInteract
+    }
+
+    fn get_hover_info(&self) -> Option<ActionHoverInfo> {
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        let prop = area_state.props().get(self.index);
+        let point = prop.location.to_point();
+        Some(ActionHoverInfo::new(&prop.prop.size, point))
+    }
+
+    fn fire_action(&mut self, widget: &Rc<RefCell<Widget>>) -> bool {
+        let menu = ItemActionMenu::new();
+
+        let mut at_least_one_action = false;
+        if GameState::has_prop_key(self.index) {
+            let cb = LockedPropAction::unlock_callback(self.index, UnlockKind::Key);
+            menu.borrow_mut().add_action("Use Key", cb);
+            at_least_one_action = true;
+        }
+
+        if Module::rules().locks.is_some() {
+            let cb = LockedPropAction::unlock_callback(self.index, UnlockKind::Pick);
+            menu.borrow_mut().add_action("Pick Lock", cb);
+            let cb = LockedPropAction::unlock_callback(self.index, UnlockKind::Bash);
+            menu.borrow_mut().add_action("Bash Open", cb);
+            at_least_one_action = true;
+        }
+
+        if !at_least_one_action {
+            return false;
+        }
+
+        let menu = Widget::with_defaults(menu);
+        menu.borrow_mut().state.set_modal(true);
+        menu.borrow_mut().state.modal_remove_on_click_outside = true;
+        let root = Widget::get_root(widget);
+        Widget::add_child_to(&root, menu);
+        true
+    }
+}
+
 struct LootPropAction {
     index: usize,
 }
@@ -465,6 +660,67 @@ impl TransitionAction {
 
         Some(cb_action)
     }
+
+    /// Whether all living party members are close enough to the transition
+    /// to leave the area together
+    fn is_party_gathered(&self) -> bool {
+        let max_dist = match Module::rules().transition_gather_distance {
+            None => return true,
+            Some(dist) => dist,
+        };
+
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        let transition = match area_state.get_transition_at(self.x, self.y) {
+            None => return true,
+            Some(transition) => transition,
+        };
+
+        GameState::party().iter().all(|member| {
+            let member = member.borrow();
+            member.actor.is_dead() || is_within(&*member, transition, max_dist)
+        })
+    }
+
+    fn show_gather_dialog(&self, widget: &Rc<RefCell<Widget>>) {
+        let (x, y) = (self.x, self.y);
+        let dialog = ModalDialog::confirm(
+            &tr!("transition.gather_party"),
+            Rc::new(move |_| gather_party(x, y)),
+        );
+        dialog
+            .borrow_mut()
+            .set_accept_text(&tr!("transition.gather_accept"));
+        modal_dialog::show(widget, dialog);
+    }
+}
+
+/// Moves all living party members towards the transition at `x`, `y`
+fn gather_party(x: i32, y: i32) {
+    let area_state = GameState::area_state();
+    let (from, size) = match area_state.borrow().get_transition_at(x, y) {
+        None => return,
+        Some(transition) => (transition.from, Rc::clone(&transition.size)),
+    };
+    let dist = Module::rules().max_transition_distance;
+
+    for member in GameState::party() {
+        if member.borrow().actor.is_dead() {
+            continue;
+        }
+
+        let dest = Destination {
+            x: from.x as f32,
+            y: from.y as f32,
+            w: size.width as f32,
+            h: size.height as f32,
+            parent_w: member.borrow().size.width as f32,
+            parent_h: member.borrow().size.height as f32,
+            dist,
+            max_path_len: None,
+        };
+        GameState::move_towards_dest(&member, &entities_to_ignore(), dest, None);
+    }
 }
 
 impl ActionKind for TransitionAction {
@@ -484,6 +740,11 @@ impl ActionKind for TransitionAction {
     }
 
     fn fire_action(&mut self, widget: &Rc<RefCell<Widget>>) -> bool {
+        if !self.is_party_gathered() {
+            self.show_gather_dialog(widget);
+            return true;
+        }
+
         trace!("Firing transition callback.");
         let time = Time {
             day: 0,
@@ -514,6 +775,11 @@ impl ActionKind for TransitionAction {
                 let root = Widget::get_root(widget);
                 root.borrow_mut().invalidate_children();
             }
+            ToKind::Stairs { ref id } => {
+                GameState::transition_to_level(id, time);
+                let root = Widget::get_root(widget);
+                root.borrow_mut().invalidate_children();
+            }
         }
         false
     }
@@ -615,6 +881,10 @@ impl ActionKind for AttackAction {
     fn ap(&self) -> i32 {
         self.ap
     }
+
+    fn order_kind(&self) -> Option<OrderKind> {
+        Some(OrderKind::Attack)
+    }
 }
 
 struct ActionCallback {
@@ -697,6 +967,10 @@ impl ActionKind for MoveThenAction {
     fn ap(&self) -> i32 {
         self.move_action.ap + self.cb_action.as_ref().map_or(0, |cb| cb.ap())
     }
+
+    fn order_kind(&self) -> Option<OrderKind> {
+        self.cb_action.as_ref().and_then(|cb| cb.order_kind())
+    }
 }
 struct MoveAction {
     selected: Vec<Rc<RefCell<EntityState>>>,
@@ -705,6 +979,7 @@ struct MoveAction {
 
     ap: i32,
     path: Vec<(f32, f32)>,
+    unreachable_path: Vec<(f32, f32)>,
 }
 
 fn entities_to_ignore() -> Vec<usize> {
@@ -754,33 +1029,41 @@ impl MoveAction {
             Some(path) => path,
         };
 
-        let (ap, path) = if !path.is_empty() {
+        let (ap, path, unreachable_path) = if !path.is_empty() {
             let pc = pc.borrow();
-            let cost_per_move = pc.actor.get_move_ap_cost(1) as i32;
-
             let total_ap = if !GameState::is_combat_active() {
                 std::i32::MAX
             } else {
                 pc.actor.ap() as i32
             };
-            let moves = cmp::min(path.len() as i32 - 1, total_ap / cost_per_move);
-            let ap = moves * cost_per_move;
 
-            (
-                ap,
-                path.iter()
-                    .skip(1)
-                    .take(moves as usize)
-                    .map(|p| {
-                        (
-                            p.x as f32 + (pc.size.width as f32 - 1.0) / 2.0,
-                            p.y as f32 + (pc.size.height as f32 - 1.0) / 2.0,
-                        )
-                    })
-                    .collect(),
-            )
+            let mut ap = 0;
+            let mut moves = 0;
+            for step in path.windows(2) {
+                let cost = if step[0].x != step[1].x && step[0].y != step[1].y {
+                    pc.actor.get_move_ap_cost(0, 1)
+                } else {
+                    pc.actor.get_move_ap_cost(1, 0)
+                } as i32;
+
+                if ap + cost > total_ap {
+                    break;
+                }
+                ap += cost;
+                moves += 1;
+            }
+
+            let mut points = path.iter().skip(1).map(|p| {
+                (
+                    p.x as f32 + (pc.size.width as f32 - 1.0) / 2.0,
+                    p.y as f32 + (pc.size.height as f32 - 1.0) / 2.0,
+                )
+            });
+
+            let reachable = points.by_ref().take(moves as usize).collect();
+            (ap, reachable, points.collect())
         } else {
-            (0, Vec::new())
+            (0, Vec::new(), Vec::new())
         };
 
         Some(MoveAction {
@@ -788,6 +1071,7 @@ impl MoveAction {
             dest,
             cb: None,
             path,
+            unreachable_path,
             ap,
         })
     }
@@ -843,12 +1127,22 @@ impl ActionKind for MoveAction {
             self.dest.x as i32 - entity.size.width / 2,
             self.dest.y as i32 - entity.size.height / 2,
         );
-        Some(ActionHoverInfo::with_path(entity, p, &self.path, self.ap))
+        Some(ActionHoverInfo::with_path(
+            entity,
+            p,
+            &self.path,
+            &self.unreachable_path,
+            self.ap,
+        ))
     }
 
     fn ap(&self) -> i32 {
         self.ap
     }
+
+    fn order_kind(&self) -> Option<OrderKind> {
+        Some(OrderKind::Move)
+    }
 }
 
 struct InvalidAction {}
@@ -22,6 +22,7 @@ use sulis_core::io::{event::ClickKind, GraphicsRenderer};
 use sulis_core::ui::{Widget, WidgetKind, WidgetState};
 use sulis_core::util::Point;
 use sulis_core::widgets::TextArea;
+use sulis_module::{Cover, DamageKind};
 use sulis_state::{ChangeListener, EntityState, GameState};
 
 const NAME: &str = "area_mouseover";
@@ -85,6 +86,8 @@ impl AreaMouseover {
                 state.add_text_arg("name", &actor.actor.name);
                 state.add_text_arg("cur_hp", &actor.hp().to_string());
                 state.add_text_arg("max_hp", &actor.stats.max_hp.to_string());
+                add_cover_text_arg(&entity.borrow(), state);
+                add_resistance_text_args(&entity.borrow(), state);
             }
             Kind::Prop(index) => {
                 let area_state = GameState::area_state();
@@ -104,6 +107,10 @@ impl AreaMouseover {
                 if let Some(ref text) = prop.prop.status_text {
                     state.add_text_arg("status", text);
                 }
+
+                if prop.is_locked() {
+                    state.add_text_arg("locked", "true");
+                }
             }
             Kind::Transition(ref name) => {
                 state.add_text_arg("name", name);
@@ -114,6 +121,52 @@ impl AreaMouseover {
     }
 }
 
+fn add_resistance_text_args(target: &EntityState, state: &mut WidgetState) {
+    let resistance = &target.actor.stats.resistance;
+    let mut immune = Vec::new();
+    let mut resistant = Vec::new();
+    let mut vulnerable = Vec::new();
+    for kind in DamageKind::iter() {
+        if resistance.is_immune(*kind) {
+            immune.push(kind.to_string());
+        } else if resistance.amount(*kind) > 0 {
+            resistant.push(kind.to_string());
+        } else if resistance.is_vulnerable(*kind) {
+            vulnerable.push(kind.to_string());
+        }
+    }
+
+    for (id, kinds) in [
+        ("immune", immune),
+        ("resistant", resistant),
+        ("vulnerable", vulnerable),
+    ] {
+        if !kinds.is_empty() {
+            state.add_text_arg(id, &kinds.join(", "));
+        }
+    }
+}
+
+fn add_cover_text_arg(target: &EntityState, state: &mut WidgetState) {
+    let selected = GameState::selected();
+    let attacker = match selected.first() {
+        None => return,
+        Some(entity) => entity.borrow(),
+    };
+
+    if !attacker.actor.stats.attack_is_ranged() || !attacker.is_hostile(target) {
+        return;
+    }
+
+    let area_state = GameState::area_state();
+    let cover = area_state.borrow().cover(&attacker, target);
+    match cover {
+        Cover::None => (),
+        Cover::Soft => state.add_text_arg("cover", "Soft Cover"),
+        Cover::Hard => state.add_text_arg("cover", "Hard Cover"),
+    }
+}
+
 impl WidgetKind for AreaMouseover {
     widget_kind!(NAME);
 
@@ -21,7 +21,8 @@ use crate::{action_kind, AreaMouseover};
 use sulis_core::image::Image;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::resource::{ResourceSet, Sprite};
-use sulis_core::ui::{animation_state, Cursor, LineRenderer, Theme, Widget};
+use sulis_core::tr;
+use sulis_core::ui::{animation_state, color, Color, Cursor, LineRenderer, Theme, Widget};
 use sulis_core::util::{Offset, Rect, Scale};
 use sulis_module::Module;
 use sulis_state::{area_feedback_text::Params, AreaState, EntityState, GameState};
@@ -45,9 +46,13 @@ pub struct AreaOverlayHandler {
     area_mouseover_widget: Option<Rc<RefCell<Widget>>>,
 
     path: Vec<(f32, f32)>,
+    unreachable_path: Vec<(f32, f32)>,
     path_point_image: Option<Rc<dyn Image>>,
     path_point_end_image: Option<Rc<dyn Image>>,
-    path_ap: Option<i32>,
+    path_unreachable_color: Color,
+
+    // the AP cost of the hovered move, and the AP remaining after it
+    path_ap: Option<(i32, i32)>,
 }
 
 impl AreaOverlayHandler {
@@ -195,14 +200,16 @@ impl AreaOverlayHandler {
                 };
                 self.hover_sprite = Some(hover_sprite);
                 self.path = info.path;
+                self.unreachable_path = info.unreachable_path;
                 match info.ap {
                     0 => self.path_ap = None,
-                    ap => self.path_ap = Some(info.total_ap - ap),
+                    ap => self.path_ap = Some((ap, info.total_ap - ap)),
                 }
             }
             None => {
                 self.hover_sprite = None;
                 self.path.clear();
+                self.unreachable_path.clear();
                 self.path_ap = None;
             }
         }
@@ -282,6 +289,7 @@ impl AreaOverlayHandler {
         self.hover_sprite = None;
         self.selection_box_start = None;
         self.path.clear();
+        self.unreachable_path.clear();
         self.path_ap = None;
         Cursor::set_cursor_state(animation_state::Kind::Normal);
         self.clear_area_mouseover();
@@ -299,53 +307,60 @@ impl AreaOverlayHandler {
         if let Some(image_id) = theme.custom.get("path_point_end_image") {
             self.path_point_end_image = ResourceSet::image(image_id);
         }
+
+        self.path_unreachable_color =
+            theme.get_custom_or_default("path_unreachable_color", color::RED);
     }
 
     pub fn hover_sprite(&self) -> Option<&HoverSprite> {
         self.hover_sprite.as_ref()
     }
 
-    pub fn get_path_draw_list(&self, offset: Offset, millis: u32) -> Option<DrawList> {
-        if !GameState::is_combat_active() {
-            return None;
-        }
+    /// Returns the draw lists for the path of the currently hovered move, if
+    /// any.  The path ends with a marker where the movement will end, with any
+    /// portion of the path that cannot be reached with the available AP drawn
+    /// in the unreachable color.
+    pub fn get_path_draw_lists(&self, offset: Offset, millis: u32) -> Vec<DrawList> {
+        let mut draw_lists = Vec::new();
 
         let image = match self.path_point_image {
-            None => return None,
+            None => return draw_lists,
             Some(ref image) => image,
         };
 
-        if self.path.is_empty() {
-            return None;
-        }
-
-        let mut draw_list = DrawList::empty_sprite();
-
-        for p in &self.path[0..self.path.len() - 1] {
+        let append = |draw_list: &mut DrawList, image: &Rc<dyn Image>, p: &(f32, f32)| {
             let rect = Rect {
                 x: p.0 - offset.x,
                 y: p.1 - offset.y,
                 w: 1.0,
                 h: 1.0,
             };
-            image.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+            image.append_to_draw_list(draw_list, &animation_state::NORMAL, rect, millis);
+        };
+
+        if let Some(last) = self.path.last() {
+            let mut draw_list = DrawList::empty_sprite();
+            for p in &self.path[0..self.path.len() - 1] {
+                append(&mut draw_list, image, p);
+            }
+
+            match self.path_point_end_image {
+                None => append(&mut draw_list, image, last),
+                Some(ref end_image) => append(&mut draw_list, end_image, last),
+            }
+            draw_lists.push(draw_list);
         }
 
-        match self.path_point_end_image {
-            None => (),
-            Some(ref image) => {
-                let last = self.path.last().unwrap();
-                let rect = Rect {
-                    x: last.0 - offset.x,
-                    y: last.1 - offset.y,
-                    w: 1.0,
-                    h: 1.0,
-                };
-                image.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+        if !self.unreachable_path.is_empty() {
+            let mut draw_list = DrawList::empty_sprite();
+            for p in self.unreachable_path.iter() {
+                append(&mut draw_list, image, p);
             }
+            draw_list.set_color(self.path_unreachable_color);
+            draw_lists.push(draw_list);
         }
 
-        Some(draw_list)
+        draw_lists
     }
 
     pub fn draw_top(
@@ -376,9 +391,14 @@ impl AreaOverlayHandler {
         if !GameState::is_combat_active() {
             return;
         }
-        if let Some(ap) = self.path_ap {
+        if let Some((cost, remaining)) = self.path_ap {
             let font_rend = LineRenderer::new(&params.font);
-            let text = format!("{} AP", Module::rules().format_ap(ap));
+            let rules = Module::rules();
+            let text = tr!(
+                "area.move_ap",
+                cost = rules.format_ap(cost),
+                remaining = rules.format_ap(remaining),
+            );
             let (x, y) = match &self.hover_sprite {
                 None => (0.0, 0.0),
                 Some(hover) => (
@@ -417,11 +437,12 @@ impl AreaOverlayHandler {
             if w < 1.0 && h < 1.0 {
                 fire_action = true;
             } else {
-                GameState::select_party_members(self.select_party_in_box(
-                    &widget.borrow(),
-                    scale,
-                    scroll,
-                ));
+                let party = self.select_party_in_box(&widget.borrow(), scale, scroll);
+                if Cursor::is_shift_down() {
+                    GameState::add_selected_party_members(party);
+                } else {
+                    GameState::select_party_members(party);
+                }
             }
             self.selection_box_start = None;
         } else {
@@ -435,6 +456,7 @@ impl AreaOverlayHandler {
         self.hover_sprite = None;
         self.selection_box_start = None;
         self.path.clear();
+        self.unreachable_path.clear();
         self.path_ap = None;
     }
 }
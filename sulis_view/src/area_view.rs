@@ -19,10 +19,11 @@ use std::cell::{RefCell, RefMut};
 use std::cmp::{self, Ordering};
 use std::mem;
 use std::rc::Rc;
+use std::str::FromStr;
 use std::time;
 
-use sulis_core::config::Config;
-use sulis_core::extern_image::ImageBuffer;
+use sulis_core::config::{Config, FeedbackChannel};
+use sulis_core::extern_image::{ImageBuffer, Rgba};
 use sulis_core::image::Image;
 use sulis_core::io::event::ClickKind;
 use sulis_core::io::*;
@@ -36,9 +37,14 @@ use sulis_module::{
     DamageKind, Module,
 };
 use sulis_state::{area_feedback_text, area_state::PCVisRedraw, RangeIndicatorImageSet};
-use sulis_state::{AreaDrawable, AreaState, EntityState, EntityTextureCache, GameState};
+use sulis_state::{
+    replay, AreaDrawable, AreaState, EntityState, EntityTextureCache, GameState, OrderKind,
+    ReplayCommand,
+};
 
-use crate::{action_kind, window_fade, AreaOverlayHandler, ScreenShake, WindowFade};
+use crate::{
+    action_kind, window_fade, AreaOverlayHandler, ScreenShake, SmoothScroll, WindowFade,
+};
 
 struct Range {
     min_x: i32,
@@ -52,6 +58,7 @@ const NAME: &str = "area";
 pub struct AreaView {
     scale: (f32, f32),
     cache_invalid: bool,
+    lighting_invalid: bool,
     layers: Vec<String>,
     entity_texture_cache: EntityTextureCache,
 
@@ -64,20 +71,21 @@ pub struct AreaView {
     feedback_text_params: area_feedback_text::Params,
     entity_see_through_alpha: f32,
 
-    scroll_target: Option<(f32, f32)>,
+    smooth_scroll: Option<SmoothScroll>,
     screen_shake: Option<ScreenShake>,
 
     overlay_handler: AreaOverlayHandler,
 }
 
-const TILE_CACHE_TEXTURE_SIZE: u32 = 2048;
-const TILE_SIZE: u32 = 16;
+pub(crate) const TILE_CACHE_TEXTURE_SIZE: u32 = 2048;
+pub(crate) const TILE_SIZE: u32 = 16;
 const TEX_COORDS: [f32; 8] = [0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0];
 
 const ENTITY_TEX_ID: &str = "__entities__";
-const VISIBILITY_TEX_ID: &str = "__visibility__";
-const BASE_LAYER_ID: &str = "__base_layer__";
-const AERIAL_LAYER_ID: &str = "__aerial_layer__";
+const LIGHTING_TEX_ID: &str = "__lighting__";
+pub(crate) const VISIBILITY_TEX_ID: &str = "__visibility__";
+pub(crate) const BASE_LAYER_ID: &str = "__base_layer__";
+pub(crate) const AERIAL_LAYER_ID: &str = "__aerial_layer__";
 
 impl AreaView {
     pub fn new(scroll: Scrollable) -> Rc<RefCell<AreaView>> {
@@ -85,6 +93,7 @@ impl AreaView {
             targeter_label: Widget::with_theme(Label::empty(), "targeter_label"),
             scale: (1.0, 1.0),
             cache_invalid: true,
+            lighting_invalid: true,
             entity_texture_cache: EntityTextureCache::new(
                 ENTITY_TEX_ID,
                 TILE_CACHE_TEXTURE_SIZE,
@@ -97,7 +106,7 @@ impl AreaView {
             active_entity: None,
             entity_see_through_alpha: 0.2,
             feedback_text_params: area_feedback_text::Params::default(),
-            scroll_target: None,
+            smooth_scroll: None,
             screen_shake: None,
             overlay_handler: AreaOverlayHandler::default(),
         }))
@@ -150,23 +159,62 @@ impl AreaView {
 
     pub fn get_scroll(&self) -> Scrollable { self.scroll }
 
-    pub fn screen_shake(&mut self) {
-        if !Config::crit_screen_shake() { return; }
+    pub fn scale(&self) -> (f32, f32) { self.scale }
 
-        self.screen_shake = Some(ScreenShake::new());
-    }
+    /// Carries out any pending requests from the current area's camera,
+    /// and keeps the followed entity, if any, centered
+    fn update_camera(&mut self, widget: &Rc<RefCell<Widget>>) {
+        let area_state = GameState::area_state();
+        let mut area_state = area_state.borrow_mut();
+        let (area_width, area_height) = (area_state.area.width, area_state.area.height);
 
-    pub fn delayed_scroll_to_point(
-        &mut self,
-        x: f32,
-        y: f32,
-        area_width: i32,
-        area_height: i32,
-        widget: &Widget,
-    ) {
-        let (x, y) = self.center_scroll_on_point(x, y, area_width, area_height, widget);
-        let (x, y) = self.scroll.bound(x, y);
-        self.scroll_target = Some((x, y));
+        if area_state.camera().take_shake() && Config::crit_screen_shake() {
+            self.screen_shake = Some(ScreenShake::new());
+        }
+
+        if let Some(target) = area_state.camera().take_scroll_to() {
+            let (x, y) = self.center_scroll_on_point(
+                target.x,
+                target.y,
+                area_width,
+                area_height,
+                &widget.borrow(),
+            );
+            let start = (self.scroll.x(), self.scroll.y());
+            let end = self.scroll.bound(x, y);
+            let scroll = match target.millis {
+                Some(millis) => SmoothScroll::new(start, end, millis),
+                None => {
+                    let dist = (end.0 - start.0).hypot(end.1 - start.1) * self.scale.0;
+                    SmoothScroll::with_speed(start, end, dist)
+                }
+            };
+            self.smooth_scroll = Some(scroll);
+        }
+
+        if self.smooth_scroll.is_some() {
+            return;
+        }
+
+        let entity = match area_state.camera().following() {
+            None => return,
+            Some(entity) => Rc::clone(entity),
+        };
+
+        if !entity.borrow().location.is_in(&area_state) || entity.borrow().actor.is_dead() {
+            area_state.camera().follow(None);
+            return;
+        }
+
+        let (x, y) = {
+            let entity = entity.borrow();
+            (
+                entity.location.x as f32 + entity.sub_pos.0 + entity.size.width as f32 / 2.0,
+                entity.location.y as f32 + entity.sub_pos.1 + entity.size.height as f32 / 2.0,
+            )
+        };
+        let (x, y) = self.center_scroll_on_point(x, y, area_width, area_height, &widget.borrow());
+        self.scroll.set(x, y);
     }
 
     fn get_cursor_pos(&self, widget: &Rc<RefCell<Widget>>) -> (f32, f32) {
@@ -360,6 +408,53 @@ impl AreaView {
         renderer.draw(draw_list);
     }
 
+    /// Draws the area's light map over everything drawn so far, darkening
+    /// and tinting it.  The light map texture is only rebuilt when it changes
+    fn draw_lighting(
+        &mut self,
+        renderer: &mut dyn GraphicsRenderer,
+        scale: Scale,
+        widget: &Widget,
+        state: &mut AreaState,
+    ) {
+        let map = match state.light_map() {
+            None => return,
+            Some(map) => map,
+        };
+
+        if map.take_redraw() || self.lighting_invalid {
+            let (width, height) = (map.width() as u32, map.height() as u32);
+            let image = ImageBuffer::from_fn(width, height, |x, y| {
+                let color = map.color(x as i32, y as i32);
+                Rgba([
+                    (color.r * 255.0) as u8,
+                    (color.g * 255.0) as u8,
+                    (color.b * 255.0) as u8,
+                    255,
+                ])
+            });
+            renderer.register_texture(
+                LIGHTING_TEX_ID,
+                image,
+                TextureMinFilter::Linear,
+                TextureMagFilter::Linear,
+            );
+            self.lighting_invalid = false;
+        }
+
+        let p = widget.state.inner_position();
+        let rect = Rect {
+            x: p.x as f32 - self.scroll.x(),
+            y: p.y as f32 - self.scroll.y(),
+            w: map.width() as f32,
+            h: map.height() as f32,
+        };
+        let mut draw_list = DrawList::from_texture_id(LIGHTING_TEX_ID, &TEX_COORDS, rect);
+        draw_list.set_scale(scale);
+        draw_list.set_blend_mode(BlendMode::Multiply);
+        renderer.draw(draw_list);
+    }
+
     fn draw_entities_props(
         &mut self,
         renderer: &mut dyn GraphicsRenderer,
@@ -373,6 +468,9 @@ impl AreaView {
         let mut to_draw: Vec<&dyn AreaDrawable> = Vec::new();
 
         for prop_state in state.props().iter() {
+            if !prop_state.is_drawn() {
+                continue;
+            }
             to_draw.push(prop_state);
         }
 
@@ -454,6 +552,10 @@ impl AreaView {
     }
 
     pub fn scroll(&mut self, delta_x: f32, delta_y: f32, millis: u32) {
+        // manual scrolling takes precedence over any camera movement
+        self.smooth_scroll = None;
+        GameState::area_state().borrow_mut().camera().follow(None);
+
         let speed = Config::scroll_speed() * millis as f32 / 33.0;
         let delta_x = speed * delta_x / self.scale.0;
         let delta_y = speed * delta_y / self.scale.1;
@@ -518,13 +620,16 @@ impl AreaView {
         // cause full area visibility redraw at the next step
         state.pc_vis_full_redraw();
         self.cache_invalid = false;
+        self.lighting_invalid = true;
     }
 }
 
 impl WidgetKind for AreaView {
     widget_kind!(NAME);
 
-    fn update(&mut self, _widget: &Rc<RefCell<Widget>>, millis: u32) {
+    fn update(&mut self, widget: &Rc<RefCell<Widget>>, millis: u32) {
+        self.update_camera(widget);
+
         if let Some(shake) = self.screen_shake.as_mut() {
             let result = shake.shake(millis);
 
@@ -538,33 +643,13 @@ impl WidgetKind for AreaView {
             }
         }
 
-        let (dest_x, dest_y) = match self.scroll_target {
-            None => return,
-            Some((x, y)) => (x, y),
-        };
-
-        let (sign_x, sign_y) = (
-            (self.scroll.x() - dest_x).signum(),
-            (self.scroll.y() - dest_y).signum(),
-        );
-
-        let speed = Config::scroll_speed() * 4.0 * millis as f32 / 33.3;
-        let (speed_x, speed_y) = (speed / self.scale.0, speed / self.scale.1);
+        if let Some(scroll) = self.smooth_scroll.as_mut() {
+            let ((x, y), done) = scroll.update(millis);
+            self.scroll.set(x, y);
 
-        let (cur_x, cur_y) = (self.scroll.x(), self.scroll.y());
-
-        let (dir_x, dir_y) = (cur_x - dest_x, cur_y - dest_y);
-
-        let mag = dir_y.hypot(dir_x);
-        let (delta_x, delta_y) = (dir_x * speed_x / mag, dir_y * speed_y / mag);
-
-        self.scroll.change(delta_x, delta_y);
-
-        if (self.scroll.x() - dest_x).signum() != sign_x
-            || (self.scroll.y() - dest_y).signum() != sign_y
-        {
-            self.scroll.set(dest_x, dest_y);
-            self.scroll_target = None;
+            if done {
+                self.smooth_scroll = None;
+            }
         }
     }
 
@@ -615,6 +700,25 @@ impl WidgetKind for AreaView {
             theme.get_custom_or_default("feedback_text_hit_color", color::RED);
         self.feedback_text_params.heal_color =
             theme.get_custom_or_default("feedback_text_heal_color", color::BLUE);
+        self.feedback_text_params.crit_scale =
+            theme.get_custom_or_default("feedback_text_crit_scale", 1.5);
+
+        for channel in FeedbackChannel::iter() {
+            let name = channel.to_str();
+            let params = &mut self.feedback_text_params.channels[channel.index()];
+
+            let color_id = format!("feedback_text_{name}_channel_color");
+            params.color = theme.custom.get(&color_id).and_then(|c| Color::from_str(c).ok());
+
+            let font_id = format!("feedback_text_{name}_channel_font");
+            params.font = theme.custom.get(&font_id).and_then(|font_id| {
+                let font = ResourceSet::font(font_id);
+                if font.is_none() {
+                    warn!("Invalid font specified for '{}' feedback text", name);
+                }
+                font
+            });
+        }
 
         for kind in DamageKind::iter() {
             let id = format!(
@@ -723,7 +827,8 @@ impl WidgetKind for AreaView {
         // way for the targeter to cause a layout of the label
         self.handle_targeter_label(&mut state);
 
-        if let Some(entity) = state.pop_scroll_to_callback() {
+        if let Some(entity) = state.camera().take_center_on() {
+            self.smooth_scroll = None;
             self.center_scroll_on(&entity, state.area.width, state.area.height, widget)
         }
 
@@ -801,7 +906,7 @@ impl WidgetKind for AreaView {
             renderer.draw(draw_list);
         }
 
-        if let Some(mut draw_list) = self.overlay_handler.get_path_draw_list(offset, millis) {
+        for mut draw_list in self.overlay_handler.get_path_draw_lists(offset, millis) {
             draw_list.set_scale(scale);
             renderer.draw(draw_list);
         }
@@ -851,6 +956,7 @@ impl WidgetKind for AreaView {
         };
         GameState::draw_above_entities(renderer, offset, scale, millis);
         self.draw_layer(renderer, scale, widget, AERIAL_LAYER_ID, area_color);
+        self.draw_lighting(renderer, scale, widget, &mut state);
 
         if let Some(hover) = self.overlay_handler.hover_sprite() {
             let rect = Rect {
@@ -868,6 +974,25 @@ impl WidgetKind for AreaView {
             renderer.draw(draw_list);
         }
 
+        for entity in GameState::selected().iter() {
+            for order in GameState::queued_orders(entity) {
+                let rect = Rect {
+                    x: (order.location.x + p.x) as f32 - self.scroll.x(),
+                    y: (order.location.y + p.y) as f32 - self.scroll.y(),
+                    w: order.size.width as f32,
+                    h: order.size.height as f32,
+                };
+
+                let mut draw_list = DrawList::from_sprite_f32(&order.size.cursor_sprite, rect);
+                match order.kind {
+                    OrderKind::Move => draw_list.set_color(color::YELLOW),
+                    OrderKind::Attack => draw_list.set_color(color::RED),
+                }
+                draw_list.set_scale(scale);
+                renderer.draw(draw_list);
+            }
+        }
+
         let targeter_tile = match self.targeter_tile {
             None => return,
             Some(ref tile) => Rc::clone(tile),
@@ -915,7 +1040,8 @@ impl WidgetKind for AreaView {
             return true;
         }
 
-        if kind == ClickKind::Tertiary {
+        // the recorded commands are in control during playback
+        if kind == ClickKind::Tertiary || replay::is_playing() {
             return true;
         }
 
@@ -923,9 +1049,17 @@ impl WidgetKind for AreaView {
 
         let targeter = area_state.borrow_mut().targeter();
         if let Some(targeter) = targeter {
+            let mut targeter = targeter.borrow_mut();
             match kind {
-                ClickKind::Primary => targeter.borrow_mut().on_activate(),
-                ClickKind::Secondary => targeter.borrow_mut().on_cancel(),
+                ClickKind::Primary => {
+                    let pos = targeter.cursor_pos();
+                    replay::record(ReplayCommand::Target { x: pos.x, y: pos.y });
+                    targeter.on_activate();
+                }
+                ClickKind::Secondary => {
+                    replay::record(ReplayCommand::CancelTarget);
+                    targeter.on_cancel();
+                }
                 _ => (),
             }
         } else {
@@ -938,8 +1072,7 @@ impl WidgetKind for AreaView {
             };
 
             if fire_action {
-                let mut action = action_kind::get_action(x, y);
-                let clear_mouse_state = action.fire_action(widget);
+                let clear_mouse_state = action_kind::fire_or_queue_action(x, y, widget);
 
                 if clear_mouse_state {
                     self.overlay_handler.clear_mouse_state();
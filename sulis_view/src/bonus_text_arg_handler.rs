@@ -135,6 +135,7 @@ fn add_bonus(
             &format!("resistance_{kind}").to_lowercase(),
             *amount,
         ),
+        Immunity(kind) => add(state, &format!("immunity_{kind}").to_lowercase(), true),
         Damage(damage) => {
             let index = *damage_index;
             if damage.max > 0 {
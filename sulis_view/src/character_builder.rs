@@ -32,7 +32,11 @@ use self::color_button::ColorButton;
 mod cosmetic_selector_pane;
 use self::cosmetic_selector_pane::CosmeticSelectorPane;
 
+mod level_up_attribute_pane;
+use self::level_up_attribute_pane::LevelUpAttributePane;
+
 mod level_up_builder;
+pub(crate) use self::level_up_builder::level_up;
 use self::level_up_builder::LevelUpBuilder;
 
 mod level_up_finish_pane;
@@ -316,8 +320,11 @@ impl BuilderSet for CharacterCreator {
             sex: builder.sex,
             attributes: builder.attributes.unwrap(),
             faction: Some(Faction::Friendly),
+            faction_id: None,
             conversation: None,
             images: builder.images.clone(),
+            sprite_images: HashMap::new(),
+            facing_sprite_images: HashMap::new(),
             hue: builder.hue,
             hair_color: builder.hair_color,
             skin_color: builder.skin_color,
@@ -327,6 +334,7 @@ impl BuilderSet for CharacterCreator {
             reward: None,
             abilities,
             ai: None,
+            ai_script: None,
         };
 
         if let Err(e) = write_character_to_file(&filename, &actor) {
@@ -26,7 +26,7 @@ use sulis_core::ui::{Callback, Color, Widget, WidgetKind};
 use sulis_core::util::{Offset, Point, Scale};
 use sulis_core::widgets::{Button, InputField, Label, ScrollDirection, ScrollPane};
 use sulis_module::actor::Sex;
-use sulis_module::{ImageLayer, ImageLayerSet, ItemState, Race, Slot};
+use sulis_module::{ImageLayer, ImageLayerSet, ItemState, LayerInsert, Race, Slot};
 
 use crate::character_builder::{BuilderPane, ColorButton};
 use crate::CharacterBuilder;
@@ -81,7 +81,7 @@ impl CosmeticSelectorPane {
             Ok(image) => image,
         };
 
-        let mut insert: HashMap<ImageLayer, Rc<dyn Image>> = HashMap::new();
+        let mut insert: HashMap<ImageLayer, Vec<LayerInsert>> = HashMap::new();
         for (slot, item) in self.items.iter() {
             if *slot == Slot::Head {
                 continue;
@@ -90,12 +90,12 @@ impl CosmeticSelectorPane {
                 continue;
             }
 
-            let iter = match &item.item.equippable {
+            let alternate = match &item.item.equippable {
                 Some(equip) => {
                     if equip.slot == *slot {
-                        item.image_iter()
+                        false
                     } else if equip.alternate_slot.unwrap() == *slot {
-                        item.alt_image_iter()
+                        true
                     } else {
                         unreachable!()
                     }
@@ -103,9 +103,9 @@ impl CosmeticSelectorPane {
                 None => unreachable!(),
             };
 
-            iter.for_each(|(layer, image)| {
-                insert.insert(*layer, Rc::clone(image));
-            });
+            for (layer, layer_insert) in item.layer_inserts(alternate, race) {
+                insert.entry(layer).or_default().push(layer_insert);
+            }
         }
 
         let images_list =
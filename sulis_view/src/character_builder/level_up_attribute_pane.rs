@@ -0,0 +1,159 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Label, Spinner};
+use sulis_module::{Attribute, AttributeList, Module};
+
+use crate::character_builder::BuilderPane;
+use crate::CharacterBuilder;
+
+pub const NAME: &str = "level_up_attribute_pane";
+
+pub struct LevelUpAttributePane {
+    base: AttributeList,
+    attrs: AttributeList,
+    points: i32,
+}
+
+impl LevelUpAttributePane {
+    pub fn new(base: AttributeList, points: i32) -> Rc<RefCell<LevelUpAttributePane>> {
+        Rc::new(RefCell::new(LevelUpAttributePane {
+            base,
+            attrs: base,
+            points,
+        }))
+    }
+
+    fn available(&self) -> i32 {
+        let mut spent = 0;
+        for attr in Attribute::iter() {
+            spent += self.attrs.get(*attr) as i32 - self.base.get(*attr) as i32;
+        }
+        self.points - spent
+    }
+
+    fn max_value(&self, attr: Attribute) -> i32 {
+        let base = self.base.get(attr) as i32;
+        std::cmp::max(base, Module::rules().builder_max_attribute)
+    }
+
+    fn is_complete(&self) -> bool {
+        let available = self.available();
+        if available == 0 {
+            return true;
+        }
+
+        // allow continuing if there is nowhere to spend the remaining points
+        Attribute::iter().all(|attr| self.attrs.get(*attr) as i32 >= self.max_value(*attr))
+    }
+
+    fn set_next_enabled(&self, widget: &Rc<RefCell<Widget>>) {
+        let (_, builder) = Widget::parent_mut::<CharacterBuilder>(widget);
+        builder
+            .next
+            .borrow_mut()
+            .state
+            .set_enabled(self.is_complete());
+    }
+}
+
+impl BuilderPane for LevelUpAttributePane {
+    fn on_selected(&mut self, builder: &mut CharacterBuilder, widget: Rc<RefCell<Widget>>) {
+        builder.attributes = None;
+        builder.prev.borrow_mut().state.set_enabled(true);
+        builder
+            .next
+            .borrow_mut()
+            .state
+            .set_enabled(self.is_complete());
+        widget.borrow_mut().invalidate_children();
+    }
+
+    fn next(&mut self, builder: &mut CharacterBuilder, widget: Rc<RefCell<Widget>>) {
+        builder.attributes = Some(self.attrs);
+        builder.next(&widget);
+    }
+
+    fn prev(&mut self, builder: &mut CharacterBuilder, widget: Rc<RefCell<Widget>>) {
+        self.attrs = self.base;
+        builder.prev(&widget);
+    }
+}
+
+impl WidgetKind for LevelUpAttributePane {
+    fn get_name(&self) -> &str {
+        NAME
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let mut children = Vec::new();
+
+        let title = Widget::with_theme(Label::empty(), "title");
+        children.push(title);
+
+        let available = self.available();
+        for attr in Attribute::iter() {
+            let value = self.attrs.get(*attr) as i32;
+            let min = self.base.get(*attr) as i32;
+            let max = if available > 0 {
+                self.max_value(*attr)
+            } else {
+                value
+            };
+
+            let spinner = Spinner::new(value, min, max);
+            let widget = Widget::with_theme(spinner, &format!("{}_spinner", attr.short_name()));
+            widget
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, kind| {
+                    let value = Widget::downcast_mut::<Spinner>(kind).value();
+
+                    let (parent, pane) = Widget::parent_mut::<LevelUpAttributePane>(widget);
+                    parent.borrow_mut().invalidate_children();
+                    pane.attrs.set(*attr, value as u8);
+                    pane.set_next_enabled(&parent);
+                })));
+            children.push(widget);
+
+            let label = Widget::with_theme(Label::empty(), &format!("{}_label", attr.short_name()));
+            children.push(label);
+        }
+
+        let points_label = Widget::with_theme(Label::empty(), "points_label");
+        children.push(points_label);
+
+        let amount_label = Widget::with_theme(Label::empty(), "amount_label");
+        amount_label
+            .borrow_mut()
+            .state
+            .add_text_arg("points", &available.to_string());
+        children.push(amount_label);
+
+        children
+    }
+}
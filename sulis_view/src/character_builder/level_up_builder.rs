@@ -18,10 +18,11 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::ui::Widget;
-use sulis_module::Actor;
-use sulis_state::EntityState;
+use sulis_module::{Ability, AttributeList, Class, Module};
+use sulis_state::{replay, EntityState, ReplayCommand};
 
 use crate::character_builder::*;
+use crate::RootView;
 
 pub struct LevelUpBuilder {
     pub pc: Rc<RefCell<EntityState>>,
@@ -61,6 +62,17 @@ impl BuilderSet for LevelUpBuilder {
 
         let actor = &self.pc.borrow().actor.actor;
         let level = actor.total_level + 1;
+
+        let points = Module::rules().level_up_attribute_points(level);
+        if points > 0 {
+            let pane = LevelUpAttributePane::new(actor.attributes, points);
+            let widget = Widget::with_defaults(pane.clone());
+            widget.borrow_mut().state.set_visible(false);
+
+            builder.builder_panes.push(pane);
+            children.push(widget);
+        }
+
         for (index, ability_list) in actor
             .base_class()
             .ability_choices(level)
@@ -89,24 +101,47 @@ impl BuilderSet for LevelUpBuilder {
         children
     }
 
-    fn finish(&self, builder: &mut CharacterBuilder, _widget: &Rc<RefCell<Widget>>) {
+    fn finish(&self, builder: &mut CharacterBuilder, widget: &Rc<RefCell<Widget>>) {
         let class = match builder.class {
             None => return,
             Some(ref class) => Rc::clone(class),
         };
 
-        let mut pc = self.pc.borrow_mut();
-        let state = &mut pc.actor;
-
-        let new_actor = Actor::from(
-            &state.actor,
-            Some((class, 1)),
-            state.xp(),
+        let attributes = builder
+            .attributes
+            .unwrap_or(self.pc.borrow().actor.actor.attributes);
+        level_up(
+            &self.pc,
+            class,
             builder.abilities.clone(),
-            Vec::new(),
-            state.actor.inventory.clone(),
+            attributes,
+            widget,
         );
-        state.replace_actor(new_actor);
-        state.init_day();
+    }
+}
+
+/// Levels up the `pc` with the specified choices, reporting any error to
+/// the player
+pub(crate) fn level_up(
+    pc: &Rc<RefCell<EntityState>>,
+    class: Rc<Class>,
+    abilities: Vec<Rc<Ability>>,
+    attributes: AttributeList,
+    widget: &Rc<RefCell<Widget>>,
+) {
+    replay::record(ReplayCommand::LevelUp {
+        entity: pc.borrow().index(),
+        class: class.id.to_string(),
+        abilities: abilities.iter().map(|a| a.id.to_string()).collect(),
+        attributes,
+    });
+
+    let mut pc = pc.borrow_mut();
+    let state = &mut pc.actor;
+    if let Err(e) = state.level_up(class, abilities, attributes) {
+        warn!("Unable to level up '{}'", state.actor.id);
+        warn!("{}", e);
+        let (_, root_view) = Widget::parent_mut::<RootView>(widget);
+        root_view.add_status_text(&tr!("status.level_up_error"));
     }
 }
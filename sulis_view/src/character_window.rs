@@ -28,8 +28,8 @@ use sulis_core::ui::{Callback, Widget, WidgetKind, WidgetState};
 use sulis_core::util::ExtInt;
 use sulis_core::widgets::{Button, ScrollDirection, ScrollPane, TextArea};
 use sulis_module::{
-    ActorBuilder, Attribute, DamageKind, InventoryBuilder, ItemListEntrySaveState, ItemSaveState,
-    Module, QuickSlot, Slot,
+    Ability, ActorBuilder, Attribute, DamageKind, InventoryBuilder, ItemListEntrySaveState,
+    ItemSaveState, Module, QuickSlot, Slot,
 };
 use sulis_state::{ActorState, ChangeListener, Effect, EntityState, GameState};
 
@@ -159,8 +159,12 @@ impl WidgetKind for CharacterWindow {
                     .borrow_mut()
                     .state
                     .set_visible(self.character.borrow_mut().actor.has_level_up());
-                let is_pc = Rc::ptr_eq(&self.character, &GameState::player());
-                create_details_text_box(&self.character.borrow().actor, is_pc)
+                let export = if self.character.borrow().is_party_member() {
+                    Some(Rc::clone(&self.character))
+                } else {
+                    None
+                };
+                create_details_text_box(&self.character.borrow().actor, export)
             }
             ActivePane::Ability { show_passives } => {
                 abilities_pane.borrow_mut().state.set_active(true);
@@ -213,7 +217,10 @@ pub fn get_inventory(pc: &ActorState, include_stash: bool) -> InventoryBuilder {
     InventoryBuilder::new(equipped, quick, coins, items)
 }
 
-fn export_character(pc: &ActorState) {
+/// Writes the specified character to the user characters directory, where it
+/// may be selected when starting a new campaign.  Equipment, along with the
+/// party stash and coins, is only written if `include_equipment` is true
+fn export_character(pc: &ActorState, include_equipment: bool) {
     let (filename, id) = match get_character_export_filename(&pc.actor.name) {
         Err(e) => {
             warn!("{}", e);
@@ -225,12 +232,13 @@ fn export_character(pc: &ActorState) {
 
     let portrait = pc.actor.portrait.as_ref().map(|i| i.id());
 
-    let abilities = pc
-        .actor
-        .abilities
-        .iter()
-        .map(|a| a.ability.id.to_string())
-        .collect();
+    // the builder lists each ability once per level, as in save states
+    let mut abilities = Vec::new();
+    for owned_ability in pc.actor.abilities.iter() {
+        for _ in 0..=owned_ability.level {
+            abilities.push(owned_ability.ability.id.to_string());
+        }
+    }
     let levels = pc
         .actor
         .levels
@@ -238,7 +246,11 @@ fn export_character(pc: &ActorState) {
         .map(|(class, level)| (class.id.to_string(), *level))
         .collect();
 
-    let inventory = get_inventory(pc, true);
+    let inventory = if include_equipment {
+        get_inventory(pc, true)
+    } else {
+        InventoryBuilder::default()
+    };
 
     let actor = ActorBuilder {
         id,
@@ -249,8 +261,11 @@ fn export_character(pc: &ActorState) {
         sex: Some(pc.actor.sex),
         attributes: pc.actor.attributes,
         faction: Some(pc.actor.faction()),
+        faction_id: pc.actor.faction_id.clone(),
         conversation: None,
         images: pc.actor.builder_images.clone(),
+        sprite_images: pc.actor.builder_sprite_images.clone(),
+        facing_sprite_images: pc.actor.builder_facing_sprite_images.clone(),
         hue: pc.actor.hue,
         hair_color: pc.actor.hair_color,
         skin_color: pc.actor.skin_color,
@@ -260,6 +275,7 @@ fn export_character(pc: &ActorState) {
         xp: Some(pc.xp()),
         reward: None,
         ai: None,
+        ai_script: None,
     };
 
     if let Err(e) = write_character_to_file(&filename, &actor) {
@@ -340,10 +356,7 @@ pub fn create_abilities_pane(pc: &ActorState, show_passive: bool) -> Rc<RefCell<
             .state
             .add_callback(Callback::new(Rc::new(move |_, _| {
                 add_ability_text_args(&mut details_ref.borrow_mut().state, &ability_ref);
-                details_ref
-                    .borrow_mut()
-                    .state
-                    .add_text_arg("owned_level", &(level + 1).to_string());
+                add_tier_text_args(&mut details_ref.borrow_mut().state, &ability_ref, level);
                 details_widget_ref.borrow_mut().invalidate_layout();
             })));
 
@@ -355,6 +368,16 @@ pub fn create_abilities_pane(pc: &ActorState, show_passive: bool) -> Rc<RefCell<
     abilities
 }
 
+fn add_tier_text_args(state: &mut WidgetState, ability: &Ability, level: u32) {
+    state.add_text_arg("owned_level", &(level + 1).to_string());
+    state.add_text_arg("max_level", &(ability.upgrades.len() + 1).to_string());
+
+    if let Some(upgrade) = ability.upgrades.get(level as usize) {
+        state.add_text_arg("next_level", &(level + 2).to_string());
+        state.add_text_arg("next_upgrade", &upgrade.description);
+    }
+}
+
 pub fn create_effects_pane(pc: &mut ActorState) -> Rc<RefCell<Widget>> {
     let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
     let effects = Widget::with_theme(scrollpane.clone(), "effects");
@@ -420,20 +443,27 @@ fn add_if_nonzero(state: &mut WidgetState, index: usize, name: &str, value: f32)
     state.add_text_arg(&format!("{index}_{name}"), &value.to_string());
 }
 
-pub fn create_details_text_box(pc: &ActorState, is_pc: bool) -> Rc<RefCell<Widget>> {
+/// Creates the character details pane.  If `export` is specified, buttons to
+/// export that character, with or without equipment, are added
+pub fn create_details_text_box(
+    pc: &ActorState,
+    export: Option<Rc<RefCell<EntityState>>>,
+) -> Rc<RefCell<Widget>> {
     let details = Widget::with_theme(TextArea::empty(), "details");
     {
-        if is_pc {
-            let export = Widget::with_theme(Button::empty(), "export");
-            export
-                .borrow_mut()
-                .state
-                .add_callback(Callback::new(Rc::new(|widget, _| {
-                    let player = GameState::player();
-                    widget.borrow_mut().state.set_enabled(false);
-                    export_character(&player.borrow().actor);
-                })));
-            Widget::add_child_to(&details, export);
+        if let Some(character) = export {
+            for (theme, include_equipment) in [("export", true), ("export_no_equipment", false)] {
+                let character = Rc::clone(&character);
+                let button = Widget::with_theme(Button::empty(), theme);
+                button
+                    .borrow_mut()
+                    .state
+                    .add_callback(Callback::new(Rc::new(move |widget, _| {
+                        widget.borrow_mut().state.set_enabled(false);
+                        export_character(&character.borrow().actor, include_equipment);
+                    })));
+                Widget::add_child_to(&details, button);
+            }
         }
 
         let rules = Module::rules();
@@ -562,6 +592,11 @@ pub fn create_details_text_box(pc: &ActorState, is_pc: bool) -> Rc<RefCell<Widge
         }
 
         for kind in DamageKind::iter() {
+            if stats.resistance.is_immune(*kind) {
+                state.add_text_arg(&format!("immunity_{kind}").to_lowercase(), "true");
+                continue;
+            }
+
             let amount = stats.resistance.amount(*kind);
             if amount == 0 {
                 continue;
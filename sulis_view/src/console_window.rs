@@ -25,10 +25,20 @@ use sulis_state::{GameState, ScriptState};
 
 pub const NAME: &str = "console_window";
 
+const OUTPUT_LINES: usize = 5;
+
+/// The number of lines of output kept for scrolling back through
+const OUTPUT_HISTORY: usize = 500;
+
 pub struct ConsoleWindow {
     input: Rc<RefCell<InputField>>,
     input_widget: Rc<RefCell<Widget>>,
     output: Rc<RefCell<Widget>>,
+    output_lines: Vec<Rc<RefCell<Widget>>>,
+    output_text: Vec<String>,
+
+    /// The number of lines scrolled back from the most recent output
+    output_scroll: usize,
     history: Vec<String>,
     history_index: usize,
     script_state: ScriptState,
@@ -37,10 +47,22 @@ pub struct ConsoleWindow {
 impl ConsoleWindow {
     pub fn new() -> Rc<RefCell<ConsoleWindow>> {
         let input = InputField::new("");
+
+        let output = Widget::empty("output");
+        let mut output_lines = Vec::new();
+        for _ in 0..OUTPUT_LINES {
+            let line = Widget::with_theme(Label::empty(), "line");
+            Widget::add_child_to(&output, Rc::clone(&line));
+            output_lines.push(line);
+        }
+
         Rc::new(RefCell::new(ConsoleWindow {
             input: Rc::clone(&input),
             input_widget: Widget::with_theme(input, "input"),
-            output: Widget::with_theme(Label::empty(), "output"),
+            output,
+            output_lines,
+            output_text: Vec::new(),
+            output_scroll: 0,
             history: Vec::new(),
             history_index: 0,
             script_state: ScriptState::default(),
@@ -57,14 +79,89 @@ impl ConsoleWindow {
 
         let party = GameState::party();
 
-        let result = match self.script_state.console(script, &party) {
+        let (printed, result) = self.script_state.console(script, &party);
+        let result = match result {
             Ok(result) => result,
             Err(rlua::Error::FromLuaConversionError { .. }) => "Success".to_string(),
             Err(e) => format!("{e}"),
         };
 
+        for line in printed {
+            info!("Console print: {}", line);
+            self.output_text.push(line);
+        }
+
         info!("Console result: {}", result);
-        self.output.borrow_mut().state.text = result;
+        self.output_text.push(result);
+        self.output_scroll = 0;
+        self.update_output();
+    }
+
+    /// Completes the identifier at the end of the input field's text with the
+    /// longest prefix shared by all matching names.  If there is more than one
+    /// match, the matches are listed in the output.
+    pub fn complete(&mut self, field: &mut InputField, widget: &Rc<RefCell<Widget>>) {
+        let text = field.text();
+        let completions = match self.script_state.console_completions(&text) {
+            Ok(completions) => completions,
+            Err(e) => {
+                warn!("Error finding console completions: {}", e);
+                return;
+            }
+        };
+
+        let names = completions.names;
+        let first = match names.first() {
+            None => return,
+            Some(first) => first,
+        };
+
+        let mut prefix_len = first.len();
+        for name in names.iter().skip(1) {
+            prefix_len = first
+                .char_indices()
+                .zip(name.chars())
+                .take_while(|((_, a), b)| a == b)
+                .map(|((i, a), _)| i + a.len_utf8())
+                .last()
+                .unwrap_or(0)
+                .min(prefix_len);
+        }
+
+        let completed = format!("{}{}", &text[..completions.start], &first[..prefix_len]);
+        field.set_text(&completed, widget);
+
+        if names.len() > 1 {
+            self.output_text.push(names.join("  "));
+            self.output_scroll = 0;
+            self.update_output();
+        }
+    }
+
+    /// Scrolls the output back through older lines by `lines`, or forward
+    /// towards the most recent output if `lines` is negative
+    pub fn scroll_output(&mut self, lines: i32) {
+        let max_scroll = self.output_text.len().saturating_sub(OUTPUT_LINES);
+        let scroll = self.output_scroll as i32 + lines;
+        self.output_scroll = (scroll.max(0) as usize).min(max_scroll);
+        self.update_output();
+    }
+
+    fn update_output(&mut self) {
+        if self.output_text.len() > OUTPUT_HISTORY {
+            let excess = self.output_text.len() - OUTPUT_HISTORY;
+            self.output_text.drain(0..excess);
+        }
+
+        let end = self.output_text.len() - self.output_scroll;
+        let start = end.saturating_sub(OUTPUT_LINES);
+        for (index, line) in self.output_lines.iter().enumerate() {
+            let text = self.output_text[start..end]
+                .get(index)
+                .cloned()
+                .unwrap_or_default();
+            line.borrow_mut().state.text = text;
+        }
     }
 
     pub fn current_history_text(&self) -> String {
@@ -108,6 +205,10 @@ impl WidgetKind for ConsoleWindow {
                             field.set_text(&console.current_history_text(), widget);
                         }
                     }
+                    InputActionKind::ConsoleComplete => console.complete(field, widget),
+                    // page up / down and the mouse wheel scroll the output
+                    InputActionKind::ZoomIn => console.scroll_output(1),
+                    InputActionKind::ZoomOut => console.scroll_output(-1),
                     _ => (),
                 }
             }));
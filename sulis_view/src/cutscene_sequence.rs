@@ -0,0 +1,169 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::io::InputActionKind;
+use sulis_core::ui::{Widget, WidgetKind};
+use sulis_module::on_trigger::CutsceneStep;
+use sulis_state::{area_feedback_text::ColorKind, AreaFeedbackText, EntityState, GameState};
+
+use crate::trigger_activator::scroll_view;
+use crate::{window_fade, WindowFade};
+
+pub const NAME: &str = "cutscene_sequence";
+
+/// Plays a scripted sequence of cutscene steps, one after another.  This widget
+/// is modal, locking player input for as long as the sequence plays.
+pub struct CutsceneSequence {
+    steps: Vec<CutsceneStep>,
+    index: usize,
+    step_started: bool,
+    remaining_millis: u32,
+}
+
+impl CutsceneSequence {
+    pub fn new(steps: Vec<CutsceneStep>) -> Rc<RefCell<CutsceneSequence>> {
+        Rc::new(RefCell::new(CutsceneSequence {
+            steps,
+            index: 0,
+            step_started: false,
+            remaining_millis: 0,
+        }))
+    }
+
+    fn start_step(&mut self, widget: &Rc<RefCell<Widget>>) {
+        use CutsceneStep::*;
+        match self.steps[self.index] {
+            MoveEntity { entity, x, y } => {
+                let entity = match get_entity(entity) {
+                    None => return,
+                    Some(entity) => entity,
+                };
+                let dest = GameState::get_point_dest(&entity.borrow(), x as f32, y as f32);
+                if !GameState::move_towards_dest(&entity, &[], dest, None) {
+                    warn!("Unable to move entity to {},{} in cutscene", x, y);
+                }
+            }
+            ShowText {
+                entity,
+                ref text,
+                millis,
+            } => {
+                self.remaining_millis = millis;
+                let entity = match get_entity(entity) {
+                    None => return,
+                    Some(entity) => entity,
+                };
+                let area = GameState::area_state();
+                let mut feedback = AreaFeedbackText::with_target(&entity.borrow(), &area.borrow());
+                feedback.add_entry(text.to_string(), ColorKind::Info);
+                area.borrow_mut().add_feedback_text(feedback);
+            }
+            PanTo { x, y } => scroll_view(x, y),
+            FadeOut => {
+                let fade = Widget::with_defaults(WindowFade::new(window_fade::Mode::OutIn));
+                Widget::add_child_to(widget, fade);
+            }
+            Wait { millis } => self.remaining_millis = millis,
+        }
+    }
+
+    fn is_step_complete(&mut self, widget: &Rc<RefCell<Widget>>, millis: u32) -> bool {
+        use CutsceneStep::*;
+        match self.steps[self.index] {
+            MoveEntity { entity, .. } => match get_entity(entity) {
+                None => true,
+                Some(entity) => !GameState::has_blocking_animations(&entity),
+            },
+            ShowText { .. } | Wait { .. } => {
+                if millis >= self.remaining_millis {
+                    true
+                } else {
+                    self.remaining_millis -= millis;
+                    false
+                }
+            }
+            PanTo { .. } => true,
+            FadeOut => widget.borrow().children.is_empty(),
+        }
+    }
+
+    /// Skips the remainder of the sequence, moving any entities with pending
+    /// movement directly to their destinations
+    fn skip(&mut self, widget: &Rc<RefCell<Widget>>) {
+        info!("Skipping cutscene sequence at step {}", self.index);
+        let area = GameState::area_state();
+        for step in self.steps.iter().skip(self.index) {
+            let (entity, x, y) = match step {
+                CutsceneStep::MoveEntity { entity, x, y } => (*entity, *x, *y),
+                _ => continue,
+            };
+
+            let entity = match get_entity(entity) {
+                None => continue,
+                Some(entity) => entity,
+            };
+            GameState::remove_blocking_animations(&entity);
+            area.borrow_mut().move_entity(&entity, x, y, 0, 0);
+        }
+
+        self.index = self.steps.len();
+        widget.borrow_mut().mark_for_removal();
+    }
+}
+
+fn get_entity(index: usize) -> Option<Rc<RefCell<EntityState>>> {
+    let entity = GameState::turn_manager().borrow().entity_checked(index);
+    if entity.is_none() {
+        warn!("Invalid entity {} for cutscene step", index);
+    }
+    entity
+}
+
+impl WidgetKind for CutsceneSequence {
+    widget_kind!(NAME);
+
+    fn update(&mut self, widget: &Rc<RefCell<Widget>>, millis: u32) {
+        if self.index >= self.steps.len() {
+            widget.borrow_mut().mark_for_removal();
+            return;
+        }
+
+        if !self.step_started {
+            self.start_step(widget);
+            self.step_started = true;
+            return;
+        }
+
+        if self.is_step_complete(widget, millis) {
+            self.index += 1;
+            self.step_started = false;
+        }
+    }
+
+    fn on_key_press(&mut self, widget: &Rc<RefCell<Widget>>, key: InputActionKind) -> bool {
+        match key {
+            InputActionKind::Back => {
+                self.skip(widget);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -23,8 +23,8 @@ use sulis_core::ui::{theme, Widget, WidgetKind};
 use sulis_core::widgets::TextArea;
 use sulis_module::{conversation::Response, Conversation, OnTrigger};
 use sulis_state::{
-    area_feedback_text::ColorKind, script::entity_with_id, AreaFeedbackText, ChangeListener,
-    EntityState, GameState,
+    area_feedback_text::ColorKind, replay, script::entity_with_id, AreaFeedbackText,
+    ChangeListener, EntityState, GameState, ReplayCommand,
 };
 
 use crate::trigger_activator::{activate, is_match, scroll_view};
@@ -39,6 +39,7 @@ pub struct DialogWindow {
     cur_node: String,
 
     node: Rc<RefCell<TextArea>>,
+    responses: Vec<Rc<RefCell<Widget>>>,
 }
 
 impl DialogWindow {
@@ -55,6 +56,7 @@ impl DialogWindow {
             convo,
             node: TextArea::empty(),
             cur_node,
+            responses: Vec::new(),
         }))
     }
 }
@@ -122,6 +124,7 @@ impl WidgetKind for DialogWindow {
             &self.entity,
         );
 
+        self.responses.clear();
         let responses_widget = Widget::empty("responses");
         {
             for response in responses {
@@ -129,9 +132,11 @@ impl WidgetKind for DialogWindow {
                     continue;
                 }
 
-                let response_button = ResponseButton::new(&self.convo, response, &self.pc);
+                let index = self.responses.len();
+                let response_button = ResponseButton::new(&self.convo, response, &self.pc, index);
                 let widget = Widget::with_defaults(response_button);
-                Widget::add_child_to(&responses_widget, widget);
+                Widget::add_child_to(&responses_widget, Rc::clone(&widget));
+                self.responses.push(widget);
             }
         }
 
@@ -145,6 +150,7 @@ struct ResponseButton {
     on_select: Vec<OnTrigger>,
     pc: Rc<RefCell<EntityState>>,
     convo: Rc<Conversation>,
+    index: usize,
 }
 
 impl ResponseButton {
@@ -152,6 +158,7 @@ impl ResponseButton {
         convo: &Rc<Conversation>,
         response: &Response,
         pc: &Rc<RefCell<EntityState>>,
+        index: usize,
     ) -> Rc<RefCell<ResponseButton>> {
         Rc::new(RefCell::new(ResponseButton {
             text: response.text.to_string(),
@@ -159,9 +166,33 @@ impl ResponseButton {
             on_select: response.on_select.clone(),
             pc: Rc::clone(pc),
             convo: Rc::clone(convo),
+            index,
         }))
     }
 
+    fn select(&self, widget: &Rc<RefCell<Widget>>) {
+        replay::record(ReplayCommand::Response { index: self.index });
+
+        let (parent, window) = Widget::parent_mut::<DialogWindow>(widget);
+
+        activate(widget, &self.on_select, &window.pc, &window.entity);
+
+        let (_, view) = Widget::parent_mut::<RootView>(&parent);
+        let (area, _) = view.area_view();
+
+        match self.to {
+            None => {
+                parent.borrow_mut().mark_for_removal();
+                area.borrow_mut().set_active_entity(None);
+            }
+            Some(ref to) => {
+                self.check_switch_speaker(to, &area);
+                window.cur_node = to.to_string();
+                parent.borrow_mut().invalidate_children()
+            }
+        }
+    }
+
     fn check_switch_speaker(&self, node: &str, area: &Rc<RefCell<AreaView>>) {
         let speaker = match self.convo.switch_speaker(node) {
             None => return,
@@ -212,28 +243,32 @@ impl WidgetKind for ResponseButton {
 
     fn on_mouse_release(&mut self, widget: &Rc<RefCell<Widget>>, kind: event::ClickKind) -> bool {
         self.super_on_mouse_release(widget, kind);
+        self.select(widget);
+        true
+    }
+}
 
-        let (parent, window) = Widget::parent_mut::<DialogWindow>(widget);
-
-        activate(widget, &self.on_select, &window.pc, &window.entity);
-
-        let (_, view) = Widget::parent_mut::<RootView>(&parent);
-        let (area, _) = view.area_view();
-
-        match self.to {
-            None => {
-                parent.borrow_mut().mark_for_removal();
-                area.borrow_mut().set_active_entity(None);
-            }
-            Some(ref to) => {
-                self.check_switch_speaker(to, &area);
-                window.cur_node = to.to_string();
-                parent.borrow_mut().invalidate_children()
-            }
+/// Selects the response at `index`, counting only the viewable responses, in
+/// the conversation open under `root`
+pub(crate) fn select_response(root: &Rc<RefCell<Widget>>, index: usize) {
+    let window = match Widget::get_child_with_name(root, NAME) {
+        None => {
+            warn!("Replay response with no open conversation");
+            return;
+        }
+        Some(window) => window,
+    };
+
+    let window = Widget::kind_mut::<DialogWindow>(&window);
+    let button = match window.responses.get(index) {
+        None => {
+            warn!("Replay references invalid response {}", index);
+            return;
         }
+        Some(button) => Rc::clone(button),
+    };
 
-        true
-    }
+    Widget::kind_mut::<ResponseButton>(&button).select(&button);
 }
 
 pub fn show_convo(
@@ -263,7 +298,7 @@ pub fn show_convo(
             let loc = &target.borrow().location;
             (loc.x, loc.y)
         };
-        scroll_view(&root, x, y);
+        scroll_view(x, y);
         Widget::add_child_to(&root, window);
     }
 }
@@ -21,7 +21,7 @@ use std::rc::Rc;
 use sulis_core::ui::{Callback, Cursor, Widget, WidgetKind};
 use sulis_core::util::Size;
 use sulis_core::widgets::{Button, Label};
-use sulis_state::{ChangeListener, GameState};
+use sulis_state::{ChangeListener, FormationLayout, GameState};
 
 pub const NAME: &str = "formation_window";
 
@@ -198,10 +198,30 @@ impl WidgetKind for FormationWindow {
 
         let mut children = vec![close];
 
-        self.entries.clear();
-        let party = GameState::party();
         let formation = GameState::party_formation();
         let formation = formation.borrow();
+
+        for layout in FormationLayout::iter() {
+            let layout = *layout;
+            let theme = format!("{layout:?}").to_lowercase();
+            let button = Widget::with_theme(Button::empty(), &theme);
+            button
+                .borrow_mut()
+                .state
+                .set_active(formation.layout() == layout);
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, _) = Widget::parent::<FormationWindow>(widget);
+                    GameState::set_formation(layout);
+                    parent.borrow_mut().invalidate_children();
+                })));
+            children.push(button);
+        }
+
+        self.entries.clear();
+        let party = GameState::party();
         for (index, (x, y)) in formation.positions_iter().enumerate() {
             let button = Widget::with_theme(Button::empty(), "position");
             button
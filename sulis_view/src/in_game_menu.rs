@@ -20,9 +20,9 @@ use std::rc::Rc;
 
 use sulis_core::ui::{Callback, Widget, WidgetKind};
 use sulis_core::widgets::{Button, ConfirmationWindow};
-use sulis_state::GameState;
+use sulis_state::{script::script_cache, GameState};
 
-use crate::{LoadWindow, RootView};
+use crate::{main_menu::Options, AchievementsWindow, LoadWindow, RootView};
 
 const NAME: &str = "in_game_menu";
 
@@ -64,9 +64,11 @@ impl WidgetKind for InGameMenu {
                 let (_, view) = Widget::parent_mut::<RootView>(&parent);
                 view.save();
             })));
-        save.borrow_mut()
-            .state
-            .set_enabled(!GameState::is_combat_active());
+        save.borrow_mut().state.set_enabled(
+            !GameState::is_combat_active()
+                && !GameState::is_ironman()
+                && !script_cache::has_suspended_coroutines(),
+        );
 
         let load = Widget::with_theme(Button::empty(), "load");
         load.borrow_mut()
@@ -81,6 +83,40 @@ impl WidgetKind for InGameMenu {
                 Widget::add_child_to(&root, window);
             })));
 
+        let achievements = Widget::with_theme(Button::empty(), "achievements");
+        achievements
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<InGameMenu>(widget);
+                parent.borrow_mut().mark_for_removal();
+
+                let root = Widget::get_root(widget);
+                let window = Widget::with_defaults(AchievementsWindow::new());
+                window.borrow_mut().state.set_modal(true);
+                Widget::add_child_to(&root, window);
+            })));
+
+        let options = Widget::with_theme(Button::empty(), "options");
+        options
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<InGameMenu>(widget);
+                parent.borrow_mut().mark_for_removal();
+
+                let (root, view) = Widget::parent::<RootView>(&parent);
+                let configs = view.display_configurations();
+                let audio = sulis_core::io::audio::get_audio_devices()
+                    .iter()
+                    .map(|d| d.name.to_string())
+                    .collect();
+
+                let window = Widget::with_defaults(Options::new(configs, audio, false));
+                window.borrow_mut().state.set_modal(true);
+                Widget::add_child_to(&root, window);
+            })));
+
         let menu = Widget::with_theme(Button::empty(), "menu");
         let menu_cb = self.menu_callback.clone();
         menu.borrow_mut()
@@ -111,6 +147,6 @@ impl WidgetKind for InGameMenu {
                 Widget::add_child_to(&root, window);
             })));
 
-        vec![back, save, load, menu, exit]
+        vec![back, save, load, options, achievements, menu, exit]
     }
 }
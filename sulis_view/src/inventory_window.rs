@@ -21,11 +21,11 @@ use std::time;
 
 use sulis_core::ui::{Callback, Widget, WidgetKind};
 use sulis_core::util;
-use sulis_core::widgets::{Button, Label};
-use sulis_module::{QuickSlot, Slot};
+use sulis_core::widgets::{Button, Label, ProgressBar};
+use sulis_module::{item::format_item_weight, QuickSlot, Slot};
 use sulis_state::{script::ScriptItemKind, ChangeListener, EntityState, GameState};
 
-use crate::{item_callback_handler::*, item_list_pane::Filter, ItemButton, ItemListPane};
+use crate::{item_callback_handler::*, item_list_pane::Filter, ItemButton, ItemListPane, RootView};
 
 pub const NAME: &str = "inventory_window";
 
@@ -92,6 +92,19 @@ impl WidgetKind for InventoryWindow {
                 parent.borrow_mut().mark_for_removal();
             })));
 
+        let storage = Widget::with_theme(Button::empty(), "storage");
+        storage
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (root, view) = Widget::parent_mut::<RootView>(widget);
+                view.set_storage_window(&root, true);
+            })));
+        storage
+            .borrow_mut()
+            .state
+            .set_enabled(!GameState::is_combat_active());
+
         let swap_weapons = Widget::with_theme(Button::empty(), "swap_weapons");
         let entity_ref = Rc::clone(&self.entity);
         swap_weapons
@@ -172,11 +185,30 @@ impl WidgetKind for InventoryWindow {
 
         let stash_title = Widget::with_theme(Label::empty(), "stash_title");
 
+        let mut children = vec![close, storage, equipped_area, item_list_pane, stash_title];
+
+        if let Some(max_weight) = actor.stats.max_carry_weight() {
+            let weight = actor.stats.carried_weight;
+            let frac = (weight as f32 / max_weight.max(1) as f32).min(1.0);
+            let theme = if actor.stats.encumbered {
+                "encumbered_weight_bar"
+            } else {
+                "weight_bar"
+            };
+            let weight_bar = Widget::with_theme(ProgressBar::new(frac), theme);
+            {
+                let state = &mut weight_bar.borrow_mut().state;
+                state.add_text_arg("weight", &format_item_weight(weight));
+                state.add_text_arg("max_weight", &format_item_weight(max_weight));
+            }
+            children.push(weight_bar);
+        }
+
         trace!(
             "Inventory window creation time: {}",
             util::format_elapsed_secs(start_time.elapsed())
         );
 
-        vec![close, equipped_area, item_list_pane, stash_title]
+        children
     }
 }
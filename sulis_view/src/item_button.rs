@@ -19,19 +19,22 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::bonus_text_arg_handler::{
-    add_attack_text_args, add_bonus_text_args, add_prereq_text_args,
+    add_attack_text_args, add_bonus_text_args, add_prereq_text_args, format_bonus_or_penalty,
 };
-use crate::item_callback_handler::sell_item_cb;
-use crate::{ItemActionMenu, MerchantWindow, RootView};
+use crate::item_callback_handler::{
+    self, repair_item_cb, sell_item_cb, sell_stack_cb, store_item_cb,
+};
+use crate::{inventory_window, ItemActionMenu, MerchantWindow, PropWindow, RootView};
+use sulis_core::config::Config;
 use sulis_core::io::{event, keyboard_event::Key};
-use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::ui::{Callback, Cursor, Widget, WidgetKind, WidgetState};
 use sulis_core::widgets::{Label, TextArea};
 use sulis_module::{
     ability,
     item::{format_item_value, format_item_weight},
     Module,
 };
-use sulis_module::{ItemState, QuickSlot, Slot};
+use sulis_module::{ItemState, QuickSlot, Slot, StatList};
 use sulis_state::{inventory::has_proficiency, EntityState, GameState};
 
 enum Kind {
@@ -46,6 +49,9 @@ enum Kind {
     Inventory {
         item_index: usize,
     },
+    Storage {
+        item_index: usize,
+    },
     Equipped {
         player: Rc<RefCell<EntityState>>,
         slot: Slot,
@@ -71,10 +77,18 @@ pub struct ItemButton {
     keyboard_shortcut: Option<Key>,
 
     item_window: Option<Rc<RefCell<Widget>>>,
+    compare_window: Option<Rc<RefCell<Widget>>>,
+
+    drag_distance: f32,
+    drag_icon: Option<Rc<RefCell<Widget>>>,
 }
 
 const ITEM_BUTTON_NAME: &str = "item_button";
 
+/// The distance the mouse must be dragged before an item starts being
+/// dragged, so that small movements during a click are ignored
+const DRAG_THRESHOLD: f32 = 2.0;
+
 impl ItemButton {
     pub fn inventory(
         item: &ItemState,
@@ -84,6 +98,14 @@ impl ItemButton {
         ItemButton::new(item, quantity, Kind::Inventory { item_index })
     }
 
+    pub fn storage(
+        item: &ItemState,
+        quantity: u32,
+        item_index: usize,
+    ) -> Rc<RefCell<ItemButton>> {
+        ItemButton::new(item, quantity, Kind::Storage { item_index })
+    }
+
     pub fn equipped(
         player: &Rc<RefCell<EntityState>>,
         item: &ItemState,
@@ -146,7 +168,10 @@ impl ItemButton {
             kind,
             actions: Vec::new(),
             item_window: None,
+            compare_window: None,
             keyboard_shortcut: None,
+            drag_distance: 0.0,
+            drag_icon: None,
         }))
     }
 
@@ -156,8 +181,10 @@ impl ItemButton {
 
     pub fn fire_left_click_action(&mut self, widget: &Rc<RefCell<Widget>>) {
         let sell_action = self.check_sell_action(widget);
+        let store_action = self.check_store_action(widget);
         let cb = sell_action
             .iter()
+            .chain(store_action.iter())
             .chain(self.actions.iter())
             .find_map(|action| {
                 if action.can_left_click {
@@ -189,6 +216,84 @@ impl ItemButton {
                 .mark_for_removal();
             self.item_window = None;
         }
+
+        if let Some(window) = self.compare_window.take() {
+            window.borrow_mut().mark_for_removal();
+        }
+    }
+
+    /// Creates a window comparing the hovered item against the item the
+    /// selected party member currently has equipped in the same slot.
+    fn create_compare_window(&self, item_state: &ItemState) -> Option<Rc<RefCell<Widget>>> {
+        match self.kind {
+            Kind::Prop { .. }
+            | Kind::Inventory { .. }
+            | Kind::Storage { .. }
+            | Kind::Merchant { .. } => (),
+            _ => return None,
+        }
+
+        let slot = match item_state.item.equippable {
+            None => return None,
+            Some(ref equippable) => equippable.slot,
+        };
+
+        let player = GameState::selected();
+        let player = player.first()?;
+        let player = player.borrow();
+        let actor = &player.actor;
+
+        let equipped = actor.inventory().equipped(slot)?;
+        if !actor.can_equip(item_state) {
+            return None;
+        }
+
+        let new_stats = actor.stats_if_equipped(item_state);
+
+        let window = Widget::with_theme(TextArea::empty(), "item_compare_window");
+        {
+            let mut window = window.borrow_mut();
+            window.state.disable();
+            window.state.set_visible(false);
+            window.state.add_text_arg("name", &equipped.item.name);
+            if !add_stat_delta_text_args(&actor.stats, &new_stats, &mut window.state) {
+                window.state.add_text_arg("no_change", "true");
+            }
+        }
+
+        Some(window)
+    }
+
+    /// Places the compare window beside the item window, once both have
+    /// been sized by the layout pass
+    fn position_compare_window(&mut self) {
+        let (item_window, compare_window) = match (&self.item_window, &self.compare_window) {
+            (Some(item_window), Some(compare_window)) => (item_window, compare_window),
+            _ => return,
+        };
+
+        let item_window = item_window.borrow();
+        let mut compare_window = compare_window.borrow_mut();
+
+        let width = compare_window.state.width();
+        if item_window.state.width() == 0 || width == 0 {
+            return;
+        }
+
+        let mut x = item_window.state.right();
+        if x + width > Config::ui_width() {
+            x = item_window.state.left() - width;
+        }
+        let y = item_window.state.top();
+
+        let position = compare_window.state.position();
+        if compare_window.state.is_visible() && position.x == x && position.y == y {
+            return;
+        }
+
+        compare_window.state.set_position(x, y);
+        compare_window.state.set_visible(true);
+        compare_window.invalidate_layout();
     }
 
     fn get_item_state(&self) -> Option<ItemState> {
@@ -200,6 +305,11 @@ impl ItemButton {
                 let stash = stash.borrow();
                 stash.items().get(item_index).map(|(_, item_state)| item_state.clone())
             }
+            Kind::Storage { item_index } => {
+                let stash = GameState::party_stash();
+                let stash = stash.borrow();
+                stash.storage().get(item_index).map(|(_, item_state)| item_state.clone())
+            }
             Kind::Quick { ref player, quick } => {
                 let pc = player.borrow();
                 pc.actor.inventory().quick(quick).cloned()
@@ -257,6 +367,163 @@ impl ItemButton {
         }
     }
 
+    fn check_sell_stack_action(&self, widget: &Rc<RefCell<Widget>>) -> Option<ButtonAction> {
+        let item_index = match self.kind {
+            Kind::Inventory { item_index, .. } => item_index,
+            _ => return None,
+        };
+
+        if self.quantity < 2 {
+            return None;
+        }
+
+        let (root, root_view) = Widget::parent_mut::<RootView>(widget);
+        let window_widget = root_view.get_merchant_window(&root)?;
+        let merchant_window = Widget::kind_mut::<MerchantWindow>(&window_widget);
+
+        Some(ButtonAction {
+            label: "Sell All".to_string(),
+            callback: sell_stack_cb(merchant_window.player(), item_index),
+            can_left_click: false,
+        })
+    }
+
+    fn check_store_action(&self, widget: &Rc<RefCell<Widget>>) -> Option<ButtonAction> {
+        let item_index = match self.kind {
+            Kind::Inventory { item_index, .. } => item_index,
+            _ => return None,
+        };
+
+        let (root, root_view) = Widget::parent_mut::<RootView>(widget);
+        root_view.get_storage_window(&root)?;
+
+        let stash = GameState::party_stash();
+        let stash = stash.borrow();
+        match stash.items().get(item_index) {
+            Some((_, item_state)) if !item_state.item.quest => {
+                if !stash.storage_has_room(item_state) {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+
+        Some(ButtonAction {
+            label: "Store".to_string(),
+            callback: store_item_cb(item_index),
+            can_left_click: true,
+        })
+    }
+
+    fn can_drag(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::Inventory { .. }
+                | Kind::Storage { .. }
+                | Kind::Prop { .. }
+                | Kind::Equipped { .. }
+        )
+    }
+
+    fn start_drag(&mut self, widget: &Rc<RefCell<Widget>>) {
+        self.remove_item_window();
+
+        let root = Widget::get_root(widget);
+        let icon = Widget::empty("item_drag_icon");
+        {
+            let mut icon = icon.borrow_mut();
+            icon.state.disable();
+            icon.state.add_text_arg("icon", &self.icon);
+        }
+        Widget::add_child_to(&root, Rc::clone(&icon));
+        self.drag_icon = Some(icon);
+        self.move_drag_icon();
+
+        // keep receiving mouse events, including the release, while dragging
+        root.borrow_mut().set_mouse_drag_child(widget);
+    }
+
+    fn move_drag_icon(&self) {
+        if let Some(icon) = &self.drag_icon {
+            let mut icon = icon.borrow_mut();
+            let x = Cursor::get_x() - icon.state.width() / 2;
+            let y = Cursor::get_y() - icon.state.height() / 2;
+            icon.state.set_position(x, y);
+        }
+    }
+
+    /// Moves the dragged item to the window it was dropped on.  Items may be
+    /// moved between the stash and party storage, taken from a prop, or
+    /// dropped into a prop
+    fn drop_item(&mut self, widget: &Rc<RefCell<Widget>>) {
+        let root = Widget::get_root(widget);
+        root.borrow_mut().clear_mouse_drag_child(widget);
+        if let Some(icon) = self.drag_icon.take() {
+            icon.borrow_mut().mark_for_removal();
+        }
+
+        let (x, y) = Cursor::get_position();
+        let over = |window: &Option<Rc<RefCell<Widget>>>| match window {
+            None => false,
+            Some(window) => window.borrow().state.in_bounds(x, y),
+        };
+
+        let root_view = Widget::kind_mut::<RootView>(&root);
+        let storage_window = root_view.get_storage_window(&root);
+        let prop_window = root_view.get_prop_window(&root);
+        let inventory_window = Widget::get_child_with_name(&root, inventory_window::NAME);
+
+        if over(&storage_window) {
+            if let Kind::Inventory { item_index } = self.kind {
+                item_callback_handler::store_item(item_index);
+            }
+        } else if over(&prop_window) {
+            let prop_index = prop_window
+                .as_ref()
+                .map(|window| Widget::kind_mut::<PropWindow>(window).prop_index());
+            match self.kind {
+                Kind::Inventory { item_index } => {
+                    let player = GameState::player();
+                    item_callback_handler::drop_item(&player, item_index, prop_index);
+                }
+                Kind::Equipped { ref player, slot } => {
+                    item_callback_handler::unequip_and_drop_item(player, slot, prop_index);
+                }
+                _ => (),
+            }
+        } else if over(&inventory_window) {
+            match self.kind {
+                Kind::Storage { item_index } => item_callback_handler::retrieve_item(item_index),
+                Kind::Prop {
+                    prop_index,
+                    item_index,
+                } => item_callback_handler::take_item(prop_index, item_index, false),
+                _ => (),
+            }
+        }
+    }
+
+    fn check_repair_action(&self, widget: &Rc<RefCell<Widget>>) -> Option<ButtonAction> {
+        let (player, slot) = match self.kind {
+            Kind::Equipped { ref player, slot } => (player, slot),
+            _ => return None,
+        };
+
+        match player.borrow().actor.inventory().equipped(slot) {
+            Some(item_state) if item_state.is_damaged() => (),
+            _ => return None,
+        }
+
+        let (root, root_view) = Widget::parent_mut::<RootView>(widget);
+        root_view.get_merchant_window(&root)?;
+
+        Some(ButtonAction {
+            label: "Repair".to_string(),
+            callback: repair_item_cb(player, slot),
+            can_left_click: false,
+        })
+    }
+
     fn add_price_text_arg(
         &self,
         root: &Rc<RefCell<Widget>>,
@@ -288,6 +555,13 @@ impl ItemButton {
                     item_window
                         .state
                         .add_text_arg("price", &format_item_value(value));
+
+                    if item_state.is_damaged() {
+                        let cost = merchant.get_repair_price(item_state);
+                        item_window
+                            .state
+                            .add_text_arg("repair_price", &format_item_value(cost));
+                    }
                 }
             }
             _ => (),
@@ -302,6 +576,10 @@ impl WidgetKind for ItemButton {
         self.remove_item_window();
     }
 
+    fn update(&mut self, _widget: &Rc<RefCell<Widget>>, _millis: u32) {
+        self.position_compare_window();
+    }
+
     fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
         let qty_label = Widget::with_theme(Label::empty(), "quantity_label");
         if self.quantity > 1 {
@@ -361,7 +639,10 @@ impl WidgetKind for ItemButton {
             }
 
             match self.kind {
-                Kind::Prop { .. } | Kind::Inventory { .. } | Kind::Merchant { .. } => {
+                Kind::Prop { .. }
+                | Kind::Inventory { .. }
+                | Kind::Storage { .. }
+                | Kind::Merchant { .. } => {
                     let player = GameState::selected();
                     if !player.is_empty() {
                         if !has_proficiency(&item_state, &player[0].borrow().actor.stats) {
@@ -404,6 +685,13 @@ impl WidgetKind for ItemButton {
             item_window
                 .state
                 .add_text_arg("weight", &format_item_weight(item_state.item.weight));
+            if let Some((cur, max)) = item_state.durability() {
+                item_window.state.add_text_arg("durability", &cur.to_string());
+                item_window.state.add_text_arg("max_durability", &max.to_string());
+                if item_state.armor_multiplier() < 1.0 {
+                    item_window.state.add_text_arg("degraded", "true");
+                }
+            }
             self.add_price_text_arg(&root, &mut item_window, &item_state);
 
             if let Some(ref prereqs) = &item_state.item.prereqs {
@@ -447,6 +735,11 @@ impl WidgetKind for ItemButton {
         Widget::add_child_to(&root, Rc::clone(&item_window));
         self.item_window = Some(item_window);
 
+        if let Some(compare_window) = self.create_compare_window(&item_state) {
+            Widget::add_child_to(&root, Rc::clone(&compare_window));
+            self.compare_window = Some(compare_window);
+        }
+
         true
     }
 
@@ -457,9 +750,38 @@ impl WidgetKind for ItemButton {
         true
     }
 
+    fn on_mouse_drag(
+        &mut self,
+        widget: &Rc<RefCell<Widget>>,
+        kind: event::ClickKind,
+        delta_x: f32,
+        delta_y: f32,
+    ) -> bool {
+        if kind != event::ClickKind::Primary || !self.can_drag() {
+            return false;
+        }
+
+        if self.drag_icon.is_none() {
+            self.drag_distance += delta_x.abs() + delta_y.abs();
+            if self.drag_distance < DRAG_THRESHOLD {
+                return true;
+            }
+            self.start_drag(widget);
+        }
+
+        self.move_drag_icon();
+        true
+    }
+
     fn on_mouse_release(&mut self, widget: &Rc<RefCell<Widget>>, kind: event::ClickKind) -> bool {
         self.super_on_mouse_release(widget, kind);
         self.remove_item_window();
+        self.drag_distance = 0.0;
+
+        if self.drag_icon.is_some() {
+            self.drop_item(widget);
+            return true;
+        }
 
         match kind {
             event::ClickKind::Primary => {
@@ -474,6 +796,21 @@ impl WidgetKind for ItemButton {
                     at_least_one_action = true;
                 }
 
+                if let Some(action) = self.check_sell_stack_action(widget) {
+                    menu.borrow_mut().add_action(&action.label, action.callback);
+                    at_least_one_action = true;
+                }
+
+                if let Some(action) = self.check_store_action(widget) {
+                    menu.borrow_mut().add_action(&action.label, action.callback);
+                    at_least_one_action = true;
+                }
+
+                if let Some(action) = self.check_repair_action(widget) {
+                    menu.borrow_mut().add_action(&action.label, action.callback);
+                    at_least_one_action = true;
+                }
+
                 for action in self.actions.iter() {
                     menu.borrow_mut()
                         .add_action(&action.label, action.callback.clone());
@@ -493,3 +830,52 @@ impl WidgetKind for ItemButton {
         true
     }
 }
+
+/// Adds `<stat>_inc` or `<stat>_dec` text args for each stat that differs
+/// between `cur` and `new`.  Returns false if no stats differ.
+fn add_stat_delta_text_args(cur: &StatList, new: &StatList, state: &mut WidgetState) -> bool {
+    fn min_damage(stats: &StatList) -> i32 {
+        stats.attacks.iter().map(|a| a.damage.min() as i32).sum()
+    }
+
+    fn max_damage(stats: &StatList) -> i32 {
+        stats.attacks.iter().map(|a| a.damage.max() as i32).sum()
+    }
+
+    let deltas = [
+        ("min_damage", min_damage(new) - min_damage(cur)),
+        ("max_damage", max_damage(new) - max_damage(cur)),
+        ("armor", new.armor.base() - cur.armor.base()),
+        ("max_hp", new.max_hp - cur.max_hp),
+        ("melee_accuracy", new.melee_accuracy - cur.melee_accuracy),
+        ("ranged_accuracy", new.ranged_accuracy - cur.ranged_accuracy),
+        ("spell_accuracy", new.spell_accuracy - cur.spell_accuracy),
+        ("defense", new.defense - cur.defense),
+        ("fortitude", new.fortitude - cur.fortitude),
+        ("reflex", new.reflex - cur.reflex),
+        ("will", new.will - cur.will),
+        ("crit_chance", new.crit_chance - cur.crit_chance),
+        ("concealment", new.concealment - cur.concealment),
+        ("initiative", new.initiative - cur.initiative),
+    ];
+
+    let mut any_change = false;
+    for (id, delta) in deltas.iter() {
+        if *delta == 0 {
+            continue;
+        }
+
+        let kind = if *delta > 0 { "inc" } else { "dec" };
+        state.add_text_arg(&format!("{id}_{kind}"), &format_bonus_or_penalty(*delta));
+        any_change = true;
+    }
+
+    let movement = new.movement_rate - cur.movement_rate;
+    if movement.abs() > f32::EPSILON {
+        let kind = if movement > 0.0 { "inc" } else { "dec" };
+        state.add_text_arg(&format!("movement_rate_{kind}"), &format!("{movement:+.2}"));
+        any_change = true;
+    }
+
+    any_change
+}
@@ -19,181 +19,408 @@ use std::rc::Rc;
 
 use sulis_core::ui::{Callback, Widget};
 use sulis_module::{ItemState, QuickSlot, Slot};
-use sulis_state::{script::{ScriptCallback, ScriptItemKind}, EntityState, GameState, Script};
+use sulis_state::{
+    replay,
+    script::{ScriptCallback, ScriptItemKind},
+    EntityState, GameState, ReplayCommand, Script,
+};
 
 use crate::{MerchantWindow, PropWindow, RootView};
 
 pub fn clear_quickslot_cb(entity: &Rc<RefCell<EntityState>>, slot: QuickSlot) -> Callback {
     let entity = Rc::clone(entity);
-    Callback::new(Rc::new(move |_, _| {
-        let item = {
-            let actor = &mut entity.borrow_mut().actor;
-            actor.clear_quick(slot)
-        };
-        if let Some(item) = item {
-            let stash = GameState::party_stash();
-            stash.borrow_mut().add_item(1, item);
-        }
-    }))
+    Callback::new(Rc::new(move |_, _| clear_quickslot(&entity, slot)))
+}
+
+pub(crate) fn clear_quickslot(entity: &Rc<RefCell<EntityState>>, slot: QuickSlot) {
+    replay::record(ReplayCommand::ClearQuickSlot {
+        entity: entity.borrow().index(),
+        slot,
+    });
+
+    let item = {
+        let actor = &mut entity.borrow_mut().actor;
+        actor.clear_quick(slot)
+    };
+    if let Some(item) = item {
+        let stash = GameState::party_stash();
+        stash.borrow_mut().add_item(1, item);
+    }
 }
 
 pub fn set_quickslot_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
     let entity = Rc::clone(entity);
-    Callback::new(Rc::new(move |_, _| {
-        let stash = GameState::party_stash();
-        let item = match stash.borrow_mut().remove_item(index) {
-            None => return,
-            Some(item) => item,
-        };
+    Callback::new(Rc::new(move |_, _| set_quickslot(&entity, index)))
+}
 
-        let to_add = {
-            let actor = &mut entity.borrow_mut().actor;
-            for slot in QuickSlot::usable_iter() {
-                if actor.inventory().quick(*slot).is_none() {
-                    let _ = actor.set_quick(item, *slot); // we know there is no item here
-                    return;
-                }
+pub(crate) fn set_quickslot(entity: &Rc<RefCell<EntityState>>, index: usize) {
+    replay::record(ReplayCommand::SetQuickSlot {
+        entity: entity.borrow().index(),
+        index,
+    });
+
+    let stash = GameState::party_stash();
+    let item = match stash.borrow_mut().remove_item(index) {
+        None => return,
+        Some(item) => item,
+    };
+
+    let to_add = {
+        let actor = &mut entity.borrow_mut().actor;
+        for slot in QuickSlot::usable_iter() {
+            if actor.inventory().quick(*slot).is_none() {
+                let _ = actor.set_quick(item, *slot); // we know there is no item here
+                return;
             }
+        }
 
-            actor.set_quick(item, QuickSlot::Usable1)
-        };
+        actor.set_quick(item, QuickSlot::Usable1)
+    };
 
-        if let Some(item) = to_add {
-            stash.borrow_mut().add_item(1, item);
-        }
-    }))
+    if let Some(item) = to_add {
+        stash.borrow_mut().add_item(1, item);
+    }
 }
 
 pub fn use_item_cb(entity: &Rc<RefCell<EntityState>>, kind: ScriptItemKind) -> Callback {
     let entity = Rc::clone(entity);
-    Callback::new(Rc::new(move |_, _| {
-        if let ScriptItemKind::Quick(slot) = kind {
-            if !entity.borrow().actor.can_use_quick(slot) {
-                return;
-            }
+    Callback::new(Rc::new(move |_, _| use_item(&entity, kind.clone())))
+}
+
+pub(crate) fn use_item(entity: &Rc<RefCell<EntityState>>, kind: ScriptItemKind) {
+    if let ScriptItemKind::Quick(slot) = kind {
+        if !entity.borrow().actor.can_use_quick(slot) {
+            return;
         }
-        Script::item_on_activate(&entity, "on_activate".to_string(), kind.clone());
-    }))
+    }
+
+    replay::record(ReplayCommand::UseItem {
+        entity: entity.borrow().index(),
+        item: kind.clone(),
+    });
+    Script::item_on_activate(entity, "on_activate".to_string(), kind);
 }
 
 pub fn take_item_cb(prop_index: usize, index: usize) -> Callback {
-    Callback::with(Box::new(move || {
-        let stash = GameState::party_stash();
-        stash.borrow_mut().take(prop_index, index);
-    }))
+    Callback::with(Box::new(move || take_item(prop_index, index, false)))
+}
+
+pub fn take_one_item_cb(prop_index: usize, index: usize) -> Callback {
+    Callback::with(Box::new(move || take_item(prop_index, index, true)))
+}
+
+pub(crate) fn take_item(prop: usize, index: usize, one: bool) {
+    replay::record(ReplayCommand::TakeItem { prop, index, one });
+
+    let stash = GameState::party_stash();
+    if one {
+        stash.borrow_mut().take_one(prop, index);
+    } else {
+        stash.borrow_mut().take(prop, index);
+    }
+}
+
+pub(crate) fn take_all(prop: usize) {
+    replay::record(ReplayCommand::TakeAll { prop });
+
+    let stash = GameState::party_stash();
+    stash.borrow_mut().take_all(prop);
+}
+
+pub fn store_item_cb(index: usize) -> Callback {
+    Callback::with(Box::new(move || store_item(index)))
+}
+
+pub(crate) fn store_item(index: usize) {
+    replay::record(ReplayCommand::StoreItem { index });
+
+    let stash = GameState::party_stash();
+    stash.borrow_mut().store(index);
+}
+
+pub fn retrieve_item_cb(index: usize) -> Callback {
+    Callback::with(Box::new(move || retrieve_item(index)))
+}
+
+pub(crate) fn retrieve_item(index: usize) {
+    replay::record(ReplayCommand::RetrieveItem { index });
+
+    let stash = GameState::party_stash();
+    stash.borrow_mut().retrieve(index);
 }
 
 pub fn equip_item_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
     let entity = Rc::clone(entity);
-    Callback::with(Box::new(move || {
-        let stash = GameState::party_stash();
-        let item = match stash.borrow_mut().remove_item(index) {
-            None => return,
-            Some(item) => item,
-        };
+    Callback::with(Box::new(move || equip_item(&entity, index)))
+}
 
-        let slot = item.item.equippable.as_ref().map_or(Slot::Neck, |e| e.slot);
+pub(crate) fn equip_item(entity: &Rc<RefCell<EntityState>>, index: usize) {
+    replay::record(ReplayCommand::EquipItem {
+        entity: entity.borrow().index(),
+        index,
+    });
 
-        // equip with no preferred slot
-        let to_add = entity.borrow_mut().actor.equip(item, None);
+    let stash = GameState::party_stash();
+    let item = match stash.borrow_mut().remove_item(index) {
+        None => return,
+        Some(item) => item,
+    };
 
-        for item in to_add {
-            stash.borrow_mut().add_item(1, item);
-        }
+    let slot = item.item.equippable.as_ref().map_or(Slot::Neck, |e| e.slot);
+
+    // equip with no preferred slot
+    let to_add = entity.borrow_mut().actor.equip(item, None);
+
+    for item in to_add {
+        stash.borrow_mut().add_item(1, item);
+    }
 
-        match slot {
-            Slot::HeldMain | Slot::HeldOff => {
-                let mgr = GameState::turn_manager();
-                let cbs = entity.borrow().callbacks(&mgr.borrow());
-                cbs.iter().for_each(|cb| cb.on_held_changed());
-            },
-            _ => (),
+    on_held_changed(entity, slot);
+}
+
+fn on_held_changed(entity: &Rc<RefCell<EntityState>>, slot: Slot) {
+    match slot {
+        Slot::HeldMain | Slot::HeldOff => {
+            let mgr = GameState::turn_manager();
+            let cbs = entity.borrow().callbacks(&mgr.borrow());
+            cbs.iter().for_each(|cb| cb.on_held_changed());
         }
-    }))
+        _ => (),
+    }
 }
 
 pub fn buy_item_cb(merchant_id: &str, index: usize) -> Callback {
     let merchant_id = merchant_id.to_string();
-    Callback::with(Box::new(move || {
-        let area_state = GameState::area_state();
-        let mut area_state = area_state.borrow_mut();
+    Callback::with(Box::new(move || buy_item(&merchant_id, index)))
+}
 
-        let mut merchant = area_state.get_merchant_mut(&merchant_id);
-        let merchant = match merchant {
-            None => return,
-            Some(ref mut merchant) => merchant,
-        };
+pub(crate) fn buy_item(merchant_id: &str, index: usize) {
+    replay::record(ReplayCommand::BuyItem {
+        merchant: merchant_id.to_string(),
+        index,
+        stack: false,
+    });
 
-        let value = match merchant.items().get(index) {
-            None => return,
-            Some((_, item_state)) => merchant.get_buy_price(item_state),
-        };
+    let area_state = GameState::area_state();
+    let mut area_state = area_state.borrow_mut();
 
-        if GameState::party_coins() < value {
-            return;
-        }
+    let mut merchant = area_state.get_merchant_mut(merchant_id);
+    let merchant = match merchant {
+        None => return,
+        Some(ref mut merchant) => merchant,
+    };
 
-        if let Some(item_state) = merchant.remove(index) {
-            GameState::add_party_coins(-value);
-            let stash = GameState::party_stash();
-            stash.borrow_mut().add_item(1, item_state);
+    let value = match merchant.items().get(index) {
+        None => return,
+        Some((_, item_state)) => merchant.get_buy_price(item_state),
+    };
+
+    if GameState::party_coins() < value {
+        return;
+    }
+
+    if let Some(item_state) = merchant.remove(index) {
+        GameState::add_party_coins(-value);
+        let stash = GameState::party_stash();
+        stash.borrow_mut().add_item(1, item_state);
+    }
+}
+
+/// Buys as much of the stack at the specified index as the party can afford
+pub fn buy_stack_cb(merchant_id: &str, index: usize) -> Callback {
+    let merchant_id = merchant_id.to_string();
+    Callback::with(Box::new(move || buy_stack(&merchant_id, index)))
+}
+
+pub(crate) fn buy_stack(merchant_id: &str, index: usize) {
+    replay::record(ReplayCommand::BuyItem {
+        merchant: merchant_id.to_string(),
+        index,
+        stack: true,
+    });
+
+    let area_state = GameState::area_state();
+    let mut area_state = area_state.borrow_mut();
+
+    let mut merchant = area_state.get_merchant_mut(merchant_id);
+    let merchant = match merchant {
+        None => return,
+        Some(ref mut merchant) => merchant,
+    };
+
+    let (qty, value) = match merchant.items().get(index) {
+        None => return,
+        Some((qty, item_state)) => (*qty, merchant.get_buy_price(item_state)),
+    };
+
+    let affordable = if value > 0 {
+        (GameState::party_coins().max(0) / value) as u32
+    } else {
+        qty
+    };
+
+    if let Some((qty, item_state)) = merchant.remove_quantity(index, qty.min(affordable)) {
+        GameState::add_party_coins(-value * qty as i32);
+        let stash = GameState::party_stash();
+        stash.borrow_mut().add_item(qty, item_state);
+    }
+}
+
+fn open_merchant_id(widget: &Rc<RefCell<Widget>>) -> Option<String> {
+    let (root, root_view) = Widget::parent_mut::<RootView>(widget);
+    let window = root_view.get_merchant_window(&root)?;
+    let merchant_window = Widget::kind_mut::<MerchantWindow>(&window);
+    Some(merchant_window.merchant_id().to_string())
+}
+
+pub fn sell_stack_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
+    let entity = Rc::clone(entity);
+    Callback::new(Rc::new(move |widget, _| {
+        if let Some(merchant_id) = open_merchant_id(widget) {
+            sell_item(&entity, &merchant_id, index, true);
         }
     }))
 }
 
+pub fn split_stack_cb(index: usize) -> Callback {
+    Callback::with(Box::new(move || split_stack(index)))
+}
+
+pub(crate) fn split_stack(index: usize) {
+    replay::record(ReplayCommand::SplitStack { index });
+
+    let stash = GameState::party_stash();
+    stash.borrow_mut().split_stack(index);
+}
+
 pub fn sell_item_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
     let entity = Rc::clone(entity);
     Callback::new(Rc::new(move |widget, _| {
-        let (root, root_view) = Widget::parent_mut::<RootView>(widget);
-        let merchant = match root_view.get_merchant_window(&root) {
-            None => return,
-            Some(ref window) => {
-                let merchant_window = Widget::kind_mut::<MerchantWindow>(window);
-                merchant_window.merchant_id().to_string()
-            }
-        };
+        if let Some(merchant_id) = open_merchant_id(widget) {
+            sell_item(&entity, &merchant_id, index, false);
+        }
+    }))
+}
 
+/// Sells the stash item at `index`, or its whole stack when `stack` is set,
+/// to the merchant.  `entity` is notified so the inventory is refreshed
+pub(crate) fn sell_item(
+    entity: &Rc<RefCell<EntityState>>,
+    merchant_id: &str,
+    index: usize,
+    stack: bool,
+) {
+    replay::record(ReplayCommand::SellItem {
+        merchant: merchant_id.to_string(),
+        index,
+        stack,
+    });
+
+    {
         let area_state = GameState::area_state();
         let mut area_state = area_state.borrow_mut();
-        let mut merchant = area_state.get_merchant_mut(&merchant);
+        let mut merchant = area_state.get_merchant_mut(merchant_id);
         let merchant = match merchant {
             None => return,
             Some(ref mut merchant) => merchant,
         };
 
         let stash = GameState::party_stash();
-        let item_state = stash.borrow_mut().remove_item(index);
-        if let Some(item_state) = item_state {
-            let value = merchant.get_sell_price(&item_state);
-            GameState::add_party_coins(value);
-            merchant.add(item_state);
+        if stack {
+            let result = stash.borrow_mut().remove_quantity(index, u32::MAX);
+            if let Some((qty, item_state)) = result {
+                let value = merchant.get_sell_price(&item_state);
+                GameState::add_party_coins(value * qty as i32);
+                merchant.add_quantity(qty, item_state);
+            }
+        } else {
+            let item_state = stash.borrow_mut().remove_item(index);
+            if let Some(item_state) = item_state {
+                let value = merchant.get_sell_price(&item_state);
+                GameState::add_party_coins(value);
+                merchant.add(item_state);
+            }
         }
+    }
 
-        let actor = &entity.borrow().actor;
-        actor.listeners.notify(actor);
-    }))
+    let actor = &entity.borrow().actor;
+    actor.listeners.notify(actor);
 }
 
-pub fn drop_item_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
+pub fn repair_item_cb(entity: &Rc<RefCell<EntityState>>, slot: Slot) -> Callback {
     let entity = Rc::clone(entity);
     Callback::new(Rc::new(move |widget, _| {
-        let stash = GameState::party_stash();
-        let item = stash.borrow_mut().remove_item(index);
-        if let Some(item) = item {
-            drop_item(widget, &entity, item);
+        if let Some(merchant_id) = open_merchant_id(widget) {
+            repair_item(&entity, &merchant_id, slot);
         }
     }))
 }
 
-fn drop_item(widget: &Rc<RefCell<Widget>>, entity: &Rc<RefCell<EntityState>>, item: ItemState) {
+pub(crate) fn repair_item(entity: &Rc<RefCell<EntityState>>, merchant_id: &str, slot: Slot) {
+    replay::record(ReplayCommand::RepairItem {
+        entity: entity.borrow().index(),
+        merchant: merchant_id.to_string(),
+        slot,
+    });
+
+    let value = {
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        let merchant = match area_state.get_merchant(merchant_id) {
+            None => return,
+            Some(merchant) => merchant,
+        };
+
+        match entity.borrow().actor.inventory().equipped(slot) {
+            None => return,
+            Some(item_state) => merchant.get_repair_price(item_state),
+        }
+    };
+
+    if GameState::party_coins() < value {
+        return;
+    }
+
+    if entity.borrow_mut().actor.repair_equipped(slot, None) {
+        GameState::add_party_coins(-value);
+    }
+}
+
+/// The index of the prop whose window is open, which dropped items go into
+fn open_prop_index(widget: &Rc<RefCell<Widget>>) -> Option<usize> {
     let (root, root_view) = Widget::parent_mut::<RootView>(widget);
-    match root_view.get_prop_window(&root) {
+    let window = root_view.get_prop_window(&root)?;
+    let prop_window = Widget::kind_mut::<PropWindow>(&window);
+    Some(prop_window.prop_index())
+}
+
+pub fn drop_item_cb(entity: &Rc<RefCell<EntityState>>, index: usize) -> Callback {
+    let entity = Rc::clone(entity);
+    Callback::new(Rc::new(move |widget, _| {
+        drop_item(&entity, index, open_prop_index(widget));
+    }))
+}
+
+/// Drops the stash item at `index` into the prop at `prop`, or on the ground
+/// at the entity's feet if `prop` is None
+pub(crate) fn drop_item(entity: &Rc<RefCell<EntityState>>, index: usize, prop: Option<usize>) {
+    replay::record(ReplayCommand::DropItem {
+        entity: entity.borrow().index(),
+        index,
+        prop,
+    });
+
+    let stash = GameState::party_stash();
+    let item = stash.borrow_mut().remove_item(index);
+    if let Some(item) = item {
+        drop_item_state(entity, item, prop);
+    }
+}
+
+fn drop_item_state(entity: &Rc<RefCell<EntityState>>, item: ItemState, prop: Option<usize>) {
+    match prop {
         None => drop_to_ground(entity, item),
-        Some(ref window) => {
-            let prop_window = Widget::kind_mut::<PropWindow>(window);
-            let prop_index = prop_window.prop_index();
-            drop_to_prop(item, prop_index);
-        }
+        Some(prop_index) => drop_to_prop(item, prop_index),
     }
 }
 
@@ -226,38 +453,45 @@ fn drop_to_ground(entity: &Rc<RefCell<EntityState>>, item: ItemState) {
 pub fn unequip_and_drop_item_cb(entity: &Rc<RefCell<EntityState>>, slot: Slot) -> Callback {
     let entity = Rc::clone(entity);
     Callback::new(Rc::new(move |widget, _| {
-        let item = entity.borrow_mut().actor.unequip(slot);
-        if let Some(item) = item {
-            drop_item(widget, &entity, item);
-        }
-
-        match slot {
-            Slot::HeldMain | Slot::HeldOff => {
-                let mgr = GameState::turn_manager();
-                let cbs = entity.borrow().callbacks(&mgr.borrow());
-                cbs.iter().for_each(|cb| cb.on_held_changed());
-            },
-            _ => (),
-        }
+        unequip_and_drop_item(&entity, slot, open_prop_index(widget));
     }))
 }
 
+pub(crate) fn unequip_and_drop_item(
+    entity: &Rc<RefCell<EntityState>>,
+    slot: Slot,
+    prop: Option<usize>,
+) {
+    replay::record(ReplayCommand::UnequipAndDropItem {
+        entity: entity.borrow().index(),
+        slot,
+        prop,
+    });
+
+    let item = entity.borrow_mut().actor.unequip(slot);
+    if let Some(item) = item {
+        drop_item_state(entity, item, prop);
+    }
+
+    on_held_changed(entity, slot);
+}
+
 pub fn unequip_item_cb(entity: &Rc<RefCell<EntityState>>, slot: Slot) -> Callback {
     let entity = Rc::clone(entity);
-    Callback::with(Box::new(move || {
-        let item = entity.borrow_mut().actor.unequip(slot);
-        if let Some(item) = item {
-            let stash = GameState::party_stash();
-            stash.borrow_mut().add_item(1, item);
-        }
+    Callback::with(Box::new(move || unequip_item(&entity, slot)))
+}
 
-        match slot {
-            Slot::HeldMain | Slot::HeldOff => {
-                let mgr = GameState::turn_manager();
-                let cbs = entity.borrow().callbacks(&mgr.borrow());
-                cbs.iter().for_each(|cb| cb.on_held_changed());
-            },
-            _ => (),
-        }
-    }))
+pub(crate) fn unequip_item(entity: &Rc<RefCell<EntityState>>, slot: Slot) {
+    replay::record(ReplayCommand::UnequipItem {
+        entity: entity.borrow().index(),
+        slot,
+    });
+
+    let item = entity.borrow_mut().actor.unequip(slot);
+    if let Some(item) = item {
+        let stash = GameState::party_stash();
+        stash.borrow_mut().add_item(1, item);
+    }
+
+    on_held_changed(entity, slot);
 }
@@ -31,6 +31,7 @@ enum Kind {
     Entity,
     Merchant(String),
     Prop(usize),
+    Storage,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -97,6 +98,13 @@ impl ItemListPane {
         ItemListPane::new(entity, Kind::Prop(prop_index), cur_filter)
     }
 
+    pub fn new_storage(
+        entity: &Rc<RefCell<EntityState>>,
+        cur_filter: &Rc<Cell<Filter>>,
+    ) -> Rc<RefCell<ItemListPane>> {
+        ItemListPane::new(entity, Kind::Storage, cur_filter)
+    }
+
     pub fn new_merchant(
         entity: &Rc<RefCell<EntityState>>,
         merchant_id: String,
@@ -131,6 +139,14 @@ impl ItemListPane {
                 .borrow_mut()
                 .add_action("Buy", buy_item_cb(merchant_id, index), true);
 
+            if qty > 1 {
+                item_button.borrow_mut().add_action(
+                    "Buy All",
+                    buy_stack_cb(merchant_id, index),
+                    false,
+                );
+            }
+
             scrollpane
                 .borrow()
                 .add_to_content(Widget::with_defaults(item_button));
@@ -163,6 +179,14 @@ impl ItemListPane {
                             take_item_cb(prop_index, index),
                             true,
                         );
+
+                        if qty > 1 {
+                            item_button.borrow_mut().add_action(
+                                "Take One",
+                                take_one_item_cb(prop_index, index),
+                                false,
+                            );
+                        }
                     }
                     scrollpane
                         .borrow()
@@ -174,6 +198,34 @@ impl ItemListPane {
         list_content
     }
 
+    fn create_content_storage(&self) -> Rc<RefCell<Widget>> {
+        let combat_active = GameState::is_combat_active();
+
+        let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
+        let list_content = Widget::with_theme(scrollpane.clone(), "items_list");
+
+        let stash = GameState::party_stash();
+        let stash = stash.borrow();
+        for (index, &(qty, ref item)) in stash.storage().iter().enumerate() {
+            if !self.cur_filter.get().is_allowed(&item.item) {
+                continue;
+            }
+
+            let item_button = ItemButton::storage(item, qty, index);
+            if !combat_active {
+                item_button
+                    .borrow_mut()
+                    .add_action("Take", retrieve_item_cb(index), true);
+            }
+
+            scrollpane
+                .borrow()
+                .add_to_content(Widget::with_defaults(item_button));
+        }
+
+        list_content
+    }
+
     fn create_content_inventory(&self) -> Rc<RefCell<Widget>> {
         let combat_active = GameState::is_combat_active();
 
@@ -213,6 +265,12 @@ impl ItemListPane {
                     .add_action("Equip", equip_item_cb(&self.entity, index), true);
             }
 
+            if !combat_active && stash.items().can_split(index) {
+                item_but
+                    .borrow_mut()
+                    .add_action("Split Stack", split_stack_cb(index), false);
+            }
+
             if !combat_active && !item.item.quest {
                 item_but
                     .borrow_mut()
@@ -238,6 +296,7 @@ impl WidgetKind for ItemListPane {
             Kind::Entity => self.create_content_inventory(),
             Kind::Prop(index) => self.create_content_prop(*index),
             Kind::Merchant(id) => self.create_content_merchant(id),
+            Kind::Storage => self.create_content_storage(),
         };
         children.push(content);
 
@@ -28,6 +28,9 @@ pub use self::abilities_bar::AbilitiesBar;
 mod ability_pane;
 pub use self::ability_pane::AbilityPane;
 
+mod achievements_window;
+pub use self::achievements_window::AchievementsWindow;
+
 mod action_kind;
 pub use self::action_kind::ActionKind;
 
@@ -57,6 +60,9 @@ pub use self::class_pane::ClassPane;
 mod console_window;
 pub use self::console_window::ConsoleWindow;
 
+mod cutscene_sequence;
+pub use self::cutscene_sequence::CutsceneSequence;
+
 mod cutscene_window;
 pub use self::cutscene_window::CutsceneWindow;
 
@@ -98,6 +104,12 @@ pub use self::load_window::LoadWindow;
 mod merchant_window;
 pub use self::merchant_window::MerchantWindow;
 
+mod minimap;
+pub use self::minimap::Minimap;
+
+pub mod modal_dialog;
+pub use self::modal_dialog::ModalDialog;
+
 mod portrait_view;
 pub use self::portrait_view::PortraitView;
 
@@ -115,15 +127,32 @@ pub mod main_menu;
 mod race_pane;
 pub use self::race_pane::RacePane;
 
+mod radial_menu;
+pub use self::radial_menu::RadialMenu;
+
+pub mod replay_player;
+
 mod root_view;
 pub use self::root_view::RootView;
 
 mod screen_shake;
 pub use self::screen_shake::ScreenShake;
 
+mod script_error_window;
+pub use self::script_error_window::ScriptErrorWindow;
+
 mod script_menu;
 pub use self::script_menu::ScriptMenu;
 
+mod script_window;
+pub use self::script_window::ScriptWindow;
+
+mod smooth_scroll;
+pub use self::smooth_scroll::SmoothScroll;
+
+mod storage_window;
+pub use self::storage_window::StorageWindow;
+
 pub mod trigger_activator;
 
 mod window_fade;
@@ -137,7 +166,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::ui::{Widget, WidgetKind};
-use sulis_core::widgets::{Button, ConfirmationWindow, Label};
+use sulis_core::widgets::{Button, Label};
 use sulis_state::{ChangeListener, GameState};
 
 pub struct PortraitPane {}
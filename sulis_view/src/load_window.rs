@@ -18,23 +18,38 @@ use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use sulis_core::io::{DrawList, GraphicsRenderer, Screenshot, TextureMagFilter, TextureMinFilter};
 use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::util::{Point, Rect};
 use sulis_core::widgets::{
-    Button, ConfirmationWindow, Label, ScrollDirection, ScrollPane, TextArea,
+    Button, ConfirmationWindow, InputField, Label, ScrollDirection, ScrollPane, TextArea,
 };
-use sulis_state::save_file::{delete_save, get_available_save_files, load_state};
+use sulis_state::save_file::{delete_save, get_available_save_files, load_state, rename_save};
 use sulis_state::{NextGameStep, SaveFileMetaData, SaveState};
 
-use crate::{main_menu::MainMenu, LoadingScreen, RootView};
+use crate::{main_menu::MainMenu, modal_dialog, LoadingScreen, ModalDialog, RootView};
 
 const NAME: &str = "load_window";
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SortKind {
+    /// Most recent saves first
+    Date,
+
+    /// Alphabetically by save name
+    Name,
+}
+
 pub struct LoadWindow {
     accept: Rc<RefCell<Widget>>,
     delete: Rc<RefCell<Widget>>,
+    rename: Rc<RefCell<Widget>>,
+    search: Rc<RefCell<Widget>>,
     pub(crate) cancel: Rc<RefCell<Widget>>,
     pub(crate) entries: Vec<SaveFileMetaData>,
     pub(crate) selected_entry: Option<usize>,
+    sort: SortKind,
+    search_text: String,
     main_menu_mode: bool,
 }
 
@@ -43,6 +58,8 @@ impl LoadWindow {
         let accept = Widget::with_theme(Button::empty(), "accept");
         let cancel = Widget::with_theme(Button::empty(), "cancel");
         let delete = Widget::with_theme(Button::empty(), "delete");
+        let rename = Widget::with_theme(Button::empty(), "rename");
+        let search = Widget::with_theme(InputField::new(""), "search");
         let entries = match get_available_save_files() {
             Ok(files) => files,
             Err(e) => {
@@ -55,19 +72,76 @@ impl LoadWindow {
         Rc::new(RefCell::new(LoadWindow {
             accept,
             delete,
+            rename,
+            search,
             cancel,
             entries,
             selected_entry: None,
+            sort: SortKind::Date,
+            search_text: String::new(),
             main_menu_mode,
         }))
     }
 
+    /// The indices of the entries matching the current search, in the
+    /// current sort order
+    fn visible_entries(&self) -> Vec<usize> {
+        let search = self.search_text.trim().to_lowercase();
+        let mut indices: Vec<usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, meta)| search.is_empty() || matches_search(meta, &search))
+            .map(|(index, _)| index)
+            .collect();
+
+        match self.sort {
+            SortKind::Date => {
+                indices.sort_by(|a, b| {
+                    self.entries[*b]
+                        .timestamp
+                        .cmp(&self.entries[*a].timestamp)
+                });
+            }
+            SortKind::Name => {
+                indices.sort_by_cached_key(|index| {
+                    self.entries[*index].display_name().to_lowercase()
+                });
+            }
+        }
+
+        indices
+    }
+
+    /// The names of mods used by the selected save that are not installed
+    fn missing_mods(&self) -> Vec<String> {
+        match self.selected_entry {
+            None => Vec::new(),
+            Some(index) => self.entries[index].mod_status().missing,
+        }
+    }
+
     pub fn load(&self, root: &Rc<RefCell<Widget>>) {
         let index = match self.selected_entry {
             None => return,
             Some(index) => index,
         };
 
+        // the save's mods must be active before it is read, as it may
+        // reference their resources
+        let mod_status = self.entries[index].mod_status();
+        if mod_status.changed {
+            info!("Activating mods {:?} to load save", mod_status.dirs);
+            self.set_next_step(
+                NextGameStep::LoadModsAndCampaign {
+                    mods: mod_status.dirs,
+                    save_file: Box::new(self.entries[index].clone()),
+                },
+                root,
+            );
+            return;
+        }
+
         match load_state(&self.entries[index]) {
             Err(e) => {
                 error!("Error reading game state");
@@ -80,17 +154,22 @@ impl LoadWindow {
     }
 
     pub fn set_load_step(&self, save_state: SaveState, root: &Rc<RefCell<Widget>>) {
+        self.set_next_step(
+            NextGameStep::LoadCampaign {
+                save_state: Box::new(save_state),
+            },
+            root,
+        );
+    }
+
+    fn set_next_step(&self, step: NextGameStep, root: &Rc<RefCell<Widget>>) {
         // TODO remove the bool flag passed in the constructor
         if self.main_menu_mode {
             let main_menu = Widget::kind_mut::<MainMenu>(root);
-            main_menu.next_step = Some(NextGameStep::LoadCampaign {
-                save_state: Box::new(save_state),
-            });
+            main_menu.next_step = Some(step);
         } else {
             let root_view = Widget::kind_mut::<RootView>(root);
-            root_view.next_step = Some(NextGameStep::LoadCampaign {
-                save_state: Box::new(save_state),
-            });
+            root_view.next_step = Some(step);
         }
 
         let loading_screen = Widget::with_defaults(LoadingScreen::new());
@@ -112,21 +191,146 @@ impl LoadWindow {
         self.entries.remove(index);
     }
 
+    fn rename_save(&mut self, name: &str) {
+        let index = match self.selected_entry {
+            None => return,
+            Some(index) => index,
+        };
+
+        if let Err(e) = rename_save(&mut self.entries[index], name) {
+            error!("Error renaming save");
+            error!("{}", e);
+        }
+    }
+
     fn set_button_state(&self) {
         self.delete
             .borrow_mut()
             .state
             .set_enabled(self.selected_entry.is_some());
 
-        let accept_enabled = match self.selected_entry {
-            None => false,
-            Some(index) => self.entries[index].error.is_none(),
+        let (valid_selected, can_load) = match self.selected_entry {
+            None => (false, false),
+            Some(index) => {
+                let entry = &self.entries[index];
+                let valid = entry.error.is_none();
+                (valid, valid && !entry.abandoned)
+            }
+        };
+
+        self.accept.borrow_mut().state.set_enabled(can_load);
+        self.rename.borrow_mut().state.set_enabled(valid_selected);
+    }
+
+    fn add_sort_button(
+        &self,
+        kind: SortKind,
+        theme: &str,
+        children: &mut Vec<Rc<RefCell<Widget>>>,
+    ) {
+        let button = Widget::with_theme(Button::empty(), theme);
+        button.borrow_mut().state.set_active(self.sort == kind);
+        button
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(move |widget, _| {
+                let (parent, load_window) = Widget::parent_mut::<LoadWindow>(widget);
+                load_window.sort = kind;
+                parent.borrow_mut().invalidate_children();
+            })));
+        children.push(button);
+    }
+
+    fn create_preview(&self) -> Rc<RefCell<Widget>> {
+        let preview = Widget::empty("preview");
+
+        let meta = match self.selected_entry {
+            None => return preview,
+            Some(index) => &self.entries[index],
         };
 
-        self.accept.borrow_mut().state.set_enabled(accept_enabled);
+        let thumbnail = Widget::with_theme(SaveThumbnail::new(meta.load_thumbnail()), "thumbnail");
+
+        let portraits = Widget::empty("portraits");
+        for portrait in meta.portraits.iter() {
+            let label = Widget::with_theme(Label::empty(), "portrait");
+            label.borrow_mut().state.add_text_arg("portrait", portrait);
+            Widget::add_child_to(&portraits, label);
+        }
+
+        let details = Widget::with_theme(TextArea::empty(), "details");
+        {
+            let state = &mut details.borrow_mut().state;
+            add_meta_text_args(meta, &mut |id, value| state.add_text_arg(id, value));
+
+            if let Some(quest) = &meta.current_quest {
+                state.add_text_arg("current_quest", quest);
+            }
+
+            if let Some(millis) = meta.play_time_millis {
+                state.add_text_arg("play_time", &format_play_time(millis));
+            }
+        }
+
+        Widget::add_children_to(&preview, vec![thumbnail, portraits, details]);
+        preview
     }
 }
 
+fn matches_search(meta: &SaveFileMetaData, search: &str) -> bool {
+    let fields = [
+        Some(meta.display_name()),
+        Some(meta.player_name.as_str()),
+        Some(meta.current_area_name.as_str()),
+        meta.class.as_deref(),
+        meta.current_quest.as_deref(),
+    ];
+
+    fields
+        .iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(search))
+}
+
+fn add_meta_text_args(meta: &SaveFileMetaData, add: &mut dyn FnMut(&str, &str)) {
+    add("name", meta.display_name());
+    add("player_name", &meta.player_name);
+    add("datetime", &meta.datetime);
+    add("current_area_name", &meta.current_area_name);
+
+    if let Some(level) = meta.level {
+        add("level", &format!("{level}"));
+    }
+
+    if let Some(class) = &meta.class {
+        add("class", class);
+    }
+
+    if let Some(error) = &meta.error {
+        add("error", error);
+    }
+
+    if meta.ironman {
+        add("ironman", "true");
+    }
+
+    if meta.abandoned {
+        add("abandoned", "true");
+    }
+
+    if let Some(mods) = &meta.mods {
+        let names: Vec<&str> = mods.iter().map(|m| m.name.as_str()).collect();
+        if !names.is_empty() {
+            add("mods", &names.join(", "));
+        }
+    }
+}
+
+fn format_play_time(millis: usize) -> String {
+    let minutes = millis / 60_000;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
 impl WidgetKind for LoadWindow {
     widget_kind!(NAME);
 
@@ -141,8 +345,24 @@ impl WidgetKind for LoadWindow {
                 parent.borrow_mut().mark_for_removal();
             })));
 
-        let load_window_widget_ref = Rc::clone(widget);
+        self.search
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, kind| {
+                let (parent, load_window) = Widget::parent_mut::<LoadWindow>(widget);
+                let field = Widget::downcast::<InputField>(kind);
+                load_window.search_text = field.text();
+                parent.borrow_mut().invalidate_children();
+            })));
+
+        // the confirmation and rename windows are children of the root, so the
+        // callbacks hold a weak reference to this window rather than a cycle
+        let load_window_widget_ref = Rc::downgrade(widget);
         let delete_cb = Callback::new(Rc::new(move |widget, _| {
+            let load_window_widget_ref = match load_window_widget_ref.upgrade() {
+                None => return,
+                Some(widget) => widget,
+            };
             load_window_widget_ref.borrow_mut().invalidate_children();
 
             let load_window = Widget::kind_mut::<LoadWindow>(&load_window_widget_ref);
@@ -167,39 +387,85 @@ impl WidgetKind for LoadWindow {
                 Widget::add_child_to(&root, conf_window);
             })));
 
+        self.rename
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(move |widget, _| {
+                let (parent, load_window) = Widget::parent_mut::<LoadWindow>(widget);
+                let name = match load_window.selected_entry {
+                    None => return,
+                    Some(index) => load_window.entries[index].display_name().to_string(),
+                };
+
+                let load_window_widget_ref = Rc::downgrade(&parent);
+                let dialog = ModalDialog::text_input(
+                    &tr!("load.rename"),
+                    &name,
+                    Rc::new(move |_, text| {
+                        let load_window_widget_ref = match load_window_widget_ref.upgrade() {
+                            None => return,
+                            Some(widget) => widget,
+                        };
+                        let load_window = Widget::kind_mut::<LoadWindow>(&load_window_widget_ref);
+                        load_window.rename_save(text);
+                        load_window_widget_ref.borrow_mut().invalidate_children();
+                    }),
+                );
+                dialog.borrow_mut().set_accept_text(&tr!("load.rename_accept"));
+                modal_dialog::show(widget, dialog);
+            })));
+
+        let load_window_widget_ref = Rc::downgrade(widget);
+        let load_cb = Callback::new(Rc::new(move |widget, _| {
+            let load_window_widget_ref = match load_window_widget_ref.upgrade() {
+                None => return,
+                Some(widget) => widget,
+            };
+            let root = Widget::get_root(widget);
+            let load_window = Widget::kind_mut::<LoadWindow>(&load_window_widget_ref);
+            load_window.load(&root);
+            load_window_widget_ref.borrow_mut().mark_for_removal();
+
+            let (parent, _) = Widget::parent::<ConfirmationWindow>(widget);
+            parent.borrow_mut().mark_for_removal();
+        }));
+
         self.accept
             .borrow_mut()
             .state
-            .add_callback(Callback::new(Rc::new(|widget, _| {
+            .add_callback(Callback::new(Rc::new(move |widget, _| {
                 let (parent, load_window) = Widget::parent_mut::<LoadWindow>(widget);
                 let root = Widget::get_root(&parent);
-                load_window.load(&root);
 
-                parent.borrow_mut().mark_for_removal();
+                let missing = load_window.missing_mods();
+                if missing.is_empty() {
+                    load_window.load(&root);
+                    parent.borrow_mut().mark_for_removal();
+                    return;
+                }
+
+                warn!("Save uses mods that are not installed: {:?}", missing);
+                let conf_window = Widget::with_theme(
+                    ConfirmationWindow::new(load_cb.clone()),
+                    "missing_mods_confirmation",
+                );
+                {
+                    let state = &mut conf_window.borrow_mut().state;
+                    state.add_text_arg("custom", &missing.join(", "));
+                    state.set_modal(true);
+                }
+                Widget::add_child_to(&root, conf_window);
             })));
 
         let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
         let entries = Widget::with_theme(scrollpane.clone(), "entries");
 
-        for (index, meta) in self.entries.iter().enumerate() {
+        for index in self.visible_entries() {
+            let meta = &self.entries[index];
             let text_area = Widget::with_defaults(TextArea::empty());
             {
-                let area = &mut text_area.borrow_mut().state;
-                area.add_text_arg("player_name", &meta.player_name);
-                area.add_text_arg("datetime", &meta.datetime);
-                area.add_text_arg("current_area_name", &meta.current_area_name);
-
-                if let Some(level) = meta.level {
-                    area.add_text_arg("level", &format!("{level}"));
-                }
-
-                if let Some(class) = &meta.class {
-                    area.add_text_arg("class", class);
-                }
-
-                if let Some(error) = &meta.error {
-                    area.add_text_arg("error", error);
-                }
+                let state = &mut text_area.borrow_mut().state;
+                add_meta_text_args(meta, &mut |id, value| state.add_text_arg(id, value));
             }
 
             let widget = Widget::with_theme(Button::empty(), "entry");
@@ -208,15 +474,8 @@ impl WidgetKind for LoadWindow {
                 .state
                 .add_callback(Callback::new(Rc::new(move |widget, _| {
                     let (parent, load_window) = Widget::parent_mut::<LoadWindow>(widget);
-                    parent.borrow_mut().invalidate_layout();
                     load_window.selected_entry = Some(index);
-                    load_window.set_button_state();
-
-                    let content = Widget::direct_parent(widget);
-                    for child in content.borrow().children.iter() {
-                        child.borrow_mut().state.set_active(false);
-                    }
-                    widget.borrow_mut().state.set_active(true);
+                    parent.borrow_mut().invalidate_children();
                 })));
 
             if let Some(selected_index) = self.selected_entry {
@@ -231,12 +490,72 @@ impl WidgetKind for LoadWindow {
 
         self.set_button_state();
 
-        vec![
+        let mut children = vec![
             self.cancel.clone(),
             self.delete.clone(),
+            self.rename.clone(),
             self.accept.clone(),
+            self.search.clone(),
             title,
             entries,
-        ]
+            self.create_preview(),
+        ];
+        self.add_sort_button(SortKind::Date, "sort_date", &mut children);
+        self.add_sort_button(SortKind::Name, "sort_name", &mut children);
+        children
+    }
+}
+
+const THUMBNAIL_TEX_ID: &str = "__save_thumbnail__";
+const TEX_COORDS: [f32; 8] = [0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
+/// Draws the thumbnail screenshot of a save, scaled to fit the widget
+struct SaveThumbnail {
+    image: Option<Screenshot>,
+    size: Option<(u32, u32)>,
+}
+
+impl SaveThumbnail {
+    fn new(image: Option<Screenshot>) -> Rc<RefCell<SaveThumbnail>> {
+        let size = image.as_ref().map(|image| image.dimensions());
+        Rc::new(RefCell::new(SaveThumbnail { image, size }))
+    }
+}
+
+impl WidgetKind for SaveThumbnail {
+    widget_kind!("save_thumbnail");
+
+    fn draw(
+        &mut self,
+        renderer: &mut dyn GraphicsRenderer,
+        _pixel_size: Point,
+        widget: &Widget,
+        _millis: u32,
+    ) {
+        let (w, h) = match self.size {
+            None => return,
+            Some((w, h)) => (w as f32, h as f32),
+        };
+
+        // only the thumbnail of the selected save is shown, so they share a texture
+        if let Some(image) = self.image.take() {
+            renderer.register_texture(
+                THUMBNAIL_TEX_ID,
+                image,
+                TextureMinFilter::Linear,
+                TextureMagFilter::Linear,
+            );
+        }
+
+        let max_w = widget.state.inner_width() as f32;
+        let max_h = widget.state.inner_height() as f32;
+        let scale = (max_w / w).min(max_h / h);
+        let rect = Rect {
+            x: widget.state.inner_left() as f32 + (max_w - w * scale) / 2.0,
+            y: widget.state.inner_top() as f32 + (max_h - h * scale) / 2.0,
+            w: w * scale,
+            h: h * scale,
+        };
+        renderer.draw(DrawList::from_texture_id(THUMBNAIL_TEX_ID, &TEX_COORDS, rect));
     }
 }
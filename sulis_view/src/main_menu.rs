@@ -20,6 +20,9 @@ pub use self::character_selector::CharacterSelector;
 mod links_pane;
 use self::links_pane::LinksPane;
 
+mod load_error_report;
+use self::load_error_report::LoadErrorReport;
+
 pub mod module_selector;
 pub use self::module_selector::ModuleSelector;
 
@@ -38,6 +41,7 @@ use std::rc::Rc;
 
 use sulis_core::config::Config;
 use sulis_core::io::{DisplayConfiguration, InputActionKind, AudioDeviceInfo};
+use sulis_core::resource::{self, LoadError};
 use sulis_core::ui::*;
 use sulis_core::util;
 use sulis_core::widgets::{Button, ConfirmationWindow, TextArea};
@@ -65,6 +69,7 @@ pub struct MainMenu {
     pub(crate) audio_devices: Vec<AudioDeviceInfo>,
 
     tip_text: Option<String>,
+    load_errors: Vec<LoadError>,
 }
 
 impl MainMenu {
@@ -86,6 +91,7 @@ impl MainMenu {
             display_configurations,
             audio_devices,
             tip_text,
+            load_errors: resource::take_load_errors(),
         }))
     }
 
@@ -243,7 +249,7 @@ impl WidgetKind for MainMenu {
                 let configs = window.display_configurations.clone();
                 let audio = window.audio_devices.iter().map(|d| d.name.to_string()).collect();
 
-                window.content = Widget::with_defaults(Options::new(configs, audio));
+                window.content = Widget::with_defaults(Options::new(configs, audio, true));
 
                 parent.borrow_mut().invalidate_children();
             })));
@@ -311,11 +317,19 @@ impl WidgetKind for MainMenu {
         }
 
         if let Some(options) = Config::take_old_config() {
-            let config_confirm = Widget::with_defaults(SaveOrRevertOptionsWindow::new(options));
+            let config_confirm =
+                Widget::with_defaults(SaveOrRevertOptionsWindow::new(options, true));
             config_confirm.borrow_mut().state.set_modal(true);
             children.push(config_confirm);
         }
 
+        if !self.load_errors.is_empty() {
+            let errors = std::mem::take(&mut self.load_errors);
+            let report = Widget::with_defaults(LoadErrorReport::new(errors));
+            report.borrow_mut().state.set_modal(true);
+            children.push(report);
+        }
+
         children
     }
 }
@@ -19,10 +19,11 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::ui::*;
+use sulis_core::util::parse_seed;
 use sulis_core::widgets::{
-    Button, ConfirmationWindow, Label, ScrollDirection, ScrollPane, TextArea,
+    Button, ConfirmationWindow, InputField, Label, ScrollDirection, ScrollPane, TextArea,
 };
-use sulis_module::{Actor, Module};
+use sulis_module::{Actor, InvalidCharacter, Module};
 use sulis_state::{ActorState, NextGameStep};
 
 use crate::character_window::create_details_text_box;
@@ -33,6 +34,9 @@ pub struct CharacterSelector {
     first_add: bool,
     main_menu: Rc<RefCell<Widget>>,
     to_select: Option<String>,
+    ironman: bool,
+    seed: String,
+    play_button: Option<Rc<RefCell<Widget>>>,
 }
 
 impl CharacterSelector {
@@ -42,6 +46,9 @@ impl CharacterSelector {
             first_add: true,
             main_menu,
             to_select: None,
+            ironman: false,
+            seed: String::new(),
+            play_button: None,
         }))
     }
 
@@ -51,31 +58,41 @@ impl CharacterSelector {
 
     #[must_use]
     fn set_play_enabled(&self, play: &mut WidgetState) -> Rc<RefCell<Widget>> {
-        let max_level = Module::campaign().max_starting_level;
-
         let invalid_level = Widget::with_theme(TextArea::empty(), "invalid_level_box");
         let (enabled, invalid_vis) = match self.selected {
             None => (false, false),
-            Some(ref actor) => {
-                invalid_level
-                    .borrow_mut()
-                    .state
-                    .add_text_arg("level", &actor.total_level.to_string());
-                invalid_level
-                    .borrow_mut()
-                    .state
-                    .add_text_arg("max_level", &max_level.to_string());
-
-                let enabled = actor.total_level <= max_level;
-                (enabled, !enabled)
-            }
+            Some(ref actor) => match Module::validate_character(actor) {
+                Ok(()) => (true, false),
+                Err(invalid) => {
+                    invalid_level
+                        .borrow_mut()
+                        .state
+                        .add_text_arg("reason", &invalid_character_text(invalid));
+                    (false, true)
+                }
+            },
         };
 
         invalid_level.borrow_mut().state.set_visible(invalid_vis);
-        play.set_enabled(enabled);
+        play.set_enabled(enabled && parse_seed(&self.seed).is_ok());
 
         invalid_level
     }
+
+    fn update_play_enabled(&self) {
+        let play = match &self.play_button {
+            None => return,
+            Some(play) => play,
+        };
+
+        let valid_actor = match self.selected {
+            None => false,
+            Some(ref actor) => Module::validate_character(actor).is_ok(),
+        };
+
+        let enabled = valid_actor && parse_seed(&self.seed).is_ok();
+        play.borrow_mut().state.set_enabled(enabled);
+    }
 }
 
 impl WidgetKind for CharacterSelector {
@@ -192,19 +209,57 @@ impl WidgetKind for CharacterSelector {
                     Some(ref selected) => Rc::clone(selected),
                 };
 
+                let ironman = selector.ironman;
+                let seed = match parse_seed(&selector.seed) {
+                    Ok(seed) => seed,
+                    Err(e) => {
+                        warn!("Unable to start campaign with the entered seed");
+                        warn!("{}", e);
+                        return;
+                    }
+                };
+
                 let (root, window) = Widget::parent_mut::<MainMenu>(&parent);
-                window.next_step = Some(NextGameStep::NewCampaign { pc_actor: selected });
+                window.next_step = Some(NextGameStep::NewCampaign {
+                    pc_actor: selected,
+                    ironman,
+                    seed,
+                });
 
                 let loading_screen = Widget::with_defaults(LoadingScreen::new());
                 loading_screen.borrow_mut().state.set_modal(true);
                 Widget::add_child_to(&root, loading_screen);
             })));
 
+        let ironman_button = Widget::with_theme(Button::empty(), "ironman_button");
+        ironman_button.borrow_mut().state.set_active(self.ironman);
+        ironman_button
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (_, selector) = Widget::parent_mut::<CharacterSelector>(widget);
+                selector.ironman = !selector.ironman;
+                widget.borrow_mut().state.set_active(selector.ironman);
+            })));
+
+        let seed_label = Widget::with_theme(Label::empty(), "seed_label");
+        let seed_field = Widget::with_theme(InputField::new(&self.seed), "seed_field");
+        seed_field
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, kind| {
+                let (_, selector) = Widget::parent_mut::<CharacterSelector>(widget);
+                let field = Widget::downcast::<InputField>(kind);
+
+                selector.seed = field.text.to_string();
+                selector.update_play_enabled();
+            })));
+
         let details = if let Some(ref actor) = self.selected {
             let mut actor_state = ActorState::new(Rc::clone(actor));
             actor_state.compute_stats();
             actor_state.init_day();
-            create_details_text_box(&actor_state, false)
+            create_details_text_box(&actor_state, None)
         } else {
             Widget::with_theme(TextArea::empty(), "details")
         };
@@ -214,6 +269,7 @@ impl WidgetKind for CharacterSelector {
             .state
             .set_enabled(self.selected.is_some());
         let invalid_level = self.set_play_enabled(&mut play_button.borrow_mut().state);
+        self.play_button = Some(Rc::clone(&play_button));
 
         if self.first_add && must_create_character {
             let menu = Widget::kind_mut::<MainMenu>(&self.main_menu);
@@ -229,12 +285,30 @@ impl WidgetKind for CharacterSelector {
             new_character_button,
             delete_char_button,
             play_button,
+            ironman_button,
+            seed_label,
+            seed_field,
             details,
             invalid_level,
         ]
     }
 }
 
+fn invalid_character_text(invalid: InvalidCharacter) -> String {
+    match invalid {
+        InvalidCharacter::Level { level, max_level } => {
+            tr!("character.invalid_level", level = level, max_level = max_level)
+        }
+        InvalidCharacter::Race(race) => tr!("character.invalid_race", race = race),
+        InvalidCharacter::Class(class) => tr!("character.invalid_class", class = class),
+        InvalidCharacter::Attribute { attribute, value } => tr!(
+            "character.invalid_attribute",
+            attribute = attribute.name(),
+            value = value
+        ),
+    }
+}
+
 fn actor_callback(actor: Rc<Actor>) -> Callback {
     Callback::new(Rc::new(move |widget, _| {
         let (parent, selector) = Widget::parent_mut::<CharacterSelector>(widget);
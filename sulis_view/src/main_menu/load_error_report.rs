@@ -0,0 +1,100 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::resource::LoadError;
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{markup_renderer, Button, Label, ScrollDirection, ScrollPane, TextArea};
+
+/// The maximum number of errors listed; any more are only counted
+const MAX_SHOWN: usize = 50;
+
+/// Lists the resources and files that failed to load, shown in the main
+/// menu after resources are loaded
+pub struct LoadErrorReport {
+    errors: Vec<LoadError>,
+}
+
+impl LoadErrorReport {
+    pub fn new(errors: Vec<LoadError>) -> Rc<RefCell<LoadErrorReport>> {
+        Rc::new(RefCell::new(LoadErrorReport { errors }))
+    }
+}
+
+impl WidgetKind for LoadErrorReport {
+    widget_kind!("load_error_report");
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let title = Widget::with_theme(Label::empty(), "title");
+        title
+            .borrow_mut()
+            .state
+            .add_text_arg("count", &self.errors.len().to_string());
+
+        let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
+        let errors = Widget::with_theme(scrollpane.clone(), "errors");
+        for error in self.errors.iter().take(MAX_SHOWN) {
+            let text = Widget::with_theme(TextArea::empty(), "error");
+            add_error_args(&mut text.borrow_mut(), error);
+            scrollpane.borrow().add_to_content(text);
+        }
+
+        if self.errors.len() > MAX_SHOWN {
+            let more = Widget::with_theme(Label::empty(), "more");
+            more.borrow_mut()
+                .state
+                .add_text_arg("count", &(self.errors.len() - MAX_SHOWN).to_string());
+            scrollpane.borrow().add_to_content(more);
+        }
+
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<LoadErrorReport>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        vec![title, errors, close]
+    }
+}
+
+fn add_error_args(widget: &mut Widget, error: &LoadError) {
+    let state = &mut widget.state;
+    state.add_text_arg("kind", &error.kind);
+    state.add_text_arg("id", &markup_renderer::escape(&error.id));
+    state.add_text_arg("message", &markup_renderer::escape(&error.message));
+
+    if let Some(file) = &error.file {
+        state.add_text_arg("file", &markup_renderer::escape(file));
+    }
+    if let Some(line) = error.line {
+        state.add_text_arg("line", &line.to_string());
+    }
+    if let Some(column) = error.column {
+        state.add_text_arg("column", &column.to_string());
+    }
+    if let Some(key_path) = &error.key_path {
+        state.add_text_arg("key_path", &markup_renderer::escape(key_path));
+    }
+    if let Some(snippet) = &error.snippet {
+        state.add_text_arg("snippet", &markup_renderer::escape(snippet));
+    }
+}
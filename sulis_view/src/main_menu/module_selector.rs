@@ -22,7 +22,7 @@ use std::rc::Rc;
 use sulis_core::ui::*;
 use sulis_core::util::ActiveResources;
 use sulis_core::widgets::{Button, Label, ScrollDirection, ScrollPane, TextArea};
-use sulis_module::ModuleInfo;
+use sulis_module::{ModuleInfo, ModuleManifest};
 use sulis_state::NextGameStep;
 
 use crate::main_menu::MainMenu;
@@ -31,6 +31,7 @@ use crate::LoadingScreen;
 pub struct ModuleSelector {
     modules: Vec<ModuleInfo>,
     selected_module: Option<usize>,
+    core_manifest: ModuleManifest,
 }
 
 impl ModuleSelector {
@@ -38,8 +39,30 @@ impl ModuleSelector {
         Rc::new(RefCell::new(ModuleSelector {
             modules,
             selected_module: None,
+            core_manifest: ModuleManifest::core(),
         }))
     }
+
+    fn missing_base_modules(&self, module: &ModuleInfo) -> Vec<String> {
+        module
+            .manifest
+            .base_modules
+            .iter()
+            .filter(|id| !self.modules.iter().any(|m| &m.id == *id))
+            .cloned()
+            .collect()
+    }
+
+    fn selected_is_supported(&self) -> bool {
+        match self.selected_module {
+            None => false,
+            Some(index) => {
+                let module = &self.modules[index];
+                module.manifest.is_supported_by(&self.core_manifest)
+                    && self.missing_base_modules(module).is_empty()
+            }
+        }
+    }
 }
 
 impl WidgetKind for ModuleSelector {
@@ -53,10 +76,27 @@ impl WidgetKind for ModuleSelector {
         let details = Widget::with_theme(TextArea::empty(), "details");
 
         if let Some(index) = self.selected_module {
-            details
-                .borrow_mut()
-                .state
-                .add_text_arg("description", &self.modules[index].description);
+            let module = &self.modules[index];
+            let state = &mut details.borrow_mut().state;
+            state.add_text_arg("description", &module.description);
+
+            let manifest = &module.manifest;
+            if let Some(author) = &manifest.author {
+                state.add_text_arg("author", author);
+            }
+            if let Some(version) = &manifest.version {
+                state.add_text_arg("version", version);
+            }
+            if let Some(ruleset) = &manifest.ruleset {
+                state.add_text_arg("ruleset", ruleset);
+                if !manifest.is_supported_by(&self.core_manifest) {
+                    state.add_text_arg("missing_ruleset", "true");
+                }
+            }
+            let missing = self.missing_base_modules(module);
+            if !missing.is_empty() {
+                state.add_text_arg("missing_base_modules", &missing.join(", "));
+            }
         }
 
         let mut groups = HashMap::new();
@@ -144,7 +184,7 @@ impl WidgetKind for ModuleSelector {
             })));
         play.borrow_mut()
             .state
-            .set_enabled(self.selected_module.is_some());
+            .set_enabled(self.selected_is_supported());
 
         vec![title, modules_title, play, scroll_widget, details]
     }
@@ -21,38 +21,53 @@ use std::path::Path;
 use std::rc::Rc;
 
 use sulis_core::config::DisplayMode;
-use sulis_core::config::{self, Config, RawClick};
-use sulis_core::io::{event::ClickKind, keyboard_event::Key, DisplayConfiguration, InputActionKind};
+use sulis_core::config::{self, AutosaveFrequency, Config, FeedbackChannel, RawClick};
+use sulis_core::io::{
+    event::ClickKind, keyboard_event::Key, DisplayConfiguration, InputActionKind, KeyBindings,
+};
+use sulis_core::resource::ResourceSet;
 use sulis_core::ui::{Callback, Widget, WidgetKind};
 use sulis_core::widgets::{Button, Label, ScrollDirection, ScrollPane, TextArea};
+use sulis_module::{Difficulty, DifficultyLevel, DifficultyScaling};
+use sulis_state::{GameState, NextGameStep};
 
-use crate::main_menu::MainMenu;
+use crate::main_menu::{MainMenu, SaveOrRevertOptionsWindow};
+use crate::RootView;
 
 enum Tab {
     Display,
     Input,
     Gameplay,
-    Audio
+    Audio,
+    Difficulty,
 }
 
 pub struct Options {
     display_confs: Vec<DisplayConfiguration>,
+    main_menu_mode: bool,
 
     cur_tab: Tab,
 
     cur_display_mode: DisplayMode,
     cur_display_conf: usize,
-    cur_ui_scale: (i32, i32),
+    cur_ui_scale: f32,
+    cur_auto_ui_scale: bool,
+    cur_language: String,
     cur_resolution: (u32, u32),
     cur_default_zoom: f32,
     cur_anim_speed: u32,
     cur_scroll_speed: f32,
     cur_edge_scrolling: bool,
-    cur_keybindings: Vec<(Key, InputActionKind)>,
+    cur_keybindings: KeyBindings,
     cur_click_actions: Vec<(RawClick, ClickKind)>,
 
     cur_crit_screen_shake: bool,
     cur_scroll_to_active: bool,
+    cur_fog_of_war: bool,
+    cur_lighting: bool,
+    cur_disabled_feedback: Vec<FeedbackChannel>,
+    cur_autosave: AutosaveFrequency,
+    cur_difficulty: Difficulty,
 
     audio_devices: Vec<String>,
     cur_audio_device: Option<usize>,
@@ -66,16 +81,9 @@ impl Options {
     pub fn new(
         display_confs: Vec<DisplayConfiguration>,
         audio_devices: Vec<String>,
+        main_menu_mode: bool,
     ) -> Rc<RefCell<Options>> {
         let config = Config::get_clone();
-        let mut cur_keybindings: Vec<_> = config
-            .input
-            .keybindings
-            .iter()
-            .map(|(k, v)| (*k, *v))
-            .collect();
-        cur_keybindings.sort_by(|(_, v1), (_, v2)| v1.partial_cmp(v2).unwrap());
-
         let mut cur_click_actions: Vec<_> = config
             .input
             .click_actions
@@ -98,8 +106,16 @@ impl Options {
             Some(0)
         };
 
+        // difficulty is part of the current game rather than the config
+        let cur_difficulty = if main_menu_mode {
+            Difficulty::default()
+        } else {
+            GameState::difficulty()
+        };
+
         Rc::new(RefCell::new(Options {
             display_confs,
+            main_menu_mode,
 
             cur_tab: Tab::Display,
 
@@ -110,12 +126,19 @@ impl Options {
             cur_anim_speed: config.display.animation_base_time_millis,
             cur_scroll_speed: config.input.scroll_speed,
             cur_edge_scrolling: config.input.edge_scrolling,
-            cur_ui_scale: (config.display.width, config.display.height),
-            cur_keybindings,
+            cur_ui_scale: config.display.ui_scale,
+            cur_auto_ui_scale: config.display.auto_ui_scale,
+            cur_language: config.display.language.clone(),
+            cur_keybindings: config.input.keybindings.clone(),
             cur_click_actions,
 
             cur_crit_screen_shake: config.input.crit_screen_shake,
             cur_scroll_to_active: config.display.scroll_to_active,
+            cur_fog_of_war: config.display.fog_of_war,
+            cur_lighting: config.display.lighting,
+            cur_disabled_feedback: config.display.disabled_feedback_channels.clone(),
+            cur_autosave: config.gameplay.autosave,
+            cur_difficulty,
 
             audio_devices,
             cur_audio_device,
@@ -149,8 +172,9 @@ impl Options {
         config.display.monitor = self.cur_display_conf;
         config.display.width_pixels = self.cur_resolution.0;
         config.display.height_pixels = self.cur_resolution.1;
-        config.display.width = self.cur_ui_scale.0;
-        config.display.height = self.cur_ui_scale.1;
+        config.display.ui_scale = self.cur_ui_scale;
+        config.display.auto_ui_scale = self.cur_auto_ui_scale;
+        config.display.language = self.cur_language.clone();
         config.display.default_zoom = self.cur_default_zoom;
 
         config.input.scroll_speed = self.cur_scroll_speed;
@@ -160,13 +184,14 @@ impl Options {
             config.input.click_actions.insert(*k, *v);
         }
 
-        config.input.keybindings.clear();
-        for (k, v) in self.cur_keybindings.iter() {
-            config.input.keybindings.insert(*k, *v);
-        }
+        config.input.keybindings = self.cur_keybindings.clone();
 
         config.input.crit_screen_shake = self.cur_crit_screen_shake;
         config.display.scroll_to_active = self.cur_scroll_to_active;
+        config.display.fog_of_war = self.cur_fog_of_war;
+        config.display.lighting = self.cur_lighting;
+        config.display.disabled_feedback_channels = self.cur_disabled_feedback.clone();
+        config.gameplay.autosave = self.cur_autosave;
 
         config.audio.device = self.cur_audio_device.unwrap_or(0);
         config.audio.master_volume = self.master_volume;
@@ -179,6 +204,36 @@ impl Options {
         Config::set(config);
     }
 
+    fn apply_difficulty(&self) {
+        if self.main_menu_mode || self.cur_difficulty == GameState::difficulty() {
+            return;
+        }
+
+        GameState::set_difficulty(self.cur_difficulty);
+    }
+
+    /// Applies the newly set config.  From the main menu, all IO is recreated.
+    /// In game, the window is updated in place and the options are closed
+    fn recreate_io(&self, widget: &Rc<RefCell<Widget>>) {
+        if self.main_menu_mode {
+            let (_, menu) = Widget::parent_mut::<MainMenu>(widget);
+            menu.recreate_io();
+            return;
+        }
+
+        widget.borrow_mut().mark_for_removal();
+
+        let (root, view) = Widget::parent_mut::<RootView>(widget);
+        view.set_next_step(NextGameStep::RecreateWindow);
+
+        if let Some(old_config) = Config::take_old_config() {
+            let confirm = SaveOrRevertOptionsWindow::new(old_config, false);
+            let confirm = Widget::with_defaults(confirm);
+            confirm.borrow_mut().state.set_modal(true);
+            Widget::add_child_to(&root, confirm);
+        }
+    }
+
     fn add_display_widgets(&mut self) -> Vec<Rc<RefCell<Widget>>> {
         let mode_title = Widget::with_theme(Label::empty(), "mode_title");
 
@@ -313,40 +368,72 @@ impl Options {
 
         let ui_scale_content = Widget::empty("ui_scale_content");
 
-        let (ui_width, ui_height) = (self.cur_ui_scale.0, self.cur_ui_scale.1);
-        let normal = Widget::with_theme(Button::empty(), "normal");
-        normal
-            .borrow_mut()
+        let auto = Widget::with_theme(Button::empty(), "auto");
+        auto.borrow_mut()
             .state
             .add_callback(Callback::new(Rc::new(|widget, _| {
                 let (parent, options) = Widget::parent_mut::<Options>(widget);
-                options.cur_ui_scale = UI_SCALE_NORMAL;
+                options.cur_auto_ui_scale = true;
                 parent.borrow_mut().invalidate_children();
             })));
+        if self.cur_auto_ui_scale {
+            auto.borrow_mut().state.set_active(true);
+        }
+        Widget::add_child_to(&ui_scale_content, auto);
 
-        let small = Widget::with_theme(Button::empty(), "small");
-        small
-            .borrow_mut()
-            .state
-            .add_callback(Callback::new(Rc::new(|widget, _| {
-                let (parent, options) = Widget::parent_mut::<Options>(widget);
-                options.cur_ui_scale = UI_SCALE_SMALL;
-                parent.borrow_mut().invalidate_children();
-            })));
+        let mut scale_found = self.cur_auto_ui_scale;
+        for scale in UI_SCALES.iter() {
+            let scale = *scale;
+            let button = Widget::with_theme(Button::empty(), "ui_scale_button");
+            {
+                let state = &mut button.borrow_mut().state;
+                let percent = (scale * 100.0).round() as i32;
+                state.add_text_arg("scale", &percent.to_string());
+                state.add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, options) = Widget::parent_mut::<Options>(widget);
+                    options.cur_auto_ui_scale = false;
+                    options.cur_ui_scale = scale;
+                    parent.borrow_mut().invalidate_children();
+                })));
 
-        if ui_width == UI_SCALE_NORMAL.0 && ui_height == UI_SCALE_NORMAL.1 {
-            normal.borrow_mut().state.set_active(true);
-        } else if ui_width == UI_SCALE_SMALL.0 && ui_height == UI_SCALE_SMALL.1 {
-            small.borrow_mut().state.set_active(true);
-        } else {
+                if !self.cur_auto_ui_scale && (scale - self.cur_ui_scale).abs() < 0.01 {
+                    state.set_active(true);
+                    scale_found = true;
+                }
+            }
+
+            Widget::add_child_to(&ui_scale_content, button);
+        }
+
+        if !scale_found {
             info!(
-                "UI Scale is set to {},{} which is a nonstandard value",
-                ui_width, ui_height
+                "UI Scale is set to {} which is a nonstandard value",
+                self.cur_ui_scale
             );
         }
 
-        Widget::add_child_to(&ui_scale_content, normal);
-        Widget::add_child_to(&ui_scale_content, small);
+        let language_title = Widget::with_theme(Label::empty(), "language_title");
+        let language_content = Widget::empty("language_content");
+        for (id, name) in ResourceSet::languages() {
+            let button = Widget::with_theme(Button::empty(), "language_button");
+            {
+                let state = &mut button.borrow_mut().state;
+                state.add_text_arg("name", &name);
+                if id == self.cur_language {
+                    state.set_active(true);
+                }
+
+                // campaign data is only reloaded for a new language from the main menu
+                state.set_enabled(self.main_menu_mode);
+                state.add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, options) = Widget::parent_mut::<Options>(widget);
+                    options.cur_language = id.clone();
+                    parent.borrow_mut().invalidate_children();
+                })));
+            }
+
+            Widget::add_child_to(&language_content, button);
+        }
 
         vec![
             mode_title,
@@ -357,6 +444,8 @@ impl Options {
             resolution_pane,
             ui_scale_title,
             ui_scale_content,
+            language_title,
+            language_content,
         ]
     }
 
@@ -498,6 +587,52 @@ impl Options {
         Widget::add_child_to(&scroll_to_active_content, scroll_to_active_on);
         Widget::add_child_to(&scroll_to_active_content, scroll_to_active_off);
 
+        let fog_of_war_on = Widget::with_theme(Button::empty(), "on");
+        fog_of_war_on.borrow_mut().state.add_callback(Callback::new(Rc::new(|widget, _| {
+            let (parent, options) = Widget::parent_mut::<Options>(widget);
+            options.cur_fog_of_war = true;
+            parent.borrow_mut().invalidate_children();
+        })));
+
+        let fog_of_war_off = Widget::with_theme(Button::empty(), "off");
+        fog_of_war_off.borrow_mut().state.add_callback(Callback::new(Rc::new(|widget, _| {
+            let (parent, options) = Widget::parent_mut::<Options>(widget);
+            options.cur_fog_of_war = false;
+            parent.borrow_mut().invalidate_children();
+        })));
+        if self.cur_fog_of_war {
+            fog_of_war_on.borrow_mut().state.set_active(true);
+        } else {
+            fog_of_war_off.borrow_mut().state.set_active(true);
+        }
+
+        let fog_of_war_content = Widget::empty("fog_of_war_content");
+        Widget::add_child_to(&fog_of_war_content, fog_of_war_on);
+        Widget::add_child_to(&fog_of_war_content, fog_of_war_off);
+
+        let lighting_on = Widget::with_theme(Button::empty(), "on");
+        lighting_on.borrow_mut().state.add_callback(Callback::new(Rc::new(|widget, _| {
+            let (parent, options) = Widget::parent_mut::<Options>(widget);
+            options.cur_lighting = true;
+            parent.borrow_mut().invalidate_children();
+        })));
+
+        let lighting_off = Widget::with_theme(Button::empty(), "off");
+        lighting_off.borrow_mut().state.add_callback(Callback::new(Rc::new(|widget, _| {
+            let (parent, options) = Widget::parent_mut::<Options>(widget);
+            options.cur_lighting = false;
+            parent.borrow_mut().invalidate_children();
+        })));
+        if self.cur_lighting {
+            lighting_on.borrow_mut().state.set_active(true);
+        } else {
+            lighting_off.borrow_mut().state.set_active(true);
+        }
+
+        let lighting_content = Widget::empty("lighting_content");
+        Widget::add_child_to(&lighting_content, lighting_on);
+        Widget::add_child_to(&lighting_content, lighting_off);
+
         let zoom_content = Widget::empty("default_zoom_content");
         let mut zoom_found = false;
         for zoom in DEFAULT_ZOOMS.iter() {
@@ -558,6 +693,52 @@ impl Options {
         let slow_label = Widget::with_theme(Label::empty(), "anim_speed_slow");
         let fast_label = Widget::with_theme(Label::empty(), "anim_speed_fast");
 
+        let feedback_title = Widget::with_theme(Label::empty(), "feedback_title");
+        let feedback_content = Widget::empty("feedback_content");
+        for channel in FeedbackChannel::iter() {
+            let channel = *channel;
+            let button = Widget::with_theme(Button::empty(), channel.to_str());
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, options) = Widget::parent_mut::<Options>(widget);
+                    let disabled = &mut options.cur_disabled_feedback;
+                    match disabled.iter().position(|c| *c == channel) {
+                        None => disabled.push(channel),
+                        Some(index) => {
+                            disabled.remove(index);
+                        }
+                    }
+                    parent.borrow_mut().invalidate_children();
+                })));
+            if !self.cur_disabled_feedback.contains(&channel) {
+                button.borrow_mut().state.set_active(true);
+            }
+
+            Widget::add_child_to(&feedback_content, button);
+        }
+
+        let autosave_title = Widget::with_theme(Label::empty(), "autosave_title");
+        let autosave_content = Widget::empty("autosave_content");
+        for frequency in AutosaveFrequency::iter() {
+            let frequency = *frequency;
+            let button = Widget::with_theme(Button::empty(), frequency.to_str());
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, options) = Widget::parent_mut::<Options>(widget);
+                    options.cur_autosave = frequency;
+                    parent.borrow_mut().invalidate_children();
+                })));
+            if frequency == self.cur_autosave {
+                button.borrow_mut().state.set_active(true);
+            }
+
+            Widget::add_child_to(&autosave_content, button);
+        }
+
         vec![
             screen_shake_content,
             slow_label,
@@ -566,9 +747,95 @@ impl Options {
             anim_speed_content,
             zoom_content,
             scroll_to_active_content,
+            fog_of_war_content,
+            lighting_content,
+            feedback_title,
+            feedback_content,
+            autosave_title,
+            autosave_content,
+        ]
+    }
+
+    fn add_difficulty_widgets(&mut self) -> Vec<Rc<RefCell<Widget>>> {
+        let difficulty_title = Widget::with_theme(Label::empty(), "difficulty_title");
+        let difficulty_content = Widget::empty("difficulty_content");
+        for level in DifficultyLevel::iter() {
+            let level = *level;
+            let button = Widget::with_theme(Button::empty(), level.to_str());
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, options) = Widget::parent_mut::<Options>(widget);
+                    let difficulty = &mut options.cur_difficulty;
+                    if level == DifficultyLevel::Custom && difficulty.level != level {
+                        // start custom values from the previously selected level
+                        difficulty.custom = difficulty.scaling();
+                    }
+                    difficulty.level = level;
+                    parent.borrow_mut().invalidate_children();
+                })));
+            if level == self.cur_difficulty.level {
+                button.borrow_mut().state.set_active(true);
+            }
+
+            Widget::add_child_to(&difficulty_content, button);
+        }
+
+        let scaling = self.cur_difficulty.scaling();
+        vec![
+            difficulty_title,
+            difficulty_content,
+            self.add_scaling_widget("enemy_hp", scaling.enemy_hp, |s, v| s.enemy_hp = v),
+            self.add_scaling_widget("enemy_damage", scaling.enemy_damage, |s, v| {
+                s.enemy_damage = v
+            }),
+            self.add_scaling_widget("xp", scaling.xp, |s, v| s.xp = v),
+            self.add_scaling_widget("merchant_prices", scaling.merchant_prices, |s, v| {
+                s.merchant_prices = v
+            }),
         ]
     }
 
+    /// Adds a row of buttons for one of the custom difficulty factors.  The
+    /// buttons are only enabled when the custom difficulty is selected
+    fn add_scaling_widget(
+        &self,
+        id: &str,
+        cur: f32,
+        setter: fn(&mut DifficultyScaling, f32),
+    ) -> Rc<RefCell<Widget>> {
+        let content = Widget::empty(&format!("{id}_scaling_content"));
+
+        let title = Widget::with_theme(Label::empty(), "title");
+        Widget::add_child_to(&content, title);
+
+        let custom = self.cur_difficulty.level == DifficultyLevel::Custom;
+        let buttons = Widget::empty("buttons");
+        for factor in SCALING_LEVELS.iter() {
+            let factor = *factor;
+            let button = Widget::with_theme(Button::empty(), "scaling_button");
+            button
+                .borrow_mut()
+                .state
+                .add_text_arg("factor", &format!("{factor}"));
+            button.borrow_mut().state.add_callback(Callback::new(Rc::new(move |widget, _| {
+                let (parent, options) = Widget::parent_mut::<Options>(widget);
+                setter(&mut options.cur_difficulty.custom, factor);
+                parent.borrow_mut().invalidate_children();
+            })));
+            if (factor - cur).abs() < f32::EPSILON {
+                button.borrow_mut().state.set_active(true);
+            }
+            button.borrow_mut().state.set_enabled(custom);
+
+            Widget::add_child_to(&buttons, button);
+        }
+        Widget::add_child_to(&content, buttons);
+
+        content
+    }
+
     fn add_input_widgets(&mut self) -> Vec<Rc<RefCell<Widget>>> {
         let scroll_speed_title = Widget::with_theme(Label::empty(), "scroll_speed_title");
 
@@ -671,14 +938,14 @@ impl Options {
 
         let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
         let keybindings_pane = Widget::with_theme(scrollpane.clone(), "keybindings_pane");
-        for (key, action) in self.cur_keybindings.iter() {
+        for (key, action) in self.cur_keybindings.sorted() {
             let key_button = Widget::with_theme(Button::empty(), "key_button");
             key_button
                 .borrow_mut()
                 .state
                 .add_text_arg("key", &format!("{key:?}"));
 
-            let action_ref = *action;
+            let action_ref = action;
             key_button
                 .borrow_mut()
                 .state
@@ -714,10 +981,11 @@ impl Options {
     }
 }
 
+const SCALING_LEVELS: [f32; 8] = [0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
 const VOLUME_LEVELS: [f32; 11] = [0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
 
-const UI_SCALE_NORMAL: (i32, i32) = (320, 180);
-const UI_SCALE_SMALL: (i32, i32) = (368, 207);
+const UI_SCALES: [f32; 4] = [1.0, 0.9, 0.8, 0.7];
 const ANIM_SPEEDS: [u32; 5] = [75, 50, 35, 25, 15];
 const DEFAULT_ZOOMS: [f32; 5] = [1.0, 1.2, 1.4, 1.6, 1.8];
 const SCROLL_SPEEDS: [f32; 7] = [0.75, 1.0, 1.5, 2.25, 3.5, 5.0, 7.0];
@@ -725,6 +993,15 @@ const SCROLL_SPEEDS: [f32; 7] = [0.75, 1.0, 1.5, 2.25, 3.5, 5.0, 7.0];
 impl WidgetKind for Options {
     widget_kind!("options_window");
 
+    fn on_key_press(&mut self, widget: &Rc<RefCell<Widget>>, key: InputActionKind) -> bool {
+        if self.main_menu_mode || key != InputActionKind::Back {
+            return false;
+        }
+
+        widget.borrow_mut().mark_for_removal();
+        true
+    }
+
     fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
         let title = Widget::with_theme(Label::empty(), "title");
 
@@ -734,10 +1011,9 @@ impl WidgetKind for Options {
             .state
             .add_callback(Callback::new(Rc::new(|widget, _| {
                 let (parent, options) = Widget::parent_mut::<Options>(widget);
+                options.apply_difficulty();
                 options.save_current_config();
-
-                let (_, menu) = Widget::parent_mut::<MainMenu>(&parent);
-                menu.recreate_io();
+                options.recreate_io(&parent);
             })));
 
         let reset = Widget::with_theme(Button::empty(), "reset");
@@ -747,9 +1023,7 @@ impl WidgetKind for Options {
             .add_callback(Callback::new(Rc::new(|widget, _| {
                 let (parent, options) = Widget::parent_mut::<Options>(widget);
                 options.reset_config();
-
-                let (_, menu) = Widget::parent_mut::<MainMenu>(&parent);
-                menu.recreate_io();
+                options.recreate_io(&parent);
             })));
 
         let cancel = Widget::with_theme(Button::empty(), "cancel");
@@ -757,6 +1031,12 @@ impl WidgetKind for Options {
             .borrow_mut()
             .state
             .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, options) = Widget::parent_mut::<Options>(widget);
+                if !options.main_menu_mode {
+                    parent.borrow_mut().mark_for_removal();
+                    return;
+                }
+
                 let (root, menu) = Widget::parent_mut::<MainMenu>(widget);
                 menu.reset();
                 root.borrow_mut().invalidate_children();
@@ -796,6 +1076,14 @@ impl WidgetKind for Options {
             parent.borrow_mut().invalidate_children();
         })));
 
+        let difficulty = Widget::with_theme(Button::empty(), "difficulty");
+        difficulty.borrow_mut().state.add_callback(Callback::new(Rc::new(|widget, _| {
+            let (parent, options) = Widget::parent_mut::<Options>(widget);
+            options.cur_tab = Tab::Difficulty;
+            parent.borrow_mut().invalidate_children();
+        })));
+        difficulty.borrow_mut().state.set_enabled(!self.main_menu_mode);
+
         let content = Widget::empty("content");
 
         let widgets = match self.cur_tab {
@@ -815,11 +1103,17 @@ impl WidgetKind for Options {
                 audio.borrow_mut().state.set_active(true);
                 self.add_audio_widgets()
             }
+            Tab::Difficulty => {
+                difficulty.borrow_mut().state.set_active(true);
+                self.add_difficulty_widgets()
+            }
         };
 
         Widget::add_children_to(&content, widgets);
 
-        vec![title, apply, cancel, reset, content, display, input, gameplay, audio]
+        vec![
+            title, apply, cancel, reset, content, display, input, gameplay, audio, difficulty,
+        ]
     }
 }
 
@@ -887,6 +1181,9 @@ impl WidgetKind for MousePopup {
 pub struct KeybindingPopup {
     action: InputActionKind,
     options_widget: Rc<RefCell<Widget>>,
+
+    // a key bound to another action, which must be pressed again to confirm
+    pending_conflict: Option<(Key, InputActionKind)>,
 }
 
 impl KeybindingPopup {
@@ -897,6 +1194,7 @@ impl KeybindingPopup {
         Rc::new(RefCell::new(KeybindingPopup {
             action,
             options_widget,
+            pending_conflict: None,
         }))
     }
 }
@@ -912,15 +1210,16 @@ impl WidgetKind for KeybindingPopup {
 
     fn on_raw_key(&mut self, widget: &Rc<RefCell<Widget>>, key: Key) -> bool {
         let options = Widget::kind_mut::<Options>(&self.options_widget);
-        let mut matched_index = 0;
-        for (index, (_, v)) in options.cur_keybindings.iter().enumerate() {
-            if *v == self.action {
-                matched_index = index;
-                break;
+        if let Some(conflict) = options.cur_keybindings.conflict(key, self.action) {
+            let confirmed = matches!(self.pending_conflict, Some((pending, _)) if pending == key);
+            if !confirmed {
+                self.pending_conflict = Some((key, conflict));
+                widget.borrow_mut().invalidate_children();
+                return false;
             }
         }
 
-        options.cur_keybindings[matched_index] = (key, self.action);
+        options.cur_keybindings.bind(key, self.action);
         self.options_widget.borrow_mut().invalidate_children();
         widget.borrow_mut().mark_for_removal();
         false
@@ -935,6 +1234,15 @@ impl WidgetKind for KeybindingPopup {
             .state
             .add_text_arg("action", &format!("{:?}", self.action));
 
-        vec![title]
+        let conflict = Widget::with_theme(Label::empty(), "conflict");
+        if let Some((key, action)) = self.pending_conflict {
+            let state = &mut conflict.borrow_mut().state;
+            state.add_text_arg("key", &format!("{key:?}"));
+            state.add_text_arg("action", &format!("{action:?}"));
+        } else {
+            conflict.borrow_mut().state.set_visible(false);
+        }
+
+        vec![title, conflict]
     }
 }
@@ -18,13 +18,15 @@ use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{main_menu::MainMenu, Button, Label};
+use crate::{main_menu::MainMenu, Button, Label, RootView};
 use sulis_core::config::{self, Config};
 use sulis_core::resource::write_to_file;
 use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_state::NextGameStep;
 
 pub struct SaveOrRevertOptionsWindow {
     old_config: Config,
+    main_menu_mode: bool,
     elapsed: u32,
     timer: Rc<RefCell<Widget>>,
 }
@@ -32,11 +34,15 @@ pub struct SaveOrRevertOptionsWindow {
 const TIMER_LEN: u32 = 10000;
 
 impl SaveOrRevertOptionsWindow {
-    pub fn new(old_config: Config) -> Rc<RefCell<SaveOrRevertOptionsWindow>> {
+    pub fn new(
+        old_config: Config,
+        main_menu_mode: bool,
+    ) -> Rc<RefCell<SaveOrRevertOptionsWindow>> {
         let timer = Widget::with_theme(Label::empty(), "timer");
         timer.borrow_mut().state.text = (TIMER_LEN / 1000).to_string();
         Rc::new(RefCell::new(SaveOrRevertOptionsWindow {
             old_config,
+            main_menu_mode,
             elapsed: 0,
             timer,
         }))
@@ -48,8 +54,14 @@ impl SaveOrRevertOptionsWindow {
         Config::set(config);
         Config::take_old_config(); // throw away reverted config
 
-        let (_, main_menu) = Widget::parent_mut::<MainMenu>(widget);
-        main_menu.recreate_io();
+        if self.main_menu_mode {
+            let (_, main_menu) = Widget::parent_mut::<MainMenu>(widget);
+            main_menu.recreate_io();
+        } else {
+            widget.borrow_mut().mark_for_removal();
+            let (_, view) = Widget::parent_mut::<RootView>(widget);
+            view.set_next_step(NextGameStep::RecreateWindow);
+        }
     }
 
     fn accept(&self, widget: &Rc<RefCell<Widget>>) {
@@ -0,0 +1,237 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::image::Image;
+use sulis_core::io::{event::ClickKind, DrawList, GraphicsRenderer};
+use sulis_core::resource::ResourceSet;
+use sulis_core::ui::{animation_state, Cursor, Widget, WidgetKind};
+use sulis_core::util::{Point, Rect};
+use sulis_core::widgets::Label;
+use sulis_state::GameState;
+
+use crate::area_view::{
+    AERIAL_LAYER_ID, BASE_LAYER_ID, TILE_CACHE_TEXTURE_SIZE, TILE_SIZE, VISIBILITY_TEX_ID,
+};
+use crate::AreaView;
+
+const NAME: &str = "minimap";
+
+/// A downscaled view of the current area, drawn from the same cached layer
+/// and visibility textures as the area view.  Shows the party, visible hostiles,
+/// and explored transitions.  Clicking scrolls the camera to that point.  For
+/// procedurally generated areas, the seed the area was generated from is shown
+/// so the same layout may be generated again.
+pub struct Minimap {
+    area_view: Rc<RefCell<AreaView>>,
+    area_view_widget: Rc<RefCell<Widget>>,
+    party_marker: Option<Rc<dyn Image>>,
+    hostile_marker: Option<Rc<dyn Image>>,
+    transition_marker: Option<Rc<dyn Image>>,
+    view_marker: Option<Rc<dyn Image>>,
+    marker_size: f32,
+}
+
+impl Minimap {
+    pub fn new(
+        area_view: Rc<RefCell<AreaView>>,
+        area_view_widget: Rc<RefCell<Widget>>,
+    ) -> Rc<RefCell<Minimap>> {
+        Rc::new(RefCell::new(Minimap {
+            area_view,
+            area_view_widget,
+            party_marker: None,
+            hostile_marker: None,
+            transition_marker: None,
+            view_marker: None,
+            marker_size: 1.0,
+        }))
+    }
+
+    /// Returns the offset of the area within the widget and the size of
+    /// one tile, so that the whole area fits centered in the widget
+    fn area_transform(widget: &Widget, area_w: i32, area_h: i32) -> (f32, f32, f32) {
+        let w = widget.state.inner_width() as f32;
+        let h = widget.state.inner_height() as f32;
+        let tile = (w / area_w as f32).min(h / area_h as f32);
+
+        let x = widget.state.inner_left() as f32 + (w - tile * area_w as f32) / 2.0;
+        let y = widget.state.inner_top() as f32 + (h - tile * area_h as f32) / 2.0;
+        (x, y, tile)
+    }
+
+    fn draw_marker(
+        &self,
+        renderer: &mut dyn GraphicsRenderer,
+        image: &Option<Rc<dyn Image>>,
+        rect: Rect,
+        millis: u32,
+    ) {
+        let image = match image {
+            None => return,
+            Some(ref image) => image,
+        };
+
+        let mut draw_list = DrawList::empty_sprite();
+        image.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+        renderer.draw(draw_list);
+    }
+}
+
+impl WidgetKind for Minimap {
+    widget_kind!(NAME);
+
+    fn layout(&mut self, widget: &mut Widget) {
+        let theme = &widget.theme;
+        self.marker_size = theme.get_custom_or_default("marker_size", 1.0);
+
+        let image = |id: &str| theme.custom.get(id).and_then(|id| ResourceSet::image(id));
+        self.party_marker = image("party_marker");
+        self.hostile_marker = image("hostile_marker");
+        self.transition_marker = image("transition_marker");
+        self.view_marker = image("view_marker");
+
+        widget.do_base_layout();
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let seed = Widget::with_theme(Label::empty(), "seed");
+
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        if area_state.area.area.generator.is_some() {
+            let text = area_state.area_gen_seed.to_string();
+            seed.borrow_mut().state.add_text_arg("seed", &text);
+        } else {
+            seed.borrow_mut().state.set_visible(false);
+        }
+
+        vec![seed]
+    }
+
+    fn draw(
+        &mut self,
+        renderer: &mut dyn GraphicsRenderer,
+        _pixel_size: Point,
+        widget: &Widget,
+        millis: u32,
+    ) {
+        // the cached textures are created by the area view on its first draw
+        if !renderer.has_texture(BASE_LAYER_ID) {
+            return;
+        }
+
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        let (area_w, area_h) = (area_state.area.width, area_state.area.height);
+        if area_w == 0 || area_h == 0 {
+            return;
+        }
+
+        let (x, y, tile) = Minimap::area_transform(widget, area_w, area_h);
+
+        let tex_tiles = (TILE_CACHE_TEXTURE_SIZE / TILE_SIZE) as f32;
+        let (fx, fy) = (area_w as f32 / tex_tiles, area_h as f32 / tex_tiles);
+        let tex_coords = [0.0, 1.0, 0.0, 1.0 - fy, fx, 1.0, fx, 1.0 - fy];
+        let rect = Rect {
+            x,
+            y,
+            w: tile * area_w as f32,
+            h: tile * area_h as f32,
+        };
+        for id in [BASE_LAYER_ID, AERIAL_LAYER_ID, VISIBILITY_TEX_ID].iter() {
+            renderer.draw(DrawList::from_texture_id(id, &tex_coords, rect));
+        }
+
+        let size = tile * self.marker_size;
+        let marker_rect = |px: f32, py: f32, w: f32, h: f32| Rect {
+            x: x + px * tile + (w * tile - size) / 2.0,
+            y: y + py * tile + (h * tile - size) / 2.0,
+            w: size,
+            h: size,
+        };
+
+        for transition in area_state.area.transitions.iter() {
+            let (tx, ty) = (transition.from.x, transition.from.y);
+            if !area_state.area.area.coords_valid(tx, ty) || !area_state.is_pc_explored(tx, ty) {
+                continue;
+            }
+
+            let (w, h) = (transition.size.width as f32, transition.size.height as f32);
+            let rect = marker_rect(tx as f32, ty as f32, w, h);
+            self.draw_marker(renderer, &self.transition_marker, rect, millis);
+        }
+
+        let player = GameState::player();
+        let mgr = GameState::turn_manager();
+        let mgr = mgr.borrow();
+        for index in area_state.entity_iter() {
+            let entity = mgr.entity(*index);
+            let entity = entity.borrow();
+
+            let (ex, ey) = (entity.location.x, entity.location.y);
+            let (w, h) = (entity.size.width as f32, entity.size.height as f32);
+            let rect = marker_rect(ex as f32, ey as f32, w, h);
+
+            if entity.is_party_member() {
+                self.draw_marker(renderer, &self.party_marker, rect, millis);
+            } else if entity.is_hostile(&player.borrow()) && area_state.is_pc_visible(ex, ey) {
+                self.draw_marker(renderer, &self.hostile_marker, rect, millis);
+            }
+        }
+
+        let area_view = self.area_view.borrow();
+        let scroll = area_view.get_scroll();
+        let (scale_x, scale_y) = area_view.scale();
+        let view = &self.area_view_widget.borrow().state;
+        let view_w = (view.inner_width() as f32 / scale_x).min(area_w as f32);
+        let view_h = (view.inner_height() as f32 / scale_y).min(area_h as f32);
+        let rect = Rect {
+            x: x + scroll.x() * tile,
+            y: y + scroll.y() * tile,
+            w: view_w * tile,
+            h: view_h * tile,
+        };
+        self.draw_marker(renderer, &self.view_marker, rect, millis);
+    }
+
+    fn on_mouse_release(&mut self, widget: &Rc<RefCell<Widget>>, kind: ClickKind) -> bool {
+        self.super_on_mouse_release(widget, kind);
+        if kind != ClickKind::Primary {
+            return true;
+        }
+
+        let area_state = GameState::area_state();
+        let mut area_state = area_state.borrow_mut();
+        let (area_w, area_h) = (area_state.area.width, area_state.area.height);
+        if area_w == 0 || area_h == 0 {
+            return true;
+        }
+
+        let (x, y, tile) = Minimap::area_transform(&widget.borrow(), area_w, area_h);
+        let tile_x = (Cursor::get_x_f32() - x) / tile;
+        let tile_y = (Cursor::get_y_f32() - y) / tile;
+        if tile_x < 0.0 || tile_y < 0.0 || tile_x >= area_w as f32 || tile_y >= area_h as f32 {
+            return true;
+        }
+
+        area_state.camera().scroll_to(tile_x, tile_y, Some(300));
+        true
+    }
+}
@@ -0,0 +1,239 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Generic modal dialogs - simple confirmations, text prompts, and choices
+//! from a list.  Dialogs are built with one of the `ModalDialog` constructors
+//! and then shown with `show`, which adds the dialog to the root widget as
+//! the modal child.  While the dialog is open, `GameState::is_modal_locked`
+//! is set, so the AI and other game updates wait for the player's response.
+//!
+//! ```ignore
+//! let dialog = ModalDialog::confirm("Drop the item?", Rc::new(|_dialog| {
+//!     // drop the item
+//! }));
+//! dialog.borrow_mut().set_accept_text("Drop");
+//! modal_dialog::show(widget, dialog);
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, InputField, TextArea};
+use sulis_state::GameState;
+
+pub const NAME: &str = "modal_dialog";
+
+/// Called with the dialog widget when a confirmation is accepted
+pub type AcceptCallback = Rc<dyn Fn(&Rc<RefCell<Widget>>)>;
+
+/// Called with the dialog widget and the entered text when a text prompt
+/// is accepted
+pub type TextCallback = Rc<dyn Fn(&Rc<RefCell<Widget>>, &str)>;
+
+/// Called with the dialog widget and the index of the selected choice
+pub type ChoiceCallback = Rc<dyn Fn(&Rc<RefCell<Widget>>, usize)>;
+
+enum Kind {
+    Confirm(AcceptCallback),
+    TextInput(Rc<RefCell<InputField>>, TextCallback),
+    Choice(Vec<String>, ChoiceCallback),
+}
+
+pub struct ModalDialog {
+    kind: Kind,
+    message: String,
+    accept_text: Option<String>,
+    cancel_text: Option<String>,
+    on_cancel: Option<AcceptCallback>,
+    input_widget: Option<Rc<RefCell<Widget>>>,
+}
+
+impl ModalDialog {
+    fn new(kind: Kind, message: &str) -> Rc<RefCell<ModalDialog>> {
+        Rc::new(RefCell::new(ModalDialog {
+            kind,
+            message: message.to_string(),
+            accept_text: None,
+            cancel_text: None,
+            on_cancel: None,
+            input_widget: None,
+        }))
+    }
+
+    /// A dialog showing `message`, with accept and cancel buttons
+    pub fn confirm(message: &str, on_accept: AcceptCallback) -> Rc<RefCell<ModalDialog>> {
+        ModalDialog::new(Kind::Confirm(on_accept), message)
+    }
+
+    /// A dialog showing `message` and a text field, initially containing `text`
+    pub fn text_input(
+        message: &str,
+        text: &str,
+        on_accept: TextCallback,
+    ) -> Rc<RefCell<ModalDialog>> {
+        ModalDialog::new(Kind::TextInput(InputField::new(text), on_accept), message)
+    }
+
+    /// A dialog showing `message` and a button for each of the `choices`
+    pub fn choice(
+        message: &str,
+        choices: Vec<String>,
+        on_select: ChoiceCallback,
+    ) -> Rc<RefCell<ModalDialog>> {
+        ModalDialog::new(Kind::Choice(choices, on_select), message)
+    }
+
+    /// Overrides the theme text of the accept button
+    pub fn set_accept_text(&mut self, text: &str) {
+        self.accept_text = Some(text.to_string());
+    }
+
+    /// Overrides the theme text of the cancel button
+    pub fn set_cancel_text(&mut self, text: &str) {
+        self.cancel_text = Some(text.to_string());
+    }
+
+    /// Sets a callback that is called when the dialog is cancelled.  By
+    /// default, cancelling simply closes the dialog
+    pub fn set_cancel_callback(&mut self, on_cancel: AcceptCallback) {
+        self.on_cancel = Some(on_cancel);
+    }
+
+    fn accept(&self, dialog: &Rc<RefCell<Widget>>, text: Option<String>) {
+        dialog.borrow_mut().mark_for_removal();
+        match &self.kind {
+            Kind::Confirm(cb) => cb(dialog),
+            Kind::TextInput(input, cb) => {
+                let text = text.unwrap_or_else(|| input.borrow().text());
+                cb(dialog, &text);
+            }
+            Kind::Choice(..) => (),
+        }
+    }
+
+    fn cancel(&self, dialog: &Rc<RefCell<Widget>>) {
+        dialog.borrow_mut().mark_for_removal();
+        if let Some(cb) = &self.on_cancel {
+            cb(dialog);
+        }
+    }
+}
+
+/// Shows the specified dialog as the modal child of the root of `parent`
+pub fn show(parent: &Rc<RefCell<Widget>>, dialog: Rc<RefCell<ModalDialog>>) {
+    let root = Widget::get_root(parent);
+    let widget = Widget::with_defaults(dialog);
+    widget.borrow_mut().state.set_modal(true);
+    Widget::add_child_to(&root, widget);
+    GameState::set_modal_locked(true);
+}
+
+impl WidgetKind for ModalDialog {
+    widget_kind!(NAME);
+
+    fn update(&mut self, _widget: &Rc<RefCell<Widget>>, _millis: u32) {
+        if let Some(input) = self.input_widget.take() {
+            Widget::grab_keyboard_focus(&input);
+        }
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let message = Widget::with_theme(TextArea::empty(), "message");
+        message
+            .borrow_mut()
+            .state
+            .add_text_arg("message", &self.message);
+
+        let mut children = vec![message];
+
+        match &self.kind {
+            Kind::Confirm(_) => (),
+            Kind::TextInput(input, _) => {
+                input
+                    .borrow_mut()
+                    .set_enter_callback(Callback::new(Rc::new(|widget, kind| {
+                        let text = Widget::downcast::<InputField>(kind).text();
+                        let (parent, dialog) = Widget::parent::<ModalDialog>(widget);
+                        dialog.accept(&parent, Some(text));
+                    })));
+                let input = Widget::with_theme(input.clone(), "input");
+                self.input_widget = Some(Rc::clone(&input));
+                children.push(input);
+            }
+            Kind::Choice(choices, on_select) => {
+                let list = Widget::empty("choices");
+                for (index, choice) in choices.iter().enumerate() {
+                    let button = Widget::with_theme(Button::empty(), "choice");
+                    button.borrow_mut().state.add_text_arg("text", choice);
+
+                    let cb = Rc::clone(on_select);
+                    button
+                        .borrow_mut()
+                        .state
+                        .add_callback(Callback::new(Rc::new(move |widget, _| {
+                            let (parent, _) = Widget::parent::<ModalDialog>(widget);
+                            parent.borrow_mut().mark_for_removal();
+                            cb(&parent, index);
+                        })));
+                    Widget::add_child_to(&list, button);
+                }
+                children.push(list);
+            }
+        }
+
+        let buttons = Widget::empty("buttons");
+        let cancel = Widget::with_theme(Button::empty(), "cancel");
+        let text = match &self.cancel_text {
+            None => tr!("dialog.cancel"),
+            Some(text) => text.to_string(),
+        };
+        cancel.borrow_mut().state.add_text_arg("text", &text);
+        cancel
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, dialog) = Widget::parent::<ModalDialog>(widget);
+                dialog.cancel(&parent);
+            })));
+        Widget::add_child_to(&buttons, cancel);
+
+        match &self.kind {
+            Kind::Confirm(_) | Kind::TextInput(..) => {
+                let accept = Widget::with_theme(Button::empty(), "accept");
+                let text = match &self.accept_text {
+                    None => tr!("dialog.accept"),
+                    Some(text) => text.to_string(),
+                };
+                accept.borrow_mut().state.add_text_arg("text", &text);
+                accept
+                    .borrow_mut()
+                    .state
+                    .add_callback(Callback::new(Rc::new(|widget, _| {
+                        let (parent, dialog) = Widget::parent::<ModalDialog>(widget);
+                        dialog.accept(&parent, None);
+                    })));
+                Widget::add_child_to(&buttons, accept);
+            }
+            Kind::Choice(..) => (),
+        }
+
+        children.push(buttons);
+
+        children
+    }
+}
@@ -19,7 +19,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::io::event;
-use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::ui::{Callback, Cursor, Widget, WidgetKind};
 use sulis_core::widgets::{Button, Label, ProgressBar};
 use sulis_state::{ChangeListener, EntityState, GameState};
 
@@ -109,6 +109,38 @@ impl WidgetKind for PortraitView {
             .state
             .set_enabled(!GameState::is_combat_active());
 
+        let entity_ref = Rc::clone(&self.entity);
+        let auto_combat = Widget::with_theme(Button::empty(), "auto_combat");
+        auto_combat
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(move |widget, _| {
+                let auto = !entity_ref.borrow().is_auto_combat();
+                entity_ref.borrow_mut().set_auto_combat(auto);
+
+                let parent = Widget::direct_parent(widget);
+                parent.borrow_mut().invalidate_children();
+            })));
+        auto_combat
+            .borrow_mut()
+            .state
+            .set_active(entity.is_auto_combat());
+
+        let entity_ref = Rc::clone(&self.entity);
+        let weapon_set = Widget::with_theme(Button::empty(), "weapon_set");
+        weapon_set
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(move |_, _| {
+                EntityState::swap_weapon_set(&entity_ref);
+            })));
+        {
+            let state = &mut weapon_set.borrow_mut().state;
+            let set = entity.actor.inventory().weapon_set() + 1;
+            state.add_text_arg("set", &set.to_string());
+            state.set_enabled(entity.actor.can_swap_weapons());
+        }
+
         widget
             .borrow_mut()
             .state
@@ -140,7 +172,15 @@ impl WidgetKind for PortraitView {
             Widget::add_child_to(&icons, icon_widget);
         }
 
-        vec![portrait, hp_bar, class_stat_bar, level_up, icons]
+        vec![
+            portrait,
+            hp_bar,
+            class_stat_bar,
+            level_up,
+            auto_combat,
+            weapon_set,
+            icons,
+        ]
     }
 
     fn on_mouse_enter(&mut self, widget: &Rc<RefCell<Widget>>) -> bool {
@@ -168,6 +208,8 @@ impl WidgetKind for PortraitView {
         if let Some(targeter) = targeter {
             let mut targeter = targeter.borrow_mut();
             targeter.on_activate();
+        } else if Cursor::is_shift_down() {
+            GameState::toggle_selected_party_member(Rc::clone(&self.entity));
         } else {
             GameState::set_selected_party_member(Rc::clone(&self.entity));
         }
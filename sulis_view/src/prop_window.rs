@@ -18,7 +18,7 @@ use std::any::Any;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
-use crate::{item_list_pane::Filter, ItemListPane, RootView};
+use crate::{item_callback_handler, item_list_pane::Filter, ItemListPane, RootView};
 use sulis_core::ui::{Callback, Widget, WidgetKind};
 use sulis_core::widgets::{Button, Label};
 use sulis_state::{ChangeListener, EntityState, GameState};
@@ -109,8 +109,7 @@ impl WidgetKind for PropWindow {
                     let (parent, _) = Widget::parent::<PropWindow>(widget);
                     parent.borrow_mut().mark_for_removal();
 
-                    let stash = GameState::party_stash();
-                    stash.borrow_mut().take_all(prop_index);
+                    item_callback_handler::take_all(prop_index);
 
                     let (root, view) = Widget::parent_mut::<RootView>(&parent);
                     view.set_inventory_window(&root, false);
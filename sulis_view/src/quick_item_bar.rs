@@ -144,6 +144,11 @@ impl WidgetKind for QuickItemBar {
         ));
 
         let title = Widget::with_theme(Label::empty(), "title");
+        if GameState::is_combat_active() {
+            if let Some(uses) = self.entity.borrow().actor.quick_item_uses_remaining() {
+                title.borrow_mut().state.add_text_arg("uses", &uses.to_string());
+            }
+        }
 
         let swap_weapons = Widget::with_theme(Button::empty(), "swap_weapons");
         {
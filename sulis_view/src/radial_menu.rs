@@ -0,0 +1,137 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use sulis_core::io::{event::ClickKind, InputActionKind};
+use sulis_core::ui::{Callback, Cursor, Widget, WidgetKind};
+use sulis_core::widgets::{Button, Label};
+use sulis_state::{ability_state::DisabledReason, EntityState};
+
+use crate::abilities_bar::activate_ability;
+
+pub const NAME: &str = "radial_menu";
+
+/// A ring of ability buttons for the specified entity, opened around the
+/// cursor.  This allows abilities to be selected with only a few small stick
+/// movements when using a gamepad.
+pub struct RadialMenu {
+    entity: Rc<RefCell<EntityState>>,
+    center: (i32, i32),
+    buttons: Vec<Rc<RefCell<Widget>>>,
+}
+
+impl RadialMenu {
+    pub fn new(entity: &Rc<RefCell<EntityState>>) -> Rc<RefCell<RadialMenu>> {
+        Rc::new(RefCell::new(RadialMenu {
+            entity: Rc::clone(entity),
+            center: Cursor::get_position(),
+            buttons: Vec::new(),
+        }))
+    }
+}
+
+impl WidgetKind for RadialMenu {
+    widget_kind!(NAME);
+
+    fn layout(&mut self, widget: &mut Widget) {
+        let radius: f32 = widget.theme.get_custom_or_default("radius", 20.0);
+
+        widget.do_self_layout();
+        let (x, y) = self.center;
+        widget.state.set_position_centered(x, y);
+        widget.do_children_layout();
+
+        let center_x = widget.state.inner_left() + widget.state.inner_width() / 2;
+        let center_y = widget.state.inner_top() + widget.state.inner_height() / 2;
+        let count = self.buttons.len() as f32;
+        for (index, button) in self.buttons.iter().enumerate() {
+            // start at the top and proceed clockwise
+            let angle = 2.0 * PI * index as f32 / count - PI / 2.0;
+            let x = center_x + (radius * angle.cos()).round() as i32;
+            let y = center_y + (radius * angle.sin()).round() as i32;
+            button.borrow_mut().state.set_position_centered(x, y);
+        }
+
+        widget.do_children_layout();
+    }
+
+    fn on_key_press(&mut self, widget: &Rc<RefCell<Widget>>, key: InputActionKind) -> bool {
+        match key {
+            InputActionKind::Back | InputActionKind::RadialMenu => {
+                widget.borrow_mut().mark_for_removal();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn on_mouse_release(&mut self, widget: &Rc<RefCell<Widget>>, _kind: ClickKind) -> bool {
+        widget.borrow_mut().mark_for_removal();
+        true
+    }
+
+    fn on_add(&mut self, widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        widget.borrow_mut().state.set_modal(true);
+
+        let name = Widget::with_theme(Label::empty(), "name");
+        name.borrow_mut()
+            .state
+            .add_text_arg("name", &self.entity.borrow().actor.actor.name);
+
+        self.buttons.clear();
+        let abilities = self.entity.borrow().actor.actor.abilities.clone();
+        for owned in abilities {
+            let ability = owned.ability;
+            if ability.active.is_none() {
+                continue;
+            }
+
+            let enabled = {
+                let actor = &self.entity.borrow().actor;
+                actor.can_activate(&ability.id)
+                    || actor.can_toggle(&ability.id) == DisabledReason::Enabled
+            };
+
+            let button = Widget::with_theme(Button::empty(), "ability_button");
+            {
+                let state = &mut button.borrow_mut().state;
+                state.add_text_arg("icon", &ability.icon.id());
+                state.add_text_arg("name", &ability.name);
+                state.set_enabled(enabled);
+            }
+
+            let entity = Rc::clone(&self.entity);
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, _) = Widget::parent::<RadialMenu>(widget);
+                    parent.borrow_mut().mark_for_removal();
+                    activate_ability(&entity, &ability);
+                })));
+
+            self.buttons.push(button);
+        }
+
+        let mut children = vec![name];
+        children.extend(self.buttons.iter().cloned());
+        children
+    }
+}
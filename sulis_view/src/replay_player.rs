@@ -0,0 +1,209 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::Widget;
+use sulis_module::Module;
+use sulis_state::{replay, EntityState, GameState, ReplayCommand};
+
+use crate::item_callback_handler as items;
+use crate::{
+    abilities_bar, action_kind, character_builder, dialog_window, world_map_window, RootView,
+};
+
+/// Executes any replay commands that are due before the next game update.
+/// Does nothing unless a replay is being played back.  This must be called
+/// from outside the widget tree, as it borrows the `RootView`
+pub fn play_commands(root: &Rc<RefCell<Widget>>) {
+    for command in replay::take_commands() {
+        debug!("Replaying {:?}", command);
+        execute(root, command);
+    }
+}
+
+fn entity(index: usize) -> Option<Rc<RefCell<EntityState>>> {
+    let entity = GameState::turn_manager().borrow().entity_checked(index);
+    if entity.is_none() {
+        warn!("Replay references invalid entity {}", index);
+    }
+    entity
+}
+
+fn execute(root: &Rc<RefCell<Widget>>, command: ReplayCommand) {
+    use ReplayCommand::*;
+    match command {
+        Select { indices } => {
+            let members = indices.into_iter().filter_map(entity).collect();
+            GameState::select_party_members(members);
+        }
+        Action { x, y } => {
+            action_kind::fire_or_queue_action(x, y, root);
+        }
+        Target { x, y } => {
+            let targeter = GameState::area_state().borrow_mut().targeter();
+            match targeter {
+                None => warn!("Replay targeter activation with no active targeter"),
+                Some(targeter) => {
+                    let mut targeter = targeter.borrow_mut();
+                    targeter.on_mouse_move(x, y);
+                    targeter.on_activate();
+                }
+            }
+        }
+        CancelTarget => {
+            let targeter = GameState::area_state().borrow_mut().targeter();
+            if let Some(targeter) = targeter {
+                targeter.borrow_mut().on_cancel();
+            }
+        }
+        Ability {
+            entity: index,
+            ability,
+        } => {
+            let ability = match Module::ability(&ability) {
+                None => {
+                    warn!("Replay references invalid ability '{}'", ability);
+                    return;
+                }
+                Some(ability) => ability,
+            };
+
+            if let Some(entity) = entity(index) {
+                abilities_bar::activate_ability(&entity, &ability);
+            }
+        }
+        UseItem {
+            entity: index,
+            item,
+        } => {
+            if let Some(entity) = entity(index) {
+                items::use_item(&entity, item);
+            }
+        }
+        EndTurn => {
+            let (_, view) = Widget::parent_mut::<RootView>(root);
+            view.end_turn();
+        }
+        Response { index } => dialog_window::select_response(root, index),
+        TakeItem { prop, index, one } => items::take_item(prop, index, one),
+        TakeAll { prop } => items::take_all(prop),
+        StoreItem { index } => items::store_item(index),
+        RetrieveItem { index } => items::retrieve_item(index),
+        SplitStack { index } => items::split_stack(index),
+        EquipItem { entity: i, index } => {
+            if let Some(entity) = entity(i) {
+                items::equip_item(&entity, index);
+            }
+        }
+        UnequipItem { entity: i, slot } => {
+            if let Some(entity) = entity(i) {
+                items::unequip_item(&entity, slot);
+            }
+        }
+        DropItem {
+            entity: i,
+            index,
+            prop,
+        } => {
+            if let Some(entity) = entity(i) {
+                items::drop_item(&entity, index, prop);
+            }
+        }
+        UnequipAndDropItem {
+            entity: i,
+            slot,
+            prop,
+        } => {
+            if let Some(entity) = entity(i) {
+                items::unequip_and_drop_item(&entity, slot, prop);
+            }
+        }
+        SetQuickSlot { entity: i, index } => {
+            if let Some(entity) = entity(i) {
+                items::set_quickslot(&entity, index);
+            }
+        }
+        ClearQuickSlot { entity: i, slot } => {
+            if let Some(entity) = entity(i) {
+                items::clear_quickslot(&entity, slot);
+            }
+        }
+        BuyItem {
+            merchant,
+            index,
+            stack,
+        } => {
+            if stack {
+                items::buy_stack(&merchant, index);
+            } else {
+                items::buy_item(&merchant, index);
+            }
+        }
+        SellItem {
+            merchant,
+            index,
+            stack,
+        } => {
+            // the seller is only notified to refresh the inventory view
+            let seller = GameState::player();
+            items::sell_item(&seller, &merchant, index, stack);
+        }
+        RepairItem {
+            entity: i,
+            merchant,
+            slot,
+        } => {
+            if let Some(entity) = entity(i) {
+                items::repair_item(&entity, &merchant, slot);
+            }
+        }
+        Rest => {
+            let (_, view) = Widget::parent_mut::<RootView>(root);
+            view.rest_confirmed();
+        }
+        Travel { location, hours } => world_map_window::travel(root, &location, hours),
+        LevelUp {
+            entity: i,
+            class,
+            abilities,
+            attributes,
+        } => {
+            let class = match Module::class(&class) {
+                None => {
+                    warn!("Replay references invalid class '{}'", class);
+                    return;
+                }
+                Some(class) => class,
+            };
+            let abilities = abilities
+                .iter()
+                .filter_map(|id| {
+                    let ability = Module::ability(id);
+                    if ability.is_none() {
+                        warn!("Replay references invalid ability '{}'", id);
+                    }
+                    ability
+                })
+                .collect();
+
+            if let Some(entity) = entity(i) {
+                character_builder::level_up(&entity, class, abilities, attributes, root);
+            }
+        }
+    }
+}
@@ -15,27 +15,34 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Weak;
 use std::{any::Any, cell::RefCell, rc::Rc, time::Instant};
 
 use crate::{
-    character_window, formation_window, inventory_window, merchant_window, prop_window,
-    quest_window, world_map_window, AbilitiesBar, ApBar, AreaView, CharacterWindow, ConsoleWindow,
-    FormationWindow, GameOverWindow, InGameMenu, InitiativeTicker, InventoryWindow, MerchantWindow,
-    PortraitPane, PropWindow, QuestWindow, QuickItemBar, WorldMapWindow,
+    character_window, formation_window, inventory_window, merchant_window, modal_dialog,
+    prop_window, quest_window, radial_menu, script_error_window, storage_window, world_map_window,
+    AbilitiesBar, ApBar, AreaView, CharacterWindow, ConsoleWindow, FormationWindow, GameOverWindow,
+    InGameMenu, InitiativeTicker, InventoryWindow, MerchantWindow, Minimap, ModalDialog,
+    PortraitPane, PropWindow, QuestWindow, QuickItemBar, RadialMenu, ScriptErrorWindow,
+    StorageWindow, WorldMapWindow,
 };
-use sulis_core::config::Config;
-use sulis_core::io::{keyboard_event::Key, InputActionKind};
+use sulis_core::config::{AutosaveFrequency, Config};
+use sulis_core::io::{self, keyboard_event::Key, DisplayConfiguration, InputActionKind};
 use sulis_core::ui::{Callback, Cursor, Scrollable, Widget, WidgetKind};
 use sulis_core::util;
 use sulis_core::widgets::{Button, ConfirmationWindow, Label};
-use sulis_module::{area::OnRest, Module};
+use sulis_module::{area::OnRest, Module, OnTrigger};
 use sulis_state::{
-    area_feedback_text::ColorKind, save_file::create_save, script::script_callback,
-    script::ScriptEntity, AreaFeedbackText, ChangeListener, EntityState, GameState, NextGameStep,
-    Script,
+    area_feedback_text::ColorKind, replay, save_file::abandon_ironman_save,
+    save_file::attach_thumbnail, save_file::create_autosave, save_file::create_ironman_save,
+    save_file::create_save, save_file::delete_ironman_save,
+    save_file::remove_unreferenced_area_saves, script::script_cache, script::script_callback,
+    script::take_aborted_scripts, script::ScriptEntity, AreaFeedbackText, AreaState,
+    ChangeListener, EntityState, GameState, NextGameStep, ReplayCommand, RestResult, Script,
 };
 
-const WINDOW_NAMES: [&str; 7] = [
+const WINDOW_NAMES: [&str; 9] = [
     self::formation_window::NAME,
     self::inventory_window::NAME,
     self::character_window::NAME,
@@ -43,6 +50,8 @@ const WINDOW_NAMES: [&str; 7] = [
     self::world_map_window::NAME,
     self::merchant_window::NAME,
     self::prop_window::NAME,
+    self::storage_window::NAME,
+    self::script_error_window::NAME,
 ];
 
 const NAME: &str = "game";
@@ -59,6 +68,13 @@ pub struct RootView {
     quick_item_bar: Option<Rc<RefCell<Widget>>>,
     abilities_bar: Option<Rc<RefCell<Widget>>>,
     area: String,
+    area_state: Weak<RefCell<AreaState>>,
+    display_confs: Vec<DisplayConfiguration>,
+    autosave_elapsed: u32,
+    /// A save written without its thumbnail, which is attached once the
+    /// screenshot has been captured
+    pending_thumbnail: Option<PathBuf>,
+    was_combat_active: bool,
 
     scroll_keys_down: Vec<InputActionKind>,
 }
@@ -84,7 +100,11 @@ impl RootView {
         self.status_added = Some(Instant::now());
     }
 
-    pub fn new() -> Rc<RefCell<RootView>> {
+    pub fn display_configurations(&self) -> Vec<DisplayConfiguration> {
+        self.display_confs.clone()
+    }
+
+    pub fn new(display_confs: Vec<DisplayConfiguration>) -> Rc<RefCell<RootView>> {
         let area_view = AreaView::new(Scrollable::default());
         let area_view_widget = Widget::with_defaults(area_view.clone());
 
@@ -98,6 +118,11 @@ impl RootView {
             area_view,
             area_view_widget,
             area: "".to_string(),
+            area_state: Weak::new(),
+            display_confs,
+            autosave_elapsed: 0,
+            pending_thumbnail: None,
+            was_combat_active: false,
             console,
             console_widget,
             quick_item_bar: None,
@@ -116,6 +141,37 @@ impl RootView {
         Widget::get_child_with_name(widget, prop_window::NAME).map(|w| Rc::clone(&w))
     }
 
+    /// Gets the party storage window if it is currently opened
+    pub fn get_storage_window(&self, widget: &Rc<RefCell<Widget>>) -> Option<Rc<RefCell<Widget>>> {
+        Widget::get_child_with_name(widget, storage_window::NAME).map(|w| Rc::clone(&w))
+    }
+
+    /// Returns true if the party storage may currently be accessed, either
+    /// because the party is able to camp or a merchant window is open
+    pub fn can_access_storage(&self, widget: &Rc<RefCell<Widget>>) -> bool {
+        if GameState::is_combat_active() {
+            return false;
+        }
+
+        GameState::is_at_camp() || self.get_merchant_window(widget).is_some()
+    }
+
+    pub fn set_storage_window(&mut self, widget: &Rc<RefCell<Widget>>, desired_state: bool) {
+        if desired_state && !self.can_access_storage(widget) {
+            self.add_status_text(&tr!("status.storage_unavailable"));
+            return;
+        }
+
+        self.set_window(widget, self::storage_window::NAME, desired_state, &|| {
+            match GameState::selected().first() {
+                None => None,
+                Some(entity) => Some(StorageWindow::new(Rc::clone(entity))),
+            }
+        });
+
+        self.set_inventory_window(widget, desired_state);
+    }
+
     pub fn set_merchant_window(
         &mut self,
         widget: &Rc<RefCell<Widget>>,
@@ -246,11 +302,33 @@ impl RootView {
         self.set_formation_window(widget, desired_state);
     }
 
+    pub fn toggle_radial_menu(&mut self, widget: &Rc<RefCell<Widget>>) {
+        if let Some(menu) = Widget::get_child_with_name(widget, self::radial_menu::NAME) {
+            menu.borrow_mut().mark_for_removal();
+            return;
+        }
+
+        let entity = match GameState::selected().first() {
+            None => return,
+            Some(entity) => Rc::clone(entity),
+        };
+
+        let menu = Widget::with_defaults(RadialMenu::new(&entity));
+        Widget::add_child_to(widget, menu);
+    }
+
     pub fn toggle_console_window(&mut self, widget: &Rc<RefCell<Widget>>) {
         let desired_state = !self.console_widget.borrow().state.is_visible();
         self.set_console_window(widget, desired_state);
     }
 
+    pub fn toggle_script_error_window(&mut self, widget: &Rc<RefCell<Widget>>) {
+        let desired_state = !Widget::has_child_with_name(widget, self::script_error_window::NAME);
+        self.set_window(widget, self::script_error_window::NAME, desired_state, &|| {
+            Some(ScriptErrorWindow::new())
+        });
+    }
+
     pub fn toggle_inventory_window(&mut self, widget: &Rc<RefCell<Widget>>) {
         let desired_state = !Widget::has_child_with_name(widget, self::inventory_window::NAME);
         self.set_inventory_window(widget, desired_state);
@@ -274,11 +352,13 @@ impl RootView {
     pub fn show_menu(&mut self, widget: &Rc<RefCell<Widget>>) {
         let exit_cb = Callback::new(Rc::new(|widget, _| {
             let (_, root_view) = Widget::parent_mut::<RootView>(widget);
+            root_view.save_ironman_on_quit();
             root_view.next_step = Some(NextGameStep::Exit);
         }));
 
         let menu_cb = Callback::new(Rc::new(|widget, _| {
             let (_, root_view) = Widget::parent_mut::<RootView>(widget);
+            root_view.save_ironman_on_quit();
             root_view.next_step = Some(NextGameStep::MainMenu);
         }));
 
@@ -290,6 +370,7 @@ impl RootView {
     pub fn show_exit(&mut self, widget: &Rc<RefCell<Widget>>) {
         let exit_cb = Callback::new(Rc::new(|widget, _| {
             let (_, view) = Widget::parent_mut::<RootView>(widget);
+            view.save_ironman_on_quit();
             view.next_step = Some(NextGameStep::Exit);
         }));
 
@@ -298,6 +379,12 @@ impl RootView {
         Widget::add_child_to(widget, window);
     }
 
+    /// Ends the turn at the player's request, recording it for replays
+    fn player_end_turn(&self) {
+        replay::record(ReplayCommand::EndTurn);
+        self.end_turn();
+    }
+
     pub fn end_turn(&self) {
         self.cancel_targeter();
 
@@ -317,7 +404,19 @@ impl RootView {
         }
     }
 
-    pub fn rest(&self) {
+    pub fn rest(&mut self, widget: &Rc<RefCell<Widget>>) {
+        let area = Rc::clone(&GameState::area_state().borrow().area.area);
+        match area.on_rest {
+            OnRest::Camp { .. } => self.show_camp_confirm(widget),
+            _ => self.rest_confirmed(),
+        }
+    }
+
+    /// Rests in the current area, once the player has confirmed making
+    /// camp where that is needed
+    pub fn rest_confirmed(&mut self) {
+        replay::record(ReplayCommand::Rest);
+
         let area_state = GameState::area_state();
         let area = Rc::clone(&area_state.borrow().area.area);
 
@@ -332,21 +431,168 @@ impl RootView {
             OnRest::FireScript { ref id, ref func } => {
                 Script::trigger(id, func, ScriptEntity::from(&target));
             }
+            OnRest::Camp { .. } => self.camp(),
         }
     }
 
+    fn show_camp_confirm(&self, widget: &Rc<RefCell<Widget>>) {
+        let rules = Module::rules();
+        let rest = match rules.rest {
+            None => return,
+            Some(ref rest) => rest,
+        };
+
+        let message = match GameState::camp_supplies() {
+            None => tr!("rest.confirm", hours = rest.hours),
+            Some(available) => tr!(
+                "rest.confirm_supplies",
+                hours = rest.hours,
+                supplies = rest.supplies_per_rest,
+                available = available
+            ),
+        };
+
+        let dialog = ModalDialog::confirm(
+            &message,
+            Rc::new(|widget| {
+                let (_, view) = Widget::parent_mut::<RootView>(widget);
+                view.rest_confirmed();
+            }),
+        );
+        modal_dialog::show(widget, dialog);
+    }
+
+    fn camp(&mut self) {
+        let text = match GameState::rest() {
+            RestResult::Rested => {
+                let pc = GameState::player();
+                GameState::add_ui_callback(vec![OnTrigger::FadeOutIn], &pc, &pc);
+                tr!("status.rest_complete")
+            }
+            RestResult::Interrupted => tr!("status.rest_interrupted"),
+            RestResult::NotAllowed => tr!("status.cannot_rest"),
+            RestResult::NoSupplies => tr!("status.rest_no_supplies"),
+        };
+        self.add_status_text(&text);
+    }
+
+    /// Writes a quick save.  Its thumbnail is attached once the screenshot
+    /// has been captured on the next frame
     pub fn save(&mut self) {
+        if GameState::is_ironman() {
+            self.add_status_text(&tr!("status.ironman_no_save"));
+            return;
+        }
+
         if GameState::is_combat_active() {
-            self.add_status_text("Cannot save during combat.");
+            self.add_status_text(&tr!("status.cannot_save_combat"));
             return;
         }
 
-        if let Err(e) = create_save() {
-            error!("Error quick saving game");
-            error!("{}", e);
-            self.add_status_text("Error performing Save!");
+        if script_cache::has_suspended_coroutines() {
+            self.add_status_text(&tr!("status.cannot_save_scripts"));
+            return;
+        }
+
+        match create_save() {
+            Err(e) => {
+                error!("Error quick saving game");
+                error!("{}", e);
+                self.add_status_text(&tr!("status.save_error"));
+            }
+            Ok(path) => {
+                self.add_status_text(&tr!("status.save_complete"));
+                self.request_thumbnail(path);
+            }
+        }
+    }
+
+    /// Writes an autosave, which in an ironman game writes the ironman
+    /// save slot instead
+    fn autosave(&mut self) {
+        if !can_autosave() {
+            return;
+        }
+
+        self.autosave_elapsed = 0;
+        let result = if GameState::is_ironman() {
+            create_ironman_save()
         } else {
-            self.add_status_text("Save Complete.");
+            create_autosave()
+        };
+
+        match result {
+            Err(e) => {
+                error!("Error autosaving game");
+                error!("{}", e);
+                self.add_status_text(&tr!("status.autosave_error"));
+            }
+            Ok(path) => {
+                self.add_status_text(&tr!("status.autosave_complete"));
+                self.request_thumbnail(path);
+            }
+        }
+    }
+
+    fn request_thumbnail(&mut self, path: Option<PathBuf>) {
+        if path.is_some() {
+            io::request_screenshot();
+            self.pending_thumbnail = path;
+        }
+    }
+
+    /// Writes the ironman save slot, so that the game can be resumed after
+    /// quitting.  Quitting during combat instead abandons the game, as the
+    /// slot would otherwise allow escaping the fight
+    fn save_ironman_on_quit(&mut self) {
+        if !GameState::is_ironman() || GameState::player().borrow().actor.is_dead() {
+            return;
+        }
+
+        if GameState::is_combat_active() {
+            if let Err(e) = abandon_ironman_save() {
+                error!("Error abandoning ironman game");
+                error!("{}", e);
+            }
+            return;
+        }
+
+        if script_cache::has_suspended_coroutines() {
+            warn!("Suspended scripts will be discarded from the ironman save");
+        }
+
+        if let Err(e) = create_ironman_save() {
+            error!("Error saving ironman game");
+            error!("{}", e);
+            return;
+        }
+        remove_unreferenced_area_saves();
+    }
+
+    fn attach_pending_thumbnail(&mut self) {
+        if io::is_screenshot_pending() {
+            return;
+        }
+
+        let path = match self.pending_thumbnail.take() {
+            None => return,
+            Some(path) => path,
+        };
+
+        // the save may have been deleted in the meantime, such as an
+        // ironman save when the party is defeated
+        if !path.is_file() {
+            return;
+        }
+
+        let screenshot = match io::take_screenshot() {
+            None => return,
+            Some(screenshot) => screenshot,
+        };
+
+        if let Err(e) = attach_thumbnail(&path, &screenshot) {
+            warn!("Unable to add thumbnail to save {:?}", path);
+            warn!("{}", e);
         }
     }
 
@@ -365,10 +611,43 @@ impl WidgetKind for RootView {
     fn update(&mut self, widget: &Rc<RefCell<Widget>>, millis: u32) {
         let area_state = GameState::area_state();
         let root = Widget::get_root(widget);
-        let area = area_state.borrow().area.area.id.clone();
-        if area != self.area {
-            self.area = area;
+        // an area regenerated in place keeps its ID, so compare the states
+        let same_state = match self.area_state.upgrade() {
+            None => false,
+            Some(prev) => Rc::ptr_eq(&prev, &area_state),
+        };
+
+        if !same_state {
+            self.area_state = Rc::downgrade(&area_state);
             root.borrow_mut().invalidate_children();
+
+            let area = area_state.borrow().area.area.id.clone();
+            if area != self.area {
+                let transition = !self.area.is_empty();
+                self.area = area;
+
+                let on_transition = Config::autosave() == AutosaveFrequency::AreaTransition;
+                if transition && (on_transition || GameState::is_ironman()) {
+                    self.autosave();
+                }
+            }
+        }
+
+        // ironman games are also saved at the end of each combat
+        let combat_active = GameState::is_combat_active();
+        if self.was_combat_active && !combat_active && GameState::is_ironman() {
+            self.autosave();
+        }
+        self.was_combat_active = combat_active;
+
+        self.attach_pending_thumbnail();
+
+        for aborted in take_aborted_scripts() {
+            self.add_status_text(&tr!(
+                "status.script_aborted",
+                script = aborted.script,
+                reason = aborted.reason,
+            ));
         }
 
         if let Some(instant) = self.status_added {
@@ -383,6 +662,13 @@ impl WidgetKind for RootView {
         let has_modal = root.borrow().has_modal();
         GameState::set_modal_locked(has_modal);
 
+        if let Some(interval) = Config::autosave().interval_millis() {
+            self.autosave_elapsed = self.autosave_elapsed.saturating_add(millis);
+            if self.autosave_elapsed >= interval && !has_modal {
+                self.autosave();
+            }
+        }
+
         let (cx, cy) = (Cursor::get_x(), Cursor::get_y());
         let mut area_view_updated = false;
         if !has_modal {
@@ -464,13 +750,15 @@ impl WidgetKind for RootView {
                 }
             }
             ToggleConsole => self.toggle_console_window(widget),
+            ToggleScriptErrors => self.toggle_script_error_window(widget),
             ToggleInventory => self.toggle_inventory_window(widget),
             ToggleCharacter => self.toggle_character_window(widget),
             ToggleMap => self.toggle_map_window(widget),
             ToggleJournal => self.toggle_quest_window(widget),
             ToggleFormation => self.toggle_formation_window(widget),
-            EndTurn => self.end_turn(),
-            Rest => self.rest(),
+            InputActionKind::RadialMenu => self.toggle_radial_menu(widget),
+            EndTurn => self.player_end_turn(),
+            Rest => self.rest(widget),
             Exit => self.show_exit(widget),
             SelectAll => GameState::select_party_members(GameState::party()),
             QuickSave => self.save(),
@@ -543,7 +831,7 @@ impl WidgetKind for RootView {
                 "rest_button",
                 Rc::new(|widget, _| {
                     let (_, view) = Widget::parent_mut::<RootView>(widget);
-                    view.rest();
+                    view.rest(widget);
                 }),
             );
 
@@ -555,7 +843,7 @@ impl WidgetKind for RootView {
                 "end_turn_button",
                 Rc::new(|widget, _| {
                     let (_, view) = Widget::parent_mut::<RootView>(widget);
-                    view.end_turn();
+                    view.player_end_turn();
                 }),
             );
             end_turn_button
@@ -720,6 +1008,11 @@ impl WidgetKind for RootView {
                 // this prevents this callback from being called over and over
                 party[0].borrow_mut().actor.set_disabled(true);
 
+                if let Err(e) = delete_ironman_save() {
+                    error!("Error deleting ironman save");
+                    error!("{}", e);
+                }
+
                 let menu_cb = Callback::new(Rc::new(|widget, _| {
                     let (_, view) = Widget::parent_mut::<RootView>(widget);
                     view.next_step = Some(NextGameStep::MainMenu);
@@ -733,12 +1026,18 @@ impl WidgetKind for RootView {
 
         let ticker = Widget::with_defaults(InitiativeTicker::new());
 
+        let minimap = Widget::with_defaults(Minimap::new(
+            Rc::clone(&self.area_view),
+            Rc::clone(&self.area_view_widget),
+        ));
+
         // area widget must be the first entry in the children list
         vec![
             Rc::clone(&self.area_view_widget),
             bot_pane,
             ap_bar,
             ticker,
+            minimap,
             self.status.clone(),
             Rc::clone(&self.console_widget),
         ]
@@ -765,6 +1064,13 @@ fn create_button(
     button
 }
 
+/// Automatic saves are skipped during combat and once the player has died
+fn can_autosave() -> bool {
+    !GameState::is_combat_active()
+        && !script_cache::has_suspended_coroutines()
+        && !GameState::player().borrow().actor.is_dead()
+}
+
 fn is_defeated(party: &[Rc<RefCell<EntityState>>]) -> bool {
     if party.is_empty() {
         return true;
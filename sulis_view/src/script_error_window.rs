@@ -0,0 +1,142 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2026 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+use sulis_core::config;
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::util::copy_to_clipboard;
+use sulis_core::widgets::{markup_renderer, Button, Label, ScrollDirection, ScrollPane, TextArea};
+use sulis_state::script::script_errors::{self, ScriptError};
+
+use crate::RootView;
+
+pub const NAME: &str = "script_error_window";
+
+/// Developer overlay listing the most recent script errors, with the Lua
+/// stack trace and the entity or ability that triggered each one
+pub struct ScriptErrorWindow {
+    total: usize,
+}
+
+impl ScriptErrorWindow {
+    pub fn new() -> Rc<RefCell<ScriptErrorWindow>> {
+        Rc::new(RefCell::new(ScriptErrorWindow { total: 0 }))
+    }
+}
+
+impl WidgetKind for ScriptErrorWindow {
+    widget_kind!(NAME);
+
+    fn update(&mut self, widget: &Rc<RefCell<Widget>>, _millis: u32) {
+        if script_errors::total() != self.total {
+            widget.borrow_mut().invalidate_children();
+        }
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        self.total = script_errors::total();
+        let errors = script_errors::errors();
+
+        let title = Widget::with_theme(Label::empty(), "title");
+        title
+            .borrow_mut()
+            .state
+            .add_text_arg("count", &errors.len().to_string());
+
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<ScriptErrorWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        let clear = Widget::with_theme(Button::empty(), "clear");
+        clear
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                script_errors::clear();
+                let (parent, _) = Widget::parent::<ScriptErrorWindow>(widget);
+                parent.borrow_mut().invalidate_children();
+            })));
+        clear.borrow_mut().state.set_enabled(!errors.is_empty());
+
+        let scrollpane = ScrollPane::new(ScrollDirection::Vertical);
+        let list = Widget::with_theme(scrollpane.clone(), "errors");
+        if errors.is_empty() {
+            let none = Widget::with_theme(Label::empty(), "none");
+            scrollpane.borrow().add_to_content(none);
+        }
+
+        for error in errors {
+            let text = Widget::with_theme(TextArea::empty(), "error");
+            add_error_args(&mut text.borrow_mut(), &error);
+            scrollpane.borrow().add_to_content(text);
+
+            let copy = Widget::with_theme(Button::empty(), "copy");
+            let details = error.details();
+            copy.borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let status = copy_details(&details);
+                    let (_, view) = Widget::parent_mut::<RootView>(widget);
+                    view.add_status_text(&status);
+                })));
+            scrollpane.borrow().add_to_content(copy);
+        }
+
+        vec![title, list, clear, close]
+    }
+}
+
+fn add_error_args(widget: &mut Widget, error: &ScriptError) {
+    let state = &mut widget.state;
+    state.add_text_arg("script", &markup_renderer::escape(&error.script));
+    state.add_text_arg("func", &markup_renderer::escape(&error.func));
+    state.add_text_arg("message", &markup_renderer::escape(&error.message));
+
+    if let Some(context) = &error.context {
+        state.add_text_arg("context", &markup_renderer::escape(context));
+    }
+    if !error.traceback.is_empty() {
+        let traceback = error.traceback.trim_end();
+        state.add_text_arg("traceback", &markup_renderer::escape(traceback));
+    }
+}
+
+/// Copies the error details to the clipboard, falling back to a file in the
+/// user directory if no clipboard is available.  Returns the status text
+fn copy_details(details: &str) -> String {
+    if copy_to_clipboard(details).is_ok() {
+        return tr!("status.script_error_copied");
+    }
+
+    let mut path = config::USER_DIR.clone();
+    path.push("script_error.txt");
+    match fs::write(&path, details) {
+        Ok(()) => tr!("status.script_error_written", path = path.to_string_lossy()),
+        Err(e) => {
+            warn!("Unable to write script error to {path:?}: {e}");
+            tr!("status.script_error_copy_failed")
+        }
+    }
+}
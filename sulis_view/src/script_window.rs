@@ -0,0 +1,104 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, Label, TextArea};
+use sulis_module::on_trigger::WindowElement;
+use sulis_state::script::{CallbackData, ScriptCallback, ScriptMenuSelection};
+
+pub const NAME: &str = "script_window";
+
+/// A custom window defined by a script with `ui:create_window`.  See
+/// `sulis_state::script::ScriptWindow`
+pub struct ScriptWindow {
+    callback: CallbackData,
+    title: String,
+    rows: Vec<Vec<WindowElement>>,
+}
+
+impl ScriptWindow {
+    pub fn new(
+        callback: CallbackData,
+        title: String,
+        rows: Vec<Vec<WindowElement>>,
+    ) -> Rc<RefCell<ScriptWindow>> {
+        Rc::new(RefCell::new(ScriptWindow {
+            callback,
+            title,
+            rows,
+        }))
+    }
+}
+
+impl WidgetKind for ScriptWindow {
+    widget_kind!(NAME);
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let title = Widget::with_theme(Label::empty(), "title");
+        title.borrow_mut().state.add_text_arg("title", &self.title);
+
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<ScriptWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        let rows = Widget::empty("rows");
+        for elements in self.rows.iter() {
+            let row = Widget::empty("row");
+            for element in elements {
+                let widget = match element {
+                    WindowElement::Label(text) => {
+                        let label = Widget::with_theme(TextArea::empty(), "label");
+                        label.borrow_mut().state.add_text_arg("text", text);
+                        label
+                    }
+                    WindowElement::Button(choice) => {
+                        let button = Widget::with_theme(Button::empty(), "button");
+                        button
+                            .borrow_mut()
+                            .state
+                            .add_text_arg("text", &choice.display);
+
+                        let value = choice.value.to_string();
+                        let cb = self.callback.clone();
+                        button
+                            .borrow_mut()
+                            .state
+                            .add_callback(Callback::new(Rc::new(move |_, _| {
+                                let selection = ScriptMenuSelection {
+                                    value: value.to_string(),
+                                };
+                                cb.on_menu_select(selection);
+                            })));
+                        button
+                    }
+                };
+                Widget::add_child_to(&row, widget);
+            }
+            Widget::add_child_to(&rows, row);
+        }
+
+        vec![title, rows, close]
+    }
+}
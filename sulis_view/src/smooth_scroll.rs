@@ -0,0 +1,68 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2020 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use sulis_core::config::Config;
+
+const MIN_SCROLL_MILLIS: u32 = 200;
+const MAX_SCROLL_MILLIS: u32 = 2000;
+
+/// A scroll of the area view from one point to another over a fixed time,
+/// easing in at the start of the scroll and out at the end
+pub struct SmoothScroll {
+    start: (f32, f32),
+    end: (f32, f32),
+    elapsed_millis: u32,
+    total_millis: u32,
+}
+
+impl SmoothScroll {
+    pub fn new(start: (f32, f32), end: (f32, f32), total_millis: u32) -> SmoothScroll {
+        SmoothScroll {
+            start,
+            end,
+            elapsed_millis: 0,
+            total_millis: total_millis.max(1),
+        }
+    }
+
+    /// Creates a scroll with a duration based on the distance to be covered,
+    /// in scaled screen units, and the configured scroll speed
+    pub fn with_speed(start: (f32, f32), end: (f32, f32), scaled_dist: f32) -> SmoothScroll {
+        // the base scroll speed is in pixels per 33 milliseconds
+        let speed = Config::scroll_speed() * 4.0 / 33.3;
+        let millis = (scaled_dist / speed) as u32;
+        let millis = millis.clamp(MIN_SCROLL_MILLIS, MAX_SCROLL_MILLIS);
+        SmoothScroll::new(start, end, millis)
+    }
+
+    /// Advances the scroll by the specified time, returning the new scroll
+    /// position and whether the scroll is complete
+    pub fn update(&mut self, millis: u32) -> ((f32, f32), bool) {
+        self.elapsed_millis += millis;
+        if self.elapsed_millis >= self.total_millis {
+            return (self.end, true);
+        }
+
+        let frac = ease(self.elapsed_millis as f32 / self.total_millis as f32);
+        let x = self.start.0 + (self.end.0 - self.start.0) * frac;
+        let y = self.start.1 + (self.end.1 - self.start.1) * frac;
+        ((x, y), false)
+    }
+}
+
+fn ease(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
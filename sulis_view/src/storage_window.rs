@@ -0,0 +1,87 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::{item_list_pane::Filter, ItemListPane};
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, Label};
+use sulis_module::Module;
+use sulis_state::{ChangeListener, EntityState, GameState};
+
+pub const NAME: &str = "storage_window";
+
+/// Displays the contents of the party storage, allowing items to be moved
+/// back into the party stash.  Available at camps and merchants
+pub struct StorageWindow {
+    player: Rc<RefCell<EntityState>>,
+    filter: Rc<Cell<Filter>>,
+}
+
+impl StorageWindow {
+    pub fn new(player: Rc<RefCell<EntityState>>) -> Rc<RefCell<StorageWindow>> {
+        Rc::new(RefCell::new(StorageWindow {
+            player,
+            filter: Rc::new(Cell::new(Filter::All)),
+        }))
+    }
+}
+
+impl WidgetKind for StorageWindow {
+    widget_kind!(NAME);
+
+    fn layout(&mut self, widget: &mut Widget) {
+        widget.do_base_layout();
+    }
+
+    fn on_remove(&mut self, _widget: &Rc<RefCell<Widget>>) {
+        let stash = GameState::party_stash();
+        stash.borrow_mut().listeners.remove(NAME);
+    }
+
+    fn on_add(&mut self, widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let stash = GameState::party_stash();
+        stash
+            .borrow_mut()
+            .listeners
+            .add(ChangeListener::invalidate(NAME, widget));
+
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<StorageWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        let capacity = Widget::with_theme(Label::empty(), "capacity");
+        {
+            let state = &mut capacity.borrow_mut().state;
+            state.add_text_arg("count", &stash.borrow().storage().len().to_string());
+            if let Some(capacity) = Module::rules().party_storage_capacity {
+                state.add_text_arg("capacity", &capacity.to_string());
+            }
+        }
+
+        let item_list_pane =
+            Widget::with_defaults(ItemListPane::new_storage(&self.player, &self.filter));
+
+        vec![close, capacity, item_list_pane]
+    }
+}
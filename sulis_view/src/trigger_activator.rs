@@ -24,13 +24,16 @@ use sulis_module::{
 };
 use sulis_state::{
     area_feedback_text::ColorKind,
-    script::{entity_with_id, CallbackData, FuncKind, ScriptEntity},
+    script::{
+        entity_with_id, CallbackData, FuncKind, ScriptCallback, ScriptEntity, ScriptMenuSelection,
+    },
     AreaFeedbackText, EntityState, GameState, NextGameStep, Script,
 };
 
 use crate::{
-    ap_bar, character_window, dialog_window, window_fade, ConfirmationWindow, CutsceneWindow,
-    GameOverWindow, LoadingScreen, RootView, ScriptMenu, UIBlocker, WindowFade,
+    ap_bar, character_window, dialog_window, modal_dialog, window_fade, CutsceneSequence,
+    script_window, CutsceneWindow, GameOverWindow, LoadingScreen, ModalDialog, RootView,
+    ScriptMenu, ScriptWindow, UIBlocker, WindowFade,
 };
 
 pub fn is_match(
@@ -187,6 +190,9 @@ pub fn activate(
             PlayerCoins(amount) => {
                 GameState::add_party_coins(*amount);
             }
+            AwardXp(amount) => {
+                GameState::award_xp(*amount, target);
+            }
             PartyMember(ref id) => match entity_with_id(id.to_string()) {
                 None => warn!(
                     "Attempted to add party member '{}' but entity does not exist",
@@ -237,14 +243,18 @@ pub fn activate(
                 area.borrow_mut().add_feedback_text(feedback);
             }
             ShowCutscene(ref cutscene) => show_cutscene(widget, cutscene),
+            PlayCutscene(ref data) => play_cutscene(widget, data),
             FireScript(ref script) => fire_script(&script.id, &script.func, pc, target),
             GameOverWindow(ref text) => game_over_window(widget, text.to_string()),
             ExitToMenu => exit_to_menu(widget),
-            ScrollView(x, y) => scroll_view(widget, *x, *y),
-            ScreenShake => screen_shake(widget),
+            ScrollView(x, y) => scroll_view(*x, *y),
+            ScreenShake => screen_shake(),
             LoadModule(ref module_data) => load_module(widget, module_data),
             ShowConfirm(ref data) => show_confirm(widget, data),
             ShowMenu(ref data) => show_menu(widget, data),
+            ShowChoice(ref data) => show_choice(widget, data),
+            ShowWindow(ref data) => show_window(widget, data),
+            CloseWindow => close_window(widget),
             FadeOutIn => fade_out_in(widget),
             QuestState(ref data) => {
                 verify_quest(data);
@@ -282,56 +292,80 @@ fn verify_quest(data: &QuestStateData) {
     }
 }
 
+fn menu_callback(kind: &Kind, parent: usize, func: &str) -> CallbackData {
+    let mut script_cb = match kind {
+        Kind::Ability(ref id) => CallbackData::new_ability(parent, id),
+        Kind::Item(id) => CallbackData::new_item(parent, id.to_string()),
+        Kind::Entity => CallbackData::new_entity(parent),
+        Kind::Script(id) => CallbackData::new_trigger(parent, id.to_string()),
+    };
+    script_cb.add_func(FuncKind::OnMenuSelect, func.to_string());
+    script_cb
+}
+
 fn show_menu(widget: &Rc<RefCell<Widget>>, data: &on_trigger::MenuData) {
     let root = Widget::get_root(widget);
-
-    let mut script_cb = match &data.cb_kind {
-        Kind::Ability(ref id) => CallbackData::new_ability(data.cb_parent, id),
-        Kind::Item(id) => CallbackData::new_item(data.cb_parent, id.to_string()),
-        Kind::Entity => CallbackData::new_entity(data.cb_parent),
-        Kind::Script(id) => CallbackData::new_trigger(data.cb_parent, id.to_string()),
-    };
-    script_cb.add_func(FuncKind::OnMenuSelect, data.cb_func.to_string());
+    let script_cb = menu_callback(&data.cb_kind, data.cb_parent, &data.cb_func);
 
     let window = ScriptMenu::new(script_cb, data.title.to_string(), data.choices.clone());
     let widget = Widget::with_defaults(window);
     Widget::add_child_to(&root, widget);
 }
 
-fn show_confirm(widget: &Rc<RefCell<Widget>>, data: &on_trigger::DialogData) {
+fn show_window(widget: &Rc<RefCell<Widget>>, data: &on_trigger::WindowData) {
+    close_window(widget);
+
     let root = Widget::get_root(widget);
+    let script_cb = menu_callback(&data.cb_kind, data.cb_parent, &data.cb_func);
 
-    let cb = if let Some(ref on_accept) = data.on_accept {
-        let id = on_accept.id.to_string();
-        let func = on_accept.func.to_string();
-        Callback::new(Rc::new(move |widget, _| {
-            let target = GameState::player();
-            fire_script(&id, &func, &target, &target);
-
-            let (parent, _) = Widget::parent::<ConfirmationWindow>(widget);
-            parent.borrow_mut().mark_for_removal();
-        }))
-    } else {
-        Callback::empty()
-    };
-    let window = ConfirmationWindow::new(cb);
-    {
-        let title = Rc::clone(window.borrow().title());
-        title
-            .borrow_mut()
-            .state
-            .add_text_arg("message", &data.message);
-        window
-            .borrow()
-            .add_accept_text_arg("text", &data.accept_text);
-        window
-            .borrow()
-            .add_cancel_text_arg("text", &data.cancel_text);
+    let window = ScriptWindow::new(script_cb, data.title.to_string(), data.rows.clone());
+    let window = Widget::with_defaults(window);
+    window.borrow_mut().state.set_modal(true);
+    Widget::add_child_to(&root, window);
+}
+
+fn close_window(widget: &Rc<RefCell<Widget>>) {
+    let root = Widget::get_root(widget);
+    if let Some(window) = Widget::get_child_with_name(&root, script_window::NAME) {
+        window.borrow_mut().mark_for_removal();
     }
+}
 
-    let widget = Widget::with_theme(window, "script_confirmation");
-    widget.borrow_mut().state.set_modal(true);
-    Widget::add_child_to(&root, widget);
+fn show_choice(widget: &Rc<RefCell<Widget>>, data: &on_trigger::MenuData) {
+    let script_cb = menu_callback(&data.cb_kind, data.cb_parent, &data.cb_func);
+    let values: Vec<String> = data.choices.iter().map(|c| c.value.to_string()).collect();
+    let choices = data.choices.iter().map(|c| c.display.to_string()).collect();
+
+    let dialog = ModalDialog::choice(
+        &data.title,
+        choices,
+        Rc::new(move |_, index| {
+            let selection = ScriptMenuSelection {
+                value: values[index].to_string(),
+            };
+            script_cb.on_menu_select(selection);
+        }),
+    );
+    modal_dialog::show(widget, dialog);
+}
+
+fn show_confirm(widget: &Rc<RefCell<Widget>>, data: &on_trigger::DialogData) {
+    let on_accept = data.on_accept.clone();
+    let dialog = ModalDialog::confirm(
+        &data.message,
+        Rc::new(move |_| {
+            if let Some(ref on_accept) = on_accept {
+                let target = GameState::player();
+                fire_script(&on_accept.id, &on_accept.func, &target, &target);
+            }
+        }),
+    );
+    {
+        let mut dialog = dialog.borrow_mut();
+        dialog.set_accept_text(&data.accept_text);
+        dialog.set_cancel_text(&data.cancel_text);
+    }
+    modal_dialog::show(widget, dialog);
 }
 
 fn load_module(widget: &Rc<RefCell<Widget>>, module_data: &ModuleLoadData) {
@@ -378,6 +412,7 @@ fn load_module(widget: &Rc<RefCell<Widget>>, module_data: &ModuleLoadData) {
             party_actors,
             flags: module_data.flags.clone(),
             module_dir: module.dir,
+            ironman: GameState::is_ironman(),
         };
         view.set_next_step(step);
 
@@ -405,38 +440,14 @@ fn fade_out_in(widget: &Rc<RefCell<Widget>>) {
     Widget::add_child_to(&area_view_widget, fade);
 }
 
-pub fn scroll_view(widget: &Rc<RefCell<Widget>>, x: i32, y: i32) {
-    let root = Widget::get_root(widget);
-
-    let (area_view, area_view_widget) = {
-        let view = Widget::kind_mut::<RootView>(&root);
-        view.area_view()
-    };
-
-    let (width, height) = {
-        let area = GameState::area_state();
-        let area = area.borrow();
-        (area.area.width, area.area.height)
-    };
-
-    area_view.borrow_mut().delayed_scroll_to_point(
-        x as f32,
-        y as f32,
-        width,
-        height,
-        &area_view_widget.borrow(),
-    );
+pub fn scroll_view(x: i32, y: i32) {
+    let area = GameState::area_state();
+    area.borrow_mut().camera().scroll_to(x as f32, y as f32, None);
 }
 
-pub fn screen_shake(widget: &Rc<RefCell<Widget>>) {
-    let root = Widget::get_root(widget);
-
-    let (area_view, _) = {
-        let view = Widget::kind_mut::<RootView>(&root);
-        view.area_view()
-    };
-
-    area_view.borrow_mut().screen_shake();
+pub fn screen_shake() {
+    let area = GameState::area_state();
+    area.borrow_mut().camera().shake();
 }
 
 fn exit_to_menu(widget: &Rc<RefCell<Widget>>) {
@@ -484,17 +495,32 @@ fn show_merchant(widget: &Rc<RefCell<Widget>>, merch: &MerchantData) {
         Some(loot) => loot,
     };
 
+    if let Some(hours) = merch.hours {
+        let hour = GameState::turn_manager().borrow().current_time().hour;
+        if !hours.is_open(hour) {
+            info!("Merchant '{}' is closed at hour {}", id, hour);
+            let player = GameState::player();
+            let area = GameState::area_state();
+
+            let mut feedback = AreaFeedbackText::with_target(&player.borrow(), &area.borrow());
+            feedback.add_entry(tr!("feedback.closed"), ColorKind::Info);
+            area.borrow_mut().add_feedback_text(feedback);
+            return;
+        }
+    }
+
     {
         let area_state = GameState::area_state();
         let mut area_state = area_state.borrow_mut();
 
-        area_state.get_or_create_merchant(
+        let merchant = area_state.get_or_create_merchant(
             id,
             &loot,
             merch.buy_frac,
             merch.sell_frac,
             merch.refresh_time,
         );
+        merchant.faction = merch.faction.clone();
     }
 
     let (root, view) = Widget::parent_mut::<RootView>(widget);
@@ -522,6 +548,15 @@ fn show_cutscene(widget: &Rc<RefCell<Widget>>, cutscene_id: &str) {
     Widget::add_child_to(&root, window);
 }
 
+fn play_cutscene(widget: &Rc<RefCell<Widget>>, data: &on_trigger::CutsceneData) {
+    info!("Playing scripted cutscene with {} steps.", data.steps.len());
+
+    let root = Widget::get_root(widget);
+    let sequence = Widget::with_defaults(CutsceneSequence::new(data.steps.clone()));
+    sequence.borrow_mut().state.set_modal(true);
+    Widget::add_child_to(&root, sequence);
+}
+
 fn start_convo(
     widget: &Rc<RefCell<Widget>>,
     convo_id: &str,
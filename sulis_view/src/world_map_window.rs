@@ -19,10 +19,9 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::ui::{animation_state, Callback, Widget, WidgetKind};
-use sulis_core::util::Point;
 use sulis_core::widgets::{Button, TextArea};
 use sulis_module::{campaign::WorldMapLocation, Module, Time};
-use sulis_state::GameState;
+use sulis_state::{replay, GameState, ReplayCommand};
 
 pub const NAME: &str = "world_map_window";
 
@@ -107,6 +106,8 @@ impl WidgetKind for WorldMapWindow {
         let area_state = GameState::area_state();
         let cur_location_id = area_state.borrow().area.area.world_map_location.clone();
 
+        let destinations = GameState::quest_state().destinations();
+
         for location in map.locations.iter() {
             let button = Widget::with_theme(Button::empty(), "location");
 
@@ -141,6 +142,10 @@ impl WidgetKind for WorldMapWindow {
                     .add_text_arg("name", &location.name);
                 label.borrow_mut().state.set_visible(is_visible);
 
+                if let Some(quest) = destinations.get(&location.id) {
+                    label.borrow_mut().state.add_text_arg("quest", quest);
+                }
+
                 (
                     self.transition_enabled && is_enabled && is_visible && !is_active,
                     label,
@@ -194,28 +199,40 @@ fn add_travel_callback(
         .state
         .add_text_arg("travel_time", &travel_time.to_string());
 
-    let (x, y) = (location.linked_area_pos.x, location.linked_area_pos.y);
-    let area_id = match &location.linked_area {
-        None => return false,
-        Some(id) => id.to_string(),
-    };
+    if location.linked_area.is_none() {
+        return false;
+    }
 
     button
         .borrow_mut()
         .state
-        .add_callback(travel_callback(area_id, x, y, travel_time));
+        .add_callback(travel_callback(location.id.to_string(), hours));
     true
 }
 
-fn travel_callback(area_id: String, x: i32, y: i32, travel_time: Time) -> Callback {
+fn travel_callback(location_id: String, hours: u32) -> Callback {
     Callback::new(Rc::new(move |widget, _| {
-        GameState::transition_to(
-            Some(&area_id),
-            Some(Point::new(x, y)),
-            Point::default(),
-            travel_time,
-        );
-        let root = Widget::get_root(widget);
-        root.borrow_mut().invalidate_children();
+        travel(widget, &location_id, hours)
     }))
 }
+
+/// Travels to the world map location with `location_id`, taking `hours`
+pub(crate) fn travel(widget: &Rc<RefCell<Widget>>, location_id: &str, hours: u32) {
+    replay::record(ReplayCommand::Travel {
+        location: location_id.to_string(),
+        hours,
+    });
+
+    let campaign = Module::campaign();
+    let location = campaign
+        .world_map
+        .locations
+        .iter()
+        .find(|location| location.id == location_id);
+    match location {
+        None => warn!("Unable to travel to invalid location '{}'", location_id),
+        Some(location) => GameState::travel_to(location, hours),
+    }
+    let root = Widget::get_root(widget);
+    root.borrow_mut().invalidate_children();
+}